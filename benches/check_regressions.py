@@ -0,0 +1,80 @@
+#!/usr/bin/env python3
+# (C) Copyright IBM Corp. 2025.
+#
+# Licensed under the Apache License, Version 2.0 (the "License");
+# you may not use this file except in compliance with the License.
+# You may obtain a copy of the License at
+#
+#      http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing, software
+# distributed under the License is distributed on an "AS IS" BASIS,
+# WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+# See the License for the specific language governing permissions and
+# limitations under the License.
+
+"""Compares the last `cargo bench` run against benches/baseline.json.
+
+Usage:
+    cargo bench --bench evaluation
+    python3 benches/check_regressions.py [--threshold 0.20]   # exit 1 on regression
+    python3 benches/check_regressions.py --update              # rewrite the baseline
+"""
+
+import argparse
+import json
+import pathlib
+import sys
+
+ROOT = pathlib.Path(__file__).resolve().parent.parent
+CRITERION_DIR = ROOT / "target" / "criterion"
+BASELINE = ROOT / "benches" / "baseline.json"
+
+
+def latest_results():
+    """Mean time in nanoseconds of every benchmark of the last run, by id."""
+    results = {}
+    for benchmark in CRITERION_DIR.glob("**/new/benchmark.json"):
+        full_id = json.loads(benchmark.read_text())["full_id"]
+        estimates = json.loads((benchmark.parent / "estimates.json").read_text())
+        results[full_id] = round(estimates["mean"]["point_estimate"], 1)
+    return dict(sorted(results.items()))
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__.splitlines()[0])
+    parser.add_argument("--threshold", type=float, default=0.20,
+                        help="allowed slowdown relative to the baseline (default: 0.20)")
+    parser.add_argument("--update", action="store_true",
+                        help="store the last run as the new baseline")
+    args = parser.parse_args()
+
+    results = latest_results()
+    if not results:
+        sys.exit(f"No benchmark results in {CRITERION_DIR}, run `cargo bench` first.")
+
+    if args.update:
+        BASELINE.write_text(json.dumps({"mean_ns": results}, indent=2) + "\n")
+        print(f"Baseline updated with {len(results)} benchmarks.")
+        return
+
+    baseline = json.loads(BASELINE.read_text())["mean_ns"]
+    regressions = []
+    for full_id, mean in results.items():
+        expected = baseline.get(full_id)
+        if expected is None:
+            print(f"{full_id:50} {mean:>14.1f} ns  (not in baseline)")
+            continue
+        change = mean / expected - 1.0
+        print(f"{full_id:50} {mean:>14.1f} ns  {change:+7.1%}")
+        if change > args.threshold:
+            regressions.append(full_id)
+
+    if regressions:
+        print(f"\n{len(regressions)} benchmark(s) slower than the baseline by more than "
+              f"{args.threshold:.0%}: {', '.join(regressions)}")
+        sys.exit(1)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,214 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarks for the evaluation hot path.
+//!
+//! Run with `cargo bench --bench evaluation` and compare against the committed
+//! baseline with `python3 benches/check_regressions.py`.
+
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::sync::{Arc, Mutex};
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use ibm_appconfiguration_rust_sdk::{
+    AppConfigurationOffline, ConfigurationProvider, Entity, Feature, Value,
+};
+use serde_json::json;
+
+const ENVIRONMENT_ID: &str = "dev";
+const COLLECTION_ID: &str = "bench";
+
+struct BenchEntity {
+    attributes: HashMap<String, Value>,
+}
+
+impl BenchEntity {
+    fn with_attribute(value: &str) -> Self {
+        Self {
+            attributes: HashMap::from([("attr".to_string(), Value::from(value.to_string()))]),
+        }
+    }
+}
+
+impl Entity for BenchEntity {
+    fn get_id(&self) -> String {
+        "bench-entity".into()
+    }
+
+    fn get_attributes(&self) -> HashMap<String, Value> {
+        self.attributes.clone()
+    }
+}
+
+/// Builds a configuration with `features` boolean features. Every feature
+/// targets all `segments` segments (one segment rule each, in order) and every
+/// segment has `rules` rules on the `attr` attribute.
+///
+/// Segment `i` matches entities whose `attr` is `value-{i}`, so an entity with
+/// `value-{segments - 1}` walks every targeting rule before matching.
+fn configuration_json(features: usize, segments: usize, rules: usize) -> String {
+    let segments_json: Vec<_> = (0..segments)
+        .map(|s| {
+            let segment_rules: Vec<_> = (0..rules)
+                .map(|_| {
+                    json!({
+                        "attribute_name": "attr",
+                        "operator": "is",
+                        "values": [format!("value-{s}")]
+                    })
+                })
+                .collect();
+            json!({
+                "name": format!("Segment {s}"),
+                "segment_id": format!("segment-{s}"),
+                "description": "",
+                "tags": "",
+                "rules": segment_rules
+            })
+        })
+        .collect();
+    let targeting: Vec<_> = (0..segments)
+        .map(|s| {
+            json!({
+                "rules": [{"segments": [format!("segment-{s}")]}],
+                "value": true,
+                "order": s + 1,
+                "rollout_percentage": 100
+            })
+        })
+        .collect();
+    let features_json: Vec<_> = (0..features)
+        .map(|f| {
+            json!({
+                "name": format!("Feature {f}"),
+                "feature_id": format!("feature-{f}"),
+                "description": "",
+                "tags": "",
+                "type": "BOOLEAN",
+                "enabled_value": false,
+                "disabled_value": false,
+                "segment_rules": targeting,
+                "collections": [{"collection_id": COLLECTION_ID, "name": "Bench"}],
+                "enabled": true,
+                "rollout_percentage": 100,
+                "isOverridden": true
+            })
+        })
+        .collect();
+    json!({
+        "environments": [{
+            "name": "Dev",
+            "environment_id": ENVIRONMENT_ID,
+            "description": "",
+            "tags": "",
+            "color_code": "",
+            "features": features_json,
+            "properties": []
+        }],
+        "collections": [{"name": "Bench", "collection_id": COLLECTION_ID, "description": "", "tags": ""}],
+        "segments": segments_json
+    })
+    .to_string()
+}
+
+fn client(features: usize, segments: usize, rules: usize) -> AppConfigurationOffline {
+    AppConfigurationOffline::from_json_str(
+        &configuration_json(features, segments, rules),
+        ENVIRONMENT_ID,
+        COLLECTION_ID,
+    )
+    .expect("valid benchmark configuration")
+}
+
+fn bench_get_feature(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_feature");
+    for features in [10, 100, 1000] {
+        let client = client(features, 5, 1);
+        let feature_id = format!("feature-{}", features - 1);
+        group.bench_with_input(BenchmarkId::from_parameter(features), &features, |b, _| {
+            b.iter(|| client.get_feature(black_box(&feature_id)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_get_value(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_value");
+    for (segments, rules) in [(1, 1), (10, 1), (10, 10), (50, 5)] {
+        let client = client(10, segments, rules);
+        let entity = BenchEntity::with_attribute(&format!("value-{}", segments - 1));
+        let id = format!("segments={segments}/rules={rules}");
+        group.bench_function(BenchmarkId::from_parameter(id), |b| {
+            b.iter(|| {
+                let feature = client.get_feature(black_box("feature-0")).unwrap();
+                feature.get_current_value(&entity).unwrap()
+            })
+        });
+    }
+    // No segment matches: the fallback value is returned after every rule was checked.
+    let client = client(10, 10, 10);
+    let entity = BenchEntity::with_attribute("unmatched");
+    group.bench_function("segments=10/rules=10/unmatched", |b| {
+        b.iter(|| {
+            let feature = client.get_feature(black_box("feature-0")).unwrap();
+            feature.get_current_value(&entity).unwrap()
+        })
+    });
+    group.finish();
+}
+
+/// Boolean feature without targeting rules, through the generic evaluation and
+/// through the `get_bool_value` fast path.
+fn bench_get_bool_value(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_bool_value");
+    let client = client(10, 0, 0);
+    let feature = client.get_feature("feature-0").unwrap();
+    let entity = BenchEntity::with_attribute("value-0");
+    group.bench_function("get_value_into", |b| {
+        b.iter(|| feature.get_value_into::<bool>(black_box(&entity)).unwrap())
+    });
+    group.bench_function("fast_path", |b| {
+        b.iter(|| feature.get_bool_value(black_box(&entity)).unwrap())
+    });
+    group.finish();
+}
+
+/// Parses a configuration and replaces the current one, as the update thread
+/// does when a new configuration is received.
+fn bench_configuration_swap(c: &mut Criterion) {
+    let mut group = c.benchmark_group("configuration_swap");
+    for features in [10, 100, 1000] {
+        let json = configuration_json(features, 10, 2);
+        let current = Arc::new(Mutex::new(Some(client(1, 1, 1))));
+        group.bench_with_input(BenchmarkId::from_parameter(features), &json, |b, json| {
+            b.iter(|| {
+                let new =
+                    AppConfigurationOffline::from_json_str(json, ENVIRONMENT_ID, COLLECTION_ID)
+                        .unwrap();
+                *current.lock().unwrap() = Some(new);
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_get_feature,
+    bench_get_value,
+    bench_get_bool_value,
+    bench_configuration_swap
+);
+criterion_main!(benches);
@@ -0,0 +1,54 @@
+// Generated by ibm-appconfiguration-rust-sdk from a configuration dump. Do not edit.
+
+#[allow(unused_imports)]
+use ibm_appconfiguration_rust_sdk::{
+    ConfigurationProvider, Entity, Feature, Property, Result, Value,
+};
+
+/// Evaluates the feature "F1" (`f1`).
+#[allow(dead_code)]
+pub fn f1(client: &impl ConfigurationProvider, entity: &impl Entity) -> Result<i64> {
+    client.get_feature("f1")?.get_value_into(entity)
+}
+
+/// Evaluates the feature "F2" (`f2`).
+#[allow(dead_code)]
+pub fn f2(client: &impl ConfigurationProvider, entity: &impl Entity) -> Result<String> {
+    client.get_feature("f2")?.get_value_into(entity)
+}
+
+/// Evaluates the feature "F3" (`f3`).
+#[allow(dead_code)]
+pub fn f3(client: &impl ConfigurationProvider, entity: &impl Entity) -> Result<bool> {
+    client.get_feature("f3")?.get_bool_value(entity)
+}
+
+/// Evaluates the feature "F4" (`f4`).
+#[allow(dead_code)]
+pub fn f4(client: &impl ConfigurationProvider, entity: &impl Entity) -> Result<bool> {
+    client.get_feature("f4")?.get_bool_value(entity)
+}
+
+/// Evaluates the feature "F6" (`f6`).
+#[allow(dead_code)]
+pub fn f6(client: &impl ConfigurationProvider, entity: &impl Entity) -> Result<String> {
+    client.get_feature("f6")?.get_value_into(entity)
+}
+
+/// Evaluates the property "p1" (`p1`).
+#[allow(dead_code)]
+pub fn p1(client: &impl ConfigurationProvider, entity: &impl Entity) -> Result<i64> {
+    client.get_property("p1")?.get_value_into(entity)
+}
+
+/// Evaluates the property "p2" (`p2`).
+#[allow(dead_code)]
+pub fn p2(client: &impl ConfigurationProvider, entity: &impl Entity) -> Result<String> {
+    client.get_property("p2")?.get_value_into(entity)
+}
+
+/// Evaluates the property "p4" (`p4`).
+#[allow(dead_code)]
+pub fn p4(client: &impl ConfigurationProvider, entity: &impl Entity) -> Result<String> {
+    client.get_property("p4")?.get_value_into(entity)
+}
@@ -0,0 +1,60 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Evaluates features and properties through typed accessors generated with the
+//! `codegen` feature from `data/data-dump-enterprise-plan-sdk-testing.json`.
+//!
+//! `flags.rs` is committed here so the example builds without a build script.
+//! In a project it would be generated into `OUT_DIR` from `build.rs`, see the
+//! `codegen` module documentation.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::PathBuf;
+
+use ibm_appconfiguration_rust_sdk::{AppConfigurationOffline, Entity, Value};
+
+mod flags {
+    include!("flags.rs");
+}
+
+struct Customer {
+    id: String,
+    email: String,
+}
+
+impl Entity for Customer {
+    fn get_id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn get_attributes(&self) -> HashMap<String, Value> {
+        HashMap::from([("email".to_string(), Value::from(self.email.clone()))])
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let dump = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("data/data-dump-enterprise-plan-sdk-testing.json");
+    let client = AppConfigurationOffline::new(&dump, "dev", "blue-charge")?;
+    let customer = Customer {
+        id: "a1".to_string(),
+        email: "alice@ibm.com".to_string(),
+    };
+
+    println!("f1 = {}", flags::f1(&client, &customer)?);
+    println!("f3 = {}", flags::f3(&client, &customer)?);
+    println!("p2 = {}", flags::p2(&client, &customer)?);
+    Ok(())
+}
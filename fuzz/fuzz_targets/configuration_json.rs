@@ -0,0 +1,63 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parses arbitrary input as a configuration and, if it is valid, evaluates
+//! all its features and properties.
+
+#![no_main]
+
+use ibm_appconfiguration_rust_sdk::{
+    Configuration, ConfigurationJson, ConfigurationProvider, Entity, Feature, Property, Value,
+};
+use libfuzzer_sys::fuzz_target;
+use std::collections::HashMap;
+
+struct FuzzEntity;
+
+impl Entity for FuzzEntity {
+    fn get_id(&self) -> String {
+        "fuzz".to_string()
+    }
+
+    fn get_attributes(&self) -> HashMap<String, Value> {
+        HashMap::from([
+            (
+                "email".to_string(),
+                Value::from("fuzz@example.com".to_string()),
+            ),
+            ("age".to_string(), Value::from(42.0)),
+        ])
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(json) = std::str::from_utf8(data) else {
+        return;
+    };
+    let _ = ConfigurationJson::from_json_str(json);
+    let Ok(configuration) = Configuration::from_json_str(json, "environment_id", "collection_id")
+    else {
+        return;
+    };
+    for feature_id in configuration.get_feature_ids().unwrap_or_default() {
+        if let Ok(feature) = configuration.get_feature(&feature_id) {
+            let _ = feature.get_current_value(&FuzzEntity);
+        }
+    }
+    for property_id in configuration.get_property_ids().unwrap_or_default() {
+        if let Ok(property) = configuration.get_property(&property_id) {
+            let _ = property.get_current_value(&FuzzEntity);
+        }
+    }
+});
@@ -0,0 +1,133 @@
+#ifndef IBM_APPCONFIGURATION_H
+#define IBM_APPCONFIGURATION_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Result of the FFI calls.
+typedef enum AppConfigStatus {
+  AppConfigStatus_Ok = 0,
+  // A required pointer was NULL or a string was not valid UTF-8/JSON.
+  AppConfigStatus_InvalidArgument = 1,
+  // The evaluated value cannot be converted to the requested type.
+  AppConfigStatus_TypeMismatch = 2,
+  // Any other error (unknown feature, connectivity, ...).
+  AppConfigStatus_Error = 3,
+  // The library panicked, a bug. The description tells where.
+  AppConfigStatus_Panic = 4,
+} AppConfigStatus;
+
+// Opaque handle to a client.
+typedef struct AppConfigClient AppConfigClient;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates a client connected to the App Configuration service, kept up to date
+// in the background. Returns NULL on error.
+//
+// # Safety
+// All arguments must be valid NUL-terminated strings.
+AppConfigClient *appconfig_client_new(const char *region,
+                                      const char *guid,
+                                      const char *apikey,
+                                      const char *environment_id,
+                                      const char *collection_id);
+
+// Creates a client evaluating the configuration from a local file (same format as
+// the configuration exports). Returns NULL on error.
+//
+// # Safety
+// All arguments must be valid NUL-terminated strings.
+AppConfigClient *appconfig_client_new_offline(const char *filepath,
+                                              const char *environment_id,
+                                              const char *collection_id);
+
+// Destroys a client created with `appconfig_client_new*`. NULL is a no-op.
+//
+// # Safety
+// `client` must be NULL or a handle not yet destroyed.
+void appconfig_client_free(AppConfigClient *client);
+
+// Evaluates a boolean feature for the entity and writes it to `out`.
+//
+// # Safety
+// `client` must be a valid handle, `out` NULL or an aligned pointer valid for
+// writes, the strings valid NUL-terminated strings and `attributes_json` NULL
+// or a valid string.
+AppConfigStatus appconfig_feature_get_bool(const AppConfigClient *client,
+                                           const char *feature_id,
+                                           const char *entity_id,
+                                           const char *attributes_json,
+                                           bool *out);
+
+// Evaluates a numeric feature for the entity and writes it to `out`.
+//
+// # Safety
+// Same as [`appconfig_feature_get_bool`].
+AppConfigStatus appconfig_feature_get_number(const AppConfigClient *client,
+                                             const char *feature_id,
+                                             const char *entity_id,
+                                             const char *attributes_json,
+                                             double *out);
+
+// Evaluates a string feature for the entity and writes it to `out`. The string
+// must be released with [`appconfig_string_free`].
+//
+// # Safety
+// Same as [`appconfig_feature_get_bool`].
+AppConfigStatus appconfig_feature_get_string(const AppConfigClient *client,
+                                             const char *feature_id,
+                                             const char *entity_id,
+                                             const char *attributes_json,
+                                             char **out);
+
+// Evaluates a boolean property for the entity and writes it to `out`.
+//
+// # Safety
+// Same as [`appconfig_feature_get_bool`].
+AppConfigStatus appconfig_property_get_bool(const AppConfigClient *client,
+                                            const char *property_id,
+                                            const char *entity_id,
+                                            const char *attributes_json,
+                                            bool *out);
+
+// Evaluates a numeric property for the entity and writes it to `out`.
+//
+// # Safety
+// Same as [`appconfig_feature_get_bool`].
+AppConfigStatus appconfig_property_get_number(const AppConfigClient *client,
+                                              const char *property_id,
+                                              const char *entity_id,
+                                              const char *attributes_json,
+                                              double *out);
+
+// Evaluates a string property for the entity and writes it to `out`. The string
+// must be released with [`appconfig_string_free`].
+//
+// # Safety
+// Same as [`appconfig_feature_get_bool`].
+AppConfigStatus appconfig_property_get_string(const AppConfigClient *client,
+                                              const char *property_id,
+                                              const char *entity_id,
+                                              const char *attributes_json,
+                                              char **out);
+
+// Releases a string returned by this library. NULL is a no-op.
+//
+// # Safety
+// `value` must be NULL or a string returned by this library not yet released.
+void appconfig_string_free(char *value);
+
+// Returns the description of the last error on the calling thread, or NULL.
+// The pointer is valid until the next call into this library on the same thread.
+const char *appconfig_last_error_message(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* IBM_APPCONFIGURATION_H */
@@ -12,19 +12,34 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
-use crate::OfflineMode;
 use crate::client::app_configuration_ibm_cloud::{
-    ResolvedUrls, resolve_urls_from_service_override,
+    ResolvedUrls, resolve_urls_from_service_override, validate_api_key, validate_region,
 };
 use crate::client::app_configuration_offline::AppConfigurationOffline;
+use crate::client::feature_defaults::FeatureDefaults;
+use crate::client::feature_prerequisites::FeaturePrerequisites;
+use crate::client::local_overrides::LocalOverrides;
 use crate::client::{
-    AppConfigurationClientIBMCloud, ConfigurationId, ConfigurationProvider, RuntimeEventEmitter,
-    RuntimeStatus,
+    AppConfigurationClientIBMCloud, Authenticator, ConfigurationId, ConfigurationProvider,
+    DeprecatedFlagUsage, Exposure, RuntimeEventEmitter, RuntimeEventKind, RuntimeStatus,
+    SdkErrorSnapshot, SdkStats, SyncRecord, UnusedFeatures,
 };
 use crate::errors::{Error, Result};
-use crate::models::{FeatureSnapshot, PropertySnapshot, SecretManager, SecretPropertySnapshot};
+use crate::models::{
+    Configuration, FeatureSnapshot, PropertySnapshot, SecretManager, SecretPropertySnapshot,
+    SegmentSnapshot,
+};
+use crate::network::{RequestAuthenticator, TokenProvider, check_credentials};
+use crate::utils::ReadySignal;
+use crate::{
+    CacheCipher, Clock, ConfigurationGuard, LiveConfigurationOptions, MeteringOptions, OfflineMode,
+    Ready, SharedCache, SocketOptions, ThreadOptions, ValidationMode, Value,
+};
 
 #[derive(Default)]
 pub struct AppConfiguration {
@@ -33,15 +48,36 @@ pub struct AppConfiguration {
     runtime_emitter: RuntimeEventEmitter,
     /// Set by [`AppConfiguration::override_service_url`] before `init()`.
     service_url_override: Option<String>,
+    /// Set by [`AppConfiguration::override_iam_url`] before `init()`.
+    iam_url_override: Option<String>,
+    /// Set by [`AppConfiguration::add_custom_header`] before `init()`.
+    custom_headers: Vec<(String, String)>,
+    /// Set from [`AppConfigurationContextOptions::value_interpolation_enabled`].
+    value_interpolation_enabled: bool,
+    /// Set from [`AppConfigurationContextOptions::rollout_salt`].
+    rollout_salt: Option<Arc<str>>,
+    /// Declared with [`AppConfiguration::add_feature_prerequisite`].
+    feature_prerequisites: FeaturePrerequisites,
+    /// Set by [`AppConfiguration::set_clock`]; the system clock otherwise.
+    clock: Option<Arc<dyn Clock>>,
+    /// Set by [`AppConfiguration::set_local_override`] and
+    /// [`AppConfiguration::load_local_overrides`].
+    local_overrides: LocalOverrides,
+    /// Set by [`AppConfiguration::register_default`].
+    feature_defaults: FeatureDefaults,
+    /// Set once the client created by `set_context()` is ready.
+    ready: Arc<ReadySignal>,
 }
 
 #[derive(Debug, Clone)]
 struct InitState {
-    apikey: String,
+    /// `None` until `init()` (or one of its variants) is called.
+    authenticator: Option<Authenticator>,
     region: String,
     guid: String,
     use_private_endpoint: bool,
-    /// Pre-resolved URLs computed at `init()` time from `service_url_override`.
+    /// Pre-resolved URLs computed at `init()` time from `service_url_override`
+    /// and `iam_url_override`.
     resolved_urls: ResolvedUrls,
 }
 
@@ -50,6 +86,67 @@ pub struct AppConfigurationContextOptions {
     pub persistent_cache_directory: Option<PathBuf>,
     pub bootstrap_file: Option<PathBuf>,
     pub live_config_update_enabled: bool,
+    /// Collapses bursts of websocket update notifications into at most one
+    /// configuration fetch per window. See [`LiveConfigurationOptions`].
+    pub refresh_coalescing_window: Duration,
+    /// Substitutes `${entity.id}` and `${attr:<name>}` placeholders in String
+    /// values of features and properties with data from the evaluated entity.
+    pub value_interpolation_enabled: bool,
+    /// Whether invalid features and properties reject the whole configuration
+    /// or are skipped. See [`ValidationMode`].
+    pub validation_mode: ValidationMode,
+    /// Sent with the usage metering data to tell apart the deployments
+    /// evaluating the same collection. See [`LiveConfigurationOptions`].
+    pub instance_label: Option<String>,
+    /// What the usage metering records and sends, e.g. whether entity IDs are
+    /// hashed. See [`MeteringOptions`].
+    pub metering: MeteringOptions,
+    /// Added to the input of the hash assigning entities to rollout buckets, so
+    /// applications sharing feature IDs do not roll out to the same entities.
+    /// See [`FeatureSnapshot::with_rollout_salt`].
+    pub rollout_salt: Option<String>,
+    /// Schema version the configurations must declare, others are rejected.
+    /// See [`LiveConfigurationOptions`].
+    pub expected_schema_version: Option<u32>,
+    /// Regions of the same instance to fail over to, in order, while the
+    /// region given to `init()` is unreachable. Ignored when the service URL is
+    /// overridden. See [`LiveConfigurationOptions::fallback_service_addresses`].
+    pub fallback_regions: Vec<String>,
+    /// Checks run on every configuration received from the server before it
+    /// is used. See [`ConfigurationGuard`].
+    pub configuration_guards: Vec<ConfigurationGuard>,
+    /// Name, stack size and priority of the background thread keeping the
+    /// configuration up to date. See [`ThreadOptions`].
+    pub thread_options: ThreadOptions,
+    /// Time `set_context()` waits for a configuration before failing with
+    /// [`Error::InitialFetchTimeout`]. See [`LiveConfigurationOptions`].
+    pub initial_fetch_timeout: Option<Duration>,
+    /// Fetches the configuration on this interval instead of keeping a
+    /// websocket open. See [`LiveConfigurationOptions::polling_interval`].
+    pub polling_interval: Option<Duration>,
+    /// Period covered by [`ConfigurationProvider::unused_features`]. See
+    /// [`LiveConfigurationOptions::unused_feature_window`].
+    pub unused_feature_window: Option<Duration>,
+    /// IDs of features planned for removal, reported by
+    /// [`ConfigurationProvider::deprecated_flags_in_use`] when evaluated. See
+    /// [`LiveConfigurationOptions::deprecated_features`].
+    pub deprecated_features: Vec<String>,
+    /// Encrypts the configuration written to the persistent cache directory.
+    /// See [`CacheCipher`].
+    pub cache_cipher: Option<CacheCipher>,
+    /// Shares the configurations downloaded with the clients of other
+    /// replicas. See [`SharedCache`].
+    pub shared_cache: Option<SharedCache>,
+    /// Configuration fetches allowed over any hour. See
+    /// [`LiveConfigurationOptions::max_fetches_per_hour`].
+    pub max_fetches_per_hour: Option<u32>,
+    /// Writes the configurations received from the server to `bootstrap_file`,
+    /// so restarts fall back to the newest one known. See
+    /// [`OfflineMode::BootstrapFile`].
+    pub persist_bootstrap_updates: bool,
+    /// TCP keepalive, nodelay and connect timeout of the connections to the
+    /// server. See [`SocketOptions`].
+    pub socket_options: SocketOptions,
 }
 
 impl Default for AppConfigurationContextOptions {
@@ -58,6 +155,25 @@ impl Default for AppConfigurationContextOptions {
             persistent_cache_directory: None,
             bootstrap_file: None,
             live_config_update_enabled: true,
+            refresh_coalescing_window: Duration::ZERO,
+            value_interpolation_enabled: false,
+            validation_mode: ValidationMode::Strict,
+            instance_label: None,
+            metering: MeteringOptions::default(),
+            rollout_salt: None,
+            expected_schema_version: None,
+            fallback_regions: Vec::new(),
+            configuration_guards: Vec::new(),
+            thread_options: ThreadOptions::default(),
+            initial_fetch_timeout: None,
+            polling_interval: None,
+            unused_feature_window: None,
+            deprecated_features: Vec::new(),
+            cache_cipher: None,
+            shared_cache: None,
+            max_fetches_per_hour: None,
+            persist_bootstrap_updates: false,
+            socket_options: SocketOptions::default(),
         }
     }
 }
@@ -76,11 +192,132 @@ impl AppConfigurationContextOptions {
             persistent_cache_directory,
             bootstrap_file,
             live_config_update_enabled,
+            ..Default::default()
         };
         opts.validate()?;
         Ok(opts)
     }
 
+    /// Sets [`AppConfigurationContextOptions::refresh_coalescing_window`].
+    pub fn with_refresh_coalescing_window(mut self, window: Duration) -> Self {
+        self.refresh_coalescing_window = window;
+        self
+    }
+
+    /// Sets [`AppConfigurationContextOptions::validation_mode`].
+    pub fn with_validation_mode(mut self, validation_mode: ValidationMode) -> Self {
+        self.validation_mode = validation_mode;
+        self
+    }
+
+    /// Sets [`AppConfigurationContextOptions::instance_label`].
+    pub fn with_instance_label(mut self, label: impl Into<String>) -> Self {
+        self.instance_label = Some(label.into());
+        self
+    }
+
+    /// Sets [`AppConfigurationContextOptions::metering`].
+    pub fn with_metering_options(mut self, metering: MeteringOptions) -> Self {
+        self.metering = metering;
+        self
+    }
+
+    /// Sets [`AppConfigurationContextOptions::value_interpolation_enabled`].
+    pub fn with_value_interpolation(mut self, enabled: bool) -> Self {
+        self.value_interpolation_enabled = enabled;
+        self
+    }
+
+    /// Sets [`AppConfigurationContextOptions::rollout_salt`].
+    pub fn with_rollout_salt(mut self, salt: impl Into<String>) -> Self {
+        self.rollout_salt = Some(salt.into());
+        self
+    }
+
+    /// Sets [`AppConfigurationContextOptions::expected_schema_version`].
+    pub fn with_expected_schema_version(mut self, version: u32) -> Self {
+        self.expected_schema_version = Some(version);
+        self
+    }
+
+    /// Sets [`AppConfigurationContextOptions::fallback_regions`].
+    pub fn with_fallback_regions<S: Into<String>>(
+        mut self,
+        regions: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.fallback_regions = regions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Adds a guard to [`AppConfigurationContextOptions::configuration_guards`].
+    pub fn with_configuration_guard(mut self, guard: ConfigurationGuard) -> Self {
+        self.configuration_guards.push(guard);
+        self
+    }
+
+    /// Sets [`AppConfigurationContextOptions::thread_options`].
+    pub fn with_thread_options(mut self, thread_options: ThreadOptions) -> Self {
+        self.thread_options = thread_options;
+        self
+    }
+
+    /// Sets [`AppConfigurationContextOptions::initial_fetch_timeout`].
+    pub fn with_initial_fetch_timeout(mut self, timeout: Duration) -> Self {
+        self.initial_fetch_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets [`AppConfigurationContextOptions::polling_interval`].
+    pub fn with_polling_interval(mut self, interval: Duration) -> Self {
+        self.polling_interval = Some(interval);
+        self
+    }
+
+    /// Sets [`AppConfigurationContextOptions::unused_feature_window`].
+    pub fn with_unused_feature_window(mut self, window: Duration) -> Self {
+        self.unused_feature_window = Some(window);
+        self
+    }
+
+    /// Sets [`AppConfigurationContextOptions::deprecated_features`].
+    pub fn with_deprecated_features<S: Into<String>>(
+        mut self,
+        feature_ids: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.deprecated_features = feature_ids.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets [`AppConfigurationContextOptions::cache_cipher`].
+    pub fn with_cache_cipher(mut self, cipher: CacheCipher) -> Self {
+        self.cache_cipher = Some(cipher);
+        self
+    }
+
+    /// Sets [`AppConfigurationContextOptions::shared_cache`].
+    pub fn with_shared_cache(mut self, shared_cache: SharedCache) -> Self {
+        self.shared_cache = Some(shared_cache);
+        self
+    }
+
+    /// Sets [`AppConfigurationContextOptions::max_fetches_per_hour`].
+    pub fn with_max_fetches_per_hour(mut self, max_fetches: u32) -> Self {
+        self.max_fetches_per_hour = Some(max_fetches);
+        self
+    }
+
+    /// Sets [`AppConfigurationContextOptions::persist_bootstrap_updates`].
+    pub fn with_persist_bootstrap_updates(mut self, persist: bool) -> Self {
+        self.persist_bootstrap_updates = persist;
+        self
+    }
+
+    /// Sets [`AppConfigurationContextOptions::socket_options`].
+    pub fn with_socket_options(mut self, socket_options: SocketOptions) -> Self {
+        self.socket_options = socket_options;
+        self
+    }
+
     pub fn validate(&self) -> Result<()> {
         if let Some(path) = &self.persistent_cache_directory {
             validate_non_empty_path(
@@ -106,7 +343,7 @@ impl AppConfigurationContextOptions {
             ));
         }
 
-        Ok(())
+        self.thread_options.validate()
     }
 }
 
@@ -114,6 +351,7 @@ impl AppConfiguration {
     /// Typical usage is:
     /// - call [`AppConfiguration::new()`]
     /// - optionally call [`AppConfiguration::override_service_url()`]
+    /// - optionally call [`AppConfiguration::override_iam_url()`]
     /// - optionally call [`AppConfiguration::use_private_endpoint()`]
     /// - call [`AppConfiguration::init()`]
     /// - call [`AppConfiguration::set_context()`]
@@ -142,13 +380,198 @@ impl AppConfiguration {
         self.service_url_override = Some(url.to_string());
     }
 
+    /// Overrides the IAM token endpoint used to exchange the apikey for an access token.
+    ///
+    /// Must be called **before** [`AppConfiguration::init()`].
+    ///
+    /// `url` is the full token URL including scheme and path. It takes precedence over
+    /// the endpoint derived from [`AppConfiguration::use_private_endpoint()`] and
+    /// [`AppConfiguration::override_service_url()`], which is needed when the workload
+    /// reaches IAM through a VPE or a dedicated endpoint.
+    ///
+    /// ```ignore
+    /// sdk.use_private_endpoint(true);
+    /// sdk.override_iam_url("https://iam.my-vpe.example.com/identity/token");
+    /// sdk.init(region, guid, apikey)?;
+    /// ```
+    pub fn override_iam_url(&mut self, url: &str) {
+        self.iam_url_override = Some(url.to_string());
+    }
+
+    /// Adds an HTTP header sent with every request to the App Configuration service,
+    /// including the websocket handshake.
+    ///
+    /// Must be called **before** [`AppConfiguration::init()`]. Needed to traverse API
+    /// gateways in front of on-prem instances, e.g. `X-Internal-Routing`.
+    pub fn add_custom_header(&mut self, name: &str, value: &str) -> Result<()> {
+        reqwest::header::HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| Error::Other(format!("invalid custom header name '{name}'")))?;
+        reqwest::header::HeaderValue::from_str(value)
+            .map_err(|_| Error::Other(format!("invalid value for custom header '{name}'")))?;
+        self.custom_headers
+            .push((name.to_string(), value.to_string()));
+        Ok(())
+    }
+
+    /// Declares `prerequisite_id` as a prerequisite of `feature_id`: the feature only
+    /// evaluates as enabled for an entity if the prerequisite (and, transitively, its
+    /// own prerequisites) is enabled for that same entity.
+    ///
+    /// Typical use is a kill switch gating many child features. Returns an error if the
+    /// declaration would introduce a dependency cycle. Prerequisites must exist in the
+    /// configuration, otherwise evaluating the dependent feature fails.
+    ///
+    /// ```ignore
+    /// sdk.add_feature_prerequisite("new-checkout-ui", "checkout-kill-switch")?;
+    /// ```
+    pub fn add_feature_prerequisite(
+        &mut self,
+        feature_id: &str,
+        prerequisite_id: &str,
+    ) -> Result<()> {
+        self.feature_prerequisites.add(feature_id, prerequisite_id)
+    }
+
+    /// Sets the [`Clock`] used to resolve time-based targeting (scheduled targeting
+    /// rules and progressive rollouts) when evaluating features and properties.
+    ///
+    /// Defaults to the system clock. Mostly useful in tests to simulate time.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = Some(clock);
+    }
+
+    /// Forces the feature or property `id` to evaluate to `value` for every entity,
+    /// regardless of the configuration received from the server.
+    ///
+    /// Local overrides are meant for development: they let a flag be turned on
+    /// locally without changing the shared configuration in the dashboard.
+    /// Overriding a boolean feature with `false` also reports it as disabled.
+    ///
+    /// ```ignore
+    /// sdk.set_local_override("new-checkout-ui", Value::Boolean(true));
+    /// ```
+    pub fn set_local_override(&mut self, id: &str, value: Value) {
+        self.local_overrides.set(id, value);
+    }
+
+    /// Adds the local overrides declared in a JSON file, an object mapping feature
+    /// and property IDs to boolean, numeric or string values:
+    ///
+    /// ```json
+    /// {"new-checkout-ui": true, "checkout-banner": "Local build"}
+    /// ```
+    ///
+    /// Overrides already set for the same IDs are replaced. No override is added if
+    /// the file cannot be read or any of its values is invalid.
+    pub fn load_local_overrides(&mut self, path: &Path) -> Result<()> {
+        self.local_overrides.load_file(path)
+    }
+
+    /// Returns the local overrides currently set, by feature or property ID.
+    pub fn get_local_overrides(&self) -> &HashMap<String, Value> {
+        self.local_overrides.list()
+    }
+
+    /// Removes the local override of `id`, returning its value if there was one.
+    pub fn remove_local_override(&mut self, id: &str) -> Option<Value> {
+        self.local_overrides.remove(id)
+    }
+
+    /// Removes all the local overrides.
+    pub fn clear_local_overrides(&mut self) {
+        self.local_overrides.clear();
+    }
+
+    /// Registers `value` as the value of the feature `feature_id` while it does
+    /// not exist in the configuration, instead of failing with a "feature not
+    /// found" error.
+    ///
+    /// This lets an application be deployed before its new flags are created in
+    /// the dashboard. Each time a default is used, the counter returned by
+    /// [`AppConfiguration::get_default_fallback_counts`] is incremented. A
+    /// warning is logged the first time only. The feature evaluates to `value`
+    /// for every entity, with the `REGISTERED_DEFAULT` value type in its
+    /// evaluation details.
+    ///
+    /// ```ignore
+    /// sdk.register_default("new_checkout", Value::Boolean(false));
+    /// ```
+    pub fn register_default(&mut self, feature_id: &str, value: Value) {
+        self.feature_defaults.register(feature_id, value);
+    }
+
+    /// Returns the registered defaults, by feature ID.
+    pub fn get_registered_defaults(&self) -> &HashMap<String, Value> {
+        self.feature_defaults.list()
+    }
+
+    /// Returns how many times the registered default of each feature was
+    /// returned because the feature was missing from the configuration.
+    pub fn get_default_fallback_counts(&self) -> HashMap<String, u64> {
+        self.feature_defaults.fallback_counts()
+    }
+
+    /// Returns the feature snapshot with its declared prerequisites attached.
+    fn get_feature_with_prerequisites(
+        &self,
+        feature_id: &str,
+        get_feature: &dyn Fn(&str) -> Result<FeatureSnapshot>,
+    ) -> Result<FeatureSnapshot> {
+        let prerequisites = self
+            .feature_prerequisites
+            .get(feature_id)
+            .iter()
+            .map(|prerequisite_id| {
+                self.get_feature_with_prerequisites(prerequisite_id, get_feature)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.feature_defaults
+            .or_default(feature_id, get_feature(feature_id))?
+            .with_prerequisites(prerequisites)
+            .with_local_override(self.local_overrides.get(feature_id).cloned())
+    }
+
+    /// Returns the feature snapshot with the prerequisites and the settings of
+    /// this instance applied.
+    fn get_feature_with_settings(
+        &self,
+        feature_id: &str,
+        get_feature: &dyn Fn(&str) -> Result<FeatureSnapshot>,
+    ) -> Result<FeatureSnapshot> {
+        let mut feature = self
+            .get_feature_with_prerequisites(feature_id, get_feature)?
+            .with_value_interpolation(self.value_interpolation_enabled);
+        if let Some(clock) = &self.clock {
+            feature = feature.with_clock(clock.clone());
+        }
+        if let Some(salt) = &self.rollout_salt {
+            feature = feature.with_rollout_salt(salt.clone());
+        }
+        Ok(feature)
+    }
+
+    /// Returns the property snapshot with the settings of this instance applied.
+    fn with_property_settings(
+        &self,
+        property_id: &str,
+        property: PropertySnapshot,
+    ) -> Result<PropertySnapshot> {
+        let mut property = property
+            .with_value_interpolation(self.value_interpolation_enabled)
+            .with_local_override(self.local_overrides.get(property_id).cloned())?;
+        if let Some(clock) = &self.clock {
+            property = property.with_clock(clock.clone());
+        }
+        Ok(property)
+    }
+
     /// Controls whether the SDK should use the IBM Cloud private endpoint.
     pub fn use_private_endpoint(&mut self, use_private_endpoint: bool) {
         match self.init_state.as_mut() {
             Some(state) => state.use_private_endpoint = use_private_endpoint,
             None => {
                 self.init_state = Some(InitState {
-                    apikey: String::new(),
+                    authenticator: None,
                     region: String::new(),
                     guid: String::new(),
                     use_private_endpoint,
@@ -166,9 +589,72 @@ impl AppConfiguration {
             return Ok(());
         }
 
+        validate_required("apikey", apikey)?;
+        validate_api_key(apikey)?;
+        self.init_with_authenticator(region, guid, Authenticator::ApiKey(apikey.to_string()))
+    }
+
+    /// Initializes the SDK authenticating with an IBM Cloud trusted profile instead of an
+    /// API key.
+    ///
+    /// The compute resource token mounted by IKS / Code Engine is read from `cr_token_file`,
+    /// or from the platform default locations when `None`.
+    ///
+    /// Repeated calls are ignored after the first successful initialization.
+    pub fn init_with_trusted_profile(
+        &mut self,
+        region: &str,
+        guid: &str,
+        profile_id: &str,
+        cr_token_file: Option<PathBuf>,
+    ) -> Result<()> {
+        if self.is_initialized() {
+            return Ok(());
+        }
+
+        validate_required("profile_id", profile_id)?;
+        if let Some(path) = &cr_token_file {
+            validate_non_empty_path(path, "cr_token_file cannot be empty when provided")?;
+        }
+        self.init_with_authenticator(
+            region,
+            guid,
+            Authenticator::TrustedProfile {
+                profile_id: profile_id.to_string(),
+                cr_token_file,
+            },
+        )
+    }
+
+    /// Initializes the SDK authenticating every request with `authenticator`
+    /// instead of an IAM access token, e.g. for self-hosted deployments behind
+    /// a gateway expecting signed requests.
+    ///
+    /// Repeated calls are ignored after the first successful initialization.
+    pub fn init_with_request_authenticator(
+        &mut self,
+        region: &str,
+        guid: &str,
+        authenticator: Arc<dyn RequestAuthenticator>,
+    ) -> Result<()> {
+        if self.is_initialized() {
+            return Ok(());
+        }
+
+        self.init_with_authenticator(region, guid, Authenticator::Custom(authenticator))
+    }
+
+    fn init_with_authenticator(
+        &mut self,
+        region: &str,
+        guid: &str,
+        authenticator: Authenticator,
+    ) -> Result<()> {
         validate_required("region", region)?;
         validate_required("guid", guid)?;
-        validate_required("apikey", apikey)?;
+        if self.service_url_override.is_none() {
+            validate_region(region)?;
+        }
 
         let use_private_endpoint = self
             .init_state
@@ -177,13 +663,19 @@ impl AppConfiguration {
             .unwrap_or(false);
 
         // Resolve URLs once at init time so set_context() can just pass them through.
-        let resolved_urls = match &self.service_url_override {
+        let mut resolved_urls = match &self.service_url_override {
             Some(url) => resolve_urls_from_service_override(url, use_private_endpoint),
             None => ResolvedUrls::default(),
         };
+        if let Some(url) = &self.iam_url_override {
+            resolved_urls = resolved_urls.with_token_url(url);
+        }
+        resolved_urls
+            .custom_headers
+            .extend(self.custom_headers.iter().cloned());
 
         self.init_state = Some(InitState {
-            apikey: apikey.to_string(),
+            authenticator: Some(authenticator),
             region: region.to_string(),
             guid: guid.to_string(),
             use_private_endpoint,
@@ -206,9 +698,10 @@ impl AppConfiguration {
             return Ok(());
         }
 
-        let init_state = self
+        let (init_state, authenticator) = self
             .init_state
             .clone()
+            .and_then(|state| state.authenticator.clone().map(|auth| (state, auth)))
             .ok_or_else(|| Error::Other("init must be called before set_context".to_string()))?;
 
         validate_required("collection_id", collection_id)?;
@@ -220,28 +713,113 @@ impl AppConfiguration {
             init_state.guid,
             environment_id.to_string(),
             collection_id.to_string(),
-        );
+        )?;
 
-        let client = AppConfigurationClientIBMCloud::new(
-            &init_state.apikey,
+        let fallback_service_addresses = if init_state.resolved_urls.service_host_override.is_some()
+        {
+            Vec::new()
+        } else {
+            options
+                .fallback_regions
+                .iter()
+                .map(|region| {
+                    AppConfigurationClientIBMCloud::build_service_address(
+                        region,
+                        init_state.use_private_endpoint,
+                        &init_state.resolved_urls,
+                    )
+                })
+                .collect()
+        };
+
+        // Registered before the client starts, not to miss its first configuration
+        let ready = self.ready.clone();
+        self.runtime_emitter.on(Arc::new(move |event| {
+            if event.kind == RuntimeEventKind::RefreshSuccess {
+                ready.set();
+            }
+        }))?;
+        let client = AppConfigurationClientIBMCloud::new_with_authenticator(
+            authenticator,
             &init_state.region,
             configuration_id,
             offline_mode,
             init_state.use_private_endpoint,
             init_state.resolved_urls,
+            LiveConfigurationOptions {
+                refresh_coalescing_window: options.refresh_coalescing_window,
+                validation_mode: options.validation_mode,
+                instance_label: options.instance_label.clone(),
+                metering: options.metering,
+                expected_schema_version: options.expected_schema_version,
+                fallback_service_addresses,
+                failover_threshold: None,
+                configuration_guards: options.configuration_guards.clone(),
+                thread_options: options.thread_options.clone(),
+                initial_fetch_timeout: options.initial_fetch_timeout,
+                polling_interval: options.polling_interval,
+                unused_feature_window: options.unused_feature_window,
+                deprecated_features: options.deprecated_features.clone(),
+                cache_cipher: options.cache_cipher.clone(),
+                shared_cache: options.shared_cache.clone(),
+                max_fetches_per_hour: options.max_fetches_per_hour,
+                socket_options: options.socket_options.clone(),
+                step_controller: None,
+            },
             self.runtime_emitter.clone(),
         )?;
 
+        if client.ready().is_ready() {
+            self.ready.set();
+        }
         self.client = Some(client);
+        self.value_interpolation_enabled = options.value_interpolation_enabled;
+        self.rollout_salt = options.rollout_salt.map(Into::into);
         Ok(())
     }
 
+    /// Downloads the configuration of the collection and environment once,
+    /// instead of calling [`AppConfiguration::set_context()`].
+    ///
+    /// No websocket is opened and no thread is started: the returned
+    /// [`Configuration`] evaluates features and properties, but it is never
+    /// updated and evaluations are not metered. Local overrides and the other
+    /// settings of this instance are not applied. Requires `init()`.
+    ///
+    /// ```ignore
+    /// let configuration = sdk.fetch_once("ecommerce", "production")?;
+    /// let enabled = configuration.get_feature("new-checkout-ui")?.is_enabled()?;
+    /// ```
+    pub fn fetch_once(&self, collection_id: &str, environment_id: &str) -> Result<Configuration> {
+        let (init_state, authenticator) = self
+            .init_state
+            .clone()
+            .and_then(|state| state.authenticator.clone().map(|auth| (state, auth)))
+            .ok_or_else(|| Error::Other("init must be called before fetch_once".to_string()))?;
+
+        validate_required("collection_id", collection_id)?;
+        validate_required("environment_id", environment_id)?;
+
+        let configuration_id = ConfigurationId::new(
+            init_state.guid,
+            environment_id.to_string(),
+            collection_id.to_string(),
+        )?;
+        AppConfigurationClientIBMCloud::fetch_once(
+            authenticator,
+            &init_state.region,
+            &configuration_id,
+            init_state.use_private_endpoint,
+            &init_state.resolved_urls,
+        )
+    }
+
     /// Returns whether [`AppConfiguration::init()`] has been completed.
     pub fn is_initialized(&self) -> bool {
         self.init_state
             .as_ref()
             .map(|state| {
-                !state.region.is_empty() && !state.guid.is_empty() && !state.apikey.is_empty()
+                !state.region.is_empty() && !state.guid.is_empty() && state.authenticator.is_some()
             })
             .unwrap_or(false)
     }
@@ -272,6 +850,63 @@ impl AppConfiguration {
     pub fn emitter(&self) -> RuntimeEventEmitter {
         self.runtime_emitter.clone()
     }
+
+    /// Switches to the configuration of `fallback`, ignoring the updates from
+    /// the server until [`AppConfiguration::exit_safe_mode()`] is called.
+    ///
+    /// See [`AppConfigurationClientIBMCloud::enter_safe_mode`].
+    pub fn enter_safe_mode(&self, fallback: OfflineMode) -> Result<()> {
+        self.client()?.enter_safe_mode(fallback)
+    }
+
+    /// Lifts the safe mode, the latest configuration from the server is used again.
+    pub fn exit_safe_mode(&self) -> Result<()> {
+        self.client()?.exit_safe_mode()
+    }
+
+    /// Replaces the source of the access tokens of the client started by
+    /// [`AppConfiguration::set_context()`], without restarting it, and of the
+    /// clients created afterwards.
+    ///
+    /// An access token is requested from `token_provider` first, and the
+    /// previous credentials are kept if that fails. See
+    /// [`AppConfigurationClientIBMCloud::update_token_provider`].
+    pub fn update_token_provider(&mut self, token_provider: Box<dyn TokenProvider>) -> Result<()> {
+        self.update_credentials(Authenticator::Custom(Arc::new(token_provider)))
+    }
+
+    /// Rotates the API key of the client started by
+    /// [`AppConfiguration::set_context()`] and of the clients created
+    /// afterwards, see [`AppConfiguration::update_token_provider`].
+    pub fn update_api_key(&mut self, apikey: &str) -> Result<()> {
+        validate_api_key(apikey)?;
+        self.update_credentials(Authenticator::ApiKey(apikey.to_string()))
+    }
+
+    /// Checks `authenticator` and uses it instead of the credentials given to
+    /// `init()`. Requires `init()`.
+    fn update_credentials(&mut self, authenticator: Authenticator) -> Result<()> {
+        let state = self
+            .init_state
+            .as_mut()
+            .filter(|state| state.authenticator.is_some())
+            .ok_or_else(|| {
+                Error::Other("init must be called before updating the credentials".to_string())
+            })?;
+        match &self.client {
+            Some(client) => client.update_credentials(authenticator.clone())?,
+            None => check_credentials(
+                AppConfigurationClientIBMCloud::build_authenticator(
+                    authenticator.clone(),
+                    state.use_private_endpoint,
+                    &state.resolved_urls,
+                )
+                .as_ref(),
+            )?,
+        }
+        state.authenticator = Some(authenticator);
+        Ok(())
+    }
 }
 
 impl ConfigurationProvider for AppConfiguration {
@@ -280,7 +915,8 @@ impl ConfigurationProvider for AppConfiguration {
     }
 
     fn get_feature(&self, feature_id: &str) -> Result<FeatureSnapshot> {
-        self.client()?.get_feature(feature_id)
+        let client = self.client()?;
+        self.get_feature_with_settings(feature_id, &|id| client.get_feature(id))
     }
 
     fn get_property_ids(&self) -> Result<Vec<String>> {
@@ -288,7 +924,27 @@ impl ConfigurationProvider for AppConfiguration {
     }
 
     fn get_property(&self, property_id: &str) -> Result<PropertySnapshot> {
-        self.client()?.get_property(property_id)
+        self.with_property_settings(property_id, self.client()?.get_property(property_id)?)
+    }
+
+    fn get_feature_from(
+        &self,
+        configuration: &Configuration,
+        feature_id: &str,
+    ) -> Result<FeatureSnapshot> {
+        let client = self.client()?;
+        self.get_feature_with_settings(feature_id, &|id| client.get_feature_from(configuration, id))
+    }
+
+    fn get_property_from(
+        &self,
+        configuration: &Configuration,
+        property_id: &str,
+    ) -> Result<PropertySnapshot> {
+        let property = self
+            .client()?
+            .get_property_from(configuration, property_id)?;
+        self.with_property_settings(property_id, property)
     }
 
     fn get_secret_property(&self, property_id: &str) -> Result<SecretPropertySnapshot> {
@@ -299,6 +955,22 @@ impl ConfigurationProvider for AppConfiguration {
         self.client()?.is_connected()
     }
 
+    fn get_segment_ids(&self) -> Result<Vec<String>> {
+        self.client()?.get_segment_ids()
+    }
+
+    fn get_segment(&self, segment_id: &str) -> Result<SegmentSnapshot> {
+        self.client()?.get_segment(segment_id)
+    }
+
+    fn get_configuration_snapshot(&self) -> Result<Configuration> {
+        self.client()?.get_configuration_snapshot()
+    }
+
+    fn get_shared_configuration(&self) -> Result<Arc<Configuration>> {
+        self.client()?.get_shared_configuration()
+    }
+
     fn is_online(&self) -> Result<bool> {
         self.client()?.is_online()
     }
@@ -307,6 +979,45 @@ impl ConfigurationProvider for AppConfiguration {
         self.client()?.get_runtime_status()
     }
 
+    fn last_error(&self) -> Result<Option<SdkErrorSnapshot>> {
+        self.client()?.last_error()
+    }
+
+    fn sdk_stats(&self) -> Result<Option<SdkStats>> {
+        self.client()?.sdk_stats()
+    }
+
+    /// Unlike the other methods, it can be called before `set_context()`.
+    fn ready(&self) -> Ready {
+        Ready::new(self.ready.clone())
+    }
+
+    fn fresh_configuration(
+        &self,
+        max_age: std::time::Duration,
+    ) -> Result<Option<Arc<Configuration>>> {
+        self.client()?.fresh_configuration(max_age)
+    }
+
+    fn unused_features(&self) -> Result<Option<UnusedFeatures>> {
+        self.client()?.unused_features()
+    }
+
+    fn deprecated_flags_in_use(&self) -> Result<Vec<DeprecatedFlagUsage>> {
+        self.client()?.deprecated_flags_in_use()
+    }
+
+    fn sync_history(&self) -> Result<Vec<SyncRecord>> {
+        self.client()?.sync_history()
+    }
+
+    /// Exposures are recorded by the emitter of this client, also before
+    /// `set_context()` and while the client is recreated.
+    fn record_exposure(&self, exposure: Exposure) -> Result<()> {
+        self.runtime_emitter.emit_exposure(exposure);
+        Ok(())
+    }
+
     fn wait_until_online(&self) -> bool {
         if let Some(client) = self.client.as_ref() {
             client.wait_until_online()
@@ -364,11 +1075,12 @@ fn build_offline_mode(
                 }
             }
         }
-        return OfflineMode::bootstrap_file(
-            bootstrap_file,
-            environment_id.to_string(),
-            collection_id.to_string(),
-        );
+        return OfflineMode::BootstrapFile {
+            path: bootstrap_file.clone(),
+            environment_id: environment_id.to_string(),
+            collection_id: collection_id.to_string(),
+            persist_updates: options.persist_bootstrap_updates,
+        };
     }
 
     if let Some(persistent_cache_directory) = &options.persistent_cache_directory {
@@ -449,7 +1161,106 @@ mod tests {
         let init_state = sdk.init_state.unwrap();
         assert_eq!(init_state.region, "us-south");
         assert_eq!(init_state.guid, "guid-1");
-        assert_eq!(init_state.apikey, "apikey-1");
+        assert_eq!(
+            init_state.authenticator,
+            Some(Authenticator::ApiKey("apikey-1".to_string()))
+        );
+    }
+
+    #[test]
+    fn init_with_trusted_profile() {
+        let mut sdk = AppConfiguration::new();
+        sdk.use_private_endpoint(true);
+        assert!(!sdk.is_initialized());
+
+        sdk.init_with_trusted_profile("us-south", "guid-1", "Profile-1234", None)
+            .unwrap();
+        assert!(sdk.is_initialized());
+
+        // Already initialized, ignored
+        sdk.init("eu-de", "guid-2", "apikey-2").unwrap();
+
+        let init_state = sdk.init_state.unwrap();
+        assert!(init_state.use_private_endpoint);
+        assert_eq!(
+            init_state.authenticator,
+            Some(Authenticator::TrustedProfile {
+                profile_id: "Profile-1234".to_string(),
+                cr_token_file: None,
+            })
+        );
+    }
+
+    #[test]
+    fn init_with_request_authenticator() {
+        #[derive(Debug)]
+        struct GatewayKey;
+        impl RequestAuthenticator for GatewayKey {
+            fn authenticate(
+                &self,
+                request: &mut crate::OutgoingRequest<'_>,
+            ) -> crate::network::NetworkResult<()> {
+                request.set_header("X-Api-Key", "key")
+            }
+        }
+
+        let authenticator: Arc<dyn RequestAuthenticator> = Arc::new(GatewayKey);
+        let mut sdk = AppConfiguration::new();
+        sdk.init_with_request_authenticator("us-south", "guid-1", authenticator.clone())
+            .unwrap();
+        assert!(sdk.is_initialized());
+
+        let init_state = sdk.init_state.unwrap();
+        assert_eq!(
+            init_state.authenticator,
+            Some(Authenticator::Custom(authenticator))
+        );
+    }
+
+    #[test]
+    fn update_credentials() {
+        #[derive(Debug)]
+        struct StaticTokenProvider(std::result::Result<&'static str, u16>);
+        impl TokenProvider for StaticTokenProvider {
+            fn get_access_token(&self) -> crate::network::NetworkResult<String> {
+                self.0.map(str::to_string).map_err(|status_code| {
+                    crate::NetworkError::TokenRequestRejected {
+                        status_code,
+                        message: "invalid credentials".to_string(),
+                    }
+                })
+            }
+        }
+
+        let mut sdk = AppConfiguration::new();
+        let err = sdk
+            .update_token_provider(Box::new(StaticTokenProvider(Ok("token"))))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "init must be called before updating the credentials"
+        );
+
+        sdk.init("us-south", "guid-1", "apikey-1").unwrap();
+        // The clients created by set_context() from now on use the new credentials
+        sdk.update_token_provider(Box::new(StaticTokenProvider(Ok("token"))))
+            .unwrap();
+        let authenticator = sdk.init_state.as_ref().unwrap().authenticator.clone();
+        assert!(matches!(authenticator, Some(Authenticator::Custom(_))));
+
+        // Rejected ones are not stored
+        sdk.update_token_provider(Box::new(StaticTokenProvider(Err(400))))
+            .unwrap_err();
+        assert_eq!(sdk.init_state.unwrap().authenticator, authenticator);
+    }
+
+    #[test]
+    fn init_with_trusted_profile_requires_profile_id() {
+        let mut sdk = AppConfiguration::new();
+        let err = sdk
+            .init_with_trusted_profile("us-south", "guid-1", " ", None)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "profile_id is required");
     }
 
     #[test]
@@ -468,6 +1279,25 @@ mod tests {
             persistent_cache_directory: Some(PathBuf::from("/tmp/cache")),
             bootstrap_file: Some(PathBuf::from("/tmp/bootstrap.json")),
             live_config_update_enabled: true,
+            refresh_coalescing_window: Duration::ZERO,
+            value_interpolation_enabled: false,
+            validation_mode: ValidationMode::Strict,
+            instance_label: None,
+            metering: MeteringOptions::default(),
+            rollout_salt: None,
+            expected_schema_version: None,
+            fallback_regions: Vec::new(),
+            configuration_guards: Vec::new(),
+            thread_options: ThreadOptions::default(),
+            initial_fetch_timeout: None,
+            polling_interval: None,
+            unused_feature_window: None,
+            deprecated_features: Vec::new(),
+            cache_cipher: None,
+            shared_cache: None,
+            max_fetches_per_hour: None,
+            persist_bootstrap_updates: true,
+            socket_options: SocketOptions::default(),
         };
 
         match build_offline_mode(&options, "environment", "collection") {
@@ -475,10 +1305,12 @@ mod tests {
                 path,
                 environment_id,
                 collection_id,
+                persist_updates,
             } => {
                 assert_eq!(path, PathBuf::from("/tmp/bootstrap.json"));
                 assert_eq!(environment_id, "environment");
                 assert_eq!(collection_id, "collection");
+                assert!(persist_updates);
             }
             other => panic!("unexpected offline mode: {:?}", other),
         }
@@ -558,4 +1390,91 @@ mod tests {
         assert!(state.resolved_urls.service_no_ssl);
         assert_eq!(state.resolved_urls.service_port_override, Some(8080));
     }
+
+    // ── add_custom_header ─────────────────────────────────────────────────────
+
+    #[test]
+    fn custom_headers_are_baked_into_resolved_urls() {
+        let mut sdk = AppConfiguration::new();
+        sdk.add_custom_header("X-Internal-Routing", "blue").unwrap();
+        sdk.init("us-south", "guid-1", "apikey-1").unwrap();
+
+        let state = sdk.init_state.unwrap();
+        assert_eq!(
+            state.resolved_urls.custom_headers,
+            vec![("X-Internal-Routing".to_string(), "blue".to_string())]
+        );
+    }
+
+    #[test]
+    fn invalid_custom_header_is_rejected() {
+        let mut sdk = AppConfiguration::new();
+        let err = sdk.add_custom_header("X Routing", "blue").unwrap_err();
+        assert_eq!(err.to_string(), "invalid custom header name 'X Routing'");
+
+        let err = sdk.add_custom_header("X-Routing", "blue\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid value for custom header 'X-Routing'"
+        );
+        assert!(sdk.custom_headers.is_empty());
+    }
+
+    // ── override_iam_url ──────────────────────────────────────────────────────
+
+    /// An explicit IAM URL is used even when no service URL override is set.
+    #[test]
+    fn override_iam_url_without_service_override() {
+        let mut sdk = AppConfiguration::new();
+        sdk.use_private_endpoint(true);
+        sdk.override_iam_url("https://iam.vpe.example.com/identity/token");
+        sdk.init("us-south", "guid-1", "apikey-1").unwrap();
+
+        let state = sdk.init_state.unwrap();
+        assert!(state.use_private_endpoint);
+        assert!(state.resolved_urls.service_host_override.is_none());
+        assert_eq!(
+            state.resolved_urls.token_url_override.as_deref(),
+            Some("https://iam.vpe.example.com/identity/token")
+        );
+    }
+
+    /// An explicit IAM URL wins over the one derived from the service URL override.
+    #[test]
+    fn override_iam_url_wins_over_derived_token_url() {
+        let mut sdk = AppConfiguration::new();
+        sdk.override_service_url("https://dndev.apprapp.test.cloud.ibm.com");
+        sdk.override_iam_url("https://iam.custom.example.com/identity/token");
+        sdk.init("us-south", "guid-1", "apikey-1").unwrap();
+
+        let state = sdk.init_state.unwrap();
+        assert_eq!(
+            state.resolved_urls.service_host_override.as_deref(),
+            Some("dndev.apprapp.test.cloud.ibm.com")
+        );
+        assert_eq!(
+            state.resolved_urls.token_url_override.as_deref(),
+            Some("https://iam.custom.example.com/identity/token")
+        );
+    }
+
+    #[test]
+    fn registered_defaults_replace_missing_features() {
+        let mut sdk = AppConfiguration::new();
+        sdk.register_default("new_checkout", Value::Boolean(true));
+        let configuration = Configuration::default();
+
+        let feature = sdk
+            .get_feature_with_settings("new_checkout", &|id| configuration.get_feature(id))
+            .unwrap();
+        assert!(crate::Feature::is_enabled(&feature).unwrap());
+        assert!(
+            sdk.get_feature_with_settings("other", &|id| configuration.get_feature(id))
+                .is_err()
+        );
+        assert_eq!(
+            sdk.get_default_fallback_counts(),
+            HashMap::from([("new_checkout".to_string(), 1)])
+        );
+    }
 }
@@ -11,11 +11,22 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
-use crate::Result;
+use crate::client::evaluation_session::EvaluationSession;
 use crate::client::feature_proxy::FeatureProxy;
+use crate::client::pinned_configuration::PinnedConfiguration;
+use crate::client::prewarm::{PrewarmReport, prewarm};
 use crate::client::property_proxy::PropertyProxy;
-use crate::models::{FeatureSnapshot, PropertySnapshot, SecretPropertySnapshot};
+use crate::entity::Entity;
+use crate::errors::DeserializationError;
+use crate::errors::Error;
+use crate::models::{
+    Configuration, FeatureSnapshot, PropertySnapshot, SecretPropertySnapshot, SegmentSnapshot,
+};
 use crate::network::live_configuration::CurrentModeOfflineReason;
+use crate::utils::MutexExt;
+use crate::{NetworkErrorCategory, Property, Ready, Result, Value};
+use serde::de::DeserializeOwned;
+use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 /// Identifies a configuration
 #[derive(Debug, Clone)]
@@ -29,12 +40,56 @@ pub struct ConfigurationId {
 }
 
 impl ConfigurationId {
-    pub fn new(guid: String, environment_id: String, collection_id: String) -> Self {
-        Self {
+    /// Checks that none of the IDs is empty, and that `guid` is made of ASCII
+    /// letters, digits and dashes, like the GUIDs of the service instances.
+    pub fn new(guid: String, environment_id: String, collection_id: String) -> Result<Self> {
+        let invalid = |field, value: &str, reason| {
+            Err(Error::InvalidConfigurationId {
+                field,
+                value: value.to_string(),
+                reason,
+            })
+        };
+        for (field, value) in [
+            ("guid", &guid),
+            ("environment_id", &environment_id),
+            ("collection_id", &collection_id),
+        ] {
+            if value.trim().is_empty() {
+                return invalid(field, value, "it must not be empty");
+            }
+        }
+        if !guid.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return invalid(
+                "guid",
+                &guid,
+                "it must only contain ASCII letters, digits and dashes",
+            );
+        }
+        Ok(Self {
             guid,
             environment_id,
             collection_id,
-        }
+        })
+    }
+
+    /// Reads the IDs from the `GUID`, `ENVIRONMENT_ID` and `COLLECTION_ID`
+    /// environment variables, the same ones as the examples.
+    pub fn from_env() -> Result<Self> {
+        let var = |name: &str| {
+            std::env::var(name).map_err(|_| Error::Other(format!("{name} must be set")))
+        };
+        Self::new(var("GUID")?, var("ENVIRONMENT_ID")?, var("COLLECTION_ID")?)
+    }
+}
+
+impl std::fmt::Display for ConfigurationId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "instance '{}', environment '{}' and collection '{}'",
+            self.guid, self.environment_id, self.collection_id
+        )
     }
 }
 
@@ -65,6 +120,180 @@ pub trait ConfigurationProvider {
     /// will be received from the server
     fn get_property(&self, property_id: &str) -> Result<PropertySnapshot>;
 
+    /// Returns the value of a property holding a service-wide setting, without
+    /// evaluating it for an entity. Targeting rules are ignored, see
+    /// [`Property::get_static_value`].
+    fn get_property_value(&self, property_id: &str) -> Result<Value> {
+        self.get_property(property_id)?.get_static_value(false)
+    }
+
+    /// Evaluates all the properties for the entity and deserializes them into `T`,
+    /// using the property IDs as field names.
+    ///
+    /// Fields of `T` without a matching property must be optional or have a
+    /// default (`#[serde(default)]`), properties without a matching field are
+    /// ignored unless `T` denies unknown fields.
+    fn get_properties_into<T: DeserializeOwned>(&self, entity: &(impl Entity + ?Sized)) -> Result<T>
+    where
+        Self: Sized,
+    {
+        let properties = self
+            .get_property_ids()?
+            .into_iter()
+            .map(|property_id| {
+                let value = self.get_property(&property_id)?.get_current_value(entity)?;
+                Ok((property_id, value.value.into()))
+            })
+            .collect::<Result<serde_json::Map<_, _>>>()?;
+        serde_json::from_value(properties.into()).map_err(|e| {
+            DeserializationError {
+                string: format!(
+                    "Error deserializing properties into '{}'",
+                    std::any::type_name::<T>()
+                ),
+                source: e.into(),
+            }
+            .into()
+        })
+    }
+
+    /// Retrieves and evaluates the given features for the entity, e.g. during
+    /// application startup to catch misconfigured feature IDs before the first
+    /// request.
+    ///
+    /// The report lists the features that are missing or whose values do not
+    /// match their type, and keeps the snapshots of the others. Any other error,
+    /// e.g. the configuration not being available yet, is returned.
+    fn prewarm(
+        &self,
+        feature_ids: &[&str],
+        entity: &(impl Entity + ?Sized),
+    ) -> Result<PrewarmReport>
+    where
+        Self: Sized,
+    {
+        prewarm(self, feature_ids, entity)
+    }
+
+    /// Returns the list of segments.
+    ///
+    /// The list contains the `id`s that can be used in other methods to return
+    /// concrete segments, like [`get_segment`](ConfigurationProvider::get_segment).
+    fn get_segment_ids(&self) -> Result<Vec<String>> {
+        self.get_shared_configuration()?.get_segment_ids()
+    }
+
+    /// Returns a read-only snapshot of a segment: its name, description, tags
+    /// and rules.
+    fn get_segment(&self, segment_id: &str) -> Result<SegmentSnapshot> {
+        self.get_shared_configuration()?.get_segment(segment_id)
+    }
+
+    /// Returns a copy of the whole configuration currently used for evaluation.
+    ///
+    /// Compare two snapshots with [`Configuration::diff`](crate::Configuration::diff).
+    fn get_configuration_snapshot(&self) -> Result<Configuration>;
+
+    /// Like [`get_configuration_snapshot`](ConfigurationProvider::get_configuration_snapshot),
+    /// sharing the configuration instead of copying it where the client allows.
+    #[doc(hidden)]
+    fn get_shared_configuration(&self) -> Result<Arc<Configuration>> {
+        self.get_configuration_snapshot().map(Arc::new)
+    }
+
+    /// Returns the display name of the environment the configuration belongs
+    /// to, e.g. to check that the client uses the intended environment. `None`
+    /// if the configuration does not include it.
+    fn environment_name(&self) -> Result<Option<String>> {
+        Ok(self.get_shared_configuration()?.environment_name.clone())
+    }
+
+    /// Pins the configuration currently used for evaluation.
+    ///
+    /// Features and properties retrieved through the returned guard are
+    /// evaluated against this configuration until the guard is dropped, even
+    /// if updates are received from the server in the meantime.
+    fn pin_configuration(&self) -> Result<PinnedConfiguration<'_, Self>>
+    where
+        Self: Sized,
+    {
+        Ok(PinnedConfiguration::new(
+            self,
+            self.get_shared_configuration()?,
+        ))
+    }
+
+    /// Starts a session evaluating features and properties for the entity
+    /// against the current configuration, see [`EvaluationSession`]. Sessions
+    /// share the configuration with the client, starting one does not copy it.
+    fn evaluation_session(
+        &self,
+        entity: &(impl Entity + ?Sized),
+    ) -> Result<EvaluationSession<'_, Self>>
+    where
+        Self: Sized,
+    {
+        Ok(EvaluationSession::new(self.pin_configuration()?, entity))
+    }
+
+    /// Returns a snapshot for a feature of the given configuration, applying
+    /// the same settings as [`get_feature`](ConfigurationProvider::get_feature).
+    #[doc(hidden)]
+    fn get_feature_from(
+        &self,
+        configuration: &Configuration,
+        feature_id: &str,
+    ) -> Result<FeatureSnapshot> {
+        configuration.get_feature(feature_id)
+    }
+
+    /// Returns a snapshot for a property of the given configuration, applying
+    /// the same settings as [`get_property`](ConfigurationProvider::get_property).
+    #[doc(hidden)]
+    fn get_property_from(
+        &self,
+        configuration: &Configuration,
+        property_id: &str,
+    ) -> Result<PropertySnapshot> {
+        configuration.get_property(property_id)
+    }
+
+    /// Returns the feature whose display name is `name`, as shown in the App
+    /// Configuration dashboard.
+    ///
+    /// Fails with [`Error::NameNotFound`] if no feature has that name, and with
+    /// [`Error::AmbiguousName`] if several do.
+    fn find_feature_by_name(&self, name: &str) -> Result<FeatureSnapshot> {
+        let configuration = self.get_shared_configuration()?;
+        let names = configuration
+            .features
+            .iter()
+            .map(|(id, (feature, _))| (&*feature.name, id.as_str()));
+        let feature_id = find_by_name(names, "feature", name)?;
+        self.get_feature_from(&configuration, feature_id)
+    }
+
+    /// Returns the property whose display name is `name`, as shown in the App
+    /// Configuration dashboard.
+    ///
+    /// Fails with [`Error::NameNotFound`] if no property has that name, and with
+    /// [`Error::AmbiguousName`] if several do.
+    fn find_property_by_name(&self, name: &str) -> Result<PropertySnapshot> {
+        let configuration = self.get_shared_configuration()?;
+        let names = configuration
+            .properties
+            .iter()
+            .map(|(id, (property, _))| (property.name.as_str(), id.as_str()));
+        let property_id = find_by_name(names, "property", name)?;
+        self.get_property_from(&configuration, property_id)
+    }
+
+    /// Returns whether the entity belongs to the given segment, regardless of
+    /// any feature or property targeting it.
+    fn entity_matches_segment(&self, segment_id: &str, entity: &dyn Entity) -> Result<bool> {
+        self.get_segment(segment_id)?.matches(entity)
+    }
+
     /// For remote configurations, it returns whether it's connected to the
     /// remote or not
     fn is_online(&self) -> Result<bool>;
@@ -85,6 +314,96 @@ pub trait ConfigurationProvider {
         Ok(None)
     }
 
+    /// For remote configurations: the most recent error connecting to the
+    /// server or processing its responses, which tells why
+    /// [`is_online`](ConfigurationProvider::is_online) returns `false`.
+    ///
+    /// The error is kept after the client recovers.
+    fn last_error(&self) -> Result<Option<SdkErrorSnapshot>> {
+        Ok(None)
+    }
+
+    /// For remote configurations: counters of the connection to the server and
+    /// of the metering, e.g. to export them as metrics.
+    fn sdk_stats(&self) -> Result<Option<SdkStats>> {
+        Ok(None)
+    }
+
+    /// Returns a future resolving once the first configuration is loaded, from
+    /// the server or from the offline mode, e.g. to accept traffic only once the
+    /// flags can be evaluated.
+    ///
+    /// Local configurations are always ready.
+    fn ready(&self) -> Ready {
+        Ready::resolved()
+    }
+
+    /// For remote configurations: the configuration served, downloaded now if
+    /// it was requested from the server more than `max_age` ago. `None` when the
+    /// changes are notified through a connected websocket, in safe mode and for
+    /// local configurations.
+    ///
+    /// Fails if the configuration cannot be downloaded, or is rejected.
+    fn fresh_configuration(
+        &self,
+        _max_age: std::time::Duration,
+    ) -> Result<Option<Arc<Configuration>>> {
+        Ok(None)
+    }
+
+    /// Like [`get_feature`](ConfigurationProvider::get_feature), with a
+    /// configuration received from the server at most `max_age` ago. If the
+    /// configuration served is older, blocks while it is downloaded, see
+    /// [`fresh_configuration`](ConfigurationProvider::fresh_configuration).
+    ///
+    /// Updates are usually received within seconds, this is meant for the few
+    /// features that must not be evaluated with a configuration missing one,
+    /// e.g. those of prices.
+    fn get_feature_fresh(
+        &self,
+        feature_id: &str,
+        max_age: std::time::Duration,
+    ) -> Result<FeatureSnapshot> {
+        match self.fresh_configuration(max_age)? {
+            Some(configuration) => self.get_feature_from(&configuration, feature_id),
+            None => self.get_feature(feature_id),
+        }
+    }
+
+    /// For remote configurations: the most recent downloads of the configuration,
+    /// oldest first, with the request ids to share with IBM support.
+    fn sync_history(&self) -> Result<Vec<SyncRecord>> {
+        Ok(Vec::new())
+    }
+
+    /// For remote configurations: the features of the configuration that were
+    /// not evaluated recently, and the features requested that the
+    /// configuration does not have, e.g. to clean up dead flags.
+    ///
+    /// The period covered is set with
+    /// [`LiveConfigurationOptions::unused_feature_window`](crate::LiveConfigurationOptions::unused_feature_window).
+    fn unused_features(&self) -> Result<Option<UnusedFeatures>> {
+        Ok(None)
+    }
+
+    /// For remote configurations: the features planned for removal that were
+    /// evaluated, sorted by ID, to find the code to update before they are
+    /// deleted.
+    ///
+    /// The features planned for removal are those listed in
+    /// [`LiveConfigurationOptions::deprecated_features`](crate::LiveConfigurationOptions::deprecated_features).
+    /// A warning is logged the first time each of them is evaluated, with the
+    /// location of the call.
+    fn deprecated_flags_in_use(&self) -> Result<Vec<DeprecatedFlagUsage>> {
+        Ok(Vec::new())
+    }
+
+    /// For remote configurations: passes `exposure` to the listeners registered
+    /// with [`RuntimeEventEmitter::on_exposure`].
+    fn record_exposure(&self, _exposure: Exposure) -> Result<()> {
+        Ok(())
+    }
+
     fn add_runtime_event_listener(&self, _listener: RuntimeEventListener) -> Result<()> {
         Ok(())
     }
@@ -105,6 +424,131 @@ pub struct RuntimeStatus {
     pub offline_reason: Option<CurrentModeOfflineReason>,
 }
 
+/// An error of the thread keeping the configuration in sync with the server,
+/// see [`ConfigurationProvider::last_error`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SdkErrorSnapshot {
+    pub message: String,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Errors in a row, this one excluded, since the last configuration
+    /// successfully received from the server.
+    pub retry_count: u32,
+    /// What failed, for errors of the requests to the server.
+    pub category: Option<NetworkErrorCategory>,
+    /// Whether the client keeps trying to get new configurations. If not, it
+    /// serves the configuration it has until it is created again.
+    pub will_retry: bool,
+}
+
+/// Counters of the background work of a client, see
+/// [`ConfigurationProvider::sdk_stats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SdkStats {
+    /// Websocket connections established after the first one.
+    pub websocket_reconnects: u64,
+    /// When a configuration was last received from the server.
+    pub last_refresh: Option<chrono::DateTime<chrono::Utc>>,
+    /// Evaluations of features and properties obtained from the client.
+    pub evaluations: u64,
+    /// Evaluations recorded for metering and not sent to the server yet.
+    pub metering_queue_depth: u64,
+    /// Transmissions of metering data that failed.
+    pub metering_flush_failures: u64,
+    /// Evaluations not recorded for metering because the metering thread fell
+    /// behind.
+    pub metering_dropped_evaluations: u64,
+    /// Configuration fetches postponed because
+    /// [`LiveConfigurationOptions::max_fetches_per_hour`](crate::LiveConfigurationOptions::max_fetches_per_hour)
+    /// was reached.
+    pub fetch_budget_exhaustions: u64,
+    /// Latencies of all the downloads of the configuration, failed ones
+    /// included, see [`SyncRecord::latency`].
+    pub fetch_latency: LatencyHistogram,
+}
+
+/// Distribution of the latencies of the downloads of the configuration since
+/// the client was created, see [`SdkStats::fetch_latency`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    /// Upper bound of each bucket, in increasing order, and the downloads not
+    /// slower than it. Counts are cumulative, as in Prometheus histograms: the
+    /// downloads slower than the last bound are only counted in `count`.
+    pub buckets: Vec<(std::time::Duration, u64)>,
+    /// Downloads recorded.
+    pub count: u64,
+    /// Total latency of the downloads recorded.
+    pub sum: std::time::Duration,
+}
+
+#[cfg(feature = "remote")]
+impl LatencyHistogram {
+    /// An empty histogram with the given bucket bounds, in increasing order.
+    pub(crate) fn with_bounds(bounds: &[std::time::Duration]) -> Self {
+        Self {
+            buckets: bounds.iter().map(|bound| (*bound, 0)).collect(),
+            count: 0,
+            sum: std::time::Duration::ZERO,
+        }
+    }
+
+    pub(crate) fn record(&mut self, latency: std::time::Duration) {
+        for (bound, count) in &mut self.buckets {
+            if latency <= *bound {
+                *count += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += latency;
+    }
+}
+
+/// Usage of the features of a client, see
+/// [`ConfigurationProvider::unused_features`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnusedFeatures {
+    /// Start of the period covered: the beginning of the window, or when the
+    /// client was created if it is more recent.
+    pub since: chrono::DateTime<chrono::Utc>,
+    /// Features of the current configuration not evaluated since `since`.
+    pub never_evaluated: Vec<String>,
+    /// Features requested since `since` that the current configuration does
+    /// not have, up to the first 1000 different ones.
+    pub missing: Vec<String>,
+}
+
+/// A deprecated feature still evaluated by the application, see
+/// [`ConfigurationProvider::deprecated_flags_in_use`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeprecatedFlagUsage {
+    pub feature_id: String,
+    /// When the feature was first evaluated.
+    pub first_evaluated: chrono::DateTime<chrono::Utc>,
+    /// Source location, `file:line:column`, of the first evaluation.
+    pub call_site: String,
+    /// Times the feature was evaluated.
+    pub evaluations: u64,
+}
+
+/// A download of the configuration from the server, see
+/// [`ConfigurationProvider::sync_history`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncRecord {
+    /// When the request was sent.
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Time until the configuration was received and parsed, or the request failed.
+    pub latency: std::time::Duration,
+    /// Part of `latency` spent reading and deserializing the response body.
+    /// Both happen together, as the body is parsed while it is received.
+    pub parse_duration: std::time::Duration,
+    /// Size of the configuration received, after decompression.
+    pub payload_bytes: u64,
+    /// Id the server assigned to the request, from the `X-Correlation-Id`,
+    /// `X-Request-Id` or `X-Global-Transaction-Id` response headers.
+    pub request_id: Option<String>,
+    /// Why the download failed, `None` if it succeeded.
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RuntimeMode {
     Online,
@@ -130,9 +574,94 @@ pub struct RuntimeEvent {
 
 pub type RuntimeEventListener = Arc<dyn Fn(RuntimeEvent) + Send + Sync + 'static>;
 
+/// Returns the ID of the only resource named `name`, `resources` giving the
+/// display name and the ID of each resource.
+fn find_by_name<'a>(
+    resources: impl Iterator<Item = (&'a str, &'a str)>,
+    kind: &'static str,
+    name: &str,
+) -> Result<&'a str> {
+    let mut found: Vec<&str> = resources
+        .filter(|(resource_name, _)| *resource_name == name)
+        .map(|(_, id)| id)
+        .collect();
+    match found.len() {
+        0 => Err(Error::NameNotFound {
+            kind,
+            name: name.to_string(),
+        }),
+        1 => Ok(found[0]),
+        _ => {
+            found.sort_unstable();
+            Err(Error::AmbiguousName {
+                kind,
+                name: name.to_string(),
+                ids: found.into_iter().map(str::to_string).collect(),
+            })
+        }
+    }
+}
+
+/// Failures of the background work of a client, which otherwise only show up
+/// as failing or stale evaluations.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SdkError {
+    #[error("Background thread panicked: {0}")]
+    ThreadPanicked(String),
+
+    #[error("Background thread stopped, the client no longer receives updates: {0}")]
+    Defunct(String),
+
+    #[error("Sending metering data failed: {0}")]
+    MeteringPushFailed(String),
+
+    #[error("Refreshing the access token failed: {0}")]
+    TokenRefreshFailed(String),
+
+    #[error("Configuration rejected, the previous one is kept: {0}")]
+    ConfigurationRejected(String),
+
+    #[error("Maximum configuration fetches per hour reached, next fetch in {0:?}")]
+    FetchBudgetExhausted(std::time::Duration),
+}
+
+pub type SdkErrorListener = Arc<dyn Fn(SdkError) + Send + Sync + 'static>;
+
+/// An entity was assigned a variant of an experiment, see
+/// [`AppConfigurationClient::assign_variant`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exposure {
+    pub experiment_id: String,
+    pub entity_id: String,
+    pub variant: String,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+pub type ExposureListener = Arc<dyn Fn(Exposure) + Send + Sync + 'static>;
+
+/// Exposures recorded while no exposure listener is registered, passed to the
+/// first one registered. The oldest are dropped beyond this number.
+#[cfg(feature = "remote")]
+const MAX_PENDING_EXPOSURES: usize = 1_000;
+
+#[derive(Default)]
+struct ExposureListeners {
+    listeners: Vec<ExposureListener>,
+    pending: VecDeque<Exposure>,
+}
+
 #[derive(Default, Clone)]
 pub struct RuntimeEventEmitter {
     listeners: Arc<Mutex<Vec<RuntimeEventListener>>>,
+    error_listeners: Arc<Mutex<Vec<SdkErrorListener>>>,
+    exposures: Arc<Mutex<ExposureListeners>>,
+}
+
+impl std::fmt::Debug for RuntimeEventEmitter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RuntimeEventEmitter")
+            .finish_non_exhaustive()
+    }
 }
 
 impl RuntimeEventEmitter {
@@ -141,18 +670,72 @@ impl RuntimeEventEmitter {
     }
 
     pub fn on(&self, listener: RuntimeEventListener) -> Result<()> {
-        self.listeners.lock()?.push(listener);
+        self.listeners.lock_unpoisoned().push(listener);
         Ok(())
     }
 
     pub fn emit(&self, event: RuntimeEvent) -> Result<()> {
-        let listeners = self.listeners.lock()?.clone();
+        let listeners = self.listeners.lock_unpoisoned().clone();
 
         for listener in listeners {
             listener(event.clone());
         }
         Ok(())
     }
+
+    /// Registers a listener for the failures of the background work, e.g. to
+    /// raise an alert. Listeners are called on the SDK background thread.
+    pub fn on_error(&self, listener: SdkErrorListener) -> Result<()> {
+        self.error_listeners.lock_unpoisoned().push(listener);
+        Ok(())
+    }
+
+    #[cfg(feature = "remote")]
+    pub(crate) fn emit_error(&self, error: SdkError) {
+        let listeners = self.error_listeners.lock_unpoisoned().clone();
+
+        for listener in listeners {
+            listener(error.clone());
+        }
+    }
+
+    /// Registers a listener for the experiment exposures, e.g. to send them to
+    /// an analytics pipeline. Listeners are called on the thread assigning the
+    /// variant.
+    ///
+    /// The exposures recorded before the first listener is registered (at most
+    /// 1000, the latest ones) are passed to it when it is registered.
+    pub fn on_exposure(&self, listener: ExposureListener) -> Result<()> {
+        let pending = {
+            let mut exposures = self.exposures.lock_unpoisoned();
+            exposures.listeners.push(listener.clone());
+            std::mem::take(&mut exposures.pending)
+        };
+
+        for exposure in pending {
+            listener(exposure);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "remote")]
+    pub(crate) fn emit_exposure(&self, exposure: Exposure) {
+        let listeners = {
+            let mut exposures = self.exposures.lock_unpoisoned();
+            if exposures.listeners.is_empty() {
+                if exposures.pending.len() == MAX_PENDING_EXPOSURES {
+                    exposures.pending.pop_front();
+                }
+                exposures.pending.push_back(exposure);
+                return;
+            }
+            exposures.listeners.clone()
+        };
+
+        for listener in listeners {
+            listener(exposure.clone());
+        }
+    }
 }
 
 /// AppConfiguration client for browsing, and evaluating features and properties.
@@ -161,12 +744,14 @@ pub trait AppConfigurationClient: ConfigurationProvider {
     ///
     /// This proxied feature will evaluate entities using the latest information
     /// available if the client implementation supports some kind of live-updates.
+    /// Use [`FeatureProxy::shared`] for a proxy that does not borrow the client.
     fn get_feature_proxy<'a>(&'a self, feature_id: &str) -> Result<FeatureProxy<'a>>;
 
     /// Returns a proxied [`Property`](crate::Property).
     ///
     /// This proxied property will evaluate entities using the latest information
     /// available if the client implementation supports some kind of live-updates.
+    /// Use [`PropertyProxy::shared`] for a proxy that does not borrow the client.
     fn get_property_proxy(&self, property_id: &str) -> Result<PropertyProxy<'_>>;
 
     /// Track a custom event for analytics.
@@ -178,6 +763,51 @@ pub trait AppConfigurationClient: ConfigurationProvider {
     fn track(&self, _event_key: &str, _entity_id: &str) -> Result<()> {
         Ok(())
     }
+
+    /// Assigns `entity` to one of `variants` of the experiment `experiment_id`
+    /// and records the [`Exposure`].
+    ///
+    /// Entities are split evenly among the variants. The assignment is given
+    /// by the murmur3 hash of `<experiment_id>:<entity_id>`, with a seed of its
+    /// own so it does not follow the rollout buckets of the features, and over
+    /// the full 32 bits of the hash. An entity always gets the same variant as
+    /// long as the list does not change.
+    fn assign_variant<'v>(
+        &self,
+        experiment_id: &str,
+        entity: &dyn Entity,
+        variants: &[&'v str],
+    ) -> Result<&'v str> {
+        if variants.is_empty() {
+            return Err(Error::Other(format!(
+                "Experiment '{experiment_id}' has no variants"
+            )));
+        }
+        let entity_id = entity.get_id();
+        let variant = variants[variant_index(experiment_id, &entity_id, variants.len())];
+        self.record_exposure(Exposure {
+            experiment_id: experiment_id.to_string(),
+            entity_id,
+            variant: variant.to_string(),
+            timestamp: chrono::Utc::now(),
+        })?;
+        Ok(variant)
+    }
+}
+
+/// Seed of the hash assigning the variants of experiments, distinct from the
+/// one of the rollouts.
+const VARIANT_HASH_SEED: u32 = 0x6578_7031;
+
+/// Returns the index, below `variant_count`, of the variant of `experiment_id`
+/// assigned to `entity_id`.
+fn variant_index(experiment_id: &str, entity_id: &str, variant_count: usize) -> usize {
+    let data = format!("{experiment_id}:{entity_id}");
+    // Reading from memory cannot fail, there is no I/O error to report.
+    let hash =
+        murmur3::murmur3_32(&mut std::io::Cursor::new(data), VARIANT_HASH_SEED).unwrap_or_default();
+    // Scales the hash to the variants without a modulo bias
+    ((u64::from(hash) * variant_count as u64) >> 32) as usize
 }
 
 impl<T: ConfigurationProvider> AppConfigurationClient for T {
@@ -194,3 +824,133 @@ impl<T: ConfigurationProvider> AppConfigurationClient for T {
         Ok(PropertyProxy::new(self, property_id.to_string()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::collections::HashMap;
+
+    #[rstest]
+    #[case("", "dev", "c1", "Invalid guid '': it must not be empty")]
+    #[case("guid", " ", "c1", "Invalid environment_id ' ': it must not be empty")]
+    #[case("guid", "dev", "", "Invalid collection_id '': it must not be empty")]
+    #[case(
+        "https://us-south.apprapp.cloud.ibm.com",
+        "dev",
+        "c1",
+        "Invalid guid 'https://us-south.apprapp.cloud.ibm.com': it must only contain ASCII letters, digits and dashes"
+    )]
+    fn test_invalid_configuration_id(
+        #[case] guid: &str,
+        #[case] environment_id: &str,
+        #[case] collection_id: &str,
+        #[case] message: &str,
+    ) {
+        let err = ConfigurationId::new(
+            guid.to_string(),
+            environment_id.to_string(),
+            collection_id.to_string(),
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), message);
+    }
+
+    #[test]
+    fn test_configuration_id_display() {
+        let configuration_id = ConfigurationId::new(
+            "12345678-1234-1234-1234-12345678abcd".to_string(),
+            "dev".to_string(),
+            "c1".to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            configuration_id.to_string(),
+            "instance '12345678-1234-1234-1234-12345678abcd', environment 'dev' and collection 'c1'"
+        );
+    }
+
+    #[test]
+    fn test_assign_variant() {
+        let client = crate::AppConfigurationOffline::new(
+            &crate::network::serialization::fixtures::example_configuration_enterprise_path(),
+            "dev",
+            "blue-charge",
+        )
+        .unwrap();
+        let variants = ["control", "treatment"];
+        let mut counts = HashMap::new();
+        for id in 0..1000 {
+            let entity = crate::tests::GenericEntity {
+                id: format!("user-{id}"),
+                attributes: HashMap::new(),
+            };
+            let variant = client
+                .assign_variant("checkout", &entity, &variants)
+                .unwrap();
+            assert_eq!(
+                client
+                    .assign_variant("checkout", &entity, &variants)
+                    .unwrap(),
+                variant
+            );
+            *counts.entry(variant).or_insert(0) += 1;
+        }
+        assert!((400..600).contains(&counts["control"]));
+        assert!((400..600).contains(&counts["treatment"]));
+
+        let err = client
+            .assign_variant("checkout", &crate::entity::tests::TrivialEntity, &[])
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Experiment 'checkout' has no variants");
+    }
+
+    #[test]
+    fn test_variant_index() {
+        // The assignment does not follow the rollout buckets
+        let mut same_as_rollout = 0;
+        for id in 0..1000 {
+            let entity_id = format!("user-{id}");
+            let index = variant_index("checkout", &entity_id, 2);
+            let bucket = FeatureSnapshot::normalized_hash(&format!("{entity_id}:checkout"));
+            if index == (bucket as usize * 2 / 100).min(1) {
+                same_as_rollout += 1;
+            }
+        }
+        assert!((400..600).contains(&same_as_rollout));
+
+        // Every index is reachable with many variants
+        let mut seen = vec![false; 250];
+        for id in 0..10_000 {
+            seen[variant_index("checkout", &format!("user-{id}"), 250)] = true;
+        }
+        assert!(seen.into_iter().all(|seen| seen));
+    }
+
+    #[test]
+    fn test_pending_exposures() {
+        // Before set_context(), the assignments are kept for the first listener
+        let client = crate::AppConfiguration::default();
+        let entity = crate::entity::tests::TrivialEntity;
+        let variant = client
+            .assign_variant("checkout", &entity, &["control", "treatment"])
+            .unwrap();
+
+        let exposures = Arc::new(Mutex::new(Vec::new()));
+        {
+            let exposures = exposures.clone();
+            client
+                .emitter()
+                .on_exposure(Arc::new(move |exposure| {
+                    exposures.lock().unwrap().push(exposure)
+                }))
+                .unwrap();
+        }
+        client
+            .assign_variant("checkout", &entity, &["control", "treatment"])
+            .unwrap();
+        let exposures = exposures.lock().unwrap();
+        assert_eq!(exposures.len(), 2);
+        assert!(exposures.iter().all(|exposure| exposure.variant == variant));
+    }
+}
@@ -12,25 +12,145 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
 
-use crate::errors::Result;
-use crate::models::{FeatureSnapshot, PropertySnapshot, SecretPropertySnapshot};
+use crate::errors::{ConfigurationDataError, Error, Result};
+use crate::models::{
+    Configuration, FeatureSnapshot, PropertySnapshot, SecretPropertySnapshot, SegmentSnapshot,
+};
 
-use crate::metering::{MeteringClientHttp, MeteringRecorder, start_metering};
-use crate::network::live_configuration::{LiveConfiguration, LiveConfigurationImpl};
-use crate::network::{ServiceAddress, TokenProvider};
+use crate::client::DeprecatedFeatures;
+use crate::metering::{
+    MeteringClient, MeteringClientHttp, MeteringMetadata, MeteringRecorder, start_metering,
+};
+use crate::network::http_client::ServerClient;
+use crate::network::live_configuration::{
+    Applied, ConfigurationStore, CurrentMode, LiveConfiguration, LiveConfigurationImpl,
+    LiveConfigurationOptions,
+};
+use crate::network::{
+    NetworkError, NetworkResult, OutgoingRequest, RequestAuthenticator, ServiceAddress,
+    SharedServerClient, SyncHistory, check_credentials,
+};
+use crate::utils::{MutexExt, ReadySignal, RwLockExt};
 use crate::{
-    ConfigurationProvider, OfflineMode, RuntimeEvent, RuntimeEventEmitter, ServerClientImpl,
+    ConfigurationProvider, OfflineMode, Ready, RuntimeEvent, RuntimeEventEmitter, RuntimeEventKind,
+    RuntimeMode, ServerClientImpl,
 };
 
-use super::{ConfigurationId, RuntimeStatus};
+use super::{
+    ConfigurationId, DeprecatedFlagUsage, Exposure, RuntimeStatus, SdkError, SdkErrorSnapshot,
+    SdkStats, SyncRecord, UnusedFeatures,
+};
 
 /// AppConfiguration client implementation that connects to a server
 #[derive(Debug)]
 pub(crate) struct AppConfigurationClientHttp<T: LiveConfiguration> {
     live_configuration: T,
     metering: MeteringRecorder,
+    connection_stats: Arc<ConnectionStats>,
+    runtime_emitter: RuntimeEventEmitter,
+    /// Empty for the clients created with a custom [`ServerClient`].
+    sync_history: Arc<SyncHistory>,
+    /// See [`LiveConfigurationOptions::unused_feature_window`].
+    unused_feature_window: Option<std::time::Duration>,
+    /// See [`ConfigurationProvider::deprecated_flags_in_use`].
+    deprecated_features: Arc<DeprecatedFeatures>,
+    /// Downloads configurations on demand, see
+    /// [`ConfigurationProvider::fresh_configuration`].
+    fresh_configuration: Option<FreshConfiguration>,
+    /// Authenticates the requests of the server and metering clients. `None`
+    /// for the clients created with a custom [`ServerClient`].
+    authenticator: Option<Arc<ErrorReportingAuthenticator>>,
+}
+
+/// Counted from the runtime events of the live configuration.
+#[derive(Debug, Default)]
+struct ConnectionStats {
+    connections: AtomicU64,
+    /// Milliseconds since the epoch, `0` until the first configuration is received.
+    last_refresh: AtomicI64,
+    /// Set with the first configuration received, or preloaded.
+    ready: Arc<ReadySignal>,
+    /// See [`SdkStats::fetch_budget_exhaustions`].
+    fetch_budget_exhaustions: AtomicU64,
+}
+
+impl ConnectionStats {
+    fn last_refresh(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        let last_refresh = self.last_refresh.load(Ordering::Relaxed);
+        (last_refresh > 0)
+            .then(|| chrono::DateTime::from_timestamp_millis(last_refresh))
+            .flatten()
+    }
+
+    fn record(&self, event: &RuntimeEvent) {
+        match event.kind {
+            RuntimeEventKind::Connected => {
+                self.connections.fetch_add(1, Ordering::Relaxed);
+            }
+            RuntimeEventKind::RefreshSuccess => {
+                self.last_refresh
+                    .store(chrono::Utc::now().timestamp_millis(), Ordering::Relaxed);
+                self.ready.set();
+            }
+            _ => {}
+        }
+    }
+
+    fn record_error(&self, error: &SdkError) {
+        if let SdkError::FetchBudgetExhausted(_) = error {
+            self.fetch_budget_exhaustions
+                .fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Configurations downloaded on the threads of the callers of
+/// [`ConfigurationProvider::fresh_configuration`], with the server client of
+/// the update thread. They are verified and served like those of the update
+/// thread.
+#[derive(Debug)]
+struct FreshConfiguration {
+    server_client: SharedServerClient,
+    store: ConfigurationStore,
+    /// Whether the update thread polls the server instead of being notified
+    /// of the changes.
+    polling: bool,
+    /// Held while downloading, so concurrent callers wait for the same download.
+    download: Mutex<()>,
+}
+
+impl FreshConfiguration {
+    /// Returns the configuration served if it was requested at most `max_age`
+    /// ago, downloads a new one otherwise, if the fetch budget allows it.
+    fn get(&self, max_age: Duration) -> Result<Arc<Configuration>> {
+        let _download = self.download.lock_unpoisoned();
+        if let Some((requested_at, configuration)) = self.store.latest()
+            && requested_at.elapsed() <= max_age
+        {
+            return Ok(configuration);
+        }
+        if let Some(retry_after) = self.store.fetch_budget().take() {
+            return Err(NetworkError::FetchBudgetExhausted { retry_after }.into());
+        }
+        let requested_at = Instant::now();
+        let fetched_at = chrono::Utc::now();
+        let configuration_json = self
+            .server_client
+            .get_configuration_json(self.store.configuration_id())?;
+        match self
+            .store
+            .apply(configuration_json, requested_at, Some(fetched_at))?
+        {
+            Applied::Accepted(configuration) => Ok(configuration),
+            Applied::Rejected(reason) => Err(Error::Other(format!(
+                "Rejected the configuration received from the server: {reason}"
+            ))),
+        }
+    }
 }
 
 impl AppConfigurationClientHttp<LiveConfigurationImpl> {
@@ -42,39 +162,240 @@ impl AppConfigurationClientHttp<LiveConfigurationImpl> {
     /// # Arguments
     ///
     /// * `service_address` - The address of the server to connect to.
-    /// * `token_provider` - An object that can provide the tokens required by the server.
+    /// * `authenticator` - Adds the credentials required by the server to the requests.
     /// * `configuration_id` - Identifies the App Configuration configuration to use.
     /// * `offline_mode` - Behavior when the configuration might not be synced with the server
+    /// * `runtime_emitter` - Receives the runtime events of the background thread.
+    /// * `options` - Tuning of the background threads.
     pub fn new(
         service_address: ServiceAddress,
-        token_provider: Box<dyn TokenProvider>,
+        authenticator: Arc<dyn RequestAuthenticator>,
         configuration_id: ConfigurationId,
         offline_mode: OfflineMode,
         runtime_emitter: RuntimeEventEmitter,
+        options: LiveConfigurationOptions,
     ) -> Result<Self> {
-        let token_provider = Arc::new(token_provider);
-        let server_client = ServerClientImpl::new(service_address.clone(), token_provider.clone())?;
-        let metering_client = MeteringClientHttp::new(service_address, token_provider);
+        let authenticator = Arc::new(ErrorReportingAuthenticator {
+            authenticator: RwLock::new(authenticator),
+            error_emitter: runtime_emitter.clone(),
+        });
+        let server_client = ServerClientImpl::new(service_address.clone(), authenticator.clone())?
+            .with_fallback_addresses(options.fallback_service_addresses.clone())
+            .with_socket_options(options.socket_options.clone());
+        let sync_history = server_client.sync_history();
+        let metering_client = MeteringClientHttp::new(service_address, authenticator.clone());
+        let client = Self::new_with_server_client(
+            SharedServerClient::new(server_client),
+            metering_client,
+            configuration_id,
+            offline_mode,
+            runtime_emitter,
+            options,
+        )?;
+        Ok(Self {
+            sync_history,
+            authenticator: Some(authenticator),
+            ..client
+        })
+    }
 
+    /// Creates a new [`crate::AppConfigurationClient`] that downloads the configuration
+    /// through `server_client`, and sends the metering data to `metering_client`.
+    pub(crate) fn new_with_server_client(
+        server_client: SharedServerClient,
+        metering_client: impl MeteringClient,
+        configuration_id: ConfigurationId,
+        offline_mode: OfflineMode,
+        runtime_emitter: RuntimeEventEmitter,
+        options: LiveConfigurationOptions,
+    ) -> Result<Self> {
+        options.thread_options.validate()?;
         let metering = start_metering(
             configuration_id.clone(),
             std::time::Duration::from_secs(10 * 60),
             metering_client,
+            MeteringMetadata::new(options.instance_label.clone(), options.metering.send_host),
+            options.metering.clone(),
+            &options.thread_options,
+            runtime_emitter.clone(),
         );
 
         // Pre-seed the forwarding listener BEFORE the background thread starts.
         // This guarantees Connected + first RefreshSuccess are never missed.
-        let bridge = Arc::new(move |event: RuntimeEvent| {
-            let _ = runtime_emitter.emit(event);
-        });
+        let error_emitter = runtime_emitter.clone();
+        let exposure_emitter = runtime_emitter.clone();
+        let connection_stats = Arc::new(ConnectionStats::default());
+        {
+            let connection_stats = connection_stats.clone();
+            runtime_emitter.on_error(Arc::new(move |error| {
+                connection_stats.record_error(&error);
+            }))?;
+        }
+        let bridge = {
+            let connection_stats = connection_stats.clone();
+            let metering = metering.sender.clone();
+            Arc::new(move |event: RuntimeEvent| {
+                if event.status.mode == Some(RuntimeMode::Defunct) {
+                    // A defunct client no longer reports to the server
+                    metering.stop();
+                }
+                connection_stats.record(&event);
+                let _ = runtime_emitter.emit(event);
+            })
+        };
 
-        let live_configuration =
-            LiveConfigurationImpl::new(offline_mode, server_client, configuration_id, vec![bridge]);
+        let initial_fetch_timeout = options.initial_fetch_timeout;
+        let unused_feature_window = options.unused_feature_window;
+        let deprecated_features =
+            Arc::new(DeprecatedFeatures::new(options.deprecated_features.clone()));
+        let polling = options.polling_interval.is_some();
+        let live_configuration = LiveConfigurationImpl::new(
+            offline_mode,
+            server_client.clone(),
+            configuration_id,
+            vec![bridge],
+            options,
+            error_emitter,
+        );
+        let fresh_configuration =
+            live_configuration
+                .configuration_store()
+                .map(|store| FreshConfiguration {
+                    server_client,
+                    store,
+                    polling,
+                    download: Mutex::default(),
+                });
+        if live_configuration.get_configuration_snapshot().is_ok() {
+            // From a persistent cache, a bootstrap file or fallback data
+            connection_stats.ready.set();
+        }
+        if let Some(timeout) = initial_fetch_timeout {
+            live_configuration.wait_for_initial_configuration(timeout)?;
+        }
         Ok(Self {
             live_configuration,
             metering,
+            connection_stats,
+            runtime_emitter: exposure_emitter,
+            sync_history: Arc::default(),
+            unused_feature_window,
+            deprecated_features,
+            fresh_configuration,
+            authenticator: None,
+        })
+    }
+
+    /// Downloads the configuration from the server once, without opening a
+    /// websocket nor starting any thread.
+    ///
+    /// The returned [`Configuration`] evaluates features and properties like a
+    /// client does, but it is never updated and evaluations are not metered.
+    /// Meant for batch jobs and command line tools that only read the
+    /// configuration at startup.
+    pub fn fetch_once(
+        service_address: ServiceAddress,
+        authenticator: Arc<dyn RequestAuthenticator>,
+        configuration_id: &ConfigurationId,
+    ) -> Result<Configuration> {
+        let server_client = ServerClientImpl::new(service_address, authenticator)?;
+        Ok(server_client.get_configuration(configuration_id)?)
+    }
+}
+
+impl<T: LiveConfiguration> AppConfigurationClientHttp<T> {
+    /// Attaches the metering to `feature`, or records that `feature_id` was
+    /// requested and is missing from the configuration.
+    ///
+    /// The evaluations of deprecated features are reported too.
+    fn metered_feature(
+        &self,
+        feature: Result<FeatureSnapshot>,
+        feature_id: &str,
+    ) -> Result<FeatureSnapshot> {
+        match feature {
+            Ok(mut feature) => {
+                if self.deprecated_features.is_deprecated(feature_id) {
+                    feature.deprecation = Some(self.deprecated_features.clone());
+                }
+                feature.metering = Some(self.metering.sender.clone());
+                Ok(feature)
+            }
+            Err(e @ Error::ConfigurationDataError(ConfigurationDataError::FeatureNotFound(_))) => {
+                self.metering
+                    .sender
+                    .stats
+                    .feature_usage
+                    .record_missing(feature_id);
+                Err(e)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// See [`LiveConfiguration::enter_safe_mode`].
+    pub fn enter_safe_mode(&self, fallback: OfflineMode) -> Result<()> {
+        Ok(self.live_configuration.enter_safe_mode(fallback)?)
+    }
+
+    /// See [`LiveConfiguration::exit_safe_mode`].
+    pub fn exit_safe_mode(&self) -> Result<()> {
+        Ok(self.live_configuration.exit_safe_mode()?)
+    }
+
+    /// Replaces the credentials of the configuration requests, the websocket
+    /// handshakes and the metering pushes sent from now on. The websocket
+    /// already open is kept.
+    ///
+    /// The previous credentials are kept if `authenticator` fails to
+    /// authenticate a request, e.g. if IAM rejects its API key.
+    pub fn update_authenticator(&self, authenticator: Arc<dyn RequestAuthenticator>) -> Result<()> {
+        let current = self.authenticator.as_ref().ok_or_else(|| {
+            Error::Other(
+                "The credentials of a client with a custom ServerClient cannot be updated"
+                    .to_string(),
+            )
+        })?;
+        check_credentials(authenticator.as_ref())?;
+        *current.authenticator.write_unpoisoned() = authenticator;
+        log::info!("Credentials of the client updated");
+        Ok(())
+    }
+}
+
+/// Sends the failures to authenticate a request (e.g. to get an access token)
+/// to the error listeners of the client.
+///
+/// The authenticator can be replaced while the client runs, to rotate the
+/// credentials, see [`AppConfigurationClientHttp::update_authenticator`].
+struct ErrorReportingAuthenticator {
+    authenticator: RwLock<Arc<dyn RequestAuthenticator>>,
+    error_emitter: RuntimeEventEmitter,
+}
+
+impl std::fmt::Debug for ErrorReportingAuthenticator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ErrorReportingAuthenticator")
+            .field("authenticator", &self.authenticator)
+            .finish()
+    }
+}
+
+impl RequestAuthenticator for ErrorReportingAuthenticator {
+    fn authenticate(&self, request: &mut OutgoingRequest<'_>) -> NetworkResult<()> {
+        // Not holding the lock while a token is requested
+        let authenticator = self.authenticator.read_unpoisoned().clone();
+        authenticator.authenticate(request).inspect_err(|e| {
+            self.error_emitter
+                .emit_error(SdkError::TokenRefreshFailed(e.to_string()))
         })
     }
+
+    fn invalidate_credentials(&self) {
+        self.authenticator
+            .read_unpoisoned()
+            .invalidate_credentials()
+    }
 }
 
 impl<T: LiveConfiguration> ConfigurationProvider for AppConfigurationClientHttp<T> {
@@ -83,9 +404,15 @@ impl<T: LiveConfiguration> ConfigurationProvider for AppConfigurationClientHttp<
     }
 
     fn get_feature(&self, feature_id: &str) -> Result<FeatureSnapshot> {
-        let mut feature = self.live_configuration.get_feature(feature_id)?;
-        feature.metering = Some(self.metering.sender.clone());
-        Ok(feature)
+        self.metered_feature(self.live_configuration.get_feature(feature_id), feature_id)
+    }
+
+    fn get_feature_from(
+        &self,
+        configuration: &Configuration,
+        feature_id: &str,
+    ) -> Result<FeatureSnapshot> {
+        self.metered_feature(configuration.get_feature(feature_id), feature_id)
     }
 
     fn get_property_ids(&self) -> Result<Vec<String>> {
@@ -98,6 +425,32 @@ impl<T: LiveConfiguration> ConfigurationProvider for AppConfigurationClientHttp<
         Ok(property)
     }
 
+    fn get_property_from(
+        &self,
+        configuration: &Configuration,
+        property_id: &str,
+    ) -> Result<PropertySnapshot> {
+        let mut property = configuration.get_property(property_id)?;
+        property.metering = Some(self.metering.sender.clone());
+        Ok(property)
+    }
+
+    fn get_segment_ids(&self) -> Result<Vec<String>> {
+        self.live_configuration.get_segment_ids()
+    }
+
+    fn get_segment(&self, segment_id: &str) -> Result<SegmentSnapshot> {
+        self.live_configuration.get_segment(segment_id)
+    }
+
+    fn get_configuration_snapshot(&self) -> Result<Configuration> {
+        self.live_configuration.get_configuration_snapshot()
+    }
+
+    fn get_shared_configuration(&self) -> Result<Arc<Configuration>> {
+        self.live_configuration.get_shared_configuration()
+    }
+
     fn is_online(&self) -> Result<bool> {
         self.live_configuration.is_online()
     }
@@ -118,6 +471,78 @@ impl<T: LiveConfiguration> ConfigurationProvider for AppConfigurationClientHttp<
         self.live_configuration.get_runtime_status()
     }
 
+    fn last_error(&self) -> Result<Option<SdkErrorSnapshot>> {
+        self.live_configuration.last_error()
+    }
+
+    fn record_exposure(&self, exposure: Exposure) -> Result<()> {
+        self.runtime_emitter.emit_exposure(exposure);
+        Ok(())
+    }
+
+    fn sync_history(&self) -> Result<Vec<SyncRecord>> {
+        Ok(self.sync_history.records())
+    }
+
+    fn deprecated_flags_in_use(&self) -> Result<Vec<DeprecatedFlagUsage>> {
+        Ok(self.deprecated_features.in_use())
+    }
+
+    fn unused_features(&self) -> Result<Option<UnusedFeatures>> {
+        let feature_ids = self.live_configuration.get_feature_ids()?;
+        Ok(Some(
+            self.metering
+                .sender
+                .stats
+                .feature_usage
+                .unused_features(feature_ids, self.unused_feature_window),
+        ))
+    }
+
+    fn ready(&self) -> Ready {
+        Ready::new(self.connection_stats.ready.clone())
+    }
+
+    fn fresh_configuration(&self, max_age: Duration) -> Result<Option<Arc<Configuration>>> {
+        let Some(fresh_configuration) = &self.fresh_configuration else {
+            return Ok(None);
+        };
+        if self.live_configuration.is_in_safe_mode() {
+            return Ok(None);
+        }
+        // Online with a websocket, the changes are notified as they happen
+        if !fresh_configuration.polling
+            && matches!(
+                self.live_configuration.get_current_mode(),
+                Ok(CurrentMode::Online)
+            )
+        {
+            return Ok(None);
+        }
+        fresh_configuration.get(max_age).map(Some)
+    }
+
+    fn sdk_stats(&self) -> Result<Option<SdkStats>> {
+        let metering = &self.metering.sender.stats;
+        Ok(Some(SdkStats {
+            websocket_reconnects: self
+                .connection_stats
+                .connections
+                .load(Ordering::Relaxed)
+                .saturating_sub(1),
+            last_refresh: self.connection_stats.last_refresh(),
+            evaluations: metering.evaluations.load(Ordering::Relaxed),
+            metering_queue_depth: metering.pending.load(Ordering::Relaxed),
+            metering_flush_failures: metering.flush_failures.load(Ordering::Relaxed),
+            metering_dropped_evaluations: metering.dropped.load(Ordering::Relaxed),
+            fetch_budget_exhaustions: self
+                .connection_stats
+                .fetch_budget_exhaustions
+                .load(Ordering::Relaxed),
+            fetch_latency: self.sync_history.latency(),
+        }))
+    }
+
     fn clean_up(&mut self) -> Result<()> {
         LiveConfiguration::clean_up(&mut self.live_configuration).map_err(crate::Error::from)
     }
@@ -131,15 +556,19 @@ impl<T: LiveConfiguration> ConfigurationProvider for AppConfigurationClientHttp<
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::metering::metering::tests::start_metering_mock;
-    use crate::models::Configuration;
-    use crate::network::live_configuration::CurrentMode;
+    use std::sync::atomic::AtomicBool;
+
+    use crate::metering::metering::tests::{start_metering_mock, start_metering_mock_with_options};
+    use crate::models::{Configuration, ConfigurationSource, ValidationMode};
+    use crate::network::live_configuration::{ConfigurationGuard, FetchBudget, GuardDecision};
+    use crate::network::serialization::ConfigurationJson;
     use crate::network::serialization::fixtures::{
-        configuration_feature1_enabled, configuration_property1_enabled,
-        example_configuration_enterprise,
+        configuration_feature1_enabled, configuration_json_feature1_enabled,
+        configuration_property1_enabled, example_configuration_enterprise,
+        example_configuration_enterprise_path,
     };
     use crate::utils::ThreadStatus;
-    use crate::{Feature, Property};
+    use crate::{AppConfigurationClient, Feature, MeteringOptions, Property};
     use rstest::rstest;
 
     struct LiveConfigurationMock {
@@ -166,12 +595,24 @@ mod tests {
             self.configuration.get_property(property_id)
         }
 
+        fn get_segment_ids(&self) -> Result<Vec<String>> {
+            self.configuration.get_segment_ids()
+        }
+
+        fn get_segment(&self, segment_id: &str) -> Result<SegmentSnapshot> {
+            self.configuration.get_segment(segment_id)
+        }
+
+        fn get_configuration_snapshot(&self) -> Result<Configuration> {
+            Ok(self.configuration.clone())
+        }
+
         fn is_online(&self) -> Result<bool> {
-            todo!()
+            Ok(true)
         }
 
         fn wait_until_online(&self) -> bool {
-            todo!()
+            true
         }
 
         fn clean_up(&mut self) -> Result<()> {
@@ -186,11 +627,11 @@ mod tests {
         fn get_thread_status(
             &mut self,
         ) -> ThreadStatus<crate::network::live_configuration::Result<()>> {
-            todo!()
+            ThreadStatus::Running
         }
 
         fn get_current_mode(&self) -> crate::network::live_configuration::Result<CurrentMode> {
-            todo!()
+            Ok(CurrentMode::Online)
         }
 
         fn clean_up(&mut self) -> crate::network::live_configuration::Result<()> {
@@ -200,6 +641,56 @@ mod tests {
         fn clean_up_with_cache_clear(&mut self) -> crate::network::live_configuration::Result<()> {
             Ok(())
         }
+
+        fn enter_safe_mode(
+            &self,
+            _fallback: OfflineMode,
+        ) -> crate::network::live_configuration::Result<()> {
+            Err(
+                crate::network::live_configuration::Error::UnrecoverableError(
+                    "The mock has no safe mode".to_string(),
+                ),
+            )
+        }
+
+        fn exit_safe_mode(&self) -> crate::network::live_configuration::Result<()> {
+            Err(
+                crate::network::live_configuration::Error::UnrecoverableError(
+                    "The mock has no safe mode".to_string(),
+                ),
+            )
+        }
+
+        fn is_in_safe_mode(&self) -> bool {
+            false
+        }
+    }
+
+    fn configuration_id() -> ConfigurationId {
+        ConfigurationId::new(
+            "test-guid".to_string(),
+            "test_env_id".to_string(),
+            "test_collection_id".to_string(),
+        )
+        .unwrap()
+    }
+
+    /// A client evaluating `configuration`, with the defaults of the other fields.
+    fn client(
+        configuration: Configuration,
+        metering: MeteringRecorder,
+    ) -> AppConfigurationClientHttp<LiveConfigurationMock> {
+        AppConfigurationClientHttp {
+            live_configuration: LiveConfigurationMock { configuration },
+            metering,
+            connection_stats: Arc::default(),
+            sync_history: Arc::default(),
+            unused_feature_window: None,
+            deprecated_features: Arc::default(),
+            fresh_configuration: None,
+            authenticator: None,
+            runtime_emitter: RuntimeEventEmitter::new(),
+        }
     }
 
     #[rstest]
@@ -207,26 +698,14 @@ mod tests {
         example_configuration_enterprise: Configuration,
         configuration_feature1_enabled: Configuration,
     ) {
-        let (mut client, metering_recv) = {
-            let live_cfg_mock = LiveConfigurationMock {
-                configuration: example_configuration_enterprise,
-            };
-
-            let configuration_id = ConfigurationId::new(
-                "test_guid".to_string(),
-                "test_env_id".to_string(),
-                "test_collection_id".to_string(),
-            );
-            let (metering, metering_recv) = start_metering_mock(configuration_id);
-
-            (
-                AppConfigurationClientHttp {
-                    live_configuration: live_cfg_mock,
-                    metering,
-                },
-                metering_recv,
-            )
-        };
+        let (metering, metering_recv) = start_metering_mock_with_options(
+            configuration_id(),
+            MeteringOptions {
+                send_evaluation_details: true,
+                ..Default::default()
+            },
+        );
+        let mut client = client(example_configuration_enterprise, metering);
 
         let feature = client.get_feature("f1").unwrap();
 
@@ -262,34 +741,313 @@ mod tests {
             // Do not care about the way they are sorted.
             let total_counts: u32 = metering_data.usages.iter().map(|usage| usage.count).sum();
             assert_eq!(total_counts, 3);
+
+            // The second configuration rolls f1 out to no entity
+            let disabled_usage = metering_data
+                .usages
+                .iter()
+                .find(|usage| usage.enabled == Some(false))
+                .unwrap();
+            assert_eq!(disabled_usage.count, 1);
         }
     }
 
+    /// Evaluations returning the disabled value are not sent when excluded.
     #[rstest]
-    fn test_get_property_persistence(
+    fn test_disabled_evaluations_not_recorded(
         example_configuration_enterprise: Configuration,
-        configuration_property1_enabled: Configuration,
+        configuration_feature1_enabled: Configuration,
     ) {
-        let (mut client, metering_recv) = {
-            let live_cfg_mock = LiveConfigurationMock {
-                configuration: example_configuration_enterprise,
-            };
-
-            let configuration_id = ConfigurationId::new(
-                "test_guid".to_string(),
-                "test_env_id".to_string(),
-                "test_collection_id".to_string(),
-            );
-            let (metering, metering_recv) = start_metering_mock(configuration_id);
+        let (metering, metering_recv) = start_metering_mock_with_options(
+            configuration_id(),
+            MeteringOptions {
+                record_disabled_evaluations: false,
+                send_evaluation_details: true,
+                ..Default::default()
+            },
+        );
+        let mut client = client(example_configuration_enterprise, metering);
+
+        let entity = crate::entity::tests::TrivialEntity {};
+        let feature = client.get_feature("f1").unwrap();
+        assert!(feature.get_current_value(&entity).unwrap().is_enabled);
+        // Rolls f1 out to no entity
+        client.live_configuration = LiveConfigurationMock {
+            configuration: configuration_feature1_enabled,
+        };
+        let feature = client.get_feature("f1").unwrap();
+        assert!(!feature.get_current_value(&entity).unwrap().is_enabled);
 
+        let metering_data = metering_recv.recv().unwrap();
+        assert_eq!(metering_data.usages.len(), 1);
+        assert_eq!(metering_data.usages[0].enabled, Some(true));
+        assert_eq!(metering_data.usages[0].count, 1);
+    }
+
+    #[rstest]
+    fn test_value_source_metered(configuration_feature1_enabled: Configuration) {
+        let (metering, metering_recv) = start_metering_mock_with_options(
+            configuration_id(),
+            MeteringOptions {
+                send_evaluation_details: true,
+                ..Default::default()
+            },
+        );
+        let client = client(configuration_feature1_enabled, metering);
+
+        // f1 is enabled, but rolled out to no entity
+        let entity = crate::entity::tests::TrivialEntity {};
+        let feature = client.get_feature("f1").unwrap();
+        assert!(!feature.get_current_value(&entity).unwrap().is_enabled);
+
+        let metering_data = metering_recv.recv().unwrap();
+        assert_eq!(metering_data.usages.len(), 1);
+        assert_eq!(metering_data.usages[0].enabled, Some(false));
+        assert_eq!(
+            metering_data.usages[0].value_source,
+            Some(crate::metering::ValueSource::RolloutExcluded)
+        );
+    }
+
+    #[rstest]
+    fn test_sdk_stats(configuration_feature1_enabled: Configuration) {
+        let (metering, _metering_recv) = start_metering_mock(configuration_id());
+        let client = client(configuration_feature1_enabled, metering);
+        let status = RuntimeStatus {
+            is_connected: true,
+            mode: None,
+            offline_reason: None,
+        };
+        let ready = client.ready();
+        assert!(!ready.is_ready());
+        for kind in [
+            RuntimeEventKind::Connected,
+            RuntimeEventKind::Disconnected,
+            RuntimeEventKind::Connected,
+            RuntimeEventKind::RefreshSuccess,
+        ] {
+            client.connection_stats.record(&RuntimeEvent {
+                kind,
+                status: status.clone(),
+            });
+        }
+        assert!(ready.is_ready());
+        let entity = crate::entity::tests::TrivialEntity {};
+        client
+            .get_feature("f1")
+            .unwrap()
+            .get_current_value(&entity)
+            .unwrap();
+
+        let exposures = Arc::new(std::sync::Mutex::new(Vec::new()));
+        {
+            let exposures = exposures.clone();
+            client
+                .runtime_emitter
+                .on_exposure(Arc::new(move |exposure| {
+                    exposures.lock().unwrap().push(exposure)
+                }))
+                .unwrap();
+        }
+        let variant = client
+            .assign_variant("checkout", &entity, &["control", "treatment"])
+            .unwrap();
+        let exposures = exposures.lock().unwrap();
+        assert_eq!(exposures.len(), 1);
+        assert_eq!(
             (
-                AppConfigurationClientHttp {
-                    live_configuration: live_cfg_mock,
-                    metering,
-                },
-                metering_recv,
+                exposures[0].entity_id.as_str(),
+                exposures[0].variant.as_str()
+            ),
+            ("TrivialId", variant)
+        );
+
+        let stats = client.sdk_stats().unwrap().unwrap();
+        assert_eq!(stats.websocket_reconnects, 1);
+        assert!(stats.last_refresh.is_some());
+        assert_eq!(stats.evaluations, 1);
+        assert_eq!(stats.metering_flush_failures, 0);
+        assert_eq!(stats.fetch_budget_exhaustions, 0);
+
+        client
+            .connection_stats
+            .record_error(&SdkError::FetchBudgetExhausted(Duration::from_secs(60)));
+        let stats = client.sdk_stats().unwrap().unwrap();
+        assert_eq!(stats.fetch_budget_exhaustions, 1);
+    }
+
+    #[rstest]
+    fn test_get_feature_fresh(
+        configuration_feature1_enabled: Configuration,
+        configuration_json_feature1_enabled: ConfigurationJson,
+    ) {
+        struct ServerClientMock {
+            configuration_json: ConfigurationJson,
+            fetches: Arc<AtomicU64>,
+        }
+        impl ServerClient for ServerClientMock {
+            fn get_configuration_json(
+                &self,
+                _configuration_id: &ConfigurationId,
+            ) -> NetworkResult<ConfigurationJson> {
+                self.fetches.fetch_add(1, Ordering::Relaxed);
+                Ok(self.configuration_json.clone())
+            }
+
+            fn get_configuration_monitoring_websocket(
+                &self,
+                _collection: &ConfigurationId,
+            ) -> NetworkResult<Box<dyn crate::network::http_client::WebsocketReader>> {
+                unreachable!()
+            }
+        }
+
+        let fetches = Arc::new(AtomicU64::new(0));
+        let (metering, _metering_recv) = start_metering_mock(configuration_id());
+        let rejecting = Arc::new(AtomicBool::new(false));
+        // The environment and collection of the configuration downloaded
+        let store = ConfigurationStore::new(
+            ConfigurationId::new(
+                "guid".to_string(),
+                "environment_id".to_string(),
+                "collection_id".to_string(),
             )
+            .unwrap(),
+            Arc::default(),
+        )
+        .with_validation_mode(ValidationMode::Strict)
+        .with_fetch_budget(FetchBudget::new(Some(3)))
+        .with_configuration_guards(vec![ConfigurationGuard::new({
+            let rejecting = rejecting.clone();
+            move |_, _| {
+                if rejecting.load(Ordering::Relaxed) {
+                    GuardDecision::Reject("not now".to_string())
+                } else {
+                    GuardDecision::Accept
+                }
+            }
+        })]);
+        let mut client = AppConfigurationClientHttp {
+            fresh_configuration: Some(FreshConfiguration {
+                server_client: SharedServerClient::new(ServerClientMock {
+                    configuration_json: configuration_json_feature1_enabled,
+                    fetches: fetches.clone(),
+                }),
+                store: store.clone(),
+                polling: true,
+                download: Mutex::default(),
+            }),
+            ..client(configuration_feature1_enabled, metering)
         };
+        let max_age = Duration::from_secs(60);
+
+        // Nothing received yet, downloaded on demand and served from now on
+        let feature = client.get_feature_fresh("f1", max_age).unwrap();
+        assert_eq!(feature.source(), ConfigurationSource::Live);
+        assert!(feature.metering.is_some());
+        assert_eq!(fetches.load(Ordering::Relaxed), 1);
+        let (_, served) = store.latest().unwrap();
+
+        // The configuration served is reused while it is recent enough
+        let fresh = client.fresh_configuration(max_age).unwrap().unwrap();
+        assert!(Arc::ptr_eq(&fresh, &served));
+        assert_eq!(fetches.load(Ordering::Relaxed), 1);
+
+        // Downloaded configurations go through the configuration guards
+        rejecting.store(true, Ordering::Relaxed);
+        let error = client.get_feature_fresh("f1", Duration::ZERO).unwrap_err();
+        assert!(error.to_string().contains("not now"), "{error}");
+        assert_eq!(fetches.load(Ordering::Relaxed), 2);
+        assert!(Arc::ptr_eq(&store.latest().unwrap().1, &served));
+
+        // Notified of the changes through the websocket, the configuration served is up to date
+        client.fresh_configuration.as_mut().unwrap().polling = false;
+        let feature = client.get_feature_fresh("f1", Duration::ZERO).unwrap();
+        assert_eq!(feature.source(), ConfigurationSource::Local);
+        assert_eq!(fetches.load(Ordering::Relaxed), 2);
+
+        // Downloads count against the budget of fetches shared with the update thread
+        client.fresh_configuration.as_mut().unwrap().polling = true;
+        rejecting.store(false, Ordering::Relaxed);
+        assert!(client.get_feature_fresh("f1", Duration::ZERO).is_ok());
+        let error = client.get_feature_fresh("f1", Duration::ZERO).unwrap_err();
+        assert!(
+            matches!(
+                error,
+                Error::NetworkError(NetworkError::FetchBudgetExhausted { .. })
+            ),
+            "{error}"
+        );
+        assert_eq!(fetches.load(Ordering::Relaxed), 3);
+    }
+
+    #[rstest]
+    fn test_unused_features(example_configuration_enterprise: Configuration) {
+        let (metering, _metering_recv) = start_metering_mock(configuration_id());
+        let mut client = client(example_configuration_enterprise, metering);
+        let entity = crate::entity::tests::TrivialEntity {};
+        for feature_id in ["f1", "f3"] {
+            client
+                .get_feature(feature_id)
+                .unwrap()
+                .get_current_value(&entity)
+                .unwrap();
+        }
+        // Getting a feature is not using it
+        client.get_feature("f2").unwrap();
+        assert!(client.get_feature("removed-flag").is_err());
+
+        let usage = client.unused_features().unwrap().unwrap();
+        assert_eq!(usage.never_evaluated, ["f2", "f4", "f6"]);
+        assert_eq!(usage.missing, ["removed-flag"]);
+
+        // Nothing happened within an empty window
+        client.unused_feature_window = Some(std::time::Duration::ZERO);
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let usage = client.unused_features().unwrap().unwrap();
+        assert_eq!(usage.never_evaluated, ["f1", "f2", "f3", "f4", "f6"]);
+        assert!(usage.missing.is_empty());
+    }
+
+    #[rstest]
+    fn test_deprecated_flags_in_use(example_configuration_enterprise: Configuration) {
+        let (metering, _metering_recv) = start_metering_mock(configuration_id());
+        let client = AppConfigurationClientHttp {
+            deprecated_features: Arc::new(DeprecatedFeatures::new(["f2".to_string()])),
+            ..client(example_configuration_enterprise, metering)
+        };
+        let entity = crate::entity::tests::TrivialEntity {};
+        client
+            .get_feature("f1")
+            .unwrap()
+            .get_current_value(&entity)
+            .unwrap();
+        // Requesting a deprecated feature is not using it
+        let f2 = client.get_feature("f2").unwrap();
+        client.find_feature_by_name("F2").unwrap();
+        assert!(client.deprecated_flags_in_use().unwrap().is_empty());
+
+        let line = line!() + 1;
+        f2.get_current_value(&entity).unwrap();
+        f2.get_values_for(&[&entity]);
+        let in_use = client.deprecated_flags_in_use().unwrap();
+        assert_eq!(in_use.len(), 1);
+        assert_eq!(in_use[0].feature_id, "f2");
+        assert_eq!(in_use[0].evaluations, 2);
+        assert!(
+            in_use[0]
+                .call_site
+                .starts_with(&format!("{}:{line}:", file!()))
+        );
+    }
+
+    #[rstest]
+    fn test_get_property_persistence(
+        example_configuration_enterprise: Configuration,
+        configuration_property1_enabled: Configuration,
+    ) {
+        let (metering, metering_recv) = start_metering_mock(configuration_id());
+        let mut client = client(example_configuration_enterprise, metering);
 
         let property = client.get_property("p1").unwrap();
 
@@ -327,4 +1085,150 @@ mod tests {
             assert_eq!(total_counts, 3);
         }
     }
+
+    #[test]
+    fn test_token_failures_are_reported() {
+        #[derive(Debug)]
+        struct FailingTokenProvider;
+        impl crate::network::TokenProvider for FailingTokenProvider {
+            fn get_access_token(&self) -> NetworkResult<String> {
+                Err(crate::network::NetworkError::UrlParseError(
+                    "iam".to_string(),
+                ))
+            }
+        }
+
+        let errors = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let error_emitter = RuntimeEventEmitter::new();
+        {
+            let errors = errors.clone();
+            error_emitter
+                .on_error(Arc::new(move |error| errors.lock().unwrap().push(error)))
+                .unwrap();
+        }
+        let authenticator = ErrorReportingAuthenticator {
+            authenticator: RwLock::new(Arc::new(FailingTokenProvider)),
+            error_emitter,
+        };
+
+        let mut url = url::Url::parse("https://ibm.com").unwrap();
+        let mut headers = reqwest::header::HeaderMap::new();
+        let mut request = OutgoingRequest::new("GET", &mut url, &mut headers, &[]);
+        assert!(authenticator.authenticate(&mut request).is_err());
+        assert!(matches!(
+            errors.lock().unwrap().as_slice(),
+            [SdkError::TokenRefreshFailed(_)]
+        ));
+    }
+
+    #[rstest]
+    fn test_fetch_once(
+        example_configuration_enterprise_path: std::path::PathBuf,
+        example_configuration_enterprise: Configuration,
+    ) {
+        #[derive(Debug)]
+        struct MockTokenProvider;
+        impl crate::network::TokenProvider for MockTokenProvider {
+            fn get_access_token(&self) -> NetworkResult<String> {
+                Ok("mocked_token".to_string())
+            }
+        }
+
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/feature/v1/instances/guid/config");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file(example_configuration_enterprise_path.display().to_string());
+        });
+
+        let address = ServiceAddress::new_without_ssl(server.host(), Some(server.port()), None);
+        let configuration_id =
+            ConfigurationId::new("guid".into(), "dev".into(), "blue-charge".into()).unwrap();
+        let configuration = AppConfigurationClientHttp::fetch_once(
+            address,
+            Arc::new(MockTokenProvider),
+            &configuration_id,
+        )
+        .unwrap();
+
+        mock.assert();
+        assert_eq!(
+            configuration,
+            example_configuration_enterprise.with_source(ConfigurationSource::Live)
+        );
+    }
+
+    #[rstest]
+    fn test_update_authenticator(example_configuration_enterprise_path: std::path::PathBuf) {
+        #[derive(Debug)]
+        struct StaticTokenProvider(&'static str);
+        impl crate::network::TokenProvider for StaticTokenProvider {
+            fn get_access_token(&self) -> NetworkResult<String> {
+                Ok(self.0.to_string())
+            }
+        }
+        #[derive(Debug)]
+        struct RejectedTokenProvider;
+        impl crate::network::TokenProvider for RejectedTokenProvider {
+            fn get_access_token(&self) -> NetworkResult<String> {
+                Err(NetworkError::TokenRequestRejected {
+                    status_code: 400,
+                    message: "invalid API key".to_string(),
+                })
+            }
+        }
+
+        let server = httpmock::MockServer::start();
+        let config_mock = |token: &str| {
+            server.mock(|when, then| {
+                when.method(httpmock::Method::GET)
+                    .path("/feature/v1/instances/guid/config")
+                    .header("Authorization", format!("Bearer {token}"));
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body_from_file(example_configuration_enterprise_path.display().to_string());
+            })
+        };
+        let old_mock = config_mock("old");
+        let new_mock = config_mock("new");
+
+        let client = AppConfigurationClientHttp::new(
+            ServiceAddress::new_without_ssl(server.host(), Some(server.port()), None),
+            Arc::new(StaticTokenProvider("old")),
+            ConfigurationId::new("guid".into(), "dev".into(), "blue-charge".into()).unwrap(),
+            OfflineMode::Fail,
+            RuntimeEventEmitter::new(),
+            LiveConfigurationOptions {
+                polling_interval: Some(Duration::from_secs(3600)),
+                initial_fetch_timeout: Some(Duration::from_secs(5)),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        client.fresh_configuration(Duration::ZERO).unwrap().unwrap();
+        assert_eq!(old_mock.calls(), 2);
+        assert_eq!(new_mock.calls(), 0);
+
+        let token_provider: Box<dyn crate::network::TokenProvider> =
+            Box::new(StaticTokenProvider("new"));
+        client
+            .update_authenticator(Arc::new(token_provider))
+            .unwrap();
+        client.fresh_configuration(Duration::ZERO).unwrap().unwrap();
+        assert_eq!(old_mock.calls(), 2);
+        assert_eq!(new_mock.calls(), 1);
+
+        // Credentials rejected by IAM are not used
+        let err = client
+            .update_authenticator(Arc::new(RejectedTokenProvider))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::NetworkError(NetworkError::TokenRequestRejected { .. })
+        ));
+        client.fresh_configuration(Duration::ZERO).unwrap().unwrap();
+        assert_eq!(new_mock.calls(), 2);
+    }
 }
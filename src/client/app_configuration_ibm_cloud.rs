@@ -12,13 +12,29 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 // TODO : Check this implementation of prod and test url.
-use crate::errors::Result;
-use crate::models::{FeatureSnapshot, PropertySnapshot, SecretManager, SecretPropertySnapshot};
-use crate::network::ServiceAddress;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::errors::{BuildError, Error, Result};
+use crate::metering::NoopMeteringClient;
+use crate::models::{
+    Configuration, FeatureSnapshot, PropertySnapshot, SecretManager, SecretPropertySnapshot,
+    SegmentSnapshot,
+};
 use crate::network::live_configuration::LiveConfigurationImpl;
-use crate::{ConfigurationProvider, OfflineMode, RuntimeEventEmitter, TokenProviderImpl};
+use crate::network::{
+    NetworkError, NetworkErrorCategory, RequestAuthenticator, ServerClient, ServiceAddress,
+    ServiceAddressProtocol, SharedServerClient, TokenProvider,
+};
+use crate::{
+    ConfigurationProvider, LiveConfigurationOptions, OfflineMode, Ready, RuntimeEventEmitter,
+    TokenProviderImpl, TrustedProfileTokenProviderImpl,
+};
 
-use super::{ConfigurationId, RuntimeEventListener, RuntimeStatus};
+use super::{
+    ConfigurationId, DeprecatedFlagUsage, Exposure, RuntimeEventListener, RuntimeStatus,
+    SdkErrorSnapshot, SdkStats, SyncRecord, UnusedFeatures,
+};
 use crate::client::app_configuration_http::AppConfigurationClientHttp;
 
 // ── IAM hostname constants ────────────────────────────────────────────────────
@@ -41,12 +57,87 @@ pub struct ResolvedUrls {
     pub service_no_ssl: bool,
     /// Optional port parsed from the service URL override.
     pub service_port_override: Option<u16>,
+    /// Extra HTTP headers (name, value) sent to the service, e.g. for API gateways.
+    pub custom_headers: Vec<(String, String)>,
 }
 
+impl ResolvedUrls {
+    /// Full endpoint override for the App Configuration service, e.g. a
+    /// dedicated or staging instance (`"https://dndev.apprapp.test.cloud.ibm.com"`)
+    /// or a mock server (`"http://localhost:3000"`).
+    ///
+    /// See [`resolve_urls_from_service_override`] for how the IAM token URL is derived.
+    pub fn from_service_url(url: &str, use_private_endpoint: bool) -> Self {
+        resolve_urls_from_service_override(url, use_private_endpoint)
+    }
+
+    /// Overrides the IAM token endpoint used by the token provider.
+    ///
+    /// `url` is the full token URL, e.g. `"https://private.iam.cloud.ibm.com/identity/token"`.
+    /// It takes precedence over the URL derived from the region, the private-endpoint
+    /// flag or the service URL override.
+    pub fn with_token_url(mut self, url: &str) -> Self {
+        self.token_url_override = Some(url.to_string());
+        self
+    }
+
+    /// Adds an HTTP header sent with the configuration requests, the websocket
+    /// handshake and metering. See [`ServiceAddress::with_custom_header`].
+    pub fn with_custom_header(mut self, name: &str, value: &str) -> Self {
+        self.custom_headers
+            .push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// How the client authenticates against IBM Cloud IAM, or against the gateway
+/// of a self-hosted deployment.
+#[derive(Debug, Clone)]
+pub enum Authenticator {
+    /// Exchange an IAM API key for an access token.
+    ApiKey(String),
+    /// Exchange the compute resource token mounted by IKS / Code Engine for an access
+    /// token of the given trusted profile.
+    ///
+    /// `cr_token_file` defaults to the standard mount locations when `None`.
+    TrustedProfile {
+        profile_id: String,
+        cr_token_file: Option<PathBuf>,
+    },
+    /// Adds custom credentials to every request instead of an IAM access token,
+    /// e.g. an HMAC signature or an API gateway key.
+    Custom(Arc<dyn RequestAuthenticator>),
+}
+
+impl PartialEq for Authenticator {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::ApiKey(a), Self::ApiKey(b)) => a == b,
+            (
+                Self::TrustedProfile {
+                    profile_id: a,
+                    cr_token_file: a_file,
+                },
+                Self::TrustedProfile {
+                    profile_id: b,
+                    cr_token_file: b_file,
+                },
+            ) => a == b && a_file == b_file,
+            (Self::Custom(a), Self::Custom(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Authenticator {}
+
 /// AppConfiguration client connection to IBM Cloud.
 #[derive(Debug)]
 pub struct AppConfigurationClientIBMCloud {
     client: AppConfigurationClientHttp<LiveConfigurationImpl>,
+    /// IAM endpoint the API keys given to [`update_api_key`](Self::update_api_key)
+    /// are exchanged at.
+    token_url: String,
 }
 
 impl AppConfigurationClientIBMCloud {
@@ -63,7 +154,7 @@ impl AppConfigurationClientIBMCloud {
     /// * `offline_mode`        – Behaviour when not synced with the server.
     /// * `use_private_endpoint`– Use IBM Cloud private network endpoint.
     /// * `resolved_urls`       – Pre-computed URL overrides (see
-    ///                           [`AppConfiguration::override_service_url`]).
+    ///   [`AppConfiguration::override_service_url`]).
     pub fn new(
         apikey: &str,
         region: &str,
@@ -73,25 +164,205 @@ impl AppConfigurationClientIBMCloud {
         resolved_urls: ResolvedUrls,
         runtime_emitter: RuntimeEventEmitter,
     ) -> Result<Self> {
+        Self::new_with_authenticator(
+            Authenticator::ApiKey(apikey.to_string()),
+            region,
+            configuration_id,
+            offline_mode,
+            use_private_endpoint,
+            resolved_urls,
+            LiveConfigurationOptions::default(),
+            runtime_emitter,
+        )
+    }
+
+    /// Creates a new client connecting to IBM Cloud using the given [`Authenticator`].
+    ///
+    /// Use this instead of [`AppConfigurationClientIBMCloud::new`] to authenticate with a
+    /// trusted profile where long-lived API keys are not allowed, or to tune the background
+    /// thread through [`LiveConfigurationOptions`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_with_authenticator(
+        authenticator: Authenticator,
+        region: &str,
+        configuration_id: ConfigurationId,
+        offline_mode: OfflineMode,
+        use_private_endpoint: bool,
+        resolved_urls: ResolvedUrls,
+        live_options: LiveConfigurationOptions,
+        runtime_emitter: RuntimeEventEmitter,
+    ) -> Result<Self> {
+        Self::validate(&authenticator, region, &resolved_urls)?;
         let service_address =
             Self::build_service_address(region, use_private_endpoint, &resolved_urls);
-        let token_provider = Box::new(Self::build_token_provider(
-            apikey,
-            use_private_endpoint,
-            &resolved_urls,
-        ));
+        let authenticator =
+            Self::build_authenticator(authenticator, use_private_endpoint, &resolved_urls);
+        let token_url = Self::build_token_url(use_private_endpoint, &resolved_urls);
 
         Ok(Self {
             client: AppConfigurationClientHttp::new(
                 service_address,
-                token_provider,
+                authenticator,
+                configuration_id,
+                offline_mode,
+                runtime_emitter,
+                live_options,
+            )?,
+            token_url,
+        })
+    }
+
+    /// Like [`AppConfigurationClientIBMCloud::new_with_authenticator`], but
+    /// downloads the configuration once before starting the client, so that wrong
+    /// credentials or IDs make the constructor fail instead of the client staying
+    /// offline.
+    ///
+    /// The configuration is downloaded again when the client connects.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_checked(
+        authenticator: Authenticator,
+        region: &str,
+        configuration_id: ConfigurationId,
+        offline_mode: OfflineMode,
+        use_private_endpoint: bool,
+        resolved_urls: ResolvedUrls,
+        live_options: LiveConfigurationOptions,
+        runtime_emitter: RuntimeEventEmitter,
+    ) -> Result<Self> {
+        Self::validate(&authenticator, region, &resolved_urls)?;
+        let service_address =
+            Self::build_service_address(region, use_private_endpoint, &resolved_urls);
+        let authenticator =
+            Self::build_authenticator(authenticator, use_private_endpoint, &resolved_urls);
+        let token_url = Self::build_token_url(use_private_endpoint, &resolved_urls);
+        AppConfigurationClientHttp::fetch_once(
+            service_address.clone(),
+            authenticator.clone(),
+            &configuration_id,
+        )
+        .map_err(|error| build_error(error, &service_address))?;
+
+        Ok(Self {
+            client: AppConfigurationClientHttp::new(
+                service_address,
+                authenticator,
+                configuration_id,
+                offline_mode,
+                runtime_emitter,
+                live_options,
+            )?,
+            token_url,
+        })
+    }
+
+    /// Creates a new client that downloads the configuration through a custom
+    /// [`ServerClient`] instead of connecting to IBM Cloud.
+    ///
+    /// Use it to serve the configuration from another backend, e.g. a proxy or a
+    /// test double. Evaluations are not metered: the metering data is only sent to
+    /// the App Configuration service.
+    ///
+    /// The requests of the background thread and those of
+    /// [`fresh_configuration`](ConfigurationProvider::fresh_configuration) are
+    /// sent one at a time, as `server_client` is not required to be [`Sync`].
+    pub fn new_with_server_client(
+        server_client: impl ServerClient,
+        configuration_id: ConfigurationId,
+        offline_mode: OfflineMode,
+        live_options: LiveConfigurationOptions,
+        runtime_emitter: RuntimeEventEmitter,
+    ) -> Result<Self> {
+        Ok(Self {
+            client: AppConfigurationClientHttp::new_with_server_client(
+                SharedServerClient::new_serialized(server_client),
+                NoopMeteringClient,
                 configuration_id,
                 offline_mode,
                 runtime_emitter,
+                live_options,
             )?,
+            // Not used, the credentials of these clients cannot be updated
+            token_url: String::new(),
         })
     }
 
+    /// Downloads the configuration from IBM Cloud once, without opening a
+    /// websocket nor starting any thread.
+    ///
+    /// The returned [`Configuration`] can evaluate features and properties, but
+    /// it is never updated and evaluations are not metered. Use it in batch
+    /// jobs and command line tools that only need the flag values at startup.
+    pub fn fetch_once(
+        authenticator: Authenticator,
+        region: &str,
+        configuration_id: &ConfigurationId,
+        use_private_endpoint: bool,
+        resolved_urls: &ResolvedUrls,
+    ) -> Result<Configuration> {
+        Self::validate(&authenticator, region, resolved_urls)?;
+        let service_address =
+            Self::build_service_address(region, use_private_endpoint, resolved_urls);
+        AppConfigurationClientHttp::fetch_once(
+            service_address.clone(),
+            Self::build_authenticator(authenticator, use_private_endpoint, resolved_urls),
+            configuration_id,
+        )
+        .map_err(|error| build_error(error, &service_address))
+    }
+
+    /// Checks the settings that are wrong whatever the server answers.
+    fn validate(
+        authenticator: &Authenticator,
+        region: &str,
+        resolved_urls: &ResolvedUrls,
+    ) -> std::result::Result<(), BuildError> {
+        // The region only makes the host name of the service
+        if resolved_urls.service_host_override.is_none() {
+            validate_region(region)?;
+        }
+        if let Authenticator::ApiKey(apikey) = authenticator {
+            validate_api_key(apikey)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn build_authenticator(
+        authenticator: Authenticator,
+        use_private_endpoint: bool,
+        resolved_urls: &ResolvedUrls,
+    ) -> Arc<dyn RequestAuthenticator> {
+        match authenticator {
+            Authenticator::ApiKey(apikey) => Arc::new(Self::build_token_provider(
+                &apikey,
+                use_private_endpoint,
+                resolved_urls,
+            )),
+            authenticator => Self::build_authenticator_for(
+                authenticator,
+                &Self::build_token_url(use_private_endpoint, resolved_urls),
+            ),
+        }
+    }
+
+    /// Builds the authenticator exchanging the credentials at `token_url`.
+    fn build_authenticator_for(
+        authenticator: Authenticator,
+        token_url: &str,
+    ) -> Arc<dyn RequestAuthenticator> {
+        match authenticator {
+            Authenticator::ApiKey(apikey) => Arc::new(TokenProviderImpl::new(&apikey, token_url)),
+            Authenticator::TrustedProfile {
+                profile_id,
+                cr_token_file,
+            } => Arc::new(TrustedProfileTokenProviderImpl::new(
+                &profile_id,
+                cr_token_file,
+                token_url,
+            )),
+            Authenticator::Custom(authenticator) => authenticator,
+        }
+    }
+
     // ── Internal URL builders ────────────────────────────────────────────────
 
     pub(crate) fn build_service_address(
@@ -99,28 +370,35 @@ impl AppConfigurationClientIBMCloud {
         use_private_endpoint: bool,
         urls: &ResolvedUrls,
     ) -> ServiceAddress {
-        if let Some(host) = &urls.service_host_override {
+        let address = if let Some(host) = &urls.service_host_override {
             if urls.service_no_ssl {
-                return ServiceAddress::new_without_ssl(
+                ServiceAddress::new_without_ssl(
+                    host.clone(),
+                    urls.service_port_override,
+                    Some("apprapp".to_string()),
+                )
+            } else {
+                ServiceAddress::new(
                     host.clone(),
                     urls.service_port_override,
                     Some("apprapp".to_string()),
-                );
+                )
             }
-            return ServiceAddress::new(
-                host.clone(),
-                urls.service_port_override,
-                Some("apprapp".to_string()),
-            );
-        }
-
-        // Default: production cloud.ibm.com
-        let host = if use_private_endpoint {
-            format!("private.{region}.apprapp.cloud.ibm.com")
         } else {
-            format!("{region}.apprapp.cloud.ibm.com")
+            // Default: production cloud.ibm.com
+            let host = if use_private_endpoint {
+                format!("private.{region}.apprapp.cloud.ibm.com")
+            } else {
+                format!("{region}.apprapp.cloud.ibm.com")
+            };
+            ServiceAddress::new(host, None, Some("apprapp".to_string()))
         };
-        ServiceAddress::new(host, None, Some("apprapp".to_string()))
+
+        urls.custom_headers
+            .iter()
+            .fold(address, |address, (name, value)| {
+                address.with_custom_header(name, value)
+            })
     }
 
     pub(crate) fn build_token_provider(
@@ -128,8 +406,12 @@ impl AppConfigurationClientIBMCloud {
         use_private_endpoint: bool,
         urls: &ResolvedUrls,
     ) -> TokenProviderImpl {
+        TokenProviderImpl::new(apikey, &Self::build_token_url(use_private_endpoint, urls))
+    }
+
+    fn build_token_url(use_private_endpoint: bool, urls: &ResolvedUrls) -> String {
         if let Some(token_url) = &urls.token_url_override {
-            return TokenProviderImpl::new(apikey, token_url);
+            return token_url.clone();
         }
 
         // Default: production IAM
@@ -138,11 +420,63 @@ impl AppConfigurationClientIBMCloud {
         } else {
             IAM_PROD_HOST.to_string()
         };
-        TokenProviderImpl::new(apikey, &format!("https://{host}/identity/token"))
+        format!("https://{host}/identity/token")
     }
 
     // ── Public helpers ───────────────────────────────────────────────────────
 
+    /// Switches the client to the configuration of `fallback`, e.g. during an
+    /// incident caused by a bad configuration push.
+    ///
+    /// The updates received from the server are ignored until
+    /// [`exit_safe_mode`](Self::exit_safe_mode) is called, then the latest one is
+    /// used. With [`OfflineMode::Fail`] every evaluation fails, with
+    /// [`OfflineMode::Cache`] the current configuration is frozen. Returns an
+    /// error, without entering safe mode, if `fallback` has no configuration.
+    pub fn enter_safe_mode(&self, fallback: OfflineMode) -> Result<()> {
+        self.client.enter_safe_mode(fallback)
+    }
+
+    /// Lifts the safe mode entered with [`enter_safe_mode`](Self::enter_safe_mode).
+    pub fn exit_safe_mode(&self) -> Result<()> {
+        self.client.exit_safe_mode()
+    }
+
+    /// Replaces the source of the access tokens, e.g. to rotate credentials
+    /// without recreating the client.
+    ///
+    /// An access token is requested from `token_provider` first: if that
+    /// fails, the error is returned and the previous credentials are kept.
+    /// The configuration requests, websocket connections and metering pushes
+    /// sent from now on are authenticated with `token_provider`; the websocket
+    /// already open is kept. Fails for the clients created with
+    /// [`new_with_server_client`](Self::new_with_server_client).
+    pub fn update_token_provider(&self, token_provider: Box<dyn TokenProvider>) -> Result<()> {
+        self.update_credentials(Authenticator::Custom(Arc::new(token_provider)))
+    }
+
+    /// Like [`update_token_provider`](Self::update_token_provider), exchanging
+    /// `apikey` for access tokens at the same IAM endpoint as the previous
+    /// credentials. The previous API key may already be revoked: a client
+    /// whose token requests are rejected keeps retrying, and gets back online
+    /// with the new one.
+    pub fn update_api_key(&self, apikey: &str) -> Result<()> {
+        self.update_credentials(Authenticator::ApiKey(apikey.to_string()))
+    }
+
+    /// Checks `authenticator` and authenticates the requests sent from now on
+    /// with it, see [`update_token_provider`](Self::update_token_provider).
+    pub(crate) fn update_credentials(&self, authenticator: Authenticator) -> Result<()> {
+        if let Authenticator::ApiKey(apikey) = &authenticator {
+            validate_api_key(apikey)?;
+        }
+        self.client
+            .update_authenticator(Self::build_authenticator_for(
+                authenticator,
+                &self.token_url,
+            ))
+    }
+
     pub fn get_secret(
         &self,
         property_id: &str,
@@ -155,6 +489,80 @@ impl AppConfigurationClientIBMCloud {
     }
 }
 
+/// Checks that `region` looks like an IBM Cloud region ID, e.g. `us-south`.
+pub(crate) fn validate_region(region: &str) -> std::result::Result<(), BuildError> {
+    let valid = !region.is_empty()
+        && !region.starts_with('-')
+        && !region.ends_with('-')
+        && region
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-');
+    if valid {
+        Ok(())
+    } else {
+        Err(BuildError::InvalidRegion {
+            region: region.to_string(),
+        })
+    }
+}
+
+/// Checks that `apikey` has the format of IAM API keys, letters, digits, `-`
+/// and `_`, to report keys mangled when copied before IAM rejects them.
+pub(crate) fn validate_api_key(apikey: &str) -> std::result::Result<(), BuildError> {
+    let reason = if apikey.is_empty() {
+        "it is empty"
+    } else if apikey.chars().any(char::is_whitespace) {
+        "it contains whitespace, e.g. a newline left from the file or variable it was read from"
+    } else if !apikey
+        .bytes()
+        .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+    {
+        "it contains characters other than letters, digits, '-' and '_'"
+    } else {
+        return Ok(());
+    };
+    Err(BuildError::InvalidApiKey { reason })
+}
+
+/// Reports the errors of a first download that come from the settings of the
+/// client, rather than from its IDs or the data, as [`BuildError`]s.
+fn build_error(error: Error, service_address: &ServiceAddress) -> Error {
+    match error {
+        Error::NetworkError(NetworkError::TokenRequestRejected {
+            status_code,
+            message,
+        }) => BuildError::TokenExchangeFailed {
+            status: status_code,
+            message,
+        }
+        .into(),
+        Error::NetworkError(error)
+            if matches!(
+                error.category(),
+                NetworkErrorCategory::Dns
+                    | NetworkErrorCategory::Tls
+                    | NetworkErrorCategory::Timeout
+                    | NetworkErrorCategory::Connect
+            ) =>
+        {
+            let endpoint = match &error {
+                NetworkError::ReqwestError(e) => {
+                    e.url().map(|url| url.origin().ascii_serialization())
+                }
+                _ => None,
+            }
+            .unwrap_or_else(|| service_address.base_url(ServiceAddressProtocol::Http));
+            BuildError::EndpointUnreachable {
+                endpoint,
+                category: error.category(),
+                source: error,
+            }
+            .into()
+        }
+        error => error,
+    }
+}
+
 /// Parses a raw `override_service_url` string like
 /// `"https://dndev.apprapp.test.cloud.ibm.com"` or `"http://localhost:3000"`
 /// and produces a [`ResolvedUrls`] with the token URL automatically derived
@@ -217,6 +625,7 @@ pub fn resolve_urls_from_service_override(
         token_url_override: Some(format!("https://{iam_base}/identity/token")),
         service_no_ssl: !use_ssl,
         service_port_override: port,
+        custom_headers: Vec::new(),
     }
 }
 
@@ -237,6 +646,38 @@ impl ConfigurationProvider for AppConfigurationClientIBMCloud {
         self.client.get_property(property_id)
     }
 
+    fn get_feature_from(
+        &self,
+        configuration: &Configuration,
+        feature_id: &str,
+    ) -> Result<FeatureSnapshot> {
+        self.client.get_feature_from(configuration, feature_id)
+    }
+
+    fn get_property_from(
+        &self,
+        configuration: &Configuration,
+        property_id: &str,
+    ) -> Result<PropertySnapshot> {
+        self.client.get_property_from(configuration, property_id)
+    }
+
+    fn get_segment_ids(&self) -> Result<Vec<String>> {
+        self.client.get_segment_ids()
+    }
+
+    fn get_segment(&self, segment_id: &str) -> Result<SegmentSnapshot> {
+        self.client.get_segment(segment_id)
+    }
+
+    fn get_configuration_snapshot(&self) -> Result<Configuration> {
+        self.client.get_configuration_snapshot()
+    }
+
+    fn get_shared_configuration(&self) -> Result<Arc<Configuration>> {
+        self.client.get_shared_configuration()
+    }
+
     fn is_online(&self) -> Result<bool> {
         self.client.is_online()
     }
@@ -257,6 +698,41 @@ impl ConfigurationProvider for AppConfigurationClientIBMCloud {
         self.client.get_runtime_status()
     }
 
+    fn last_error(&self) -> Result<Option<SdkErrorSnapshot>> {
+        self.client.last_error()
+    }
+
+    fn sdk_stats(&self) -> Result<Option<SdkStats>> {
+        self.client.sdk_stats()
+    }
+
+    fn ready(&self) -> Ready {
+        self.client.ready()
+    }
+
+    fn fresh_configuration(
+        &self,
+        max_age: std::time::Duration,
+    ) -> Result<Option<Arc<Configuration>>> {
+        self.client.fresh_configuration(max_age)
+    }
+
+    fn unused_features(&self) -> Result<Option<UnusedFeatures>> {
+        self.client.unused_features()
+    }
+
+    fn deprecated_flags_in_use(&self) -> Result<Vec<DeprecatedFlagUsage>> {
+        self.client.deprecated_flags_in_use()
+    }
+
+    fn sync_history(&self) -> Result<Vec<SyncRecord>> {
+        self.client.sync_history()
+    }
+
+    fn record_exposure(&self, exposure: Exposure) -> Result<()> {
+        self.client.record_exposure(exposure)
+    }
+
     fn add_runtime_event_listener(&self, listener: RuntimeEventListener) -> Result<()> {
         self.client.add_runtime_event_listener(listener)
     }
@@ -275,6 +751,7 @@ impl ConfigurationProvider for AppConfigurationClientIBMCloud {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::NetworkError;
     use crate::network::http_client::ServiceAddressProtocol;
 
     // Helper: production defaults (no override)
@@ -396,6 +873,252 @@ mod tests {
         );
     }
 
+    // ── IAM endpoint override ─────────────────────────────────────────────────
+
+    #[test]
+    fn test_token_url_override_with_private_endpoint() {
+        let urls = ResolvedUrls::default().with_token_url("https://iam.vpe.example.com/token");
+
+        // The service host is still derived from region + private flag ...
+        let sa = AppConfigurationClientIBMCloud::build_service_address("eu-de", true, &urls);
+        assert_eq!(
+            sa.base_url(ServiceAddressProtocol::Http),
+            "https://private.eu-de.apprapp.cloud.ibm.com/apprapp"
+        );
+        // ... while the token provider uses the explicit IAM URL.
+        let tp = AppConfigurationClientIBMCloud::build_token_provider("key", true, &urls);
+        assert_eq!(tp.endpoint, "https://iam.vpe.example.com/token");
+    }
+
+    #[test]
+    fn test_from_service_url_with_token_url() {
+        let urls = ResolvedUrls::from_service_url("https://dedicated.example.com:8443", false)
+            .with_token_url("https://iam.dedicated.example.com/identity/token");
+
+        let sa = AppConfigurationClientIBMCloud::build_service_address("us-south", false, &urls);
+        assert_eq!(
+            sa.base_url(ServiceAddressProtocol::Http),
+            "https://dedicated.example.com:8443/apprapp"
+        );
+        let tp = AppConfigurationClientIBMCloud::build_token_provider("key", false, &urls);
+        assert_eq!(
+            tp.endpoint,
+            "https://iam.dedicated.example.com/identity/token"
+        );
+    }
+
+    #[test]
+    fn test_trusted_profile_token_url() {
+        assert_eq!(
+            AppConfigurationClientIBMCloud::build_token_url(true, &no_override()),
+            "https://private.iam.cloud.ibm.com/identity/token"
+        );
+        let urls = ResolvedUrls::default().with_token_url("https://iam.vpe.example.com/token");
+        assert_eq!(
+            AppConfigurationClientIBMCloud::build_token_url(true, &urls),
+            "https://iam.vpe.example.com/token"
+        );
+    }
+
+    // ── Custom headers ────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_custom_headers_forwarded_to_service_address() {
+        let urls = resolve_urls_from_service_override("https://onprem.example.com", false)
+            .with_custom_header("X-Internal-Routing", "appconfig");
+        let sa = AppConfigurationClientIBMCloud::build_service_address("us-south", false, &urls);
+        assert_eq!(
+            sa.custom_headers().unwrap()["x-internal-routing"],
+            "appconfig"
+        );
+    }
+
+    // ── Fail-fast constructor ─────────────────────────────────────────────────
+
+    #[derive(Debug)]
+    struct StaticToken;
+
+    impl RequestAuthenticator for StaticToken {
+        fn authenticate(
+            &self,
+            request: &mut crate::network::OutgoingRequest<'_>,
+        ) -> crate::network::NetworkResult<()> {
+            request.set_header("Authorization", "Bearer token")
+        }
+    }
+
+    #[test]
+    fn test_new_checked() {
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.query_param("collection_id", "missing");
+            then.status(404);
+        });
+        server.mock(|when, then| {
+            when.query_param("collection_id", "blue-charge");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file(concat!(
+                    env!("CARGO_MANIFEST_DIR"),
+                    "/data/data-dump-enterprise-plan-sdk-testing.json"
+                ));
+        });
+        let new_checked = |collection_id: &str| {
+            AppConfigurationClientIBMCloud::new_checked(
+                Authenticator::Custom(Arc::new(StaticToken)),
+                "us-south",
+                ConfigurationId::new("guid".into(), "dev".into(), collection_id.into()).unwrap(),
+                OfflineMode::Fail,
+                false,
+                ResolvedUrls::from_service_url(&server.base_url(), false),
+                LiveConfigurationOptions::default(),
+                RuntimeEventEmitter::new(),
+            )
+        };
+
+        assert!(matches!(
+            new_checked("missing").unwrap_err(),
+            crate::Error::NetworkError(NetworkError::ConfigurationNotFound { .. })
+        ));
+        assert!(new_checked("blue-charge").is_ok());
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let new_checked = |authenticator: Authenticator, region: &str, urls: ResolvedUrls| {
+            AppConfigurationClientIBMCloud::new_checked(
+                authenticator,
+                region,
+                ConfigurationId::new("guid".into(), "dev".into(), "blue-charge".into()).unwrap(),
+                OfflineMode::Fail,
+                false,
+                urls,
+                LiveConfigurationOptions::default(),
+                RuntimeEventEmitter::new(),
+            )
+            .unwrap_err()
+        };
+
+        let error = new_checked(
+            Authenticator::ApiKey("key".into()),
+            "US South",
+            no_override(),
+        );
+        assert!(matches!(
+            &error,
+            crate::Error::BuildError(BuildError::InvalidRegion { region }) if region == "US South"
+        ));
+        let error = new_checked(
+            Authenticator::ApiKey("key\n".into()),
+            "us-south",
+            no_override(),
+        );
+        assert!(matches!(
+            &error,
+            crate::Error::BuildError(error @ BuildError::InvalidApiKey { .. }) if !error.is_retryable()
+        ));
+
+        // IAM rejects the API key
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("POST").path("/identity/token");
+            then.status(400)
+                .header("content-type", "application/json")
+                .body(r#"{"errorCode":"BXNIM0415E","errorMessage":"Provided API key could not be found."}"#);
+        });
+        let urls = ResolvedUrls::from_service_url(&server.base_url(), false)
+            .with_token_url(&server.url("/identity/token"));
+        let error = new_checked(Authenticator::ApiKey("key".into()), "us-south", urls);
+        assert!(matches!(
+            &error,
+            crate::Error::BuildError(error @ BuildError::TokenExchangeFailed { status: 400, message })
+                if message == "Provided API key could not be found." && !error.is_retryable()
+        ));
+        assert!(error.to_string().contains("Check that the API key"));
+
+        // Nothing listens on the service port
+        let urls = ResolvedUrls::from_service_url("http://127.0.0.1:1", false);
+        let error = new_checked(
+            Authenticator::Custom(Arc::new(StaticToken)),
+            "us-south",
+            urls,
+        );
+        assert!(matches!(
+            &error,
+            crate::Error::BuildError(error @ BuildError::EndpointUnreachable { endpoint, category: NetworkErrorCategory::Connect, .. })
+                if endpoint == "http://127.0.0.1:1" && error.is_retryable()
+        ));
+    }
+
+    #[test]
+    fn test_update_api_key_after_revocation() {
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method("POST")
+                .path("/identity/token")
+                .form_urlencoded_tuple("apikey", "revoked-key");
+            then.status(400)
+                .header("content-type", "application/json")
+                .body(r#"{"errorCode":"BXNIM0415E","errorMessage":"Provided API key could not be found."}"#);
+        });
+        server.mock(|when, then| {
+            when.method("POST")
+                .path("/identity/token")
+                .form_urlencoded_tuple("apikey", "rotated-key");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"access_token":"token","expires_in":3600}"#);
+        });
+        server.mock(|when, then| {
+            when.query_param("collection_id", "blue-charge")
+                .header("Authorization", "Bearer token");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file(concat!(
+                    env!("CARGO_MANIFEST_DIR"),
+                    "/data/data-dump-enterprise-plan-sdk-testing.json"
+                ));
+        });
+        let client = AppConfigurationClientIBMCloud::new_with_authenticator(
+            Authenticator::ApiKey("revoked-key".into()),
+            "us-south",
+            ConfigurationId::new("guid".into(), "dev".into(), "blue-charge".into()).unwrap(),
+            OfflineMode::Fail,
+            false,
+            ResolvedUrls::from_service_url(&server.base_url(), false)
+                .with_token_url(&server.url("/identity/token")),
+            LiveConfigurationOptions {
+                polling_interval: Some(std::time::Duration::from_millis(50)),
+                ..Default::default()
+            },
+            RuntimeEventEmitter::new(),
+        )
+        .unwrap();
+        let wait_for = |condition: &dyn Fn() -> bool| {
+            let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+            while !condition() {
+                assert!(std::time::Instant::now() < deadline, "Timed out");
+                std::thread::sleep(std::time::Duration::from_millis(10));
+            }
+        };
+
+        // IAM keeps rejecting the revoked key, the client keeps retrying
+        wait_for(&|| {
+            client
+                .last_error()
+                .unwrap()
+                .is_some_and(|error| error.retry_count > 0)
+        });
+        let error = client.last_error().unwrap().unwrap();
+        assert_eq!(error.category, Some(NetworkErrorCategory::Auth));
+        assert!(error.will_retry);
+        assert!(!client.is_online().unwrap());
+
+        client.update_api_key("rotated-key").unwrap();
+        wait_for(&|| client.is_online().unwrap());
+        client.get_feature("f1").unwrap();
+    }
+
     // ── Region is still respected when no override is present ─────────────────
 
     #[test]
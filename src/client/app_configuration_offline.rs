@@ -14,12 +14,17 @@
 
 use crate::ConfigurationProvider;
 use crate::errors::Result;
-use crate::models::{Configuration, FeatureSnapshot, PropertySnapshot, SecretPropertySnapshot};
+use crate::models::{
+    Configuration, FeatureSnapshot, PropertySnapshot, SecretPropertySnapshot, SegmentSnapshot,
+    ValidationMode,
+};
+use crate::network::serialization::{ConfigurationFileFormat, ConfigurationJson};
+use std::sync::Arc;
 
 /// AppConfiguration client using a local file with a configuration snapshot
 #[derive(Debug)]
 pub struct AppConfigurationOffline {
-    pub(crate) config_snapshot: Configuration,
+    pub(crate) config_snapshot: Arc<Configuration>,
 }
 
 impl AppConfigurationOffline {
@@ -35,7 +40,139 @@ impl AppConfigurationOffline {
         collection_id: &str,
     ) -> Result<Self> {
         let config_snapshot = Configuration::from_file(filepath, environment_id, collection_id)?;
-        Ok(Self { config_snapshot })
+        Ok(Self {
+            config_snapshot: Arc::new(config_snapshot),
+        })
+    }
+
+    /// Creates a new [`crate::AppConfigurationClient`] taking the configuration from a
+    /// JSON string, for runtimes without filesystem access (e.g. WASM).
+    ///
+    /// # Arguments
+    ///
+    /// * `json` - The configuration, with the same format as the configuration files.
+    /// * `environment_id` - ID of the environment to use from the configuration.
+    pub fn from_json_str(json: &str, environment_id: &str, collection_id: &str) -> Result<Self> {
+        let config_snapshot = Configuration::from_json_str(json, environment_id, collection_id)?;
+        Ok(Self {
+            config_snapshot: Arc::new(config_snapshot),
+        })
+    }
+
+    /// Returns the IDs of the environments in a configuration file.
+    ///
+    /// Use [`ConfigurationDump`] to open several of them without reading the file again.
+    pub fn environments(filepath: &std::path::Path) -> Result<Vec<String>> {
+        Ok(ConfigurationDump::from_file(filepath)?.environments())
+    }
+}
+
+/// A configuration file, parsed once to create [`AppConfigurationOffline`] clients
+/// for any of its environments and collections, e.g. to compare the targeting of
+/// two environments.
+#[derive(Debug, Clone)]
+pub struct ConfigurationDump {
+    configuration: ConfigurationJson,
+    validation_mode: ValidationMode,
+}
+
+impl ConfigurationDump {
+    /// Reads the configuration from a local file, in the format given by its
+    /// extension, see [`ConfigurationFileFormat::from_path`].
+    pub fn from_file(filepath: &std::path::Path) -> Result<Self> {
+        Self::from_file_with_format(filepath, ConfigurationFileFormat::from_path(filepath))
+    }
+
+    /// Reads the configuration from a local file in the given format, whatever
+    /// its extension.
+    pub fn from_file_with_format(
+        filepath: &std::path::Path,
+        format: ConfigurationFileFormat,
+    ) -> Result<Self> {
+        let configuration = ConfigurationJson::from_file(filepath, format)?;
+        configuration.verify_checksum()?;
+        Ok(Self {
+            configuration,
+            validation_mode: ValidationMode::default(),
+        })
+    }
+
+    /// Reads the configuration from a JSON string with the same format as the
+    /// configuration files.
+    pub fn from_json_str(json: &str) -> Result<Self> {
+        let configuration = ConfigurationJson::from_json_str(json)?;
+        configuration.verify_checksum()?;
+        Ok(Self {
+            configuration,
+            validation_mode: ValidationMode::default(),
+        })
+    }
+
+    /// Sets how [`ConfigurationDump::open`] handles invalid features, properties
+    /// and segments. Strict by default.
+    pub fn with_validation_mode(mut self, validation_mode: ValidationMode) -> Self {
+        self.validation_mode = validation_mode;
+        self
+    }
+
+    /// Returns the checksum of the configuration, to add it to the file as the
+    /// `checksum` field. Configurations with a `checksum` field are verified
+    /// against it when they are read, and rejected if they do not match.
+    pub fn checksum(&self) -> Result<String> {
+        Ok(self.configuration.checksum()?)
+    }
+
+    /// Returns the IDs of the environments, in the order of the file.
+    pub fn environments(&self) -> Vec<String> {
+        self.configuration
+            .environments
+            .iter()
+            .map(|environment| environment.environment_id.clone())
+            .collect()
+    }
+
+    /// Returns the IDs of the collections, in the order of the file. Empty if the
+    /// file does not list them.
+    pub fn collections(&self) -> Vec<String> {
+        self.configuration
+            .collections
+            .iter()
+            .flatten()
+            .map(|collection| collection.collection_id.clone())
+            .collect()
+    }
+
+    /// Creates a client for the given environment and collection.
+    pub fn open(
+        &self,
+        environment_id: &str,
+        collection_id: &str,
+    ) -> Result<AppConfigurationOffline> {
+        // Only the requested environment is copied, the others are not needed.
+        let configuration = ConfigurationJson {
+            environments: self
+                .configuration
+                .environments
+                .iter()
+                .filter(|environment| environment.environment_id == environment_id)
+                .cloned()
+                .collect(),
+            collections: self.configuration.collections.clone(),
+            segments: self.configuration.segments.clone(),
+            // Verified when the dump was read, the copy no longer matches it
+            schema_version: None,
+            checksum: None,
+            document_checksum: None,
+        };
+        let config_snapshot = Configuration::new_with_validation_mode(
+            environment_id,
+            collection_id,
+            configuration,
+            self.validation_mode,
+        )?;
+        Ok(AppConfigurationOffline {
+            config_snapshot: Arc::new(config_snapshot),
+        })
     }
 }
 
@@ -56,6 +193,22 @@ impl ConfigurationProvider for AppConfigurationOffline {
         self.config_snapshot.get_property(property_id)
     }
 
+    fn get_segment_ids(&self) -> Result<Vec<String>> {
+        self.config_snapshot.get_segment_ids()
+    }
+
+    fn get_segment(&self, segment_id: &str) -> Result<SegmentSnapshot> {
+        self.config_snapshot.get_segment(segment_id)
+    }
+
+    fn get_configuration_snapshot(&self) -> Result<Configuration> {
+        Ok(Configuration::clone(&self.config_snapshot))
+    }
+
+    fn get_shared_configuration(&self) -> Result<Arc<Configuration>> {
+        Ok(self.config_snapshot.clone())
+    }
+
     fn is_online(&self) -> Result<bool> {
         Ok(false)
     }
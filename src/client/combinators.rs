@@ -0,0 +1,552 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::Value;
+use crate::client::{
+    ConfigurationProvider, DeprecatedFlagUsage, Exposure, RuntimeEventListener, RuntimeStatus,
+    SdkErrorSnapshot, SdkStats, SyncRecord, UnusedFeatures,
+};
+use crate::errors::{Error, LiveConfigurationError, Result};
+use crate::models::{
+    Configuration, FeatureSnapshot, PropertySnapshot, SecretPropertySnapshot, SegmentSnapshot,
+};
+use crate::network::live_configuration;
+use crate::utils::Ready;
+
+/// A provider that reads from `primary` and falls back to `fallback` while
+/// the primary has no configuration to serve: before it obtained its first
+/// one, or while it is offline and fails the lookups (see
+/// [`OfflineMode::Fail`](crate::OfflineMode::Fail)).
+///
+/// Other errors are returned as they are: a feature missing from the
+/// configuration of the primary is not looked up in the fallback.
+///
+/// A typical chain is a live client backed by an offline configuration file,
+/// so lookups keep succeeding while the live client cannot serve them:
+///
+/// ```rust,ignore
+/// let offline = AppConfigurationOffline::new(&path, "dev", "blue-charge")?;
+/// let client = Layered::new(live_client, offline);
+/// ```
+///
+/// Connectivity (`is_online`, `wait_until_online`, runtime status and errors)
+/// is reported by the primary, which downloads the fresh configurations too.
+#[derive(Debug)]
+pub struct Layered<P: ConfigurationProvider, F: ConfigurationProvider> {
+    primary: P,
+    fallback: F,
+}
+
+impl<P: ConfigurationProvider, F: ConfigurationProvider> Layered<P, F> {
+    pub fn new(primary: P, fallback: F) -> Self {
+        Self { primary, fallback }
+    }
+
+    /// Returns the provider consulted first.
+    pub fn primary(&self) -> &P {
+        &self.primary
+    }
+
+    /// Returns the provider consulted when the primary has no configuration.
+    pub fn fallback(&self) -> &F {
+        &self.fallback
+    }
+}
+
+/// Returns `result`, or the result of `fallback` if `result` failed because
+/// no configuration is available.
+fn fallback_if_unavailable<T>(
+    result: Result<T>,
+    fallback: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    match result {
+        Err(Error::LiveConfigurationError(LiveConfigurationError(
+            live_configuration::Error::Offline(_)
+            | live_configuration::Error::ConfigurationNotYetAvailable,
+        ))) => fallback(),
+        result => result,
+    }
+}
+
+impl<P: ConfigurationProvider, F: ConfigurationProvider> ConfigurationProvider for Layered<P, F> {
+    fn get_feature_ids(&self) -> Result<Vec<String>> {
+        fallback_if_unavailable(self.primary.get_feature_ids(), || {
+            self.fallback.get_feature_ids()
+        })
+    }
+
+    fn get_feature(&self, feature_id: &str) -> Result<FeatureSnapshot> {
+        fallback_if_unavailable(self.primary.get_feature(feature_id), || {
+            self.fallback.get_feature(feature_id)
+        })
+    }
+
+    fn get_property_ids(&self) -> Result<Vec<String>> {
+        fallback_if_unavailable(self.primary.get_property_ids(), || {
+            self.fallback.get_property_ids()
+        })
+    }
+
+    fn get_property(&self, property_id: &str) -> Result<PropertySnapshot> {
+        fallback_if_unavailable(self.primary.get_property(property_id), || {
+            self.fallback.get_property(property_id)
+        })
+    }
+
+    fn get_segment_ids(&self) -> Result<Vec<String>> {
+        fallback_if_unavailable(self.primary.get_segment_ids(), || {
+            self.fallback.get_segment_ids()
+        })
+    }
+
+    fn get_segment(&self, segment_id: &str) -> Result<SegmentSnapshot> {
+        fallback_if_unavailable(self.primary.get_segment(segment_id), || {
+            self.fallback.get_segment(segment_id)
+        })
+    }
+
+    fn get_configuration_snapshot(&self) -> Result<Configuration> {
+        fallback_if_unavailable(self.primary.get_configuration_snapshot(), || {
+            self.fallback.get_configuration_snapshot()
+        })
+    }
+
+    fn get_shared_configuration(&self) -> Result<Arc<Configuration>> {
+        fallback_if_unavailable(self.primary.get_shared_configuration(), || {
+            self.fallback.get_shared_configuration()
+        })
+    }
+
+    fn get_feature_from(
+        &self,
+        configuration: &Configuration,
+        feature_id: &str,
+    ) -> Result<FeatureSnapshot> {
+        fallback_if_unavailable(
+            self.primary.get_feature_from(configuration, feature_id),
+            || self.fallback.get_feature_from(configuration, feature_id),
+        )
+    }
+
+    fn get_property_from(
+        &self,
+        configuration: &Configuration,
+        property_id: &str,
+    ) -> Result<PropertySnapshot> {
+        fallback_if_unavailable(
+            self.primary.get_property_from(configuration, property_id),
+            || self.fallback.get_property_from(configuration, property_id),
+        )
+    }
+
+    fn is_online(&self) -> Result<bool> {
+        self.primary.is_online()
+    }
+
+    fn wait_until_online(&self) -> bool {
+        self.primary.wait_until_online()
+    }
+
+    fn get_secret_property(&self, property_id: &str) -> Result<SecretPropertySnapshot> {
+        fallback_if_unavailable(self.primary.get_secret_property(property_id), || {
+            self.fallback.get_secret_property(property_id)
+        })
+    }
+
+    fn is_connected(&self) -> Result<bool> {
+        self.primary.is_connected()
+    }
+
+    fn get_runtime_status(&self) -> Result<Option<RuntimeStatus>> {
+        self.primary.get_runtime_status()
+    }
+
+    fn last_error(&self) -> Result<Option<SdkErrorSnapshot>> {
+        self.primary.last_error()
+    }
+
+    fn sdk_stats(&self) -> Result<Option<SdkStats>> {
+        self.primary.sdk_stats()
+    }
+
+    fn ready(&self) -> Ready {
+        self.primary.ready()
+    }
+
+    fn fresh_configuration(
+        &self,
+        max_age: std::time::Duration,
+    ) -> Result<Option<Arc<Configuration>>> {
+        self.primary.fresh_configuration(max_age)
+    }
+
+    fn unused_features(&self) -> Result<Option<UnusedFeatures>> {
+        self.primary.unused_features()
+    }
+
+    fn deprecated_flags_in_use(&self) -> Result<Vec<DeprecatedFlagUsage>> {
+        self.primary.deprecated_flags_in_use()
+    }
+
+    fn sync_history(&self) -> Result<Vec<SyncRecord>> {
+        self.primary.sync_history()
+    }
+
+    fn record_exposure(&self, exposure: Exposure) -> Result<()> {
+        self.primary.record_exposure(exposure)
+    }
+
+    fn add_runtime_event_listener(&self, listener: RuntimeEventListener) -> Result<()> {
+        self.primary.add_runtime_event_listener(listener)
+    }
+
+    fn clean_up(&mut self) -> Result<()> {
+        self.primary.clean_up()?;
+        self.fallback.clean_up()
+    }
+
+    fn clean_up_with_cache_clear(&mut self) -> Result<()> {
+        self.primary.clean_up_with_cache_clear()?;
+        self.fallback.clean_up_with_cache_clear()
+    }
+}
+
+/// A provider that forces some features and properties of `inner` to fixed
+/// values, by ID.
+///
+/// Overridden features and properties evaluate to their value for every
+/// entity, like the local overrides of [`AppConfiguration`](crate::AppConfiguration).
+/// Features and properties must still exist in `inner`.
+#[derive(Debug)]
+pub struct Overrides<C: ConfigurationProvider> {
+    values: HashMap<String, Value>,
+    inner: C,
+}
+
+impl<C: ConfigurationProvider> Overrides<C> {
+    pub fn new(values: HashMap<String, Value>, inner: C) -> Self {
+        Self { values, inner }
+    }
+
+    /// Returns the overridden values, by feature or property ID.
+    pub fn values(&self) -> &HashMap<String, Value> {
+        &self.values
+    }
+
+    /// Returns the provider the overrides are applied to.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    fn apply_to_feature(
+        &self,
+        feature: FeatureSnapshot,
+        feature_id: &str,
+    ) -> Result<FeatureSnapshot> {
+        feature.with_local_override(self.values.get(feature_id).cloned())
+    }
+
+    fn apply_to_property(
+        &self,
+        property: PropertySnapshot,
+        property_id: &str,
+    ) -> Result<PropertySnapshot> {
+        property.with_local_override(self.values.get(property_id).cloned())
+    }
+}
+
+impl<C: ConfigurationProvider> ConfigurationProvider for Overrides<C> {
+    fn get_feature_ids(&self) -> Result<Vec<String>> {
+        self.inner.get_feature_ids()
+    }
+
+    fn get_feature(&self, feature_id: &str) -> Result<FeatureSnapshot> {
+        let feature = self.inner.get_feature(feature_id)?;
+        self.apply_to_feature(feature, feature_id)
+    }
+
+    fn get_property_ids(&self) -> Result<Vec<String>> {
+        self.inner.get_property_ids()
+    }
+
+    fn get_property(&self, property_id: &str) -> Result<PropertySnapshot> {
+        let property = self.inner.get_property(property_id)?;
+        self.apply_to_property(property, property_id)
+    }
+
+    fn get_segment_ids(&self) -> Result<Vec<String>> {
+        self.inner.get_segment_ids()
+    }
+
+    fn get_segment(&self, segment_id: &str) -> Result<SegmentSnapshot> {
+        self.inner.get_segment(segment_id)
+    }
+
+    fn get_configuration_snapshot(&self) -> Result<Configuration> {
+        self.inner.get_configuration_snapshot()
+    }
+
+    fn get_shared_configuration(&self) -> Result<Arc<Configuration>> {
+        self.inner.get_shared_configuration()
+    }
+
+    fn get_feature_from(
+        &self,
+        configuration: &Configuration,
+        feature_id: &str,
+    ) -> Result<FeatureSnapshot> {
+        let feature = self.inner.get_feature_from(configuration, feature_id)?;
+        self.apply_to_feature(feature, feature_id)
+    }
+
+    fn get_property_from(
+        &self,
+        configuration: &Configuration,
+        property_id: &str,
+    ) -> Result<PropertySnapshot> {
+        let property = self.inner.get_property_from(configuration, property_id)?;
+        self.apply_to_property(property, property_id)
+    }
+
+    fn is_online(&self) -> Result<bool> {
+        self.inner.is_online()
+    }
+
+    fn wait_until_online(&self) -> bool {
+        self.inner.wait_until_online()
+    }
+
+    fn get_secret_property(&self, property_id: &str) -> Result<SecretPropertySnapshot> {
+        self.inner.get_secret_property(property_id)
+    }
+
+    fn is_connected(&self) -> Result<bool> {
+        self.inner.is_connected()
+    }
+
+    fn get_runtime_status(&self) -> Result<Option<RuntimeStatus>> {
+        self.inner.get_runtime_status()
+    }
+
+    fn last_error(&self) -> Result<Option<SdkErrorSnapshot>> {
+        self.inner.last_error()
+    }
+
+    fn sdk_stats(&self) -> Result<Option<SdkStats>> {
+        self.inner.sdk_stats()
+    }
+
+    fn ready(&self) -> Ready {
+        self.inner.ready()
+    }
+
+    fn fresh_configuration(
+        &self,
+        max_age: std::time::Duration,
+    ) -> Result<Option<Arc<Configuration>>> {
+        self.inner.fresh_configuration(max_age)
+    }
+
+    fn unused_features(&self) -> Result<Option<UnusedFeatures>> {
+        self.inner.unused_features()
+    }
+
+    fn deprecated_flags_in_use(&self) -> Result<Vec<DeprecatedFlagUsage>> {
+        self.inner.deprecated_flags_in_use()
+    }
+
+    fn sync_history(&self) -> Result<Vec<SyncRecord>> {
+        self.inner.sync_history()
+    }
+
+    fn record_exposure(&self, exposure: Exposure) -> Result<()> {
+        self.inner.record_exposure(exposure)
+    }
+
+    fn add_runtime_event_listener(&self, listener: RuntimeEventListener) -> Result<()> {
+        self.inner.add_runtime_event_listener(listener)
+    }
+
+    fn clean_up(&mut self) -> Result<()> {
+        self.inner.clean_up()
+    }
+
+    fn clean_up_with_cache_clear(&mut self) -> Result<()> {
+        self.inner.clean_up_with_cache_clear()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use crate::client::AppConfigurationOffline;
+    use crate::errors::ConfigurationDataError;
+    use crate::network::serialization::fixtures::example_configuration_enterprise_path;
+    use crate::tests::GenericEntity;
+    use crate::{Feature, Property};
+    use rstest::rstest;
+
+    fn entity() -> GenericEntity {
+        GenericEntity {
+            id: "a1".to_string(),
+            attributes: HashMap::new(),
+        }
+    }
+
+    /// A live client that did not obtain its first configuration yet.
+    struct NoConfigurationYet;
+
+    impl NoConfigurationYet {
+        fn fail<T>(&self) -> Result<T> {
+            Err(
+                LiveConfigurationError(live_configuration::Error::ConfigurationNotYetAvailable)
+                    .into(),
+            )
+        }
+    }
+
+    impl ConfigurationProvider for NoConfigurationYet {
+        fn get_feature_ids(&self) -> Result<Vec<String>> {
+            self.fail()
+        }
+
+        fn get_feature(&self, _feature_id: &str) -> Result<FeatureSnapshot> {
+            self.fail()
+        }
+
+        fn get_property_ids(&self) -> Result<Vec<String>> {
+            self.fail()
+        }
+
+        fn get_property(&self, _property_id: &str) -> Result<PropertySnapshot> {
+            self.fail()
+        }
+
+        fn get_configuration_snapshot(&self) -> Result<Configuration> {
+            self.fail()
+        }
+
+        fn is_online(&self) -> Result<bool> {
+            Ok(false)
+        }
+
+        fn wait_until_online(&self) -> bool {
+            false
+        }
+
+        fn get_secret_property(&self, _property_id: &str) -> Result<SecretPropertySnapshot> {
+            self.fail()
+        }
+    }
+
+    #[rstest]
+    fn test_layered(example_configuration_enterprise_path: PathBuf) {
+        let offline = || {
+            AppConfigurationOffline::new(
+                &example_configuration_enterprise_path,
+                "dev",
+                "blue-charge",
+            )
+            .unwrap()
+        };
+
+        // Without a configuration, the primary is replaced by the fallback
+        let client = Layered::new(NoConfigurationYet, offline());
+        assert_eq!(
+            client.get_feature_ids().unwrap(),
+            client.fallback().get_feature_ids().unwrap()
+        );
+        let feature = client.get_feature("f1").unwrap();
+        assert_eq!(feature.get_feature_id().unwrap(), "f1");
+        let property = client.get_property("p4").unwrap();
+        assert_eq!(
+            property.get_current_value(&entity()).unwrap().value,
+            Value::String("my text".to_string())
+        );
+        assert!(client.get_feature("non-existing").is_err());
+        // Connectivity is the one of the primary
+        assert!(!client.is_online().unwrap());
+
+        // With a configuration, missing features are not looked up in the
+        // fallback, matching the IDs listed
+        let client = Layered::new(Configuration::default(), offline());
+        assert!(client.get_feature_ids().unwrap().is_empty());
+        assert!(matches!(
+            client.get_feature("f1"),
+            Err(Error::ConfigurationDataError(
+                ConfigurationDataError::FeatureNotFound(_)
+            ))
+        ));
+        assert!(matches!(
+            client.get_property("p4"),
+            Err(Error::ConfigurationDataError(
+                ConfigurationDataError::PropertyNotFound(_)
+            ))
+        ));
+    }
+
+    #[rstest]
+    fn test_overrides(example_configuration_enterprise_path: PathBuf) {
+        let offline = AppConfigurationOffline::new(
+            &example_configuration_enterprise_path,
+            "dev",
+            "blue-charge",
+        )
+        .unwrap();
+        let values = HashMap::from([
+            ("p4".to_string(), Value::String("overridden".to_string())),
+            ("non-existing".to_string(), Value::Boolean(true)),
+        ]);
+        let client = Overrides::new(values, offline);
+
+        assert_eq!(
+            client
+                .get_property("p4")
+                .unwrap()
+                .get_current_value(&entity())
+                .unwrap()
+                .value,
+            Value::String("overridden".to_string())
+        );
+        assert_eq!(
+            client
+                .get_property("p1")
+                .unwrap()
+                .get_current_value(&entity())
+                .unwrap()
+                .value,
+            Value::Int64(5)
+        );
+        // Overrides don't make features and properties appear
+        assert!(client.get_feature("non-existing").is_err());
+
+        // Pinned configurations keep the overrides
+        let pinned = client.pin_configuration().unwrap();
+        assert_eq!(
+            pinned
+                .get_property("p4")
+                .unwrap()
+                .get_current_value(&entity())
+                .unwrap()
+                .value,
+            Value::String("overridden".to_string())
+        );
+    }
+}
@@ -0,0 +1,105 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+#[cfg(feature = "remote")]
+use std::collections::HashSet;
+use std::panic::Location;
+use std::sync::Mutex;
+
+use crate::client::DeprecatedFlagUsage;
+use crate::utils::MutexExt;
+
+/// Features planned for removal that the application still evaluates.
+///
+/// The IDs of the deprecated features are given to the client. Their snapshots
+/// record every evaluation, and a warning is logged the first time each of
+/// them is evaluated, with the location of the call.
+#[derive(Debug, Default)]
+pub(crate) struct DeprecatedFeatures {
+    #[cfg(feature = "remote")]
+    deny_list: HashSet<String>,
+    in_use: Mutex<HashMap<String, DeprecatedFlagUsage>>,
+}
+
+impl DeprecatedFeatures {
+    #[cfg(feature = "remote")]
+    pub(crate) fn new(deny_list: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            deny_list: deny_list.into_iter().collect(),
+            in_use: Mutex::default(),
+        }
+    }
+
+    #[cfg(feature = "remote")]
+    pub(crate) fn is_deprecated(&self, feature_id: &str) -> bool {
+        self.deny_list.contains(feature_id)
+    }
+
+    /// Records that `feature_id` was evaluated from `call_site`.
+    pub(crate) fn record(&self, feature_id: &str, call_site: &Location<'_>) {
+        let mut in_use = self.in_use.lock_unpoisoned();
+        match in_use.get_mut(feature_id) {
+            Some(usage) => usage.evaluations += 1,
+            None => {
+                log::warn!(
+                    "Feature '{feature_id}' is deprecated and will be removed, but it is still evaluated at {call_site}"
+                );
+                in_use.insert(
+                    feature_id.to_string(),
+                    DeprecatedFlagUsage {
+                        feature_id: feature_id.to_string(),
+                        first_evaluated: chrono::Utc::now(),
+                        call_site: call_site.to_string(),
+                        evaluations: 1,
+                    },
+                );
+            }
+        }
+    }
+
+    /// The deprecated features evaluated so far, sorted by ID.
+    #[cfg(feature = "remote")]
+    pub(crate) fn in_use(&self) -> Vec<DeprecatedFlagUsage> {
+        let mut in_use: Vec<_> = self.in_use.lock_unpoisoned().values().cloned().collect();
+        in_use.sort_by(|a, b| a.feature_id.cmp(&b.feature_id));
+        in_use
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deprecated_features() {
+        let deprecated = DeprecatedFeatures::new(["old_checkout".to_string()]);
+        assert!(deprecated.is_deprecated("old_checkout"));
+        assert!(!deprecated.is_deprecated("new_checkout"));
+
+        let first_call_site = Location::caller();
+        deprecated.record("old_checkout", first_call_site);
+        deprecated.record("old_checkout", Location::caller());
+
+        let in_use = deprecated.in_use();
+        assert_eq!(
+            in_use
+                .iter()
+                .map(|usage| (usage.feature_id.as_str(), usage.evaluations))
+                .collect::<Vec<_>>(),
+            [("old_checkout", 2)]
+        );
+        assert_eq!(in_use[0].call_site, first_call_site.to_string());
+    }
+}
@@ -0,0 +1,121 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use crate::client::{ConfigurationProvider, PinnedConfiguration};
+use crate::entity::Entity;
+use crate::errors::Result;
+use crate::models::{FeatureEvaluationResult, PropertyEvaluationResult};
+use crate::{Feature, Property, Value};
+
+/// Evaluates features and properties for one entity against a pinned
+/// configuration, see [`ConfigurationProvider::evaluation_session`].
+///
+/// All the lookups of a session are consistent with each other, even if the
+/// client receives updates in the meantime. The ID and attributes of the
+/// entity are collected once, when the session is created.
+#[derive(Debug)]
+pub struct EvaluationSession<'a, C: ConfigurationProvider> {
+    configuration: PinnedConfiguration<'a, C>,
+    entity: CapturedEntity,
+}
+
+/// ID and attributes collected from an [`Entity`].
+#[derive(Debug)]
+struct CapturedEntity {
+    id: String,
+    attributes: HashMap<String, Value>,
+}
+
+impl Entity for CapturedEntity {
+    fn get_id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn get_attributes(&self) -> HashMap<String, Value> {
+        self.attributes.clone()
+    }
+}
+
+impl<'a, C: ConfigurationProvider> EvaluationSession<'a, C> {
+    pub(crate) fn new(
+        configuration: PinnedConfiguration<'a, C>,
+        entity: &(impl Entity + ?Sized),
+    ) -> Self {
+        Self {
+            configuration,
+            entity: CapturedEntity {
+                id: entity.get_id(),
+                attributes: entity.get_attributes(),
+            },
+        }
+    }
+
+    /// Returns the ID of the entity of the session.
+    pub fn entity_id(&self) -> &str {
+        &self.entity.id
+    }
+
+    /// Returns the configuration the session evaluates against.
+    pub fn configuration(&self) -> &PinnedConfiguration<'a, C> {
+        &self.configuration
+    }
+
+    /// Evaluates the feature for the entity of the session.
+    pub fn get_feature_value(&self, feature_id: &str) -> Result<FeatureEvaluationResult> {
+        self.configuration
+            .get_feature(feature_id)?
+            .get_current_value(&self.entity)
+    }
+
+    /// Evaluates the feature for the entity of the session and converts the value to `T`.
+    pub fn get_feature_value_into<T: TryFrom<Value, Error = crate::Error>>(
+        &self,
+        feature_id: &str,
+    ) -> Result<T> {
+        self.configuration
+            .get_feature(feature_id)?
+            .get_value_into(&self.entity)
+    }
+
+    /// Evaluates a `BOOLEAN` feature for the entity of the session.
+    pub fn get_bool(&self, feature_id: &str) -> Result<bool> {
+        self.configuration
+            .get_feature(feature_id)?
+            .get_bool_value(&self.entity)
+    }
+
+    /// Evaluates the property for the entity of the session.
+    pub fn get_property_value(&self, property_id: &str) -> Result<PropertyEvaluationResult> {
+        self.configuration
+            .get_property(property_id)?
+            .get_current_value(&self.entity)
+    }
+
+    /// Evaluates the property for the entity of the session and converts the value to `T`.
+    pub fn get_property_value_into<T: TryFrom<Value, Error = crate::Error>>(
+        &self,
+        property_id: &str,
+    ) -> Result<T> {
+        self.configuration
+            .get_property(property_id)?
+            .get_value_into(&self.entity)
+    }
+
+    /// Evaluates a `STRING` property for the entity of the session.
+    pub fn get_string(&self, property_id: &str) -> Result<String> {
+        self.get_property_value_into(property_id)
+    }
+}
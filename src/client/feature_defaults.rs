@@ -0,0 +1,148 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::errors::{ConfigurationDataError, Error, Result};
+use crate::models::FeatureSnapshot;
+use crate::utils::MutexExt;
+use crate::value::Value;
+
+/// Values returned for features missing from the configuration, by ID.
+///
+/// They cover deployments that reach production before the feature is created
+/// in the dashboard. Every time a default is used a counter is incremented, a
+/// warning is only logged the first time for each feature.
+#[derive(Debug, Default)]
+pub(crate) struct FeatureDefaults {
+    values: HashMap<String, Value>,
+    fallbacks: Mutex<HashMap<String, u64>>,
+}
+
+impl FeatureDefaults {
+    pub(crate) fn register(&mut self, feature_id: &str, value: Value) {
+        self.values.insert(feature_id.to_string(), value);
+    }
+
+    pub(crate) fn list(&self) -> &HashMap<String, Value> {
+        &self.values
+    }
+
+    /// Number of times the default of each feature was returned.
+    pub(crate) fn fallback_counts(&self) -> HashMap<String, u64> {
+        self.fallbacks.lock_unpoisoned().clone()
+    }
+
+    /// Replaces a "feature not found" error by the default registered for the
+    /// feature, if any. Other results are returned unchanged.
+    pub(crate) fn or_default(
+        &self,
+        feature_id: &str,
+        result: Result<FeatureSnapshot>,
+    ) -> Result<FeatureSnapshot> {
+        let err = match result {
+            Err(
+                err @ Error::ConfigurationDataError(ConfigurationDataError::FeatureNotFound(_)),
+            ) => err,
+            result => return result,
+        };
+        let Some(value) = self.values.get(feature_id) else {
+            return Err(err);
+        };
+        let mut fallbacks = self.fallbacks.lock_unpoisoned();
+        let count = fallbacks.entry(feature_id.to_string()).or_default();
+        if *count == 0 {
+            log::warn!(
+                "Feature '{feature_id}' not found in the configuration, returning its registered default"
+            );
+        }
+        *count += 1;
+        drop(fallbacks);
+        Ok(FeatureSnapshot::registered_default(
+            feature_id,
+            value.clone(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::models::Configuration;
+    use crate::{ConfigurationProvider, Feature};
+
+    #[test]
+    fn test_registered_default() {
+        let configuration = Configuration::default();
+        let mut defaults = FeatureDefaults::default();
+        defaults.register("new_checkout", Value::Boolean(false));
+
+        let feature = defaults
+            .or_default("new_checkout", configuration.get_feature("new_checkout"))
+            .unwrap();
+        assert_eq!(feature.get_feature_id().unwrap(), "new_checkout");
+        assert_eq!(feature.get_feature_data_type().unwrap(), "BOOLEAN");
+        assert!(!feature.is_enabled().unwrap());
+        let result = feature
+            .get_current_value(&crate::tests::GenericEntity {
+                id: "a1".to_string(),
+                attributes: HashMap::new(),
+            })
+            .unwrap();
+        assert_eq!(result.value, Value::Boolean(false));
+        assert_eq!(result.details.value_type, "REGISTERED_DEFAULT");
+
+        defaults
+            .or_default("new_checkout", configuration.get_feature("new_checkout"))
+            .unwrap();
+        assert_eq!(
+            defaults.fallback_counts(),
+            HashMap::from([("new_checkout".to_string(), 2)])
+        );
+
+        // Features without a default still fail
+        assert!(matches!(
+            defaults.or_default("other", configuration.get_feature("other")),
+            Err(Error::ConfigurationDataError(
+                ConfigurationDataError::FeatureNotFound(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_registered_list_default() {
+        let configuration = Configuration::default();
+        let mut defaults = FeatureDefaults::default();
+        defaults.register("ports", Value::from(vec![80u64, 443]));
+        defaults.register("tenants", Value::List(Vec::new()));
+        defaults.register(
+            "mixed",
+            Value::List(vec![Value::from(80u64), Value::from("a".to_string())]),
+        );
+
+        let data_type = |feature_id| {
+            defaults
+                .or_default(feature_id, configuration.get_feature(feature_id))
+                .unwrap()
+                .get_feature_data_type()
+                .unwrap()
+        };
+        assert_eq!(data_type("ports"), "NUMERIC");
+        // The items give no type
+        assert_eq!(data_type("tenants"), "UNKNOWN");
+        assert_eq!(data_type("mixed"), "UNKNOWN");
+    }
+}
@@ -0,0 +1,108 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::errors::{Error, Result};
+
+/// Client-side declaration of prerequisite relationships between features.
+///
+/// A feature only evaluates as enabled for an entity if all its prerequisites
+/// (transitively) are enabled for that same entity. The graph is kept acyclic:
+/// declarations that would introduce a cycle are rejected.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct FeaturePrerequisites {
+    prerequisites: HashMap<String, Vec<String>>,
+}
+
+impl FeaturePrerequisites {
+    /// Declares `prerequisite_id` as a prerequisite of `feature_id`.
+    pub(crate) fn add(&mut self, feature_id: &str, prerequisite_id: &str) -> Result<()> {
+        if self.depends_on(prerequisite_id, feature_id) {
+            return Err(Error::FeaturePrerequisiteCycle {
+                feature_id: feature_id.to_string(),
+                prerequisite_id: prerequisite_id.to_string(),
+            });
+        }
+
+        let prerequisites = self
+            .prerequisites
+            .entry(feature_id.to_string())
+            .or_default();
+        if !prerequisites.iter().any(|id| id == prerequisite_id) {
+            prerequisites.push(prerequisite_id.to_string());
+        }
+        Ok(())
+    }
+
+    /// Returns the direct prerequisites of `feature_id`.
+    pub(crate) fn get(&self, feature_id: &str) -> &[String] {
+        self.prerequisites
+            .get(feature_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Returns whether `feature_id` is, or transitively depends on, `target_id`.
+    fn depends_on(&self, feature_id: &str, target_id: &str) -> bool {
+        let mut visited = HashSet::new();
+        let mut pending = vec![feature_id];
+        while let Some(current) = pending.pop() {
+            if current == target_id {
+                return true;
+            }
+            if visited.insert(current) {
+                pending.extend(self.get(current).iter().map(String::as_str));
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_prerequisites() {
+        let mut prerequisites = FeaturePrerequisites::default();
+        prerequisites.add("child", "parent").unwrap();
+        prerequisites.add("child", "other").unwrap();
+        prerequisites.add("child", "parent").unwrap();
+
+        assert_eq!(prerequisites.get("child"), ["parent", "other"]);
+        assert!(prerequisites.get("parent").is_empty());
+    }
+
+    #[test]
+    fn test_cycles_are_rejected() {
+        let mut prerequisites = FeaturePrerequisites::default();
+        prerequisites.add("c", "b").unwrap();
+        prerequisites.add("b", "a").unwrap();
+
+        for (feature_id, prerequisite_id) in [("a", "c"), ("a", "b"), ("a", "a")] {
+            let err = prerequisites.add(feature_id, prerequisite_id).unwrap_err();
+            assert!(matches!(
+                err,
+                Error::FeaturePrerequisiteCycle { feature_id: ref f, prerequisite_id: ref p }
+                    if f == feature_id && p == prerequisite_id
+            ));
+        }
+        assert!(prerequisites.get("a").is_empty());
+
+        // Diamonds are fine
+        prerequisites.add("d", "b").unwrap();
+        prerequisites.add("d", "c").unwrap();
+    }
+}
@@ -12,26 +12,66 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
 use super::AppConfigurationClient;
+use super::proxy_client::ProxyClient;
 use crate::entity::Entity;
 use crate::models::FeatureSnapshot;
 use crate::value::Value;
-use crate::{Feature, FeatureEvaluationResult};
+use crate::{Feature, FeatureEvaluationResult, Variation};
 /// Provides live-updated data for a given [`Feature`].
+///
+/// Unlike the snapshots returned by `get_feature`, every call reads the feature from the client,
+/// so evaluations always use the latest configuration received.
+#[derive(Clone)]
 pub struct FeatureProxy<'a> {
-    client: &'a dyn AppConfigurationClient,
+    client: ProxyClient<'a>,
     feature_id: String,
 }
 
 impl<'a> FeatureProxy<'a> {
     pub(crate) fn new(client: &'a dyn AppConfigurationClient, feature_id: String) -> Self {
-        Self { client, feature_id }
+        Self {
+            client: ProxyClient::Borrowed(client),
+            feature_id,
+        }
     }
 
-    /// Take a snapshot of this proxied property
+    /// Take a snapshot of this proxied feature
     pub fn snapshot(&self) -> crate::errors::Result<FeatureSnapshot> {
         self.client.get_feature(&self.feature_id)
     }
+
+    /// Evaluates a `BOOLEAN` feature for the entity, like the `get_bool_value` of feature snapshots.
+    #[track_caller]
+    pub fn get_bool_value(&self, entity: &(impl Entity + ?Sized)) -> crate::errors::Result<bool> {
+        self.client
+            .get_feature(&self.feature_id)?
+            .get_bool_value(entity)
+    }
+
+    /// Returns the variation served to the entity, like the `get_variation` of feature snapshots.
+    #[track_caller]
+    pub fn get_variation(
+        &self,
+        entity: &(impl Entity + ?Sized),
+    ) -> crate::errors::Result<Variation> {
+        self.client
+            .get_feature(&self.feature_id)?
+            .get_variation(entity)
+    }
+}
+
+impl FeatureProxy<'static> {
+    /// Returns a proxy that shares ownership of the client instead of borrowing it,
+    /// so it can be kept in long-lived objects.
+    pub fn shared(client: Arc<dyn AppConfigurationClient>, feature_id: impl Into<String>) -> Self {
+        Self {
+            client: ProxyClient::Shared(client),
+            feature_id: feature_id.into(),
+        }
+    }
 }
 
 impl Feature for FeatureProxy<'_> {
@@ -61,21 +101,35 @@ impl Feature for FeatureProxy<'_> {
             .get_feature_data_format()
     }
 
+    #[track_caller]
     fn get_current_value(
         &self,
-        entity: &impl Entity,
+        entity: &(impl Entity + ?Sized),
     ) -> crate::errors::Result<FeatureEvaluationResult> {
         self.client
             .get_feature(&self.feature_id)?
             .get_current_value(entity)
     }
 
+    #[track_caller]
     fn get_value_into<T: TryFrom<Value, Error = crate::Error>>(
         &self,
-        entity: &impl Entity,
+        entity: &(impl Entity + ?Sized),
     ) -> crate::errors::Result<T> {
         self.client
             .get_feature(&self.feature_id)?
             .get_value_into(entity)
     }
+
+    #[track_caller]
+    fn get_values_for(&self, entities: &[&dyn Entity]) -> Vec<crate::errors::Result<Value>> {
+        match self.client.get_feature(&self.feature_id) {
+            Ok(feature) => feature.get_values_for(entities),
+            // Every evaluation fails with its own error
+            Err(_) => entities
+                .iter()
+                .map(|entity| self.get_current_value(*entity).map(|r| r.value))
+                .collect(),
+        }
+    }
 }
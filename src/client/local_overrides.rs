@@ -0,0 +1,128 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::errors::{DeserializationError, Error, Result};
+use crate::value::Value;
+
+/// Values forced locally for features and properties, by ID.
+///
+/// They take precedence over the configuration received from the server, so a
+/// flag can be turned on for local development without touching the dashboard.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct LocalOverrides {
+    values: HashMap<String, Value>,
+}
+
+impl LocalOverrides {
+    pub(crate) fn set(&mut self, id: &str, value: Value) {
+        self.values.insert(id.to_string(), value);
+    }
+
+    pub(crate) fn remove(&mut self, id: &str) -> Option<Value> {
+        self.values.remove(id)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    pub(crate) fn get(&self, id: &str) -> Option<&Value> {
+        self.values.get(id)
+    }
+
+    pub(crate) fn list(&self) -> &HashMap<String, Value> {
+        &self.values
+    }
+
+    /// Adds the overrides in a JSON file containing an object that maps IDs to
+    /// boolean, numeric or string values, e.g. `{"dark-mode": true}`.
+    ///
+    /// Nothing is added if any of the values is invalid.
+    pub(crate) fn load_file(&mut self, path: &Path) -> Result<()> {
+        let content = std::fs::read_to_string(path).map_err(|_| {
+            Error::Other(format!(
+                "File '{}' doesn't exist or cannot be read",
+                path.display()
+            ))
+        })?;
+        let json: HashMap<String, serde_json::Value> =
+            serde_json::from_str(&content).map_err(|e| DeserializationError {
+                string: format!(
+                    "Error deserializing local overrides from '{}'",
+                    path.display()
+                ),
+                source: e.into(),
+            })?;
+        let values = json
+            .into_iter()
+            .map(|(id, value)| {
+                let value = Value::try_from(value).map_err(|_| {
+                    Error::Other(format!(
+                        "Local override for '{id}' must be a boolean, number, string or list"
+                    ))
+                })?;
+                Ok((id, value))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.values.extend(values);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_remove_clear() {
+        let mut overrides = LocalOverrides::default();
+        overrides.set("f1", Value::Boolean(true));
+        overrides.set("p1", Value::Int64(1));
+        overrides.set("f1", Value::Boolean(false));
+
+        assert_eq!(overrides.get("f1"), Some(&Value::Boolean(false)));
+        assert_eq!(overrides.list().len(), 2);
+        assert_eq!(overrides.remove("p1"), Some(Value::Int64(1)));
+        assert_eq!(overrides.remove("p1"), None);
+
+        overrides.clear();
+        assert!(overrides.list().is_empty());
+    }
+
+    #[test]
+    fn test_load_file() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let path = dir.path().join("overrides.json");
+        std::fs::write(&path, r#"{"f1": true, "p1": "local"}"#).unwrap();
+        let mut overrides = LocalOverrides::default();
+        overrides.load_file(&path).unwrap();
+        assert_eq!(overrides.get("f1"), Some(&Value::Boolean(true)));
+        assert_eq!(overrides.get("p1"), Some(&Value::String("local".into())));
+
+        let path = dir.path().join("invalid.json");
+        std::fs::write(&path, r#"{"f2": true, "p2": {"a": 1}}"#).unwrap();
+        let err = overrides.load_file(&path).unwrap_err();
+        assert!(matches!(err, Error::Other(ref msg) if msg.contains("'p2'")));
+        assert_eq!(overrides.get("f2"), None);
+
+        let err = overrides
+            .load_file(&dir.path().join("missing.json"))
+            .unwrap_err();
+        assert!(matches!(err, Error::Other(_)));
+    }
+}
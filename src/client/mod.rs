@@ -12,20 +12,48 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "remote")]
 mod app_configuration;
 mod app_configuration_client;
+#[cfg(feature = "remote")]
 pub(crate) mod app_configuration_http;
+#[cfg(feature = "remote")]
 mod app_configuration_ibm_cloud;
 mod app_configuration_offline;
+mod combinators;
+mod evaluation_session;
 
+mod deprecated_features;
+#[cfg(feature = "remote")]
+mod feature_defaults;
+#[cfg(feature = "remote")]
+mod feature_prerequisites;
 pub(crate) mod feature_proxy;
+#[cfg(feature = "remote")]
+mod local_overrides;
+mod pinned_configuration;
+mod prewarm;
 pub(crate) mod property_proxy;
+mod proxy_client;
 
 pub use app_configuration_client::{
-    AppConfigurationClient, ConfigurationId, ConfigurationProvider, RuntimeEvent,
-    RuntimeEventEmitter, RuntimeEventKind, RuntimeEventListener, RuntimeMode, RuntimeStatus,
+    AppConfigurationClient, ConfigurationId, ConfigurationProvider, DeprecatedFlagUsage, Exposure,
+    ExposureListener, LatencyHistogram, RuntimeEvent, RuntimeEventEmitter, RuntimeEventKind,
+    RuntimeEventListener, RuntimeMode, RuntimeStatus, SdkError, SdkErrorListener, SdkErrorSnapshot,
+    SdkStats, SyncRecord, UnusedFeatures,
 };
 
+#[cfg(feature = "remote")]
 pub use app_configuration::{AppConfiguration, AppConfigurationContextOptions};
-pub use app_configuration_ibm_cloud::{AppConfigurationClientIBMCloud, ResolvedUrls};
-pub use app_configuration_offline::AppConfigurationOffline;
+#[cfg(feature = "remote")]
+pub use app_configuration_ibm_cloud::{
+    AppConfigurationClientIBMCloud, Authenticator, ResolvedUrls,
+};
+pub use app_configuration_offline::{AppConfigurationOffline, ConfigurationDump};
+pub use combinators::{Layered, Overrides};
+pub(crate) use deprecated_features::DeprecatedFeatures;
+pub use evaluation_session::EvaluationSession;
+pub use feature_proxy::FeatureProxy;
+pub use pinned_configuration::PinnedConfiguration;
+pub use prewarm::PrewarmReport;
+pub use property_proxy::PropertyProxy;
@@ -0,0 +1,137 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::client::{
+    ConfigurationProvider, DeprecatedFlagUsage, Exposure, RuntimeStatus, SdkErrorSnapshot,
+    SdkStats, SyncRecord, UnusedFeatures,
+};
+use crate::errors::Result;
+use crate::models::{
+    Configuration, FeatureSnapshot, PropertySnapshot, SecretPropertySnapshot, SegmentSnapshot,
+};
+use crate::utils::Ready;
+use std::sync::Arc;
+
+/// Configuration pinned by [`ConfigurationProvider::pin_configuration`].
+///
+/// All the features and properties retrieved through the guard are evaluated
+/// against the configuration that was current when it was pinned, even if the
+/// client receives updates in the meantime. Use it to keep a multi-step
+/// transaction consistent. The client keeps applying its own settings (usage
+/// metering, local overrides, prerequisites...) to these evaluations.
+///
+/// The configuration is released when the guard is dropped.
+#[derive(Debug)]
+pub struct PinnedConfiguration<'a, C: ConfigurationProvider> {
+    client: &'a C,
+    configuration: Arc<Configuration>,
+}
+
+impl<'a, C: ConfigurationProvider> PinnedConfiguration<'a, C> {
+    pub(crate) fn new(client: &'a C, configuration: Arc<Configuration>) -> Self {
+        Self {
+            client,
+            configuration,
+        }
+    }
+
+    /// Returns the pinned configuration.
+    pub fn configuration(&self) -> &Configuration {
+        &self.configuration
+    }
+}
+
+impl<C: ConfigurationProvider> ConfigurationProvider for PinnedConfiguration<'_, C> {
+    fn get_feature_ids(&self) -> Result<Vec<String>> {
+        self.configuration.get_feature_ids()
+    }
+
+    fn get_feature(&self, feature_id: &str) -> Result<FeatureSnapshot> {
+        self.client
+            .get_feature_from(&self.configuration, feature_id)
+    }
+
+    fn get_property_ids(&self) -> Result<Vec<String>> {
+        self.configuration.get_property_ids()
+    }
+
+    fn get_property(&self, property_id: &str) -> Result<PropertySnapshot> {
+        self.client
+            .get_property_from(&self.configuration, property_id)
+    }
+
+    fn get_segment_ids(&self) -> Result<Vec<String>> {
+        self.configuration.get_segment_ids()
+    }
+
+    fn get_segment(&self, segment_id: &str) -> Result<SegmentSnapshot> {
+        self.configuration.get_segment(segment_id)
+    }
+
+    fn get_configuration_snapshot(&self) -> Result<Configuration> {
+        Ok(Configuration::clone(&self.configuration))
+    }
+
+    fn get_shared_configuration(&self) -> Result<Arc<Configuration>> {
+        Ok(self.configuration.clone())
+    }
+
+    fn is_online(&self) -> Result<bool> {
+        self.client.is_online()
+    }
+
+    fn wait_until_online(&self) -> bool {
+        self.client.wait_until_online()
+    }
+
+    fn get_secret_property(&self, property_id: &str) -> Result<SecretPropertySnapshot> {
+        self.configuration.get_secret_property(property_id)
+    }
+
+    fn is_connected(&self) -> Result<bool> {
+        self.client.is_connected()
+    }
+
+    fn get_runtime_status(&self) -> Result<Option<RuntimeStatus>> {
+        self.client.get_runtime_status()
+    }
+
+    fn last_error(&self) -> Result<Option<SdkErrorSnapshot>> {
+        self.client.last_error()
+    }
+
+    fn sdk_stats(&self) -> Result<Option<SdkStats>> {
+        self.client.sdk_stats()
+    }
+
+    fn ready(&self) -> Ready {
+        self.client.ready()
+    }
+
+    fn unused_features(&self) -> Result<Option<UnusedFeatures>> {
+        self.client.unused_features()
+    }
+
+    fn deprecated_flags_in_use(&self) -> Result<Vec<DeprecatedFlagUsage>> {
+        self.client.deprecated_flags_in_use()
+    }
+
+    fn sync_history(&self) -> Result<Vec<SyncRecord>> {
+        self.client.sync_history()
+    }
+
+    fn record_exposure(&self, exposure: Exposure) -> Result<()> {
+        self.client.record_exposure(exposure)
+    }
+}
@@ -0,0 +1,104 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use crate::errors::{ConfigurationDataError, Error, Result};
+use crate::models::FeatureSnapshot;
+use crate::{ConfigurationProvider, Entity, Feature};
+
+/// Result of [`ConfigurationProvider::prewarm`].
+#[derive(Debug, Default)]
+pub struct PrewarmReport {
+    features: HashMap<String, FeatureSnapshot>,
+    /// Features that are not in the configuration.
+    pub missing: Vec<String>,
+    /// Features whose values do not match their declared type.
+    pub type_mismatches: Vec<String>,
+}
+
+impl PrewarmReport {
+    /// Returns `true` if all the features were retrieved and evaluated.
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.type_mismatches.is_empty()
+    }
+
+    /// Returns the snapshot retrieved for the feature, if it could be evaluated.
+    ///
+    /// Snapshots do not receive updates from the server, see
+    /// [`ConfigurationProvider::get_feature`].
+    pub fn get_feature(&self, feature_id: &str) -> Option<&FeatureSnapshot> {
+        self.features.get(feature_id)
+    }
+
+    /// IDs of the features that were retrieved and evaluated.
+    pub fn get_feature_ids(&self) -> Vec<&String> {
+        self.features.keys().collect()
+    }
+}
+
+pub(crate) fn prewarm<P: ConfigurationProvider>(
+    provider: &P,
+    feature_ids: &[&str],
+    entity: &(impl Entity + ?Sized),
+) -> Result<PrewarmReport> {
+    let mut report = PrewarmReport::default();
+    for feature_id in feature_ids {
+        let evaluated = provider.get_feature(feature_id).and_then(|feature| {
+            feature.get_current_value(entity)?;
+            Ok(feature)
+        });
+        match evaluated {
+            Ok(feature) => {
+                report.features.insert(feature_id.to_string(), feature);
+            }
+            Err(Error::ConfigurationDataError(ConfigurationDataError::FeatureNotFound(_))) => {
+                report.missing.push(feature_id.to_string());
+            }
+            Err(Error::MismatchType) => report.type_mismatches.push(feature_id.to_string()),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::network::serialization::fixtures::example_configuration_enterprise;
+    use crate::{Configuration, ConfigurationProvider, Feature, Value};
+
+    #[rstest]
+    fn test_prewarm(example_configuration_enterprise: Configuration) {
+        let entity = crate::entity::tests::TrivialEntity;
+        let mut configuration = example_configuration_enterprise;
+        let (feature, _) = configuration.features.get_mut("f2").unwrap();
+        feature.enabled_value = serde_json::from_value(serde_json::json!(3)).unwrap();
+
+        let report = configuration
+            .prewarm(&["f1", "f2", "does-not-exist"], &entity)
+            .unwrap();
+        assert!(!report.is_ok());
+        assert_eq!(report.get_feature_ids(), ["f1"]);
+        assert_eq!(report.missing, ["does-not-exist"]);
+        assert_eq!(report.type_mismatches, ["f2"]);
+
+        let feature = report.get_feature("f1").unwrap();
+        assert_eq!(
+            feature.get_current_value(&entity).unwrap().value,
+            Value::Int64(5)
+        );
+    }
+}
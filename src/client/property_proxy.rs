@@ -14,21 +14,28 @@
 
 use crate::Property;
 
+use std::sync::Arc;
+
 use super::AppConfigurationClient;
+use super::proxy_client::ProxyClient;
 use crate::models::PropertySnapshot;
 use crate::value::Value;
 use crate::{Entity, PropertyEvaluationResult};
 
 /// Provides live-updated data for a given [`Property`].
+///
+/// Unlike the snapshots returned by `get_property`, every call reads the property from the client,
+/// so evaluations always use the latest configuration received.
+#[derive(Clone)]
 pub struct PropertyProxy<'a> {
-    client: &'a dyn AppConfigurationClient,
+    client: ProxyClient<'a>,
     property_id: String,
 }
 
 impl<'a> PropertyProxy<'a> {
     pub(crate) fn new(client: &'a dyn AppConfigurationClient, property_id: String) -> Self {
         Self {
-            client,
+            client: ProxyClient::Borrowed(client),
             property_id,
         }
     }
@@ -39,6 +46,17 @@ impl<'a> PropertyProxy<'a> {
     }
 }
 
+impl PropertyProxy<'static> {
+    /// Returns a proxy that shares ownership of the client instead of borrowing it,
+    /// so it can be kept in long-lived objects.
+    pub fn shared(client: Arc<dyn AppConfigurationClient>, property_id: impl Into<String>) -> Self {
+        Self {
+            client: ProxyClient::Shared(client),
+            property_id: property_id.into(),
+        }
+    }
+}
+
 impl Property for PropertyProxy<'_> {
     fn get_property_name(&self) -> crate::errors::Result<String> {
         self.client
@@ -66,16 +84,22 @@ impl Property for PropertyProxy<'_> {
 
     fn get_current_value(
         &self,
-        entity: &impl Entity,
+        entity: &(impl Entity + ?Sized),
     ) -> crate::errors::Result<PropertyEvaluationResult> {
         self.client
             .get_property(&self.property_id)?
             .get_current_value(entity)
     }
 
+    fn get_static_value(&self, strict: bool) -> crate::errors::Result<Value> {
+        self.client
+            .get_property(&self.property_id)?
+            .get_static_value(strict)
+    }
+
     fn get_value_into<T: TryFrom<Value, Error = crate::Error>>(
         &self,
-        entity: &impl Entity,
+        entity: &(impl Entity + ?Sized),
     ) -> crate::errors::Result<T> {
         self.client
             .get_property(&self.property_id)?
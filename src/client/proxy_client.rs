@@ -0,0 +1,39 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ops::Deref;
+use std::sync::Arc;
+
+use super::AppConfigurationClient;
+
+/// Client the proxies read from on every call.
+///
+/// Proxies either borrow the client, or share ownership of it so they can be
+/// stored in long-lived objects.
+#[derive(Clone)]
+pub(crate) enum ProxyClient<'a> {
+    Borrowed(&'a dyn AppConfigurationClient),
+    Shared(Arc<dyn AppConfigurationClient>),
+}
+
+impl<'a> Deref for ProxyClient<'a> {
+    type Target = dyn AppConfigurationClient + 'a;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            ProxyClient::Borrowed(client) => *client,
+            ProxyClient::Shared(client) => client.as_ref(),
+        }
+    }
+}
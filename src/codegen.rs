@@ -0,0 +1,325 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generation of typed accessors for the features and properties of a
+//! configuration dump, meant to be called from a build script.
+//!
+//! Every feature and property gets a function named after its ID (in snake case)
+//! that evaluates it and returns the value with the matching Rust type, so a flag
+//! removed from the configuration fails to compile instead of failing at runtime:
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() {
+//!     println!("cargo::rerun-if-changed=appconfiguration.json");
+//!     let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("flags.rs");
+//!     ibm_appconfiguration_rust_sdk::codegen::write(
+//!         "appconfiguration.json".as_ref(),
+//!         "dev",
+//!         "blue-charge",
+//!         &out,
+//!     )
+//!     .unwrap();
+//! }
+//!
+//! // src/flags.rs
+//! include!(concat!(env!("OUT_DIR"), "/flags.rs"));
+//!
+//! // Anywhere else
+//! let enabled: bool = flags::ab_testing_feature(&client, &entity)?;
+//! ```
+//!
+//! `BOOLEAN` values map to `bool`, `STRING` values to `String` and `NUMERIC`
+//! values to `i64`, or `f64` if any of the values in the dump is not an integer.
+//! Secret properties are skipped, they are resolved with
+//! [`ConfigurationProvider::get_secret_property`](crate::ConfigurationProvider::get_secret_property).
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::errors::{Error, Result};
+use crate::models::Configuration;
+use crate::network::serialization::ValueType;
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Generates the accessors for the features and properties of the dump at
+/// `dump` that belong to the given environment and collection.
+pub fn generate(dump: &Path, environment_id: &str, collection_id: &str) -> Result<String> {
+    generate_from_configuration(&Configuration::from_file(
+        dump,
+        environment_id,
+        collection_id,
+    )?)
+}
+
+/// Like [`generate`], writing the module to `out`. The file is left untouched if
+/// its content does not change, to avoid needless rebuilds.
+pub fn write(dump: &Path, environment_id: &str, collection_id: &str, out: &Path) -> Result<()> {
+    let code = generate(dump, environment_id, collection_id)?;
+    if std::fs::read_to_string(out).is_ok_and(|current| current == code) {
+        return Ok(());
+    }
+    std::fs::write(out, code)
+        .map_err(|e| Error::Other(format!("Cannot write '{}': {e}", out.display())))
+}
+
+/// Generates the accessors for the features and properties of `configuration`.
+pub fn generate_from_configuration(configuration: &Configuration) -> Result<String> {
+    let mut accessors: Vec<Accessor> = configuration
+        .features
+        .values()
+        .map(|(feature, _)| Accessor {
+            kind: "feature",
+            id: feature.feature_id.to_string(),
+            name: feature.name.to_string(),
+            value_type: feature.r#type,
+            is_integer: [&feature.enabled_value, &feature.disabled_value]
+                .into_iter()
+                .chain(feature.segment_rules.iter().map(|rule| &rule.value))
+                .filter(|value| !value.is_default())
+                .all(|value| value.as_i64().is_some()),
+        })
+        .chain(configuration.properties.values().map(|(property, _)| {
+            Accessor {
+                kind: "property",
+                id: property.property_id.clone(),
+                name: property.name.clone(),
+                value_type: property.r#type,
+                is_integer: std::iter::once(&property.value)
+                    .chain(property.segment_rules.iter().map(|rule| &rule.value))
+                    .filter(|value| !value.is_default())
+                    .all(|value| value.as_i64().is_some()),
+            }
+        }))
+        .filter(|accessor| accessor.value_type != ValueType::SecretRef)
+        .collect();
+    accessors.sort_by(|a, b| (a.kind, &a.id).cmp(&(b.kind, &b.id)));
+
+    let mut functions: HashMap<String, &Accessor> = HashMap::new();
+    for accessor in &accessors {
+        let function = function_name(&accessor.id)?;
+        if let Some(other) = functions.insert(function.clone(), accessor) {
+            return Err(Error::Other(format!(
+                "The {} '{}' and the {} '{}' would both generate the function '{function}'",
+                other.kind, other.id, accessor.kind, accessor.id
+            )));
+        }
+    }
+
+    let mut code = String::from(
+        "// Generated by ibm-appconfiguration-rust-sdk from a configuration dump. Do not edit.\n\n\
+         #[allow(unused_imports)]\n\
+         use ibm_appconfiguration_rust_sdk::{\n    \
+         ConfigurationProvider, Entity, Feature, Property, Result, Value,\n};\n",
+    );
+    for accessor in &accessors {
+        let function = function_name(&accessor.id)?;
+        let getter = match accessor.kind {
+            "feature" => format!("client.get_feature({:?})?", accessor.id),
+            _ => format!("client.get_property({:?})?", accessor.id),
+        };
+        let (return_type, body) = match accessor.value_type {
+            ValueType::Boolean if accessor.kind == "feature" => {
+                ("bool", format!("{getter}.get_bool_value(entity)"))
+            }
+            ValueType::Boolean => ("bool", format!("{getter}.get_value_into(entity)")),
+            ValueType::String => ("String", format!("{getter}.get_value_into(entity)")),
+            ValueType::Numeric if accessor.is_integer => {
+                ("i64", format!("{getter}.get_value_into(entity)"))
+            }
+            // Converted inline rather than by a helper, which could collide
+            // with the accessors and be dead code when they are not used
+            ValueType::Numeric => (
+                "f64",
+                format!(
+                    "match {getter}.get_current_value(entity)?.value {{\n        \
+                     Value::Float64(v) => Ok(v),\n        \
+                     Value::Int64(v) => Ok(v as f64),\n        \
+                     Value::UInt64(v) => Ok(v as f64),\n        \
+                     _ => Err(ibm_appconfiguration_rust_sdk::Error::MismatchType),\n    \
+                     }}"
+                ),
+            ),
+            // Secret references are filtered out above, resources of unknown
+            // types are skipped when loading a configuration
+            ValueType::SecretRef | ValueType::Unknown => {
+                return Err(Error::Other(format!(
+                    "Cannot generate an accessor for the {} '{}' of type {}",
+                    accessor.kind, accessor.id, accessor.value_type
+                )));
+            }
+        };
+        let _ = write!(
+            code,
+            "\n/// Evaluates the {} \"{}\" (`{}`).\n\
+             #[allow(dead_code)]\n\
+             pub fn {function}(client: &impl ConfigurationProvider, entity: &impl Entity) -> Result<{return_type}> {{\n    \
+             {body}\n}}\n",
+            accessor.kind,
+            accessor.name.replace(['\n', '\r'], " "),
+            accessor.id,
+        );
+    }
+    Ok(code)
+}
+
+struct Accessor {
+    kind: &'static str,
+    id: String,
+    name: String,
+    value_type: ValueType,
+    is_integer: bool,
+}
+
+/// Converts an ID like `ab-testing-feature` or `abTestingFeature` into a valid
+/// function name like `ab_testing_feature`.
+fn function_name(id: &str) -> Result<String> {
+    let mut name = String::with_capacity(id.len());
+    let mut previous_lowercase = false;
+    for c in id.chars() {
+        if c.is_ascii_alphanumeric() {
+            if c.is_ascii_uppercase() && previous_lowercase {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+            previous_lowercase = c.is_ascii_lowercase() || c.is_ascii_digit();
+        } else {
+            if !name.ends_with('_') {
+                name.push('_');
+            }
+            previous_lowercase = false;
+        }
+    }
+    let name = name.trim_matches('_');
+    if name.is_empty() {
+        return Err(Error::Other(format!(
+            "Cannot generate a function name for '{id}'"
+        )));
+    }
+    Ok(if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{name}")
+    } else if KEYWORDS.contains(&name) {
+        format!("{name}_")
+    } else {
+        name.to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::serialization::fixtures::example_configuration_enterprise_path;
+
+    #[test]
+    fn test_function_name() {
+        assert_eq!(
+            function_name("ab-testing-feature").unwrap(),
+            "ab_testing_feature"
+        );
+        assert_eq!(
+            function_name("abTestingFeature").unwrap(),
+            "ab_testing_feature"
+        );
+        assert_eq!(function_name("API key.v2").unwrap(), "api_key_v2");
+        assert_eq!(function_name("2fa").unwrap(), "_2fa");
+        assert_eq!(function_name("type").unwrap(), "type_");
+        assert!(function_name("--").is_err());
+    }
+
+    // The example includes the module generated from the enterprise dump, which
+    // makes sure the generated code compiles.
+    #[test]
+    fn test_generate_matches_example() {
+        let code = generate(
+            &example_configuration_enterprise_path(),
+            "dev",
+            "blue-charge",
+        )
+        .unwrap();
+        assert_eq!(code, include_str!("../examples/typed_flags/flags.rs"));
+    }
+
+    #[test]
+    fn test_float_values() {
+        let mut configuration = Configuration::from_file(
+            &example_configuration_enterprise_path(),
+            "dev",
+            "blue-charge",
+        )
+        .unwrap();
+        let (property, _) = configuration.properties.get_mut("p1").unwrap();
+        property.value = serde_json::from_str("2.5").unwrap();
+
+        let code = generate_from_configuration(&configuration).unwrap();
+        assert!(code.contains(
+            "pub fn p1(client: &impl ConfigurationProvider, entity: &impl Entity) -> Result<f64> {\n    \
+             match client.get_property(\"p1\")?.get_current_value(entity)?.value {\n        \
+             Value::Float64(v) => Ok(v),\n        \
+             Value::Int64(v) => Ok(v as f64),\n        \
+             Value::UInt64(v) => Ok(v as f64),\n        \
+             _ => Err(ibm_appconfiguration_rust_sdk::Error::MismatchType),\n    \
+             }\n}"
+        ));
+        assert!(code.contains(
+            "pub fn f1(client: &impl ConfigurationProvider, entity: &impl Entity) -> Result<i64> {"
+        ));
+    }
+
+    #[test]
+    fn test_unknown_type() {
+        let mut configuration = Configuration::from_file(
+            &example_configuration_enterprise_path(),
+            "dev",
+            "blue-charge",
+        )
+        .unwrap();
+        let (feature, _) = configuration.features.get_mut("f1").unwrap();
+        feature.r#type = ValueType::Unknown;
+
+        let err = generate_from_configuration(&configuration).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Cannot generate an accessor for the feature 'f1' of type UNKNOWN"
+        );
+    }
+
+    #[test]
+    fn test_name_collision() {
+        let mut configuration = Configuration::from_file(
+            &example_configuration_enterprise_path(),
+            "dev",
+            "blue-charge",
+        )
+        .unwrap();
+        let (mut property, rules) = configuration.properties["p1"].clone();
+        property.property_id = "P1".into();
+        configuration
+            .properties
+            .insert("P1".into(), (property, rules));
+
+        let err = generate_from_configuration(&configuration).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The property 'P1' and the property 'p1' would both generate the function 'p1'"
+        );
+    }
+}
@@ -0,0 +1,542 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Local server sharing one client with other processes on the same host.
+//!
+//! Services written in other languages, or several instances of the same one,
+//! can evaluate features and properties through a single client instead of each
+//! keeping its own websocket to the service and its own copy of the configuration:
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//!
+//! use ibm_appconfiguration_rust_sdk::{
+//!     AppConfigurationClientIBMCloud, ConfigurationId, OfflineMode, Result, RuntimeEventEmitter,
+//! };
+//!
+//! # fn func() -> Result<()> {
+//! # let apikey: &str = "api_key";
+//! # let region: &str = "us-south";
+//! # let guid: String = "12345678-1234-1234-1234-12345678abcd".to_string();
+//! # let environment_id: String = "production".to_string();
+//! # let collection_id: String = "ecommerce".to_string();
+//! let configuration = ConfigurationId::new(guid, environment_id, collection_id)?;
+//! let client = AppConfigurationClientIBMCloud::new(&apikey, &region, configuration, OfflineMode::Fail, false, Default::default(), RuntimeEventEmitter::new())?;
+//! ibm_appconfiguration_rust_sdk::daemon::serve(Arc::new(client), "/run/appconfiguration.sock")?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The protocol is line-delimited JSON over a Unix domain socket. Each line sent
+//! by the peer is a request and is answered with a single line:
+//!
+//! ```text
+//! > {"op": "feature", "id": "f1", "entity_id": "user-1", "attributes": {"country": "ES"}}
+//! < {"value": 5, "isEnabled": true, "details": {...}}
+//! > {"op": "property", "id": "p1", "entity_id": "user-1"}
+//! < {"value": "v", "details": {...}}
+//! > {"op": "config"}
+//! < {"online": true, "features": ["f1", "f2"], "properties": ["p1"]}
+//! ```
+//!
+//! Evaluations answer with the same JSON as [`FeatureEvaluationResult`] and
+//! [`PropertyEvaluationResult`]. `attributes` is optional. Requests that cannot be
+//! parsed or answered get `{"error": "<message>"}`, and the connection stays open.
+//!
+//! Only the owner of the socket can connect by default, and the number of
+//! connections and the length of the requests are limited, see
+//! [`DaemonOptions`]. [`Daemon`] can be stopped with a [`ShutdownHandle`].
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::errors::{Error, Result};
+use crate::utils::MutexExt;
+use crate::{AppConfigurationClient, Entity, Feature, Property, Value};
+
+#[cfg(doc)]
+use crate::{FeatureEvaluationResult, PropertyEvaluationResult};
+
+/// Limits and permissions of a [`Daemon`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DaemonOptions {
+    max_connections: usize,
+    max_request_length: usize,
+    socket_mode: u32,
+}
+
+impl Default for DaemonOptions {
+    /// 64 connections, requests of 64 KiB and a socket only its owner can use.
+    fn default() -> Self {
+        Self {
+            max_connections: 64,
+            max_request_length: 64 * 1024,
+            socket_mode: 0o600,
+        }
+    }
+}
+
+impl DaemonOptions {
+    /// Connections served at the same time, each from its own thread. Other
+    /// connections are answered with an error and closed.
+    pub fn with_max_connections(mut self, max_connections: usize) -> Self {
+        self.max_connections = max_connections;
+        self
+    }
+
+    /// Length in bytes of the longest request line. A longer one is answered
+    /// with an error and its connection is closed.
+    pub fn with_max_request_length(mut self, max_request_length: usize) -> Self {
+        self.max_request_length = max_request_length;
+        self
+    }
+
+    /// Permissions of the socket file, e.g. `0o660` to let the group of the
+    /// owner connect. Only the owner can by default (`0o600`).
+    pub fn with_socket_mode(mut self, socket_mode: u32) -> Self {
+        self.socket_mode = socket_mode;
+        self
+    }
+}
+
+/// Stops a [`Daemon`] from another thread, see [`Daemon::shutdown_handle`].
+#[derive(Debug, Clone)]
+pub struct ShutdownHandle {
+    requested: Arc<AtomicBool>,
+    socket_path: PathBuf,
+}
+
+impl ShutdownHandle {
+    /// Makes [`Daemon::run`] close the connections and return.
+    pub fn shutdown(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+        // Wakes up the listener waiting for a connection
+        let _ = UnixStream::connect(&self.socket_path);
+    }
+}
+
+/// Local server answering the requests of other processes with one client, see
+/// the [module documentation](self) for the protocol.
+#[derive(Debug)]
+pub struct Daemon {
+    listener: UnixListener,
+    socket_path: PathBuf,
+    options: DaemonOptions,
+    shutdown: ShutdownHandle,
+}
+
+impl Daemon {
+    /// Listens on `socket_path`, with the permissions of `options`.
+    ///
+    /// A socket left at `socket_path` by a previous run is replaced. Fails if
+    /// another process is listening on it, or if it is another kind of file.
+    pub fn bind(socket_path: impl AsRef<Path>, options: DaemonOptions) -> Result<Self> {
+        let socket_path = socket_path.as_ref();
+        remove_stale_socket(socket_path)?;
+        let listener = UnixListener::bind(socket_path).map_err(|e| {
+            Error::Other(format!("Cannot listen on '{}': {e}", socket_path.display()))
+        })?;
+        let permissions = std::fs::Permissions::from_mode(options.socket_mode);
+        if let Err(e) = std::fs::set_permissions(socket_path, permissions) {
+            let _ = std::fs::remove_file(socket_path);
+            return Err(Error::Other(format!(
+                "Cannot set the permissions of '{}': {e}",
+                socket_path.display()
+            )));
+        }
+        Ok(Self {
+            listener,
+            socket_path: socket_path.to_path_buf(),
+            options,
+            shutdown: ShutdownHandle {
+                requested: Arc::default(),
+                socket_path: socket_path.to_path_buf(),
+            },
+        })
+    }
+
+    /// Returns a handle stopping [`run`](Self::run) from another thread.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.shutdown.clone()
+    }
+
+    /// Answers the requests of every connection with `client`, until
+    /// [`ShutdownHandle::shutdown`] is called. The connections are then closed
+    /// and the socket file is removed.
+    pub fn run(self, client: Arc<dyn AppConfigurationClient + Send + Sync>) -> Result<()> {
+        let connections = Arc::new(Mutex::new(HashMap::<u64, UnixStream>::new()));
+        let mut threads = Vec::new();
+        for (connection_id, stream) in (0u64..).zip(self.listener.incoming()) {
+            if self.shutdown.requested.load(Ordering::SeqCst) {
+                break;
+            }
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    log::warn!("Failed to accept daemon connection: {e}");
+                    continue;
+                }
+            };
+            threads.retain(|thread: &std::thread::JoinHandle<()>| !thread.is_finished());
+            if threads.len() >= self.options.max_connections {
+                let _ = writeln!(stream, "{}", json!({"error": "Too many connections"}));
+                continue;
+            }
+            if let Ok(registered) = stream.try_clone() {
+                connections
+                    .lock_unpoisoned()
+                    .insert(connection_id, registered);
+            }
+            let client = client.clone();
+            let registry = connections.clone();
+            let max_request_length = self.options.max_request_length;
+            let spawned = std::thread::Builder::new()
+                .name("appconfiguration-daemon".to_string())
+                .spawn(move || {
+                    if let Err(e) = handle_connection(client.as_ref(), stream, max_request_length) {
+                        log::debug!("Daemon connection closed: {e}");
+                    }
+                    registry.lock_unpoisoned().remove(&connection_id);
+                });
+            match spawned {
+                Ok(thread) => threads.push(thread),
+                Err(e) => {
+                    log::warn!("Failed to spawn thread for daemon connection: {e}");
+                    connections.lock_unpoisoned().remove(&connection_id);
+                }
+            }
+        }
+
+        for stream in connections.lock_unpoisoned().values() {
+            let _ = stream.shutdown(std::net::Shutdown::Both);
+        }
+        for thread in threads {
+            let _ = thread.join();
+        }
+        let _ = std::fs::remove_file(&self.socket_path);
+        Ok(())
+    }
+}
+
+/// Listens on `socket_path` with the default [`DaemonOptions`] and answers the
+/// requests of every connection with `client`, see [`Daemon`].
+///
+/// Blocks for as long as the listener works. Use [`Daemon::bind`] and
+/// [`Daemon::shutdown_handle`] to stop it.
+pub fn serve(
+    client: Arc<dyn AppConfigurationClient + Send + Sync>,
+    socket_path: impl AsRef<Path>,
+) -> Result<()> {
+    Daemon::bind(socket_path, DaemonOptions::default())?.run(client)
+}
+
+/// Removes the socket left at `socket_path` by a previous run, if no process
+/// is listening on it anymore.
+fn remove_stale_socket(socket_path: &Path) -> Result<()> {
+    let Ok(metadata) = std::fs::symlink_metadata(socket_path) else {
+        return Ok(());
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(Error::Other(format!(
+            "Cannot listen on '{}': the file exists and is not a socket",
+            socket_path.display()
+        )));
+    }
+    if UnixStream::connect(socket_path).is_ok() {
+        return Err(Error::Other(format!(
+            "Cannot listen on '{}': another process is listening on it",
+            socket_path.display()
+        )));
+    }
+    std::fs::remove_file(socket_path).map_err(|e| {
+        Error::Other(format!(
+            "Cannot remove stale socket '{}': {e}",
+            socket_path.display()
+        ))
+    })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Request {
+    Feature {
+        id: String,
+        #[serde(flatten)]
+        entity: RequestEntity,
+    },
+    Property {
+        id: String,
+        #[serde(flatten)]
+        entity: RequestEntity,
+    },
+    Config,
+}
+
+#[derive(Debug, Deserialize)]
+struct RequestEntity {
+    entity_id: String,
+    #[serde(default)]
+    attributes: HashMap<String, Value>,
+}
+
+impl Entity for RequestEntity {
+    fn get_id(&self) -> String {
+        self.entity_id.clone()
+    }
+
+    fn get_attributes(&self) -> HashMap<String, Value> {
+        self.attributes.clone()
+    }
+}
+
+fn handle_connection(
+    client: &(dyn AppConfigurationClient + Send + Sync),
+    stream: UnixStream,
+    max_request_length: usize,
+) -> std::io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        // One byte more than the limit, for the newline ending the request
+        let limit = max_request_length as u64 + 1;
+        if (&mut reader).take(limit).read_until(b'\n', &mut line)? == 0 {
+            return Ok(());
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        } else if line.len() > max_request_length {
+            let error = format!("Request longer than {max_request_length} bytes");
+            writeln!(writer, "{}", json!({"error": error}))?;
+            return Ok(());
+        }
+        let line = String::from_utf8_lossy(&line);
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = serde_json::from_str::<Request>(&line)
+            .map_err(|e| Error::Other(format!("Invalid request: {e}")))
+            .and_then(|request| answer(client, request))
+            .unwrap_or_else(|e| json!({"error": e.to_string()}));
+        writeln!(writer, "{response}")?;
+    }
+}
+
+fn answer(
+    client: &(dyn AppConfigurationClient + Send + Sync),
+    request: Request,
+) -> Result<serde_json::Value> {
+    let response = match request {
+        Request::Feature { id, entity } => {
+            serde_json::to_value(client.get_feature(&id)?.get_current_value(&entity)?)
+        }
+        Request::Property { id, entity } => {
+            serde_json::to_value(client.get_property(&id)?.get_current_value(&entity)?)
+        }
+        Request::Config => Ok(json!({
+            "online": client.is_online()?,
+            "features": client.get_feature_ids()?,
+            "properties": client.get_property_ids()?,
+        })),
+    };
+    response.map_err(|e| Error::Other(format!("Cannot serialize response: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppConfigurationOffline;
+    use crate::network::serialization::fixtures::example_configuration_enterprise_path;
+    use std::time::Duration;
+
+    fn connect(socket_path: &Path) -> UnixStream {
+        for _ in 0..100 {
+            if let Ok(stream) = UnixStream::connect(socket_path) {
+                return stream;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("daemon is not listening on {}", socket_path.display());
+    }
+
+    fn request(stream: &mut UnixStream, line: &str) -> serde_json::Value {
+        writeln!(stream, "{line}").unwrap();
+        let mut response = String::new();
+        BufReader::new(stream.try_clone().unwrap())
+            .read_line(&mut response)
+            .unwrap();
+        serde_json::from_str(&response).unwrap()
+    }
+
+    fn client() -> Arc<dyn AppConfigurationClient + Send + Sync> {
+        let client = AppConfigurationOffline::new(
+            &example_configuration_enterprise_path(),
+            "dev",
+            "blue-charge",
+        )
+        .unwrap();
+        Arc::new(client)
+    }
+
+    /// Runs a daemon on a socket in a new directory, returns the directory,
+    /// the socket path, the shutdown handle and the thread running it.
+    fn start(
+        name: &str,
+        options: DaemonOptions,
+    ) -> (
+        PathBuf,
+        PathBuf,
+        ShutdownHandle,
+        std::thread::JoinHandle<Result<()>>,
+    ) {
+        let dir = std::env::temp_dir().join(format!(
+            "appconfiguration-daemon-{name}-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let socket_path = dir.join("daemon.sock");
+        let daemon = Daemon::bind(&socket_path, options).unwrap();
+        let shutdown = daemon.shutdown_handle();
+        let thread = std::thread::spawn(move || daemon.run(client()));
+        (dir, socket_path, shutdown, thread)
+    }
+
+    #[test]
+    fn test_serve() {
+        let (dir, socket_path, shutdown, thread) = start("serve", DaemonOptions::default());
+        let mode = std::fs::metadata(&socket_path)
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        let mut stream = connect(&socket_path);
+        let feature = request(
+            &mut stream,
+            r#"{"op": "feature", "id": "f1", "entity_id": "a1"}"#,
+        );
+        assert_eq!(feature["value"], 5);
+        assert_eq!(feature["isEnabled"], true);
+
+        let property = request(
+            &mut stream,
+            r#"{"op": "property", "id": "p1", "entity_id": "a1", "attributes": {"email": "a@b.c"}}"#,
+        );
+        assert_eq!(property["value"], 5);
+
+        let config = request(&mut stream, r#"{"op": "config"}"#);
+        assert_eq!(config["online"], false);
+        assert!(
+            config["features"]
+                .as_array()
+                .unwrap()
+                .contains(&json!("f1"))
+        );
+
+        let missing = request(
+            &mut stream,
+            r#"{"op": "feature", "id": "nope", "entity_id": "a1"}"#,
+        );
+        assert_eq!(missing["error"], "Feature `nope` not found.");
+
+        let invalid = request(&mut stream, "not json");
+        assert!(
+            invalid["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("Invalid request")
+        );
+
+        // A live socket is not replaced
+        let err = Daemon::bind(&socket_path, DaemonOptions::default()).unwrap_err();
+        assert!(err.to_string().contains("another process is listening"));
+
+        // Shutting down closes the open connections and removes the socket
+        shutdown.shutdown();
+        thread.join().unwrap().unwrap();
+        let mut response = String::new();
+        BufReader::new(stream).read_line(&mut response).unwrap();
+        assert!(response.is_empty());
+        assert!(!socket_path.exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_limits() {
+        let options = DaemonOptions::default()
+            .with_max_connections(1)
+            .with_max_request_length(64)
+            .with_socket_mode(0o660);
+        let (dir, socket_path, shutdown, thread) = start("limits", options);
+        let mode = std::fs::metadata(&socket_path)
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o660);
+
+        let mut stream = connect(&socket_path);
+        let config = request(&mut stream, r#"{"op": "config"}"#);
+        assert_eq!(config["online"], false);
+
+        let mut second = connect(&socket_path);
+        let mut response = String::new();
+        BufReader::new(second.try_clone().unwrap())
+            .read_line(&mut response)
+            .unwrap();
+        assert_eq!(response.trim(), r#"{"error":"Too many connections"}"#);
+        let _ = writeln!(second, "{{}}");
+
+        let long = format!(
+            r#"{{"op": "feature", "id": "{}", "entity_id": "a1"}}"#,
+            "f".repeat(64)
+        );
+        // Written at once, the daemon closes the connection without reading
+        // the rest of the request
+        stream.write_all(format!("{long}\n").as_bytes()).unwrap();
+        let mut response = String::new();
+        let mut reader = BufReader::new(stream);
+        reader.read_line(&mut response).unwrap();
+        let too_long: serde_json::Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(too_long["error"], "Request longer than 64 bytes");
+        response.clear();
+        assert_eq!(reader.read_line(&mut response).unwrap(), 0);
+
+        shutdown.shutdown();
+        thread.join().unwrap().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_refuses_to_replace_regular_files() {
+        let path = std::env::temp_dir().join(format!(
+            "appconfiguration-daemon-file-{}",
+            std::process::id()
+        ));
+        std::fs::write(&path, "").unwrap();
+
+        let result = serve(client(), &path);
+        assert!(matches!(result, Err(Error::Other(ref msg)) if msg.contains("not a socket")));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -23,6 +23,9 @@ pub trait Entity {
 
     /// Gets a map of attributes names and values against which evaluate the
     /// entities belonging to segments.
+    ///
+    /// Segment rules can also use the built-in `entityId` attribute, set to
+    /// [`get_id`](Entity::get_id) unless it is part of these attributes.
     fn get_attributes(&self) -> HashMap<String, Value> {
         HashMap::new()
     }
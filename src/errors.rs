@@ -16,16 +16,13 @@ use std::sync::PoisonError;
 
 use thiserror::Error;
 
-use crate::network::errors::NetworkError;
+use crate::network::errors::{NetworkError, NetworkErrorCategory, is_transient_token_rejection};
 use crate::segment_evaluation::errors::SegmentEvaluationError;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug, Error)]
 pub enum Error {
-    #[error("Cannot acquire snapshot lock")]
-    CannotAcquireLock,
-
     #[error(
         "Feature '{feature_id}' does not exist in environment '{environment_id}' and collection '{collection_id}'"
     )]
@@ -59,9 +56,47 @@ pub enum Error {
         message: String,
     },
 
+    #[error(
+        "Cannot declare '{prerequisite_id}' as prerequisite of '{feature_id}': it would create a dependency cycle"
+    )]
+    FeaturePrerequisiteCycle {
+        feature_id: String,
+        prerequisite_id: String,
+    },
+
+    #[error("No {kind} is named '{name}'")]
+    NameNotFound { kind: &'static str, name: String },
+
+    #[error("Several {kind}s are named '{name}': {ids:?}")]
+    AmbiguousName {
+        kind: &'static str,
+        name: String,
+        ids: Vec<String>,
+    },
+
     #[error("Inner type cannot be converted to requested type")]
     MismatchType,
 
+    #[error("Value {found:?} cannot be converted to {expected}")]
+    ValueConversionError {
+        expected: &'static str,
+        found: crate::Value,
+    },
+
+    #[error("Local override of '{id}' is {found:?}, not a {expected} value")]
+    LocalOverrideTypeMismatch {
+        id: String,
+        expected: String,
+        found: crate::Value,
+    },
+
+    #[error("Values of property '{property_id}' with format '{format}' cannot be parsed")]
+    UnsupportedPropertyFormat { property_id: String, format: String },
+
+    #[error("Property '{property_id}' has targeting rules, its value depends on the entity")]
+    PropertyHasTargetingRules { property_id: String },
+
+    #[cfg(feature = "remote")]
     #[error(transparent)]
     TungsteniteError(#[from] tungstenite::Error),
 
@@ -74,41 +109,150 @@ pub enum Error {
     #[error("Client is not configured")]
     ClientNotConfigured,
 
+    #[error(
+        "No configuration obtained within {timeout:?}{}",
+        .last_error.as_deref().map(|e| format!(": {e}")).unwrap_or_default()
+    )]
+    InitialFetchTimeout {
+        timeout: std::time::Duration,
+        /// Last error connecting to the server or processing its responses.
+        last_error: Option<String>,
+    },
+
     #[error(transparent)]
     ConfigurationAccessError(#[from] ConfigurationAccessError),
 
     #[error(transparent)]
     ConfigurationDataError(#[from] ConfigurationDataError),
 
+    /// A targeting rule could not be evaluated for an entity. Returned by
+    /// the evaluations of features and properties as the `source` of an
+    /// [`Error::EvaluationError`].
     #[error("Failed to evaluate entity: {0}")]
     EntityEvaluationError(EntityEvaluationError),
 
+    #[error(transparent)]
+    EvaluationError(#[from] EvaluationError),
+
     #[error(transparent)]
     NetworkError(#[from] NetworkError),
 
+    #[error(transparent)]
+    BuildError(#[from] BuildError),
+
     #[error(transparent)]
     LiveConfigurationError(#[from] LiveConfigurationError),
 
     #[error("Failed to record evaluation event for metering")]
     MeteringError,
 
+    #[error("Invalid {field} '{value}': {reason}")]
+    InvalidConfigurationId {
+        field: &'static str,
+        value: String,
+        reason: &'static str,
+    },
+
     #[error("{0}")]
     Other(String),
 }
 
+/// Why a client could not be created, telling which setting to check.
+///
+/// Only the errors for which [`BuildError::is_retryable`] is `true` may go
+/// away by creating the client again later.
+#[derive(Debug, Error)]
+pub enum BuildError {
+    #[error("Invalid region '{region}': expected a region ID such as 'us-south' or 'eu-de'")]
+    InvalidRegion { region: String },
+
+    #[error("Invalid API key: {reason}")]
+    InvalidApiKey { reason: &'static str },
+
+    #[error(
+        "IAM refused to exchange the credentials for an access token. Status code: {status}. Message: {message}. {}",
+        token_exchange_hint(*.status)
+    )]
+    TokenExchangeFailed {
+        /// HTTP status IAM answered the token request with.
+        status: u16,
+        message: String,
+    },
+
+    #[error(
+        "Cannot reach {endpoint} ({category} error). Check the region, the private endpoint setting and the network between the application and IBM Cloud"
+    )]
+    EndpointUnreachable {
+        /// Scheme, host and port of the endpoint.
+        endpoint: String,
+        category: NetworkErrorCategory,
+        source: NetworkError,
+    },
+}
+
+impl BuildError {
+    /// Whether creating the client again may succeed without changing its
+    /// settings: the service was unreachable or temporarily unavailable.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            BuildError::InvalidRegion { .. } | BuildError::InvalidApiKey { .. } => false,
+            BuildError::TokenExchangeFailed { status, .. } => is_transient_token_rejection(*status),
+            BuildError::EndpointUnreachable { .. } => true,
+        }
+    }
+}
+
+fn token_exchange_hint(status: u16) -> &'static str {
+    match status {
+        400 | 401 | 403 => {
+            "Check that the API key or trusted profile exists, is not disabled and belongs to the account of the instance"
+        }
+        429 | 500.. => "IAM is temporarily unavailable, retry later",
+        _ => "Check the IAM token URL",
+    }
+}
+
 #[derive(Debug, Error)]
 #[error(transparent)]
 pub struct EntityEvaluationError(pub(crate) SegmentEvaluationError);
 
-impl From<SegmentEvaluationError> for Error {
-    fn from(value: SegmentEvaluationError) -> Self {
-        Self::EntityEvaluationError(EntityEvaluationError(value))
+/// An error evaluating a feature or a property for an entity, with the one
+/// that failed and the ID of the entity.
+///
+/// The evaluations of features and properties return all their errors wrapped
+/// in it, [`Error::EntityEvaluationError`] included: match on `source` for
+/// the cause.
+#[derive(Debug, Error)]
+#[error("Failed to evaluate {kind} '{id}' for entity '{entity_id}': {source}")]
+pub struct EvaluationError {
+    /// `"feature"` or `"property"`.
+    pub kind: &'static str,
+    /// ID of the feature or the property.
+    pub id: String,
+    pub entity_id: String,
+    pub source: Box<Error>,
+}
+
+impl EvaluationError {
+    /// Wraps `source`, the error evaluating `id` for `entity`.
+    pub(crate) fn wrap(
+        kind: &'static str,
+        id: &str,
+        entity: &(impl crate::Entity + ?Sized),
+        source: Error,
+    ) -> Error {
+        Error::EvaluationError(Self {
+            kind,
+            id: id.to_string(),
+            entity_id: entity.get_id(),
+            source: Box::new(source),
+        })
     }
 }
 
-impl<T> From<PoisonError<T>> for Error {
-    fn from(_value: PoisonError<T>) -> Self {
-        Error::CannotAcquireLock
+impl From<SegmentEvaluationError> for Error {
+    fn from(value: SegmentEvaluationError) -> Self {
+        Self::EntityEvaluationError(EntityEvaluationError(value))
     }
 }
 
@@ -122,15 +266,31 @@ pub struct DeserializationError {
 
 /// Additional information for [`DeserializationError`] error
 #[derive(Debug, Error)]
+#[allow(clippy::enum_variant_names)]
 pub enum DeserializationErrorKind {
     #[error(transparent)]
     SerdeError(#[from] serde_json::Error),
+
+    #[cfg(feature = "yaml")]
+    #[error(transparent)]
+    YamlError(#[from] serde_yaml_ng::Error),
+
+    #[cfg(feature = "toml")]
+    #[error(transparent)]
+    TomlError(#[from] toml::de::Error),
 }
 
 #[derive(Debug, Error)]
 pub enum ConfigurationDataError {
-    #[error("Environment '{0}' not found")]
-    EnvironmentNotFound(String),
+    #[error(
+        "Environment '{environment_id}' not found, the configuration contains: [{}]",
+        .available.join(", ")
+    )]
+    EnvironmentNotFound {
+        environment_id: String,
+        /// IDs of the environments present in the configuration.
+        available: Vec<String>,
+    },
 
     #[error("Collection '{0}' not found")]
     CollectionNotFound(String),
@@ -144,11 +304,35 @@ pub enum ConfigurationDataError {
     #[error("Property `{0}` not found.")]
     PropertyNotFound(String),
 
+    #[error("Segment `{0}` not found.")]
+    SegmentNotFound(String),
+
+    #[error("Unknown operator '{operator}' in a rule of segment '{segment_id}'")]
+    UnknownSegmentRuleOperator {
+        segment_id: String,
+        operator: String,
+    },
+
     #[error("Missing segments for resource '{0}'")]
     MissingSegments(String),
 
     #[error("Improper collection format in resource '{0}'")]
     InvalidResourceCollections(String),
+
+    #[error("Invalid {location} in resource '{resource_id}': {reason}")]
+    InvalidValue {
+        resource_id: String,
+        location: String,
+        reason: String,
+    },
+
+    #[error("Configuration checksum '{found}' does not match the expected '{expected}'")]
+    ChecksumMismatch { expected: String, found: String },
+
+    #[error("Configuration schema version {found:?} does not match the expected {expected}")]
+    SchemaVersionMismatch { expected: u32, found: Option<u32> },
+    #[error("Cannot serialize the configuration: {0}")]
+    Serialization(serde_json::Error),
 }
 
 #[derive(Debug, Error)]
@@ -167,6 +351,19 @@ impl<T> From<PoisonError<T>> for ConfigurationAccessError {
 #[error(transparent)]
 pub struct LiveConfigurationError(pub(crate) crate::network::live_configuration::Error);
 
+impl Error {
+    /// Why the client is offline, when it failed because no configuration is
+    /// available while offline (see [`OfflineMode::Fail`](crate::OfflineMode::Fail)).
+    pub fn offline_details(&self) -> Option<&crate::OfflineDetails> {
+        match self {
+            Error::LiveConfigurationError(LiveConfigurationError(
+                crate::network::live_configuration::Error::Offline(details),
+            )) => Some(details),
+            _ => None,
+        }
+    }
+}
+
 impl From<crate::network::live_configuration::Error> for Error {
     fn from(value: crate::network::live_configuration::Error) -> Self {
         Self::LiveConfigurationError(LiveConfigurationError(value))
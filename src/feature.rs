@@ -47,10 +47,15 @@ pub trait Feature {
     ///         Value::Int64(v) => println!("i64 with value {v}"),
     ///         Value::String(v) => println!("String with value {v}"),
     ///         Value::Boolean(v) => println!("bool with value {v}"),
+    ///         Value::List(v) => println!("list of {} values", v.len()),
+    ///         other => println!("other value {other}"),
     ///     }
     /// #   Ok(())
     /// # }
     /// ```
+    fn get_current_value(&self, entity: &(impl Entity + ?Sized))
+    -> Result<FeatureEvaluationResult>;
+
     /// Evaluates a feature for the given [`Entity`] and returns its value converted (if possible)
     /// to the given type.
     ///
@@ -68,20 +73,44 @@ pub trait Feature {
     /// #   Ok(())
     /// # }
     /// ```
-
     fn get_value_into<T: TryFrom<Value, Error = crate::Error>>(
         &self,
-        entity: &impl Entity,
+        entity: &(impl Entity + ?Sized),
     ) -> Result<T> {
         let value = self.get_current_value(entity).map(|r| r.value)?;
         value.try_into()
     }
 
+    /// Evaluates the feature for each of the given entities, and returns the values
+    /// in the same order.
+    ///
+    /// Meant for batch jobs evaluating a feature for many entities: implementations
+    /// read the feature, and the clock used by scheduled rules and progressive
+    /// rollouts, once for all of them instead of once per entity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ibm_appconfiguration_rust_sdk::{AppConfigurationClient, Feature, Result, Entity};
+    /// # fn doctest_get_values_for(client: impl AppConfigurationClient, users: &[Box<dyn Entity>]) -> Result<()> {
+    ///     let feature = client.get_feature("my_feature")?;
+    ///     let entities: Vec<&dyn Entity> = users.iter().map(|user| user.as_ref()).collect();
+    ///     for value in feature.get_values_for(&entities) {
+    ///         println!("{}", value?);
+    ///     }
+    /// #   Ok(())
+    /// # }
+    /// ```
+    fn get_values_for(&self, entities: &[&dyn Entity]) -> Vec<Result<Value>> {
+        entities
+            .iter()
+            .map(|entity| self.get_current_value(*entity).map(|r| r.value))
+            .collect()
+    }
+
     fn get_feature_id(&self) -> Result<String>;
 
     fn get_feature_data_type(&self) -> Result<String>;
 
     fn get_feature_data_format(&self) -> Result<Option<String>>;
-
-    fn get_current_value(&self, entity: &impl Entity) -> Result<FeatureEvaluationResult>;
 }
@@ -0,0 +1,685 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! C ABI to embed the SDK in non-Rust services (C/C++, Python via `ctypes`, ...).
+//!
+//! The matching header is `include/ibm_appconfiguration.h`, generated with
+//! `cbindgen --config cbindgen.toml --output include/ibm_appconfiguration.h`.
+//!
+//! Conventions:
+//! * Strings are NUL-terminated UTF-8.
+//! * Entity attributes are passed as a JSON object (or NULL for no attributes).
+//! * Functions return an [`AppConfigStatus`]; on error a description can be
+//!   retrieved with [`appconfig_last_error_message`].
+//! * Panics never cross the C ABI: they are reported as
+//!   [`AppConfigStatus::Panic`], or NULL for the functions returning a handle.
+//! * Strings returned by the library must be released with [`appconfig_string_free`].
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString, c_char};
+use std::path::Path;
+
+use crate::utils::panic_message;
+use crate::{
+    AppConfigurationClient, AppConfigurationClientIBMCloud, AppConfigurationOffline,
+    ConfigurationId, Entity, Error, Feature, OfflineMode, Property, Result, RuntimeEventEmitter,
+    Value,
+};
+
+/// Opaque handle to a client.
+pub struct AppConfigClient {
+    client: Box<dyn AppConfigurationClient>,
+}
+
+/// Result of the FFI calls.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppConfigStatus {
+    Ok = 0,
+    /// A required pointer was NULL or a string was not valid UTF-8/JSON.
+    InvalidArgument = 1,
+    /// The evaluated value cannot be converted to the requested type.
+    TypeMismatch = 2,
+    /// Any other error (unknown feature, connectivity, ...).
+    Error = 3,
+    /// The library panicked, a bug. The description tells where.
+    Panic = 4,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+fn status_from_error(error: Error) -> AppConfigStatus {
+    let status = match error {
+        Error::MismatchType | Error::ValueConversionError { .. } => AppConfigStatus::TypeMismatch,
+        _ => AppConfigStatus::Error,
+    };
+    set_last_error(error.to_string());
+    status
+}
+
+/// Runs `f`, returning `on_panic` if it panics, as unwinding into the caller
+/// of an `extern "C"` function is undefined behavior.
+fn catch_panic<R>(on_panic: R, f: impl FnOnce() -> R) -> R {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        set_last_error(panic_message(&*payload));
+        on_panic
+    })
+}
+
+fn invalid_argument(message: &str) -> AppConfigStatus {
+    set_last_error(message.to_string());
+    AppConfigStatus::InvalidArgument
+}
+
+/// # Safety
+/// `ptr` must be NULL or point to a NUL-terminated string.
+unsafe fn read_str<'a>(ptr: *const c_char, name: &str) -> std::result::Result<&'a str, String> {
+    if ptr.is_null() {
+        return Err(format!("'{name}' must not be NULL"));
+    }
+    // SAFETY: guaranteed by the caller.
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .map_err(|_| format!("'{name}' is not valid UTF-8"))
+}
+
+/// Entity built from the id and JSON attributes passed through the C ABI.
+struct FfiEntity {
+    id: String,
+    attributes: HashMap<String, Value>,
+}
+
+impl FfiEntity {
+    fn from_json(id: &str, attributes_json: Option<&str>) -> std::result::Result<Self, String> {
+        let attributes = match attributes_json {
+            None => HashMap::new(),
+            Some(json) => {
+                let object: serde_json::Map<String, serde_json::Value> = serde_json::from_str(json)
+                    .map_err(|e| format!("attributes must be a JSON object: {e}"))?;
+                object
+                    .into_iter()
+                    .map(|(name, value)| {
+                        let value = match value {
+                            serde_json::Value::Bool(v) => Value::Boolean(v),
+                            serde_json::Value::String(v) => Value::String(v),
+                            serde_json::Value::Number(n) => {
+                                if let Some(v) = n.as_u64() {
+                                    Value::UInt64(v)
+                                } else if let Some(v) = n.as_i64() {
+                                    Value::Int64(v)
+                                } else {
+                                    Value::Float64(n.as_f64().unwrap_or_default())
+                                }
+                            }
+                            _ => return Err(format!("unsupported type for attribute '{name}'")),
+                        };
+                        Ok((name, value))
+                    })
+                    .collect::<std::result::Result<_, _>>()?
+            }
+        };
+        Ok(Self {
+            id: id.to_string(),
+            attributes,
+        })
+    }
+}
+
+impl Entity for FfiEntity {
+    fn get_id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn get_attributes(&self) -> HashMap<String, Value> {
+        self.attributes.clone()
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Subject {
+    Feature,
+    Property,
+}
+
+/// Shared implementation of the `appconfig_*_get_*` functions.
+///
+/// # Safety
+/// See the public functions.
+unsafe fn evaluate<T>(
+    client: *const AppConfigClient,
+    subject: Subject,
+    id: *const c_char,
+    entity_id: *const c_char,
+    attributes_json: *const c_char,
+    convert: impl FnOnce(Value) -> Result<T>,
+) -> std::result::Result<T, AppConfigStatus> {
+    // SAFETY: guaranteed by the caller.
+    let Some(client) = (unsafe { client.as_ref() }) else {
+        return Err(invalid_argument("'client' must not be NULL"));
+    };
+    let parse = || -> std::result::Result<_, String> {
+        // SAFETY: guaranteed by the caller.
+        let id = unsafe { read_str(id, "id") }?;
+        let entity_id = unsafe { read_str(entity_id, "entity_id") }?;
+        let attributes_json = if attributes_json.is_null() {
+            None
+        } else {
+            Some(unsafe { read_str(attributes_json, "attributes_json") }?)
+        };
+        Ok((id, FfiEntity::from_json(entity_id, attributes_json)?))
+    };
+    let (id, entity) = parse().map_err(|message| invalid_argument(&message))?;
+
+    let value = match subject {
+        Subject::Feature => client
+            .client
+            .get_feature(id)
+            .and_then(|feature| feature.get_current_value(&entity))
+            .map(|result| result.value),
+        Subject::Property => client
+            .client
+            .get_property(id)
+            .and_then(|property| property.get_current_value(&entity))
+            .map(|result| result.value),
+    };
+    value.and_then(convert).map_err(status_from_error)
+}
+
+fn as_number(value: Value) -> Result<f64> {
+    match value {
+        Value::Float64(v) => Ok(v),
+        Value::UInt64(v) => Ok(v as f64),
+        Value::Int64(v) => Ok(v as f64),
+        _ => Err(Error::MismatchType),
+    }
+}
+
+fn as_c_string(value: Value) -> Result<*mut c_char> {
+    let value: String = value.try_into()?;
+    CString::new(value)
+        .map(CString::into_raw)
+        .map_err(|_| Error::Other("string value contains a NUL byte".to_string()))
+}
+
+/// Writes the value of `result` to `out`, returning the status of `result`.
+///
+/// # Safety
+/// `out` must be non-NULL, properly aligned and valid for writes of `T`.
+unsafe fn write_out<T>(
+    out: *mut T,
+    result: std::result::Result<T, AppConfigStatus>,
+) -> AppConfigStatus {
+    match result {
+        Ok(value) => {
+            // SAFETY: guaranteed by the caller.
+            unsafe { out.write(value) };
+            AppConfigStatus::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+fn into_handle(client: Result<Box<dyn AppConfigurationClient>>) -> *mut AppConfigClient {
+    match client {
+        Ok(client) => Box::into_raw(Box::new(AppConfigClient { client })),
+        Err(e) => {
+            set_last_error(e.to_string());
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Creates a client connected to the App Configuration service, kept up to date
+/// in the background. Returns NULL on error.
+///
+/// # Safety
+/// All arguments must be valid NUL-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn appconfig_client_new(
+    region: *const c_char,
+    guid: *const c_char,
+    apikey: *const c_char,
+    environment_id: *const c_char,
+    collection_id: *const c_char,
+) -> *mut AppConfigClient {
+    catch_panic(std::ptr::null_mut(), || {
+        let args = || -> std::result::Result<_, String> {
+            // SAFETY: guaranteed by the caller.
+            unsafe {
+                Ok((
+                    read_str(region, "region")?,
+                    read_str(guid, "guid")?,
+                    read_str(apikey, "apikey")?,
+                    read_str(environment_id, "environment_id")?,
+                    read_str(collection_id, "collection_id")?,
+                ))
+            }
+        };
+        let (region, guid, apikey, environment_id, collection_id) = match args() {
+            Ok(args) => args,
+            Err(message) => {
+                set_last_error(message);
+                return std::ptr::null_mut();
+            }
+        };
+
+        into_handle(
+            ConfigurationId::new(
+                guid.to_string(),
+                environment_id.to_string(),
+                collection_id.to_string(),
+            )
+            .and_then(|configuration_id| {
+                AppConfigurationClientIBMCloud::new(
+                    apikey,
+                    region,
+                    configuration_id,
+                    OfflineMode::Fail,
+                    false,
+                    Default::default(),
+                    RuntimeEventEmitter::new(),
+                )
+            })
+            .map(|client| Box::new(client) as Box<dyn AppConfigurationClient>),
+        )
+    })
+}
+
+/// Creates a client evaluating the configuration from a local file (same format as
+/// the configuration exports). Returns NULL on error.
+///
+/// # Safety
+/// All arguments must be valid NUL-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn appconfig_client_new_offline(
+    filepath: *const c_char,
+    environment_id: *const c_char,
+    collection_id: *const c_char,
+) -> *mut AppConfigClient {
+    catch_panic(std::ptr::null_mut(), || {
+        let args = || -> std::result::Result<_, String> {
+            // SAFETY: guaranteed by the caller.
+            unsafe {
+                Ok((
+                    read_str(filepath, "filepath")?,
+                    read_str(environment_id, "environment_id")?,
+                    read_str(collection_id, "collection_id")?,
+                ))
+            }
+        };
+        let (filepath, environment_id, collection_id) = match args() {
+            Ok(args) => args,
+            Err(message) => {
+                set_last_error(message);
+                return std::ptr::null_mut();
+            }
+        };
+
+        into_handle(
+            AppConfigurationOffline::new(Path::new(filepath), environment_id, collection_id)
+                .map(|client| Box::new(client) as Box<dyn AppConfigurationClient>),
+        )
+    })
+}
+
+/// Destroys a client created with `appconfig_client_new*`. NULL is a no-op.
+///
+/// # Safety
+/// `client` must be NULL or a handle not yet destroyed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn appconfig_client_free(client: *mut AppConfigClient) {
+    catch_panic((), || {
+        if !client.is_null() {
+            // SAFETY: the handle was created by `into_handle`.
+            drop(unsafe { Box::from_raw(client) });
+        }
+    })
+}
+
+/// Evaluates a boolean feature for the entity and writes it to `out`.
+///
+/// # Safety
+/// `client` must be a valid handle, `out` NULL or an aligned pointer valid for
+/// writes, the strings valid NUL-terminated strings and `attributes_json` NULL
+/// or a valid string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn appconfig_feature_get_bool(
+    client: *const AppConfigClient,
+    feature_id: *const c_char,
+    entity_id: *const c_char,
+    attributes_json: *const c_char,
+    out: *mut bool,
+) -> AppConfigStatus {
+    catch_panic(AppConfigStatus::Panic, || {
+        if out.is_null() {
+            return invalid_argument("'out' must not be NULL");
+        }
+        // SAFETY: guaranteed by the caller.
+        let result = unsafe {
+            evaluate(
+                client,
+                Subject::Feature,
+                feature_id,
+                entity_id,
+                attributes_json,
+                bool::try_from,
+            )
+        };
+        // SAFETY: `out` is not NULL, and valid for writes as guaranteed by the caller.
+        unsafe { write_out(out, result) }
+    })
+}
+
+/// Evaluates a numeric feature for the entity and writes it to `out`.
+///
+/// # Safety
+/// Same as [`appconfig_feature_get_bool`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn appconfig_feature_get_number(
+    client: *const AppConfigClient,
+    feature_id: *const c_char,
+    entity_id: *const c_char,
+    attributes_json: *const c_char,
+    out: *mut f64,
+) -> AppConfigStatus {
+    catch_panic(AppConfigStatus::Panic, || {
+        if out.is_null() {
+            return invalid_argument("'out' must not be NULL");
+        }
+        // SAFETY: guaranteed by the caller.
+        let result = unsafe {
+            evaluate(
+                client,
+                Subject::Feature,
+                feature_id,
+                entity_id,
+                attributes_json,
+                as_number,
+            )
+        };
+        // SAFETY: `out` is not NULL, and valid for writes as guaranteed by the caller.
+        unsafe { write_out(out, result) }
+    })
+}
+
+/// Evaluates a string feature for the entity and writes it to `out`. The string
+/// must be released with [`appconfig_string_free`].
+///
+/// # Safety
+/// Same as [`appconfig_feature_get_bool`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn appconfig_feature_get_string(
+    client: *const AppConfigClient,
+    feature_id: *const c_char,
+    entity_id: *const c_char,
+    attributes_json: *const c_char,
+    out: *mut *mut c_char,
+) -> AppConfigStatus {
+    catch_panic(AppConfigStatus::Panic, || {
+        if out.is_null() {
+            return invalid_argument("'out' must not be NULL");
+        }
+        // SAFETY: guaranteed by the caller.
+        let result = unsafe {
+            evaluate(
+                client,
+                Subject::Feature,
+                feature_id,
+                entity_id,
+                attributes_json,
+                as_c_string,
+            )
+        };
+        // SAFETY: `out` is not NULL, and valid for writes as guaranteed by the caller.
+        unsafe { write_out(out, result) }
+    })
+}
+
+/// Evaluates a boolean property for the entity and writes it to `out`.
+///
+/// # Safety
+/// Same as [`appconfig_feature_get_bool`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn appconfig_property_get_bool(
+    client: *const AppConfigClient,
+    property_id: *const c_char,
+    entity_id: *const c_char,
+    attributes_json: *const c_char,
+    out: *mut bool,
+) -> AppConfigStatus {
+    catch_panic(AppConfigStatus::Panic, || {
+        if out.is_null() {
+            return invalid_argument("'out' must not be NULL");
+        }
+        // SAFETY: guaranteed by the caller.
+        let result = unsafe {
+            evaluate(
+                client,
+                Subject::Property,
+                property_id,
+                entity_id,
+                attributes_json,
+                bool::try_from,
+            )
+        };
+        // SAFETY: `out` is not NULL, and valid for writes as guaranteed by the caller.
+        unsafe { write_out(out, result) }
+    })
+}
+
+/// Evaluates a numeric property for the entity and writes it to `out`.
+///
+/// # Safety
+/// Same as [`appconfig_feature_get_bool`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn appconfig_property_get_number(
+    client: *const AppConfigClient,
+    property_id: *const c_char,
+    entity_id: *const c_char,
+    attributes_json: *const c_char,
+    out: *mut f64,
+) -> AppConfigStatus {
+    catch_panic(AppConfigStatus::Panic, || {
+        if out.is_null() {
+            return invalid_argument("'out' must not be NULL");
+        }
+        // SAFETY: guaranteed by the caller.
+        let result = unsafe {
+            evaluate(
+                client,
+                Subject::Property,
+                property_id,
+                entity_id,
+                attributes_json,
+                as_number,
+            )
+        };
+        // SAFETY: `out` is not NULL, and valid for writes as guaranteed by the caller.
+        unsafe { write_out(out, result) }
+    })
+}
+
+/// Evaluates a string property for the entity and writes it to `out`. The string
+/// must be released with [`appconfig_string_free`].
+///
+/// # Safety
+/// Same as [`appconfig_feature_get_bool`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn appconfig_property_get_string(
+    client: *const AppConfigClient,
+    property_id: *const c_char,
+    entity_id: *const c_char,
+    attributes_json: *const c_char,
+    out: *mut *mut c_char,
+) -> AppConfigStatus {
+    catch_panic(AppConfigStatus::Panic, || {
+        if out.is_null() {
+            return invalid_argument("'out' must not be NULL");
+        }
+        // SAFETY: guaranteed by the caller.
+        let result = unsafe {
+            evaluate(
+                client,
+                Subject::Property,
+                property_id,
+                entity_id,
+                attributes_json,
+                as_c_string,
+            )
+        };
+        // SAFETY: `out` is not NULL, and valid for writes as guaranteed by the caller.
+        unsafe { write_out(out, result) }
+    })
+}
+
+/// Releases a string returned by this library. NULL is a no-op.
+///
+/// # Safety
+/// `value` must be NULL or a string returned by this library not yet released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn appconfig_string_free(value: *mut c_char) {
+    catch_panic((), || {
+        if !value.is_null() {
+            // SAFETY: the string was created with `CString::into_raw`.
+            drop(unsafe { CString::from_raw(value) });
+        }
+    })
+}
+
+/// Returns the description of the last error on the calling thread, or NULL.
+/// The pointer is valid until the next call into this library on the same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn appconfig_last_error_message() -> *const c_char {
+    catch_panic(std::ptr::null(), || {
+        LAST_ERROR.with(|last| {
+            last.borrow()
+                .as_ref()
+                .map_or(std::ptr::null(), |message| message.as_ptr())
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offline_client() -> *mut AppConfigClient {
+        let mut path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("data/data-dump-enterprise-plan-sdk-testing.json");
+        let path = CString::new(path.to_str().unwrap()).unwrap();
+        let client = unsafe {
+            appconfig_client_new_offline(path.as_ptr(), c"dev".as_ptr(), c"blue-charge".as_ptr())
+        };
+        assert!(!client.is_null());
+        client
+    }
+
+    #[test]
+    fn test_evaluate_feature_and_property() {
+        let client = offline_client();
+
+        let mut number = 0.0;
+        let status = unsafe {
+            appconfig_feature_get_number(
+                client,
+                c"f1".as_ptr(),
+                c"a1".as_ptr(),
+                std::ptr::null(),
+                &mut number,
+            )
+        };
+        assert_eq!(status, AppConfigStatus::Ok);
+        assert_eq!(number, 5.0);
+
+        let mut value = std::ptr::null_mut();
+        let status = unsafe {
+            appconfig_property_get_string(
+                client,
+                c"p4".as_ptr(),
+                c"a1".as_ptr(),
+                c"{\"email\": \"john@ibm.com\", \"age\": 42}".as_ptr(),
+                &mut value,
+            )
+        };
+        assert_eq!(status, AppConfigStatus::Ok);
+        assert_eq!(unsafe { CStr::from_ptr(value) }, c"my text");
+        unsafe { appconfig_string_free(value) };
+
+        unsafe { appconfig_client_free(client) };
+    }
+
+    #[test]
+    fn test_errors() {
+        let client = offline_client();
+
+        let mut flag = false;
+        let status = unsafe {
+            appconfig_feature_get_bool(
+                client,
+                c"f1".as_ptr(),
+                c"a1".as_ptr(),
+                std::ptr::null(),
+                &mut flag,
+            )
+        };
+        assert_eq!(status, AppConfigStatus::TypeMismatch);
+
+        let status = unsafe {
+            appconfig_feature_get_bool(
+                client,
+                c"f1".as_ptr(),
+                c"a1".as_ptr(),
+                c"[1, 2]".as_ptr(),
+                &mut flag,
+            )
+        };
+        assert_eq!(status, AppConfigStatus::InvalidArgument);
+        let message = unsafe { CStr::from_ptr(appconfig_last_error_message()) };
+        assert!(
+            message
+                .to_str()
+                .unwrap()
+                .starts_with("attributes must be a JSON object")
+        );
+
+        let status = unsafe {
+            appconfig_feature_get_bool(
+                client,
+                c"does-not-exist".as_ptr(),
+                c"a1".as_ptr(),
+                std::ptr::null(),
+                &mut flag,
+            )
+        };
+        assert_eq!(status, AppConfigStatus::Error);
+
+        unsafe { appconfig_client_free(client) };
+    }
+
+    #[test]
+    fn test_panics_are_caught() {
+        let status = catch_panic(AppConfigStatus::Panic, || panic!("boom"));
+        assert_eq!(status, AppConfigStatus::Panic);
+        let message = unsafe { CStr::from_ptr(appconfig_last_error_message()) };
+        assert!(message.to_str().unwrap().ends_with("boom"));
+    }
+}
@@ -0,0 +1,130 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Extractors for [actix-web](https://docs.rs/actix-web).
+//!
+//! Register [`AppConfigurationState`] as application data and take an
+//! [`Evaluation`] or a [`RequestEntity`] as handler argument:
+//!
+//! ```ignore
+//! let state = web::Data::new(AppConfigurationState::new(Arc::new(client)));
+//! HttpServer::new(move || App::new().app_data(state.clone()).service(checkout))
+//! ```
+
+use std::future::{Future, Ready, ready};
+use std::pin::Pin;
+
+use actix_web::dev::Payload;
+use actix_web::http::StatusCode;
+use actix_web::{FromRequest, HttpRequest, ResponseError, web};
+
+use super::{AppConfigurationState, Evaluation, RequestEntity, RequestEntityRejection};
+use crate::Entity;
+
+impl ResponseError for RequestEntityRejection {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+fn app_configuration_state(req: &HttpRequest) -> actix_web::Result<&AppConfigurationState> {
+    req.app_data::<web::Data<AppConfigurationState>>()
+        .map(|state| state.get_ref())
+        .ok_or_else(|| {
+            actix_web::error::ErrorInternalServerError(
+                "AppConfigurationState is not registered as app data",
+            )
+        })
+}
+
+impl FromRequest for RequestEntity {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(app_configuration_state(req).and_then(|state| {
+            Ok(state.entity_from_headers(|name| req.headers().get(name)?.to_str().ok())?)
+        }))
+    }
+}
+
+impl<E> FromRequest for Evaluation<E>
+where
+    E: FromRequest + Entity + 'static,
+{
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let client = app_configuration_state(req).map(|state| state.client().clone());
+        let entity = E::from_request(req, payload);
+        Box::pin(async move {
+            let client = client?;
+            let entity = entity.await.map_err(Into::into)?;
+            Ok(Evaluation::new(client, entity))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrations::tests::state;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn test_evaluation_extractor() {
+        actix_web::rt::System::new().block_on(async {
+            let state = state();
+            let req = TestRequest::default()
+                .insert_header(("x-user-id", "a1"))
+                .app_data(web::Data::new(state.clone()))
+                .to_http_request();
+
+            let evaluation = Evaluation::<RequestEntity>::extract(&req).await.unwrap();
+            assert_eq!(evaluation.entity().id, "a1");
+            assert!(evaluation.entity().attributes.is_empty());
+            assert_eq!(
+                evaluation.get_bool_value("f1").ok(),
+                state
+                    .client()
+                    .get_feature("f1")
+                    .and_then(|feature| feature.get_bool_value(evaluation.entity()))
+                    .ok()
+            );
+
+            let req = TestRequest::default()
+                .app_data(web::Data::new(state))
+                .to_http_request();
+            let err = Evaluation::<RequestEntity>::extract(&req)
+                .await
+                .unwrap_err();
+            assert_eq!(
+                err.as_response_error().status_code(),
+                StatusCode::BAD_REQUEST
+            );
+
+            let req = TestRequest::default()
+                .insert_header(("x-user-id", "a1"))
+                .to_http_request();
+            let err = Evaluation::<RequestEntity>::extract(&req)
+                .await
+                .unwrap_err();
+            assert_eq!(
+                err.as_response_error().status_code(),
+                StatusCode::INTERNAL_SERVER_ERROR
+            );
+        });
+    }
+}
@@ -0,0 +1,115 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Extractors for [axum](https://docs.rs/axum).
+//!
+//! Make [`AppConfigurationState`] available from the router state (directly or
+//! through [`FromRef`]) and take an [`Evaluation`] or a [`RequestEntity`] as
+//! handler argument:
+//!
+//! ```ignore
+//! let app = Router::new()
+//!     .route("/checkout", get(checkout))
+//!     .with_state(AppConfigurationState::new(Arc::new(client)));
+//! ```
+
+use axum::extract::{FromRef, FromRequestParts};
+use axum::http::StatusCode;
+use axum::http::request::Parts;
+use axum::response::{IntoResponse, Response};
+
+use super::{AppConfigurationState, Evaluation, RequestEntity, RequestEntityRejection};
+use crate::Entity;
+
+impl IntoResponse for RequestEntityRejection {
+    fn into_response(self) -> Response {
+        (StatusCode::BAD_REQUEST, self.to_string()).into_response()
+    }
+}
+
+impl<S> FromRequestParts<S> for RequestEntity
+where
+    AppConfigurationState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = RequestEntityRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        AppConfigurationState::from_ref(state)
+            .entity_from_headers(|name| parts.headers.get(name)?.to_str().ok())
+    }
+}
+
+impl<S, E> FromRequestParts<S> for Evaluation<E>
+where
+    AppConfigurationState: FromRef<S>,
+    E: FromRequestParts<S> + Entity,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let entity = E::from_request_parts(parts, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        let client = AppConfigurationState::from_ref(state).client().clone();
+        Ok(Evaluation::new(client, entity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrations::tests::state;
+    use crate::{Feature, Value};
+    use axum::http::Request;
+
+    fn parts(headers: &[(&str, &str)]) -> Parts {
+        let mut request = Request::builder();
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        request.body(()).unwrap().into_parts().0
+    }
+
+    #[tokio::test]
+    async fn test_evaluation_extractor() {
+        let state = state();
+        let mut parts = parts(&[("x-user-id", "a1"), ("x-email", "alice@ibm.com")]);
+
+        let evaluation = Evaluation::<RequestEntity>::from_request_parts(&mut parts, &state)
+            .await
+            .unwrap();
+        assert_eq!(evaluation.entity().id, "a1");
+        assert_eq!(
+            evaluation.entity().attributes["email"],
+            Value::String("alice@ibm.com".into())
+        );
+        let expected = state
+            .client()
+            .get_feature("f1")
+            .unwrap()
+            .get_current_value(evaluation.entity())
+            .unwrap();
+        assert_eq!(evaluation.get_feature_value("f1").unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_missing_entity_id() {
+        let rejection = Evaluation::<RequestEntity>::from_request_parts(&mut parts(&[]), &state())
+            .await
+            .unwrap_err();
+        assert_eq!(rejection.status(), StatusCode::BAD_REQUEST);
+    }
+}
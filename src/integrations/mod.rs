@@ -0,0 +1,276 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Integrations with web frameworks.
+//!
+//! Each framework module provides extractors that build the [`Entity`] of an
+//! incoming request and give handlers an [`Evaluation`]: the client paired with
+//! that entity, so features and properties are evaluated with a single call.
+//!
+//! The client and the rules to build the entity are registered once as an
+//! [`AppConfigurationState`]. By default the entity is a [`RequestEntity`] built
+//! from request headers. To build it from other request data, like the claims of
+//! an authenticated user, implement the framework extractor for your own entity
+//! type and use it as `Evaluation<MyEntity>`.
+//!
+//! ```ignore
+//! let state = AppConfigurationState::new(Arc::new(client))
+//!     .with_entity_id_header("x-user-id")
+//!     .with_attribute_header("x-country", "country");
+//!
+//! async fn checkout(evaluation: Evaluation) -> String {
+//!     match evaluation.get_bool_value("new-checkout-ui") {
+//!         Ok(true) => "new".into(),
+//!         _ => "old".into(),
+//!     }
+//! }
+//! ```
+
+#[cfg(feature = "actix")]
+pub mod actix;
+#[cfg(feature = "axum")]
+pub mod axum;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use crate::errors::Result;
+use crate::{
+    AppConfigurationClient, Entity, Feature, FeatureEvaluationResult, Property,
+    PropertyEvaluationResult, Value,
+};
+
+/// Client shared by all the requests and the rules to build a [`RequestEntity`]
+/// from request headers.
+#[derive(Clone)]
+pub struct AppConfigurationState {
+    client: Arc<dyn AppConfigurationClient + Send + Sync>,
+    entity_id_header: String,
+    anonymous_id: Option<String>,
+    attribute_headers: Vec<(String, String)>,
+}
+
+impl AppConfigurationState {
+    /// Uses `client` for all the evaluations. The entity ID is read from the
+    /// `x-entity-id` header.
+    pub fn new(client: Arc<dyn AppConfigurationClient + Send + Sync>) -> Self {
+        Self {
+            client,
+            entity_id_header: "x-entity-id".to_string(),
+            anonymous_id: None,
+            attribute_headers: Vec::new(),
+        }
+    }
+
+    /// Reads the entity ID from `header` instead of `x-entity-id`.
+    pub fn with_entity_id_header(mut self, header: &str) -> Self {
+        self.entity_id_header = header.to_ascii_lowercase();
+        self
+    }
+
+    /// Uses `id` as entity ID for requests without the entity ID header. Such
+    /// requests are rejected with `400 Bad Request` otherwise.
+    pub fn with_anonymous_id(mut self, id: &str) -> Self {
+        self.anonymous_id = Some(id.to_string());
+        self
+    }
+
+    /// Adds the value of `header`, when present, as the String attribute `attribute`
+    /// of the entity.
+    pub fn with_attribute_header(mut self, header: &str, attribute: &str) -> Self {
+        self.attribute_headers
+            .push((header.to_ascii_lowercase(), attribute.to_string()));
+        self
+    }
+
+    /// Returns the client used for the evaluations.
+    pub fn client(&self) -> &Arc<dyn AppConfigurationClient + Send + Sync> {
+        &self.client
+    }
+
+    /// Builds the entity from the headers returned by `header`.
+    fn entity_from_headers<'a>(
+        &self,
+        header: impl Fn(&str) -> Option<&'a str>,
+    ) -> std::result::Result<RequestEntity, RequestEntityRejection> {
+        let id = header(&self.entity_id_header)
+            .map(str::to_string)
+            .or_else(|| self.anonymous_id.clone())
+            .ok_or_else(|| RequestEntityRejection::MissingEntityId {
+                header: self.entity_id_header.clone(),
+            })?;
+        let attributes = self
+            .attribute_headers
+            .iter()
+            .filter_map(|(name, attribute)| {
+                let value = header(name)?;
+                Some((attribute.clone(), Value::String(value.to_string())))
+            })
+            .collect();
+        Ok(RequestEntity { id, attributes })
+    }
+}
+
+impl std::fmt::Debug for AppConfigurationState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppConfigurationState")
+            .field("entity_id_header", &self.entity_id_header)
+            .field("anonymous_id", &self.anonymous_id)
+            .field("attribute_headers", &self.attribute_headers)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Entity built from the headers of a request, see [`AppConfigurationState`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestEntity {
+    pub id: String,
+    pub attributes: HashMap<String, Value>,
+}
+
+impl Entity for RequestEntity {
+    fn get_id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn get_attributes(&self) -> HashMap<String, Value> {
+        self.attributes.clone()
+    }
+}
+
+/// Error building a [`RequestEntity`], the request is answered with `400 Bad Request`.
+#[derive(Debug, Error)]
+pub enum RequestEntityRejection {
+    #[error("Missing header '{header}' with the entity ID")]
+    MissingEntityId { header: String },
+}
+
+/// Evaluates features and properties for the entity of a request.
+pub struct Evaluation<E = RequestEntity> {
+    client: Arc<dyn AppConfigurationClient + Send + Sync>,
+    entity: E,
+}
+
+impl<E: Entity> Evaluation<E> {
+    pub fn new(client: Arc<dyn AppConfigurationClient + Send + Sync>, entity: E) -> Self {
+        Self { client, entity }
+    }
+
+    /// Returns the entity of the request.
+    pub fn entity(&self) -> &E {
+        &self.entity
+    }
+
+    /// Returns the client used for the evaluations.
+    pub fn client(&self) -> &Arc<dyn AppConfigurationClient + Send + Sync> {
+        &self.client
+    }
+
+    /// Evaluates the feature for the entity of the request.
+    pub fn get_feature_value(&self, feature_id: &str) -> Result<FeatureEvaluationResult> {
+        self.client
+            .get_feature(feature_id)?
+            .get_current_value(&self.entity)
+    }
+
+    /// Evaluates the feature for the entity of the request and converts the value to `T`.
+    pub fn get_feature_value_into<T: TryFrom<Value, Error = crate::Error>>(
+        &self,
+        feature_id: &str,
+    ) -> Result<T> {
+        self.client
+            .get_feature(feature_id)?
+            .get_value_into(&self.entity)
+    }
+
+    /// Evaluates a `BOOLEAN` feature for the entity of the request.
+    pub fn get_bool_value(&self, feature_id: &str) -> Result<bool> {
+        self.client
+            .get_feature(feature_id)?
+            .get_bool_value(&self.entity)
+    }
+
+    /// Evaluates the property for the entity of the request.
+    pub fn get_property_value(&self, property_id: &str) -> Result<PropertyEvaluationResult> {
+        self.client
+            .get_property(property_id)?
+            .get_current_value(&self.entity)
+    }
+
+    /// Evaluates the property for the entity of the request and converts the value to `T`.
+    pub fn get_property_value_into<T: TryFrom<Value, Error = crate::Error>>(
+        &self,
+        property_id: &str,
+    ) -> Result<T> {
+        self.client
+            .get_property(property_id)?
+            .get_value_into(&self.entity)
+    }
+}
+
+impl<E: std::fmt::Debug> std::fmt::Debug for Evaluation<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Evaluation")
+            .field("entity", &self.entity)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::AppConfigurationOffline;
+    use crate::network::serialization::fixtures::example_configuration_enterprise_path;
+
+    pub(crate) fn state() -> AppConfigurationState {
+        let client = AppConfigurationOffline::new(
+            &example_configuration_enterprise_path(),
+            "dev",
+            "blue-charge",
+        )
+        .unwrap();
+        AppConfigurationState::new(Arc::new(client))
+            .with_entity_id_header("X-User-Id")
+            .with_attribute_header("x-email", "email")
+    }
+
+    #[test]
+    fn test_entity_from_headers() {
+        let state = state();
+        let headers = HashMap::from([("x-user-id", "a1"), ("x-email", "alice@ibm.com")]);
+        let entity = state
+            .entity_from_headers(|name| headers.get(name).copied())
+            .unwrap();
+        assert_eq!(entity.id, "a1");
+        assert_eq!(
+            entity.attributes,
+            HashMap::from([("email".into(), Value::String("alice@ibm.com".into()))])
+        );
+
+        let err = state.entity_from_headers(|_| None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Missing header 'x-user-id' with the entity ID"
+        );
+
+        let entity = state
+            .with_anonymous_id("anonymous")
+            .entity_from_headers(|_| None)
+            .unwrap();
+        assert_eq!(entity.id, "anonymous");
+        assert!(entity.attributes.is_empty());
+    }
+}
@@ -0,0 +1,253 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Entities built from the claims of an access token.
+//!
+//! The token is decoded and validated by the application, with the library of
+//! its choice, and the claims are passed as a JSON object:
+//!
+//! ```ignore
+//! let claims: serde_json::Map<String, serde_json::Value> = decode_and_validate(token)?;
+//! let entity = JwtEntity::from_claims(&claims)?;
+//! let enabled = client.get_feature("new-checkout-ui")?.get_bool_value(&entity)?;
+//! ```
+//!
+//! By default the entity ID is the `sub` claim and the `email`, `groups` and
+//! `tenant` claims are the attributes of the same name. Use a [`ClaimMapping`]
+//! for other claims.
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::{Entity, Value};
+
+type Claims = serde_json::Map<String, serde_json::Value>;
+
+/// Which claims are the ID and the attributes of a [`JwtEntity`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimMapping {
+    id_claim: String,
+    attribute_claims: Vec<(String, String)>,
+}
+
+impl Default for ClaimMapping {
+    /// The `sub` claim is the ID, and `email`, `groups` and `tenant` the attributes.
+    fn default() -> Self {
+        Self::new("sub")
+            .with_attribute_claim("email", "email")
+            .with_attribute_claim("groups", "groups")
+            .with_attribute_claim("tenant", "tenant")
+    }
+}
+
+impl ClaimMapping {
+    /// Reads the entity ID from `id_claim`, without any attribute.
+    pub fn new(id_claim: &str) -> Self {
+        Self {
+            id_claim: id_claim.to_string(),
+            attribute_claims: Vec::new(),
+        }
+    }
+
+    /// Adds the value of `claim`, when present, as the attribute `attribute`.
+    ///
+    /// Strings, numbers and booleans keep their type. Arrays, like `groups`, are
+    /// lists matching a rule if any of their items does, so `groups is admin`
+    /// matches `["admin", "beta"]` but not `["superadmin"]`. Objects and
+    /// `null` are ignored.
+    pub fn with_attribute_claim(mut self, claim: &str, attribute: &str) -> Self {
+        self.attribute_claims
+            .push((claim.to_string(), attribute.to_string()));
+        self
+    }
+
+    /// Builds the entity from `claims`.
+    ///
+    /// Fails if the ID claim is missing, or is not a non-empty string or a number.
+    pub fn entity(&self, claims: &Claims) -> Result<JwtEntity, JwtEntityError> {
+        let id = match claims.get(&self.id_claim) {
+            Some(serde_json::Value::String(id)) if !id.trim().is_empty() => id.clone(),
+            Some(serde_json::Value::Number(id)) => id.to_string(),
+            Some(_) => {
+                return Err(JwtEntityError::InvalidIdClaim {
+                    claim: self.id_claim.clone(),
+                });
+            }
+            None => {
+                return Err(JwtEntityError::MissingIdClaim {
+                    claim: self.id_claim.clone(),
+                });
+            }
+        };
+        let attributes = self
+            .attribute_claims
+            .iter()
+            .filter_map(|(claim, attribute)| {
+                let value = claim_value(claims.get(claim)?)?;
+                Some((attribute.clone(), value))
+            })
+            .collect();
+        Ok(JwtEntity { id, attributes })
+    }
+}
+
+/// Entity built from the claims of an access token, see [`ClaimMapping`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct JwtEntity {
+    pub id: String,
+    pub attributes: HashMap<String, Value>,
+}
+
+impl JwtEntity {
+    /// Builds the entity from `claims` with the default [`ClaimMapping`].
+    pub fn from_claims(claims: &Claims) -> Result<Self, JwtEntityError> {
+        ClaimMapping::default().entity(claims)
+    }
+}
+
+impl Entity for JwtEntity {
+    fn get_id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn get_attributes(&self) -> HashMap<String, Value> {
+        self.attributes.clone()
+    }
+}
+
+/// Error building a [`JwtEntity`].
+#[derive(Debug, Error)]
+pub enum JwtEntityError {
+    #[error("Missing claim '{claim}' with the entity ID")]
+    MissingIdClaim { claim: String },
+
+    #[error("Claim '{claim}' with the entity ID must be a non-empty string or a number")]
+    InvalidIdClaim { claim: String },
+}
+
+fn claim_value(claim: &serde_json::Value) -> Option<Value> {
+    match claim {
+        serde_json::Value::String(s) => Some(Value::String(s.clone())),
+        serde_json::Value::Bool(b) => Some(Value::Boolean(*b)),
+        serde_json::Value::Number(n) => n
+            .as_u64()
+            .map(Value::UInt64)
+            .or_else(|| n.as_i64().map(Value::Int64))
+            .or_else(|| n.as_f64().map(Value::Float64)),
+        serde_json::Value::Array(items) => {
+            Some(Value::List(items.iter().filter_map(claim_value).collect()))
+        }
+        serde_json::Value::Object(_) | serde_json::Value::Null => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn claims(value: serde_json::Value) -> Claims {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_from_claims() {
+        let claims = claims(json!({
+            "sub": "a1",
+            "email": "alice@ibm.com",
+            "groups": ["admin", "beta"],
+            "tenant": 42,
+            "exp": 1767225600,
+        }));
+        let entity = JwtEntity::from_claims(&claims).unwrap();
+        assert_eq!(entity.get_id(), "a1");
+        assert_eq!(
+            entity.get_attributes(),
+            HashMap::from([
+                ("email".into(), Value::String("alice@ibm.com".into())),
+                (
+                    "groups".into(),
+                    Value::List(vec![
+                        Value::String("admin".into()),
+                        Value::String("beta".into())
+                    ])
+                ),
+                ("tenant".into(), Value::UInt64(42)),
+            ])
+        );
+
+        let err = JwtEntity::from_claims(&Claims::new()).unwrap_err();
+        assert_eq!(err.to_string(), "Missing claim 'sub' with the entity ID");
+    }
+
+    #[test]
+    fn test_invalid_id_claim() {
+        for sub in [json!(""), json!(" "), json!(null), json!(["a1"])] {
+            let err = JwtEntity::from_claims(&claims(json!({"sub": sub}))).unwrap_err();
+            assert!(matches!(err, JwtEntityError::InvalidIdClaim { .. }));
+        }
+    }
+
+    #[test]
+    fn test_groups_match_exactly() {
+        use crate::{Configuration, ConfigurationProvider};
+
+        let json = json!({
+            "environments": [{
+                "name": "Dev", "environment_id": "dev", "features": [], "properties": []
+            }],
+            "segments": [{
+                "name": "Admins",
+                "segment_id": "admins",
+                "rules": [{"attribute_name": "groups", "operator": "is", "values": ["admin"]}]
+            }]
+        });
+        let configuration = Configuration::from_json_str(&json.to_string(), "dev", "c1").unwrap();
+        let is_admin = |groups: serde_json::Value| {
+            let entity = JwtEntity::from_claims(&claims(json!({"sub": "a1", "groups": groups})));
+            configuration
+                .entity_matches_segment("admins", &entity.unwrap())
+                .unwrap()
+        };
+        assert!(is_admin(json!(["beta", "admin"])));
+        assert!(!is_admin(json!(["superadmin"])));
+    }
+
+    #[test]
+    fn test_claim_mapping() {
+        let claims = claims(json!({
+            "sub": "a1",
+            "user_id": 7,
+            "https://example.com/plan": "gold",
+            "verified": true,
+            "address": {"country": "ES"},
+        }));
+        let entity = ClaimMapping::new("user_id")
+            .with_attribute_claim("https://example.com/plan", "plan")
+            .with_attribute_claim("verified", "verified")
+            .with_attribute_claim("address", "address")
+            .with_attribute_claim("email", "email")
+            .entity(&claims)
+            .unwrap();
+        assert_eq!(entity.id, "7");
+        assert_eq!(
+            entity.attributes,
+            HashMap::from([
+                ("plan".into(), Value::String("gold".into())),
+                ("verified".into(), Value::Boolean(true)),
+            ])
+        );
+    }
+}
@@ -61,7 +61,7 @@
 //! # let collection_id: String = "ecommerce".to_string();
 //!
 //! // Create the client connecting to the server
-//! let configuration = ConfigurationId::new(guid, environment_id, collection_id);
+//! let configuration = ConfigurationId::new(guid, environment_id, collection_id)?;
 //! let client = AppConfigurationClientIBMCloud::new(&apikey, &region, configuration, OfflineMode::Fail, false, Default::default(), RuntimeEventEmitter::new())?;
 //!
 //! // Get the feature you want to evaluate for your entities
@@ -81,39 +81,102 @@
 //! # }
 //! ```
 //!
+//! # Cargo features
+//!
+//! * `remote` (default): clients connecting to the App Configuration service. Disable
+//!   default features to build only the offline evaluation engine
+//!   ([`AppConfigurationOffline`], snapshots, segments and [`Value`]), which also
+//!   compiles to `wasm32-unknown-unknown`.
+//! * `axum`, `actix`: extractors that build an [`Entity`] from incoming requests
+//!   and evaluate features and properties for it, see `integrations`.
+//! * `jwt`: an [`Entity`] built from the claims of an access token, see `jwt`.
+//! * `redis`: a [`DistributedCache`] sharing the configurations downloaded by a
+//!   fleet of clients through Redis, see `redis_cache`.
+//! * `codegen`: generation of typed accessors from a configuration dump, to use in
+//!   build scripts, see `codegen`.
+//! * `yaml`, `toml`: configuration files in YAML or TOML, see
+//!   [`ConfigurationFileFormat`].
+//! * `ffi`: a C ABI over the client, see `ffi` and `include/ibm_appconfiguration.h`.
+//! * `daemon`: a line-delimited JSON server over a Unix socket, on Unix only, see
+//!   `daemon`.
+//! * `prometheus`: the metrics of a client in the Prometheus text format, see
+//!   `prometheus`.
+//! * `simd-json`: parsing of the downloaded configurations with SIMD instructions,
+//!   faster for large configurations.
+//!
+
 mod client;
+#[cfg(feature = "codegen")]
+pub mod codegen;
+#[cfg(all(feature = "daemon", unix))]
+pub mod daemon;
 mod entity;
 mod errors;
 mod feature;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(any(feature = "axum", feature = "actix"))]
+pub mod integrations;
+#[cfg(feature = "jwt")]
+pub mod jwt;
 pub(crate) mod metering;
 mod models;
 mod network;
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
 mod property;
+#[cfg(feature = "redis")]
+pub mod redis_cache;
 mod segment_evaluation;
 pub(crate) mod utils;
 mod value;
 
+#[cfg(feature = "remote")]
 pub use client::{
-    AppConfiguration, AppConfigurationClient, AppConfigurationClientIBMCloud,
-    AppConfigurationContextOptions, AppConfigurationOffline, ConfigurationId,
-    ConfigurationProvider, ResolvedUrls, RuntimeEvent, RuntimeEventEmitter, RuntimeEventKind,
-    RuntimeMode, RuntimeStatus,
+    AppConfiguration, AppConfigurationClientIBMCloud, AppConfigurationContextOptions,
+    Authenticator, ResolvedUrls,
+};
+pub use client::{
+    AppConfigurationClient, AppConfigurationOffline, ConfigurationDump, ConfigurationId,
+    ConfigurationProvider, DeprecatedFlagUsage, EvaluationSession, Exposure, ExposureListener,
+    FeatureProxy, LatencyHistogram, Layered, Overrides, PinnedConfiguration, PrewarmReport,
+    PropertyProxy, RuntimeEvent, RuntimeEventEmitter, RuntimeEventKind, RuntimeEventListener,
+    RuntimeMode, RuntimeStatus, SdkError, SdkErrorListener, SdkErrorSnapshot, SdkStats, SyncRecord,
+    UnusedFeatures,
 };
 pub use entity::Entity;
-pub use errors::{ConfigurationDataError, Error, Result};
+pub use errors::{BuildError, ConfigurationDataError, Error, EvaluationError, Result};
 pub use feature::Feature;
+pub use metering::{MeteringEntityIds, MeteringOptions};
 pub use models::{
-    EvaluationContext, EvaluationRuleCondition, EvaluationRuleContext, EvaluationSegmentContext,
+    Configuration, ConfigurationDiff, ConfigurationSource, EvaluationContext,
+    EvaluationRuleCondition, EvaluationRuleContext, EvaluationSegmentContext,
     FeatureEvaluationDetails, FeatureEvaluationResult, PropertyEvaluationDetails,
-    PropertyEvaluationResult, SecretManager, SecretPropertySnapshot,
+    PropertyEvaluationResult, ResourceChange, ResourceKind, SecretManager, SecretPropertySnapshot,
+    SegmentRuleSnapshot, SegmentSnapshot, SkippedResource, ValidationMode, ValidationReport,
+    Variation,
 };
 pub use network::live_configuration::CurrentModeOfflineReason;
-pub use network::live_configuration::OfflineMode;
-pub(crate) use network::{ServerClientImpl, TokenProviderImpl};
+pub use network::live_configuration::{
+    CacheCipher, CachedConfiguration, ConfigurationGuard, DistributedCache, GuardDecision,
+    LiveConfigurationOptions, OfflineDetails, OfflineMode, SharedCache,
+};
+#[cfg(feature = "remote")]
+pub(crate) use network::{ServerClientImpl, TokenProviderImpl, TrustedProfileTokenProviderImpl};
 pub use property::Property;
+pub use utils::{Clock, Ready, SystemClock, ThreadOptions};
 pub use value::Value;
 
-pub use network::ServiceAddress;
+pub use network::serialization::{ConfigurationFileFormat, ConfigurationJson};
+pub use network::{NetworkError, NetworkErrorCategory, NetworkResult};
+#[cfg(feature = "remote")]
+pub use network::{
+    OutgoingRequest, RequestAuthenticator, ServerClient, ServiceAddress, SocketOptions,
+    TokenProvider, WebsocketReader,
+};
+/// The websocket library of the SDK, needed to implement [`WebsocketReader`].
+#[cfg(feature = "remote")]
+pub use tungstenite;
 #[cfg(test)]
 mod tests;
 
@@ -16,5 +16,26 @@ use super::MeteringResult;
 use crate::metering::serialization::MeteringDataJson;
 
 pub(crate) trait MeteringClient: Send + 'static {
-    fn push_metering_data(&self, guid: &str, data: &MeteringDataJson) -> MeteringResult<()>;
+    /// Sends `data` to the server. Returns how far the local clock is ahead of
+    /// the one of the server, if the response gives the date of the server.
+    fn push_metering_data(
+        &self,
+        guid: &str,
+        data: &MeteringDataJson,
+    ) -> MeteringResult<Option<chrono::TimeDelta>>;
+}
+
+/// Drops the metering data, used when the configuration is not downloaded from
+/// the App Configuration service.
+#[derive(Debug)]
+pub(crate) struct NoopMeteringClient;
+
+impl MeteringClient for NoopMeteringClient {
+    fn push_metering_data(
+        &self,
+        _guid: &str,
+        _data: &MeteringDataJson,
+    ) -> MeteringResult<Option<chrono::TimeDelta>> {
+        Ok(None)
+    }
 }
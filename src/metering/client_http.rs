@@ -17,50 +17,79 @@ use crate::metering::serialization::MeteringDataJson;
 use crate::metering::{MeteringClient, MeteringError, MeteringResult};
 use crate::network::NetworkError;
 use crate::network::http_client::SDK_USER_AGENT;
-use crate::network::{ServiceAddress, ServiceAddressProtocol, TokenProvider};
+use crate::network::{
+    OutgoingRequest, RequestAuthenticator, ServiceAddress, ServiceAddressProtocol,
+};
 use reqwest::blocking::Client;
+use reqwest::header::{CONTENT_TYPE, DATE, HeaderMap, HeaderValue, USER_AGENT};
 use url::Url;
 
 /// A MeteringClient pushing metering data to a http server.
 #[derive(Debug)]
 pub(crate) struct MeteringClientHttp {
     service_address: ServiceAddress,
-    token_provider: Arc<Box<dyn TokenProvider>>,
+    authenticator: Arc<dyn RequestAuthenticator>,
 }
 
 impl MeteringClientHttp {
     pub(crate) fn new(
         service_address: ServiceAddress,
-        token_provider: Arc<Box<dyn TokenProvider>>,
+        authenticator: Arc<dyn RequestAuthenticator>,
     ) -> MeteringClientHttp {
         Self {
             service_address,
-            token_provider,
+            authenticator,
         }
     }
 }
 
+/// How far the local clock is ahead of the server, given the `Date` header of a
+/// response and the local times the request was sent and the response received.
+/// The header has a resolution of one second, the request is assumed to reach
+/// the server halfway.
+fn clock_offset(
+    headers: &HeaderMap,
+    sent: chrono::DateTime<chrono::Utc>,
+    received: chrono::DateTime<chrono::Utc>,
+) -> Option<chrono::TimeDelta> {
+    let server_date = headers.get(DATE)?.to_str().ok()?;
+    let server_date = chrono::DateTime::parse_from_rfc2822(server_date).ok()?;
+    let local_date = sent + (received - sent) / 2;
+    Some(local_date - server_date.to_utc())
+}
+
 impl MeteringClient for MeteringClientHttp {
-    fn push_metering_data(&self, guid: &str, data: &MeteringDataJson) -> MeteringResult<()> {
+    fn push_metering_data(
+        &self,
+        guid: &str,
+        data: &MeteringDataJson,
+    ) -> MeteringResult<Option<chrono::TimeDelta>> {
         let url = format!(
             "{}/events/v1/instances/{}/usage",
             self.service_address.base_url(ServiceAddressProtocol::Http),
             guid
         );
-        let url = Url::parse(&url).map_err(|_| NetworkError::UrlParseError(url))?;
+        let mut url = Url::parse(&url).map_err(|_| NetworkError::UrlParseError(url))?;
+        let body = serde_json::to_vec(data).map_err(|_| NetworkError::ProtocolError)?;
+        let mut headers = self.service_address.custom_headers()?;
+        headers.insert(USER_AGENT, HeaderValue::from_static(SDK_USER_AGENT));
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        self.authenticator.authenticate(&mut OutgoingRequest::new(
+            "POST",
+            &mut url,
+            &mut headers,
+            &body,
+        ))?;
+
         let client = Client::new();
-        let r = client
-            .post(url)
-            .header("User-Agent", SDK_USER_AGENT)
-            .bearer_auth(self.token_provider.get_access_token()?)
-            .json(data)
-            .send();
+        let sent = chrono::Utc::now();
+        let r = client.post(url).headers(headers).body(body).send();
 
         match r {
             Ok(response) => {
                 let status = response.status();
                 if status.is_success() {
-                    Ok(())
+                    Ok(clock_offset(response.headers(), sent, chrono::Utc::now()))
                 } else {
                     Err(MeteringError::DataNotAccepted(status.to_string()))
                 }
@@ -73,6 +102,8 @@ impl MeteringClient for MeteringClientHttp {
 #[cfg(test)]
 pub(crate) mod tests {
     use super::*;
+    use crate::metering::MeteringMetadata;
+    use crate::network::TokenProvider;
 
     use httpmock::Method::POST;
     use httpmock::MockServer;
@@ -103,6 +134,10 @@ pub(crate) mod tests {
                     {
                     "collection_id": "test",
                     "environment_id": "dev",
+                    "sdk_name": "appconfiguration-rust-sdk",
+                    "sdk_version": "1.2.3",
+                    "host": "test-host",
+                    "instance_label": "canary",
                     "usages": []
                     }
                 ));
@@ -111,10 +146,16 @@ pub(crate) mod tests {
 
         let client = MeteringClientHttp::new(
             ServiceAddress::new_without_ssl(server.host(), Some(server.port()), None),
-            Arc::new(Box::new(MockTokenProvider::default())),
+            Arc::new(MockTokenProvider::default()),
         );
 
-        let data = MeteringDataJson::new("test".to_string(), "dev".to_string());
+        let metadata = MeteringMetadata {
+            sdk_name: "appconfiguration-rust-sdk".to_string(),
+            sdk_version: "1.2.3".to_string(),
+            host: Some("test-host".to_string()),
+            instance_label: Some("canary".to_string()),
+        };
+        let data = MeteringDataJson::new("test".to_string(), "dev".to_string(), &metadata);
 
         let result = client.push_metering_data("example_guid", &data);
 
@@ -133,14 +174,57 @@ pub(crate) mod tests {
 
         let client = MeteringClientHttp::new(
             ServiceAddress::new_without_ssl(server.host(), Some(server.port()), None),
-            Arc::new(Box::new(MockTokenProvider::default())),
+            Arc::new(MockTokenProvider::default()),
         );
 
-        let data = MeteringDataJson::new("test".to_string(), "dev".to_string());
+        let data = MeteringDataJson::new(
+            "test".to_string(),
+            "dev".to_string(),
+            &MeteringMetadata::new(None, false),
+        );
 
         let result = client.push_metering_data("example_guid", &data);
 
         assert!(matches!(result, Err(MeteringError::DataNotAccepted(_))));
         mock.assert();
     }
+
+    /// The offset of the local clock is measured with the `Date` header of the response.
+    #[test]
+    fn test_clock_offset() {
+        let server_date = chrono::Utc::now() - chrono::TimeDelta::hours(1);
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST);
+            then.status(200)
+                .header("date", server_date.to_rfc2822().replace("+0000", "GMT"));
+        });
+
+        let client = MeteringClientHttp::new(
+            ServiceAddress::new_without_ssl(server.host(), Some(server.port()), None),
+            Arc::new(MockTokenProvider::default()),
+        );
+        let data = MeteringDataJson::new(
+            "test".to_string(),
+            "dev".to_string(),
+            &MeteringMetadata::new(None, false),
+        );
+
+        let offset = client
+            .push_metering_data("example_guid", &data)
+            .unwrap()
+            .unwrap();
+        assert!((offset - chrono::TimeDelta::hours(1)).abs() < chrono::TimeDelta::seconds(5));
+        mock.assert();
+    }
+
+    #[test]
+    fn test_clock_offset_without_date() {
+        let now = chrono::Utc::now();
+        assert_eq!(clock_offset(&HeaderMap::new(), now, now), None);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(DATE, HeaderValue::from_static("yesterday"));
+        assert_eq!(clock_offset(&headers, now, now), None);
+    }
 }
@@ -12,19 +12,73 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "remote")]
 use log::debug;
 use log::warn;
 
+use crate::Entity;
+#[cfg(feature = "remote")]
 use crate::metering::models::{
-    EvaluationData, EvaluationEvent, EvaluationEventData, MeteringKey, SubjectId,
+    EntityIdEncoding, EvaluationData, MeteringEntityIds, MeteringKey, MeteringMetadata,
+    MeteringOptions,
 };
+use crate::metering::models::{EvaluationEvent, EvaluationEventData, SubjectId, ValueSource};
+#[cfg(feature = "remote")]
 use crate::metering::serialization::MeteringDataJson;
+#[cfg(feature = "remote")]
 use crate::metering::{MeteringClient, MeteringError};
 use crate::models::{FeatureSnapshot, PropertySnapshot};
 use crate::network::serialization::Segment;
-use crate::utils::ThreadHandle;
-use crate::{ConfigurationId, Entity};
-use std::sync::mpsc;
+use crate::utils::RwLockExt;
+#[cfg(feature = "remote")]
+use crate::utils::{MutexExt, ScheduledTask, TaskRun, TaskWaker, ThreadOptions, panic_message};
+#[cfg(feature = "remote")]
+use crate::{ConfigurationId, RuntimeEventEmitter, SdkError, UnusedFeatures};
+#[cfg(feature = "remote")]
+use chrono::{DateTime, Utc};
+use std::cell::RefCell;
+use std::collections::HashMap;
+#[cfg(feature = "remote")]
+use std::ops::ControlFlow;
+#[cfg(feature = "remote")]
+use std::sync::Mutex;
+#[cfg(feature = "remote")]
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock, mpsc};
+#[cfg(feature = "remote")]
+use std::time::Instant;
+
+#[cfg(feature = "remote")]
+const RETRY_INITIAL_DELAY: std::time::Duration = std::time::Duration::from_secs(15);
+#[cfg(feature = "remote")]
+const RETRY_MAX_DELAY: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+#[cfg(feature = "remote")]
+const RETRY_MULTIPLIER: u32 = 2;
+/// Clock offsets to the server above which a warning is logged.
+#[cfg(feature = "remote")]
+const CLOCK_SKEW_WARNING: chrono::TimeDelta = chrono::TimeDelta::minutes(1);
+/// How often the metering task aggregates the evaluation events and looks at
+/// the transmission interval.
+#[cfg(feature = "remote")]
+const AGGREGATION_PERIOD: std::time::Duration = std::time::Duration::from_millis(100);
+/// Evaluation events the metering task has not aggregated yet, beyond which
+/// new events are dropped.
+#[cfg(feature = "remote")]
+const QUEUE_CAPACITY: usize = 10_000;
+/// Evaluation events queued from which the metering task runs before the end
+/// of its period.
+#[cfg(feature = "remote")]
+const QUEUE_WAKE_THRESHOLD: usize = QUEUE_CAPACITY / 2;
+/// Features missing from the configuration remembered by [`FeatureUsage`],
+/// so that requests of arbitrary names do not grow the memory used.
+#[cfg(feature = "remote")]
+const MAX_MISSING_FEATURES: usize = 1_000;
+/// Evaluations of an [`EvaluationBatch`] queued together in one event.
+const MAX_BATCH_LEN: usize = 1_000;
+/// Name of the metering threads when [`ThreadOptions::name`] is not set.
+#[cfg(feature = "remote")]
+const METERING_THREAD_NAME: &str = "appconfig-meter";
 
 /// Starts periodic metering transmission to the server.
 ///
@@ -33,67 +87,357 @@ use std::sync::mpsc;
 /// * `config_id` - The ConfigurationID to which all evaluations are associated to when reported to the server.
 /// * `transmit_interval` - Time between transmissions to the server
 /// * `client` - Used for push access to the server
+/// * `metadata` - Identifies the SDK and the deployment in every transmission
+/// * `options` - What is recorded and sent, e.g. whether entity IDs are hashed.
+/// * `thread_options` - How the metering thread is spawned, it is named `appconfig-meter` by default.
+///   The clients with the same options share it.
+/// * `error_emitter` - Receives the failed transmissions and the panics of the metering task.
 ///
 /// # Return values
 ///
 /// * MeteringRecorder - Use this to record all evaluations, which will eventually be sent to the server.
-const RETRY_INITIAL_DELAY: std::time::Duration = std::time::Duration::from_secs(15);
-const RETRY_MAX_DELAY: std::time::Duration = std::time::Duration::from_secs(60 * 60);
-const RETRY_MULTIPLIER: u32 = 2;
-
+#[cfg(feature = "remote")]
 pub(crate) fn start_metering<T: MeteringClient>(
     config_id: ConfigurationId,
     transmit_interval: std::time::Duration,
     client: T,
+    metadata: MeteringMetadata,
+    options: MeteringOptions,
+    thread_options: &ThreadOptions,
+    error_emitter: RuntimeEventEmitter,
 ) -> MeteringRecorder {
-    let (sender, receiver) = mpsc::channel();
-
-    let thread = ThreadHandle::new(move |_terminator: mpsc::Receiver<()>| {
-        let mut batcher = MeteringBatcher::new(client, config_id);
-        let mut last_flush = std::time::Instant::now();
-        debug!("Starting Metering transmitting thread");
-        loop {
-            let recv_result = receiver.recv_timeout(std::time::Duration::from_millis(100));
-            match recv_result {
-                // Actually received an event, sort it in using the batcher:
-                Ok(event) => batcher.handle_event(event),
-                // Hit the timeout, do nothing here, but give the batcher a chance to flush:
-                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
-                // All senders have been dropped, exit the thread:
-                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+    let (sender, receiver) = mpsc::sync_channel(QUEUE_CAPACITY);
+    let stats = Arc::new(MeteringStats::default());
+    let stopped = Arc::new(AtomicBool::new(false));
+
+    let mut batcher = MeteringBatcher::new(client, config_id, metadata)
+        .with_entity_ids(options.entity_ids)
+        .with_evaluation_details(options.send_evaluation_details)
+        .with_error_emitter(error_emitter)
+        .with_stats(stats.clone());
+    let thread_options = ThreadOptions {
+        name: Some(
+            thread_options
+                .name
+                .clone()
+                .unwrap_or_else(|| METERING_THREAD_NAME.to_string()),
+        ),
+        ..thread_options.clone()
+    };
+    let task_stopped = stopped.clone();
+    let task_stats = stats.clone();
+    let mut last_flush = std::time::Instant::now();
+    debug!("Scheduling metering transmissions");
+    // Runs on a thread shared with the metering of other clients, instead of
+    // the update thread, so a slow transmission never delays the updates.
+    let task = ScheduledTask::new(&thread_options, AGGREGATION_PERIOD, move |run| {
+        task_stats.feature_usage.tick();
+        let mut stopping = false;
+        // A bug in metering must not stop the evaluations being metered, nor
+        // the metering of the other clients. The evaluations of the batch are
+        // lost though.
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            // Bounded, as evaluations may be queued as fast as they are read
+            for event in receiver.try_iter().take(QUEUE_CAPACITY) {
+                task_stats.queued.fetch_sub(1, Ordering::Relaxed);
+                batcher.handle_event(event);
             }
-            if last_flush.elapsed() >= transmit_interval {
+            // All the senders have been dropped
+            let disconnected = matches!(receiver.try_recv(), Err(mpsc::TryRecvError::Disconnected));
+            // The recorder was dropped, or the client became defunct
+            stopping = run == TaskRun::Last || disconnected || task_stopped.load(Ordering::Relaxed);
+            if stopping || last_flush.elapsed() >= transmit_interval {
                 batcher.flush();
                 last_flush = std::time::Instant::now();
             }
+        }));
+        if let Err(payload) = result {
+            batcher
+                .error_emitter
+                .emit_error(SdkError::ThreadPanicked(panic_message(&*payload)));
+            batcher.clear_evaluations();
+        }
+        if stopping {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
         }
     });
 
     MeteringRecorder {
-        _thread: thread,
         sender: MeteringRecorderSender {
             evaluation_event_sender: sender,
+            record_disabled_evaluations: options.record_disabled_evaluations,
+            stopped,
+            stats,
+            waker: Some(task.waker()),
         },
+        _task: task,
+    }
+}
+
+/// Counters shared by the senders of the evaluation events and the batcher.
+#[derive(Debug, Default)]
+pub(crate) struct MeteringStats {
+    /// Evaluations of the features and properties of the client.
+    pub(crate) evaluations: AtomicU64,
+    /// Evaluations recorded and not yet sent to the server.
+    pub(crate) pending: AtomicU64,
+    /// Transmissions to the server that failed.
+    #[cfg(feature = "remote")]
+    pub(crate) flush_failures: AtomicU64,
+    /// Evaluations not recorded because the metering task fell behind.
+    pub(crate) dropped: AtomicU64,
+    /// Evaluation events queued and not aggregated yet.
+    #[cfg(feature = "remote")]
+    queued: AtomicUsize,
+    /// Whether the dropped evaluations were logged already.
+    dropped_warned: AtomicBool,
+    pub(crate) feature_usage: FeatureUsage,
+}
+
+/// When the features were last evaluated, and when the features missing from
+/// the configuration were last requested.
+///
+/// Evaluations read the time from a clock ticked by the metering thread, and
+/// update an atomic per feature: a lock is only written the first time a
+/// feature is evaluated.
+#[derive(Debug)]
+pub(crate) struct FeatureUsage {
+    #[cfg(feature = "remote")]
+    started: DateTime<Utc>,
+    #[cfg(feature = "remote")]
+    started_at: Instant,
+    /// Milliseconds since `started_at`, updated by the metering thread at
+    /// least every [`RECEIVE_TIMEOUT`].
+    clock: AtomicU64,
+    /// Value of `clock` at the last evaluation of each feature.
+    evaluated: RwLock<HashMap<String, AtomicU64>>,
+    /// Value of `clock` at the last request of each feature missing from the
+    /// configuration, at most [`MAX_MISSING_FEATURES`] of them.
+    #[cfg(feature = "remote")]
+    missing: Mutex<HashMap<String, u64>>,
+}
+
+impl Default for FeatureUsage {
+    fn default() -> Self {
+        Self {
+            #[cfg(feature = "remote")]
+            started: Utc::now(),
+            #[cfg(feature = "remote")]
+            started_at: Instant::now(),
+            clock: AtomicU64::new(0),
+            evaluated: RwLock::default(),
+            #[cfg(feature = "remote")]
+            missing: Mutex::default(),
+        }
+    }
+}
+
+impl FeatureUsage {
+    /// Advances the clock read by the evaluations.
+    #[cfg(feature = "remote")]
+    fn tick(&self) {
+        self.clock.store(
+            self.started_at.elapsed().as_millis() as u64,
+            Ordering::Relaxed,
+        );
+    }
+
+    fn record_evaluation(&self, feature_id: &str) {
+        let now = self.clock.load(Ordering::Relaxed);
+        if let Some(last) = self.evaluated.read_unpoisoned().get(feature_id) {
+            // Only written once per tick, the other evaluations just read it
+            if last.load(Ordering::Relaxed) < now {
+                last.store(now, Ordering::Relaxed);
+            }
+            return;
+        }
+        self.evaluated
+            .write_unpoisoned()
+            .entry(feature_id.to_string())
+            .or_default()
+            .fetch_max(now, Ordering::Relaxed);
+    }
+
+    #[cfg(feature = "remote")]
+    pub(crate) fn record_missing(&self, feature_id: &str) {
+        let now = self.clock.load(Ordering::Relaxed);
+        let mut missing = self.missing.lock_unpoisoned();
+        if let Some(last) = missing.get_mut(feature_id) {
+            *last = now;
+        } else if missing.len() < MAX_MISSING_FEATURES {
+            missing.insert(feature_id.to_string(), now);
+        }
+    }
+
+    /// Compares the usage within `window` with the features of the configuration.
+    #[cfg(feature = "remote")]
+    pub(crate) fn unused_features(
+        &self,
+        feature_ids: Vec<String>,
+        window: Option<std::time::Duration>,
+    ) -> UnusedFeatures {
+        let since = window
+            .and_then(|window| chrono::Duration::from_std(window).ok())
+            .map_or(self.started, |window| {
+                (Utc::now() - window).max(self.started)
+            });
+        let since_clock = (since - self.started).num_milliseconds().max(0) as u64;
+        let evaluated = self.evaluated.read_unpoisoned();
+        let mut never_evaluated: Vec<String> = feature_ids
+            .iter()
+            .filter(|id| {
+                evaluated
+                    .get(*id)
+                    .is_none_or(|last| last.load(Ordering::Relaxed) < since_clock)
+            })
+            .cloned()
+            .collect();
+        never_evaluated.sort();
+        let mut missing: Vec<String> = self
+            .missing
+            .lock_unpoisoned()
+            .iter()
+            .filter(|(id, last)| **last >= since_clock && !feature_ids.contains(id))
+            .map(|(id, _)| id.clone())
+            .collect();
+        missing.sort();
+        UnusedFeatures {
+            since,
+            never_evaluated,
+            missing,
+        }
     }
 }
 
 /// Allows recording of evaluation events.
-/// Communicates with the thread, which leads to eventual transmission of recorded evaluations to the server.
+/// Communicates with the metering task, which leads to eventual transmission of recorded evaluations to the server.
+/// The task stops, sending what was recorded, when this object is dropped.
 #[derive(Debug)]
+#[cfg(feature = "remote")]
 pub(crate) struct MeteringRecorder {
-    _thread: ThreadHandle<()>,
     pub(crate) sender: MeteringRecorderSender,
+    _task: ScheduledTask,
 }
 
 #[derive(Debug, Clone)]
 pub(crate) struct MeteringRecorderSender {
-    evaluation_event_sender: mpsc::Sender<EvaluationEvent>,
+    evaluation_event_sender: mpsc::SyncSender<EvaluationEvent>,
+    record_disabled_evaluations: bool,
+    /// Set once the client is defunct, evaluations are no longer recorded.
+    stopped: Arc<AtomicBool>,
+    pub(crate) stats: Arc<MeteringStats>,
+    /// Runs the metering task when evaluations pile up.
+    #[cfg(feature = "remote")]
+    waker: Option<TaskWaker>,
+}
+
+impl MeteringRecorderSender {
+    /// Whether feature evaluations serving a value from `value_source` are
+    /// recorded at all.
+    fn records_feature(&self, value_source: ValueSource) -> bool {
+        self.record_disabled_evaluations || value_source.is_enabled()
+    }
+
+    /// Stops recording evaluations, and makes the metering task send what
+    /// was recorded and stop.
+    #[cfg(feature = "remote")]
+    pub(crate) fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+
+    /// Queues `event` for the metering task. Never blocks: the event is
+    /// dropped, and counted, if the task fell behind. Does nothing once
+    /// [`MeteringRecorderSender::stop`] was called.
+    fn send(&self, event: EvaluationEvent) -> Result<(), mpsc::TrySendError<EvaluationEvent>> {
+        if self.stopped.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let evaluations = event.evaluations();
+        // Counted before sending, so the batcher never sees it sent and not pending
+        self.stats.pending.fetch_add(evaluations, Ordering::Relaxed);
+        #[cfg(feature = "remote")]
+        if self.stats.queued.fetch_add(1, Ordering::Relaxed) + 1 == QUEUE_WAKE_THRESHOLD
+            && let Some(waker) = &self.waker
+        {
+            waker.wake();
+        }
+        self.evaluation_event_sender.try_send(event).or_else(|e| {
+            self.stats.pending.fetch_sub(evaluations, Ordering::Relaxed);
+            #[cfg(feature = "remote")]
+            self.stats.queued.fetch_sub(1, Ordering::Relaxed);
+            match e {
+                mpsc::TrySendError::Full(_) => {
+                    self.stats.dropped.fetch_add(evaluations, Ordering::Relaxed);
+                    if !self.stats.dropped_warned.swap(true, Ordering::Relaxed) {
+                        warn!(
+                            "Metering cannot keep up with the evaluations, some are not recorded."
+                        );
+                    }
+                    Ok(())
+                }
+                disconnected => Err(disconnected),
+            }
+        })
+    }
+}
+
+/// Evaluations made together, e.g. by
+/// [`Feature::get_values_for`](crate::Feature::get_values_for), queued for the
+/// metering thread in a single event every [`MAX_BATCH_LEN`] evaluations
+/// instead of one event each. The rest are queued when the batch is dropped.
+pub(crate) struct EvaluationBatch<'a> {
+    recorder: Option<&'a MeteringRecorderSender>,
+    events: RefCell<Vec<EvaluationEvent>>,
+}
+
+impl<'a> EvaluationBatch<'a> {
+    pub(crate) fn new(recorder: Option<&'a MeteringRecorderSender>) -> Self {
+        Self {
+            recorder,
+            events: RefCell::default(),
+        }
+    }
+
+    fn push(&self, event: EvaluationEvent) {
+        let mut events = self.events.borrow_mut();
+        events.push(event);
+        if events.len() == MAX_BATCH_LEN {
+            self.send(std::mem::take(&mut events));
+        }
+    }
+
+    fn send(&self, events: Vec<EvaluationEvent>) {
+        if let Some(recorder) = self.recorder
+            && let Err(e) = recorder.send(EvaluationEvent::Batch(events))
+        {
+            warn!("Fail to enqueue metering data: {e}");
+        }
+    }
+}
+
+impl Drop for EvaluationBatch<'_> {
+    fn drop(&mut self) {
+        let events = self.events.take();
+        if !events.is_empty() {
+            self.send(events);
+        }
+    }
 }
 
 pub(crate) trait MeteringSubject {
     fn get_metering_sender(&self) -> Option<&MeteringRecorderSender>;
 
-    fn record_evaluation(&self, entity: &impl Entity, segment: Option<&Segment>);
+    /// Records an evaluation for `entity` that served a value from
+    /// `value_source`, in `batch` if it is part of one.
+    fn record_evaluation(
+        &self,
+        entity: &(impl Entity + ?Sized),
+        segment: Option<&Segment>,
+        value_source: ValueSource,
+        batch: Option<&EvaluationBatch>,
+    );
 }
 
 impl MeteringSubject for PropertySnapshot {
@@ -101,20 +445,33 @@ impl MeteringSubject for PropertySnapshot {
         self.metering.as_ref()
     }
 
-    fn record_evaluation(&self, entity: &impl Entity, segment: Option<&Segment>) {
-        if let Some(recorder) = self.get_metering_sender() {
-            if let Err(e) = recorder
-                .evaluation_event_sender
-                .send(EvaluationEvent::Property(EvaluationEventData {
-                    subject_id: SubjectId::Property(self.property_id.clone()),
-                    entity_id: entity.get_id(),
-                    segment_id: segment.map(|s| s.segment_id.clone()),
-                }))
-            {
-                warn!(
-                    "Fail to enqueue metering data for property '{}': {e}",
-                    self.name
-                );
+    fn record_evaluation(
+        &self,
+        entity: &(impl Entity + ?Sized),
+        segment: Option<&Segment>,
+        value_source: ValueSource,
+        batch: Option<&EvaluationBatch>,
+    ) {
+        let Some(recorder) = self.get_metering_sender() else {
+            return;
+        };
+        recorder.stats.evaluations.fetch_add(1, Ordering::Relaxed);
+        let event = EvaluationEvent::Property(EvaluationEventData {
+            subject_id: SubjectId::Property(self.property_id.clone()),
+            entity_id: entity.get_id(),
+            segment_id: segment.map(|s| s.segment_id.clone()),
+            evaluation_time: chrono::Utc::now(),
+            value_source,
+        });
+        match batch {
+            Some(batch) => batch.push(event),
+            None => {
+                if let Err(e) = recorder.send(event) {
+                    warn!(
+                        "Fail to enqueue metering data for property '{}': {e}",
+                        self.name
+                    );
+                }
             }
         }
     }
@@ -125,71 +482,151 @@ impl MeteringSubject for FeatureSnapshot {
         self.metering.as_ref()
     }
 
-    fn record_evaluation(&self, entity: &impl Entity, segment: Option<&Segment>) {
-        if let Some(recorder) = self.get_metering_sender() {
-            if let Err(e) = recorder
-                .evaluation_event_sender
-                .send(EvaluationEvent::Feature(EvaluationEventData {
-                    subject_id: SubjectId::Feature(self.feature_id.clone()),
-                    entity_id: entity.get_id(),
-                    segment_id: segment.map(|s| s.segment_id.clone()),
-                }))
-            {
-                warn!(
-                    "Fail to enqueue metering data for feature '{}': {e}",
-                    self.name
-                );
+    fn record_evaluation(
+        &self,
+        entity: &(impl Entity + ?Sized),
+        segment: Option<&Segment>,
+        value_source: ValueSource,
+        batch: Option<&EvaluationBatch>,
+    ) {
+        let Some(recorder) = self.get_metering_sender() else {
+            return;
+        };
+        recorder.stats.evaluations.fetch_add(1, Ordering::Relaxed);
+        recorder
+            .stats
+            .feature_usage
+            .record_evaluation(&self.feature_id);
+        if !recorder.records_feature(value_source) {
+            return;
+        }
+        let event = EvaluationEvent::Feature(EvaluationEventData {
+            subject_id: SubjectId::Feature(self.feature_id.to_string()),
+            entity_id: entity.get_id(),
+            segment_id: segment.map(|s| s.segment_id.clone()),
+            evaluation_time: chrono::Utc::now(),
+            value_source,
+        });
+        match batch {
+            Some(batch) => batch.push(event),
+            None => {
+                if let Err(e) = recorder.send(event) {
+                    warn!(
+                        "Fail to enqueue metering data for feature '{}': {e}",
+                        self.name
+                    );
+                }
             }
         }
     }
 }
 
 /// The responsibility of the MeteringBatcher is to aggregate evaluation events and batch them for transmission to the server.
+#[cfg(feature = "remote")]
 struct MeteringBatcher<T: MeteringClient> {
     evaluations: std::collections::HashMap<MeteringKey, EvaluationData>,
     client: T,
     config_id: ConfigurationId,
+    metadata: MeteringMetadata,
+    entity_ids: EntityIdEncoding,
+    /// See [`MeteringOptions::send_evaluation_details`].
+    evaluation_details: bool,
+    error_emitter: RuntimeEventEmitter,
+    stats: Arc<MeteringStats>,
     retry_attempt: u32,
     next_retry_at: Option<std::time::Instant>,
+    /// How far the local clock is ahead of the server, sent with the next
+    /// transmission so the server can correct the evaluation times, if
+    /// `evaluation_details` is set.
+    clock_offset: Option<chrono::TimeDelta>,
 }
 
+#[cfg(feature = "remote")]
 impl<T: MeteringClient> MeteringBatcher<T> {
-    fn new(client: T, config_id: ConfigurationId) -> Self {
+    fn new(client: T, config_id: ConfigurationId, metadata: MeteringMetadata) -> Self {
         Self {
             evaluations: std::collections::HashMap::new(),
             client,
             config_id,
+            metadata,
+            entity_ids: EntityIdEncoding::Send,
+            evaluation_details: false,
+            error_emitter: RuntimeEventEmitter::new(),
+            stats: Arc::default(),
             retry_attempt: 0,
             next_retry_at: None,
+            clock_offset: None,
         }
     }
 
+    #[cfg(feature = "remote")]
+    fn with_entity_ids(mut self, entity_ids: MeteringEntityIds) -> Self {
+        self.entity_ids = entity_ids.into();
+        self
+    }
+
+    fn with_evaluation_details(mut self, evaluation_details: bool) -> Self {
+        self.evaluation_details = evaluation_details;
+        self
+    }
+
+    fn with_error_emitter(mut self, error_emitter: RuntimeEventEmitter) -> Self {
+        self.error_emitter = error_emitter;
+        self
+    }
+
+    fn with_stats(mut self, stats: Arc<MeteringStats>) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    /// Forgets the aggregated evaluations, which are no longer pending.
+    fn clear_evaluations(&mut self) {
+        let count: u64 = self
+            .evaluations
+            .values()
+            .map(|data| u64::from(data.number_of_evaluations))
+            .sum();
+        let _ = self
+            .stats
+            .pending
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |pending| {
+                Some(pending.saturating_sub(count))
+            });
+        self.evaluations.clear();
+    }
+
     fn handle_event(&mut self, event: EvaluationEvent) {
-        let key = match event {
-            EvaluationEvent::Feature(data) => match data.subject_id {
-                SubjectId::Feature(ref id) => {
-                    MeteringKey::from_feature(id.clone(), data.entity_id, data.segment_id)
-                }
-                _ => unreachable!(
-                    "If it's a EvaluationEvent::Feature inside it contains a SubjectId::Feature"
-                ),
-            },
-            EvaluationEvent::Property(data) => match data.subject_id {
-                SubjectId::Property(ref id) => {
-                    MeteringKey::from_property(id.clone(), data.entity_id, data.segment_id)
+        let data = match event {
+            EvaluationEvent::Feature(data) | EvaluationEvent::Property(data) => data,
+            EvaluationEvent::Batch(events) => {
+                for event in events {
+                    self.handle_event(event);
                 }
-                _ => unreachable!(
-                    "If it's a EvaluationEvent::Property inside it contains a SubjectId::Property"
-                ),
-            },
+                return;
+            }
+        };
+        let evaluation_time = data.evaluation_time;
+        let entity_id = self.entity_ids.apply(data.entity_id);
+        let mut key = match data.subject_id {
+            SubjectId::Feature(id) => {
+                MeteringKey::from_feature(id, entity_id, data.segment_id, data.value_source)
+            }
+            SubjectId::Property(id) => {
+                MeteringKey::from_property(id, entity_id, data.segment_id, data.value_source)
+            }
         };
+        if !self.evaluation_details {
+            key.enabled = None;
+            key.value_source = None;
+        }
 
         self.evaluations
             .entry(key)
             .and_modify(|v| {
-                v.add_one();
+                v.add_one(evaluation_time);
             })
-            .or_default();
+            .or_insert_with(|| EvaluationData::new(evaluation_time));
     }
 
     fn flush(&mut self) {
@@ -197,17 +634,21 @@ impl<T: MeteringClient> MeteringBatcher<T> {
             return;
         }
 
-        if let Some(next_retry_at) = self.next_retry_at {
-            if std::time::Instant::now() < next_retry_at {
-                return;
-            }
+        if let Some(next_retry_at) = self.next_retry_at
+            && std::time::Instant::now() < next_retry_at
+        {
+            return;
         }
 
         let mut json_data = MeteringDataJson::new(
             self.config_id.collection_id.clone(),
             self.config_id.environment_id.clone(),
+            &self.metadata,
         );
 
+        if self.evaluation_details {
+            json_data.clock_offset_ms = self.clock_offset.map(|offset| offset.num_milliseconds());
+        }
         for evaluation in self.evaluations.iter() {
             json_data.add_usage(evaluation.0, evaluation.1);
         }
@@ -221,13 +662,19 @@ impl<T: MeteringClient> MeteringBatcher<T> {
             .push_metering_data(&self.config_id.guid, &json_data);
 
         match result {
-            Ok(()) => {
-                self.evaluations.clear();
+            Ok(clock_offset) => {
+                if let Some(clock_offset) = clock_offset {
+                    self.update_clock_offset(clock_offset);
+                }
+                self.clear_evaluations();
                 self.retry_attempt = 0;
                 self.next_retry_at = None;
             }
             Err(err) => {
                 warn!("Sending metering data failed: {}", err);
+                self.stats.flush_failures.fetch_add(1, Ordering::Relaxed);
+                self.error_emitter
+                    .emit_error(SdkError::MeteringPushFailed(err.to_string()));
                 if Self::is_retryable_error(&err) {
                     let delay = Self::calculate_retry_delay(self.retry_attempt);
                     self.retry_attempt = self.retry_attempt.saturating_add(1);
@@ -241,7 +688,7 @@ impl<T: MeteringClient> MeteringBatcher<T> {
                     // Keep self.evaluations intact so they can be retried on the next flush.
                 } else {
                     // Non-retryable error: drop the data to avoid an indefinite accumulation.
-                    self.evaluations.clear();
+                    self.clear_evaluations();
                     self.retry_attempt = 0;
                     self.next_retry_at = None;
                 }
@@ -251,6 +698,19 @@ impl<T: MeteringClient> MeteringBatcher<T> {
         // intentionally preserves the buffer.
     }
 
+    /// Records the offset measured with the last transmission, warning once
+    /// when it becomes large.
+    fn update_clock_offset(&mut self, clock_offset: chrono::TimeDelta) {
+        let skewed = |offset: chrono::TimeDelta| offset.abs() > CLOCK_SKEW_WARNING;
+        if skewed(clock_offset) && !self.clock_offset.is_some_and(skewed) {
+            warn!(
+                "The local clock is {} seconds off the server clock, evaluation times sent for metering are skewed.",
+                clock_offset.num_seconds()
+            );
+        }
+        self.clock_offset = Some(clock_offset);
+    }
+
     fn calculate_retry_delay(attempt: u32) -> std::time::Duration {
         let multiplier = RETRY_MULTIPLIER.saturating_pow(attempt);
         let delay = RETRY_INITIAL_DELAY.saturating_mul(multiplier);
@@ -272,7 +732,11 @@ impl<T: MeteringClient> MeteringBatcher<T> {
 pub(crate) mod tests {
     use super::*;
 
+    use crate::Value;
+    use crate::feature::Feature;
     use crate::metering::MeteringResult;
+    use crate::network::serialization::ValueType;
+    use crate::segment_evaluation::TargetingRules;
 
     struct MeteringClientMock {
         metering_data_sender: mpsc::Sender<MeteringDataJson>,
@@ -291,20 +755,66 @@ pub(crate) mod tests {
     }
 
     impl MeteringClient for MeteringClientMock {
-        fn push_metering_data(&self, _guid: &str, data: &MeteringDataJson) -> MeteringResult<()> {
+        fn push_metering_data(
+            &self,
+            _guid: &str,
+            data: &MeteringDataJson,
+        ) -> MeteringResult<Option<chrono::TimeDelta>> {
             self.metering_data_sender.send(data.clone()).unwrap();
-            Ok(())
+            Ok(None)
+        }
+    }
+
+    fn configuration_id() -> ConfigurationId {
+        ConfigurationId::new(
+            "test-guid".to_string(),
+            "test_env_id".to_string(),
+            "test_collection_id".to_string(),
+        )
+        .unwrap()
+    }
+
+    fn batcher<T: MeteringClient>(client: T) -> MeteringBatcher<T> {
+        MeteringBatcher::new(
+            client,
+            configuration_id(),
+            MeteringMetadata::new(None, false),
+        )
+    }
+
+    /// A sender queueing all the evaluations, disabled ones included.
+    fn sender(
+        evaluation_event_sender: mpsc::SyncSender<EvaluationEvent>,
+    ) -> MeteringRecorderSender {
+        MeteringRecorderSender {
+            evaluation_event_sender,
+            record_disabled_evaluations: true,
+            stopped: Arc::default(),
+            stats: Arc::default(),
+            waker: None,
         }
     }
 
     pub(crate) fn start_metering_mock(
         configuration_id: ConfigurationId,
+    ) -> (MeteringRecorder, mpsc::Receiver<MeteringDataJson>) {
+        start_metering_mock_with_options(configuration_id, MeteringOptions::default())
+    }
+
+    /// Like [`start_metering_mock`], with the given options.
+    pub(crate) fn start_metering_mock_with_options(
+        configuration_id: ConfigurationId,
+        options: MeteringOptions,
     ) -> (MeteringRecorder, mpsc::Receiver<MeteringDataJson>) {
         let (client, receiver) = MeteringClientMock::new();
         let recorder = start_metering(
             configuration_id,
             std::time::Duration::from_millis(200), // Use 200ms for test flushing
             client,
+            MeteringMetadata::new(Some("test-label".to_string()), false),
+            options,
+            &ThreadOptions::default(),
+            RuntimeEventEmitter::new(),
         );
         (recorder, receiver)
     }
@@ -312,25 +822,22 @@ pub(crate) mod tests {
     /// Tests the propagation of evaluation events through the batcher to the server client and the timings of the flush.
     #[test]
     fn test_record_evaluation_leads_to_metering_data_sent() {
-        let configuration_id = ConfigurationId::new(
-            "test_guid".to_string(),
-            "test_env_id".to_string(),
-            "test_collection_id".to_string(),
-        );
-        let (metering_handle, metering_data_sent_receiver) = start_metering_mock(configuration_id);
+        let (metering_handle, metering_data_sent_receiver) =
+            start_metering_mock(configuration_id());
 
         // Send a single evaluation event
+        let time_record_evaluation = chrono::Utc::now();
         metering_handle
             .sender
-            .evaluation_event_sender
             .send(EvaluationEvent::Feature(EvaluationEventData {
                 subject_id: SubjectId::Feature("feature1".to_string()),
                 entity_id: "entity1".to_string(),
                 segment_id: None,
+                evaluation_time: chrono::Utc::now(),
+                value_source: ValueSource::EnabledValue,
             }))
             .unwrap();
 
-        let time_record_evaluation = chrono::Utc::now();
         let metering_data = metering_data_sent_receiver.recv().unwrap();
         assert!(chrono::Utc::now() - time_record_evaluation >= chrono::Duration::milliseconds(200));
 
@@ -339,10 +846,14 @@ pub(crate) mod tests {
             "test_collection_id".to_string()
         );
         assert_eq!(metering_data.environment_id, "test_env_id".to_string());
+        assert_eq!(metering_data.sdk_name, "appconfiguration-rust-sdk");
+        assert_eq!(metering_data.sdk_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(metering_data.instance_label.as_deref(), Some("test-label"));
+        assert_eq!(metering_data.host, None);
         let usage = &metering_data.usages[0];
         assert_eq!(usage.feature_id, Some("feature1".to_string()));
         assert_eq!(usage.property_id, None);
-        assert_eq!(usage.entity_id, "entity1".to_string());
+        assert_eq!(usage.entity_id, Some("entity1".to_string()));
         assert_eq!(usage.segment_id, None);
         assert_eq!(usage.count, 1);
         // Evaluation time should be close to when we called record_evaluation.
@@ -357,32 +868,31 @@ pub(crate) mod tests {
     #[test]
     fn test_metrics_multiple_same_evaluation_events_are_batched_to_one_entry() {
         let (client, metering_data_sent_receiver) = MeteringClientMock::new();
-        let mut batcher = MeteringBatcher::new(
-            client,
-            ConfigurationId::new(
-                "test_guid".to_string(),
-                "test_env_id".to_string(),
-                "test_collection_id".to_string(),
-            ),
-        );
+        let mut batcher = batcher(client).with_evaluation_details(true);
 
         // Simulate two events for the same feature/entity
         batcher.handle_event(EvaluationEvent::Feature(EvaluationEventData {
             subject_id: SubjectId::Feature("feature1".to_string()),
             entity_id: "entity1".to_string(),
             segment_id: None,
+            evaluation_time: chrono::Utc::now(),
+            value_source: ValueSource::EnabledValue,
         }));
         let time_second_record = chrono::Utc::now();
         batcher.handle_event(EvaluationEvent::Feature(EvaluationEventData {
             subject_id: SubjectId::Feature("feature1".to_string()),
             entity_id: "entity1".to_string(),
             segment_id: None,
+            evaluation_time: chrono::Utc::now(),
+            value_source: ValueSource::EnabledValue,
         }));
         let time_third_record = chrono::Utc::now();
         batcher.handle_event(EvaluationEvent::Property(EvaluationEventData {
             subject_id: SubjectId::Property("property1".to_string()),
             entity_id: "entity1".to_string(),
             segment_id: Some("some_segment".to_string()),
+            evaluation_time: chrono::Utc::now(),
+            value_source: ValueSource::SegmentValue,
         }));
 
         // Force flush
@@ -397,10 +907,12 @@ pub(crate) mod tests {
             .find(|u| u.feature_id == Some("feature1".to_string()))
             .unwrap();
         assert_eq!(feature_usage.property_id, None);
-        assert_eq!(feature_usage.entity_id, "entity1".to_string());
+        assert_eq!(feature_usage.entity_id, Some("entity1".to_string()));
         assert_eq!(feature_usage.segment_id, None);
         assert!(feature_usage.evaluation_time >= time_second_record);
         assert_eq!(feature_usage.count, 2);
+        assert_eq!(feature_usage.enabled, Some(true));
+        assert_eq!(feature_usage.value_source, Some(ValueSource::EnabledValue));
 
         // The property evaluation should be a separate entry:
         let property_usage = metering_data
@@ -409,9 +921,471 @@ pub(crate) mod tests {
             .find(|u| u.property_id == Some("property1".to_string()))
             .unwrap();
         assert_eq!(property_usage.feature_id, None);
-        assert_eq!(property_usage.entity_id, "entity1".to_string());
+        assert_eq!(property_usage.entity_id, Some("entity1".to_string()));
         assert_eq!(property_usage.segment_id, Some("some_segment".to_string()));
         assert!(property_usage.evaluation_time >= time_third_record);
         assert_eq!(property_usage.count, 1);
+        let json = serde_json::to_value(property_usage).unwrap();
+        assert_eq!(json["value_source"], "SEGMENT_VALUE");
+        assert!(json.get("enabled").is_none());
+    }
+
+    /// The details of the evaluations are only sent with
+    /// `MeteringOptions::send_evaluation_details`.
+    #[test]
+    fn test_evaluation_details_are_not_sent_by_default() {
+        let (client, metering_data_sent_receiver) = MeteringClientMock::new();
+        let mut batcher = batcher(client);
+        for value_source in [ValueSource::EnabledValue, ValueSource::DisabledValue] {
+            batcher.handle_event(EvaluationEvent::Feature(EvaluationEventData {
+                subject_id: SubjectId::Feature("feature1".to_string()),
+                entity_id: "entity1".to_string(),
+                segment_id: None,
+                evaluation_time: chrono::Utc::now(),
+                value_source,
+            }));
+        }
+        batcher.flush();
+
+        // Evaluations differing only in the details not sent are aggregated
+        let metering_data = metering_data_sent_receiver.recv().unwrap();
+        assert_eq!(metering_data.usages.len(), 1);
+        let usage = &metering_data.usages[0];
+        assert_eq!(usage.count, 2);
+        assert_eq!(usage.enabled, None);
+        assert_eq!(usage.value_source, None);
+        let json = serde_json::to_value(usage).unwrap();
+        assert!(json.get("enabled").is_none());
+        assert!(json.get("value_source").is_none());
+    }
+
+    /// Entity IDs are hashed or dropped before evaluations are aggregated.
+    #[test]
+    fn test_metering_entity_ids() {
+        let send_evaluations = |entity_ids| {
+            let (client, metering_data_sent_receiver) = MeteringClientMock::new();
+            let mut batcher = batcher(client).with_entity_ids(entity_ids);
+            for entity_id in ["entity1", "entity2", "entity1"] {
+                batcher.handle_event(EvaluationEvent::Feature(EvaluationEventData {
+                    subject_id: SubjectId::Feature("feature1".to_string()),
+                    entity_id: entity_id.to_string(),
+                    segment_id: Some("some_segment".to_string()),
+                    evaluation_time: chrono::Utc::now(),
+                    value_source: ValueSource::EnabledValue,
+                }));
+            }
+            batcher.flush();
+            let mut usages = metering_data_sent_receiver.recv().unwrap().usages;
+            usages.sort_by_key(|usage| std::cmp::Reverse(usage.count));
+            usages
+                .into_iter()
+                .map(|usage| (usage.entity_id, usage.count))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            send_evaluations(MeteringEntityIds::HashWithKey("secret".to_string())),
+            [
+                (
+                    Some(
+                        "1831a30c237ba6d58876610fe0cd7d57ca36d10f00409f23c04c0d636216f1bf"
+                            .to_string()
+                    ),
+                    2
+                ),
+                (
+                    Some(
+                        "c057220915d27cf04ec2521a72132ca0376b664ef04ab969e10d2ba1f04a624c"
+                            .to_string()
+                    ),
+                    1
+                )
+            ]
+        );
+
+        // Each client hashes with its own random key
+        let hashes = send_evaluations(MeteringEntityIds::Hash);
+        assert_eq!(
+            hashes.iter().map(|(_, count)| *count).collect::<Vec<_>>(),
+            [2, 1]
+        );
+        assert_ne!(hashes, send_evaluations(MeteringEntityIds::Hash));
+        // Not the plain SHA-256 of "entity1"
+        assert_ne!(
+            hashes[0].0.as_deref(),
+            Some("e6e0c4d9830348ec0074eb09d106869103350dde243a25a0fbf1413e861de5d4")
+        );
+
+        assert_eq!(send_evaluations(MeteringEntityIds::Drop), [(None, 3)]);
+
+        // The key is not logged
+        assert_eq!(
+            format!("{:?}", MeteringEntityIds::HashWithKey("secret".to_string())),
+            "HashWithKey(..)"
+        );
+    }
+
+    /// Failed transmissions are sent to the error listeners.
+    #[test]
+    fn test_failed_push_is_reported() {
+        struct FailingMeteringClient;
+        impl MeteringClient for FailingMeteringClient {
+            fn push_metering_data(
+                &self,
+                _guid: &str,
+                _data: &MeteringDataJson,
+            ) -> MeteringResult<Option<chrono::TimeDelta>> {
+                Err(MeteringError::DataNotAccepted(
+                    "400 Bad Request".to_string(),
+                ))
+            }
+        }
+
+        let errors = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let error_emitter = RuntimeEventEmitter::new();
+        {
+            let errors = errors.clone();
+            error_emitter
+                .on_error(std::sync::Arc::new(move |error| {
+                    errors.lock().unwrap().push(error)
+                }))
+                .unwrap();
+        }
+        let mut batcher = batcher(FailingMeteringClient).with_error_emitter(error_emitter);
+
+        batcher.handle_event(EvaluationEvent::Feature(EvaluationEventData {
+            subject_id: SubjectId::Feature("feature1".to_string()),
+            entity_id: "entity1".to_string(),
+            segment_id: None,
+            evaluation_time: chrono::Utc::now(),
+            value_source: ValueSource::EnabledValue,
+        }));
+        batcher.flush();
+
+        assert_eq!(
+            *errors.lock().unwrap(),
+            [SdkError::MeteringPushFailed(
+                "Metering data was rejected with status 400 Bad Request".to_string()
+            )]
+        );
+        assert_eq!(batcher.stats.flush_failures.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_stats() {
+        let (client, _data_receiver) = MeteringClientMock::new();
+        let stats = Arc::new(MeteringStats::default());
+        let mut batcher = batcher(client).with_stats(stats.clone());
+        let (evaluation_event_sender, receiver) = mpsc::sync_channel(QUEUE_CAPACITY);
+        let sender = MeteringRecorderSender {
+            stats: stats.clone(),
+            ..sender(evaluation_event_sender)
+        };
+
+        for _ in 0..3 {
+            sender
+                .send(EvaluationEvent::Feature(EvaluationEventData {
+                    subject_id: SubjectId::Feature("feature1".to_string()),
+                    entity_id: "entity1".to_string(),
+                    segment_id: None,
+                    evaluation_time: chrono::Utc::now(),
+                    value_source: ValueSource::EnabledValue,
+                }))
+                .unwrap();
+        }
+        for event in receiver.try_iter() {
+            batcher.handle_event(event);
+        }
+        assert_eq!(stats.pending.load(Ordering::Relaxed), 3);
+
+        batcher.flush();
+        assert_eq!(stats.pending.load(Ordering::Relaxed), 0);
+        assert_eq!(stats.flush_failures.load(Ordering::Relaxed), 0);
+    }
+
+    /// The clock offset measured with a transmission is sent with the next ones,
+    /// if the details of the evaluations are sent.
+    #[rstest::rstest]
+    #[case(true, Some(300_000))]
+    #[case(false, None)]
+    fn test_clock_offset(
+        #[case] evaluation_details: bool,
+        #[case] expected_offset_ms: Option<i64>,
+    ) {
+        struct SkewedMeteringClient(mpsc::Sender<MeteringDataJson>);
+        impl MeteringClient for SkewedMeteringClient {
+            fn push_metering_data(
+                &self,
+                _guid: &str,
+                data: &MeteringDataJson,
+            ) -> MeteringResult<Option<chrono::TimeDelta>> {
+                self.0.send(data.clone()).unwrap();
+                Ok(Some(chrono::TimeDelta::minutes(5)))
+            }
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        let mut batcher =
+            batcher(SkewedMeteringClient(sender)).with_evaluation_details(evaluation_details);
+        let mut flush = || {
+            batcher.handle_event(EvaluationEvent::Feature(EvaluationEventData {
+                subject_id: SubjectId::Feature("feature1".to_string()),
+                entity_id: "entity1".to_string(),
+                segment_id: None,
+                evaluation_time: chrono::Utc::now(),
+                value_source: ValueSource::EnabledValue,
+            }));
+            batcher.flush();
+            receiver.recv().unwrap()
+        };
+
+        let data = flush();
+        assert_eq!(data.clock_offset_ms, None);
+        assert!(
+            serde_json::to_value(&data)
+                .unwrap()
+                .get("clock_offset_ms")
+                .is_none()
+        );
+
+        let data = flush();
+        assert_eq!(data.clock_offset_ms, expected_offset_ms);
+        assert_eq!(
+            serde_json::to_value(&data).unwrap().get("clock_offset_ms"),
+            expected_offset_ms.map(serde_json::Value::from).as_ref()
+        );
+    }
+
+    fn feature_event() -> EvaluationEvent {
+        EvaluationEvent::Feature(EvaluationEventData {
+            subject_id: SubjectId::Feature("feature1".to_string()),
+            entity_id: "entity1".to_string(),
+            segment_id: None,
+            evaluation_time: chrono::Utc::now(),
+            value_source: ValueSource::EnabledValue,
+        })
+    }
+
+    #[test]
+    fn test_send_never_blocks() {
+        let (evaluation_event_sender, receiver) = mpsc::sync_channel(1);
+        let sender = sender(evaluation_event_sender);
+
+        sender.send(feature_event()).unwrap();
+        sender.send(feature_event()).unwrap();
+        assert_eq!(receiver.try_iter().count(), 1);
+        assert_eq!(sender.stats.pending.load(Ordering::Relaxed), 1);
+        assert_eq!(sender.stats.dropped.load(Ordering::Relaxed), 1);
+
+        sender.stop();
+        sender.send(feature_event()).unwrap();
+        assert_eq!(receiver.try_iter().count(), 0);
+        assert_eq!(sender.stats.dropped.load(Ordering::Relaxed), 1);
+    }
+
+    // Only the evaluated feature is metered, not its prerequisites.
+    #[test]
+    fn test_prerequisites_are_not_metered() {
+        let (evaluation_event_sender, receiver) = mpsc::sync_channel(QUEUE_CAPACITY);
+        let sender = sender(evaluation_event_sender);
+        let feature = |feature_id: &str| {
+            FeatureSnapshot::new(
+                true,
+                Value::Boolean(true),
+                Value::Boolean(false),
+                100,
+                None,
+                None,
+                feature_id,
+                feature_id,
+                "BOOLEAN".to_string(),
+                None,
+                TargetingRules::new(HashMap::new(), Vec::new(), ValueType::Boolean, None),
+                Some(sender.clone()),
+            )
+        };
+        let child = feature("child").with_prerequisites(vec![
+            feature("parent").with_prerequisites(vec![feature("grandparent")]),
+        ]);
+
+        child
+            .get_current_value(&crate::tests::GenericEntity {
+                id: "entity1".into(),
+                attributes: HashMap::new(),
+            })
+            .unwrap();
+        let events: Vec<_> = receiver.try_iter().collect();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            &events[0],
+            EvaluationEvent::Feature(EvaluationEventData { subject_id: SubjectId::Feature(id), .. })
+                if id == "child"
+        ));
+    }
+
+    // Evaluations of many entities at once are queued in batches, so they do
+    // not fill the queue of the metering thread.
+    #[test]
+    fn test_batch_evaluations_are_queued_together() {
+        let (evaluation_event_sender, receiver) = mpsc::sync_channel(QUEUE_CAPACITY);
+        let sender = sender(evaluation_event_sender);
+        let feature = FeatureSnapshot::new(
+            true,
+            Value::Boolean(true),
+            Value::Boolean(false),
+            100,
+            None,
+            None,
+            "f1",
+            "f1",
+            "BOOLEAN".to_string(),
+            None,
+            TargetingRules::new(HashMap::new(), Vec::new(), ValueType::Boolean, None),
+            Some(sender.clone()),
+        );
+        let entities: Vec<_> = (0..MAX_BATCH_LEN + 1)
+            .map(|i| crate::tests::GenericEntity {
+                id: format!("entity{i}"),
+                attributes: HashMap::new(),
+            })
+            .collect();
+        let entities: Vec<&dyn Entity> = entities.iter().map(|e| e as &dyn Entity).collect();
+
+        assert!(feature.get_values_for(&entities).iter().all(Result::is_ok));
+        let events: Vec<_> = receiver.try_iter().collect();
+        let lengths: Vec<_> = events
+            .iter()
+            .map(|event| match event {
+                EvaluationEvent::Batch(events) => events.len(),
+                _ => panic!("Evaluations not batched"),
+            })
+            .collect();
+        assert_eq!(lengths, [MAX_BATCH_LEN, 1]);
+        assert_eq!(
+            sender.stats.pending.load(Ordering::Relaxed),
+            MAX_BATCH_LEN as u64 + 1
+        );
+
+        let mut batcher = batcher(crate::metering::NoopMeteringClient);
+        for event in events {
+            batcher.handle_event(event);
+        }
+        assert_eq!(batcher.evaluations.len(), MAX_BATCH_LEN + 1);
+    }
+
+    #[test]
+    fn test_panicking_client() {
+        struct PanickingClient(mpsc::Sender<()>);
+
+        impl MeteringClient for PanickingClient {
+            fn push_metering_data(
+                &self,
+                _guid: &str,
+                _data: &MeteringDataJson,
+            ) -> MeteringResult<Option<chrono::TimeDelta>> {
+                let _ = self.0.send(());
+                panic!("metering bug")
+            }
+        }
+
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let error_emitter = RuntimeEventEmitter::new();
+        {
+            let errors = errors.clone();
+            error_emitter
+                .on_error(Arc::new(move |error| errors.lock().unwrap().push(error)))
+                .unwrap();
+        }
+        let (pushes_sender, pushes) = mpsc::channel();
+        let recorder = start_metering(
+            configuration_id(),
+            std::time::Duration::from_millis(50),
+            PanickingClient(pushes_sender),
+            MeteringMetadata::new(None, false),
+            MeteringOptions::default(),
+            &ThreadOptions::default(),
+            error_emitter,
+        );
+
+        // The thread goes on after a panic, and keeps recording
+        for _ in 0..2 {
+            recorder.sender.send(feature_event()).unwrap();
+            pushes
+                .recv_timeout(std::time::Duration::from_secs(5))
+                .unwrap();
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert_eq!(recorder.sender.stats.pending.load(Ordering::Relaxed), 0);
+        assert_eq!(
+            errors.lock().unwrap()[0],
+            SdkError::ThreadPanicked("Thread panicked: metering bug".to_string())
+        );
+    }
+
+    #[test]
+    fn test_clients_share_the_metering_thread() {
+        struct ThreadReportingClient(mpsc::Sender<std::thread::ThreadId>);
+        impl MeteringClient for ThreadReportingClient {
+            fn push_metering_data(
+                &self,
+                _guid: &str,
+                _data: &MeteringDataJson,
+            ) -> MeteringResult<Option<chrono::TimeDelta>> {
+                self.0.send(std::thread::current().id()).unwrap();
+                Ok(None)
+            }
+        }
+
+        let (pushes_sender, pushes) = mpsc::channel();
+        let thread_options = ThreadOptions::default().with_name("test-meter");
+        let recorders: Vec<_> = (0..2)
+            .map(|_| {
+                start_metering(
+                    configuration_id(),
+                    std::time::Duration::from_millis(50),
+                    ThreadReportingClient(pushes_sender.clone()),
+                    MeteringMetadata::new(None, false),
+                    MeteringOptions::default(),
+                    &thread_options,
+                    RuntimeEventEmitter::new(),
+                )
+            })
+            .collect();
+        for recorder in &recorders {
+            recorder.sender.send(feature_event()).unwrap();
+        }
+
+        let timeout = std::time::Duration::from_secs(5);
+        let first = pushes.recv_timeout(timeout).unwrap();
+        let second = pushes.recv_timeout(timeout).unwrap();
+        assert_eq!(first, second);
+        assert_ne!(first, std::thread::current().id());
+    }
+
+    #[test]
+    fn test_feature_usage() {
+        let usage = FeatureUsage::default();
+        let features = || vec!["f1".to_string(), "f2".to_string()];
+        usage.record_evaluation("f1");
+        for i in 0..MAX_MISSING_FEATURES + 10 {
+            usage.record_missing(&format!("missing{i}"));
+        }
+        let unused = usage.unused_features(features(), None);
+        assert_eq!(unused.never_evaluated, ["f2"]);
+        assert_eq!(unused.missing.len(), MAX_MISSING_FEATURES);
+
+        // Evaluations are timed by the clock of the metering thread
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        usage.tick();
+        let window = Some(std::time::Duration::from_millis(50));
+        assert_eq!(
+            usage.unused_features(features(), window).never_evaluated,
+            ["f1", "f2"]
+        );
+        usage.record_evaluation("f2");
+        usage.record_missing("missing0");
+        let unused = usage.unused_features(features(), window);
+        assert_eq!(unused.never_evaluated, ["f1"]);
+        assert_eq!(unused.missing, ["missing0"]);
     }
 }
@@ -12,19 +12,34 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "remote")]
 mod client;
+#[cfg(feature = "remote")]
 mod client_http;
+#[cfg(feature = "remote")]
 mod errors;
 mod models;
+#[cfg(feature = "remote")]
 mod serialization;
 
+#[allow(clippy::module_inception)]
 pub(crate) mod metering;
 
+#[cfg(feature = "remote")]
 pub(crate) use client::MeteringClient;
+#[cfg(feature = "remote")]
+pub(crate) use client::NoopMeteringClient;
+#[cfg(feature = "remote")]
 pub(crate) use client_http::MeteringClientHttp;
+#[cfg(feature = "remote")]
 pub(crate) use errors::MeteringError;
-pub(crate) use metering::{
-    MeteringRecorder, MeteringRecorderSender, MeteringSubject, start_metering,
-};
+pub(crate) use metering::{EvaluationBatch, MeteringRecorderSender, MeteringSubject};
+#[cfg(feature = "remote")]
+pub(crate) use metering::{MeteringRecorder, start_metering};
+#[cfg(feature = "remote")]
+pub(crate) use models::MeteringMetadata;
+pub(crate) use models::ValueSource;
+pub use models::{MeteringEntityIds, MeteringOptions};
 
+#[cfg(feature = "remote")]
 pub type MeteringResult<T> = std::result::Result<T, errors::MeteringError>;
@@ -12,11 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "remote")]
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+#[cfg(feature = "remote")]
+use sha2::Sha256;
+
+#[cfg_attr(not(feature = "remote"), allow(dead_code))]
 pub(crate) enum SubjectId {
     Feature(String),
     Property(String),
 }
 
+#[cfg_attr(not(feature = "remote"), allow(dead_code))]
 pub(crate) struct EvaluationEventData {
     /// ID if the subject being evaluated. E.g. feature ID.
     pub subject_id: SubjectId,
@@ -24,62 +32,268 @@ pub(crate) struct EvaluationEventData {
     pub entity_id: String,
     /// If applicable, the segment the subject was associated to during evaluation.
     pub segment_id: Option<String>,
+    /// When the evaluation happened, events are batched some time later.
+    pub evaluation_time: chrono::DateTime<chrono::Utc>,
+    /// Where the value served for the evaluation comes from.
+    pub value_source: ValueSource,
+}
+
+/// Where the value served by an evaluation comes from, sent with the usage
+/// metering data.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub(crate) enum ValueSource {
+    /// The enabled value of a feature, no targeting rule matched.
+    EnabledValue,
+    /// The disabled value of a disabled feature, or of a feature whose
+    /// prerequisites are not enabled.
+    DisabledValue,
+    /// The value of the targeting rule matching the entity.
+    SegmentValue,
+    /// The disabled value of a feature, because the rollout percentage
+    /// excluded the entity.
+    RolloutExcluded,
+    /// The default value of a property, no targeting rule matched.
+    DefaultValue,
 }
 
+impl ValueSource {
+    /// Whether a feature evaluation from this source served the enabled value.
+    pub(crate) fn is_enabled(self) -> bool {
+        matches!(self, Self::EnabledValue | Self::SegmentValue)
+    }
+}
+
+#[cfg_attr(not(feature = "remote"), allow(dead_code))]
 pub(crate) enum EvaluationEvent {
     Feature(EvaluationEventData),
     Property(EvaluationEventData),
+    /// Evaluations queued together, see
+    /// [`EvaluationBatch`](super::metering::EvaluationBatch). Never nested.
+    Batch(Vec<EvaluationEvent>),
+}
+
+impl EvaluationEvent {
+    /// Number of evaluations in the event.
+    pub(crate) fn evaluations(&self) -> u64 {
+        match self {
+            Self::Feature(_) | Self::Property(_) => 1,
+            Self::Batch(events) => events.len() as u64,
+        }
+    }
+}
+
+/// What the usage metering sends about the entities features and properties
+/// are evaluated for.
+///
+/// A plain hash of an entity ID is easily reversed by hashing candidate IDs
+/// (emails, sequential user IDs...), hence the keyed hashes.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub enum MeteringEntityIds {
+    /// Entity IDs are sent as given by [`Entity::get_id`](crate::Entity::get_id).
+    #[default]
+    Send,
+    /// The hex-encoded HMAC-SHA256 of the entity IDs, with a random key
+    /// generated by each client, is sent instead. Evaluations of the same
+    /// entity are still counted together within a client, but the hashes
+    /// differ between clients and restarts.
+    Hash,
+    /// Like [`MeteringEntityIds::Hash`], with the given key: the hashes are the
+    /// same in all the clients sharing it. Whoever knows the key can check
+    /// whether an entity ID was sent, keep it secret.
+    HashWithKey(String),
+    /// No entity IDs are sent, evaluations are only counted per feature or
+    /// property and segment.
+    Drop,
+}
+
+impl std::fmt::Debug for MeteringEntityIds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Send => f.write_str("Send"),
+            Self::Hash => f.write_str("Hash"),
+            Self::HashWithKey(_) => f.debug_tuple("HashWithKey").finish_non_exhaustive(),
+            Self::Drop => f.write_str("Drop"),
+        }
+    }
+}
+
+/// What the usage metering records and sends to the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MeteringOptions {
+    /// Whether the IDs of the evaluated entities are sent, their hashes or
+    /// nothing.
+    pub entity_ids: MeteringEntityIds,
+    /// Whether evaluations returning the disabled value of a feature are
+    /// recorded, e.g. of disabled features or of entities excluded by a
+    /// rollout. Defaults to `true`.
+    pub record_disabled_evaluations: bool,
+    /// Whether the hostname of the machine (or container) running the SDK is
+    /// sent along. Defaults to `false`.
+    pub send_host: bool,
+    /// Whether each usage also reports if the feature was enabled for the
+    /// entities (`enabled`) and where the served value comes from
+    /// (`value_source`), and each transmission the offset of the local clock
+    /// to the server (`clock_offset_ms`). Defaults to `false`, for services
+    /// that do not accept them yet.
+    pub send_evaluation_details: bool,
+}
+
+impl Default for MeteringOptions {
+    fn default() -> Self {
+        Self {
+            entity_ids: MeteringEntityIds::Send,
+            record_disabled_evaluations: true,
+            send_host: false,
+            send_evaluation_details: false,
+        }
+    }
+}
+
+/// How the entity IDs are sent, resolved from [`MeteringEntityIds`] when the
+/// metering starts.
+#[cfg(feature = "remote")]
+#[derive(Clone)]
+pub(crate) enum EntityIdEncoding {
+    Send,
+    Hash(Hmac<Sha256>),
+    Drop,
 }
 
+#[cfg(feature = "remote")]
+impl From<MeteringEntityIds> for EntityIdEncoding {
+    fn from(entity_ids: MeteringEntityIds) -> Self {
+        let hmac = |key: &[u8]| {
+            Self::Hash(Hmac::new_from_slice(key).expect("HMAC accepts keys of any length"))
+        };
+        match entity_ids {
+            MeteringEntityIds::Send => Self::Send,
+            MeteringEntityIds::Hash => hmac(&rand::random::<[u8; 32]>()),
+            MeteringEntityIds::HashWithKey(key) => hmac(key.as_bytes()),
+            MeteringEntityIds::Drop => Self::Drop,
+        }
+    }
+}
+
+#[cfg(feature = "remote")]
+impl EntityIdEncoding {
+    /// The entity ID as sent to the server.
+    pub(crate) fn apply(&self, entity_id: String) -> Option<String> {
+        match self {
+            Self::Send => Some(entity_id),
+            Self::Hash(hmac) => {
+                let mut hmac = hmac.clone();
+                hmac.update(entity_id.as_bytes());
+                Some(
+                    hmac.finalize()
+                        .into_bytes()
+                        .iter()
+                        .map(|byte| format!("{byte:02x}"))
+                        .collect(),
+                )
+            }
+            Self::Drop => None,
+        }
+    }
+}
+
+#[cfg(feature = "remote")]
 #[derive(Hash, Eq, PartialEq, Clone, Debug)]
 pub(crate) struct MeteringKey {
     pub feature_id: Option<String>,
     pub property_id: Option<String>,
-    pub entity_id: String,
+    pub entity_id: Option<String>,
     pub segment_id: Option<String>,
+    pub enabled: Option<bool>,
+    pub value_source: Option<ValueSource>,
 }
 
+#[cfg(feature = "remote")]
 impl MeteringKey {
-    pub fn from_feature(feature_id: String, entity_id: String, segment_id: Option<String>) -> Self {
+    pub fn from_feature(
+        feature_id: String,
+        entity_id: Option<String>,
+        segment_id: Option<String>,
+        value_source: ValueSource,
+    ) -> Self {
         Self {
             feature_id: Some(feature_id),
             property_id: None,
             entity_id,
             segment_id,
+            enabled: Some(value_source.is_enabled()),
+            value_source: Some(value_source),
         }
     }
 
     pub fn from_property(
         property_id: String,
-        entity_id: String,
+        entity_id: Option<String>,
         segment_id: Option<String>,
+        value_source: ValueSource,
     ) -> Self {
         Self {
             feature_id: None,
             property_id: Some(property_id),
             entity_id,
             segment_id,
+            enabled: None,
+            value_source: Some(value_source),
         }
     }
 }
 
+#[cfg(feature = "remote")]
 pub(crate) struct EvaluationData {
     pub number_of_evaluations: u32,
     pub time_of_last_evaluation: chrono::DateTime<chrono::Utc>,
 }
 
-impl Default for EvaluationData {
-    fn default() -> Self {
+#[cfg(feature = "remote")]
+impl EvaluationData {
+    pub fn new(evaluation_time: chrono::DateTime<chrono::Utc>) -> Self {
         Self {
             number_of_evaluations: 1,
-            time_of_last_evaluation: chrono::Utc::now(),
+            time_of_last_evaluation: evaluation_time,
         }
     }
-}
 
-impl EvaluationData {
-    pub fn add_one(&mut self) {
+    pub fn add_one(&mut self, evaluation_time: chrono::DateTime<chrono::Utc>) {
         self.number_of_evaluations += 1;
-        self.time_of_last_evaluation = chrono::Utc::now();
+        self.time_of_last_evaluation = self.time_of_last_evaluation.max(evaluation_time);
+    }
+}
+
+/// Identifies the SDK and the deployment that generated the metering data.
+#[cfg(feature = "remote")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MeteringMetadata {
+    pub sdk_name: String,
+    pub sdk_version: String,
+    /// Hostname of the machine (or container) running the SDK, if known and
+    /// enabled with [`MeteringOptions::send_host`].
+    pub host: Option<String>,
+    /// Free-form label given by the user, e.g. the name of the deployment.
+    pub instance_label: Option<String>,
+}
+
+#[cfg(feature = "remote")]
+impl MeteringMetadata {
+    pub fn new(instance_label: Option<String>, send_host: bool) -> Self {
+        Self {
+            sdk_name: "appconfiguration-rust-sdk".to_string(),
+            sdk_version: env!("CARGO_PKG_VERSION").to_string(),
+            host: send_host.then(Self::host).flatten(),
+            instance_label,
+        }
+    }
+
+    fn host() -> Option<String> {
+        ["HOSTNAME", "COMPUTERNAME"]
+            .into_iter()
+            .find_map(|name| std::env::var(name).ok())
+            .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+            .map(|host| host.trim().to_string())
+            .filter(|host| !host.is_empty())
     }
 }
@@ -15,16 +15,24 @@
 use chrono::{DateTime, Utc};
 use serde::Serialize;
 
-use crate::metering::models::{EvaluationData, MeteringKey};
+use crate::metering::models::{EvaluationData, MeteringKey, MeteringMetadata, ValueSource};
 
 #[derive(Debug, Clone, Serialize)]
 pub(crate) struct MeteringDataUsageJson {
     pub feature_id: Option<String>,
     pub property_id: Option<String>,
-    pub entity_id: String,
+    // Omitted with `MeteringEntityIds::Drop`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entity_id: Option<String>,
     // Serialized as "nil" when None
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub segment_id: Option<String>,
+    // Whether the feature was enabled for the entity, omitted for properties
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    // Where the served value comes from, e.g. `SEGMENT_VALUE`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value_source: Option<ValueSource>,
     // When this evaluation was last done
     pub evaluation_time: DateTime<Utc>,
     // how often this was evaluated
@@ -37,14 +45,29 @@ pub(crate) struct MeteringDataUsageJson {
 pub(crate) struct MeteringDataJson {
     pub collection_id: String,
     pub environment_id: String,
+    pub sdk_name: String,
+    pub sdk_version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance_label: Option<String>,
+    // How far the clock of the client is ahead of the server, in milliseconds,
+    // as measured with the last transmission
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clock_offset_ms: Option<i64>,
     pub usages: Vec<MeteringDataUsageJson>,
 }
 
 impl MeteringDataJson {
-    pub fn new(collection_id: String, environment_id: String) -> Self {
+    pub fn new(collection_id: String, environment_id: String, metadata: &MeteringMetadata) -> Self {
         Self {
             collection_id,
             environment_id,
+            sdk_name: metadata.sdk_name.clone(),
+            sdk_version: metadata.sdk_version.clone(),
+            host: metadata.host.clone(),
+            instance_label: metadata.instance_label.clone(),
+            clock_offset_ms: None,
             usages: Vec::default(),
         }
     }
@@ -55,6 +78,8 @@ impl MeteringDataJson {
             property_id: key.property_id.clone(),
             entity_id: key.entity_id.clone(),
             segment_id: key.segment_id.clone(),
+            enabled: key.enabled,
+            value_source: key.value_source,
             evaluation_time: data.time_of_last_evaluation,
             count: data.number_of_evaluations,
         };
@@ -13,18 +13,27 @@
 // limitations under the License.
 
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 use crate::ConfigurationDataError;
+use crate::Value;
 use crate::errors::Result;
 use crate::network::serialization::{
-    Collection, ConfigurationJson, Feature, Property, Segment, SegmentRule,
+    Collection, ConfigValue, ConfigurationJson, Environment, Experiment, Feature, Property,
+    SegmentRule, ValueType,
 };
-use crate::segment_evaluation::TargetingRules;
+use crate::segment_evaluation::{CompiledSegment, TargetingRules, check_schedule};
 
-use super::feature_snapshot::FeatureSnapshot;
+use super::configuration_diff::{ConfigurationDiff, diff_resources};
+use super::configuration_source::ConfigurationSource;
+use super::feature_snapshot::{FeatureSnapshot, Variations};
+use super::flagd_export;
 use super::property_snapshot::PropertySnapshot;
 use super::secret_property::SecretPropertySnapshot;
-use crate::ConfigurationProvider;
+use super::segment_snapshot::SegmentSnapshot;
+use super::validation::{ResourceKind, ValidationMode, ValidationReport, Validator};
+use crate::utils::{SystemClock, now_millis};
+use crate::{ConfigurationProvider, Entity, Feature as _};
 
 /// Represents all the configuration data needed for the client to perform
 /// feature/propery evaluation.
@@ -33,100 +42,220 @@ use crate::ConfigurationProvider;
 pub struct Configuration {
     pub(crate) features: HashMap<String, (Feature, TargetingRules)>,
     pub(crate) properties: HashMap<String, (Property, TargetingRules)>,
+    pub(crate) segments: HashMap<String, Arc<CompiledSegment>>,
+    pub(crate) validation_report: ValidationReport,
+    pub(crate) source: ConfigurationSource,
+    /// Display name of the environment, if the data includes it.
+    pub(crate) environment_name: Option<String>,
 }
 
+const UNKNOWN_TYPE_REASON: &str = "its type is not supported by this SDK version";
+
 impl Configuration {
     /// Constructs the Configuration, by consuming and filtering data in exchange format
-    pub fn new(
+    ///
+    /// Only the features and properties of the given collection are kept. Resources
+    /// without collections are kept too: the server omits them in responses that
+    /// are already scoped to a collection.
+    pub(crate) fn new(
         environment_id: &str,
         collection_id: &str,
         configuration: ConfigurationJson,
     ) -> std::result::Result<Self, ConfigurationDataError> {
-        let environment = configuration
-            .environments
-            .into_iter()
-            .find(|e| e.environment_id == environment_id)
-            .ok_or(ConfigurationDataError::EnvironmentNotFound(
-                environment_id.to_string(),
-            ))?;
+        Self::new_with_validation_mode(
+            environment_id,
+            collection_id,
+            configuration,
+            ValidationMode::Strict,
+        )
+    }
 
-        let features = environment
-            .features
-            .into_iter()
-            .filter(|feature| {
-                Self::resource_belongs_to_collection(&feature.collections, collection_id)
-            })
-            .map(|mut feature| {
-                feature.segment_rules.sort_by(|a, b| a.order.cmp(&b.order));
+    /// Like [`Configuration::new`], with `ValidationMode::Lenient` invalid resources
+    /// are dropped and listed in the [`ValidationReport`] instead of failing.
+    pub(crate) fn new_with_validation_mode(
+        environment_id: &str,
+        collection_id: &str,
+        configuration: ConfigurationJson,
+        validation_mode: ValidationMode,
+    ) -> std::result::Result<Self, ConfigurationDataError> {
+        configuration.verify_checksum()?;
+        let mut validator = Validator::new(validation_mode);
+        let environment = Self::select_environment(configuration.environments, environment_id)?;
 
-                // Get the segment rules that apply to this feature
-                let (segments, referenced_segment_count) = Self::get_segments_for_segment_rules(
-                    &configuration.segments,
-                    &feature.segment_rules,
-                );
+        // Dumps of the whole instance list their collections, the requested one must exist
+        if let Some(collections) = &configuration.collections
+            && !collections
+                .iter()
+                .any(|collection| collection.collection_id == collection_id)
+        {
+            return Err(ConfigurationDataError::CollectionNotFound(
+                collection_id.to_string(),
+            ));
+        }
 
-                // Integrity DB check: all segment_ids should be available in the snapshot
-                if referenced_segment_count != segments.len() {
-                    return Err(ConfigurationDataError::MissingSegments(
-                        feature.feature_id.to_string(),
-                    ));
-                }
+        // Segment rules are compiled once. Segments with unknown operators are
+        // kept unsupported when lenient, so only they are dropped and not
+        // the features and properties targeting them
+        let mut segments = HashMap::new();
+        for segment in configuration.segments {
+            let segment_id = segment.segment_id.clone();
+            let compiled = match validator.check(
+                ResourceKind::Segment,
+                &segment_id,
+                CompiledSegment::try_from(segment.clone()),
+            )? {
+                Some(compiled) => compiled,
+                None => CompiledSegment::unsupported(segment),
+            };
+            segments.insert(segment_id, Arc::new(compiled));
+        }
 
-                let segment_rules = TargetingRules::new(
-                    segments,
-                    feature.segment_rules.clone(),
+        let mut features = HashMap::new();
+        for mut feature in environment.features {
+            if !Self::resource_belongs_to_collection(&feature.collections, collection_id) {
+                continue;
+            }
+            if feature.r#type == ValueType::Unknown {
+                validator.skip(
+                    ResourceKind::Feature,
+                    &feature.feature_id,
+                    UNKNOWN_TYPE_REASON,
+                );
+                continue;
+            }
+            feature.segment_rules.sort_by_key(|a| a.order);
+            let targeting_rules = Self::check_values(
+                &feature.feature_id,
+                feature.r#type,
+                feature.format.as_deref(),
+                [
+                    ("enabled value".to_string(), &feature.enabled_value),
+                    ("disabled value".to_string(), &feature.disabled_value),
+                ]
+                .into_iter()
+                .chain(feature.experiment.iter().flat_map(|experiment| {
+                    experiment.variations.iter().map(|variation| {
+                        (
+                            format!("value of variation '{}'", variation.variation_id),
+                            &variation.variation_value,
+                        )
+                    })
+                })),
+                &feature.segment_rules,
+            )
+            .and_then(|()| Self::check_experiment(&feature.feature_id, feature.experiment.as_ref()))
+            .and_then(|()| {
+                Self::targeting_rules(
+                    &segments,
+                    &feature.segment_rules,
                     feature.r#type,
                     Some(&feature.feature_id),
-                );
-
-                Ok((feature.feature_id.clone(), (feature, segment_rules)))
-            })
-            .collect::<std::result::Result<_, _>>()?;
-
-        let properties = environment
-            .properties
-            .into_iter()
-            .filter(|property| {
-                Self::resource_belongs_to_collection(&property.collections, collection_id)
-            })
-            .map(|mut property| {
-                property.segment_rules.sort_by(|a, b| a.order.cmp(&b.order));
+                    &feature.feature_id,
+                )
+            });
+            if let Some(targeting_rules) =
+                validator.check(ResourceKind::Feature, &feature.feature_id, targeting_rules)?
+            {
+                features.insert(feature.feature_id.to_string(), (feature, targeting_rules));
+            }
+        }
 
-                // Get the segment rules that apply to this property
-                let (segments, referenced_segment_count) = Self::get_segments_for_segment_rules(
-                    &configuration.segments,
-                    &property.segment_rules,
+        let mut properties = HashMap::new();
+        for mut property in environment.properties {
+            if !Self::resource_belongs_to_collection(&property.collections, collection_id) {
+                continue;
+            }
+            if property.r#type == ValueType::Unknown {
+                validator.skip(
+                    ResourceKind::Property,
+                    &property.property_id,
+                    UNKNOWN_TYPE_REASON,
                 );
-
-                // Integrity DB check: all segment_ids should be available in the snapshot
-                if referenced_segment_count != segments.len() {
-                    return Err(ConfigurationDataError::MissingSegments(
-                        property.property_id.to_string(),
-                    ));
-                }
-
-                let segment_rules = TargetingRules::new(
-                    segments,
-                    property.segment_rules.clone(),
+                continue;
+            }
+            property.segment_rules.sort_by_key(|a| a.order);
+            let targeting_rules = Self::check_values(
+                &property.property_id,
+                property.r#type,
+                property.format.as_deref(),
+                [("value".to_string(), &property.value)],
+                &property.segment_rules,
+            )
+            .and_then(|()| {
+                Self::targeting_rules(
+                    &segments,
+                    &property.segment_rules,
                     property.r#type,
                     None,
-                );
-                Ok((property.property_id.clone(), (property, segment_rules)))
-            })
-            .collect::<std::result::Result<_, _>>()?;
+                    &property.property_id,
+                )
+            });
+            if let Some(targeting_rules) = validator.check(
+                ResourceKind::Property,
+                &property.property_id,
+                targeting_rules,
+            )? {
+                properties.insert(property.property_id.clone(), (property, targeting_rules));
+            }
+        }
 
         Ok(Configuration {
             features,
             properties,
+            segments,
+            validation_report: validator.into_report(),
+            source: ConfigurationSource::default(),
+            environment_name: environment.name,
         })
     }
 
+    /// Records where the data of this configuration comes from.
+    #[cfg(feature = "remote")]
+    pub(crate) fn with_source(mut self, source: ConfigurationSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Resources skipped while building this configuration.
+    pub fn validation_report(&self) -> &ValidationReport {
+        &self.validation_report
+    }
+
+    /// Where the data of this configuration comes from.
+    pub fn source(&self) -> ConfigurationSource {
+        self.source
+    }
+
     pub fn from_file(
         filepath: &std::path::Path,
         environment_id: &str,
         collection_id: &str,
+    ) -> Result<Self> {
+        Self::from_file_with_validation_mode(
+            filepath,
+            environment_id,
+            collection_id,
+            ValidationMode::Strict,
+        )
+    }
+
+    pub(crate) fn from_file_with_validation_mode(
+        filepath: &std::path::Path,
+        environment_id: &str,
+        collection_id: &str,
+        validation_mode: ValidationMode,
     ) -> Result<Self> {
         let configuration = ConfigurationJson::new(filepath)?;
+        Ok(Configuration::new_with_validation_mode(
+            environment_id,
+            collection_id,
+            configuration,
+            validation_mode,
+        )?)
+    }
+
+    pub fn from_json_str(json: &str, environment_id: &str, collection_id: &str) -> Result<Self> {
+        let configuration = ConfigurationJson::from_json_str(json)?;
         Ok(Configuration::new(
             environment_id,
             collection_id,
@@ -134,12 +263,12 @@ impl Configuration {
         )?)
     }
 
-    /// Returns a mapping of segment ID to `Segment` for all segments referenced
+    /// Returns a mapping of segment ID to `CompiledSegment` for all segments referenced
     /// by the given `segment_rules`.
     fn get_segments_for_segment_rules(
-        segments: &[Segment],
+        segments: &HashMap<String, Arc<CompiledSegment>>,
         segment_rules: &[SegmentRule],
-    ) -> (HashMap<String, Segment>, usize) {
+    ) -> (HashMap<String, Arc<CompiledSegment>>, usize) {
         let referenced_segment_ids = segment_rules
             .iter()
             .flat_map(|targeting_rule| {
@@ -153,15 +282,147 @@ impl Configuration {
 
         let referenced_count = referenced_segment_ids.len();
 
-        let found = segments
-            .iter()
-            .filter(|&segment| referenced_segment_ids.contains(&segment.segment_id))
-            .map(|segment| (segment.segment_id.clone(), segment.clone()))
+        let found = referenced_segment_ids
+            .into_iter()
+            .filter_map(|segment_id| {
+                let segment = segments.get(&segment_id)?.clone();
+                Some((segment_id, segment))
+            })
             .collect();
 
         (found, referenced_count)
     }
 
+    /// Checks that the values of a feature or property, including the ones of
+    /// its segment rules, convert to its type and match its format.
+    ///
+    /// Only `JSON` strings are checked against the format, `TEXT` takes any
+    /// string and `YAML` is not parsed by the SDK.
+    fn check_values<'a>(
+        resource_id: &str,
+        value_type: ValueType,
+        format: Option<&str>,
+        values: impl IntoIterator<Item = (String, &'a ConfigValue)>,
+        segment_rules: &'a [SegmentRule],
+    ) -> std::result::Result<(), ConfigurationDataError> {
+        let rule_values = segment_rules
+            .iter()
+            .filter(|rule| !rule.value.is_default())
+            .map(|rule| (format!("value of segment rule {}", rule.order), &rule.value));
+        let values = values.into_iter().chain(rule_values);
+        for (location, value) in values {
+            let invalid = |reason: String| ConfigurationDataError::InvalidValue {
+                resource_id: resource_id.to_string(),
+                location: location.clone(),
+                reason,
+            };
+            let converted = Value::try_from((value_type, value.clone()))
+                .map_err(|_| invalid(format!("{value} is not a {value_type} value")))?;
+            if let (Value::String(raw), Some("JSON")) = (&converted, format) {
+                serde_json::from_str::<serde_json::Value>(raw)
+                    .map_err(|e| invalid(format!("not a valid JSON document: {e}")))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the traffic distribution of the experiment of a feature.
+    fn check_experiment(
+        feature_id: &str,
+        experiment: Option<&Experiment>,
+    ) -> std::result::Result<(), ConfigurationDataError> {
+        let Some(experiment) = experiment else {
+            return Ok(());
+        };
+        Variations::check(experiment).map_err(|reason| ConfigurationDataError::InvalidValue {
+            resource_id: feature_id.to_string(),
+            location: format!(
+                "traffic distribution of experiment '{}'",
+                experiment.experiment_id
+            ),
+            reason,
+        })
+    }
+
+    /// Builds the targeting rules of a feature or property.
+    ///
+    /// The `start_at` and `end_at` bounds of the segment rules must be RFC 3339
+    /// timestamps.
+    ///
+    /// Integrity DB check: all segment_ids should be available in the snapshot
+    fn targeting_rules(
+        segments: &HashMap<String, Arc<CompiledSegment>>,
+        segment_rules: &[SegmentRule],
+        value_type: ValueType,
+        feature_id: Option<&str>,
+        resource_id: &str,
+    ) -> std::result::Result<TargetingRules, ConfigurationDataError> {
+        for segment_rule in segment_rules {
+            check_schedule(segment_rule).map_err(|(location, reason)| {
+                ConfigurationDataError::InvalidValue {
+                    resource_id: resource_id.to_string(),
+                    location,
+                    reason,
+                }
+            })?;
+        }
+        let (referenced_segments, referenced_segment_count) =
+            Self::get_segments_for_segment_rules(segments, segment_rules);
+        if referenced_segment_count != referenced_segments.len() {
+            return Err(ConfigurationDataError::MissingSegments(
+                resource_id.to_string(),
+            ));
+        }
+        Ok(TargetingRules::new(
+            referenced_segments,
+            segment_rules.to_vec(),
+            value_type,
+            feature_id,
+        ))
+    }
+
+    /// Lists the features, properties and segments that were added, removed
+    /// or changed in `other` compared to `self`.
+    pub fn diff(&self, other: &Configuration) -> ConfigurationDiff {
+        ConfigurationDiff {
+            features: diff_resources(&self.features, &other.features, |(feature, _)| feature),
+            properties: diff_resources(&self.properties, &other.properties, |(property, _)| {
+                property
+            }),
+            segments: diff_resources(&self.segments, &other.segments, |compiled| {
+                &compiled.segment
+            }),
+        }
+    }
+
+    /// Converts the features into a [flagd](https://flagd.dev) flag definition
+    /// file, to evaluate them with other OpenFeature engines.
+    ///
+    /// Each feature becomes a flag with the variants `enabled`, `disabled` and
+    /// `rule-<order>` for segment rules with their own value. Segments are
+    /// translated to JsonLogic targeting, `entityId` being the `targetingKey`.
+    /// Rollouts keep their percentages, but flagd assigns entities to buckets
+    /// with a different hash, so the set of entities in a partial rollout differs.
+    /// Progressive rollouts are exported at their current percentage and
+    /// experiments are not exported.
+    pub fn export_flagd(&self) -> Result<serde_json::Value> {
+        flagd_export::export_flagd(&self.features, &self.segments, now_millis(&SystemClock))
+    }
+
+    /// Evaluates all the features for `entity` and returns a flat JSON object
+    /// with the values, `{"<feature_id>": <value>}`.
+    pub fn export_flat(&self, entity: &(impl Entity + ?Sized)) -> Result<serde_json::Value> {
+        let mut values = serde_json::Map::new();
+        for feature_id in self.features.keys() {
+            let value = self
+                .get_feature(feature_id)?
+                .get_current_value(entity)?
+                .value;
+            values.insert(feature_id.clone(), value.into());
+        }
+        Ok(values.into())
+    }
+
     pub(crate) fn get_feature_ids_refs(&self) -> Vec<&String> {
         self.features.keys().collect()
     }
@@ -170,6 +431,26 @@ impl Configuration {
         self.properties.keys().collect()
     }
 
+    /// Returns the environment `environment_id`, whatever the validation mode:
+    /// serving another environment would serve the wrong values.
+    fn select_environment(
+        environments: Vec<Environment>,
+        environment_id: &str,
+    ) -> std::result::Result<Environment, ConfigurationDataError> {
+        let available: Vec<String> = environments
+            .iter()
+            .map(|e| e.environment_id.clone())
+            .collect();
+        let mut environments = environments;
+        if let Some(position) = available.iter().position(|id| id == environment_id) {
+            return Ok(environments.swap_remove(position));
+        }
+        Err(ConfigurationDataError::EnvironmentNotFound {
+            environment_id: environment_id.to_string(),
+            available,
+        })
+    }
+
     fn resource_belongs_to_collection(
         collections: &Option<Vec<Collection>>,
         collection_id: &str,
@@ -198,6 +479,12 @@ impl ConfigurationProvider for Configuration {
         let enabled_value = (feature.r#type, feature.enabled_value.clone()).try_into()?;
         let disabled_value = (feature.r#type, feature.disabled_value.clone()).try_into()?;
         let feature_type = feature.r#type.to_string();
+        let variations = feature
+            .experiment
+            .as_ref()
+            .map(|experiment| Variations::from_experiment(experiment, feature.r#type))
+            .transpose()?
+            .flatten();
         Ok(FeatureSnapshot::new(
             feature.enabled,
             enabled_value,
@@ -205,13 +492,16 @@ impl ConfigurationProvider for Configuration {
             feature.rollout_percentage,
             feature.rollout_type.clone(),
             feature.rollout_configuration.clone(),
-            &feature.name,
-            feature_id,
+            feature.name.clone(),
+            feature.feature_id.clone(),
             feature_type,
             feature.format.clone(),
             segment_rules.clone(),
             None,
-        ))
+        )
+        .with_metadata(feature.description.clone(), feature.tags.clone())
+        .with_variations(variations)
+        .with_source(self.source))
     }
 
     fn get_property_ids(&self) -> Result<Vec<String>> {
@@ -236,7 +526,29 @@ impl ConfigurationProvider for Configuration {
             &property.name,
             &property.property_id,
             None,
-        ))
+        )
+        .with_metadata(property.description.clone(), property.tags.clone())
+        .with_source(self.source))
+    }
+
+    fn get_segment_ids(&self) -> Result<Vec<String>> {
+        Ok(self.segments.keys().cloned().collect())
+    }
+
+    fn get_segment(&self, segment_id: &str) -> Result<SegmentSnapshot> {
+        let segment = self
+            .segments
+            .get(segment_id)
+            .ok_or_else(|| ConfigurationDataError::SegmentNotFound(segment_id.to_string()))?;
+        Ok(SegmentSnapshot::new(segment.clone()))
+    }
+
+    fn get_configuration_snapshot(&self) -> Result<Configuration> {
+        Ok(self.clone())
+    }
+
+    fn environment_name(&self) -> Result<Option<String>> {
+        Ok(self.environment_name.clone())
     }
 
     fn is_online(&self) -> Result<bool> {
@@ -275,11 +587,69 @@ mod tests {
     use std::path::PathBuf;
 
     use super::*;
+    use crate::ResourceChange;
     use crate::network::serialization::ConfigurationJson;
-    use crate::network::serialization::fixtures::example_configuration_enterprise_path;
+    use crate::network::serialization::fixtures::{
+        configuration_json_feature1_enabled, example_configuration_enterprise_path,
+    };
 
     use rstest::*;
 
+    #[rstest]
+    fn test_segments_shared_between_resources(example_configuration_enterprise_path: PathBuf) {
+        let configuration =
+            Configuration::from_file(&example_configuration_enterprise_path, "dev", "blue-charge")
+                .unwrap();
+
+        // f2, f4 and p1 target the same segment
+        let segment = &configuration.segments["l2dfr61d"];
+        let targeted_by =
+            |rules: &TargetingRules| Arc::ptr_eq(rules.segment("l2dfr61d").unwrap(), segment);
+        assert!(targeted_by(&configuration.features["f2"].1));
+        assert!(targeted_by(&configuration.features["f4"].1));
+        assert!(targeted_by(&configuration.properties["p1"].1));
+    }
+
+    #[rstest]
+    fn test_environment_name(example_configuration_enterprise_path: PathBuf) {
+        let configuration =
+            Configuration::from_file(&example_configuration_enterprise_path, "dev", "blue-charge")
+                .unwrap();
+        assert_eq!(
+            configuration.environment_name().unwrap(),
+            Some("Dev".to_string())
+        );
+
+        let configuration = Configuration::new(
+            "environment_id",
+            "collection_id",
+            configuration_json_feature1_enabled(),
+        )
+        .unwrap();
+        assert_eq!(configuration.environment_name().unwrap(), None);
+    }
+
+    #[rstest]
+    fn test_export_flat(example_configuration_enterprise_path: PathBuf) {
+        let configuration =
+            Configuration::from_file(&example_configuration_enterprise_path, "dev", "blue-charge")
+                .unwrap();
+        let entity = crate::tests::GenericEntity {
+            id: "a1".to_string(),
+            attributes: HashMap::from([("code".to_string(), Value::from("CHG-1".to_string()))]),
+        };
+        assert_eq!(
+            configuration.export_flat(&entity).unwrap(),
+            serde_json::json!({
+                "f1": 40,
+                "f2": "inherited-enabled",
+                "f3": true,
+                "f4": true,
+                "f6": "under maintenance",
+            })
+        );
+    }
+
     #[rstest]
     fn test_filter_configurations(example_configuration_enterprise_path: PathBuf) {
         let content = std::fs::File::open(example_configuration_enterprise_path)
@@ -290,8 +660,639 @@ mod tests {
         let result = Configuration::new("does_for_sure_not_exist", "blue-charge", config_json);
         assert!(result.is_err());
 
+        let err = result.unwrap_err();
+        assert!(matches!(
+                err,
+                 ConfigurationDataError::EnvironmentNotFound{ref environment_id, ref available}
+                    if environment_id == "does_for_sure_not_exist" && available == &["dev", "prod", "stage"]));
+        assert_eq!(
+            err.to_string(),
+            "Environment 'does_for_sure_not_exist' not found, the configuration contains: [dev, prod, stage]"
+        );
+    }
+
+    #[rstest]
+    fn test_sole_environment_not_served(configuration_json_feature1_enabled: ConfigurationJson) {
+        let err = Configuration::new(
+            "prod",
+            "collection_id",
+            configuration_json_feature1_enabled.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigurationDataError::EnvironmentNotFound { ref available, .. } if available == &["environment_id"]
+        ));
+
+        // Lenient mode requires the exact environment too
+        let err = Configuration::new_with_validation_mode(
+            "prod",
+            "collection_id",
+            configuration_json_feature1_enabled,
+            ValidationMode::Lenient,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigurationDataError::EnvironmentNotFound { ref environment_id, .. } if environment_id == "prod"
+        ));
+    }
+
+    #[rstest]
+    fn test_values_checked_against_type_and_format(
+        mut configuration_json_feature1_enabled: ConfigurationJson,
+    ) {
+        let environment = &mut configuration_json_feature1_enabled.environments[0];
+        environment.features[0].disabled_value = ConfigValue(serde_json::json!("minus one"));
+        environment.properties.push(Property {
+            name: "P1".to_string(),
+            property_id: "p1".to_string(),
+            description: None,
+            r#type: ValueType::String,
+            tags: None,
+            format: Some("JSON".to_string()),
+            value: ConfigValue(serde_json::json!("{not json")),
+            segment_rules: Vec::new(),
+            collections: None,
+        });
+
+        let err = Configuration::new(
+            "environment_id",
+            "collection_id",
+            configuration_json_feature1_enabled.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid disabled value in resource 'f1': \"minus one\" is not a NUMERIC value"
+        );
+
+        let configuration = Configuration::new_with_validation_mode(
+            "environment_id",
+            "collection_id",
+            configuration_json_feature1_enabled,
+            ValidationMode::Lenient,
+        )
+        .unwrap();
+        assert!(configuration.get_feature_ids().unwrap().is_empty());
+        assert!(configuration.get_property_ids().unwrap().is_empty());
+        let skipped = &configuration.validation_report().skipped;
+        assert_eq!(skipped.len(), 2);
+        assert_eq!(
+            (skipped[1].kind, skipped[1].id.as_str()),
+            (ResourceKind::Property, "p1")
+        );
+        assert!(
+            skipped[1]
+                .reason
+                .starts_with("Invalid value in resource 'p1': not a valid JSON document")
+        );
+    }
+
+    #[test]
+    fn test_malformed_schedule() {
+        let json = serde_json::json!({
+            "environments": [{
+                "environment_id": "dev",
+                "features": [{
+                    "name": "F1", "feature_id": "f1", "type": "BOOLEAN",
+                    "enabled_value": true, "disabled_value": false,
+                    "enabled": true, "rollout_percentage": 100,
+                    "segment_rules": [{
+                        "rules": [{"segments": ["beta"]}],
+                        "value": "$default",
+                        "order": 1,
+                        "rollout_percentage": 100,
+                        "start_at": "soon"
+                    }]
+                }],
+                "properties": []
+            }],
+            "segments": [{
+                "name": "Beta",
+                "segment_id": "beta",
+                "rules": [{"attribute_name": "groups", "operator": "is", "values": ["beta"]}]
+            }]
+        });
+        let configuration_json = ConfigurationJson::from_json_str(&json.to_string()).unwrap();
+
+        let err = Configuration::new("dev", "c1", configuration_json.clone()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid start_at of segment rule 1 in resource 'f1': 'soon' is not an RFC 3339 timestamp"
+        );
+
+        let configuration = Configuration::new_with_validation_mode(
+            "dev",
+            "c1",
+            configuration_json,
+            ValidationMode::Lenient,
+        )
+        .unwrap();
+        assert!(configuration.get_feature_ids().unwrap().is_empty());
+        assert_eq!(
+            configuration.validation_report().skipped[0].id.as_str(),
+            "f1"
+        );
+    }
+
+    #[test]
+    fn test_list_properties() {
+        use crate::Property as _;
+        use crate::tests::GenericEntity;
+
+        let json = |tenants: serde_json::Value| {
+            serde_json::json!({
+                "environments": [{
+                    "environment_id": "environment_id",
+                    "features": [],
+                    "properties": [{
+                        "name": "Tenants",
+                        "property_id": "tenants",
+                        "type": "STRING",
+                        "value": tenants,
+                        "segment_rules": [{
+                            "rules": [{"segments": ["beta"]}],
+                            "value": ["t3"],
+                            "order": 1
+                        }]
+                    }, {
+                        "name": "Ports",
+                        "property_id": "ports",
+                        "type": "NUMERIC",
+                        "value": [80, 443],
+                        "segment_rules": []
+                    }]
+                }],
+                "segments": [{
+                    "name": "Beta",
+                    "segment_id": "beta",
+                    "rules": [{"attribute_name": "groups", "operator": "is", "values": ["beta"]}]
+                }]
+            })
+            .to_string()
+        };
+        let configuration = Configuration::from_json_str(
+            &json(serde_json::json!(["t1", "t2"])),
+            "environment_id",
+            "collection_id",
+        )
+        .unwrap();
+
+        let tenants = configuration.get_property("tenants").unwrap();
+        let entity = |groups: Vec<&str>| GenericEntity {
+            id: "a".into(),
+            attributes: HashMap::from([("groups".into(), Value::from(groups))]),
+        };
+        let value = tenants
+            .get_current_value(&entity(vec!["dev"]))
+            .unwrap()
+            .value;
+        assert_eq!(value, Value::from(vec!["t1", "t2"]));
+        assert_eq!(value.to_string(), "[t1, t2]");
+        // Overridden for the segment, which a list attribute matches if any item does
+        let tenants_of_beta: Vec<String> = tenants
+            .get_value_into(&entity(vec!["dev", "beta"]))
+            .unwrap();
+        assert_eq!(tenants_of_beta, ["t3"]);
+
+        let ports = configuration.get_property("ports").unwrap();
+        let ports: Vec<i64> = ports.get_value_into(&entity(vec![])).unwrap();
+        assert_eq!(ports, [80, 443]);
+
+        let err = Configuration::from_json_str(
+            &json(serde_json::json!(["t1", 2])),
+            "environment_id",
+            "collection_id",
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid value in resource 'tenants': [\"t1\",2] is not a STRING value"
+        );
+    }
+
+    #[rstest]
+    #[case("blue-charge", &["f1", "f2", "f3", "f4", "f6"], &["p1", "p2", "p4"])]
+    #[case("my-dashboard", &["f5", "f6"], &["p3", "p4"])]
+    #[case("ci-pipeline", &["f6"], &["p4"])]
+    fn test_filter_by_collection(
+        example_configuration_enterprise_path: PathBuf,
+        #[case] collection_id: &str,
+        #[case] feature_ids: &[&str],
+        #[case] property_ids: &[&str],
+    ) {
+        let configuration =
+            Configuration::from_file(&example_configuration_enterprise_path, "dev", collection_id)
+                .unwrap();
+
+        let mut features = configuration.get_feature_ids_refs();
+        features.sort();
+        assert_eq!(features, feature_ids);
+        let mut properties = configuration.get_property_ids_refs();
+        properties.sort();
+        assert_eq!(properties, property_ids);
+    }
+
+    #[rstest]
+    fn test_collection_not_found(example_configuration_enterprise_path: PathBuf) {
+        let content = std::fs::File::open(example_configuration_enterprise_path)
+            .expect("file should open read only");
+        let config_json: ConfigurationJson =
+            serde_json::from_reader(content).expect("Error parsing JSON into Configuration");
+
+        let result = Configuration::new("dev", "does-not-exist", config_json);
+        assert!(matches!(
+            result.unwrap_err(),
+            ConfigurationDataError::CollectionNotFound(ref collection_id) if collection_id == "does-not-exist"
+        ));
+    }
+
+    // Payload of a newer service version: unknown fields everywhere and a new type
+    #[test]
+    fn test_forward_compatible_payload() {
+        let json = serde_json::json!({
+            "environments": [{
+                "name": "Dev",
+                "environment_id": "dev",
+                "new_environment_field": {"nested": true},
+                "features": [
+                    {
+                        "name": "F1", "feature_id": "f1", "type": "BOOLEAN",
+                        "enabled_value": true, "disabled_value": false,
+                        "segment_rules": [], "enabled": true, "rollout_percentage": 100,
+                        "new_feature_field": [1, 2, 3]
+                    },
+                    {
+                        "name": "F2", "feature_id": "f2", "type": "DATETIME",
+                        "enabled_value": "2030-01-01T00:00:00Z",
+                        "disabled_value": "1970-01-01T00:00:00Z",
+                        "segment_rules": [], "enabled": true, "rollout_percentage": 100
+                    }
+                ],
+                "properties": [
+                    {
+                        "name": "P1", "property_id": "p1", "type": "STRING", "value": "v",
+                        "segment_rules": [], "new_property_field": null
+                    },
+                    {
+                        "name": "P2", "property_id": "p2", "type": "JSON_SCHEMA", "value": {},
+                        "segment_rules": []
+                    }
+                ]
+            }],
+            "collections": [{"collection_id": "c1", "name": "C1", "new_collection_field": 1}],
+            "segments": [],
+            "new_top_level_field": "ignored"
+        });
+
+        let configuration = Configuration::from_json_str(&json.to_string(), "dev", "c1").unwrap();
+        assert_eq!(configuration.get_feature_ids_refs(), ["f1"]);
+        assert_eq!(configuration.get_property_ids_refs(), ["p1"]);
+        assert!(matches!(
+            configuration.get_feature("f2").unwrap_err(),
+            crate::Error::ConfigurationDataError(ConfigurationDataError::FeatureNotFound(_))
+        ));
+        let skipped = &configuration.validation_report().skipped;
+        assert_eq!(skipped.len(), 2);
+        assert_eq!(
+            (skipped[0].kind, skipped[0].id.as_str()),
+            (ResourceKind::Feature, "f2")
+        );
+        assert_eq!(
+            (skipped[1].kind, skipped[1].id.as_str()),
+            (ResourceKind::Property, "p2")
+        );
+    }
+
+    #[test]
+    fn test_resource_metadata() {
+        let json = serde_json::json!({
+            "environments": [{
+                "name": "Dev",
+                "environment_id": "dev",
+                "features": [
+                    {
+                        "name": "F1", "feature_id": "f1", "type": "BOOLEAN",
+                        "description": "Shows the new checkout", "tags": "checkout,beta",
+                        "enabled_value": true, "disabled_value": false,
+                        "segment_rules": [], "enabled": true, "rollout_percentage": 100
+                    },
+                    {
+                        "name": "F2", "feature_id": "f2", "type": "STRING",
+                        "description": "", "tags": "",
+                        "enabled_value": "a", "disabled_value": "b",
+                        "segment_rules": [], "enabled": true, "rollout_percentage": 100
+                    }
+                ],
+                "properties": [
+                    {
+                        "name": "P1", "property_id": "p1", "type": "STRING", "format": "YAML",
+                        "description": "Retry policy", "tags": "network", "value": "retries: 3",
+                        "segment_rules": []
+                    }
+                ]
+            }],
+            "collections": [{"collection_id": "c1", "name": "C1"}],
+            "segments": []
+        });
+        let configuration = Configuration::from_json_str(&json.to_string(), "dev", "c1").unwrap();
+
+        let f1 = configuration.get_feature("f1").unwrap();
+        assert_eq!(f1.description(), Some("Shows the new checkout"));
+        assert_eq!(f1.tags(), Some("checkout,beta"));
+        assert_eq!(f1.value_type(), "BOOLEAN");
+        assert_eq!(f1.format(), None);
+
+        let f2 = configuration.get_feature("f2").unwrap();
+        assert_eq!(f2.description(), None);
+        assert_eq!(f2.tags(), None);
+        assert_eq!(f2.value_type(), "STRING");
+        assert_eq!(f2.format(), Some("TEXT"));
+
+        let p1 = configuration.get_property("p1").unwrap();
+        assert_eq!(p1.description(), Some("Retry policy"));
+        assert_eq!(p1.tags(), Some("network"));
+        assert_eq!(p1.value_type(), "STRING");
+        assert_eq!(p1.format(), Some("YAML"));
+
+        assert_eq!(f1.source(), ConfigurationSource::Local);
+        let configuration = configuration.with_source(ConfigurationSource::PersistentCache);
+        let f1 = configuration.get_feature("f1").unwrap();
+        assert_eq!(f1.source(), ConfigurationSource::PersistentCache);
+        let p1 = configuration.get_property("p1").unwrap();
+        assert_eq!(p1.source(), ConfigurationSource::PersistentCache);
+    }
+
+    #[test]
+    fn test_variations() {
+        let feature = |feature_id: &str, enabled: bool, variation_b: serde_json::Value| {
+            serde_json::json!({
+                "name": feature_id, "feature_id": feature_id, "type": "STRING",
+                "enabled_value": "on", "disabled_value": "off",
+                "segment_rules": [], "enabled": enabled, "rollout_percentage": 100,
+                "experiment": {
+                    "experiment_id": "e1",
+                    "experiment_status": "RUNNING",
+                    "iteration": {"iteration_id": "i1", "iteration_key": "k1"},
+                    "variations": [
+                        {"variation_id": "a", "variation_value": "value-a"},
+                        {"variation_id": "b", "variation_value": variation_b}
+                    ],
+                    "traffic_distribution": {
+                        "type": "DEFAULT",
+                        "control_group": {"variation_id": "a", "rollout_percentage": 50},
+                        "experimental_group": [{"variation_id": "b", "rollout_percentage": 50}]
+                    }
+                }
+            })
+        };
+        let json = serde_json::json!({
+            "environments": [{
+                "name": "Dev",
+                "environment_id": "dev",
+                "features": [
+                    feature("f1", true, "value-b".into()),
+                    feature("f2", false, "value-b".into())
+                ],
+                "properties": []
+            }],
+            "collections": [{"collection_id": "c1", "name": "C1"}],
+            "segments": []
+        });
+        let configuration = Configuration::from_json_str(&json.to_string(), "dev", "c1").unwrap();
+
+        let f1 = configuration.get_feature("f1").unwrap();
+        let mut names = HashSet::new();
+        for id in 0..100 {
+            let entity = crate::tests::GenericEntity {
+                id: format!("user-{id}"),
+                attributes: HashMap::new(),
+            };
+            let variation = f1.get_variation(&entity).unwrap();
+            assert_eq!(f1.get_variation(&entity).unwrap(), variation);
+            assert_eq!(
+                variation.value,
+                Value::String(format!("value-{}", variation.name))
+            );
+            names.insert(variation.name);
+        }
+        assert_eq!(names, HashSet::from(["a".to_string(), "b".to_string()]));
+
+        let f2 = configuration.get_feature("f2").unwrap();
+        let variation = f2
+            .get_variation(&crate::entity::tests::TrivialEntity)
+            .unwrap();
+        assert_eq!(
+            variation,
+            crate::Variation {
+                name: "disabled".into(),
+                value: Value::String("off".into())
+            }
+        );
+
+        let mut json = json;
+        json["environments"][0]["features"][1] = feature("f3", true, 42.into());
+        let err = Configuration::from_json_str(&json.to_string(), "dev", "c1").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid value of variation 'b' in resource 'f3': 42 is not a STRING value"
+        );
+
+        // Features of experiments that are not running have no variations
+        json["environments"][0]["features"][1] = feature("f3", true, "value-b".into());
+        json["environments"][0]["features"][1]["experiment"]["experiment_status"] = "PAUSED".into();
+        let configuration = Configuration::from_json_str(&json.to_string(), "dev", "c1").unwrap();
+        let f3 = configuration.get_feature("f3").unwrap();
+        assert_eq!(
+            f3.get_variation(&crate::entity::tests::TrivialEntity)
+                .unwrap()
+                .name,
+            "enabled"
+        );
+
+        // Unsupported distributions and percentages not adding up to 100 are invalid
+        for (change, reason) in [
+            (
+                serde_json::json!({"type": "SEGMENT"}),
+                "unsupported distribution type 'SEGMENT'",
+            ),
+            (
+                serde_json::json!({"control_group": {"variation_id": "a", "rollout_percentage": 40}}),
+                "the percentages add up to 90, not 100",
+            ),
+            (
+                serde_json::json!({"control_group": {"variation_id": "a", "rollout_percentage": u32::MAX}}),
+                "the percentages overflow",
+            ),
+            (
+                serde_json::json!({"control_group": {"variation_id": "c", "rollout_percentage": 50}}),
+                "unknown variation 'c'",
+            ),
+        ] {
+            let mut json = json.clone();
+            json["environments"][0]["features"][1] = feature("f3", true, "value-b".into());
+            let traffic =
+                &mut json["environments"][0]["features"][1]["experiment"]["traffic_distribution"];
+            for (key, value) in change.as_object().unwrap() {
+                traffic[key] = value.clone();
+            }
+            let err = Configuration::from_json_str(&json.to_string(), "dev", "c1").unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!(
+                    "Invalid traffic distribution of experiment 'e1' in resource 'f3': {reason}"
+                )
+            );
+        }
+    }
+
+    #[rstest]
+    fn test_unknown_segment_rule_operator(example_configuration_enterprise_path: PathBuf) {
+        let content = std::fs::File::open(example_configuration_enterprise_path)
+            .expect("file should open read only");
+        let mut config_json: ConfigurationJson =
+            serde_json::from_reader(content).expect("Error parsing JSON into Configuration");
+        config_json.segments[0].rules[0].operator = "between".into();
+        let segment_id = config_json.segments[0].segment_id.clone();
+
+        let result = Configuration::new("dev", "blue-charge", config_json);
+        assert!(matches!(
+            result.unwrap_err(),
+            ConfigurationDataError::UnknownSegmentRuleOperator { segment_id: ref id, ref operator }
+                if *id == segment_id && operator == "between"
+        ));
+    }
+
+    #[rstest]
+    fn test_missing_segment_validation_mode(example_configuration_enterprise_path: PathBuf) {
+        let mut config_json =
+            ConfigurationJson::new(&example_configuration_enterprise_path).unwrap();
+        config_json
+            .segments
+            .retain(|segment| segment.segment_id != "l2dfo8do");
+
+        let result = Configuration::new("dev", "blue-charge", config_json.clone());
+        assert!(matches!(
+            result.unwrap_err(),
+            ConfigurationDataError::MissingSegments(ref id) if id == "f1"
+        ));
+
+        let configuration = Configuration::new_with_validation_mode(
+            "dev",
+            "blue-charge",
+            config_json,
+            ValidationMode::Lenient,
+        )
+        .unwrap();
+        let mut features = configuration.get_feature_ids_refs();
+        features.sort();
+        assert_eq!(features, ["f2", "f3", "f4", "f6"]);
+        let mut properties = configuration.get_property_ids_refs();
+        properties.sort();
+        assert_eq!(properties, ["p2", "p4"]);
+
+        let skipped = &configuration.validation_report().skipped;
+        assert_eq!(
+            skipped
+                .iter()
+                .map(|skipped| (skipped.kind, skipped.id.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                (ResourceKind::Feature, "f1"),
+                (ResourceKind::Property, "p1")
+            ]
+        );
+        assert_eq!(skipped[0].reason, "Missing segments for resource 'f1'");
+    }
+
+    #[rstest]
+    fn test_unknown_segment_rule_operator_lenient(example_configuration_enterprise_path: PathBuf) {
+        let mut config_json =
+            ConfigurationJson::new(&example_configuration_enterprise_path).unwrap();
+        config_json.segments[0].rules[0].operator = "between".into();
+        let segment_id = config_json.segments[0].segment_id.clone();
+
+        let configuration = Configuration::new_with_validation_mode(
+            "dev",
+            "blue-charge",
+            config_json,
+            ValidationMode::Lenient,
+        )
+        .unwrap();
+        assert!(!configuration.segments[&segment_id].supported);
+        // Only the segment is skipped, the features and properties targeting
+        // it are kept
+        let skipped = &configuration.validation_report().skipped;
+        assert_eq!(
+            skipped
+                .iter()
+                .map(|skipped| (skipped.kind, skipped.id.as_str()))
+                .collect::<Vec<_>>(),
+            [(ResourceKind::Segment, segment_id.as_str())]
+        );
+        assert!(configuration.features.contains_key("f1"));
+        assert!(configuration.properties.contains_key("p1"));
+    }
+
+    #[rstest]
+    fn test_from_json_str(example_configuration_enterprise_path: PathBuf) {
+        let json = std::fs::read_to_string(example_configuration_enterprise_path).unwrap();
+
+        let configuration = Configuration::from_json_str(&json, "dev", "blue-charge").unwrap();
+        assert_eq!(configuration.features.len(), 5);
+
+        let result = Configuration::from_json_str("{", "dev", "blue-charge");
+        assert!(matches!(
+            result.unwrap_err(),
+            crate::Error::DeserializationError(_)
+        ));
+    }
+
+    #[rstest]
+    fn test_diff(example_configuration_enterprise_path: PathBuf) {
+        let before =
+            Configuration::from_file(&example_configuration_enterprise_path, "dev", "blue-charge")
+                .unwrap();
+        assert!(before.diff(&before.clone()).is_empty());
+
+        let mut after = before.clone();
+        let (feature, _) = after.features.get_mut("f1").unwrap();
+        feature.enabled = !feature.enabled;
+        after.properties.remove("p1").unwrap();
+        let mut segment = after.segments.values().next().unwrap().segment.clone();
+        segment.segment_id = "new-segment".into();
+        after
+            .segments
+            .insert("new-segment".into(), Arc::new(segment.try_into().unwrap()));
+
+        let diff = before.diff(&after);
+        assert!(matches!(
+            &diff.features[..],
+            [ResourceChange::Changed { id, before, after }]
+                if id == "f1" && before["enabled"] != after["enabled"]
+        ));
+        assert!(matches!(
+            &diff.properties[..],
+            [ResourceChange::Removed { id, before }]
+                if id == "p1" && before["property_id"] == "p1"
+        ));
+        assert!(matches!(
+            &diff.segments[..],
+            [ResourceChange::Added { id, after }]
+                if id == "new-segment" && after["segment_id"] == "new-segment"
+        ));
+
+        // Reversing the comparison swaps additions and removals
+        let reversed = after.diff(&before);
+        assert_eq!(reversed.properties[0].id(), "p1");
+        assert!(matches!(
+            reversed.properties[0],
+            ResourceChange::Added { .. }
+        ));
         assert!(matches!(
-                result.unwrap_err(),
-                 ConfigurationDataError::EnvironmentNotFound(ref environment_id) if environment_id == "does_for_sure_not_exist"));
+            reversed.segments[0],
+            ResourceChange::Removed { .. }
+        ));
     }
 }
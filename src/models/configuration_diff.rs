@@ -0,0 +1,98 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeSet, HashMap};
+
+use serde::Serialize;
+
+/// Differences between two [`Configuration`](crate::Configuration)s, as returned
+/// by [`Configuration::diff`](crate::Configuration::diff).
+///
+/// Changes are sorted by resource ID.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigurationDiff {
+    pub features: Vec<ResourceChange>,
+    pub properties: Vec<ResourceChange>,
+    pub segments: Vec<ResourceChange>,
+}
+
+impl ConfigurationDiff {
+    /// Returns `true` if both configurations contain the same features,
+    /// properties and segments.
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty() && self.properties.is_empty() && self.segments.is_empty()
+    }
+}
+
+/// A feature, property or segment that differs between two configurations.
+///
+/// Values are the resource definitions in the JSON format used by the
+/// App Configuration service.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResourceChange {
+    Added {
+        id: String,
+        after: serde_json::Value,
+    },
+    Removed {
+        id: String,
+        before: serde_json::Value,
+    },
+    Changed {
+        id: String,
+        before: serde_json::Value,
+        after: serde_json::Value,
+    },
+}
+
+impl ResourceChange {
+    /// ID of the feature, property or segment.
+    pub fn id(&self) -> &str {
+        match self {
+            ResourceChange::Added { id, .. }
+            | ResourceChange::Removed { id, .. }
+            | ResourceChange::Changed { id, .. } => id,
+        }
+    }
+}
+
+/// Lists the changes between the resources in `before` and `after`, indexed by ID.
+pub(crate) fn diff_resources<V, R: Serialize + PartialEq>(
+    before: &HashMap<String, V>,
+    after: &HashMap<String, V>,
+    resource: impl Fn(&V) -> &R,
+) -> Vec<ResourceChange> {
+    let to_json = |value: &V| serde_json::to_value(resource(value)).unwrap_or_default();
+    let ids: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    ids.into_iter()
+        .filter_map(|id| match (before.get(id), after.get(id)) {
+            (None, Some(new)) => Some(ResourceChange::Added {
+                id: id.clone(),
+                after: to_json(new),
+            }),
+            (Some(old), None) => Some(ResourceChange::Removed {
+                id: id.clone(),
+                before: to_json(old),
+            }),
+            (Some(old), Some(new)) if resource(old) != resource(new) => {
+                Some(ResourceChange::Changed {
+                    id: id.clone(),
+                    before: to_json(old),
+                    after: to_json(new),
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,50 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// Where the data of a [`Configuration`](crate::Configuration) comes from.
+///
+/// Feature and property snapshots keep the source of the configuration they
+/// were taken from. It tells whether a value was served from the server, from
+/// a cache that may be stale or from the data the client falls back to while
+/// offline.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConfigurationSource {
+    /// Downloaded from the server by this client.
+    Live,
+    /// Downloaded by another client and read from the shared cache.
+    SharedCache,
+    /// Read from the persistent cache file.
+    PersistentCache,
+    /// Read from the bootstrap file.
+    Bootstrap,
+    /// The fallback data of [`OfflineMode::FallbackData`](crate::OfflineMode::FallbackData).
+    Fallback,
+    /// Provided by the application, e.g. to an offline client.
+    #[default]
+    Local,
+}
+
+impl std::fmt::Display for ConfigurationSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let source = match self {
+            Self::Live => "live",
+            Self::SharedCache => "shared cache",
+            Self::PersistentCache => "persistent cache",
+            Self::Bootstrap => "bootstrap",
+            Self::Fallback => "fallback",
+            Self::Local => "local",
+        };
+        f.write_str(source)
+    }
+}
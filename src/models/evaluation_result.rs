@@ -15,7 +15,8 @@
 use crate::Value;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct EvaluationRuleContext {
     pub order: u32,
     pub rollout_percentage: Option<u32>,
@@ -23,14 +24,16 @@ pub struct EvaluationRuleContext {
     pub targeted_segment_ids: Vec<Vec<String>>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct EvaluationRuleCondition {
     pub attribute_name: String,
     pub operator: String,
     pub values: Vec<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct EvaluationSegmentContext {
     pub segment_id: String,
     pub name: String,
@@ -39,7 +42,8 @@ pub struct EvaluationSegmentContext {
     pub rules: Vec<EvaluationRuleCondition>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct EvaluationContext {
     pub matched_segment: Option<EvaluationSegmentContext>,
     pub matched_rule: Option<EvaluationRuleContext>,
@@ -68,8 +72,17 @@ pub struct PropertyEvaluationDetails {
     pub segment_name: Option<String>,
 }
 
+/// Variation of a feature served to an entity, returned by
+/// `FeatureSnapshot::get_variation`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Variation {
+    pub name: String,
+    pub value: Value,
+}
+
 /// Returns: { value, isEnabled, details }
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct FeatureEvaluationResult {
     pub value: Value,
     pub is_enabled: bool,
@@ -77,8 +90,45 @@ pub struct FeatureEvaluationResult {
 }
 
 /// Returns: { value, details }
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct PropertyEvaluationResult {
     pub value: Value,
     pub details: PropertyEvaluationDetails,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feature_evaluation_result_json() {
+        let result = FeatureEvaluationResult {
+            value: Value::Int64(5),
+            is_enabled: true,
+            details: FeatureEvaluationDetails {
+                value_type: "NUMERIC".into(),
+                reason: "TARGETING_MATCH".into(),
+                segment_name: Some("beta".into()),
+                rollout_percentage_applied: None,
+            },
+        };
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "value": 5,
+                "isEnabled": true,
+                "details": {
+                    "valueType": "NUMERIC",
+                    "reason": "TARGETING_MATCH",
+                    "segmentName": "beta",
+                }
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<FeatureEvaluationResult>(json).unwrap(),
+            result
+        );
+    }
+}
@@ -12,18 +12,27 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::ConfigurationSource;
+use crate::client::DeprecatedFeatures;
 use crate::entity::Entity;
-use crate::errors::Result;
-use crate::metering::{MeteringRecorderSender, MeteringSubject};
+use crate::errors::{EvaluationError, Result};
+use crate::metering::{EvaluationBatch, MeteringRecorderSender, MeteringSubject, ValueSource};
+use crate::models::interpolation::interpolate_value;
 use crate::models::{ROLLOUT_TYPE_PROGRESSIVE, RolloutConfiguration};
+use crate::network::serialization::{Experiment, ValueType};
 use crate::segment_evaluation::TargetingRules;
-use crate::utils::{get_current_rollout_percentage, parse_rollout_configuration_phases};
+use crate::utils::{
+    Clock, SystemClock, get_current_rollout_percentage, now_millis,
+    parse_rollout_configuration_phases,
+};
 use crate::value::Value;
-use crate::{Feature, FeatureEvaluationDetails, FeatureEvaluationResult};
-use chrono::Utc;
+use crate::{Feature, FeatureEvaluationDetails, FeatureEvaluationResult, Variation};
 use murmur3::murmur3_32;
+use std::cell::OnceCell;
 use std::collections::BTreeMap;
 use std::io::Cursor;
+use std::panic::Location;
+use std::sync::Arc;
 
 /// Provides a snapshot of a [`Feature`].
 #[derive(Debug)]
@@ -36,15 +45,132 @@ pub struct FeatureSnapshot {
     rollout_configuration: Option<RolloutConfiguration>,
     /// BTreeMap for feature-level progressive rollout (timestamp -> percentage)
     rollout_btree: Option<BTreeMap<i64, u32>>,
-    pub(crate) name: String,
-    pub(crate) feature_id: String,
+    pub(crate) name: Arc<str>,
+    pub(crate) feature_id: Arc<str>,
     r#type: String,
     format: Option<String>,
+    description: Option<String>,
+    tags: Option<String>,
     segment_rules: TargetingRules,
     pub(crate) metering: Option<MeteringRecorderSender>,
+    /// Set if the feature is planned for removal, to report its evaluations.
+    pub(crate) deprecation: Option<Arc<DeprecatedFeatures>>,
+    interpolate_values: bool,
+    /// Features that must be enabled for the same entity for this one to be enabled.
+    prerequisites: Vec<FeatureSnapshot>,
+    clock: Arc<dyn Clock>,
+    /// Value forced locally, returned for every entity.
+    local_override: Option<Value>,
+    /// Whether `local_override` holds a default registered by the application
+    /// for a feature missing from the configuration.
+    registered_default: bool,
+    /// Appended to the rollout hash input, see [`FeatureSnapshot::with_rollout_salt`].
+    rollout_salt: Option<Arc<str>>,
+    /// Values of a multivariate feature, see [`FeatureSnapshot::get_variation`].
+    variations: Option<Variations>,
+    source: ConfigurationSource,
+}
+
+/// Variations of a multivariate feature and the share of entities served each one.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Variations {
+    /// Appended to the hash input, so a new iteration reassigns the entities.
+    key: String,
+    values: Vec<(String, Value)>,
+    /// Variation IDs and their percentage of entities, control group first.
+    traffic: Vec<(String, u32)>,
+}
+
+/// Status of the experiments whose variations are served, the features of
+/// the others are evaluated as if they had no variations.
+const EXPERIMENT_STATUS_RUNNING: &str = "RUNNING";
+
+/// Traffic distribution splitting the entities by percentage, the only one
+/// supported.
+const TRAFFIC_DISTRIBUTION_DEFAULT: &str = "DEFAULT";
+
+impl Variations {
+    /// Checks that the traffic distribution of `experiment` is supported, that
+    /// its percentages add up to 100 and that it only refers to variations of
+    /// the experiment. Returns the reason otherwise.
+    pub(crate) fn check(experiment: &Experiment) -> std::result::Result<(), String> {
+        let distribution = &experiment.traffic_distribution;
+        if distribution.r#type != TRAFFIC_DISTRIBUTION_DEFAULT {
+            return Err(format!(
+                "unsupported distribution type '{}'",
+                distribution.r#type
+            ));
+        }
+        let groups =
+            std::iter::once(&distribution.control_group).chain(&distribution.experimental_group);
+        let mut total: u32 = 0;
+        for group in groups {
+            if !experiment
+                .variations
+                .iter()
+                .any(|variation| variation.variation_id == group.variation_id)
+            {
+                return Err(format!("unknown variation '{}'", group.variation_id));
+            }
+            total = total
+                .checked_add(group.rollout_percentage)
+                .ok_or("the percentages overflow")?;
+        }
+        if total != 100 {
+            return Err(format!("the percentages add up to {total}, not 100"));
+        }
+        Ok(())
+    }
+
+    /// Returns the variations of `experiment`, `None` if it is not running.
+    pub(crate) fn from_experiment(
+        experiment: &Experiment,
+        value_type: ValueType,
+    ) -> Result<Option<Self>> {
+        if experiment.experiment_status != EXPERIMENT_STATUS_RUNNING {
+            return Ok(None);
+        }
+        let values = experiment
+            .variations
+            .iter()
+            .map(|variation| {
+                let value = (value_type, variation.variation_value.clone()).try_into()?;
+                Ok((variation.variation_id.clone(), value))
+            })
+            .collect::<Result<_>>()?;
+        let distribution = &experiment.traffic_distribution;
+        let traffic = std::iter::once(&distribution.control_group)
+            .chain(&distribution.experimental_group)
+            .map(|group| (group.variation_id.clone(), group.rollout_percentage))
+            .collect();
+        Ok(Some(Self {
+            key: experiment.iteration.iteration_key.clone(),
+            values,
+            traffic,
+        }))
+    }
+
+    /// Returns the variation of the bucket of the entity, from 0 to 99.
+    fn assign(&self, entity_id: &str, feature_id: &str) -> Option<Variation> {
+        // The hash of u32::MAX scales to 100, it belongs to the last bucket
+        let bucket =
+            FeatureSnapshot::normalized_hash(&format!("{entity_id}:{feature_id}:{}", self.key))
+                .min(99);
+        let mut upper: u32 = 0;
+        let (variation_id, _) = self.traffic.iter().find(|(_, percentage)| {
+            upper = upper.saturating_add(*percentage);
+            bucket < upper
+        })?;
+        let (name, value) = self.values.iter().find(|(id, _)| id == variation_id)?;
+        Some(Variation {
+            name: name.clone(),
+            value: value.clone(),
+        })
+    }
 }
 
 impl FeatureSnapshot {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         enabled: bool,
         enabled_value: Value,
@@ -52,8 +178,8 @@ impl FeatureSnapshot {
         rollout_percentage: u32,
         rollout_type: Option<String>,
         rollout_configuration: Option<RolloutConfiguration>,
-        name: &str,
-        feature_id: &str,
+        name: impl Into<Arc<str>>,
+        feature_id: impl Into<Arc<str>>,
         r#type: String,
         format: Option<String>,
         segment_rules: TargetingRules,
@@ -75,21 +201,348 @@ impl FeatureSnapshot {
             rollout_type,
             rollout_configuration,
             rollout_btree,
-            name: name.to_string(),
-            feature_id: feature_id.to_string(),
+            name: name.into(),
+            feature_id: feature_id.into(),
             r#type,
             format,
+            description: None,
+            tags: None,
             segment_rules,
             metering,
+            deprecation: None,
+            interpolate_values: false,
+            prerequisites: Vec::new(),
+            clock: Arc::new(SystemClock),
+            local_override: None,
+            registered_default: false,
+            rollout_salt: None,
+            variations: None,
+            source: ConfigurationSource::default(),
+        }
+    }
+
+    /// Serves `variations` to the entities the feature is enabled for.
+    pub(crate) fn with_variations(mut self, variations: Option<Variations>) -> Self {
+        self.variations = variations;
+        self
+    }
+
+    /// Evaluates the feature for `entity` and returns the variation it gets.
+    ///
+    /// Multivariate features split the entities they are enabled for among
+    /// the variations of their running experiment, with the percentages of
+    /// the traffic distribution. The
+    /// bucket of an entity is given by the murmur3 hash of
+    /// `<entity_id>:<feature_id>:<iteration_key>`, so it keeps getting the same
+    /// variation until a new iteration starts.
+    ///
+    /// Other features, entities the feature is disabled for and features
+    /// overridden locally get the `enabled` or `disabled` variation, with the
+    /// value of [`get_current_value`](Feature::get_current_value).
+    #[track_caller]
+    pub fn get_variation(&self, entity: &(impl Entity + ?Sized)) -> Result<Variation> {
+        let result = self.get_current_value(entity)?;
+        if result.is_enabled
+            && self.local_override.is_none()
+            && let Some(variations) = &self.variations
+            && let Some(mut variation) = variations.assign(&entity.get_id(), &self.feature_id)
+        {
+            if self.interpolate_values {
+                variation.value = interpolate_value(variation.value, entity);
+            }
+            return Ok(variation);
         }
+        let name = if result.is_enabled {
+            "enabled"
+        } else {
+            "disabled"
+        };
+        Ok(Variation {
+            name: name.to_string(),
+            value: result.value,
+        })
+    }
+
+    /// Attaches the description and tags set for the feature in the configuration.
+    pub(crate) fn with_metadata(
+        mut self,
+        description: Option<String>,
+        tags: Option<String>,
+    ) -> Self {
+        self.description = description.filter(|description| !description.is_empty());
+        self.tags = tags.filter(|tags| !tags.is_empty());
+        self
+    }
+
+    /// Records where the configuration the feature was taken from comes from.
+    pub(crate) fn with_source(mut self, source: ConfigurationSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Returns where the configuration the feature was taken from comes from,
+    /// e.g. to tell a value served from a stale cache from a live one.
+    pub fn source(&self) -> ConfigurationSource {
+        self.source
+    }
+
+    /// Returns the description of the feature, if there is one.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Returns the tags of the feature as written in the configuration, a comma-separated
+    /// list, if there are any.
+    pub fn tags(&self) -> Option<&str> {
+        self.tags.as_deref()
+    }
+
+    /// Returns the type of the feature values: `BOOLEAN`, `NUMERIC`, `STRING` or `SECRETREF`.
+    pub fn value_type(&self) -> &str {
+        &self.r#type
+    }
+
+    /// Returns the format of the feature values. String values without a format are
+    /// `TEXT`, other types have none.
+    pub fn format(&self) -> Option<&str> {
+        // If the Format is null or undefined for a String type, we default it to TEXT
+        if self.format.is_none() && self.r#type == "STRING" {
+            return Some("TEXT");
+        }
+        self.format.as_deref()
+    }
+
+    /// Enables substitution of `${entity.id}` and `${attr:<name>}` placeholders in
+    /// String values with data from the evaluated [`Entity`].
+    pub fn with_value_interpolation(mut self, enabled: bool) -> Self {
+        self.interpolate_values = enabled;
+        self
+    }
+
+    /// Uses `clock` instead of the system clock to resolve time-based targeting.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.prerequisites = std::mem::take(&mut self.prerequisites)
+            .into_iter()
+            .map(|prerequisite| prerequisite.with_clock(clock.clone()))
+            .collect();
+        self.clock = clock;
+        self
+    }
+
+    /// Adds `salt` to the input of the hash that assigns entities to rollout buckets.
+    ///
+    /// An entity is in the rollout if the murmur3 hash (32 bits, seed 0) of
+    /// `<entity_id>:<feature_id>`, scaled to `0..100`, is lower than the rollout
+    /// percentage. For progressive rollouts the start date is appended to the
+    /// entity ID. This is the input used by all the App Configuration SDKs, so an
+    /// entity gets the same value in all of them.
+    ///
+    /// With a salt the input becomes `<entity_id>:<feature_id>:<salt>`, so two
+    /// applications using the same feature ID for different purposes do not
+    /// roll out to the same entities. The buckets no longer match the ones of
+    /// other SDKs.
+    pub fn with_rollout_salt(mut self, salt: impl Into<Arc<str>>) -> Self {
+        let salt = salt.into();
+        self.prerequisites = std::mem::take(&mut self.prerequisites)
+            .into_iter()
+            .map(|prerequisite| prerequisite.with_rollout_salt(salt.clone()))
+            .collect();
+        self.rollout_salt = Some(salt);
+        self
+    }
+
+    /// Gates the evaluation of this feature on the given prerequisite features.
+    ///
+    /// Only the evaluation of this feature is metered: the application never
+    /// asked for the prerequisites.
+    #[cfg(feature = "remote")]
+    pub(crate) fn with_prerequisites(mut self, prerequisites: Vec<FeatureSnapshot>) -> Self {
+        self.prerequisites = prerequisites
+            .into_iter()
+            .map(|prerequisite| FeatureSnapshot {
+                metering: None,
+                ..prerequisite
+            })
+            .collect();
+        self
+    }
+
+    /// Returns a feature that is not part of the configuration and evaluates to
+    /// `value`, a default registered by the application, for every entity.
+    #[cfg(feature = "remote")]
+    pub(crate) fn registered_default(feature_id: &str, value: Value) -> Self {
+        fn value_type(value: &Value) -> ValueType {
+            match value {
+                Value::Boolean(_) => ValueType::Boolean,
+                Value::String(_) => ValueType::String,
+                Value::Float64(_) | Value::UInt64(_) | Value::Int64(_) => ValueType::Numeric,
+                // Lists have the type of their items, unknown if they have
+                // none or items of several types
+                Value::List(items) => {
+                    let mut types = items.iter().map(value_type);
+                    match types.next() {
+                        Some(first) if types.all(|item_type| item_type == first) => first,
+                        _ => ValueType::Unknown,
+                    }
+                }
+            }
+        }
+        let r#type = value_type(&value);
+        let mut feature = Self::new(
+            value != Value::Boolean(false),
+            value.clone(),
+            value.clone(),
+            100,
+            None,
+            None,
+            feature_id,
+            feature_id,
+            r#type.to_string(),
+            None,
+            TargetingRules::new(std::collections::HashMap::new(), Vec::new(), r#type, None),
+            None,
+        );
+        feature.local_override = Some(value);
+        feature.registered_default = true;
+        feature
+    }
+
+    /// Evaluates the feature to `value`, if any, for every entity, ignoring the
+    /// configuration and the registered default.
+    ///
+    /// Fails if `value` is not of the type of the feature.
+    pub(crate) fn with_local_override(mut self, value: Option<Value>) -> Result<Self> {
+        let Some(value) = value else {
+            return Ok(self);
+        };
+        if !ValueType::from(self.r#type.as_str()).accepts(&value) {
+            return Err(crate::Error::LocalOverrideTypeMismatch {
+                id: self.feature_id.to_string(),
+                expected: self.r#type.clone(),
+                found: value,
+            });
+        }
+        self.local_override = Some(value);
+        self.registered_default = false;
+        Ok(self)
+    }
+
+    /// Evaluates a `BOOLEAN` feature for the given entity.
+    ///
+    /// Equivalent to `get_value_into::<bool>(entity)`, but features without targeting
+    /// rules, prerequisites or progressive rollout are resolved without building the
+    /// evaluation details or cloning any [`Value`]. It still allocates to read the
+    /// entity ID for partial rollouts and to record the evaluation for usage
+    /// metering. Returns [`crate::Error::ValueConversionError`] for features of any
+    /// other type.
+    #[track_caller]
+    pub fn get_bool_value(&self, entity: &(impl Entity + ?Sized)) -> Result<bool> {
+        let (Value::Boolean(enabled_value), Value::Boolean(disabled_value)) =
+            (&self.enabled_value, &self.disabled_value)
+        else {
+            return Err(crate::Error::ValueConversionError {
+                expected: "bool",
+                found: self.enabled_value.clone(),
+            });
+        };
+        self.record_deprecated_evaluation();
+        if !self.segment_rules.is_empty()
+            || !self.prerequisites.is_empty()
+            || self.rollout_btree.is_some()
+            || self.local_override.is_some()
+        {
+            return self.current_value(entity)?.value.try_into();
+        }
+
+        let is_enabled = self.enabled
+            && (self.rollout_percentage == 100
+                || Self::should_rollout_with_id(
+                    self.rollout_percentage,
+                    &entity.get_id(),
+                    &self.feature_id,
+                    self.rollout_salt.as_deref(),
+                ));
+        let value_source = if is_enabled {
+            ValueSource::EnabledValue
+        } else if self.enabled {
+            ValueSource::RolloutExcluded
+        } else {
+            ValueSource::DisabledValue
+        };
+        self.record_evaluation(entity, None, value_source, None);
+        Ok(if is_enabled {
+            *enabled_value
+        } else {
+            *disabled_value
+        })
+    }
+
+    /// Records that the application evaluated the feature, from the location
+    /// calling the public evaluation method, if it is planned for removal.
+    #[track_caller]
+    fn record_deprecated_evaluation(&self) {
+        if let Some(deprecation) = &self.deprecation {
+            deprecation.record(&self.feature_id, Location::caller());
+        }
+    }
+
+    /// [`Feature::get_current_value`], without recording a deprecated evaluation.
+    fn current_value(&self, entity: &(impl Entity + ?Sized)) -> Result<FeatureEvaluationResult> {
+        let (value, is_enabled, details) = self.evaluate_feature_for_entity(entity)?;
+        let value = if self.interpolate_values {
+            interpolate_value(value, entity)
+        } else {
+            value
+        };
+        Ok(FeatureEvaluationResult {
+            value,
+            is_enabled,
+            details,
+        })
     }
 
     fn evaluate_feature_for_entity(
         &self,
-        entity: &impl Entity,
+        entity: &(impl Entity + ?Sized),
     ) -> Result<(Value, bool, FeatureEvaluationDetails)> {
+        self.evaluate_feature_for_entity_at(entity, &OnceCell::new(), None)
+            .map_err(|e| EvaluationError::wrap("feature", &self.feature_id, entity, e))
+    }
+
+    /// Evaluates the feature at the time in `now_ms`, read from the clock the
+    /// first time it is needed, so several evaluations can share it. The
+    /// evaluation is metered in `batch` if it is part of one.
+    fn evaluate_feature_for_entity_at(
+        &self,
+        entity: &(impl Entity + ?Sized),
+        now_ms: &OnceCell<i64>,
+        batch: Option<&EvaluationBatch>,
+    ) -> Result<(Value, bool, FeatureEvaluationDetails)> {
+        if let Some(value) = &self.local_override {
+            // Overriding a boolean feature with `false` turns it off
+            let is_enabled = *value != Value::Boolean(false);
+            let (value_type, reason) = if self.registered_default {
+                (
+                    "REGISTERED_DEFAULT",
+                    "Feature not found in the configuration. Returning registered default.",
+                )
+            } else {
+                ("LOCAL_OVERRIDE", "Feature value is overridden locally.")
+            };
+            return Ok((
+                value.clone(),
+                is_enabled,
+                FeatureEvaluationDetails {
+                    value_type: value_type.to_string(),
+                    reason: reason.to_string(),
+                    segment_name: None,
+                    rollout_percentage_applied: None,
+                },
+            ));
+        }
+
         if !self.enabled {
-            self.record_evaluation(entity, None);
+            self.record_evaluation(entity, None, ValueSource::DisabledValue, batch);
             return Ok((
                 self.disabled_value.clone(),
                 false,
@@ -102,22 +555,47 @@ impl FeatureSnapshot {
             ));
         }
 
+        for prerequisite in &self.prerequisites {
+            let (_, prerequisite_enabled, _) =
+                prerequisite.evaluate_feature_for_entity_at(entity, now_ms, batch)?;
+            if !prerequisite_enabled {
+                self.record_evaluation(entity, None, ValueSource::DisabledValue, batch);
+                return Ok((
+                    self.disabled_value.clone(),
+                    false,
+                    FeatureEvaluationDetails {
+                        value_type: "DISABLED_VALUE".to_string(),
+                        reason: format!(
+                            "Prerequisite feature '{}' is not enabled for entity. Returning disabled value.",
+                            prerequisite.feature_id
+                        ),
+                        segment_name: None,
+                        rollout_percentage_applied: None,
+                    },
+                ));
+            }
+        }
+
+        // Only read the clock when needed: there is no system clock on some
+        // targets (e.g. wasm32-unknown-unknown).
+        let now_ms = if self.rollout_btree.is_some() || self.segment_rules.is_time_dependent() {
+            *now_ms.get_or_init(|| now_millis(self.clock.as_ref()))
+        } else {
+            0
+        };
         let (segment_rule, segment) = {
-            if self.segment_rules.is_empty() || entity.get_attributes().is_empty() {
-                // TODO: this makes only sense if there can be a rule which matches
-                //       even on empty attributes
-                // No match possible. Do not consider segment rules:
+            if self.segment_rules.is_empty() {
                 (None, None)
             } else {
                 self.segment_rules
-                    .find_applicable_targeting_rule_and_segment_for_entity(entity)?
+                    .find_applicable_targeting_rule_and_segment_for_entity(entity, now_ms)?
                     .unzip()
             }
         };
+        let segment = segment.flatten();
+        let matched_rule = segment_rule.is_some();
 
-        self.record_evaluation(entity, segment);
-
-        match segment_rule {
+        let (value, is_enabled, details) = match segment_rule {
             Some(segment_rule) => {
                 // Get rollout percentage
                 let rollout_percentage =
@@ -132,9 +610,10 @@ impl FeatureSnapshot {
                     rollout_percentage,
                     &entity_id_for_hash,
                     &self.feature_id,
+                    self.rollout_salt.as_deref(),
                 ) {
                     let value = segment_rule.value(&self.enabled_value)?;
-                    Ok((
+                    (
                         value,
                         true,
                         FeatureEvaluationDetails {
@@ -147,9 +626,9 @@ impl FeatureSnapshot {
                             segment_name,
                             rollout_percentage_applied: Some(true),
                         },
-                    ))
+                    )
                 } else {
-                    Ok((
+                    (
                         self.disabled_value.clone(),
                         false,
                         FeatureEvaluationDetails {
@@ -162,17 +641,18 @@ impl FeatureSnapshot {
                             segment_name,
                             rollout_percentage_applied: Some(false),
                         },
-                    ))
+                    )
                 }
             }
             None => {
                 let (effective_percentage, entity_id_for_hash) =
-                    self.get_feature_rollout_percentage_and_entity_id(entity);
+                    self.get_feature_rollout_percentage_and_entity_id(entity, now_ms);
 
                 let is_enabled = Self::should_rollout_with_id(
                     effective_percentage,
                     &entity_id_for_hash,
                     &self.feature_id,
+                    self.rollout_salt.as_deref(),
                 );
                 let value = if is_enabled {
                     self.enabled_value.clone()
@@ -200,7 +680,7 @@ impl FeatureSnapshot {
                     )
                 };
 
-                Ok((
+                (
                     value,
                     is_enabled,
                     FeatureEvaluationDetails {
@@ -209,50 +689,113 @@ impl FeatureSnapshot {
                         segment_name: None,
                         rollout_percentage_applied,
                     },
-                ))
+                )
             }
-        }
+        };
+
+        let value_source = match (is_enabled, matched_rule) {
+            (true, true) => ValueSource::SegmentValue,
+            (true, false) => ValueSource::EnabledValue,
+            (false, _) => ValueSource::RolloutExcluded,
+        };
+        self.record_evaluation(entity, segment, value_source, batch);
+        Ok((value, is_enabled, details))
     }
 
-    fn normalized_hash(data: &str) -> u32 {
-        let hash = murmur3_32(&mut Cursor::new(data), 0).expect("Cannot hash the value.");
+    pub(crate) fn normalized_hash(data: &str) -> u32 {
+        // Reading from memory cannot fail, there is no I/O error to report.
+        let hash = murmur3_32(&mut Cursor::new(data), 0).unwrap_or_default();
         (f64::from(hash) / f64::from(u32::MAX) * 100.0) as u32
     }
 
-    fn should_rollout_with_id(rollout_percentage: u32, entity_id: &str, feature_id: &str) -> bool {
-        let tag = format!("{}:{}", entity_id, feature_id);
-        rollout_percentage == 100 || Self::normalized_hash(&tag) < rollout_percentage
+    /// Returns the bucket of `entity_id` in the rollouts of `feature_id`, from 0 to 100.
+    fn rollout_bucket(entity_id: &str, feature_id: &str, salt: Option<&str>) -> u32 {
+        match salt {
+            Some(salt) => Self::normalized_hash(&format!("{}:{}:{}", entity_id, feature_id, salt)),
+            None => Self::normalized_hash(&format!("{}:{}", entity_id, feature_id)),
+        }
     }
 
-    fn get_feature_rollout_percentage_and_entity_id(&self, entity: &impl Entity) -> (u32, String) {
-        if self.rollout_type.as_deref() == Some(ROLLOUT_TYPE_PROGRESSIVE) {
-            if let Some(rollout_config) = &self.rollout_configuration {
-                if let Some(btree) = &self.rollout_btree {
-                    let current_time_ms = Utc::now().timestamp_millis();
-                    let current_percentage = get_current_rollout_percentage(btree, current_time_ms);
-                    // Append start_at to entity ID for stable bucket assignment
-                    let modified_entity_id =
-                        format!("{}{}", entity.get_id(), rollout_config.start_at);
-                    return (current_percentage, modified_entity_id);
-                }
-            }
+    fn should_rollout_with_id(
+        rollout_percentage: u32,
+        entity_id: &str,
+        feature_id: &str,
+        salt: Option<&str>,
+    ) -> bool {
+        rollout_percentage == 100
+            || Self::rollout_bucket(entity_id, feature_id, salt) < rollout_percentage
+    }
+
+    /// Returns how many entities of `sample` would be in a feature-level rollout
+    /// of each of `percentages`, e.g. to check how the entities are distributed
+    /// among the buckets before ramping the feature up.
+    ///
+    /// The entities are placed as in [`get_current_value`](Feature::get_current_value),
+    /// salt and progressive rollout start date included. Targeting rules and
+    /// the current rollout percentage are ignored, and no evaluation is metered.
+    pub fn rollout_preview(&self, percentages: &[u32], sample: &[&dyn Entity]) -> Vec<usize> {
+        let buckets: Vec<u32> = sample
+            .iter()
+            .map(|entity| {
+                Self::rollout_bucket(
+                    &self.entity_id_for_hash(*entity),
+                    &self.feature_id,
+                    self.rollout_salt.as_deref(),
+                )
+            })
+            .collect();
+        percentages
+            .iter()
+            .map(|&percentage| {
+                buckets
+                    .iter()
+                    .filter(|&&bucket| percentage == 100 || bucket < percentage)
+                    .count()
+            })
+            .collect()
+    }
+
+    /// Returns the ID hashed to place `entity` in the feature-level rollout.
+    fn entity_id_for_hash(&self, entity: &(impl Entity + ?Sized)) -> String {
+        if self.rollout_type.as_deref() == Some(ROLLOUT_TYPE_PROGRESSIVE)
+            && let Some(rollout_config) = &self.rollout_configuration
+            && self.rollout_btree.is_some()
+        {
+            // Append start_at to entity ID for stable bucket assignment
+            return format!("{}{}", entity.get_id(), rollout_config.start_at);
         }
+        entity.get_id()
+    }
 
-        // Manual rollout — plain entity ID
-        (self.rollout_percentage, entity.get_id())
+    fn get_feature_rollout_percentage_and_entity_id(
+        &self,
+        entity: &(impl Entity + ?Sized),
+        now_ms: i64,
+    ) -> (u32, String) {
+        match &self.rollout_btree {
+            Some(btree) => (
+                get_current_rollout_percentage(btree, now_ms),
+                self.entity_id_for_hash(entity),
+            ),
+            // Manual rollout — plain entity ID
+            None => (self.rollout_percentage, entity.get_id()),
+        }
     }
 }
 
 impl Feature for FeatureSnapshot {
     fn get_feature_name(&self) -> Result<String> {
-        Ok(self.name.clone())
+        Ok(self.name.to_string())
     }
 
     fn is_enabled(&self) -> Result<bool> {
-        Ok(self.enabled)
+        Ok(match &self.local_override {
+            Some(value) => *value != Value::Boolean(false),
+            None => self.enabled,
+        })
     }
     fn get_feature_id(&self) -> Result<String> {
-        Ok(self.feature_id.clone())
+        Ok(self.feature_id.to_string())
     }
 
     fn get_feature_data_type(&self) -> Result<String> {
@@ -260,29 +803,49 @@ impl Feature for FeatureSnapshot {
     }
 
     fn get_feature_data_format(&self) -> Result<Option<String>> {
-        // If the Format is null or undefined for a String type, we default it to TEXT
-        if self.format.is_none() && self.r#type == "STRING" {
-            return Ok(Some("TEXT".to_string()));
-        }
-        Ok(self.format.clone())
+        Ok(self.format().map(str::to_string))
     }
 
-    fn get_current_value(&self, entity: &impl Entity) -> Result<FeatureEvaluationResult> {
-        let (value, is_enabled, details) = self.evaluate_feature_for_entity(entity)?;
-        Ok(FeatureEvaluationResult {
-            value,
-            is_enabled,
-            details,
-        })
+    #[track_caller]
+    fn get_current_value(
+        &self,
+        entity: &(impl Entity + ?Sized),
+    ) -> Result<FeatureEvaluationResult> {
+        self.record_deprecated_evaluation();
+        self.current_value(entity)
     }
 
+    #[track_caller]
     fn get_value_into<T: TryFrom<Value, Error = crate::Error>>(
         &self,
-        entity: &impl Entity,
+        entity: &(impl Entity + ?Sized),
     ) -> Result<T> {
         let value = self.get_current_value(entity).map(|r| r.value)?;
         value.try_into()
     }
+
+    /// Reads the clock once: all the entities are evaluated at the same time.
+    /// The evaluations are queued for usage metering together, instead of one
+    /// by one.
+    #[track_caller]
+    fn get_values_for(&self, entities: &[&dyn Entity]) -> Vec<Result<Value>> {
+        self.record_deprecated_evaluation();
+        let now_ms = OnceCell::new();
+        let batch = EvaluationBatch::new(self.metering.as_ref());
+        entities
+            .iter()
+            .map(|entity| {
+                let (value, _, _) = self
+                    .evaluate_feature_for_entity_at(*entity, &now_ms, Some(&batch))
+                    .map_err(|e| EvaluationError::wrap("feature", &self.feature_id, *entity, e))?;
+                Ok(if self.interpolate_values {
+                    interpolate_value(value, *entity)
+                } else {
+                    value
+                })
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -292,28 +855,207 @@ pub mod tests {
     use crate::feature::Feature;
     use crate::network::serialization::fixtures::{create_one_segment_rule, one_segment_rule};
     use crate::network::serialization::{Rule, Segment, SegmentRule, ValueType};
+    use crate::segment_evaluation::tests::compile_segments;
     use rstest::rstest;
     use std::collections::HashMap;
 
+    /// Values of the features built by [`feature`].
+    enum FeatureValues {
+        /// `-42` if enabled and `2` if disabled.
+        Numeric,
+        /// `true` if enabled and `false` if disabled.
+        Boolean,
+    }
+
+    /// Feature `feature_id` targeted with `segment_rules` over `segments`.
+    fn feature(
+        feature_id: &str,
+        values: FeatureValues,
+        enabled: bool,
+        rollout_percentage: u32,
+        segments: HashMap<String, Segment>,
+        segment_rules: Vec<SegmentRule>,
+    ) -> FeatureSnapshot {
+        let (value_type, enabled_value, disabled_value) = match values {
+            FeatureValues::Numeric => (ValueType::Numeric, Value::Int64(-42), Value::Int64(2)),
+            FeatureValues::Boolean => (
+                ValueType::Boolean,
+                Value::Boolean(true),
+                Value::Boolean(false),
+            ),
+        };
+        FeatureSnapshot::new(
+            enabled,
+            enabled_value,
+            disabled_value,
+            rollout_percentage,
+            None,
+            None,
+            feature_id,
+            feature_id,
+            value_type.to_string(),
+            None,
+            TargetingRules::new(compile_segments(segments), segment_rules, value_type, None),
+            None,
+        )
+    }
+
+    /// Segment `some_segment_id`, matching the entities named "heinz".
+    fn heinz_segments() -> HashMap<String, Segment> {
+        HashMap::from([(
+            "some_segment_id".into(),
+            Segment {
+                name: "".into(),
+                segment_id: "some_segment_id".into(),
+                description: None,
+                tags: None,
+                rules: vec![Rule {
+                    attribute_name: "name".into(),
+                    operator: "is".into(),
+                    values: vec!["heinz".into()],
+                }],
+            },
+        )])
+    }
+
     #[rstest]
     #[case("a1", false)]
     #[case("a2", true)]
     fn test_should_rollout(#[case] entity_id: &str, #[case] partial_rollout_expectation: bool) {
-        let result = FeatureSnapshot::should_rollout_with_id(100, entity_id, "f1");
+        let result = FeatureSnapshot::should_rollout_with_id(100, entity_id, "f1", None);
         assert!(result);
 
-        let result = FeatureSnapshot::should_rollout_with_id(0, entity_id, "f1");
+        let result = FeatureSnapshot::should_rollout_with_id(0, entity_id, "f1", None);
         assert!(!result);
 
-        let result = FeatureSnapshot::should_rollout_with_id(50, entity_id, "f1");
+        let result = FeatureSnapshot::should_rollout_with_id(50, entity_id, "f1", None);
         assert_eq!(result, partial_rollout_expectation);
 
-        let result = FeatureSnapshot::should_rollout_with_id(50, entity_id, "f4");
+        let result = FeatureSnapshot::should_rollout_with_id(50, entity_id, "f4", None);
         // We chose feature ID here so that we rollout exactly inverted to "f1"
         assert_eq!(result, !partial_rollout_expectation);
     }
 
-    // Scenarios in which no segment rule matching should be performed.
+    #[test]
+    fn test_rollout_salt() {
+        let feature = feature(
+            "f1",
+            FeatureValues::Boolean,
+            true,
+            50,
+            HashMap::new(),
+            Vec::new(),
+        );
+        let entity = |id: usize| crate::tests::GenericEntity {
+            id: format!("entity-{id}"),
+            attributes: HashMap::new(),
+        };
+        let rolled_out = |feature: &FeatureSnapshot| {
+            (0..100)
+                .map(|id| feature.get_bool_value(&entity(id)).unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        let unsalted = rolled_out(&feature);
+        let feature = feature.with_rollout_salt("my-app");
+        let salted = rolled_out(&feature);
+        assert_ne!(unsalted, salted);
+        assert_eq!(salted, rolled_out(&feature));
+
+        // Same hash input as `<entity_id>:<feature_id>:<salt>`
+        for (id, enabled) in salted.iter().enumerate() {
+            let hash = FeatureSnapshot::normalized_hash(&format!("entity-{id}:f1:my-app"));
+            assert_eq!(*enabled, hash < 50);
+            let value = feature.get_current_value(&entity(id)).unwrap().value;
+            assert_eq!(value, Value::Boolean(*enabled));
+        }
+    }
+
+    #[test]
+    fn test_rollout_preview() {
+        let feature = feature(
+            "f1",
+            FeatureValues::Boolean,
+            true,
+            50,
+            HashMap::new(),
+            Vec::new(),
+        )
+        .with_rollout_salt("my-app");
+        let entities: Vec<crate::tests::GenericEntity> = (0..100)
+            .map(|id| crate::tests::GenericEntity {
+                id: format!("entity-{id}"),
+                attributes: HashMap::new(),
+            })
+            .collect();
+        let sample: Vec<&dyn Entity> = entities.iter().map(|e| e as &dyn Entity).collect();
+
+        let preview = feature.rollout_preview(&[0, 25, 50, 100], &sample);
+        assert_eq!(preview.len(), 4);
+        assert_eq!(preview[0], 0);
+        assert_eq!(preview[3], 100);
+        assert!(preview.is_sorted());
+
+        // Same entities as the evaluation at the rollout percentage of the feature
+        let rolled_out = entities
+            .iter()
+            .filter(|entity| feature.get_bool_value(*entity).unwrap())
+            .count();
+        assert_eq!(preview[2], rolled_out);
+
+        assert!(feature.rollout_preview(&[], &sample).is_empty());
+        assert_eq!(feature.rollout_preview(&[50], &[]), [0]);
+    }
+
+    #[test]
+    fn test_entity_id_targeting_without_attributes() {
+        let segments = HashMap::from([(
+            "beta_testers".into(),
+            Segment {
+                name: "Beta testers".into(),
+                segment_id: "beta_testers".into(),
+                description: None,
+                tags: None,
+                rules: vec![Rule {
+                    attribute_name: "entityId".into(),
+                    operator: "is".into(),
+                    values: vec!["a1".into()],
+                }],
+            },
+        )]);
+        let feature = feature(
+            "f1",
+            FeatureValues::Numeric,
+            true,
+            100,
+            segments,
+            create_one_segment_rule(
+                "beta_testers".into(),
+                serde_json::Value::Number(7.into()),
+                serde_json::Value::Number(100.into()),
+            ),
+        );
+        let entity = |id: &str, attributes: HashMap<String, Value>| crate::tests::GenericEntity {
+            id: id.into(),
+            attributes,
+        };
+
+        let result = feature.get_current_value(&entity("a1", [].into())).unwrap();
+        assert_eq!(result.value, Value::Int64(7));
+        assert_eq!(result.details.segment_name.as_deref(), Some("Beta testers"));
+
+        let result = feature.get_current_value(&entity("a2", [].into())).unwrap();
+        assert_eq!(result.value, Value::Int64(-42));
+
+        // An `entityId` attribute of the entity takes precedence
+        let attributes = HashMap::from([("entityId".into(), Value::from("a2".to_string()))]);
+        let result = feature
+            .get_current_value(&entity("a1", attributes))
+            .unwrap();
+        assert_eq!(result.value, Value::Int64(-42));
+    }
+
+    // Scenarios in which no segment rule matches.
     // So we expect to always return feature's enabled/disabled values depending on rollout percentage.
     #[rstest]
     // no attrs, no segment rules
@@ -326,24 +1068,14 @@ pub mod tests {
         #[case] segment_rules: Vec<SegmentRule>,
         #[case] entity_attributes: HashMap<String, Value>,
     ) {
-        let feature = {
-            let segment_rules =
-                TargetingRules::new(HashMap::new(), segment_rules, ValueType::Numeric, None);
-            FeatureSnapshot::new(
-                true,
-                Value::Int64(-42),
-                Value::Int64(2),
-                50,
-                None,
-                None,
-                "F1",
-                "f1",
-                "NUMERIC".to_string(),
-                None,
-                segment_rules,
-                None,
-            )
-        };
+        let feature = feature(
+            "f1",
+            FeatureValues::Numeric,
+            true,
+            50,
+            heinz_segments(),
+            segment_rules,
+        );
 
         // One entity and feature combination which leads to no rollout:
         let entity = crate::tests::GenericEntity {
@@ -374,69 +1106,233 @@ pub mod tests {
         assert!(matches!(value.value, Value::Int64(ref v) if v == &(-42)));
     }
 
+    // The boolean fast path must agree with the generic evaluation.
+    #[rstest]
+    #[case(true, 100, [].into())]
+    #[case(true, 50, [].into())]
+    #[case(false, 100, [].into())]
+    #[case(true, 50, create_one_segment_rule("some_segment_id".into(), true.into(), 100.into()))]
+    fn test_get_bool_value(
+        #[case] enabled: bool,
+        #[case] rollout_percentage: u32,
+        #[case] segment_rules: Vec<SegmentRule>,
+    ) {
+        let feature = feature(
+            "f1",
+            FeatureValues::Boolean,
+            enabled,
+            rollout_percentage,
+            heinz_segments(),
+            segment_rules,
+        );
+
+        for (id, name) in [("a1", "heinz"), ("a2", "heinz"), ("a1", "peter")] {
+            let entity = crate::tests::GenericEntity {
+                id: id.into(),
+                attributes: HashMap::from([("name".into(), Value::from(name.to_string()))]),
+            };
+            let expected = feature.get_current_value(&entity).unwrap().value;
+            assert_eq!(
+                Value::Boolean(feature.get_bool_value(&entity).unwrap()),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_get_bool_value_mismatch_type() {
+        let feature = feature(
+            "f1",
+            FeatureValues::Numeric,
+            true,
+            100,
+            HashMap::new(),
+            Vec::new(),
+        );
+        let entity = crate::entity::tests::TrivialEntity {};
+        assert!(matches!(
+            feature.get_bool_value(&entity),
+            Err(crate::Error::ValueConversionError {
+                expected: "bool",
+                ..
+            })
+        ));
+    }
+
     // If the feature is disabled, always the disabled value should be returned.
     #[test]
     fn test_get_value_disabled_feature() {
-        let feature = {
-            let segment_rules =
-                TargetingRules::new(HashMap::new(), Vec::new(), ValueType::Numeric, None);
-            FeatureSnapshot::new(
+        let feature = feature(
+            "f1",
+            FeatureValues::Numeric,
+            false,
+            100,
+            HashMap::new(),
+            Vec::new(),
+        );
+
+        let entity = crate::entity::tests::TrivialEntity {};
+        let value = feature.get_current_value(&entity).unwrap();
+        assert!(matches!(value.value, Value::Int64(ref v) if v == &2));
+    }
+
+    // A feature evaluates to its disabled value if any prerequisite is not enabled
+    // for the entity, even if the feature itself would be enabled.
+    #[rstest]
+    fn test_get_value_with_prerequisites(one_segment_rule: Vec<SegmentRule>) {
+        let feature = |feature_id: &str, enabled: bool, segment_rules: Vec<SegmentRule>| {
+            feature(
+                feature_id,
+                FeatureValues::Numeric,
+                enabled,
+                0,
+                heinz_segments(),
+                segment_rules,
+            )
+        };
+        let entity = |name: &str| crate::tests::GenericEntity {
+            id: "a2".into(),
+            attributes: HashMap::from([("name".into(), Value::from(name.to_string()))]),
+        };
+
+        // Parent is only enabled for entities in the segment
+        let child = feature("child", true, one_segment_rule.clone())
+            .with_prerequisites(vec![feature("parent", true, one_segment_rule.clone())]);
+        let result = child.get_current_value(&entity("heinz")).unwrap();
+        assert!(result.is_enabled);
+        assert_eq!(result.value, Value::Int64(-48));
+
+        let result = child.get_current_value(&entity("peter")).unwrap();
+        assert!(!result.is_enabled);
+        assert_eq!(result.value, Value::Int64(2));
+        assert_eq!(
+            result.details.reason,
+            "Prerequisite feature 'parent' is not enabled for entity. Returning disabled value."
+        );
+
+        // Prerequisites are evaluated transitively
+        let child = feature("child", true, one_segment_rule.clone()).with_prerequisites(vec![
+            feature("parent", true, one_segment_rule).with_prerequisites(vec![feature(
+                "kill-switch",
                 false,
-                Value::Int64(-42),
-                Value::Int64(2),
+                Vec::new(),
+            )]),
+        ]);
+        let result = child.get_current_value(&entity("heinz")).unwrap();
+        assert!(!result.is_enabled);
+        assert_eq!(result.value, Value::Int64(2));
+    }
+
+    // Local overrides take precedence over the configuration, also for prerequisites.
+    #[test]
+    fn test_local_override() {
+        let feature = |feature_id: &str, enabled: bool| {
+            feature(
+                feature_id,
+                FeatureValues::Boolean,
+                enabled,
                 100,
-                None,
-                None,
-                "F1",
-                "f1",
-                "NUMERIC".to_string(),
-                None,
-                segment_rules,
-                None,
+                HashMap::new(),
+                Vec::new(),
             )
         };
+        let entity = crate::entity::tests::TrivialEntity {};
+
+        let disabled = feature("f1", false)
+            .with_local_override(Some(Value::Boolean(true)))
+            .unwrap();
+        assert!(disabled.is_enabled().unwrap());
+        assert!(disabled.get_bool_value(&entity).unwrap());
+        let result = disabled.get_current_value(&entity).unwrap();
+        assert!(result.is_enabled);
+        assert_eq!(result.value, Value::Boolean(true));
+        assert_eq!(result.details.value_type, "LOCAL_OVERRIDE");
+
+        let enabled = feature("f1", true)
+            .with_local_override(Some(Value::Boolean(false)))
+            .unwrap();
+        assert!(!enabled.is_enabled().unwrap());
+        assert!(!enabled.get_bool_value(&entity).unwrap());
+
+        let child = feature("child", true).with_prerequisites(vec![
+            feature("parent", true)
+                .with_local_override(Some(Value::Boolean(false)))
+                .unwrap(),
+        ]);
+        assert!(!child.get_bool_value(&entity).unwrap());
 
+        // Overrides must have the type of the feature
+        let err = feature("f1", true)
+            .with_local_override(Some(Value::String("true".into())))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Local override of 'f1' is String(\"true\"), not a BOOLEAN value"
+        );
+        feature("f1", true)
+            .with_local_override(Some(Value::from(vec![true, false])))
+            .unwrap();
+    }
+
+    // The injected clock decides which phase of a progressive rollout applies.
+    #[test]
+    fn test_progressive_rollout_uses_injected_clock() {
+        struct FixedClock(std::time::SystemTime);
+        impl Clock for FixedClock {
+            fn now(&self) -> std::time::SystemTime {
+                self.0
+            }
+        }
+        let at = |timestamp: &str| -> Arc<dyn Clock> {
+            let ms = crate::utils::parse_timestamp_millis(timestamp).unwrap();
+            Arc::new(FixedClock(
+                std::time::UNIX_EPOCH + std::time::Duration::from_millis(ms as u64),
+            ))
+        };
+
+        let feature = FeatureSnapshot::new(
+            true,
+            Value::Int64(-42),
+            Value::Int64(2),
+            0,
+            Some(ROLLOUT_TYPE_PROGRESSIVE.to_string()),
+            Some(RolloutConfiguration {
+                start_at: "2030-01-01T00:00:00Z".to_string(),
+                phases: vec![crate::models::RolloutPhase {
+                    percentage: 100,
+                    duration: None,
+                    duration_type: None,
+                    ramp: false,
+                }],
+            }),
+            "F1",
+            "f1",
+            "NUMERIC".to_string(),
+            None,
+            TargetingRules::new(HashMap::new(), Vec::new(), ValueType::Numeric, None),
+            None,
+        );
         let entity = crate::entity::tests::TrivialEntity {};
-        let value = feature.get_current_value(&entity).unwrap();
-        assert!(matches!(value.value, Value::Int64(ref v) if v == &2));
+
+        let feature = feature.with_clock(at("2029-12-31T23:59:59Z"));
+        assert!(!feature.get_current_value(&entity).unwrap().is_enabled);
+
+        let feature = feature.with_clock(at("2030-01-01T00:00:00Z"));
+        assert!(feature.get_current_value(&entity).unwrap().is_enabled);
     }
 
     // Get a feature value using different entities, matching or not matching a segment rule.
     // Uses rollout percentage to also test no rollout even if matched
     #[rstest]
     fn test_get_value_matching_a_rule(one_segment_rule: Vec<SegmentRule>) {
-        let feature = {
-            let segments = HashMap::from([(
-                "some_segment_id".into(),
-                Segment {
-                    name: "".into(),
-                    segment_id: "".into(),
-                    description: None,
-                    tags: None,
-                    rules: vec![Rule {
-                        attribute_name: "name".into(),
-                        operator: "is".into(),
-                        values: vec!["heinz".into()],
-                    }],
-                },
-            )]);
-            let segment_rules =
-                TargetingRules::new(segments, one_segment_rule, ValueType::Numeric, None);
-            FeatureSnapshot::new(
-                true,
-                Value::Int64(-42),
-                Value::Int64(2),
-                50,
-                None,
-                None,
-                "F1",
-                "f1",
-                "NUMERIC".to_string(),
-                None,
-                segment_rules,
-                None,
-            )
-        };
+        let feature = feature(
+            "f1",
+            FeatureValues::Numeric,
+            true,
+            50,
+            heinz_segments(),
+            one_segment_rule,
+        );
 
         // matching the segment + rollout allowed
         let entity = crate::tests::GenericEntity {
@@ -464,49 +1360,49 @@ pub mod tests {
 
         let value = feature.get_current_value(&entity).unwrap();
         assert!(matches!(value.value, Value::Int64(ref v) if v == &(-42)));
+
+        // the attribute has a type the segment rule cannot compare
+        let entity = crate::tests::GenericEntity {
+            id: "a3".into(),
+            attributes: HashMap::from([("name".into(), Value::from(42.0))]),
+        };
+        let error = feature.get_current_value(&entity).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("Failed to evaluate feature 'f1' for entity 'a3': ")
+        );
+        let crate::Error::EvaluationError(error) = error else {
+            panic!("Error type mismatch!");
+        };
+        assert_eq!(
+            (error.kind, error.id.as_str(), error.entity_id.as_str()),
+            ("feature", "f1", "a3")
+        );
+        assert!(matches!(
+            *error.source,
+            crate::Error::EntityEvaluationError(_)
+        ));
+        assert!(feature.get_values_for(&[&entity])[0].is_err());
     }
 
     // The matched segment rule's value has a "$default" value.
     // In this case, the feature's enabled value should be used whenever the rule matches.
     #[test]
     fn test_get_value_matching_yielding_default_value() {
-        let feature = {
-            let segments = HashMap::from([(
-                "some_segment_id".into(),
-                Segment {
-                    name: "".into(),
-                    segment_id: "".into(),
-                    description: None,
-                    tags: None,
-                    rules: vec![Rule {
-                        attribute_name: "name".into(),
-                        operator: "is".into(),
-                        values: vec!["heinz".into()],
-                    }],
-                },
-            )]);
-            let segment_rules = create_one_segment_rule(
-                "some_segment_id".into(),
-                serde_json::Value::String("$default".into()),
-                serde_json::Value::Number((50).into()),
-            );
-            let segment_rules =
-                TargetingRules::new(segments, segment_rules, ValueType::Numeric, None);
-            FeatureSnapshot::new(
-                true,
-                Value::Int64(-42),
-                Value::Int64(2),
-                50,
-                None,
-                None,
-                "F1",
-                "f1",
-                "NUMERIC".to_string(),
-                None,
-                segment_rules,
-                None,
-            )
-        };
+        let segment_rules = create_one_segment_rule(
+            "some_segment_id".into(),
+            serde_json::Value::String("$default".into()),
+            serde_json::Value::Number((50).into()),
+        );
+        let feature = feature(
+            "f1",
+            FeatureValues::Numeric,
+            true,
+            50,
+            heinz_segments(),
+            segment_rules,
+        );
 
         // matching the segment + rollout allowed
         let entity = crate::tests::GenericEntity {
@@ -522,43 +1418,19 @@ pub mod tests {
     // In this case, the feature's rollout percentage should be used whenever the rule matches.
     #[test]
     fn test_get_value_matching_segment_rollout_default_value() {
-        let feature = {
-            let segments = HashMap::from([(
-                "some_segment_id".into(),
-                Segment {
-                    name: "".into(),
-                    segment_id: "".into(),
-                    description: None,
-                    tags: None,
-                    rules: vec![Rule {
-                        attribute_name: "name".into(),
-                        operator: "is".into(),
-                        values: vec!["heinz".into()],
-                    }],
-                },
-            )]);
-            let segment_rules = create_one_segment_rule(
-                "some_segment_id".into(),
-                serde_json::Value::Number(48.into()),
-                serde_json::Value::String("$default".into()),
-            );
-            let segment_rules =
-                TargetingRules::new(segments, segment_rules, ValueType::Numeric, None);
-            FeatureSnapshot::new(
-                true,
-                Value::Int64(-42),
-                Value::Int64(2),
-                0,
-                None,
-                None,
-                "F1",
-                "f1",
-                "NUMERIC".to_string(),
-                None,
-                segment_rules,
-                None,
-            )
-        };
+        let segment_rules = create_one_segment_rule(
+            "some_segment_id".into(),
+            serde_json::Value::Number(48.into()),
+            serde_json::Value::String("$default".into()),
+        );
+        let feature = feature(
+            "f1",
+            FeatureValues::Numeric,
+            true,
+            0,
+            heinz_segments(),
+            segment_rules,
+        );
 
         // matching the segment + rollout allowed
         let entity = crate::tests::GenericEntity {
@@ -593,11 +1465,13 @@ pub mod tests {
                     percentage: 10,
                     duration: Some(1),
                     duration_type: Some("days".to_string()),
+                    ramp: false,
                 },
                 RolloutPhase {
                     percentage: 100,
                     duration: None,
                     duration_type: None,
+                    ramp: false,
                 },
             ],
         };
@@ -652,6 +1526,7 @@ pub mod tests {
                 percentage: 100,
                 duration: None,
                 duration_type: None,
+                ramp: false,
             }],
         };
 
@@ -701,6 +1576,7 @@ pub mod tests {
                 percentage: 0,
                 duration: None,
                 duration_type: None,
+                ramp: false,
             }],
         };
 
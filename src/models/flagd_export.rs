@@ -0,0 +1,269 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde_json::{Map, Value as JsonValue, json};
+
+use crate::Value;
+use crate::errors::{Error, Result};
+use crate::models::{ROLLOUT_TYPE_PROGRESSIVE, RolloutConfiguration};
+use crate::network::serialization::{ConfigValue, Feature, SegmentRule, SegmentsOperator};
+use crate::segment_evaluation::{CompiledSegment, TargetingRules};
+use crate::utils::{
+    get_current_rollout_percentage, parse_rollout_configuration_phases, parse_timestamp_millis,
+};
+
+const FLAGD_SCHEMA: &str = "https://flagd.dev/schema/v0/flags.json";
+const ENABLED_VARIANT: &str = "enabled";
+const DISABLED_VARIANT: &str = "disabled";
+
+/// Converts `features` to a flagd flag definition file, see
+/// [`Configuration::export_flagd`](crate::Configuration::export_flagd).
+pub(super) fn export_flagd(
+    features: &HashMap<String, (Feature, TargetingRules)>,
+    segments: &HashMap<String, Arc<CompiledSegment>>,
+    now_ms: i64,
+) -> Result<JsonValue> {
+    let flags = features
+        .iter()
+        .map(|(feature_id, (feature, _))| {
+            Ok((feature_id.clone(), flagd_flag(feature, segments, now_ms)?))
+        })
+        .collect::<Result<Map<_, _>>>()?;
+    Ok(json!({ "$schema": FLAGD_SCHEMA, "flags": flags }))
+}
+
+fn flagd_flag(
+    feature: &Feature,
+    segments: &HashMap<String, Arc<CompiledSegment>>,
+    now_ms: i64,
+) -> Result<JsonValue> {
+    let variant_value = |value: &ConfigValue| -> Result<JsonValue> {
+        Ok(Value::try_from((feature.r#type, value.clone()))?.into())
+    };
+    let mut variants = Map::new();
+    variants.insert(
+        ENABLED_VARIANT.to_string(),
+        variant_value(&feature.enabled_value)?,
+    );
+    variants.insert(
+        DISABLED_VARIANT.to_string(),
+        variant_value(&feature.disabled_value)?,
+    );
+    if !feature.enabled {
+        return Ok(json!({
+            "state": "ENABLED",
+            "variants": variants,
+            "defaultVariant": DISABLED_VARIANT,
+        }));
+    }
+
+    let feature_percentage = current_percentage(
+        feature.rollout_type.as_deref(),
+        feature.rollout_configuration.as_ref(),
+        now_ms,
+    )
+    .unwrap_or(feature.rollout_percentage);
+    let mut branches = Vec::new();
+    for segment_rule in &feature.segment_rules {
+        let variant = if segment_rule.value.is_default() {
+            ENABLED_VARIANT.to_string()
+        } else {
+            let variant = format!("rule-{}", segment_rule.order);
+            variants.insert(variant.clone(), variant_value(&segment_rule.value)?);
+            variant
+        };
+        branches.push(condition(segment_rule, segments));
+        branches.push(rollout(
+            &variant,
+            rule_percentage(segment_rule, feature_percentage, now_ms)?,
+        ));
+    }
+    let fallback = rollout(ENABLED_VARIANT, feature_percentage);
+
+    let mut flag = json!({
+        "state": "ENABLED",
+        "variants": variants,
+        "defaultVariant": ENABLED_VARIANT,
+    });
+    if !branches.is_empty() {
+        branches.push(fallback);
+        flag["targeting"] = json!({ "if": branches });
+    } else if fallback != ENABLED_VARIANT {
+        flag["targeting"] = fallback;
+    }
+    Ok(flag)
+}
+
+// Percentage of a progressive rollout at `now_ms`, `None` for fixed rollouts.
+fn current_percentage(
+    rollout_type: Option<&str>,
+    rollout_configuration: Option<&RolloutConfiguration>,
+    now_ms: i64,
+) -> Option<u32> {
+    if rollout_type != Some(ROLLOUT_TYPE_PROGRESSIVE) {
+        return None;
+    }
+    let phases = parse_rollout_configuration_phases(rollout_configuration?).ok()?;
+    Some(get_current_rollout_percentage(&phases, now_ms))
+}
+
+// Same resolution as `TargetingRule::rollout_percentage`.
+fn rule_percentage(
+    segment_rule: &SegmentRule,
+    feature_percentage: u32,
+    now_ms: i64,
+) -> Result<u32> {
+    if let Some(percentage) = current_percentage(
+        segment_rule.rollout_type.as_deref(),
+        segment_rule.rollout_configuration.as_ref(),
+        now_ms,
+    ) {
+        return Ok(percentage);
+    }
+    match &segment_rule.rollout_percentage {
+        None => Err(Error::ProtocolError("Rollout is missing".to_string())),
+        Some(percentage) if percentage.is_default() => Ok(feature_percentage),
+        Some(percentage) => percentage
+            .as_u64()
+            .and_then(|percentage| u32::try_from(percentage).ok())
+            .ok_or_else(|| Error::ProtocolError("Invalid rollout value.".to_string())),
+    }
+}
+
+// The entity is in (or out of) any of the segments of each list, within the
+// schedule of the rule. flagd timestamps are in seconds.
+fn condition(
+    segment_rule: &SegmentRule,
+    segments: &HashMap<String, Arc<CompiledSegment>>,
+) -> JsonValue {
+    let targeted: Vec<_> = segment_rule
+        .rules
+        .iter()
+        .map(|segment_list| {
+            let any_segment = json!({ "or": segment_list
+                .segments
+                .iter()
+                .map(|segment_id| segments
+                    .get(segment_id)
+                    .map_or(JsonValue::Bool(false), |segment| segment.to_json_logic()))
+                .collect::<Vec<_>>() });
+            match segment_list.operator {
+                SegmentsOperator::In => any_segment,
+                // Like in the SDK, excluding unsupported segments never applies
+                SegmentsOperator::Not
+                    if segment_list.segments.iter().any(|segment_id| {
+                        segments
+                            .get(segment_id)
+                            .is_some_and(|segment| !segment.supported)
+                    }) =>
+                {
+                    JsonValue::Bool(false)
+                }
+                SegmentsOperator::Not => json!({ "!": [any_segment] }),
+            }
+        })
+        .collect();
+    let mut conditions = vec![json!({ "or": targeted })];
+    let timestamp = json!({ "var": "$flagd.timestamp" });
+    if let Some(start) = segment_rule
+        .start_at
+        .as_deref()
+        .and_then(parse_timestamp_millis)
+    {
+        conditions.push(json!({ ">=": [timestamp, start.div_euclid(1000)] }));
+    }
+    if let Some(end) = segment_rule
+        .end_at
+        .as_deref()
+        .and_then(parse_timestamp_millis)
+    {
+        conditions.push(json!({ "<": [timestamp, end.div_euclid(1000)] }));
+    }
+    if conditions.len() == 1 {
+        conditions.remove(0)
+    } else {
+        json!({ "and": conditions })
+    }
+}
+
+// Variant served to `percentage` percent of the entities, the rest get the
+// disabled value.
+fn rollout(variant: &str, percentage: u32) -> JsonValue {
+    match percentage {
+        0 => json!(DISABLED_VARIANT),
+        100.. => json!(variant),
+        _ => json!({ "fractional": [[variant, percentage], [DISABLED_VARIANT, 100 - percentage]] }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Configuration;
+    use crate::network::serialization::fixtures::example_configuration_enterprise;
+    use rstest::rstest;
+
+    #[rstest]
+    fn test_export_flagd(example_configuration_enterprise: Configuration) {
+        let export = example_configuration_enterprise.export_flagd().unwrap();
+        assert_eq!(export["$schema"], FLAGD_SCHEMA);
+        let flags = export["flags"].as_object().unwrap();
+        assert_eq!(flags.len(), 5);
+
+        assert_eq!(
+            flags["f3"],
+            json!({
+                "state": "ENABLED",
+                "variants": {"enabled": true, "disabled": false},
+                "defaultVariant": "enabled",
+            })
+        );
+
+        let f1 = &flags["f1"];
+        assert_eq!(
+            f1["variants"],
+            json!({"enabled": 5, "disabled": 0, "rule-1": 40, "rule-2": 60, "rule-3": 25})
+        );
+        let branches = f1["targeting"]["if"].as_array().unwrap();
+        assert_eq!(branches.len(), 7);
+        assert_eq!(
+            branches[0],
+            json!({"or": [{"or": [{"and": [{"and": [
+                {"!=": [{"var": "code"}, null]},
+                {"or": [
+                    {"starts_with": [{"var": "code"}, "CHG"]},
+                    {"starts_with": [{"var": "code"}, "AMD"]},
+                ]},
+            ]}]}]}]})
+        );
+        assert_eq!(branches[1], "rule-1");
+        assert_eq!(branches[6], "enabled");
+
+        // A rule with the "$default" value serves the enabled variant
+        assert_eq!(flags["f2"]["targeting"]["if"][1], "enabled");
+    }
+
+    #[test]
+    fn test_rollout() {
+        assert_eq!(rollout("rule-1", 100), json!("rule-1"));
+        assert_eq!(rollout("rule-1", 0), json!("disabled"));
+        assert_eq!(
+            rollout("enabled", 30),
+            json!({"fractional": [["enabled", 30], ["disabled", 70]]})
+        );
+    }
+}
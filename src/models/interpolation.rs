@@ -0,0 +1,134 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Substitution of `${...}` placeholders in String values.
+//!
+//! Supported placeholders:
+//!  * `${entity.id}`: the [`Entity::get_id`] of the evaluated entity.
+//!  * `${attr:<name>}`: the attribute `<name>` of the evaluated entity.
+//!
+//! Placeholders that cannot be resolved (unknown syntax or missing attribute)
+//! are kept verbatim so misconfigurations are visible in the resulting value.
+
+use std::collections::HashMap;
+
+use crate::{Entity, Value};
+
+const ENTITY_ID_PLACEHOLDER: &str = "entity.id";
+const ATTRIBUTE_PLACEHOLDER_PREFIX: &str = "attr:";
+
+/// Replaces the placeholders in `value` if it is a [`Value::String`]. Other
+/// values are returned unchanged.
+pub(crate) fn interpolate_value(value: Value, entity: &(impl Entity + ?Sized)) -> Value {
+    match value {
+        Value::String(template) if template.contains("${") => {
+            Value::String(interpolate(&template, entity))
+        }
+        other => other,
+    }
+}
+
+fn interpolate(template: &str, entity: &(impl Entity + ?Sized)) -> String {
+    let mut attributes: Option<HashMap<String, Value>> = None;
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+        let Some(end) = after_open.find('}') else {
+            // Unterminated placeholder, keep the remainder as it is.
+            rest = &rest[start..];
+            break;
+        };
+        let key = &after_open[..end];
+
+        let replacement = if key == ENTITY_ID_PLACEHOLDER {
+            Some(entity.get_id())
+        } else if let Some(name) = key.strip_prefix(ATTRIBUTE_PLACEHOLDER_PREFIX) {
+            attributes
+                .get_or_insert_with(|| entity.get_attributes())
+                .get(name)
+                .map(value_to_string)
+        } else {
+            None
+        };
+
+        match replacement {
+            Some(replacement) => result.push_str(&replacement),
+            None => result.push_str(&rest[start..start + 2 + end + 1]),
+        }
+        rest = &after_open[end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::Float64(v) => v.to_string(),
+        Value::UInt64(v) => v.to_string(),
+        Value::Int64(v) => v.to_string(),
+        Value::String(v) => v.clone(),
+        Value::Boolean(v) => v.to_string(),
+        Value::List(_) => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::GenericEntity;
+
+    fn entity() -> GenericEntity {
+        GenericEntity {
+            id: "user-42".into(),
+            attributes: HashMap::from([
+                ("region".into(), Value::from("eu-de".to_string())),
+                ("tier".into(), Value::from(3u64)),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_interpolate_placeholders() {
+        assert_eq!(
+            interpolate(
+                "postgres://${attr:region}.db/${entity.id}?tier=${attr:tier}",
+                &entity()
+            ),
+            "postgres://eu-de.db/user-42?tier=3"
+        );
+    }
+
+    #[test]
+    fn test_unresolved_placeholders_are_kept() {
+        assert_eq!(
+            interpolate("${attr:missing}-${unknown}-${entity.id", &entity()),
+            "${attr:missing}-${unknown}-${entity.id"
+        );
+    }
+
+    #[test]
+    fn test_only_strings_are_interpolated() {
+        assert_eq!(
+            interpolate_value(Value::Int64(7), &entity()),
+            Value::Int64(7)
+        );
+        assert_eq!(
+            interpolate_value(Value::from("no placeholders".to_string()), &entity()),
+            Value::from("no placeholders".to_string())
+        );
+    }
+}
@@ -22,17 +22,25 @@
 //!
 
 mod configuration;
+mod configuration_diff;
+mod configuration_source;
 mod evaluation_result;
 mod feature_snapshot;
+mod flagd_export;
+mod interpolation;
 mod property_snapshot;
 mod rollout_configuration;
 mod secret_property;
+mod segment_snapshot;
+mod validation;
 
-pub(crate) use configuration::Configuration;
+pub use configuration::Configuration;
+pub use configuration_diff::{ConfigurationDiff, ResourceChange};
+pub use configuration_source::ConfigurationSource;
 pub use evaluation_result::{
     EvaluationContext, EvaluationRuleCondition, EvaluationRuleContext, EvaluationSegmentContext,
     FeatureEvaluationDetails, FeatureEvaluationResult, PropertyEvaluationDetails,
-    PropertyEvaluationResult,
+    PropertyEvaluationResult, Variation,
 };
 pub(crate) use feature_snapshot::FeatureSnapshot;
 pub(crate) use property_snapshot::PropertySnapshot;
@@ -40,3 +48,5 @@ pub(crate) use property_snapshot::PropertySnapshot;
 pub(crate) use rollout_configuration::RolloutPhase;
 pub(crate) use rollout_configuration::{DELIMITER, ROLLOUT_TYPE_PROGRESSIVE, RolloutConfiguration};
 pub use secret_property::{SecretManager, SecretPropertySnapshot};
+pub use segment_snapshot::{SegmentRuleSnapshot, SegmentSnapshot};
+pub use validation::{ResourceKind, SkippedResource, ValidationMode, ValidationReport};
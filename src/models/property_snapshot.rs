@@ -13,14 +13,20 @@
 // limitations under the License.
 
 use crate::entity::Entity;
-use crate::metering::{MeteringRecorderSender, MeteringSubject};
+use crate::metering::{MeteringRecorderSender, MeteringSubject, ValueSource};
 use crate::value::Value;
 use crate::{Property, PropertyEvaluationResult};
 
-use crate::errors::Result;
+use crate::ConfigurationSource;
+use crate::errors::{DeserializationError, Error, EvaluationError, Result};
 use crate::models::evaluation_result::PropertyEvaluationDetails;
+use crate::models::interpolation::interpolate_value;
 use crate::network::serialization::ValueType;
 use crate::segment_evaluation::TargetingRules;
+use crate::utils::{Clock, MutexExt, SystemClock, now_millis};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 /// Provides a snapshot of a [`Property`].
 #[derive(Debug)]
@@ -30,12 +36,22 @@ pub struct PropertySnapshot {
     value_type: ValueType,
     r#type: String,
     format: Option<String>,
+    description: Option<String>,
+    tags: Option<String>,
     pub(crate) name: String,
     pub(crate) property_id: String,
     pub(crate) metering: Option<MeteringRecorderSender>,
+    interpolate_values: bool,
+    clock: Arc<dyn Clock>,
+    /// Value forced locally, returned for every entity.
+    local_override: Option<Value>,
+    /// Parsed JSON values, keyed by the string they were parsed from.
+    parsed_values: Mutex<HashMap<String, Arc<serde_json::Value>>>,
+    source: ConfigurationSource,
 }
 
 impl PropertySnapshot {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         value: Value,
         segment_rules: TargetingRules,
@@ -52,30 +68,139 @@ impl PropertySnapshot {
             value_type,
             r#type,
             format,
+            description: None,
+            tags: None,
             name: name.to_string(),
             property_id: property_id.to_string(),
             metering,
+            interpolate_values: false,
+            clock: Arc::new(SystemClock),
+            local_override: None,
+            parsed_values: Mutex::default(),
+            source: ConfigurationSource::default(),
         }
     }
 
+    /// Attaches the description and tags set for the property in the configuration.
+    pub(crate) fn with_metadata(
+        mut self,
+        description: Option<String>,
+        tags: Option<String>,
+    ) -> Self {
+        self.description = description.filter(|description| !description.is_empty());
+        self.tags = tags.filter(|tags| !tags.is_empty());
+        self
+    }
+
+    /// Records where the configuration the property was taken from comes from.
+    pub(crate) fn with_source(mut self, source: ConfigurationSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Returns where the configuration the property was taken from comes from,
+    /// e.g. to tell a value served from a stale cache from a live one.
+    pub fn source(&self) -> ConfigurationSource {
+        self.source
+    }
+
+    /// Returns the description of the property, if there is one.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Returns the tags of the property as written in the configuration, a comma-separated
+    /// list, if there are any.
+    pub fn tags(&self) -> Option<&str> {
+        self.tags.as_deref()
+    }
+
+    /// Returns the type of the property values: `BOOLEAN`, `NUMERIC`, `STRING` or `SECRETREF`.
+    pub fn value_type(&self) -> &str {
+        &self.r#type
+    }
+
+    /// Returns the format of the property values. String values without a format are
+    /// `TEXT`, other types have none.
+    pub fn format(&self) -> Option<&str> {
+        // If the Format is null or undefined for a String type, we default it to TEXT
+        if self.format.is_none() && self.r#type == "STRING" {
+            return Some("TEXT");
+        }
+        self.format.as_deref()
+    }
+
+    /// Enables substitution of `${entity.id}` and `${attr:<name>}` placeholders in
+    /// String values with data from the evaluated [`Entity`].
+    pub fn with_value_interpolation(mut self, enabled: bool) -> Self {
+        self.interpolate_values = enabled;
+        self
+    }
+
+    /// Uses `clock` instead of the system clock to resolve time-based targeting.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Evaluates the property to `value`, if any, for every entity, ignoring the
+    /// configuration.
+    ///
+    /// Fails if `value` is not of the type of the property.
+    pub(crate) fn with_local_override(mut self, value: Option<Value>) -> Result<Self> {
+        let Some(value) = value else {
+            return Ok(self);
+        };
+        if !self.value_type.accepts(&value) {
+            return Err(Error::LocalOverrideTypeMismatch {
+                id: self.property_id.clone(),
+                expected: self.r#type.clone(),
+                found: value,
+            });
+        }
+        self.local_override = Some(value);
+        Ok(self)
+    }
+
     fn evaluate_property_for_entity(
         &self,
-        entity: &impl Entity,
+        entity: &(impl Entity + ?Sized),
     ) -> Result<(Value, PropertyEvaluationDetails)> {
+        if let Some(value) = &self.local_override {
+            return Ok((
+                value.clone(),
+                PropertyEvaluationDetails {
+                    value_type: "LOCAL_OVERRIDE".to_string(),
+                    reason: "Property value is overridden locally.".to_string(),
+                    segment_name: None,
+                },
+            ));
+        }
+
         let (segment_rule, segment) = {
-            if self.segment_rules.is_empty() || entity.get_attributes().is_empty() {
-                // TODO: this makes only sense if there can be a rule which matches
-                //       even on empty attributes
-                // No match possible. Do not consider segment rules:
+            if self.segment_rules.is_empty() {
                 (None, None)
             } else {
                 self.segment_rules
-                    .find_applicable_targeting_rule_and_segment_for_entity(entity)?
+                    .find_applicable_targeting_rule_and_segment_for_entity(
+                        entity,
+                        if self.segment_rules.is_time_dependent() {
+                            now_millis(self.clock.as_ref())
+                        } else {
+                            0
+                        },
+                    )?
                     .unzip()
             }
         };
+        let segment = segment.flatten();
 
-        self.record_evaluation(entity, segment);
+        let value_source = if segment_rule.is_some() {
+            ValueSource::SegmentValue
+        } else {
+            ValueSource::DefaultValue
+        };
+        self.record_evaluation(entity, segment, value_source, None);
 
         match segment_rule {
             Some(segment_rule) => {
@@ -105,6 +230,66 @@ impl PropertySnapshot {
         }
     }
 
+    /// Evaluates the property for the entity and deserializes the value into `T`.
+    ///
+    /// Values of JSON properties are parsed once and reused by later calls on
+    /// this snapshot, as they only change with a new configuration. Values of
+    /// other properties are deserialized as they are, e.g. a NUMERIC property
+    /// into an `u32`. YAML properties are not supported.
+    pub fn get_value_parsed<T: DeserializeOwned>(
+        &self,
+        entity: &(impl Entity + ?Sized),
+    ) -> Result<T> {
+        let value = self.get_current_value(entity)?.value;
+        let parsed = match (value, self.format.as_deref()) {
+            (Value::String(raw), Some("JSON")) => self.parse_json(raw)?,
+            (_, Some("YAML")) => {
+                return Err(Error::UnsupportedPropertyFormat {
+                    property_id: self.property_id.clone(),
+                    format: "YAML".to_string(),
+                });
+            }
+            (value, _) => Arc::new(value.into()),
+        };
+        T::deserialize(parsed.as_ref()).map_err(|e| {
+            DeserializationError {
+                string: format!(
+                    "Error deserializing property '{}' into '{}'",
+                    self.property_id,
+                    std::any::type_name::<T>()
+                ),
+                source: e.into(),
+            }
+            .into()
+        })
+    }
+
+    fn parse_json(&self, raw: String) -> Result<Arc<serde_json::Value>> {
+        // Interpolated values depend on the entity, caching them would grow
+        // without bounds.
+        if self.interpolate_values {
+            return Ok(Arc::new(Self::parse_json_str(&raw)?));
+        }
+        if let Some(parsed) = self.parsed_values.lock_unpoisoned().get(&raw) {
+            return Ok(parsed.clone());
+        }
+        let parsed = Arc::new(Self::parse_json_str(&raw)?);
+        self.parsed_values
+            .lock_unpoisoned()
+            .insert(raw, parsed.clone());
+        Ok(parsed)
+    }
+
+    fn parse_json_str(raw: &str) -> Result<serde_json::Value> {
+        serde_json::from_str(raw).map_err(|e| {
+            DeserializationError {
+                string: raw.to_string(),
+                source: e.into(),
+            }
+            .into()
+        })
+    }
+
     pub fn is_secret_ref(&self) -> bool {
         matches!(self.value_type, ValueType::SecretRef)
     }
@@ -124,21 +309,39 @@ impl Property for PropertySnapshot {
     }
 
     fn get_property_data_format(&self) -> Result<Option<String>> {
-        // If the Format is null or undefined for a String type, we default it to TEXT
-        if self.format.is_none() && self.r#type == "STRING" {
-            return Ok(Some("TEXT".to_string()));
-        }
-        Ok(self.format.clone())
+        Ok(self.format().map(str::to_string))
     }
 
-    fn get_current_value(&self, entity: &impl Entity) -> Result<PropertyEvaluationResult> {
-        let (value, details) = self.evaluate_property_for_entity(entity)?;
+    fn get_current_value(
+        &self,
+        entity: &(impl Entity + ?Sized),
+    ) -> Result<PropertyEvaluationResult> {
+        let (value, details) = self
+            .evaluate_property_for_entity(entity)
+            .map_err(|e| EvaluationError::wrap("property", &self.property_id, entity, e))?;
+        let value = if self.interpolate_values {
+            interpolate_value(value, entity)
+        } else {
+            value
+        };
         Ok(PropertyEvaluationResult { value, details })
     }
 
+    fn get_static_value(&self, strict: bool) -> Result<Value> {
+        if let Some(value) = &self.local_override {
+            return Ok(value.clone());
+        }
+        if strict && !self.segment_rules.is_empty() {
+            return Err(Error::PropertyHasTargetingRules {
+                property_id: self.property_id.clone(),
+            });
+        }
+        Ok(self.value.clone())
+    }
+
     fn get_value_into<T: TryFrom<Value, Error = crate::Error>>(
         &self,
-        entity: &impl Entity,
+        entity: &(impl Entity + ?Sized),
     ) -> Result<T> {
         let value = self.get_current_value(entity).map(|r| r.value)?;
         value.try_into()
@@ -151,7 +354,28 @@ pub mod tests {
     use super::*;
     use crate::network::serialization::fixtures::create_one_segment_rule;
     use crate::network::serialization::{Rule, Segment, ValueType};
-    use std::collections::HashMap;
+    use crate::segment_evaluation::tests::compile_segments;
+
+    #[test]
+    fn test_local_override() {
+        let property = PropertySnapshot::new(
+            Value::Int64(1),
+            TargetingRules::new(HashMap::new(), Vec::new(), ValueType::Numeric, None),
+            ValueType::Numeric,
+            "NUMERIC".to_string(),
+            None,
+            "P1",
+            "p1",
+            None,
+        )
+        .with_local_override(Some(Value::Int64(5)))
+        .unwrap();
+
+        let entity = crate::entity::tests::TrivialEntity {};
+        let result = property.get_current_value(&entity).unwrap();
+        assert_eq!(result.value, Value::Int64(5));
+        assert_eq!(result.details.value_type, "LOCAL_OVERRIDE");
+    }
 
     #[test]
     fn test_get_value_segment_with_default_value() {
@@ -175,8 +399,12 @@ pub mod tests {
                 serde_json::Value::String("$default".into()),
                 serde_json::Value::Number((100).into()),
             );
-            let segment_rules =
-                TargetingRules::new(segments, segment_rules, ValueType::Numeric, None);
+            let segment_rules = TargetingRules::new(
+                compile_segments(segments),
+                segment_rules,
+                ValueType::Numeric,
+                None,
+            );
             PropertySnapshot::new(
                 Value::Int64(-42),
                 segment_rules,
@@ -197,4 +425,173 @@ pub mod tests {
         let value = property.get_current_value(&entity).unwrap();
         assert!(matches!(value.value, Value::Int64(ref v) if v == &(-42)));
     }
+
+    #[test]
+    fn test_get_static_value() {
+        let segments = HashMap::from([(
+            "some_segment_id_1".into(),
+            Segment {
+                name: "".into(),
+                segment_id: "".into(),
+                description: None,
+                tags: None,
+                rules: vec![Rule {
+                    attribute_name: "name".into(),
+                    operator: "is".into(),
+                    values: vec!["heinz".into()],
+                }],
+            },
+        )]);
+        let segment_rules = create_one_segment_rule(
+            "some_segment_id_1".into(),
+            serde_json::Value::Number((100).into()),
+            serde_json::Value::Number((100).into()),
+        );
+        let property = PropertySnapshot::new(
+            Value::Int64(-42),
+            TargetingRules::new(
+                compile_segments(segments),
+                segment_rules,
+                ValueType::Numeric,
+                None,
+            ),
+            ValueType::Numeric,
+            "NUMERIC".to_string(),
+            None,
+            "P1",
+            "p1",
+            None,
+        );
+
+        assert_eq!(property.get_static_value(false).unwrap(), Value::Int64(-42));
+        assert!(matches!(
+            property.get_static_value(true).unwrap_err(),
+            Error::PropertyHasTargetingRules { property_id } if property_id == "p1"
+        ));
+
+        // Local overrides apply to every entity, so also without one
+        let property = property.with_local_override(Some(Value::Int64(5))).unwrap();
+        assert_eq!(property.get_static_value(true).unwrap(), Value::Int64(5));
+
+        // Overrides must have the type of the property
+        assert!(matches!(
+            property.with_local_override(Some(Value::Boolean(true))),
+            Err(Error::LocalOverrideTypeMismatch { id, .. }) if id == "p1"
+        ));
+    }
+
+    #[test]
+    fn test_get_value_with_interpolation() {
+        let property = PropertySnapshot::new(
+            Value::from("https://${attr:region}.example.com/${entity.id}".to_string()),
+            TargetingRules::new(HashMap::new(), vec![], ValueType::String, None),
+            ValueType::String,
+            "STRING".to_string(),
+            None,
+            "P1",
+            "p1",
+            None,
+        );
+        let entity = crate::tests::GenericEntity {
+            id: "a2".into(),
+            attributes: HashMap::from([("region".into(), Value::from("eu-de".to_string()))]),
+        };
+
+        // Opt-in: values are returned verbatim by default
+        let value = property.get_current_value(&entity).unwrap().value;
+        assert_eq!(
+            value,
+            Value::from("https://${attr:region}.example.com/${entity.id}".to_string())
+        );
+
+        let property = property.with_value_interpolation(true);
+        let value = property.get_current_value(&entity).unwrap().value;
+        assert_eq!(
+            value,
+            Value::from("https://eu-de.example.com/a2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_get_value_parsed() {
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Limits {
+            retries: u32,
+            hosts: Vec<String>,
+        }
+
+        let property = PropertySnapshot::new(
+            Value::from(r#"{"retries": 3, "hosts": ["a", "b"]}"#.to_string()),
+            TargetingRules::new(HashMap::new(), vec![], ValueType::String, None),
+            ValueType::String,
+            "STRING".to_string(),
+            Some("JSON".to_string()),
+            "P1",
+            "p1",
+            None,
+        );
+        let entity = crate::entity::tests::TrivialEntity {};
+
+        let expected = Limits {
+            retries: 3,
+            hosts: vec!["a".into(), "b".into()],
+        };
+        assert_eq!(
+            property.get_value_parsed::<Limits>(&entity).unwrap(),
+            expected
+        );
+        assert_eq!(
+            property.get_value_parsed::<Limits>(&entity).unwrap(),
+            expected
+        );
+        assert_eq!(property.parsed_values.lock().unwrap().len(), 1);
+
+        let retries: serde_json::Value = property.get_value_parsed(&entity).unwrap();
+        assert_eq!(retries["retries"], 3);
+
+        let err = property.get_value_parsed::<Vec<u32>>(&entity).unwrap_err();
+        assert!(matches!(err, Error::DeserializationError(_)));
+    }
+
+    #[test]
+    fn test_get_value_parsed_other_formats() {
+        let entity = crate::entity::tests::TrivialEntity {};
+        let property = |value: Value, format: Option<&str>| {
+            PropertySnapshot::new(
+                value,
+                TargetingRules::new(HashMap::new(), vec![], ValueType::String, None),
+                ValueType::String,
+                "STRING".to_string(),
+                format.map(str::to_string),
+                "P1",
+                "p1",
+                None,
+            )
+        };
+
+        let text = property(Value::from("{not json".to_string()), Some("TEXT"));
+        assert_eq!(
+            text.get_value_parsed::<String>(&entity).unwrap(),
+            "{not json"
+        );
+        assert!(text.parsed_values.lock().unwrap().is_empty());
+
+        let numeric = property(Value::Int64(42), None);
+        assert_eq!(numeric.get_value_parsed::<u32>(&entity).unwrap(), 42);
+
+        let yaml = property(Value::from("retries: 3".to_string()), Some("YAML"));
+        let err = yaml
+            .get_value_parsed::<serde_json::Value>(&entity)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UnsupportedPropertyFormat { ref format, .. } if format == "YAML"
+        ));
+
+        let invalid = property(Value::from("{not json".to_string()), Some("JSON"));
+        assert!(matches!(
+            invalid.get_value_parsed::<serde_json::Value>(&entity),
+            Err(Error::DeserializationError(_))
+        ));
+    }
 }
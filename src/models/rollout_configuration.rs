@@ -23,6 +23,11 @@ pub struct RolloutPhase {
     pub duration: Option<u32>,
     /// Duration type: "minutes", "hours", or "days"
     pub duration_type: Option<String>,
+    /// Ramps the percentage linearly, over the duration of the phase, up (or
+    /// down) to the percentage of the next phase, instead of switching to it
+    /// at the end of the phase.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ramp: bool,
 }
 
 /// Configuration for progressive rollout
@@ -35,7 +40,6 @@ pub struct RolloutConfiguration {
 }
 
 /// Rollout type constants — used for future experiment / progressive-rollout checks
-
 pub const ROLLOUT_TYPE_PROGRESSIVE: &str = "PROGRESSIVE";
 
 /// Delimiter used to combine feature_id and rule_id
@@ -38,7 +38,7 @@ impl SecretPropertySnapshot {
 
     pub fn get_current_value(
         &self,
-        entity: &impl Entity,
+        entity: &(impl Entity + ?Sized),
         secret_manager: &impl SecretManager,
     ) -> Result<String> {
         let evaluated = self.property.get_current_value(entity)?;
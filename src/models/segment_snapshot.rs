@@ -0,0 +1,166 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use crate::entity::Entity;
+use crate::errors::Result;
+use crate::network::serialization::Rule;
+use crate::segment_evaluation::{CompiledSegment, MatchesAttributes, entity_attributes};
+
+/// Read-only view of a segment defined in the App Configuration instance.
+///
+/// Like feature and property snapshots, it will not receive updates from the
+/// server once created.
+#[derive(Debug, Clone)]
+pub struct SegmentSnapshot {
+    segment: Arc<CompiledSegment>,
+}
+
+/// A single attribute rule of a [`SegmentSnapshot`].
+///
+/// The rule matches an entity if its attribute `attribute_name` satisfies the
+/// `operator` for ANY of the `values`.
+#[derive(Debug, Clone, Copy)]
+pub struct SegmentRuleSnapshot<'a> {
+    rule: &'a Rule,
+}
+
+impl SegmentSnapshot {
+    pub(crate) fn new(segment: Arc<CompiledSegment>) -> Self {
+        Self { segment }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.segment.segment.segment_id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.segment.segment.name
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.segment.segment.description.as_deref()
+    }
+
+    pub fn tags(&self) -> Option<&str> {
+        self.segment.segment.tags.as_deref()
+    }
+
+    /// Returns the rules of the segment. An entity belongs to the segment
+    /// only if ALL the rules match.
+    pub fn rules(&self) -> Vec<SegmentRuleSnapshot<'_>> {
+        self.segment
+            .segment
+            .rules
+            .iter()
+            .map(|rule| SegmentRuleSnapshot { rule })
+            .collect()
+    }
+
+    /// Returns whether the given entity belongs to this segment.
+    pub fn matches(&self, entity: &(impl Entity + ?Sized)) -> Result<bool> {
+        Ok(self
+            .segment
+            .matches_attributes(&entity_attributes(entity))?)
+    }
+}
+
+impl SegmentRuleSnapshot<'_> {
+    pub fn attribute_name(&self) -> &str {
+        &self.rule.attribute_name
+    }
+
+    pub fn operator(&self) -> &str {
+        &self.rule.operator
+    }
+
+    pub fn values(&self) -> &[String] {
+        &self.rule.values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::Value;
+    use crate::network::serialization::Segment;
+    use crate::tests::GenericEntity;
+
+    fn segment() -> SegmentSnapshot {
+        SegmentSnapshot::new(
+            Segment {
+                name: "Beta users".into(),
+                segment_id: "beta".into(),
+                description: Some("Opted into beta".into()),
+                tags: None,
+                rules: vec![
+                    Rule {
+                        attribute_name: "email".into(),
+                        operator: "endsWith".into(),
+                        values: vec!["@ibm.com".into(), "@example.com".into()],
+                    },
+                    Rule {
+                        attribute_name: "age".into(),
+                        operator: "greaterThanEquals".into(),
+                        values: vec!["18".into()],
+                    },
+                ],
+            }
+            .try_into()
+            .map(Arc::new)
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_segment_accessors() {
+        let segment = segment();
+        assert_eq!(segment.id(), "beta");
+        assert_eq!(segment.name(), "Beta users");
+        assert_eq!(segment.description(), Some("Opted into beta"));
+        assert_eq!(segment.tags(), None);
+
+        let rules = segment.rules();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].attribute_name(), "email");
+        assert_eq!(rules[0].operator(), "endsWith");
+        assert_eq!(rules[0].values(), ["@ibm.com", "@example.com"]);
+    }
+
+    #[test]
+    fn test_segment_matches_all_rules() {
+        let segment = segment();
+        let entity = |email: &str, age: u64| GenericEntity {
+            id: "a".into(),
+            attributes: HashMap::from([
+                ("email".into(), Value::from(email.to_string())),
+                ("age".into(), Value::UInt64(age)),
+            ]),
+        };
+
+        assert!(segment.matches(&entity("alice@example.com", 30)).unwrap());
+        assert!(!segment.matches(&entity("alice@example.com", 12)).unwrap());
+        assert!(!segment.matches(&entity("alice@other.com", 30)).unwrap());
+
+        // Missing attributes never match
+        let anonymous = GenericEntity {
+            id: "b".into(),
+            attributes: HashMap::new(),
+        };
+        assert!(!segment.matches(&anonymous).unwrap());
+    }
+}
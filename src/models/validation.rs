@@ -0,0 +1,120 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::ConfigurationDataError;
+
+/// How [`Configuration`](crate::Configuration) handles invalid resources in the
+/// data received from the server or read from a file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ValidationMode {
+    /// Any invalid feature, property or segment rejects the whole configuration.
+    #[default]
+    Strict,
+    /// Invalid features, properties and segments are dropped and listed in the
+    /// [`ValidationReport`], the rest of the configuration is served.
+    ///
+    /// A segment using a rule operator unknown to this version of the SDK is
+    /// skipped alone: the features and properties targeting it are kept, no
+    /// entity belongs to it and the targeting rules excluding it never apply.
+    Lenient,
+}
+
+/// Kind of a resource skipped while building a [`Configuration`](crate::Configuration).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceKind {
+    Feature,
+    Property,
+    Segment,
+}
+
+impl std::fmt::Display for ResourceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResourceKind::Feature => write!(f, "feature"),
+            ResourceKind::Property => write!(f, "property"),
+            ResourceKind::Segment => write!(f, "segment"),
+        }
+    }
+}
+
+/// A resource left out of a [`Configuration`](crate::Configuration).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedResource {
+    pub kind: ResourceKind,
+    pub id: String,
+    pub reason: String,
+}
+
+/// Resources that were left out while building a [`Configuration`](crate::Configuration).
+///
+/// Resources with a type unknown to this version of the SDK are always skipped.
+/// Invalid resources are only skipped with [`ValidationMode::Lenient`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub skipped: Vec<SkippedResource>,
+}
+
+impl ValidationReport {
+    /// Returns `true` if no resource was skipped.
+    pub fn is_empty(&self) -> bool {
+        self.skipped.is_empty()
+    }
+}
+
+/// Collects the resources skipped while building a configuration.
+pub(crate) struct Validator {
+    mode: ValidationMode,
+    report: ValidationReport,
+}
+
+impl Validator {
+    pub(crate) fn new(mode: ValidationMode) -> Self {
+        Self {
+            mode,
+            report: ValidationReport::default(),
+        }
+    }
+
+    /// Returns the resource if it is valid. Otherwise the error is returned in
+    /// strict mode, and the resource is skipped in lenient mode.
+    pub(crate) fn check<T>(
+        &mut self,
+        kind: ResourceKind,
+        id: &str,
+        result: std::result::Result<T, ConfigurationDataError>,
+    ) -> std::result::Result<Option<T>, ConfigurationDataError> {
+        match (result, self.mode) {
+            (Ok(resource), _) => Ok(Some(resource)),
+            (Err(e), ValidationMode::Strict) => Err(e),
+            (Err(e), ValidationMode::Lenient) => {
+                self.skip(kind, id, e.to_string());
+                Ok(None)
+            }
+        }
+    }
+
+    pub(crate) fn skip(&mut self, kind: ResourceKind, id: &str, reason: impl Into<String>) {
+        let reason = reason.into();
+        log::warn!("Skipping {kind} '{id}': {reason}");
+        self.report.skipped.push(SkippedResource {
+            kind,
+            id: id.to_string(),
+            reason,
+        });
+    }
+
+    pub(crate) fn into_report(self) -> ValidationReport {
+        self.report
+    }
+}
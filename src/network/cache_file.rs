@@ -13,14 +13,21 @@
 // limitations under the License.
 
 use std::fs;
-use std::io::{BufReader, BufWriter};
+use std::io::BufReader;
+#[cfg(feature = "remote")]
+use std::io::Write;
 use std::path::Path;
+#[cfg(feature = "remote")]
+use std::sync::atomic::{AtomicU64, Ordering};
 
+#[cfg(feature = "remote")]
+use crate::CacheCipher;
 use crate::{Error, Result, errors::DeserializationError};
 
 pub(crate) struct CacheFile;
 
 impl CacheFile {
+    #[cfg(feature = "remote")]
     pub(crate) fn read_persistent_cache_string(filepath: &Path) -> String {
         let Ok(exists) = filepath.try_exists() else {
             return String::new();
@@ -36,6 +43,31 @@ impl CacheFile {
         }
     }
 
+    /// Like [`CacheFile::read_persistent_cache_string`], for a file written
+    /// encrypted with `cipher`.
+    #[cfg(feature = "remote")]
+    pub(crate) fn read_encrypted_persistent_cache_string(
+        filepath: &Path,
+        cipher: &CacheCipher,
+    ) -> String {
+        let Ok(ciphertext) = fs::read(filepath) else {
+            return String::new();
+        };
+        match cipher.decrypt(&ciphertext).and_then(|plaintext| {
+            String::from_utf8(plaintext).map_err(|e| Error::Other(e.to_string()))
+        }) {
+            Ok(contents) => contents.trim().to_string(),
+            Err(e) => {
+                log::warn!(
+                    "Ignoring the persistent cache at '{}': {e}",
+                    filepath.display()
+                );
+                String::new()
+            }
+        }
+    }
+
+    #[cfg(feature = "remote")]
     pub(crate) fn read_bootstrap_string(filepath: &Path) -> Result<String> {
         let exists = filepath.try_exists().map_err(|e| {
             Error::Other(format!(
@@ -71,6 +103,15 @@ impl CacheFile {
         Ok(trimmed)
     }
 
+    pub(crate) fn read_file(filepath: &Path) -> Result<String> {
+        fs::read_to_string(filepath).map_err(|_| {
+            Error::Other(format!(
+                "File '{}' doesn't exist or cannot be read",
+                filepath.display()
+            ))
+        })
+    }
+
     pub(crate) fn read_json_file<T>(filepath: &Path) -> Result<T>
     where
         T: serde::de::DeserializeOwned,
@@ -94,11 +135,42 @@ impl CacheFile {
         })
     }
 
-    #[allow(dead_code)]
-    pub(crate) fn write_json_file<T>(value: &T, filepath: &Path) -> Result<()>
-    where
-        T: serde::Serialize,
-    {
+    /// Replaces the file at `filepath` with `contents` at once: they are
+    /// written to a temporary file of the same directory, flushed to the disk,
+    /// and renamed over it. A crash meanwhile keeps the previous file.
+    #[cfg(feature = "remote")]
+    pub(crate) fn write_file(contents: &[u8], filepath: &Path) -> Result<()> {
+        Self::create_parent_directory(filepath)?;
+        let write_error = |e: std::io::Error| {
+            Error::Other(format!(
+                "Failed to write configuration file '{}': {}",
+                filepath.display(),
+                e
+            ))
+        };
+        // Unique among the clients writing the same file
+        static WRITES: AtomicU64 = AtomicU64::new(0);
+        let mut temporary_name = filepath.file_name().unwrap_or_default().to_os_string();
+        temporary_name.push(format!(
+            ".{}-{}.tmp",
+            std::process::id(),
+            WRITES.fetch_add(1, Ordering::Relaxed)
+        ));
+        let temporary_path = filepath.with_file_name(temporary_name);
+
+        let written = fs::File::create(&temporary_path).and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        });
+        if let Err(e) = written.and_then(|()| fs::rename(&temporary_path, filepath)) {
+            let _ = fs::remove_file(&temporary_path);
+            return Err(write_error(e));
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "remote")]
+    fn create_parent_directory(filepath: &Path) -> Result<()> {
         if let Some(parent) = filepath.parent() {
             fs::create_dir_all(parent).map_err(|e| {
                 Error::Other(format!(
@@ -108,27 +180,10 @@ impl CacheFile {
                 ))
             })?;
         }
-
-        let file = fs::File::create(filepath).map_err(|e| {
-            Error::Other(format!(
-                "Failed to create configuration file '{}': {}",
-                filepath.display(),
-                e
-            ))
-        })?;
-        let writer = BufWriter::new(file);
-
-        serde_json::to_writer(writer, value).map_err(|e| {
-            Error::DeserializationError(DeserializationError {
-                string: format!(
-                    "Error serializing Configuration to file '{}'",
-                    filepath.display()
-                ),
-                source: e.into(),
-            })
-        })
+        Ok(())
     }
 
+    #[cfg(feature = "remote")]
     pub(crate) fn delete_file_data(filepath: &Path) {
         let Ok(exists) = filepath.try_exists() else {
             return;
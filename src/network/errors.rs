@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::sync::PoisonError;
+use std::time::Duration;
 
 use thiserror::Error;
 
@@ -20,9 +20,11 @@ use crate::ConfigurationDataError;
 
 #[derive(Debug, Error)]
 pub enum NetworkError {
+    #[cfg(feature = "remote")]
     #[error(transparent)]
     ReqwestError(#[from] reqwest::Error),
 
+    #[cfg(feature = "remote")]
     #[error(transparent)]
     TungsteniteError(#[from] tungstenite::Error),
 
@@ -35,9 +37,6 @@ pub enum NetworkError {
     #[error("Invalid header value for '{0}'")]
     InvalidHeaderValue(String),
 
-    #[error("Cannot acquire lock")]
-    CannotAcquireLock,
-
     #[error("Contact to server lost")]
     ContactToServerLost,
 
@@ -50,6 +49,11 @@ pub enum NetworkError {
     #[error("{0}")]
     TokenProviderError(String),
 
+    #[error(
+        "IAM rejected the access token request. Status code: {status_code}. Message: {message}"
+    )]
+    TokenRequestRejected { status_code: u16, message: String },
+
     #[error("Deserialization error: {0}")]
     DeserializationError(String),
 
@@ -57,10 +61,351 @@ pub enum NetworkError {
         "Websocket connect request to the App Configuration server failed. Status code: {status_code}. Message: {message}"
     )]
     WebsocketHttpStatus { status_code: u16, message: String },
+
+    #[error("Too many requests to the App Configuration server")]
+    RateLimited {
+        /// Delay requested by the server in the `Retry-After` header.
+        retry_after: Option<Duration>,
+    },
+
+    #[error(
+        "The maximum number of configuration fetches per hour was reached. Next fetch in {retry_after:?}"
+    )]
+    FetchBudgetExhausted {
+        /// Time until a fetch fits in the budget again.
+        retry_after: Duration,
+    },
+
+    #[error("The App Configuration server rejected the access token")]
+    Unauthorized,
+
+    #[error("The App Configuration server failed to process the request. Status code: {0}")]
+    ServerError(u16),
+
+    #[error(
+        "Configuration not found for instance '{guid}', environment '{environment_id}' and collection '{collection_id}'. Check the IDs of the client"
+    )]
+    ConfigurationNotFound {
+        guid: String,
+        environment_id: String,
+        collection_id: String,
+    },
+}
+
+/// Where a [`NetworkError`] comes from, to tell a problem of the local network
+/// from one of the credentials or of the service, see [`NetworkError::category`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NetworkErrorCategory {
+    /// The host name of the server could not be resolved.
+    Dns,
+    /// The TLS handshake failed, e.g. the certificate of the server is not trusted.
+    Tls,
+    /// The server did not answer in time.
+    Timeout,
+    /// No connection could be established, e.g. it was refused.
+    Connect,
+    /// The connection failed while the response was read, or was closed.
+    Read,
+    /// The credentials were rejected, or no access token could be obtained.
+    Auth,
+    /// The server answered with an error status.
+    Status,
+    /// The server answered with data the SDK does not understand.
+    Protocol,
+    /// The request could not be built from the settings of the client, e.g. an
+    /// invalid URL or header, or they did not allow sending it yet.
+    Settings,
+}
+
+impl std::fmt::Display for NetworkErrorCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            NetworkErrorCategory::Dns => "dns",
+            NetworkErrorCategory::Tls => "tls",
+            NetworkErrorCategory::Timeout => "timeout",
+            NetworkErrorCategory::Connect => "connect",
+            NetworkErrorCategory::Read => "read",
+            NetworkErrorCategory::Auth => "auth",
+            NetworkErrorCategory::Status => "status",
+            NetworkErrorCategory::Protocol => "protocol",
+            NetworkErrorCategory::Settings => "settings",
+        })
+    }
 }
 
-impl<T> From<PoisonError<T>> for NetworkError {
-    fn from(_value: PoisonError<T>) -> Self {
-        NetworkError::CannotAcquireLock
+impl NetworkError {
+    /// Classifies the error, e.g. to tell a problem of the local network from
+    /// one of the credentials or of the service.
+    pub fn category(&self) -> NetworkErrorCategory {
+        match self {
+            #[cfg(feature = "remote")]
+            NetworkError::ReqwestError(e) => reqwest_error_category(e),
+            #[cfg(feature = "remote")]
+            NetworkError::TungsteniteError(e) => tungstenite_error_category(e),
+            NetworkError::ProtocolError
+            | NetworkError::ConfigurationDataError(_)
+            | NetworkError::DeserializationError(_) => NetworkErrorCategory::Protocol,
+            NetworkError::UrlParseError(_)
+            | NetworkError::InvalidHeaderValue(_)
+            | NetworkError::FetchBudgetExhausted { .. } => NetworkErrorCategory::Settings,
+            NetworkError::ContactToServerLost => NetworkErrorCategory::Read,
+            NetworkError::WebsocketTimeout => NetworkErrorCategory::Timeout,
+            NetworkError::TokenProviderError(_) | NetworkError::Unauthorized => {
+                NetworkErrorCategory::Auth
+            }
+            NetworkError::WebsocketHttpStatus { status_code, .. } => status_category(*status_code),
+            NetworkError::TokenRequestRejected { status_code, .. } => {
+                if is_transient_token_rejection(*status_code) {
+                    NetworkErrorCategory::Status
+                } else {
+                    NetworkErrorCategory::Auth
+                }
+            }
+            NetworkError::RateLimited { .. }
+            | NetworkError::ServerError(_)
+            | NetworkError::ConfigurationNotFound { .. } => NetworkErrorCategory::Status,
+        }
+    }
+
+    /// Whether a later attempt may succeed. Otherwise the error depends on the
+    /// settings of the client, and the background thread stops.
+    #[cfg(feature = "remote")]
+    pub(crate) fn is_recoverable(&self) -> bool {
+        match self {
+            NetworkError::UrlParseError(_)
+            | NetworkError::InvalidHeaderValue(_)
+            | NetworkError::ConfigurationNotFound { .. } => false,
+            NetworkError::WebsocketHttpStatus { status_code, .. } => {
+                !(400..500).contains(status_code) || *status_code == 429
+            }
+            NetworkError::ReqwestError(_) | NetworkError::TungsteniteError(_) => true,
+            NetworkError::ProtocolError
+            | NetworkError::ContactToServerLost
+            | NetworkError::ConfigurationDataError(_)
+            | NetworkError::WebsocketTimeout
+            | NetworkError::TokenProviderError(_)
+            | NetworkError::DeserializationError(_)
+            | NetworkError::RateLimited { .. }
+            | NetworkError::FetchBudgetExhausted { .. }
+            | NetworkError::ServerError(_) => true,
+            // The access token was renewed already, it may be a transient IAM issue
+            NetworkError::Unauthorized => true,
+            // The credentials may be replaced meanwhile, see
+            // `AppConfigurationClientIBMCloud::update_api_key`
+            NetworkError::TokenRequestRejected { .. } => true,
+        }
+    }
+
+    /// HTTP status the server answered the failed request with, if any.
+    #[cfg(feature = "remote")]
+    pub(crate) fn http_status(&self) -> Option<u16> {
+        match self {
+            NetworkError::ReqwestError(e) => e.status().map(|status| status.as_u16()),
+            NetworkError::TungsteniteError(tungstenite::Error::Http(response)) => {
+                Some(response.status().as_u16())
+            }
+            NetworkError::WebsocketHttpStatus { status_code, .. }
+            | NetworkError::TokenRequestRejected { status_code, .. } => Some(*status_code),
+            NetworkError::RateLimited { .. } => Some(429),
+            NetworkError::Unauthorized => Some(401),
+            NetworkError::ServerError(status) => Some(*status),
+            NetworkError::ConfigurationNotFound { .. } => Some(404),
+            _ => None,
+        }
+    }
+}
+
+/// Whether IAM may accept a token request later after rejecting it with
+/// `status_code`: it was rate limited or failed.
+pub(crate) fn is_transient_token_rejection(status_code: u16) -> bool {
+    matches!(status_code, 429 | 500..)
+}
+
+fn status_category(status_code: u16) -> NetworkErrorCategory {
+    match status_code {
+        401 | 403 => NetworkErrorCategory::Auth,
+        _ => NetworkErrorCategory::Status,
+    }
+}
+
+/// Whether an error in the sources of a transport error mentions one of `needles`.
+/// Neither reqwest nor tungstenite tell DNS and TLS failures apart otherwise.
+#[cfg(feature = "remote")]
+fn mentions(error: &(dyn std::error::Error + 'static), needles: &[&str]) -> bool {
+    std::iter::successors(Some(error), |e| e.source()).any(|e| {
+        let message = e.to_string().to_ascii_lowercase();
+        needles.iter().any(|needle| message.contains(needle))
+    })
+}
+
+#[cfg(feature = "remote")]
+const DNS_ERROR_MESSAGES: [&str; 3] = [
+    "dns error",
+    "failed to lookup address",
+    "name or service not known",
+];
+
+#[cfg(feature = "remote")]
+const TLS_ERROR_MESSAGES: [&str; 4] = ["certificate", "tls", "ssl", "handshake"];
+
+#[cfg(feature = "remote")]
+fn reqwest_error_category(error: &reqwest::Error) -> NetworkErrorCategory {
+    if let Some(status) = error.status() {
+        status_category(status.as_u16())
+    } else if error.is_timeout() {
+        NetworkErrorCategory::Timeout
+    } else if mentions(error, &DNS_ERROR_MESSAGES) {
+        NetworkErrorCategory::Dns
+    } else if mentions(error, &TLS_ERROR_MESSAGES) {
+        NetworkErrorCategory::Tls
+    } else if error.is_connect() {
+        NetworkErrorCategory::Connect
+    } else if error.is_decode() {
+        NetworkErrorCategory::Protocol
+    } else if error.is_builder() {
+        NetworkErrorCategory::Settings
+    } else {
+        NetworkErrorCategory::Read
+    }
+}
+
+#[cfg(feature = "remote")]
+fn tungstenite_error_category(error: &tungstenite::Error) -> NetworkErrorCategory {
+    use std::io::ErrorKind;
+    match error {
+        tungstenite::Error::Http(response) => status_category(response.status().as_u16()),
+        tungstenite::Error::Tls(_) => NetworkErrorCategory::Tls,
+        tungstenite::Error::Url(_) | tungstenite::Error::HttpFormat(_) => {
+            NetworkErrorCategory::Settings
+        }
+        tungstenite::Error::Io(e) => match e.kind() {
+            ErrorKind::TimedOut | ErrorKind::WouldBlock => NetworkErrorCategory::Timeout,
+            ErrorKind::ConnectionRefused
+            | ErrorKind::HostUnreachable
+            | ErrorKind::NetworkUnreachable
+            | ErrorKind::AddrNotAvailable => NetworkErrorCategory::Connect,
+            _ if mentions(e, &DNS_ERROR_MESSAGES) => NetworkErrorCategory::Dns,
+            _ => NetworkErrorCategory::Read,
+        },
+        tungstenite::Error::ConnectionClosed
+        | tungstenite::Error::AlreadyClosed
+        | tungstenite::Error::AttackAttempt => NetworkErrorCategory::Read,
+        _ => NetworkErrorCategory::Protocol,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_category() {
+        assert_eq!(
+            NetworkError::Unauthorized.category(),
+            NetworkErrorCategory::Auth
+        );
+        assert_eq!(
+            NetworkError::WebsocketHttpStatus {
+                status_code: 403,
+                message: String::new(),
+            }
+            .category(),
+            NetworkErrorCategory::Auth
+        );
+        assert_eq!(
+            NetworkError::ServerError(503).category(),
+            NetworkErrorCategory::Status
+        );
+        assert_eq!(
+            NetworkError::WebsocketTimeout.category(),
+            NetworkErrorCategory::Timeout
+        );
+        assert_eq!(
+            NetworkError::UrlParseError("url".into()).category(),
+            NetworkErrorCategory::Settings
+        );
+        assert_eq!(
+            NetworkError::TungsteniteError(tungstenite::Error::Io(std::io::Error::from(
+                std::io::ErrorKind::ConnectionRefused
+            )))
+            .category(),
+            NetworkErrorCategory::Connect
+        );
+        assert_eq!(
+            NetworkError::TungsteniteError(tungstenite::Error::Io(std::io::Error::other(
+                "failed to lookup address information: Name or service not known"
+            )))
+            .category(),
+            NetworkErrorCategory::Dns
+        );
+        assert_eq!(
+            NetworkError::TungsteniteError(tungstenite::Error::ConnectionClosed).category(),
+            NetworkErrorCategory::Read
+        );
+    }
+
+    #[test]
+    fn test_reqwest_error_category() {
+        // Nothing listens anymore on the port the listener was given
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let error = reqwest::blocking::get(format!("http://127.0.0.1:{port}")).unwrap_err();
+        assert_eq!(
+            NetworkError::ReqwestError(error).category(),
+            NetworkErrorCategory::Connect
+        );
+    }
+
+    #[test]
+    #[ignore = "resolves a name through the DNS servers of the host"]
+    fn test_reqwest_dns_error_category() {
+        let error = reqwest::blocking::get("http://host.invalid").unwrap_err();
+        assert_eq!(
+            NetworkError::ReqwestError(error).category(),
+            NetworkErrorCategory::Dns
+        );
+    }
+
+    #[test]
+    fn test_is_recoverable() {
+        assert!(NetworkError::Unauthorized.is_recoverable());
+        assert!(NetworkError::ServerError(500).is_recoverable());
+        assert!(
+            !NetworkError::ConfigurationNotFound {
+                guid: String::new(),
+                environment_id: String::new(),
+                collection_id: String::new(),
+            }
+            .is_recoverable()
+        );
+        assert!(
+            !NetworkError::WebsocketHttpStatus {
+                status_code: 404,
+                message: String::new(),
+            }
+            .is_recoverable()
+        );
+        assert!(
+            !NetworkError::WebsocketHttpStatus {
+                status_code: 400,
+                message: String::new(),
+            }
+            .is_recoverable()
+        );
+        let token_request_rejected = |status_code| NetworkError::TokenRequestRejected {
+            status_code,
+            message: String::new(),
+        };
+        assert!(token_request_rejected(429).is_recoverable());
+        assert!(token_request_rejected(503).is_recoverable());
+        assert!(token_request_rejected(400).is_recoverable());
+        assert!(token_request_rejected(401).is_recoverable());
+        assert_eq!(
+            token_request_rejected(401).category(),
+            NetworkErrorCategory::Auth
+        );
     }
 }
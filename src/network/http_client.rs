@@ -12,18 +12,29 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::{NetworkError, NetworkResult, TokenProvider};
-use crate::ConfigurationId;
-use crate::models::Configuration;
+use super::{NetworkError, NetworkResult, OutgoingRequest, RequestAuthenticator};
+use crate::models::{Configuration, ConfigurationSource};
 use crate::network::serialization::ConfigurationJson;
-use reqwest::blocking::{Client, ClientBuilder};
-use reqwest::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue, USER_AGENT};
-use std::sync::Arc;
-use std::time::Duration;
+use crate::utils::MutexExt;
+use crate::{ConfigurationId, LatencyHistogram, SyncRecord};
+use reqwest::StatusCode;
+use reqwest::blocking::{Client, ClientBuilder, Response};
+use reqwest::header::{
+    ACCEPT, CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue, RETRY_AFTER, USER_AGENT,
+};
+use std::collections::VecDeque;
+#[cfg(not(feature = "simd-json"))]
+use std::io::BufReader;
+use std::io::Read;
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use tungstenite::client::IntoClientRequest;
 
-use tungstenite::connect;
+use tungstenite::HandshakeError;
+use tungstenite::client_tls;
 use tungstenite::stream::MaybeTlsStream;
 use url::Url;
 
@@ -44,12 +55,13 @@ impl std::fmt::Display for ServiceAddressProtocol {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ServiceAddress {
     host: String,
     port: Option<u16>,
     endpoint: Option<String>,
     use_ssl: bool,
+    custom_headers: Vec<(String, String)>,
 }
 
 impl ServiceAddress {
@@ -59,6 +71,7 @@ impl ServiceAddress {
             port,
             endpoint,
             use_ssl: true,
+            custom_headers: Vec::new(),
         }
     }
 
@@ -68,9 +81,33 @@ impl ServiceAddress {
             port,
             endpoint,
             use_ssl: false,
+            custom_headers: Vec::new(),
         }
     }
 
+    /// Adds an HTTP header sent with every request to this address: configuration
+    /// fetches, the websocket handshake and metering.
+    ///
+    /// Useful to traverse API gateways that route on custom headers. Headers set by
+    /// the SDK itself (e.g. `Authorization`) take precedence.
+    pub fn with_custom_header(mut self, name: &str, value: &str) -> Self {
+        self.custom_headers
+            .push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub(crate) fn custom_headers(&self) -> NetworkResult<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.custom_headers {
+            let header_name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| NetworkError::InvalidHeaderValue(name.clone()))?;
+            let header_value = HeaderValue::from_str(value)
+                .map_err(|_| NetworkError::InvalidHeaderValue(name.clone()))?;
+            headers.append(header_name, header_value);
+        }
+        Ok(headers)
+    }
+
     pub(crate) fn base_url(&self, protocol: ServiceAddressProtocol) -> String {
         let port = if let Some(port) = self.port {
             format!(":{port}")
@@ -88,6 +125,49 @@ impl ServiceAddress {
     }
 }
 
+/// Maps the status codes of a configuration request that need specific handling
+/// to their [`NetworkError`].
+fn check_configuration_response_status(
+    response: Response,
+    configuration_id: &ConfigurationId,
+) -> NetworkResult<Response> {
+    match response.status() {
+        StatusCode::TOO_MANY_REQUESTS => Err(NetworkError::RateLimited {
+            retry_after: response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_retry_after),
+        }),
+        StatusCode::UNAUTHORIZED => Err(NetworkError::Unauthorized),
+        StatusCode::NOT_FOUND => Err(NetworkError::ConfigurationNotFound {
+            guid: configuration_id.guid.clone(),
+            environment_id: configuration_id.environment_id.clone(),
+            collection_id: configuration_id.collection_id.clone(),
+        }),
+        status if status.is_server_error() => Err(NetworkError::ServerError(status.as_u16())),
+        _ => response
+            .error_for_status()
+            .map_err(NetworkError::ReqwestError),
+    }
+}
+
+/// Parses the value of a `Retry-After` header: either a number of seconds or
+/// an HTTP date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(seconds) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    Some(
+        (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// Stream of the messages sent by the server when the configuration changes,
+/// see [`ServerClient::get_configuration_monitoring_websocket`].
 pub trait WebsocketReader: Send + 'static {
     /// Reads a message from the stream, if possible. If the connection have been closed,
     /// this will also return the close message
@@ -109,52 +189,443 @@ impl<T: std::io::Read + std::io::Write + Send + Sync + 'static> WebsocketReader
     }
 }
 
+impl<W: WebsocketReader + ?Sized> WebsocketReader for Box<W> {
+    fn read_msg(&mut self) -> tungstenite::error::Result<tungstenite::Message> {
+        (**self).read_msg()
+    }
+
+    fn flush_socket(&mut self) -> tungstenite::error::Result<()> {
+        (**self).flush_socket()
+    }
+}
+
+/// Transport used by a client to download the configuration and to be notified
+/// when it changes.
+///
+/// The SDK implements it over HTTP and a websocket to the App Configuration
+/// service. Implement it to fetch the configuration from another backend, e.g.
+/// a proxy or a test double, and pass it to
+/// [`AppConfigurationClientIBMCloud::new_with_server_client`](crate::AppConfigurationClientIBMCloud::new_with_server_client).
+///
+/// A configuration is downloaded when the client starts and every time the
+/// websocket receives a message other than a heartbeat. When the websocket
+/// fails or is closed the client goes offline, and calls
+/// [`get_configuration_monitoring_websocket`](ServerClient::get_configuration_monitoring_websocket)
+/// again to reconnect.
 pub trait ServerClient: Send + 'static {
-    #[allow(dead_code)]
-    fn get_configuration(&self, configuration_id: &ConfigurationId)
-    -> NetworkResult<Configuration>;
+    /// Downloads the configuration, see [`ConfigurationJson::from_json_str`] to
+    /// build it from the payload sent by the server.
+    fn get_configuration_json(
+        &self,
+        configuration_id: &ConfigurationId,
+    ) -> NetworkResult<ConfigurationJson>;
+
+    /// Downloads the configuration and builds the features and properties of
+    /// its environment and collection.
+    fn get_configuration(
+        &self,
+        configuration_id: &ConfigurationId,
+    ) -> NetworkResult<Configuration> {
+        let config_json = self.get_configuration_json(configuration_id)?;
+
+        Ok(Configuration::new(
+            &configuration_id.environment_id,
+            &configuration_id.collection_id,
+            config_json,
+        )?
+        .with_source(ConfigurationSource::Live))
+    }
 
+    /// Opens the stream of notifications of configuration changes.
     fn get_configuration_monitoring_websocket(
         &self,
         collection: &ConfigurationId,
-    ) -> NetworkResult<impl WebsocketReader>;
+    ) -> NetworkResult<Box<dyn WebsocketReader>>;
+
+    /// Number of service addresses the client can fail over between.
+    fn service_address_count(&self) -> usize {
+        1
+    }
+
+    /// Index of the service address the requests are currently sent to, `0`
+    /// being the primary one.
+    fn active_service_address(&self) -> usize {
+        0
+    }
+
+    /// Sends the following requests to the service address at `index`.
+    fn set_active_service_address(&self, _index: usize) {}
+}
 
+impl<T: ServerClient + ?Sized> ServerClient for Box<T> {
     fn get_configuration_json(
         &self,
-        _configuration_id: &ConfigurationId,
+        configuration_id: &ConfigurationId,
     ) -> NetworkResult<ConfigurationJson> {
-        // Default implementation is intentionally unimplemented. Concrete types
-        // that fetch raw JSON from a remote server must override this method.
-        // Using `unimplemented!()` here instead of a silent error ensures that
-        // any implementor that forgets to override this will fail loudly at
-        // runtime rather than silently performing double work and returning an
-        // error after the network round-trip.
-        unimplemented!(
-            "get_configuration_json must be overridden by concrete ServerClient implementations"
-        )
+        (**self).get_configuration_json(configuration_id)
+    }
+
+    fn get_configuration(
+        &self,
+        configuration_id: &ConfigurationId,
+    ) -> NetworkResult<Configuration> {
+        (**self).get_configuration(configuration_id)
+    }
+
+    fn get_configuration_monitoring_websocket(
+        &self,
+        collection: &ConfigurationId,
+    ) -> NetworkResult<Box<dyn WebsocketReader>> {
+        (**self).get_configuration_monitoring_websocket(collection)
+    }
+
+    fn service_address_count(&self) -> usize {
+        (**self).service_address_count()
+    }
+
+    fn active_service_address(&self) -> usize {
+        (**self).active_service_address()
+    }
+
+    fn set_active_service_address(&self, index: usize) {
+        (**self).set_active_service_address(index)
+    }
+}
+
+/// A [`ServerClient`] shared by the update thread and the threads downloading
+/// configurations on demand, see
+/// [`ConfigurationProvider::fresh_configuration`](crate::ConfigurationProvider::fresh_configuration).
+#[derive(Clone)]
+pub(crate) struct SharedServerClient(Arc<dyn ServerClient + Sync>);
+
+impl SharedServerClient {
+    pub(crate) fn new(server_client: impl ServerClient + Sync) -> Self {
+        Self(Arc::new(server_client))
+    }
+
+    /// For the clients that cannot be used by several threads at once: their
+    /// requests are sent one at a time.
+    pub(crate) fn new_serialized(server_client: impl ServerClient) -> Self {
+        Self(Arc::new(SerializedServerClient(Mutex::new(server_client))))
+    }
+}
+
+impl std::fmt::Debug for SharedServerClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedServerClient").finish_non_exhaustive()
+    }
+}
+
+impl ServerClient for SharedServerClient {
+    fn get_configuration_json(
+        &self,
+        configuration_id: &ConfigurationId,
+    ) -> NetworkResult<ConfigurationJson> {
+        self.0.get_configuration_json(configuration_id)
+    }
+
+    fn get_configuration(
+        &self,
+        configuration_id: &ConfigurationId,
+    ) -> NetworkResult<Configuration> {
+        self.0.get_configuration(configuration_id)
+    }
+
+    fn get_configuration_monitoring_websocket(
+        &self,
+        collection: &ConfigurationId,
+    ) -> NetworkResult<Box<dyn WebsocketReader>> {
+        self.0.get_configuration_monitoring_websocket(collection)
+    }
+
+    fn service_address_count(&self) -> usize {
+        self.0.service_address_count()
+    }
+
+    fn active_service_address(&self) -> usize {
+        self.0.active_service_address()
+    }
+
+    fn set_active_service_address(&self, index: usize) {
+        self.0.set_active_service_address(index)
+    }
+}
+
+/// See [`SharedServerClient::new_serialized`].
+struct SerializedServerClient<T>(Mutex<T>);
+
+impl<T: ServerClient> ServerClient for SerializedServerClient<T> {
+    fn get_configuration_json(
+        &self,
+        configuration_id: &ConfigurationId,
+    ) -> NetworkResult<ConfigurationJson> {
+        self.0
+            .lock_unpoisoned()
+            .get_configuration_json(configuration_id)
+    }
+
+    fn get_configuration(
+        &self,
+        configuration_id: &ConfigurationId,
+    ) -> NetworkResult<Configuration> {
+        self.0.lock_unpoisoned().get_configuration(configuration_id)
+    }
+
+    fn get_configuration_monitoring_websocket(
+        &self,
+        collection: &ConfigurationId,
+    ) -> NetworkResult<Box<dyn WebsocketReader>> {
+        self.0
+            .lock_unpoisoned()
+            .get_configuration_monitoring_websocket(collection)
+    }
+
+    fn service_address_count(&self) -> usize {
+        self.0.lock_unpoisoned().service_address_count()
+    }
+
+    fn active_service_address(&self) -> usize {
+        self.0.lock_unpoisoned().active_service_address()
+    }
+
+    fn set_active_service_address(&self, index: usize) {
+        self.0.lock_unpoisoned().set_active_service_address(index)
+    }
+}
+
+/// Response headers holding the id of a request, in order of preference.
+const REQUEST_ID_HEADERS: [&str; 3] = [
+    "x-correlation-id",
+    "x-request-id",
+    "x-global-transaction-id",
+];
+
+/// Number of downloads kept in a [`SyncHistory`].
+const SYNC_HISTORY_LEN: usize = 32;
+
+/// Bucket bounds of the latency histogram of the downloads. Requests time out
+/// after 30 seconds.
+const LATENCY_BUCKETS: [Duration; 9] = [
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_millis(2500),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+    Duration::from_secs(30),
+];
+
+/// The most recent downloads of the configuration, see
+/// [`crate::ConfigurationProvider::sync_history`], and the latencies of all of
+/// them.
+#[derive(Debug)]
+pub(crate) struct SyncHistory {
+    records: Mutex<VecDeque<SyncRecord>>,
+    latency: Mutex<LatencyHistogram>,
+}
+
+impl Default for SyncHistory {
+    fn default() -> Self {
+        Self {
+            records: Mutex::default(),
+            latency: Mutex::new(LatencyHistogram::with_bounds(&LATENCY_BUCKETS)),
+        }
+    }
+}
+
+impl SyncHistory {
+    fn push(&self, record: SyncRecord) {
+        self.latency.lock_unpoisoned().record(record.latency);
+        let mut records = self.records.lock_unpoisoned();
+        if records.len() == SYNC_HISTORY_LEN {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    pub(crate) fn records(&self) -> Vec<SyncRecord> {
+        self.records.lock_unpoisoned().iter().cloned().collect()
+    }
+
+    pub(crate) fn latency(&self) -> LatencyHistogram {
+        self.latency.lock_unpoisoned().clone()
+    }
+}
+
+/// Counts the bytes read from `inner`.
+struct CountingReader<R> {
+    inner: R,
+    count: u64,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.count += read as u64;
+        Ok(read)
+    }
+}
+
+/// Deserializes the configuration while the (decompressed) body is read,
+/// instead of buffering it.
+#[cfg(not(feature = "simd-json"))]
+fn parse_configuration_body(body: impl Read) -> NetworkResult<serde_json::Value> {
+    serde_json::from_reader(BufReader::new(body)).map_err(|_| NetworkError::ProtocolError)
+}
+
+/// Reads the whole body and parses it with SIMD instructions, which is faster
+/// for large configurations than parsing while it is streamed.
+#[cfg(feature = "simd-json")]
+fn parse_configuration_body(mut body: impl Read) -> NetworkResult<serde_json::Value> {
+    let mut buffer = Vec::new();
+    body.read_to_end(&mut buffer)
+        .map_err(|_| NetworkError::ProtocolError)?;
+    simd_json::serde::from_slice(&mut buffer).map_err(|_| NetworkError::ProtocolError)
+}
+
+/// Options of the TCP connections to the server, for the websocket and the
+/// configuration requests.
+///
+/// Idle websockets only see a heartbeat from the server every minute or so,
+/// which some NAT gateways and firewalls consider too long: keepalive probes
+/// keep the connection known to them, instead of having it dropped silently.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SocketOptions {
+    /// Idle time before the operating system sends TCP keepalive probes.
+    /// `None` (the default) leaves keepalive disabled.
+    pub keepalive: Option<Duration>,
+
+    /// Disables Nagle's algorithm, so small frames are sent right away. `true`
+    /// by default.
+    pub nodelay: bool,
+
+    /// Time allowed to establish each TCP connection. `None` (the default)
+    /// waits for as long as the operating system does.
+    pub connect_timeout: Option<Duration>,
+}
+
+impl Default for SocketOptions {
+    fn default() -> Self {
+        Self {
+            keepalive: None,
+            nodelay: true,
+            connect_timeout: None,
+        }
+    }
+}
+
+impl SocketOptions {
+    /// Sets [`SocketOptions::keepalive`].
+    pub fn with_keepalive(mut self, idle: Duration) -> Self {
+        self.keepalive = Some(idle);
+        self
+    }
+
+    /// Sets [`SocketOptions::nodelay`].
+    pub fn with_nodelay(mut self, nodelay: bool) -> Self {
+        self.nodelay = nodelay;
+        self
+    }
+
+    /// Sets [`SocketOptions::connect_timeout`].
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Connects to the first of the addresses of `url` accepting the connection.
+    fn connect(&self, url: &Url) -> std::io::Result<TcpStream> {
+        let mut last_error = None;
+        for address in url.socket_addrs(|| None)? {
+            let connected = match self.connect_timeout {
+                Some(timeout) => TcpStream::connect_timeout(&address, timeout),
+                None => TcpStream::connect(address),
+            };
+            match connected {
+                Ok(stream) => {
+                    self.apply(&stream)?;
+                    return Ok(stream);
+                }
+                Err(e) => {
+                    log::debug!("[WEBSOCKET] Cannot connect to {}: {}", address, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| std::io::ErrorKind::AddrNotAvailable.into()))
+    }
+
+    fn apply(&self, stream: &TcpStream) -> std::io::Result<()> {
+        stream.set_nodelay(self.nodelay)?;
+        if let Some(idle) = self.keepalive {
+            socket2::SockRef::from(stream)
+                .set_tcp_keepalive(&socket2::TcpKeepalive::new().with_time(idle))?;
+        }
+        Ok(())
     }
 }
 
 #[derive(Debug)]
 pub(crate) struct ServerClientImpl {
-    service_address: ServiceAddress,
-    token_provider: Arc<Box<dyn TokenProvider>>,
+    /// The primary address first, followed by the fallbacks in priority order.
+    service_addresses: Vec<ServiceAddress>,
+    active_address: AtomicUsize,
+    authenticator: Arc<dyn RequestAuthenticator>,
+    sync_history: Arc<SyncHistory>,
+    socket_options: SocketOptions,
 }
 
 impl ServerClientImpl {
     pub fn new(
         service_address: ServiceAddress,
-        token_provider: Arc<Box<dyn TokenProvider>>,
+        authenticator: Arc<dyn RequestAuthenticator>,
     ) -> NetworkResult<Self> {
         Ok(Self {
-            service_address,
-            token_provider,
+            service_addresses: vec![service_address],
+            active_address: AtomicUsize::new(0),
+            authenticator,
+            sync_history: Arc::default(),
+            socket_options: SocketOptions::default(),
         })
     }
 
-    fn build_http_client() -> NetworkResult<Client> {
-        ClientBuilder::new()
+    /// Appends `addresses` to the ones the client fails over to when the
+    /// primary address is unreachable.
+    pub fn with_fallback_addresses(mut self, addresses: Vec<ServiceAddress>) -> Self {
+        self.service_addresses.extend(addresses);
+        self
+    }
+
+    /// Sets the options of the TCP connections opened by the client.
+    pub fn with_socket_options(mut self, socket_options: SocketOptions) -> Self {
+        self.socket_options = socket_options;
+        self
+    }
+
+    /// The downloads of the configuration made by this client.
+    pub fn sync_history(&self) -> Arc<SyncHistory> {
+        self.sync_history.clone()
+    }
+
+    fn service_address(&self) -> &ServiceAddress {
+        &self.service_addresses[self.active_address.load(Ordering::SeqCst)]
+    }
+
+    fn build_http_client(&self) -> NetworkResult<Client> {
+        let mut builder = ClientBuilder::new()
             .timeout(Duration::from_secs(30))
+            .connect_timeout(self.socket_options.connect_timeout)
+            .tcp_nodelay(self.socket_options.nodelay);
+        // Otherwise keeps the default keepalive of reqwest
+        if let Some(idle) = self.socket_options.keepalive {
+            builder = builder.tcp_keepalive(idle);
+        }
+        // Configurations are large JSON documents that compress well
+        builder
+            .gzip(true)
+            .deflate(true)
             .build()
             .map_err(NetworkError::ReqwestError)
     }
@@ -171,64 +642,100 @@ impl ServerClientImpl {
         headers
     }
 
-    fn build_authorization_header(&self) -> NetworkResult<HeaderValue> {
-        let bearer = format!("Bearer {}", self.token_provider.get_access_token()?);
-        HeaderValue::from_str(&bearer)
-            .map_err(|_| NetworkError::InvalidHeaderValue("Authorization".to_string()))
-    }
-}
-
-impl ServerClient for ServerClientImpl {
-    fn get_configuration(
+    /// Downloads the configuration, recording the id and the size of the response.
+    fn fetch_configuration_json(
         &self,
         configuration_id: &ConfigurationId,
-    ) -> NetworkResult<Configuration> {
-        let config_json = self.get_configuration_json(configuration_id)?;
+        record: &mut SyncRecord,
+    ) -> NetworkResult<ConfigurationJson> {
+        let client = self.build_http_client()?;
+        let mut response = self.send_configuration_request(&client, configuration_id)?;
+        if response.status() == StatusCode::UNAUTHORIZED {
+            // The token may have been revoked before its expiration: retry once with a new one
+            log::info!("Configuration request not authorized, renewing the access token");
+            self.authenticator.invalidate_credentials();
+            response = self.send_configuration_request(&client, configuration_id)?;
+        }
+        record.request_id = REQUEST_ID_HEADERS
+            .iter()
+            .find_map(|name| response.headers().get(*name)?.to_str().ok())
+            .map(str::to_string);
 
-        Ok(Configuration::new(
-            &configuration_id.environment_id,
-            &configuration_id.collection_id,
-            config_json,
-        )?)
+        let mut body = CountingReader {
+            inner: check_configuration_response_status(response, configuration_id)?,
+            count: 0,
+        };
+        let started = Instant::now();
+        let result = parse_configuration_body(&mut body).and_then(|payload| {
+            ConfigurationJson::from_payload(payload).map_err(|_| NetworkError::ProtocolError)
+        });
+        record.parse_duration = started.elapsed();
+        record.payload_bytes = body.count;
+        result
     }
 
-    fn get_configuration_json(
+    fn send_configuration_request(
         &self,
+        client: &Client,
         configuration_id: &ConfigurationId,
-    ) -> NetworkResult<ConfigurationJson> {
-        log::debug!("Fetching configuration JSON from server");
+    ) -> NetworkResult<Response> {
         let url = format!(
             "{}/feature/v1/instances/{}/config",
-            self.service_address.base_url(ServiceAddressProtocol::Http),
+            self.service_address()
+                .base_url(ServiceAddressProtocol::Http),
             configuration_id.guid
         );
-        let client = Self::build_http_client()?;
-        let mut headers = Self::build_default_headers(false);
-        headers.insert(AUTHORIZATION, self.build_authorization_header()?);
+        let mut url = Url::parse(&url).map_err(|_| NetworkError::UrlParseError(url))?;
+        url.query_pairs_mut()
+            .append_pair("action", "sdkConfig")
+            .append_pair("environment_id", &configuration_id.environment_id)
+            .append_pair("collection_id", &configuration_id.collection_id);
+        let mut headers = self.service_address().custom_headers()?;
+        headers.extend(Self::build_default_headers(false));
+        self.authenticator.authenticate(&mut OutgoingRequest::new(
+            "GET",
+            &mut url,
+            &mut headers,
+            &[],
+        ))?;
 
         client
             .get(url)
-            .query(&[
-                ("action", "sdkConfig"),
-                ("environment_id", &configuration_id.environment_id),
-                ("collection_id", &configuration_id.collection_id),
-            ])
             .headers(headers)
             .send()
-            .map_err(NetworkError::ReqwestError)?
-            .error_for_status()
-            .map_err(NetworkError::ReqwestError)?
-            .json::<ConfigurationJson>()
-            .map_err(|_| NetworkError::ProtocolError)
+            .map_err(NetworkError::ReqwestError)
+    }
+}
+
+impl ServerClient for ServerClientImpl {
+    fn get_configuration_json(
+        &self,
+        configuration_id: &ConfigurationId,
+    ) -> NetworkResult<ConfigurationJson> {
+        log::debug!("Fetching configuration JSON from server");
+        let started = Instant::now();
+        let mut record = SyncRecord {
+            timestamp: chrono::Utc::now(),
+            latency: Duration::ZERO,
+            parse_duration: Duration::ZERO,
+            payload_bytes: 0,
+            request_id: None,
+            error: None,
+        };
+        let result = self.fetch_configuration_json(configuration_id, &mut record);
+        record.latency = started.elapsed();
+        record.error = result.as_ref().err().map(ToString::to_string);
+        self.sync_history.push(record);
+        result
     }
 
     fn get_configuration_monitoring_websocket(
         &self,
         collection: &ConfigurationId,
-    ) -> NetworkResult<impl WebsocketReader> {
+    ) -> NetworkResult<Box<dyn WebsocketReader>> {
         let ws_url = format!(
             "{}/wsfeature",
-            self.service_address.base_url(ServiceAddressProtocol::Ws)
+            self.service_address().base_url(ServiceAddressProtocol::Ws)
         );
         let mut ws_url = Url::parse(&ws_url).map_err(|_| NetworkError::UrlParseError(ws_url))?;
 
@@ -238,37 +745,53 @@ impl ServerClient for ServerClientImpl {
             .append_pair("environment_id", &collection.environment_id)
             .append_pair("collection_id", &collection.collection_id);
 
+        let mut headers = self.service_address().custom_headers()?;
+        headers.insert(USER_AGENT, HeaderValue::from_static(SDK_USER_AGENT));
+        self.authenticator.authenticate(&mut OutgoingRequest::new(
+            "GET",
+            &mut ws_url,
+            &mut headers,
+            &[],
+        ))?;
+
         let mut request = ws_url
             .as_str()
             .into_client_request()
             .map_err(NetworkError::TungsteniteError)?;
-        let headers = request.headers_mut();
-        headers.insert(USER_AGENT, HeaderValue::from_static(SDK_USER_AGENT));
-        headers.insert(AUTHORIZATION, self.build_authorization_header()?);
+        request.headers_mut().extend(headers);
         log::debug!(
             "[WEBSOCKET] Establishing WebSocket connection to {}",
             ws_url
         );
-        let (mut websocket, response) = connect(request).map_err(|error| match error {
-            tungstenite::Error::Http(response) => {
-                log::warn!(
-                    "[WEBSOCKET] HTTP error during WebSocket handshake: {}",
-                    response.status().as_str()
-                );
-                NetworkError::WebsocketHttpStatus {
-                    status_code: response.status().as_u16(),
-                    message: response
-                        .status()
-                        .canonical_reason()
-                        .unwrap_or("Unknown websocket HTTP error")
-                        .to_string(),
-                }
-            }
-            other => {
-                log::warn!("[WEBSOCKET] Connection error: {}", other);
-                NetworkError::TungsteniteError(other)
-            }
+        let stream = self.socket_options.connect(&ws_url).map_err(|error| {
+            log::warn!("[WEBSOCKET] Connection error: {}", error);
+            NetworkError::TungsteniteError(tungstenite::Error::Io(error))
         })?;
+        let (mut websocket, response) =
+            client_tls(request, stream).map_err(|error| match error {
+                HandshakeError::Failure(tungstenite::Error::Http(response)) => {
+                    log::warn!(
+                        "[WEBSOCKET] HTTP error during WebSocket handshake: {}",
+                        response.status().as_str()
+                    );
+                    NetworkError::WebsocketHttpStatus {
+                        status_code: response.status().as_u16(),
+                        message: response
+                            .status()
+                            .canonical_reason()
+                            .unwrap_or("Unknown websocket HTTP error")
+                            .to_string(),
+                    }
+                }
+                HandshakeError::Failure(other) => {
+                    log::warn!("[WEBSOCKET] Connection error: {}", other);
+                    NetworkError::TungsteniteError(other)
+                }
+                // The stream is blocking, the handshake is never interrupted
+                HandshakeError::Interrupted(_) => NetworkError::TungsteniteError(
+                    tungstenite::Error::Io(std::io::ErrorKind::WouldBlock.into()),
+                ),
+            })?;
         let _ = response;
         let timeout_duration = Duration::from_secs(WEBSOCKET_READ_TIMEOUT_SECS);
 
@@ -285,13 +808,44 @@ impl ServerClient for ServerClientImpl {
             log::error!("Failed to set TCP read timeout: {:?}", e);
         }
         log::debug!("[WEBSOCKET] Connection established successfully");
-        Ok(websocket)
+        Ok(Box::new(websocket))
+    }
+
+    fn service_address_count(&self) -> usize {
+        self.service_addresses.len()
+    }
+
+    fn active_service_address(&self) -> usize {
+        self.active_address.load(Ordering::SeqCst)
+    }
+
+    fn set_active_service_address(&self, index: usize) {
+        if index < self.service_addresses.len() {
+            self.active_address.store(index, Ordering::SeqCst);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::network::TokenProvider;
+
+    #[test]
+    fn test_parse_configuration_body() {
+        let document = std::fs::read("data/data-dump-enterprise-plan-sdk-testing.json").unwrap();
+        let payload = parse_configuration_body(document.as_slice()).unwrap();
+        assert_eq!(
+            payload,
+            serde_json::from_slice::<serde_json::Value>(&document).unwrap()
+        );
+        assert!(ConfigurationJson::from_payload(payload).is_ok());
+
+        assert!(matches!(
+            parse_configuration_body(&b"{\"environments\": ["[..]),
+            Err(NetworkError::ProtocolError)
+        ));
+    }
 
     #[test]
     fn test_non_ssl_base_url() {
@@ -324,6 +878,375 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_custom_headers() {
+        let address = ServiceAddress::new("ibm.com".to_string(), None, None)
+            .with_custom_header("X-Internal-Routing", "blue")
+            .with_custom_header("X-Client-Identity", "svc-a");
+        let headers = address.custom_headers().unwrap();
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers["x-internal-routing"], "blue");
+        assert_eq!(headers["x-client-identity"], "svc-a");
+
+        let address =
+            ServiceAddress::new("ibm.com".to_string(), None, None).with_custom_header("a b", "c");
+        assert!(matches!(
+            address.custom_headers(),
+            Err(NetworkError::InvalidHeaderValue(name)) if name == "a b"
+        ));
+    }
+
+    #[derive(Debug)]
+    struct MockTokenProvider {}
+
+    impl TokenProvider for MockTokenProvider {
+        fn get_access_token(&self) -> NetworkResult<String> {
+            Ok("mocked_token".to_string())
+        }
+    }
+
+    /// Body of a configuration without environments.
+    fn empty_configuration() -> serde_json::Value {
+        serde_json::json!({"environments": [], "segments": []})
+    }
+
+    fn configuration_id(collection_id: &str) -> ConfigurationId {
+        ConfigurationId::new(
+            "guid".to_string(),
+            "dev".to_string(),
+            collection_id.to_string(),
+        )
+        .unwrap()
+    }
+
+    fn address(server: &httpmock::MockServer) -> ServiceAddress {
+        ServiceAddress::new_without_ssl(server.host(), Some(server.port()), None)
+    }
+
+    /// A client of `server`, authenticated with [`MockTokenProvider`].
+    fn mock_server_client(server: &httpmock::MockServer) -> ServerClientImpl {
+        ServerClientImpl::new(address(server), Arc::new(MockTokenProvider {})).unwrap()
+    }
+
+    #[test]
+    fn test_custom_headers_sent_with_configuration_request() {
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/feature/v1/instances/guid/config")
+                .header("X-Internal-Routing", "blue")
+                .header("Authorization", "Bearer mocked_token");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(empty_configuration());
+        });
+
+        let address = address(&server).with_custom_header("X-Internal-Routing", "blue");
+        let client = ServerClientImpl::new(address, Arc::new(MockTokenProvider {})).unwrap();
+
+        assert!(
+            client
+                .get_configuration_json(&configuration_id("collection"))
+                .is_ok()
+        );
+        mock.assert();
+    }
+
+    /// Signs the path and query of each request, the way an API gateway could expect.
+    #[derive(Debug)]
+    struct SigningAuthenticator;
+
+    impl RequestAuthenticator for SigningAuthenticator {
+        fn authenticate(&self, request: &mut OutgoingRequest<'_>) -> NetworkResult<()> {
+            request.append_query_pair("key_id", "k1");
+            let signature = format!(
+                "{} {}?{}",
+                request.method(),
+                request.path(),
+                request.query().unwrap_or_default()
+            );
+            request.set_header("X-Signature", &signature.len().to_string())
+        }
+    }
+
+    #[test]
+    fn test_custom_request_authenticator() {
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/feature/v1/instances/guid/config")
+                .query_param("action", "sdkConfig")
+                .query_param("key_id", "k1")
+                .header("X-Signature", "108")
+                .header_missing("Authorization");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(empty_configuration());
+        });
+
+        let client =
+            ServerClientImpl::new(address(&server), Arc::new(SigningAuthenticator)).unwrap();
+
+        assert!(
+            client
+                .get_configuration_json(&configuration_id("collection"))
+                .is_ok()
+        );
+        mock.assert();
+    }
+
+    #[test]
+    fn test_requests_sent_to_active_service_address() {
+        let primary = httpmock::MockServer::start();
+        let fallback = httpmock::MockServer::start();
+        let mocks = [&primary, &fallback].map(|server| {
+            server.mock(|when, then| {
+                when.method(httpmock::Method::GET)
+                    .path("/feature/v1/instances/guid/config");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(empty_configuration());
+            })
+        });
+
+        let client = mock_server_client(&primary).with_fallback_addresses(vec![address(&fallback)]);
+        assert_eq!(client.service_address_count(), 2);
+
+        client.set_active_service_address(1);
+        assert!(
+            client
+                .get_configuration_json(&configuration_id("collection"))
+                .is_ok()
+        );
+        mocks[0].assert_calls(0);
+        mocks[1].assert_calls(1);
+
+        // Out of range indexes are ignored
+        client.set_active_service_address(2);
+        assert_eq!(client.active_service_address(), 1);
+
+        client.set_active_service_address(0);
+        assert!(
+            client
+                .get_configuration_json(&configuration_id("collection"))
+                .is_ok()
+        );
+        mocks[0].assert_calls(1);
+    }
+
+    /// Returns `token-<n>`, where `n` is the number of times it was invalidated.
+    #[derive(Debug, Default)]
+    struct RenewingTokenProvider {
+        renewals: std::sync::atomic::AtomicUsize,
+    }
+
+    impl TokenProvider for RenewingTokenProvider {
+        fn get_access_token(&self) -> NetworkResult<String> {
+            let renewals = self.renewals.load(std::sync::atomic::Ordering::SeqCst);
+            Ok(format!("token-{renewals}"))
+        }
+
+        fn invalidate_access_token(&self) {
+            self.renewals
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_configuration_request_renews_rejected_token() {
+        let server = httpmock::MockServer::start();
+        let rejected = server.mock(|when, then| {
+            when.header("Authorization", "Bearer token-0");
+            then.status(401);
+        });
+        let accepted = server.mock(|when, then| {
+            when.header("Authorization", "Bearer token-1");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(empty_configuration());
+        });
+
+        let client =
+            ServerClientImpl::new(address(&server), Arc::new(RenewingTokenProvider::default()))
+                .unwrap();
+
+        assert!(
+            client
+                .get_configuration_json(&configuration_id("collection"))
+                .is_ok()
+        );
+        rejected.assert();
+        accepted.assert();
+    }
+
+    #[test]
+    fn test_configuration_request_status_codes() {
+        let server = httpmock::MockServer::start();
+        let client = mock_server_client(&server);
+        let request =
+            |collection_id: &str| client.get_configuration_json(&configuration_id(collection_id));
+        server.mock(|when, then| {
+            when.query_param("collection_id", "limited");
+            then.status(429).header("Retry-After", "120");
+        });
+        server.mock(|when, then| {
+            when.query_param("collection_id", "missing");
+            then.status(404);
+        });
+        server.mock(|when, then| {
+            when.query_param("collection_id", "failing");
+            then.status(503);
+        });
+        server.mock(|when, then| {
+            when.query_param("collection_id", "unauthorized");
+            then.status(401);
+        });
+
+        assert!(matches!(
+            request("limited"),
+            Err(NetworkError::RateLimited { retry_after: Some(d) }) if d == Duration::from_secs(120)
+        ));
+        let error = request("missing").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Configuration not found for instance 'guid', environment 'dev' and collection 'missing'. Check the IDs of the client"
+        );
+        assert!(matches!(
+            request("failing"),
+            Err(NetworkError::ServerError(503))
+        ));
+        assert!(matches!(
+            request("unauthorized"),
+            Err(NetworkError::Unauthorized)
+        ));
+    }
+
+    #[test]
+    fn test_compressed_configuration() {
+        use std::io::Write;
+
+        let body = empty_configuration().to_string();
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(body.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let server = httpmock::MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.header_includes("Accept-Encoding", "gzip");
+            then.status(200)
+                .header("content-type", "application/json")
+                .header("content-encoding", "gzip")
+                .body(compressed);
+        });
+
+        let client = mock_server_client(&server);
+
+        let configuration = client
+            .get_configuration_json(&configuration_id("collection"))
+            .unwrap();
+        assert!(configuration.environments.is_empty());
+        mock.assert();
+    }
+
+    #[test]
+    fn test_sync_history() {
+        let body = empty_configuration().to_string();
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.query_param("collection_id", "ok");
+            then.status(200)
+                .header("content-type", "application/json")
+                .header("X-Request-Id", "req-1")
+                .header("X-Global-Transaction-Id", "txn-1")
+                .body(&body);
+        });
+        server.mock(|when, then| {
+            when.query_param("collection_id", "failing");
+            then.status(503).header("X-Global-Transaction-Id", "txn-2");
+        });
+
+        let client = mock_server_client(&server);
+        let request =
+            |collection_id: &str| client.get_configuration_json(&configuration_id(collection_id));
+        assert!(request("ok").is_ok());
+        assert!(request("failing").is_err());
+
+        let records = client.sync_history().records();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].request_id.as_deref(), Some("req-1"));
+        assert_eq!(records[0].payload_bytes, body.len() as u64);
+        assert!(records[0].parse_duration <= records[0].latency);
+        assert_eq!(records[0].error, None);
+        assert_eq!(records[1].request_id.as_deref(), Some("txn-2"));
+        assert_eq!(records[1].payload_bytes, 0);
+        assert_eq!(
+            records[1].error.as_deref(),
+            Some("The App Configuration server failed to process the request. Status code: 503")
+        );
+        assert!(records[0].timestamp <= records[1].timestamp);
+
+        let latency = client.sync_history().latency();
+        assert_eq!(latency.count, 2);
+        assert_eq!(latency.sum, records[0].latency + records[1].latency);
+        assert_eq!(latency.buckets.len(), LATENCY_BUCKETS.len());
+        // Local requests answer within the last bucket
+        assert_eq!(latency.buckets.last(), Some(&(Duration::from_secs(30), 2)));
+
+        // Only the most recent downloads are kept
+        for _ in 0..SYNC_HISTORY_LEN {
+            assert!(request("ok").is_ok());
+        }
+        let records = client.sync_history().records();
+        assert_eq!(records.len(), SYNC_HISTORY_LEN);
+        assert!(records.iter().all(|record| record.error.is_none()));
+        // The histogram covers all the downloads, not only those kept
+        assert_eq!(
+            client.sync_history().latency().count,
+            2 + SYNC_HISTORY_LEN as u64
+        );
+    }
+
+    #[test]
+    fn test_socket_options() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!(
+            "ws://{}/wsfeature",
+            listener.local_addr().unwrap()
+        ))
+        .unwrap();
+
+        let stream = SocketOptions::default().connect(&url).unwrap();
+        assert!(stream.nodelay().unwrap());
+        assert!(!socket2::SockRef::from(&stream).keepalive().unwrap());
+
+        let options = SocketOptions::default()
+            .with_keepalive(Duration::from_secs(30))
+            .with_nodelay(false)
+            .with_connect_timeout(Duration::from_secs(5));
+        let stream = options.connect(&url).unwrap();
+        assert!(!stream.nodelay().unwrap());
+        let socket = socket2::SockRef::from(&stream);
+        assert!(socket.keepalive().unwrap());
+        #[cfg(target_os = "linux")]
+        assert_eq!(
+            socket.tcp_keepalive_time().unwrap(),
+            Duration::from_secs(30)
+        );
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("30"), Some(Duration::from_secs(30)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        let in_a_minute = (chrono::Utc::now() + chrono::Duration::seconds(60)).to_rfc2822();
+        let delay = parse_retry_after(&in_a_minute).unwrap();
+        assert!(delay > Duration::from_secs(55) && delay <= Duration::from_secs(60));
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
     #[test]
     fn test_url_with_port() {
         let address = ServiceAddress::new_without_ssl("ibm.com".to_string(), Some(12345), None);
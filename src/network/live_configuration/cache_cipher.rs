@@ -0,0 +1,69 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+type CipherFn = dyn Fn(&[u8]) -> Result<Vec<u8>, String> + Send + Sync;
+
+/// Encrypts the configuration written to the persistent cache file, which is
+/// otherwise stored as plain JSON.
+///
+/// The SDK does not implement any cipher: the callbacks use the ones of the
+/// application, e.g. AES-GCM with a key read from a keyring. A cache file that
+/// cannot be decrypted, e.g. after the key was rotated, is ignored and
+/// replaced by the next configuration received from the server.
+#[derive(Clone)]
+pub struct CacheCipher {
+    encrypt: Arc<CipherFn>,
+    decrypt: Arc<CipherFn>,
+}
+
+impl CacheCipher {
+    pub fn new(
+        encrypt: impl Fn(&[u8]) -> Result<Vec<u8>, String> + Send + Sync + 'static,
+        decrypt: impl Fn(&[u8]) -> Result<Vec<u8>, String> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            encrypt: Arc::new(encrypt),
+            decrypt: Arc::new(decrypt),
+        }
+    }
+
+    #[cfg(feature = "remote")]
+    pub(crate) fn encrypt(&self, plaintext: &[u8]) -> crate::Result<Vec<u8>> {
+        (self.encrypt)(plaintext)
+            .map_err(|e| crate::Error::Other(format!("Failed to encrypt the cache file: {e}")))
+    }
+
+    #[cfg(feature = "remote")]
+    pub(crate) fn decrypt(&self, ciphertext: &[u8]) -> crate::Result<Vec<u8>> {
+        (self.decrypt)(ciphertext)
+            .map_err(|e| crate::Error::Other(format!("Failed to decrypt the cache file: {e}")))
+    }
+}
+
+impl std::fmt::Debug for CacheCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CacheCipher").finish_non_exhaustive()
+    }
+}
+
+/// Two ciphers are equal if they share the same callbacks.
+impl PartialEq for CacheCipher {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.encrypt, &other.encrypt) && Arc::ptr_eq(&self.decrypt, &other.decrypt)
+    }
+}
+
+impl Eq for CacheCipher {}
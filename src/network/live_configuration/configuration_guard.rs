@@ -0,0 +1,69 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use crate::models::{Configuration, ConfigurationDiff};
+
+type GuardFn = dyn Fn(&Configuration, &ConfigurationDiff) -> GuardDecision + Send + Sync;
+
+/// Outcome of a [`ConfigurationGuard`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuardDecision {
+    /// The new configuration replaces the current one.
+    Accept,
+    /// The current configuration is kept. The reason is reported to the error
+    /// listeners as [`SdkError::ConfigurationRejected`](crate::SdkError::ConfigurationRejected).
+    Reject(String),
+}
+
+/// Checks every configuration received from the server before it replaces the
+/// current one, e.g. to enforce that some feature always exists.
+///
+/// The callback gets the new configuration and its differences with the
+/// current one (or with an empty configuration, if there is none yet).
+#[derive(Clone)]
+pub struct ConfigurationGuard(Arc<GuardFn>);
+
+impl ConfigurationGuard {
+    pub fn new(
+        guard: impl Fn(&Configuration, &ConfigurationDiff) -> GuardDecision + Send + Sync + 'static,
+    ) -> Self {
+        Self(Arc::new(guard))
+    }
+
+    #[cfg(feature = "remote")]
+    pub(crate) fn check(
+        &self,
+        configuration: &Configuration,
+        diff: &ConfigurationDiff,
+    ) -> GuardDecision {
+        (self.0)(configuration, diff)
+    }
+}
+
+impl std::fmt::Debug for ConfigurationGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ConfigurationGuard").finish_non_exhaustive()
+    }
+}
+
+/// Two guards are equal if they share the same callback.
+impl PartialEq for ConfigurationGuard {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for ConfigurationGuard {}
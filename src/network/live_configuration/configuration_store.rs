@@ -0,0 +1,257 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use super::FetchBudget;
+use super::configuration_guard::{ConfigurationGuard, GuardDecision};
+use crate::errors::ConfigurationDataError;
+use crate::models::{Configuration, ConfigurationSource, ValidationMode};
+use crate::network::serialization::ConfigurationJson;
+use crate::utils::{MutexExt, panic_message};
+use crate::{CacheCipher, ConfigurationId, SharedCache};
+
+/// Outcome of [`ConfigurationStore::apply`].
+#[derive(Debug)]
+pub(crate) enum Applied {
+    /// The configuration served from now on. It is an earlier one if another
+    /// thread applied a configuration requested later meanwhile.
+    Accepted(Arc<Configuration>),
+
+    /// The configuration failed the verifications or a configuration guard,
+    /// the previous one is still served.
+    Rejected(String),
+}
+
+/// Verifies the configurations downloaded from the server, by the update thread
+/// or on demand, and stores the accepted ones in the configuration served and
+/// in the caches. Both kinds of downloads share its [`FetchBudget`].
+#[derive(Clone)]
+pub(crate) struct ConfigurationStore {
+    configuration_id: ConfigurationId,
+    configuration: Arc<Mutex<Option<Arc<Configuration>>>>,
+    /// When the stored configuration was requested, `None` until one is
+    /// applied. Held while applying, so configurations are applied one at a
+    /// time and in the order they were requested.
+    applied_at: Arc<Mutex<Option<Instant>>>,
+    validation_mode: ValidationMode,
+    expected_schema_version: Option<u32>,
+    configuration_guards: Vec<ConfigurationGuard>,
+    persistent_cache_path: Option<PathBuf>,
+    cache_cipher: Option<CacheCipher>,
+    shared_cache: Option<SharedCache>,
+    fetch_budget: FetchBudget,
+}
+
+impl std::fmt::Debug for ConfigurationStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConfigurationStore")
+            .field("configuration_id", &self.configuration_id)
+            .field("validation_mode", &self.validation_mode)
+            .field("expected_schema_version", &self.expected_schema_version)
+            .field("persistent_cache_path", &self.persistent_cache_path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ConfigurationStore {
+    pub(crate) fn new(
+        configuration_id: ConfigurationId,
+        configuration: Arc<Mutex<Option<Arc<Configuration>>>>,
+    ) -> Self {
+        Self {
+            configuration_id,
+            configuration,
+            applied_at: Arc::default(),
+            validation_mode: ValidationMode::default(),
+            expected_schema_version: None,
+            configuration_guards: Vec::new(),
+            persistent_cache_path: None,
+            cache_cipher: None,
+            shared_cache: None,
+            fetch_budget: FetchBudget::default(),
+        }
+    }
+
+    pub(crate) fn with_validation_mode(mut self, validation_mode: ValidationMode) -> Self {
+        self.validation_mode = validation_mode;
+        self
+    }
+
+    pub(crate) fn with_expected_schema_version(mut self, version: Option<u32>) -> Self {
+        self.expected_schema_version = version;
+        self
+    }
+
+    pub(crate) fn with_configuration_guards(mut self, guards: Vec<ConfigurationGuard>) -> Self {
+        self.configuration_guards = guards;
+        self
+    }
+
+    pub(crate) fn with_persistent_cache_file(mut self, path: impl AsRef<Path>) -> Self {
+        self.persistent_cache_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    pub(crate) fn with_cache_cipher(mut self, cipher: Option<CacheCipher>) -> Self {
+        self.cache_cipher = cipher;
+        self
+    }
+
+    pub(crate) fn with_shared_cache(mut self, shared_cache: Option<SharedCache>) -> Self {
+        self.shared_cache = shared_cache;
+        self
+    }
+
+    pub(crate) fn with_fetch_budget(mut self, fetch_budget: FetchBudget) -> Self {
+        self.fetch_budget = fetch_budget;
+        self
+    }
+
+    pub(crate) fn configuration_id(&self) -> &ConfigurationId {
+        &self.configuration_id
+    }
+
+    pub(crate) fn shared_cache(&self) -> Option<&SharedCache> {
+        self.shared_cache.as_ref()
+    }
+
+    pub(crate) fn fetch_budget(&self) -> &FetchBudget {
+        &self.fetch_budget
+    }
+
+    /// Where the configuration served is stored.
+    #[cfg(test)]
+    pub(crate) fn configuration(&self) -> Arc<Mutex<Option<Arc<Configuration>>>> {
+        self.configuration.clone()
+    }
+
+    /// The configuration served and when it was requested from the server,
+    /// if one was applied.
+    pub(crate) fn latest(&self) -> Option<(Instant, Arc<Configuration>)> {
+        let applied_at = (*self.applied_at.lock_unpoisoned())?;
+        let configuration = self.configuration.lock_unpoisoned().clone()?;
+        Some((applied_at, configuration))
+    }
+
+    /// Verifies `config_json`, requested from the server at `requested_at`,
+    /// and serves it if it is accepted. `fetched_at` is when the server sent
+    /// it, `None` if it was read from the shared cache.
+    ///
+    /// Fails if the configuration cannot be parsed.
+    pub(crate) fn apply(
+        &self,
+        mut config_json: ConfigurationJson,
+        requested_at: Instant,
+        fetched_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> std::result::Result<Applied, ConfigurationDataError> {
+        let mut applied_at = self.applied_at.lock_unpoisoned();
+        if applied_at.is_some_and(|applied_at| applied_at > requested_at)
+            && let Some(configuration) = self.configuration.lock_unpoisoned().clone()
+        {
+            return Ok(Applied::Accepted(configuration));
+        }
+
+        if let Err(e) = config_json
+            .verify_schema_version(self.expected_schema_version)
+            .and_then(|()| config_json.verify_checksum())
+        {
+            return Ok(Applied::Rejected(e.to_string()));
+        }
+        // Already verified, the persistent cache file gets a new one
+        config_json.checksum = None;
+        // Written once the configuration is accepted
+        let cache_json = self
+            .persistent_cache_path
+            .as_ref()
+            .map(|path| (path, config_json.clone()));
+        // Shared with other clients once accepted, unless read from there
+        let shared_json = self
+            .shared_cache
+            .as_ref()
+            .zip(fetched_at)
+            .map(|(cache, fetched_at)| (cache, config_json.clone(), fetched_at));
+
+        let config = Configuration::new_with_validation_mode(
+            &self.configuration_id.environment_id,
+            &self.configuration_id.collection_id,
+            config_json,
+            self.validation_mode,
+        )?
+        .with_source(if fetched_at.is_some() {
+            ConfigurationSource::Live
+        } else {
+            ConfigurationSource::SharedCache
+        });
+
+        if let Some(reason) = self.guard_rejection(&config) {
+            return Ok(Applied::Rejected(reason));
+        }
+
+        if let Some((path, config_json)) = cache_json {
+            if let Err(e) = config_json.write_to_file(path, self.cache_cipher.as_ref()) {
+                log::warn!(
+                    "Failed to write configuration to persistent cache at '{}': {}",
+                    path.display(),
+                    e
+                );
+            } else {
+                log::debug!(
+                    "Successfully wrote configuration to persistent cache at '{}'",
+                    path.display()
+                );
+            }
+        }
+
+        if let Some((cache, config_json, fetched_at)) = shared_json {
+            cache.store(&self.configuration_id, &config_json, fetched_at);
+        }
+
+        let config = Arc::new(config);
+        *self.configuration.lock_unpoisoned() = Some(config.clone());
+        *applied_at = Some(requested_at);
+        Ok(Applied::Accepted(config))
+    }
+
+    /// Runs the configuration guards on `configuration`, returning the reason
+    /// of the first one rejecting it or panicking.
+    fn guard_rejection(&self, configuration: &Configuration) -> Option<String> {
+        if self.configuration_guards.is_empty() {
+            return None;
+        }
+        let diff = match &*self.configuration.lock_unpoisoned() {
+            Some(current) => current.diff(configuration),
+            None => Configuration::default().diff(configuration),
+        };
+        self.configuration_guards.iter().find_map(|guard| {
+            // A panicking guard rejects the configuration, instead of
+            // stopping the thread applying it
+            let decision = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                guard.check(configuration, &diff)
+            }))
+            .unwrap_or_else(|payload| {
+                GuardDecision::Reject(format!(
+                    "Configuration guard failed: {}",
+                    panic_message(&*payload)
+                ))
+            });
+            match decision {
+                GuardDecision::Accept => None,
+                GuardDecision::Reject(reason) => Some(reason),
+            }
+        })
+    }
+}
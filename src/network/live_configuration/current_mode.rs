@@ -12,8 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "remote")]
 use super::Result;
 
+#[cfg(feature = "remote")]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum CurrentMode {
     Online,
@@ -30,6 +32,8 @@ pub enum CurrentModeOfflineReason {
     WebsocketError,
     InternetConnectivityError,
     WebsocketHeartbeatTimeout,
+    /// Safe mode was entered with [`OfflineMode::Fail`](crate::OfflineMode::Fail).
+    SafeMode,
 }
 
 impl std::fmt::Display for CurrentModeOfflineReason {
@@ -47,6 +51,103 @@ impl std::fmt::Display for CurrentModeOfflineReason {
             CurrentModeOfflineReason::WebsocketHeartbeatTimeout => {
                 write!(f, "WebsocketHeartbeatTimeout")
             }
+            CurrentModeOfflineReason::SafeMode => write!(f, "SafeMode"),
         }
     }
 }
+
+/// Why and since when the client is offline, in [`Error::Offline`](super::Error::Offline).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OfflineDetails {
+    pub reason: CurrentModeOfflineReason,
+    /// HTTP status of the last failed request, if the server answered it.
+    pub http_status: Option<u16>,
+    /// When the client went offline.
+    pub since: chrono::DateTime<chrono::Utc>,
+    /// When the client tries to get online again, if a retry is scheduled.
+    pub next_retry_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl OfflineDetails {
+    #[cfg(feature = "remote")]
+    pub(crate) fn new(reason: CurrentModeOfflineReason) -> Self {
+        Self {
+            reason,
+            http_status: None,
+            since: chrono::Utc::now(),
+            next_retry_at: None,
+        }
+    }
+
+    /// Suggests how to get the client online again.
+    pub fn help(&self) -> &'static str {
+        match (self.http_status, &self.reason) {
+            (Some(401 | 403), _) => {
+                "Check that the apikey or trusted profile is valid and has access to the instance GUID."
+            }
+            (Some(404), _) => {
+                "Check the region, GUID, environment ID and collection ID given to the client."
+            }
+            (Some(429), _) => {
+                "The server is rate limiting requests. The client retries automatically, fewer clients or refreshes per instance avoid it."
+            }
+            (Some(500..=599), _) => {
+                "The App Configuration service is failing. The client retries automatically, fallback regions or a persistent cache keep it serving meanwhile."
+            }
+            (_, CurrentModeOfflineReason::Initializing) => {
+                "No configuration has been received yet. Wait with `wait_until_online()`, or use a persistent cache or a bootstrap file to start offline."
+            }
+            (_, CurrentModeOfflineReason::SafeMode) => {
+                "Safe mode was entered with `OfflineMode::Fail`, call `exit_safe_mode()` to serve the configuration again."
+            }
+            (_, CurrentModeOfflineReason::InternetConnectivityError) => {
+                "Check the network connection of the host and that the App Configuration endpoint resolves."
+            }
+            (
+                _,
+                CurrentModeOfflineReason::WebsocketClosed
+                | CurrentModeOfflineReason::WebsocketError
+                | CurrentModeOfflineReason::WebsocketHeartbeatTimeout,
+            ) => {
+                "Check that firewalls and proxies allow long-lived websocket connections (wss://, port 443) to the App Configuration endpoint."
+            }
+            (_, CurrentModeOfflineReason::FailedToGetNewConfiguration) => {
+                "The configuration could not be fetched or was rejected, `last_error()` and the SDK logs tell why."
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for OfflineDetails {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.reason)?;
+        if let Some(status) = self.http_status {
+            write!(f, ", HTTP status {status}")?;
+        }
+        write!(f, ", since {}", self.since.to_rfc3339())?;
+        if let Some(next_retry_at) = self.next_retry_at {
+            write!(f, ", next retry at {}", next_retry_at.to_rfc3339())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offline_details_help() {
+        let mut details = OfflineDetails::new(CurrentModeOfflineReason::WebsocketError);
+        assert!(details.help().contains("websocket"));
+        assert!(details.to_string().starts_with("WebsocketError, since "));
+
+        details.http_status = Some(401);
+        assert!(details.help().contains("apikey"));
+        assert!(
+            details
+                .to_string()
+                .starts_with("WebsocketError, HTTP status 401, since ")
+        );
+    }
+}
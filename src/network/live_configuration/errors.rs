@@ -12,23 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::sync::PoisonError;
-
 use thiserror::Error;
 
-use super::current_mode::CurrentModeOfflineReason;
+use super::current_mode::OfflineDetails;
 
+#[cfg(feature = "remote")]
 pub(crate) type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug, Error, PartialEq, Eq, Clone)]
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
-    #[error("Cannot acquire lock")]
-    CannotAcquireLock,
-
     #[error(
         "SDK is currently offline (reason: {0}). Configuration data is unavailable until the connection is restored."
     )]
-    Offline(CurrentModeOfflineReason),
+    Offline(OfflineDetails),
 
     #[error("Thread failed with internal error: {0}")]
     ThreadInternalError(String),
@@ -39,9 +36,3 @@ pub enum Error {
     #[error("Configuration is not yet available (try again later)")]
     ConfigurationNotYetAvailable,
 }
-
-impl<T> From<PoisonError<T>> for Error {
-    fn from(_value: PoisonError<T>) -> Self {
-        Error::CannotAcquireLock
-    }
-}
@@ -0,0 +1,119 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::utils::MutexExt;
+
+/// Period of [`crate::LiveConfigurationOptions::max_fetches_per_hour`].
+pub(crate) const FETCH_BUDGET_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// Limits the configuration fetches sent to the server, see
+/// [`crate::LiveConfigurationOptions::max_fetches_per_hour`]. Clones share
+/// the same budget, so the update thread and the on-demand downloads count
+/// against the same limit.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FetchBudget {
+    max_fetches: Option<usize>,
+    /// When the fetches of the last [`FETCH_BUDGET_PERIOD`] were sent, oldest first.
+    recent_fetches: Arc<Mutex<VecDeque<Instant>>>,
+}
+
+impl FetchBudget {
+    /// A budget of `max_fetches_per_hour`, unlimited if `None`.
+    pub(crate) fn new(max_fetches_per_hour: Option<u32>) -> Self {
+        Self {
+            max_fetches: max_fetches_per_hour.map(|max_fetches| max_fetches.max(1) as usize),
+            recent_fetches: Arc::default(),
+        }
+    }
+
+    /// Counts a fetch sent now. If the budget is exhausted nothing is
+    /// counted, and the time until the oldest fetch leaves the period is
+    /// returned instead.
+    pub(crate) fn take(&self) -> Option<Duration> {
+        self.check(true)
+    }
+
+    /// Time until a fetch fits in the budget again, `None` if one fits now.
+    /// Nothing is counted.
+    pub(crate) fn exhausted_for(&self) -> Option<Duration> {
+        self.check(false)
+    }
+
+    fn check(&self, take: bool) -> Option<Duration> {
+        let max_fetches = self.max_fetches?;
+        let now = Instant::now();
+        let mut recent_fetches = self.recent_fetches.lock_unpoisoned();
+        while recent_fetches
+            .front()
+            .is_some_and(|sent_at| now.duration_since(*sent_at) >= FETCH_BUDGET_PERIOD)
+        {
+            recent_fetches.pop_front();
+        }
+        if recent_fetches.len() < max_fetches {
+            if take {
+                recent_fetches.push_back(now);
+            }
+            return None;
+        }
+        recent_fetches
+            .front()
+            .map(|oldest| FETCH_BUDGET_PERIOD - now.duration_since(*oldest))
+    }
+
+    /// Makes the oldest fetch counted look `age` old.
+    ///
+    /// Panics if no fetch is counted, or if the monotonic clock started less
+    /// than `age` ago.
+    #[cfg(test)]
+    pub(crate) fn age_oldest_fetch(&self, age: Duration) {
+        let sent_at = Instant::now()
+            .checked_sub(age)
+            .expect("the monotonic clock cannot go back that far");
+        *self
+            .recent_fetches
+            .lock_unpoisoned()
+            .front_mut()
+            .expect("no fetch is counted") = sent_at;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fetch_budget() {
+        let unlimited = FetchBudget::default();
+        assert!((0..100).all(|_| unlimited.take().is_none()));
+
+        let budget = FetchBudget::new(Some(2));
+        let shared = budget.clone();
+        assert_eq!(budget.take(), None);
+        assert_eq!(shared.exhausted_for(), None);
+        assert_eq!(shared.take(), None);
+        let remaining = budget.exhausted_for().unwrap();
+        assert!(remaining > Duration::from_secs(59 * 60) && remaining <= FETCH_BUDGET_PERIOD);
+        assert!(budget.take().is_some());
+
+        // Available again once the oldest fetch is an hour old
+        budget.age_oldest_fetch(FETCH_BUDGET_PERIOD);
+        assert_eq!(budget.exhausted_for(), None);
+        assert_eq!(budget.take(), None);
+        assert!(shared.take().is_some());
+    }
+}
@@ -16,16 +16,20 @@ use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use super::current_mode::CurrentModeOfflineReason;
+use super::configuration_store::ConfigurationStore;
+use super::current_mode::{CurrentModeOfflineReason, OfflineDetails};
 use super::update_thread_worker::UpdateThreadWorker;
-use super::{CurrentMode, Error, OfflineMode, Result};
-use crate::client::{RuntimeEventListener, RuntimeMode, RuntimeStatus};
-use crate::errors::DeserializationError;
-use crate::models::Configuration;
+use super::{CurrentMode, Error, LiveConfigurationOptions, OfflineMode, Result};
+use crate::client::{
+    RuntimeEventEmitter, RuntimeEventListener, RuntimeMode, RuntimeStatus, SdkError,
+    SdkErrorSnapshot,
+};
+use crate::models::{Configuration, ConfigurationSource, ValidationMode};
 use crate::network::CacheFile;
 use crate::network::http_client::ServerClient;
-use crate::utils::{ThreadHandle, ThreadStatus, Waitable};
-use crate::{ConfigurationId, ConfigurationProvider};
+use crate::network::serialization::{ConfigurationFileFormat, ConfigurationJson};
+use crate::utils::{MutexExt, ThreadHandle, ThreadStatus, Waitable, panic_message};
+use crate::{CacheCipher, ConfigurationId, ConfigurationProvider};
 
 /// A [`ConfigurationProvider`] that keeps the configuration updated with some
 /// third-party source using an asyncronous mechanism.
@@ -44,12 +48,28 @@ pub trait LiveConfiguration: ConfigurationProvider {
     /// Stops the live runtime thread, resets in-memory state, and clears any
     /// SDK-managed persistent cache file.
     fn clean_up_with_cache_clear(&mut self) -> Result<()>;
+
+    /// Serves the configuration of `fallback` instead of the one received from
+    /// the server until [`exit_safe_mode`](LiveConfiguration::exit_safe_mode) is called.
+    fn enter_safe_mode(&self, fallback: OfflineMode) -> Result<()>;
+
+    /// Serves again the latest configuration received from the server.
+    fn exit_safe_mode(&self) -> Result<()>;
+
+    /// Whether the configuration of a fallback is served, see
+    /// [`enter_safe_mode`](LiveConfiguration::enter_safe_mode).
+    fn is_in_safe_mode(&self) -> bool;
 }
 
+/// None of the locks below is held while another one is acquired. The update
+/// thread stores the configuration before switching [`CurrentMode::Online`],
+/// and consumers read the mode before the configuration, so a consumer seeing
+/// `Online` finds the configuration unless [`LiveConfiguration::clean_up`]
+/// raced with it.
 pub(crate) struct LiveConfigurationImpl {
     /// Configuration object that will be returned to consumers. This is also the object
     /// that the thread in the backend will be updating.
-    configuration: Arc<Mutex<Option<Configuration>>>,
+    configuration: Arc<Mutex<Option<Arc<Configuration>>>>,
 
     /// Current operation mode.
     current_mode: Waitable<CurrentMode>,
@@ -60,7 +80,26 @@ pub(crate) struct LiveConfigurationImpl {
     /// Behaviour while the server is offline
     offline_mode: OfflineMode,
 
+    /// Used as well when reading the configuration from the offline files.
+    validation_mode: ValidationMode,
+
+    /// Checked as well in the configurations read from the offline files.
+    expected_schema_version: Option<u32>,
+
     runtime_event_listeners: Arc<Mutex<Vec<RuntimeEventListener>>>,
+
+    /// While in safe mode, the result returned to consumers instead of the
+    /// configuration kept updated by the thread.
+    safe_mode: Mutex<Option<Result<Arc<Configuration>>>>,
+
+    /// Most recent error of the thread, kept up to date by the [`UpdateThreadWorker`].
+    last_error: Arc<Mutex<Option<SdkErrorSnapshot>>>,
+
+    /// Details of the current offline period, kept by the update thread.
+    offline_details: Arc<Mutex<Option<OfflineDetails>>>,
+
+    /// Shared with the update thread, `None` without one.
+    configuration_store: Option<ConfigurationStore>,
 }
 
 impl std::fmt::Debug for LiveConfigurationImpl {
@@ -70,135 +109,203 @@ impl std::fmt::Debug for LiveConfigurationImpl {
             .field("current_mode", &self.current_mode)
             .field("update_thread", &self.update_thread)
             .field("offline_mode", &self.offline_mode)
+            .field("safe_mode", &self.safe_mode)
             .field(
                 "runtime_event_listeners",
                 &format!(
                     "[{} listeners]",
-                    self.runtime_event_listeners
-                        .lock()
-                        .map(|l| l.len())
-                        .unwrap_or(0)
+                    self.runtime_event_listeners.lock_unpoisoned().len()
                 ),
             )
             .finish()
     }
 }
 
+/// Runs `f` sending a [`SdkError::ThreadPanicked`] to `error_emitter` if it panics.
+/// The panic is resumed afterwards, so it is still reported by the [`ThreadHandle`].
+fn report_panics<R>(error_emitter: &RuntimeEventEmitter, f: impl FnOnce() -> R) -> R {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        error_emitter.emit_error(SdkError::ThreadPanicked(panic_message(&*payload)));
+        std::panic::resume_unwind(payload)
+    })
+}
+
 impl LiveConfigurationImpl {
     /// Creates a new [`LiveConfigurationImpl`] object and starts a thread running an instance
     /// of [`UpdateThreadWorker`].
     ///
-
+    /// Failures that stop the thread, including panics, are sent to `error_emitter`.
     pub fn new<T: ServerClient>(
         offline_mode: OfflineMode,
         server_client: T,
         configuration_id: ConfigurationId,
         initial_listeners: Vec<RuntimeEventListener>,
+        options: LiveConfigurationOptions,
+        error_emitter: RuntimeEventEmitter,
     ) -> Self {
-        let (preloaded_configuration, persistent_cache_path) =
-            Self::preload_configuration(&offline_mode);
-        let configuration = Arc::new(Mutex::new(preloaded_configuration));
+        let (preloaded_configuration, persistent_cache_path) = Self::preload_configuration(
+            &offline_mode,
+            options.validation_mode,
+            options.expected_schema_version,
+            options.cache_cipher.as_ref(),
+        );
+        let configuration = Arc::new(Mutex::new(preloaded_configuration.map(Arc::new)));
         let runtime_event_listeners = Arc::new(Mutex::new(initial_listeners));
+        let last_error = Arc::new(Mutex::new(None));
+        let offline_details = Arc::new(Mutex::new(Some(OfflineDetails::new(
+            CurrentModeOfflineReason::Initializing,
+        ))));
 
         if matches!(offline_mode, OfflineMode::FallbackData(_)) {
             let current_mode = Waitable::new(CurrentMode::Defunct(Ok(())));
-            let update_thread = ThreadHandle::new(move |_| Ok(()));
+            let update_thread =
+                ThreadHandle::with_options(&options.thread_options, move |_| Ok(()));
             drop(server_client);
             return Self {
                 configuration,
                 update_thread,
                 current_mode,
                 offline_mode,
+                validation_mode: options.validation_mode,
+                expected_schema_version: options.expected_schema_version,
                 runtime_event_listeners,
+                safe_mode: Mutex::new(None),
+                last_error,
+                offline_details,
+                configuration_store: None,
             };
         }
 
+        let shared_cache = options
+            .shared_cache
+            .map(|cache| cache.with_cipher(options.cache_cipher.clone()));
+        // Bootstrap files stay unencrypted, as shipped with the application
+        let cache_cipher = match offline_mode {
+            OfflineMode::BootstrapFile { .. } => None,
+            _ => options.cache_cipher,
+        };
         let current_mode =
             Waitable::new(CurrentMode::Offline(CurrentModeOfflineReason::Initializing));
+        let worker = UpdateThreadWorker::new(
+            server_client,
+            configuration_id,
+            configuration.clone(),
+            current_mode.clone(),
+            runtime_event_listeners.clone(),
+        )
+        .with_refresh_coalescing_window(options.refresh_coalescing_window)
+        .with_polling_interval(options.polling_interval)
+        .with_max_fetches_per_hour(options.max_fetches_per_hour)
+        .with_validation_mode(options.validation_mode)
+        .with_expected_schema_version(options.expected_schema_version)
+        .with_failover_threshold(options.failover_threshold)
+        .with_last_error(last_error.clone())
+        .with_offline_details(offline_details.clone())
+        .with_configuration_guards(options.configuration_guards)
+        .with_cache_cipher(cache_cipher)
+        .with_shared_cache(shared_cache)
+        .with_error_emitter(error_emitter.clone());
+        let worker = worker.with_step_controller(options.step_controller);
         let worker = match persistent_cache_path {
-            Some(path) => UpdateThreadWorker::new(
-                server_client,
-                configuration_id,
-                configuration.clone(),
-                current_mode.clone(),
-                runtime_event_listeners.clone(),
-            )
-            .with_persistent_cache_file(path),
-            None => UpdateThreadWorker::new(
-                server_client,
-                configuration_id,
-                configuration.clone(),
-                current_mode.clone(),
-                runtime_event_listeners.clone(),
-            ),
+            Some(path) => worker.with_persistent_cache_file(path),
+            None => worker,
         };
+        let configuration_store = worker.configuration_store().clone();
 
         let update_thread =
-            ThreadHandle::new(move |terminator_receiver| worker.run(terminator_receiver));
+            ThreadHandle::with_options(&options.thread_options, move |terminator_receiver| {
+                report_panics(&error_emitter, || worker.run(terminator_receiver))
+            });
 
         Self {
             configuration,
             update_thread,
             current_mode,
             offline_mode,
+            validation_mode: options.validation_mode,
+            expected_schema_version: options.expected_schema_version,
             runtime_event_listeners,
+            safe_mode: Mutex::new(None),
+            last_error,
+            offline_details,
+            configuration_store: Some(configuration_store),
         }
     }
 
+    /// Verifies and serves the configurations downloaded outside of the update
+    /// thread, `None` without one.
+    pub(crate) fn configuration_store(&self) -> Option<ConfigurationStore> {
+        self.configuration_store.clone()
+    }
+
     fn read_persistent_cache_configuration(
         path: &Path,
         environment_id: &str,
         collection_id: &str,
+        validation_mode: ValidationMode,
+        expected_schema_version: Option<u32>,
+        cache_cipher: Option<&CacheCipher>,
     ) -> Option<Configuration> {
-        let contents = CacheFile::read_persistent_cache_string(path);
+        let contents = match cache_cipher {
+            Some(cipher) => CacheFile::read_encrypted_persistent_cache_string(path, cipher),
+            None => CacheFile::read_persistent_cache_string(path),
+        };
         if contents.is_empty() {
             return None;
         }
 
-        serde_json::from_str::<crate::network::serialization::ConfigurationJson>(&contents)
-            .map_err(|e| {
-                crate::Error::DeserializationError(DeserializationError {
-                    string: format!(
-                        "Error deserializing Configuration from file '{}'",
-                        path.display()
-                    ),
-                    source: e.into(),
-                })
-            })
+        ConfigurationJson::from_json_str(&contents)
             .and_then(|configuration_json| {
-                Configuration::new(environment_id, collection_id, configuration_json)
-                    .map_err(crate::Error::from)
+                configuration_json.verify_schema_version(expected_schema_version)?;
+                Configuration::new_with_validation_mode(
+                    environment_id,
+                    collection_id,
+                    configuration_json,
+                    validation_mode,
+                )
+                .map(|configuration| {
+                    configuration.with_source(ConfigurationSource::PersistentCache)
+                })
+                .map_err(crate::Error::from)
             })
             .ok()
     }
 
     fn read_bootstrap_configuration(
-        path: &PathBuf,
+        path: &Path,
         environment_id: &str,
         collection_id: &str,
+        validation_mode: ValidationMode,
+        expected_schema_version: Option<u32>,
     ) -> Option<Configuration> {
         CacheFile::read_bootstrap_string(path)
             .and_then(|contents| {
-                serde_json::from_str::<crate::network::serialization::ConfigurationJson>(&contents)
-                    .map_err(|e| {
-                        crate::Error::DeserializationError(DeserializationError {
-                            string: format!(
-                                "Error deserializing Configuration from file '{}'",
-                                path.display()
-                            ),
-                            source: e.into(),
-                        })
-                    })
+                // In the format given by its extension
+                ConfigurationJson::from_str_in_format(
+                    &contents,
+                    ConfigurationFileFormat::from_path(path),
+                    &format!("file '{}'", path.display()),
+                )
             })
             .and_then(|configuration_json| {
-                Configuration::new(environment_id, collection_id, configuration_json)
-                    .map_err(crate::Error::from)
+                configuration_json.verify_schema_version(expected_schema_version)?;
+                Configuration::new_with_validation_mode(
+                    environment_id,
+                    collection_id,
+                    configuration_json,
+                    validation_mode,
+                )
+                .map(|configuration| configuration.with_source(ConfigurationSource::Bootstrap))
+                .map_err(crate::Error::from)
             })
             .ok()
     }
 
     fn preload_configuration(
         offline_mode: &OfflineMode,
+        validation_mode: ValidationMode,
+        expected_schema_version: Option<u32>,
+        cache_cipher: Option<&CacheCipher>,
     ) -> (Option<Configuration>, Option<PathBuf>) {
         match offline_mode {
             OfflineMode::PersistentCacheFile {
@@ -206,42 +313,156 @@ impl LiveConfigurationImpl {
                 environment_id,
                 collection_id,
             } => (
-                Self::read_persistent_cache_configuration(path, environment_id, collection_id),
+                Self::read_persistent_cache_configuration(
+                    path,
+                    environment_id,
+                    collection_id,
+                    validation_mode,
+                    expected_schema_version,
+                    cache_cipher,
+                ),
                 Some(path.clone()),
             ),
             OfflineMode::BootstrapFile {
                 path,
                 environment_id,
                 collection_id,
+                persist_updates,
             } => (
-                Self::read_bootstrap_configuration(path, environment_id, collection_id),
-                None,
+                Self::read_bootstrap_configuration(
+                    path,
+                    environment_id,
+                    collection_id,
+                    validation_mode,
+                    expected_schema_version,
+                ),
+                persist_updates.then(|| path.clone()),
             ),
             OfflineMode::FallbackData(app_configuration_offline) => (
-                Some(app_configuration_offline.config_snapshot.clone()),
+                Some(
+                    Configuration::clone(&app_configuration_offline.config_snapshot)
+                        .with_source(ConfigurationSource::Fallback),
+                ),
                 None,
             ),
             OfflineMode::Fail | OfflineMode::Cache => (None, None),
         }
     }
 
+    /// Blocks until a configuration can be served, received from the server
+    /// or provided by the offline mode, or `timeout` elapses.
+    pub(crate) fn wait_for_initial_configuration(&self, timeout: Duration) -> crate::Result<()> {
+        if self.get_configuration().is_ok() {
+            return Ok(());
+        }
+        let online = self
+            .current_mode
+            .wait_for_timeout(CurrentMode::Online, timeout);
+        if online.is_some() {
+            return Ok(());
+        }
+        Err(crate::Error::InitialFetchTimeout {
+            timeout,
+            last_error: self
+                .last_error
+                .lock_unpoisoned()
+                .as_ref()
+                .map(|e| e.message.clone()),
+        })
+    }
+
+    /// Reads the configuration of a persistent cache or bootstrap file.
+    fn read_configuration_file(
+        &self,
+        path: &Path,
+        environment_id: &str,
+        collection_id: &str,
+        source: ConfigurationSource,
+    ) -> crate::Result<Configuration> {
+        let configuration_json = ConfigurationJson::new(path)?;
+        configuration_json.verify_schema_version(self.expected_schema_version)?;
+        Ok(Configuration::new_with_validation_mode(
+            environment_id,
+            collection_id,
+            configuration_json,
+            self.validation_mode,
+        )?
+        .with_source(source))
+    }
+
+    /// Details of the current offline period, or new ones if the update thread
+    /// has not recorded them for `reason`.
+    fn offline_details(&self, reason: CurrentModeOfflineReason) -> Result<OfflineDetails> {
+        Ok(self
+            .offline_details
+            .lock_unpoisoned()
+            .clone()
+            .filter(|details| details.reason == reason)
+            .unwrap_or_else(|| OfflineDetails::new(reason)))
+    }
+
+    /// Returns what consumers get in safe mode with the given `fallback`.
+    ///
+    /// Fails if the fallback has no configuration to serve, `OfflineMode::Fail`
+    /// makes every consumer fail instead.
+    fn safe_mode_configuration(
+        &self,
+        fallback: &OfflineMode,
+    ) -> Result<Result<Arc<Configuration>>> {
+        match fallback {
+            OfflineMode::Fail => Ok(Err(Error::Offline(OfflineDetails::new(
+                CurrentModeOfflineReason::SafeMode,
+            )))),
+            OfflineMode::Cache => match &*self.configuration.lock_unpoisoned() {
+                Some(configuration) => Ok(Ok(configuration.clone())),
+                None => Err(Error::ConfigurationNotYetAvailable),
+            },
+            OfflineMode::FallbackData(app_configuration_offline) => Ok(Ok(Arc::new(
+                Configuration::clone(&app_configuration_offline.config_snapshot)
+                    .with_source(ConfigurationSource::Fallback),
+            ))),
+            OfflineMode::PersistentCacheFile {
+                path,
+                environment_id,
+                collection_id,
+            }
+            | OfflineMode::BootstrapFile {
+                path,
+                environment_id,
+                collection_id,
+                ..
+            } => self
+                .read_configuration_file(
+                    path,
+                    environment_id,
+                    collection_id,
+                    fallback.file_source(),
+                )
+                .map(|configuration| Ok(Arc::new(configuration)))
+                .map_err(|err| Error::UnrecoverableError(err.to_string())),
+        }
+    }
+
     /// Returns the current [`Configuration`] after considering the [`CurrentMode`] and the [`OfflineMode`]
     /// configured for this object.
     ///
-    fn get_configuration(&self) -> Result<Configuration> {
-        // TODO: Can we return a reference instead?
-        match self.current_mode.get()? {
+    fn get_configuration(&self) -> Result<Arc<Configuration>> {
+        if let Some(safe_mode) = &*self.safe_mode.lock_unpoisoned() {
+            return safe_mode.clone();
+        }
+        match self.current_mode.get() {
             CurrentMode::Online => {
-                match &*self.configuration.lock()? {
-                    // We store the configuration retrieved from the server into the Arc<Mutex> before switching the flag to Online
-                    None => unreachable!(),
+                match &*self.configuration.lock_unpoisoned() {
+                    // We store the configuration retrieved from the server into the Arc<Mutex> before switching the flag to Online,
+                    // it is only missing if the client was cleaned up meanwhile
+                    None => Err(Error::ConfigurationNotYetAvailable),
                     Some(configuration) => Ok(configuration.clone()),
                 }
             }
             CurrentMode::Offline(current_mode_offline_reason) => {
                 // Priority 1: always try the in-memory cache first — the background thread
                 // preserves the last successful fetch across reconnect cycles.
-                if let Some(configuration) = &*self.configuration.lock()? {
+                if let Some(configuration) = &*self.configuration.lock_unpoisoned() {
                     log::debug!(
                         "[OFFLINE] Serving stale in-memory config while reconnecting (reason: {})",
                         current_mode_offline_reason
@@ -251,11 +472,14 @@ impl LiveConfigurationImpl {
 
                 // Priority 2: no in-memory config yet — fall back to the configured strategy.
                 match &self.offline_mode {
-                    OfflineMode::Fail => Err(Error::Offline(current_mode_offline_reason)),
+                    OfflineMode::Fail => Err(Error::Offline(
+                        self.offline_details(current_mode_offline_reason)?,
+                    )),
                     OfflineMode::Cache => Err(Error::ConfigurationNotYetAvailable),
-                    OfflineMode::FallbackData(app_configuration_offline) => {
-                        Ok(app_configuration_offline.config_snapshot.clone())
-                    }
+                    OfflineMode::FallbackData(app_configuration_offline) => Ok(Arc::new(
+                        Configuration::clone(&app_configuration_offline.config_snapshot)
+                            .with_source(ConfigurationSource::Fallback),
+                    )),
                     OfflineMode::PersistentCacheFile {
                         path,
                         environment_id,
@@ -265,14 +489,22 @@ impl LiveConfigurationImpl {
                         path,
                         environment_id,
                         collection_id,
-                    } => Configuration::from_file(path, environment_id, collection_id)
+                        ..
+                    } => self
+                        .read_configuration_file(
+                            path,
+                            environment_id,
+                            collection_id,
+                            self.offline_mode.file_source(),
+                        )
+                        .map(Arc::new)
                         .map_err(|err| Error::UnrecoverableError(err.to_string())),
                 }
             }
             CurrentMode::Defunct(result) => {
                 // Same strategy: serve stale in-memory config when the thread has exited
                 // (e.g. after clean_up()) but a valid configuration is still held in memory.
-                if let Some(configuration) = &*self.configuration.lock()? {
+                if let Some(configuration) = &*self.configuration.lock_unpoisoned() {
                     log::debug!(
                         "[DEFUNCT] Serving stale in-memory config (thread result: {:?})",
                         result
@@ -289,9 +521,10 @@ impl LiveConfigurationImpl {
                         "Initial configuration failed to retrieve: {:?}",
                         result
                     ))),
-                    OfflineMode::FallbackData(app_configuration_offline) => {
-                        Ok(app_configuration_offline.config_snapshot.clone())
-                    }
+                    OfflineMode::FallbackData(app_configuration_offline) => Ok(Arc::new(
+                        Configuration::clone(&app_configuration_offline.config_snapshot)
+                            .with_source(ConfigurationSource::Fallback),
+                    )),
                     OfflineMode::PersistentCacheFile {
                         path,
                         environment_id,
@@ -301,7 +534,15 @@ impl LiveConfigurationImpl {
                         path,
                         environment_id,
                         collection_id,
-                    } => Configuration::from_file(path, environment_id, collection_id)
+                        ..
+                    } => self
+                        .read_configuration_file(
+                            path,
+                            environment_id,
+                            collection_id,
+                            self.offline_mode.file_source(),
+                        )
+                        .map(Arc::new)
                         .map_err(|err| Error::UnrecoverableError(err.to_string())),
                 }
             }
@@ -326,19 +567,35 @@ impl ConfigurationProvider for LiveConfigurationImpl {
         self.get_configuration()?.get_property(property_id)
     }
 
+    fn get_segment_ids(&self) -> crate::Result<Vec<String>> {
+        self.get_configuration()?.get_segment_ids()
+    }
+
+    fn get_segment(&self, segment_id: &str) -> crate::Result<crate::models::SegmentSnapshot> {
+        self.get_configuration()?.get_segment(segment_id)
+    }
+
+    fn get_configuration_snapshot(&self) -> crate::Result<Configuration> {
+        Ok(Configuration::clone(&*self.get_configuration()?))
+    }
+
+    fn get_shared_configuration(&self) -> crate::Result<Arc<Configuration>> {
+        Ok(self.get_configuration()?)
+    }
+
     fn is_online(&self) -> crate::Result<bool> {
         Ok(self.get_current_mode()? == CurrentMode::Online)
     }
 
     fn wait_until_online(&self) -> bool {
-        if let Ok(CurrentMode::Defunct(Ok(()))) = self.current_mode.get() {
-            if let OfflineMode::FallbackData(_) = &self.offline_mode {
-                return true;
-            }
+        if let CurrentMode::Defunct(Ok(())) = self.current_mode.get()
+            && let OfflineMode::FallbackData(_) = &self.offline_mode
+        {
+            return true;
         }
         self.current_mode
             .wait_for_timeout(CurrentMode::Online, Duration::from_secs(30))
-            .is_ok()
+            .is_some()
     }
 
     fn get_secret_property(
@@ -374,8 +631,14 @@ impl ConfigurationProvider for LiveConfigurationImpl {
         Ok(Some(status))
     }
 
+    fn last_error(&self) -> crate::Result<Option<SdkErrorSnapshot>> {
+        Ok(self.last_error.lock_unpoisoned().clone())
+    }
+
     fn add_runtime_event_listener(&self, listener: RuntimeEventListener) -> crate::Result<()> {
-        self.runtime_event_listeners.lock()?.push(listener);
+        self.runtime_event_listeners
+            .lock_unpoisoned()
+            .push(listener);
         Ok(())
     }
 
@@ -394,7 +657,7 @@ impl LiveConfiguration for LiveConfigurationImpl {
     }
 
     fn get_current_mode(&self) -> Result<CurrentMode> {
-        Ok(self.current_mode.get()?)
+        Ok(self.current_mode.get())
     }
 
     fn clean_up(&mut self) -> Result<()> {
@@ -407,10 +670,8 @@ impl LiveConfiguration for LiveConfigurationImpl {
             }
         }
 
-        self.current_mode
-            .set(CurrentMode::Defunct(Ok(())))
-            .map_err(Error::from)?;
-        let mut configuration = self.configuration.lock()?;
+        self.current_mode.set(CurrentMode::Defunct(Ok(())));
+        let mut configuration = self.configuration.lock_unpoisoned();
         *configuration = None;
         Ok(())
     }
@@ -422,6 +683,24 @@ impl LiveConfiguration for LiveConfigurationImpl {
         }
         Ok(())
     }
+
+    fn enter_safe_mode(&self, fallback: OfflineMode) -> Result<()> {
+        let configuration = self.safe_mode_configuration(&fallback)?;
+        log::warn!("Entering safe mode, configuration updates are ignored until it is lifted");
+        *self.safe_mode.lock_unpoisoned() = Some(configuration);
+        Ok(())
+    }
+
+    fn exit_safe_mode(&self) -> Result<()> {
+        if self.safe_mode.lock_unpoisoned().take().is_some() {
+            log::info!("Exiting safe mode, serving the latest configuration again");
+        }
+        Ok(())
+    }
+
+    fn is_in_safe_mode(&self) -> bool {
+        self.safe_mode.lock_unpoisoned().is_some()
+    }
 }
 
 impl Drop for LiveConfigurationImpl {
@@ -433,7 +712,7 @@ impl Drop for LiveConfigurationImpl {
 #[cfg(test)]
 mod tests {
 
-    use std::sync::mpsc::{self, RecvError};
+    use std::sync::mpsc;
 
     use rstest::rstest;
 
@@ -443,20 +722,51 @@ mod tests {
     use crate::network::NetworkError::ProtocolError;
     use crate::network::NetworkResult;
     use crate::network::http_client::WebsocketReader;
-    use crate::network::live_configuration::update_thread_worker::SERVER_HEARTBEAT;
     use crate::network::serialization::ConfigurationJson;
+    use crate::network::serialization::SERVER_HEARTBEAT;
 
+    use super::super::step_controller::{StepController, WorkerStep};
     use super::*;
 
+    #[test]
+    fn test_report_panics() {
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let error_emitter = RuntimeEventEmitter::new();
+        {
+            let errors = errors.clone();
+            error_emitter
+                .on_error(Arc::new(move |error| errors.lock().unwrap().push(error)))
+                .unwrap();
+        }
+
+        assert_eq!(report_panics(&error_emitter, || 42), 42);
+        assert!(errors.lock().unwrap().is_empty());
+
+        let mut thread = ThreadHandle::new(move |_| {
+            report_panics(&error_emitter, || -> Result<()> { panic!("worker bug") })
+        });
+        while matches!(thread.get_thread_status(), ThreadStatus::Running) {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(
+            thread.get_thread_status(),
+            ThreadStatus::FailedInternalError("Thread panicked: worker bug".to_string())
+        );
+        assert_eq!(
+            *errors.lock().unwrap(),
+            [SdkError::ThreadPanicked(
+                "Thread panicked: worker bug".to_string()
+            )]
+        );
+    }
+
     #[test]
     fn test_happy_path() {
         struct WebsocketReaderMock {
             rx: mpsc::Receiver<tungstenite::Message>,
-            tx: mpsc::Sender<()>,
         }
         impl WebsocketReader for WebsocketReaderMock {
             fn read_msg(&mut self) -> tungstenite::error::Result<tungstenite::Message> {
-                self.tx.send(()).unwrap();
                 Ok(self.rx.recv().unwrap())
             }
             fn flush_socket(&mut self) -> tungstenite::error::Result<()> {
@@ -485,8 +795,8 @@ mod tests {
             fn get_configuration_monitoring_websocket(
                 &self,
                 _collection: &ConfigurationId,
-            ) -> NetworkResult<impl WebsocketReader> {
-                Ok(self.websocket_rx.recv().unwrap())
+            ) -> NetworkResult<Box<dyn WebsocketReader>> {
+                Ok(Box::new(self.websocket_rx.recv().unwrap()))
             }
         }
 
@@ -497,10 +807,27 @@ mod tests {
             websocket_rx: websocket_factory_rx,
         };
 
-        let configuration_id =
-            crate::ConfigurationId::new("".into(), "environment_id".into(), "collection_id".into());
-        let mut live_config =
-            LiveConfigurationImpl::new(OfflineMode::Fail, server_client, configuration_id, vec![]);
+        let configuration_id = crate::ConfigurationId::new(
+            "guid".into(),
+            "environment_id".into(),
+            "collection_id".into(),
+        )
+        .unwrap();
+        // Paused every time the thread is about to wait for a websocket message
+        let controller = StepController::new();
+        controller.pause_at(WorkerStep::BeforeWebsocketRead);
+        let mut live_config = LiveConfigurationImpl::new(
+            OfflineMode::Fail,
+            server_client,
+            configuration_id,
+            vec![],
+            LiveConfigurationOptions {
+                step_controller: Some(controller.clone()),
+                ..Default::default()
+            },
+            RuntimeEventEmitter::new(),
+        );
+        let timeout = Duration::from_secs(5);
 
         {
             // Blocked beginning of get_configuration_from_server()
@@ -509,7 +836,10 @@ mod tests {
             assert!(
                 matches!(
                     config,
-                    Err(Error::Offline(CurrentModeOfflineReason::Initializing))
+                    Err(Error::Offline(OfflineDetails {
+                        reason: CurrentModeOfflineReason::Initializing,
+                        ..
+                    }))
                 ),
                 "{:?}",
                 config
@@ -521,20 +851,23 @@ mod tests {
                 current_mode,
                 Ok(CurrentMode::Offline(CurrentModeOfflineReason::Initializing))
             ));
+            assert!(matches!(
+                live_config.wait_for_initial_configuration(Duration::from_millis(50)),
+                Err(crate::Error::InitialFetchTimeout {
+                    last_error: None,
+                    ..
+                })
+            ));
         }
 
         let (read_msg_tx, read_msg_rx) = mpsc::channel();
-        let (read_msg_ping_tx, read_msg_ping_rx) = mpsc::channel();
         let configuration =
             crate::network::serialization::fixtures::configuration_json_feature1_enabled();
         let config = {
             // allow thread to start (unblock)
             get_configuration_tx.send(configuration).unwrap();
             websocket_factory_tx
-                .send(WebsocketReaderMock {
-                    rx: read_msg_rx,
-                    tx: read_msg_ping_tx,
-                })
+                .send(WebsocketReaderMock { rx: read_msg_rx })
                 .unwrap();
 
             // Now live_config should eventually transition into
@@ -543,19 +876,24 @@ mod tests {
             // And assert that we are really online
             let current_mode = live_config.get_current_mode();
             assert!(matches!(current_mode, Ok(CurrentMode::Online)));
-            // The initialization should have received one message from WS.
-            // We consume it. Note the `try_` here. We should not block here, as this was already asserted via the `wait_until_online()` earlier.
-            read_msg_ping_rx
-                .recv_timeout(Duration::from_secs(1))
-                .unwrap();
-            // Blocked in socket.read_msg()
+            assert!(
+                live_config
+                    .wait_for_initial_configuration(Duration::from_millis(50))
+                    .is_ok()
+            );
+            // Paused before reading the first websocket message
+            assert_eq!(
+                controller.wait_paused(timeout),
+                Some(WorkerStep::BeforeWebsocketRead)
+            );
             // Expect, we get a configuration and are Online / Running state
             let config_result = live_config.get_configuration();
-            assert!(matches!(config_result, Ok(_)), "{:?}", config_result);
+            assert!(config_result.is_ok(), "{:?}", config_result);
             let thread_state = live_config.get_thread_status();
             assert!(matches!(thread_state, ThreadStatus::Running));
             let current_mode = live_config.get_current_mode();
             assert!(matches!(current_mode, Ok(CurrentMode::Online)));
+            controller.resume();
             config_result.unwrap()
         };
 
@@ -565,7 +903,10 @@ mod tests {
                 .send(tungstenite::Message::text(SERVER_HEARTBEAT))
                 .unwrap();
             // Wait for thread to do some work and then to wait on websocket
-            read_msg_ping_rx.recv().unwrap();
+            assert_eq!(
+                controller.wait_paused(timeout),
+                Some(WorkerStep::BeforeWebsocketRead)
+            );
 
             // Expect no change due to heartbeat:
             let config_result = live_config.get_configuration();
@@ -575,6 +916,7 @@ mod tests {
             assert!(matches!(thread_state, ThreadStatus::Running));
             let current_mode = live_config.get_current_mode();
             assert!(matches!(current_mode, Ok(CurrentMode::Online)));
+            controller.resume();
         }
 
         {
@@ -585,7 +927,10 @@ mod tests {
                 crate::network::serialization::fixtures::configuration_json_property1_enabled();
             get_configuration_tx.send(configuration).unwrap();
             // Wait for thread to do some work and then to wait on websocket
-            read_msg_ping_rx.recv().unwrap();
+            assert_eq!(
+                controller.wait_paused(timeout),
+                Some(WorkerStep::BeforeWebsocketRead)
+            );
 
             // Expect new configuration, and still running/online
             let config_result = live_config.get_configuration();
@@ -605,14 +950,22 @@ mod tests {
 
         {
             // When the client is dropped, the thread will be finished
+            controller.release();
             drop(live_config);
 
             read_msg_tx
                 .send(tungstenite::Message::text(SERVER_HEARTBEAT))
                 .unwrap();
 
-            let r = read_msg_ping_rx.recv();
-            assert!(matches!(r, Err(RecvError { .. })), "{:?}", r)
+            // The websocket is dropped with the thread
+            let deadline = std::time::Instant::now() + timeout;
+            while read_msg_tx
+                .send(tungstenite::Message::text(SERVER_HEARTBEAT))
+                .is_ok()
+            {
+                assert!(std::time::Instant::now() < deadline, "Thread not finished");
+                std::thread::sleep(Duration::from_millis(5));
+            }
         }
     }
 
@@ -623,8 +976,10 @@ mod tests {
     ) {
         let (tx, _) = std::sync::mpsc::channel();
         let mut cfg = LiveConfigurationImpl {
-            configuration: Arc::new(Mutex::new(Some(Configuration::default()))),
+            configuration: Arc::new(Mutex::new(Some(Arc::default()))),
             offline_mode: OfflineMode::Fail,
+            validation_mode: ValidationMode::default(),
+            expected_schema_version: None,
             current_mode: Waitable::new(CurrentMode::Online),
             update_thread: ThreadHandle {
                 _thread_termination_sender: tx,
@@ -632,6 +987,10 @@ mod tests {
                 finished_thread_status_cached: None,
             },
             runtime_event_listeners: Arc::new(Mutex::new(Vec::new())),
+            safe_mode: Mutex::new(None),
+            last_error: Arc::default(),
+            offline_details: Arc::default(),
+            configuration_store: None,
         };
 
         {
@@ -669,7 +1028,9 @@ mod tests {
         let (tx, _) = std::sync::mpsc::channel();
         let mut cfg = LiveConfigurationImpl {
             offline_mode: OfflineMode::Fail,
-            configuration: Arc::new(Mutex::new(Some(Configuration::default()))),
+            validation_mode: ValidationMode::default(),
+            expected_schema_version: None,
+            configuration: Arc::new(Mutex::new(Some(Arc::default()))),
             current_mode: Waitable::new(CurrentMode::Offline(
                 CurrentModeOfflineReason::WebsocketClosed,
             )),
@@ -679,13 +1040,17 @@ mod tests {
                 finished_thread_status_cached: None,
             },
             runtime_event_listeners: Arc::new(Mutex::new(Vec::new())),
+            safe_mode: Mutex::new(None),
+            last_error: Arc::default(),
+            offline_details: Arc::default(),
+            configuration_store: None,
         };
 
         // OfflineMode::Fail WITH a previously-fetched in-memory cache → serve stale config
 
         {
             cfg.offline_mode = OfflineMode::Fail;
-            cfg.configuration = Arc::new(Mutex::new(Some(Configuration::default())));
+            cfg.configuration = Arc::new(Mutex::new(Some(Arc::default())));
             let r = cfg.get_configuration();
             assert!(
                 r.is_ok(),
@@ -702,10 +1067,13 @@ mod tests {
                 r.is_err(),
                 "Should error when no cached config was ever fetched"
             );
-            assert_eq!(
+            assert!(matches!(
                 r.unwrap_err(),
-                Error::Offline(CurrentModeOfflineReason::WebsocketClosed)
-            );
+                Error::Offline(OfflineDetails {
+                    reason: CurrentModeOfflineReason::WebsocketClosed,
+                    ..
+                })
+            ));
         }
 
         {
@@ -719,7 +1087,7 @@ mod tests {
             }
             {
                 // In-memory config exists → served via priority-1 stale-cache path
-                cfg.configuration = Arc::new(Mutex::new(Some(Configuration::default())));
+                cfg.configuration = Arc::new(Mutex::new(Some(Arc::default())));
                 let r = cfg.get_configuration();
                 assert!(r.is_ok(), "Error: {}", r.unwrap_err());
                 assert!(r.unwrap().features.is_empty());
@@ -738,7 +1106,9 @@ mod tests {
             cfg.configuration = Arc::new(Mutex::new(None));
             let r = cfg.get_configuration();
             assert!(r.is_ok(), "Error: {}", r.unwrap_err());
-            assert_eq!(r.unwrap().features.len(), 5);
+            let configuration = r.unwrap();
+            assert_eq!(configuration.features.len(), 5);
+            assert_eq!(configuration.source(), ConfigurationSource::Fallback);
         }
     }
 
@@ -749,7 +1119,9 @@ mod tests {
         let (tx, _) = std::sync::mpsc::channel();
         let mut cfg = LiveConfigurationImpl {
             offline_mode: OfflineMode::Fail,
-            configuration: Arc::new(Mutex::new(Some(Configuration::default()))),
+            validation_mode: ValidationMode::default(),
+            expected_schema_version: None,
+            configuration: Arc::new(Mutex::new(Some(Arc::default()))),
             current_mode: Waitable::new(CurrentMode::Defunct(Ok(()))),
             update_thread: ThreadHandle {
                 _thread_termination_sender: tx,
@@ -757,13 +1129,17 @@ mod tests {
                 finished_thread_status_cached: None,
             },
             runtime_event_listeners: Arc::new(Mutex::new(Vec::new())),
+            safe_mode: Mutex::new(None),
+            last_error: Arc::default(),
+            offline_details: Arc::default(),
+            configuration_store: None,
         };
 
         // OfflineMode::Fail WITH a stale in-memory config → serve it (thread may have
         // exited cleanly, e.g. after clean_up(), but config is still valid in RAM).
         {
             cfg.offline_mode = OfflineMode::Fail;
-            cfg.configuration = Arc::new(Mutex::new(Some(Configuration::default())));
+            cfg.configuration = Arc::new(Mutex::new(Some(Arc::default())));
             let r = cfg.get_configuration();
             assert!(
                 r.is_ok(),
@@ -800,7 +1176,7 @@ mod tests {
             }
             {
                 // In-memory config exists → served via priority-1 stale-cache path
-                cfg.configuration = Arc::new(Mutex::new(Some(Configuration::default())));
+                cfg.configuration = Arc::new(Mutex::new(Some(Arc::default())));
                 let r = cfg.get_configuration();
                 assert!(r.is_ok(), "Error: {}", r.unwrap_err());
                 assert!(r.unwrap().features.is_empty());
@@ -822,4 +1198,192 @@ mod tests {
             assert_eq!(r.unwrap().features.len(), 5);
         }
     }
+
+    #[rstest]
+    fn test_safe_mode(example_configuration_enterprise_path: std::path::PathBuf) {
+        let (tx, _) = std::sync::mpsc::channel();
+        let cfg = LiveConfigurationImpl {
+            configuration: Arc::new(Mutex::new(Some(Arc::default()))),
+            offline_mode: OfflineMode::Fail,
+            validation_mode: ValidationMode::default(),
+            expected_schema_version: None,
+            current_mode: Waitable::new(CurrentMode::Online),
+            update_thread: ThreadHandle {
+                _thread_termination_sender: tx,
+                thread_handle: None,
+                finished_thread_status_cached: None,
+            },
+            runtime_event_listeners: Arc::new(Mutex::new(Vec::new())),
+            safe_mode: Mutex::new(None),
+            last_error: Arc::default(),
+            offline_details: Arc::default(),
+            configuration_store: None,
+        };
+        let offline = AppConfigurationOffline::new(
+            &example_configuration_enterprise_path,
+            "dev",
+            "blue-charge",
+        )
+        .unwrap();
+
+        cfg.enter_safe_mode(OfflineMode::FallbackData(offline))
+            .unwrap();
+        // Updates received in safe mode are ignored
+        *cfg.configuration.lock().unwrap() = Some(Arc::default());
+        assert!(!cfg.get_configuration().unwrap().features.is_empty());
+
+        cfg.enter_safe_mode(OfflineMode::Fail).unwrap();
+        let error = crate::Error::from(cfg.get_configuration().unwrap_err());
+        assert_eq!(
+            error.offline_details().unwrap().reason,
+            CurrentModeOfflineReason::SafeMode
+        );
+
+        cfg.exit_safe_mode().unwrap();
+        assert!(cfg.get_configuration().unwrap().features.is_empty());
+
+        // Without a configuration to serve, safe mode is not entered
+        *cfg.configuration.lock().unwrap() = None;
+        assert_eq!(
+            cfg.enter_safe_mode(OfflineMode::Cache).unwrap_err(),
+            Error::ConfigurationNotYetAvailable
+        );
+        assert!(cfg.safe_mode.lock().unwrap().is_none());
+    }
+
+    #[rstest]
+    #[case::persisted(true)]
+    #[case::not_persisted(false)]
+    fn test_bootstrap_file_updates(#[case] persist_updates: bool) {
+        struct WebsocketReaderMock {
+            rx: mpsc::Receiver<tungstenite::Message>,
+        }
+        impl WebsocketReader for WebsocketReaderMock {
+            fn read_msg(&mut self) -> tungstenite::error::Result<tungstenite::Message> {
+                self.rx
+                    .recv()
+                    .map_err(|_| tungstenite::Error::ConnectionClosed)
+            }
+            fn flush_socket(&mut self) -> tungstenite::error::Result<()> {
+                Ok(())
+            }
+        }
+        struct ServerClientMock {
+            websocket_rx: Mutex<Option<mpsc::Receiver<tungstenite::Message>>>,
+        }
+        impl ServerClient for ServerClientMock {
+            fn get_configuration(
+                &self,
+                _configuration_id: &ConfigurationId,
+            ) -> NetworkResult<Configuration> {
+                Err(ProtocolError)
+            }
+
+            fn get_configuration_json(
+                &self,
+                _configuration_id: &ConfigurationId,
+            ) -> NetworkResult<ConfigurationJson> {
+                Ok(crate::network::serialization::fixtures::configuration_json_property1_enabled())
+            }
+
+            fn get_configuration_monitoring_websocket(
+                &self,
+                _collection: &ConfigurationId,
+            ) -> NetworkResult<Box<dyn WebsocketReader>> {
+                let rx = self
+                    .websocket_rx
+                    .lock()
+                    .unwrap()
+                    .take()
+                    .ok_or(ProtocolError)?;
+                Ok(Box::new(WebsocketReaderMock { rx }))
+            }
+        }
+
+        let path = std::env::temp_dir().join(format!(
+            "appconfiguration-bootstrap-{persist_updates}-{}.json",
+            std::process::id()
+        ));
+        crate::network::serialization::fixtures::configuration_json_feature1_enabled()
+            .write_to_file(&path, None)
+            .unwrap();
+        let (websocket_tx, websocket_rx) = mpsc::channel();
+        let xor = |data: &[u8]| Ok(data.iter().map(|byte| byte ^ 0x5a).collect());
+        let live_config = LiveConfigurationImpl::new(
+            OfflineMode::BootstrapFile {
+                path: path.clone(),
+                environment_id: "environment_id".into(),
+                collection_id: "collection_id".into(),
+                persist_updates,
+            },
+            ServerClientMock {
+                websocket_rx: Mutex::new(Some(websocket_rx)),
+            },
+            ConfigurationId::new(
+                "guid".into(),
+                "environment_id".into(),
+                "collection_id".into(),
+            )
+            .unwrap(),
+            vec![],
+            LiveConfigurationOptions {
+                cache_cipher: Some(CacheCipher::new(xor, xor)),
+                ..Default::default()
+            },
+            RuntimeEventEmitter::new(),
+        );
+        assert!(live_config.wait_until_online());
+        assert!(live_config.get_property("p1").is_ok());
+        drop(websocket_tx);
+        drop(live_config);
+
+        // The file is written as plain JSON, before the configuration is used
+        let bootstrap = LiveConfigurationImpl::read_bootstrap_configuration(
+            &path,
+            "environment_id",
+            "collection_id",
+            ValidationMode::Strict,
+            None,
+        )
+        .unwrap();
+        assert_eq!(bootstrap.get_property("p1").is_ok(), persist_updates);
+        assert_eq!(bootstrap.get_feature("f1").is_ok(), !persist_updates);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_encrypted_persistent_cache() {
+        let xor_cipher = |key: u8| {
+            let xor = move |data: &[u8]| Ok(data.iter().map(|byte| byte ^ key).collect());
+            CacheCipher::new(xor, xor)
+        };
+        let cipher = xor_cipher(0x5a);
+        let path = std::env::temp_dir().join(format!(
+            "appconfiguration-encrypted-cache-{}.json",
+            std::process::id()
+        ));
+        crate::network::serialization::fixtures::configuration_json_feature1_enabled()
+            .write_to_file(&path, Some(&cipher))
+            .unwrap();
+        let read = |cipher: Option<&CacheCipher>| {
+            LiveConfigurationImpl::read_persistent_cache_configuration(
+                &path,
+                "environment_id",
+                "collection_id",
+                ValidationMode::Strict,
+                None,
+                cipher,
+            )
+        };
+
+        let contents = std::fs::read(&path).unwrap();
+        assert!(!String::from_utf8_lossy(&contents).contains("feature_id"));
+        let configuration = read(Some(&cipher)).unwrap();
+        assert!(configuration.get_feature("f1").is_ok());
+
+        // Files that cannot be decrypted are ignored
+        assert!(read(Some(&xor_cipher(0x11))).is_none());
+        assert!(read(None).is_none());
+        std::fs::remove_file(&path).unwrap();
+    }
 }
@@ -12,14 +12,64 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! Keeps the configuration served up to date with the server.
+//!
+//! # Locks
+//!
+//! The update thread, the on-demand downloads of `get_feature_fresh()` and the
+//! threads of the application share some state. Locks are only nested in this
+//! order, so they cannot deadlock:
+//!
+//! 1. `FreshConfiguration::download`, one on-demand download at a time.
+//! 2. `ConfigurationStore::applied_at`, held while a configuration is
+//!    verified and applied. The configuration guards run with it held, so
+//!    they must not download configurations themselves.
+//! 3. The configuration served.
+//!
+//! Every other lock (safe mode, last error, offline details, runtime event
+//! listeners, fetch budget) is only held to read or replace its value, never
+//! while taking another lock or calling the code of the application. The
+//! current mode is read without locking, see [`Waitable`](crate::utils::Waitable).
+
+mod cache_cipher;
+mod configuration_guard;
+#[cfg(feature = "remote")]
+mod configuration_store;
 mod current_mode;
 mod errors;
+#[cfg(feature = "remote")]
+mod fetch_budget;
+#[cfg(feature = "remote")]
+#[allow(clippy::module_inception)]
 mod live_configuration;
 mod offline_mode;
+mod options;
+mod shared_cache;
+#[cfg(feature = "remote")]
+mod step_controller;
+#[cfg(feature = "remote")]
 mod update_thread_worker;
 
-pub use current_mode::{CurrentMode, CurrentModeOfflineReason};
-pub(crate) use errors::{Error, Result};
+pub use cache_cipher::CacheCipher;
+pub use configuration_guard::{ConfigurationGuard, GuardDecision};
+#[cfg(feature = "remote")]
+pub(crate) use configuration_store::{Applied, ConfigurationStore};
+#[cfg(feature = "remote")]
+pub use current_mode::CurrentMode;
+pub use current_mode::{CurrentModeOfflineReason, OfflineDetails};
+pub(crate) use errors::Error;
+#[cfg(feature = "remote")]
+pub(crate) use errors::Result;
+#[cfg(feature = "remote")]
+pub(crate) use fetch_budget::FetchBudget;
+#[cfg(feature = "remote")]
 pub use live_configuration::LiveConfiguration;
+#[cfg(feature = "remote")]
 pub(crate) use live_configuration::LiveConfigurationImpl;
 pub use offline_mode::OfflineMode;
+pub use options::LiveConfigurationOptions;
+pub use shared_cache::{CachedConfiguration, DistributedCache, SharedCache};
+#[cfg(feature = "remote")]
+pub use step_controller::StepController;
+#[cfg(feature = "test_utils")]
+pub use step_controller::WorkerStep;
@@ -13,6 +13,8 @@
 // limitations under the License.
 
 use crate::AppConfigurationOffline;
+#[cfg(feature = "remote")]
+use crate::ConfigurationSource;
 use std::path::{Path, PathBuf};
 
 /// Defines the behaviour of the client while the connection to the server
@@ -45,6 +47,10 @@ pub enum OfflineMode {
         path: PathBuf,
         environment_id: String,
         collection_id: String,
+        /// Writes the configurations received from the server to the file, in
+        /// its format and unencrypted, so that restarts fall back to the newest
+        /// configuration known instead of the one shipped with the application.
+        persist_updates: bool,
     },
 }
 
@@ -70,6 +76,16 @@ impl OfflineMode {
             path: path.as_ref().to_path_buf(),
             environment_id: environment_id.into(),
             collection_id: collection_id.into(),
+            persist_updates: false,
+        }
+    }
+
+    /// Source of the configurations read from the file of this mode.
+    #[cfg(feature = "remote")]
+    pub(crate) fn file_source(&self) -> ConfigurationSource {
+        match self {
+            Self::BootstrapFile { .. } => ConfigurationSource::Bootstrap,
+            _ => ConfigurationSource::PersistentCache,
         }
     }
 }
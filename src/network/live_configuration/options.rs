@@ -0,0 +1,129 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+#[cfg(feature = "remote")]
+use super::StepController;
+use super::{CacheCipher, ConfigurationGuard, SharedCache};
+#[cfg(feature = "remote")]
+use crate::network::{ServiceAddress, SocketOptions};
+use crate::{MeteringOptions, ThreadOptions, ValidationMode};
+
+/// Tuning of the background threads of a client: the one that keeps the
+/// configuration in sync with the server and the one that reports usage.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LiveConfigurationOptions {
+    /// Minimum time between two configuration fetches triggered by websocket
+    /// notifications.
+    ///
+    /// Notifications received within this window after a fetch are collapsed
+    /// into a single fetch, which runs on the next websocket activity once the
+    /// window has elapsed (at the latest with the next server heartbeat).
+    /// `Duration::ZERO` (the default) fetches on every notification.
+    pub refresh_coalescing_window: Duration,
+
+    /// How invalid features, properties and segments are handled, in the
+    /// configurations fetched from the server and in the cache and bootstrap files.
+    pub validation_mode: ValidationMode,
+
+    /// Label sent with the usage metering data, together with the SDK version
+    /// and, if [`MeteringOptions::send_host`] is set, the hostname, to tell
+    /// apart the deployments evaluating the same collection.
+    pub instance_label: Option<String>,
+
+    /// What the usage metering records and sends, see [`MeteringOptions`].
+    pub metering: MeteringOptions,
+
+    /// Schema version the configurations must declare. Configurations with any
+    /// other version, or none, are rejected and the previous one is kept.
+    /// Configurations carrying a checksum are always verified against it.
+    pub expected_schema_version: Option<u32>,
+
+    /// Addresses tried, in order, when the primary server is unreachable, e.g.
+    /// the same instance in another region.
+    ///
+    /// The client fails over to the next address after
+    /// [`failover_threshold`](Self::failover_threshold) consecutive failures to
+    /// connect or fetch, and periodically checks whether the primary address
+    /// recovered to fail back to it. Usage metering is always sent to the
+    /// primary address.
+    #[cfg(feature = "remote")]
+    pub fallback_service_addresses: Vec<ServiceAddress>,
+
+    /// Consecutive failures before failing over to the next address. `None`
+    /// (the default) fails over after 3.
+    pub failover_threshold: Option<u32>,
+
+    /// Run, in order, on every configuration received from the server before
+    /// it replaces the current one. The first one rejecting it keeps the
+    /// current configuration.
+    pub configuration_guards: Vec<ConfigurationGuard>,
+
+    /// Name, stack size and priority of the background thread.
+    pub thread_options: ThreadOptions,
+
+    /// Time the client constructor waits for a configuration to be available,
+    /// from the server or from the offline mode. When it elapses the
+    /// constructor fails with [`Error::InitialFetchTimeout`](crate::Error::InitialFetchTimeout).
+    /// `None` (the default) returns right away.
+    pub initial_fetch_timeout: Option<Duration>,
+
+    /// Fetches the configuration on this interval instead of listening to
+    /// update notifications on a websocket, for environments where websockets
+    /// are not allowed. `None` (the default) uses the websocket.
+    pub polling_interval: Option<Duration>,
+
+    /// Period over which [`unused_features`](crate::ConfigurationProvider::unused_features)
+    /// reports the features not evaluated. `None` (the default) covers the
+    /// whole life of the client.
+    pub unused_feature_window: Option<Duration>,
+
+    /// IDs of the features planned for removal, whose evaluations are
+    /// reported by [`deprecated_flags_in_use`](crate::ConfigurationProvider::deprecated_flags_in_use).
+    pub deprecated_features: Vec<String>,
+
+    /// Encrypts the configuration written to the persistent cache file. `None`
+    /// (the default) writes it as plain JSON.
+    pub cache_cipher: Option<CacheCipher>,
+
+    /// Shares the configurations downloaded with the clients of other replicas.
+    /// `None` (the default) always downloads them from the server.
+    pub shared_cache: Option<SharedCache>,
+
+    /// Configuration fetches sent to the server over any hour, to protect the
+    /// quota of the service when the network causes fast reconnection loops.
+    /// Once reached, fetches are postponed until the oldest one is an hour old
+    /// and counted in [`SdkStats::fetch_budget_exhaustions`](crate::SdkStats::fetch_budget_exhaustions).
+    /// The client keeps its mode and serves the configuration it has, this is
+    /// not a failure of the server. The downloads of `get_feature_fresh()`
+    /// count too, and fail while the budget is exhausted, and the websocket
+    /// is not reconnected until a fetch fits in the budget. Reads from the
+    /// shared cache do not count. `None` (the default) does not limit them.
+    pub max_fetches_per_hour: Option<u32>,
+
+    /// TCP keepalive, nodelay and connect timeout of the connections to the
+    /// server, see [`SocketOptions`].
+    #[cfg(feature = "remote")]
+    pub socket_options: SocketOptions,
+
+    /// Pauses the background thread at chosen steps, for tests, see
+    /// [`StepController`]. `None` (the default) never pauses it.
+    ///
+    /// Always present so enabling `test_utils` does not change the fields;
+    /// the controller is created with `test_utils::StepController`.
+    #[doc(hidden)]
+    #[cfg(feature = "remote")]
+    pub step_controller: Option<StepController>,
+}
@@ -0,0 +1,244 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(feature = "remote")]
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "remote")]
+use crate::errors::ConfigurationDataError;
+#[cfg(feature = "remote")]
+use crate::network::serialization::ConfigurationJson;
+use crate::{CacheCipher, ConfigurationId};
+
+/// A configuration stored in a [`DistributedCache`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedConfiguration {
+    /// Identifies the content of `payload`: its SHA-256, in hex.
+    pub etag: String,
+    /// When the client that stored it requested it from the server.
+    pub fetched_at: chrono::DateTime<chrono::Utc>,
+    /// The configuration, in the JSON format of the service, encrypted with
+    /// the [`CacheCipher`] of the client if it has one.
+    pub payload: Vec<u8>,
+}
+
+/// Storage shared by the clients of several replicas, e.g. Redis, keeping the
+/// last configuration downloaded for each collection and environment.
+///
+/// Errors are logged, and the configuration is then downloaded from the server.
+pub trait DistributedCache: Send + Sync {
+    /// Returns the configuration stored for `configuration_id`, if any.
+    fn get(
+        &self,
+        configuration_id: &ConfigurationId,
+    ) -> Result<Option<CachedConfiguration>, String>;
+
+    /// Stores `configuration` for `configuration_id`, replacing the previous one.
+    fn put(
+        &self,
+        configuration_id: &ConfigurationId,
+        configuration: &CachedConfiguration,
+    ) -> Result<(), String>;
+}
+
+/// Shares the configurations downloaded by the clients of a fleet, see
+/// [`LiveConfigurationOptions::shared_cache`](crate::LiveConfigurationOptions::shared_cache).
+///
+/// After an update notification, the clients wait a random delay before
+/// downloading the configuration. The first one stores it in the cache and
+/// the others read it from there, as long as it was downloaded after they got
+/// the notification. Other downloads, e.g. at startup, read it if it is not
+/// older than `max_age`. The times of the replicas are compared, so their
+/// clocks have to be synchronized.
+#[derive(Clone)]
+pub struct SharedCache {
+    cache: Arc<dyn DistributedCache>,
+    max_age: Duration,
+    /// See [`LiveConfigurationOptions::cache_cipher`](crate::LiveConfigurationOptions::cache_cipher).
+    cipher: Option<CacheCipher>,
+}
+
+impl SharedCache {
+    pub fn new(cache: impl DistributedCache + 'static, max_age: Duration) -> Self {
+        Self {
+            cache: Arc::new(cache),
+            max_age,
+            cipher: None,
+        }
+    }
+
+    /// Encrypts the payloads stored with `cipher`, and decrypts those read.
+    #[cfg(feature = "remote")]
+    pub(crate) fn with_cipher(mut self, cipher: Option<CacheCipher>) -> Self {
+        self.cipher = cipher;
+        self
+    }
+
+    /// Returns the stored configuration if it was downloaded after `not_before`,
+    /// or is not older than `max_age` without it.
+    #[cfg(feature = "remote")]
+    pub(crate) fn read(
+        &self,
+        configuration_id: &ConfigurationId,
+        not_before: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Option<ConfigurationJson> {
+        let not_before = not_before.unwrap_or_else(|| {
+            chrono::Utc::now() - chrono::Duration::from_std(self.max_age).unwrap_or_default()
+        });
+        let cached = match self.cache.get(configuration_id) {
+            Ok(cached) => cached?,
+            Err(e) => {
+                log::warn!("Failed to read the configuration from the shared cache: {e}");
+                return None;
+            }
+        };
+        if cached.fetched_at < not_before {
+            return None;
+        }
+        let payload = match &self.cipher {
+            Some(cipher) => match cipher.decrypt(&cached.payload) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    log::warn!("Ignoring the configuration of the shared cache: {e}");
+                    return None;
+                }
+            },
+            None => cached.payload,
+        };
+        match serde_json::from_slice(&payload) {
+            Ok(configuration) => {
+                log::debug!(
+                    "Configuration {} read from the shared cache, downloaded at {}",
+                    cached.etag,
+                    cached.fetched_at
+                );
+                Some(configuration)
+            }
+            Err(e) => {
+                log::warn!("Ignoring the configuration of the shared cache: {e}");
+                None
+            }
+        }
+    }
+
+    /// Stores `configuration`, requested from the server at `fetched_at`.
+    #[cfg(feature = "remote")]
+    pub(crate) fn store(
+        &self,
+        configuration_id: &ConfigurationId,
+        configuration: &ConfigurationJson,
+        fetched_at: chrono::DateTime<chrono::Utc>,
+    ) {
+        let payload = serde_json::to_vec(configuration)
+            .map_err(|e| crate::Error::from(ConfigurationDataError::Serialization(e)))
+            .and_then(|payload| match &self.cipher {
+                Some(cipher) => cipher.encrypt(&payload),
+                None => Ok(payload),
+            });
+        let payload = match payload {
+            Ok(payload) => payload,
+            Err(e) => {
+                log::warn!("Failed to serialize the configuration for the shared cache: {e}");
+                return;
+            }
+        };
+        let etag = Sha256::digest(&payload)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        let cached = CachedConfiguration {
+            etag,
+            fetched_at,
+            payload,
+        };
+        if let Err(e) = self.cache.put(configuration_id, &cached) {
+            log::warn!("Failed to write the configuration to the shared cache: {e}");
+        }
+    }
+}
+
+impl std::fmt::Debug for SharedCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedCache")
+            .field("max_age", &self.max_age)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Two shared caches are equal if they use the same cache, maximum age and cipher.
+impl PartialEq for SharedCache {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.cache, &other.cache)
+            && self.max_age == other.max_age
+            && self.cipher == other.cipher
+    }
+}
+
+impl Eq for SharedCache {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryCache(Mutex<Option<CachedConfiguration>>);
+    impl DistributedCache for Arc<MemoryCache> {
+        fn get(
+            &self,
+            _configuration_id: &ConfigurationId,
+        ) -> Result<Option<CachedConfiguration>, String> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+
+        fn put(
+            &self,
+            _configuration_id: &ConfigurationId,
+            configuration: &CachedConfiguration,
+        ) -> Result<(), String> {
+            *self.0.lock().unwrap() = Some(configuration.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_encrypted_payload() {
+        let reversed = |data: &[u8]| Ok(data.iter().rev().copied().collect());
+        let cipher = CacheCipher::new(reversed, reversed);
+        let cache = Arc::new(MemoryCache::default());
+        let shared_cache =
+            SharedCache::new(cache.clone(), Duration::from_secs(60)).with_cipher(Some(cipher));
+        let configuration_id =
+            ConfigurationId::new("guid".into(), "dev".into(), "collection".into()).unwrap();
+        let configuration =
+            crate::network::serialization::fixtures::configuration_json_feature1_enabled();
+
+        shared_cache.store(&configuration_id, &configuration, chrono::Utc::now());
+        let payload = cache.0.lock().unwrap().clone().unwrap().payload;
+        assert!(serde_json::from_slice::<serde_json::Value>(&payload).is_err());
+
+        let read = shared_cache.read(&configuration_id, None).unwrap();
+        assert_eq!(
+            serde_json::to_value(read).unwrap(),
+            serde_json::to_value(configuration).unwrap()
+        );
+
+        // Clients without the cipher download the configuration instead
+        let plain = SharedCache::new(cache, Duration::from_secs(60));
+        assert!(plain.read(&configuration_id, None).is_none());
+    }
+}
@@ -0,0 +1,188 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::time::Duration;
+
+use crate::utils::MutexExt;
+
+/// Points of the update thread where a [`StepController`] can pause it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WorkerStep {
+    /// Before a configuration is requested, from the server or the shared cache.
+    BeforeFetch,
+    /// After a configuration was requested, and is in use if it was received
+    /// and accepted.
+    AfterFetch,
+    /// Before waiting for the next websocket message.
+    BeforeWebsocketRead,
+    /// After a websocket message was handled, including the fetch of the
+    /// configuration it notified.
+    AfterWebsocketRead,
+}
+
+/// Pauses the update thread of a client at chosen [`WorkerStep`]s, so tests
+/// can run code while the thread is at a known point, e.g. evaluate a feature
+/// right before and right after a new configuration is applied.
+///
+/// Clones control the same thread. Pass one in
+/// [`LiveConfigurationOptions::step_controller`](crate::LiveConfigurationOptions::step_controller)
+/// when creating the client:
+///
+/// ```ignore
+/// let controller = StepController::new();
+/// controller.pause_at(WorkerStep::BeforeFetch);
+/// // ... create the client with the controller
+/// assert_eq!(controller.wait_paused(Duration::from_secs(5)), Some(WorkerStep::BeforeFetch));
+/// // ... the client has no configuration yet
+/// controller.resume();
+/// ```
+///
+/// Wait for the steps rather than for the state of the client: e.g. the
+/// client is online before the thread reaches [`WorkerStep::AfterFetch`].
+///
+/// The thread stays paused while the client is dropped, which then gives up
+/// waiting for it after a few seconds. Call [`StepController::release`] first.
+#[derive(Debug, Clone, Default)]
+pub struct StepController {
+    inner: Arc<(Mutex<StepState>, Condvar)>,
+}
+
+#[derive(Debug, Default)]
+struct StepState {
+    pause_at: HashSet<WorkerStep>,
+    paused_at: Option<WorkerStep>,
+}
+
+impl StepController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pauses the thread every time it reaches `step`, from now on.
+    pub fn pause_at(&self, step: WorkerStep) {
+        let (mutex, _) = &*self.inner;
+        mutex.lock_unpoisoned().pause_at.insert(step);
+    }
+
+    /// Stops pausing the thread at `step`. If it is paused there, it stays
+    /// paused until [`StepController::resume`] is called.
+    pub fn run_through(&self, step: WorkerStep) {
+        let (mutex, _) = &*self.inner;
+        mutex.lock_unpoisoned().pause_at.remove(&step);
+    }
+
+    /// Waits until the thread is paused, returning where, or `None` if it did
+    /// not pause within `timeout`.
+    pub fn wait_paused(&self, timeout: Duration) -> Option<WorkerStep> {
+        let (mutex, condvar) = &*self.inner;
+        let state = mutex.lock_unpoisoned();
+        let (state, _) = condvar
+            .wait_timeout_while(state, timeout, |state| state.paused_at.is_none())
+            .unwrap_or_else(PoisonError::into_inner);
+        state.paused_at
+    }
+
+    /// Where the thread is paused, if it is.
+    pub fn paused_at(&self) -> Option<WorkerStep> {
+        let (mutex, _) = &*self.inner;
+        mutex.lock_unpoisoned().paused_at
+    }
+
+    /// Lets the paused thread continue until the next step it pauses at.
+    /// Returns whether it was paused.
+    pub fn resume(&self) -> bool {
+        let (mutex, condvar) = &*self.inner;
+        let was_paused = mutex.lock_unpoisoned().paused_at.take().is_some();
+        condvar.notify_all();
+        was_paused
+    }
+
+    /// Stops pausing the thread anywhere, and resumes it if it is paused.
+    pub fn release(&self) {
+        let (mutex, condvar) = &*self.inner;
+        let mut state = mutex.lock_unpoisoned();
+        state.pause_at.clear();
+        state.paused_at = None;
+        condvar.notify_all();
+    }
+}
+
+impl StepController {
+    /// Called by the update thread at `step`. Blocks until resumed if it has
+    /// to pause there.
+    pub(crate) fn reached(&self, step: WorkerStep) {
+        let (mutex, condvar) = &*self.inner;
+        let mut state = mutex.lock_unpoisoned();
+        if !state.pause_at.contains(&step) {
+            return;
+        }
+        state.paused_at = Some(step);
+        condvar.notify_all();
+        let _state = condvar
+            .wait_while(state, |state| state.paused_at.is_some())
+            .unwrap_or_else(PoisonError::into_inner);
+    }
+}
+
+impl PartialEq for StepController {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Eq for StepController {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pause_and_resume() {
+        let controller = StepController::new();
+        // Steps not paused at are run through
+        controller.reached(WorkerStep::BeforeFetch);
+        assert!(!controller.resume());
+
+        controller.pause_at(WorkerStep::BeforeFetch);
+        controller.pause_at(WorkerStep::AfterFetch);
+        let thread = {
+            let controller = controller.clone();
+            std::thread::spawn(move || {
+                controller.reached(WorkerStep::BeforeFetch);
+                controller.reached(WorkerStep::BeforeWebsocketRead);
+                controller.reached(WorkerStep::AfterFetch);
+                controller.reached(WorkerStep::BeforeFetch);
+            })
+        };
+        let timeout = Duration::from_secs(5);
+        assert_eq!(
+            controller.wait_paused(timeout),
+            Some(WorkerStep::BeforeFetch)
+        );
+        assert!(controller.resume());
+        assert_eq!(
+            controller.wait_paused(timeout),
+            Some(WorkerStep::AfterFetch)
+        );
+        assert_eq!(controller.paused_at(), Some(WorkerStep::AfterFetch));
+
+        controller.run_through(WorkerStep::BeforeFetch);
+        controller.resume();
+        thread.join().unwrap();
+        assert_eq!(controller.paused_at(), None);
+        assert_eq!(controller.wait_paused(Duration::from_millis(10)), None);
+    }
+}
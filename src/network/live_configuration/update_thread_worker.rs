@@ -12,26 +12,31 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::path::{Path, PathBuf};
+use std::cell::Cell;
+use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::Receiver;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
 use std::sync::{Arc, Mutex};
 
 use super::CurrentMode;
-use super::current_mode::CurrentModeOfflineReason;
-use super::{Error, Result};
-use crate::ConfigurationId;
+use super::configuration_guard::ConfigurationGuard;
+use super::configuration_store::{Applied, ConfigurationStore};
+use super::current_mode::{CurrentModeOfflineReason, OfflineDetails};
+use super::step_controller::{StepController, WorkerStep};
+use super::{Error, FetchBudget, Result};
 use crate::client::{
-    RuntimeEvent, RuntimeEventKind, RuntimeEventListener, RuntimeMode, RuntimeStatus,
+    RuntimeEvent, RuntimeEventEmitter, RuntimeEventKind, RuntimeEventListener, RuntimeMode,
+    RuntimeStatus, SdkError, SdkErrorSnapshot,
 };
-use crate::models::Configuration;
-use crate::network::NetworkError;
+use crate::models::{Configuration, ValidationMode};
 use crate::network::connectivity::check_internet_once;
 use crate::network::http_client::{ServerClient, WebsocketReader};
-use crate::utils::Waitable;
+use crate::network::serialization::{ConfigurationJson, WebsocketMessage};
+use crate::network::{NetworkError, NetworkErrorCategory, NetworkResult};
+use crate::utils::{MutexExt, Waitable, panic_message};
+use crate::{CacheCipher, ConfigurationId, SharedCache};
 use rand::Rng;
 use std::time::{Duration, Instant};
-pub(crate) const SERVER_HEARTBEAT: &str = "test message";
 
 const RETRY_INITIAL_DELAY: Duration = Duration::from_secs(15);
 const RETRY_MAX_DELAY: Duration = Duration::from_secs(60 * 60);
@@ -41,42 +46,387 @@ const CONFIG_REFRESH_BASE_DELAY: Duration = Duration::from_secs(2 * 60); // 2 mi
 const CONFIG_REFRESH_CAP_DELAY: Duration = Duration::from_secs(60 * 60); // 1 hour
 const CONFIG_REFRESH_MULTIPLIER: u32 = 2;
 
+const DEFAULT_FAILOVER_THRESHOLD: u32 = 3;
+const FAILBACK_PROBE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
 pub(crate) struct UpdateThreadWorker<T: ServerClient> {
     server_client: T,
     configuration_id: ConfigurationId,
-    configuration: Arc<Mutex<Option<Configuration>>>,
+    /// Verifies the configurations received and serves the accepted ones.
+    store: ConfigurationStore,
     current_mode: Waitable<CurrentMode>,
-    persistent_cache_path: Option<PathBuf>,
+    /// When the last update notification not followed by a refresh was received.
+    notified_at: Cell<Option<chrono::DateTime<chrono::Utc>>>,
     retry_pending: Arc<AtomicBool>,
     runtime_event_listeners: Arc<Mutex<Vec<RuntimeEventListener>>>,
     is_connected: Arc<AtomicBool>,
+    /// See [`crate::LiveConfigurationOptions::refresh_coalescing_window`].
+    refresh_coalescing_window: Duration,
+    /// Receives the failures that stop this thread.
+    error_emitter: RuntimeEventEmitter,
+    /// When the last configuration fetch started.
+    last_refresh_at: Cell<Option<Instant>>,
+    /// A websocket notification arrived that has not been followed by a fetch yet.
+    refresh_pending: Cell<bool>,
+    /// No fetch before this instant, as requested by the server with `Retry-After`.
+    retry_not_before: Cell<Option<Instant>>,
+    /// See [`crate::LiveConfigurationOptions::failover_threshold`].
+    failover_threshold: u32,
+    /// Failures to connect or fetch since the last success or failover.
+    consecutive_failures: Cell<u32>,
+    /// When the client last failed over, or probed the primary address, while
+    /// using a fallback address.
+    failed_over_at: Cell<Option<Instant>>,
+    /// The configuration the primary address served when failing back to it,
+    /// and when it was requested. Applied by the next fetch instead of
+    /// downloading it again.
+    probed_configuration: Cell<Option<(ConfigurationJson, chrono::DateTime<chrono::Utc>)>>,
+    /// See [`crate::ConfigurationProvider::last_error`].
+    last_error: Arc<Mutex<Option<SdkErrorSnapshot>>>,
+    /// Errors recorded since the last configuration received from the server.
+    errors_since_refresh: Cell<u32>,
+    /// Returned in [`Error::Offline`], updated when going offline and
+    /// scheduling retries.
+    offline_details: Arc<Mutex<Option<OfflineDetails>>>,
+    /// HTTP status of the last failed request to the server.
+    last_http_status: Cell<Option<u16>>,
+    /// See [`crate::LiveConfigurationOptions::polling_interval`].
+    polling_interval: Option<Duration>,
+    /// The last refresh failed on this side: the [`FetchBudget`] postponed
+    /// it, or the configuration received was rejected. It is not counted as
+    /// a failure of the server and does not change the mode.
+    client_side_failure: Cell<bool>,
+    /// See [`crate::LiveConfigurationOptions::step_controller`].
+    step_controller: Option<StepController>,
+}
+
+/// Waits for `timeout`. Returns `Err(())` if the thread is asked to terminate
+/// meanwhile.
+fn wait(
+    thread_termination_receiver: &Receiver<()>,
+    timeout: Duration,
+) -> std::result::Result<(), ()> {
+    match thread_termination_receiver.recv_timeout(timeout) {
+        Err(RecvTimeoutError::Timeout) => Ok(()),
+        Ok(_) | Err(RecvTimeoutError::Disconnected) => Err(()),
+    }
 }
 
 impl<T: ServerClient> UpdateThreadWorker<T> {
     pub(crate) fn new(
         server_client: T,
         configuration_id: ConfigurationId,
-        configuration: Arc<Mutex<Option<Configuration>>>,
+        configuration: Arc<Mutex<Option<Arc<Configuration>>>>,
         current_mode: Waitable<CurrentMode>,
         runtime_event_listeners: Arc<Mutex<Vec<RuntimeEventListener>>>,
     ) -> Self {
         Self {
             server_client,
+            store: ConfigurationStore::new(configuration_id.clone(), configuration),
             configuration_id,
-            configuration,
             current_mode,
-            persistent_cache_path: None,
+            notified_at: Cell::new(None),
             retry_pending: Arc::new(AtomicBool::new(false)),
             runtime_event_listeners,
             is_connected: Arc::new(AtomicBool::new(true)),
+            refresh_coalescing_window: Duration::ZERO,
+            error_emitter: RuntimeEventEmitter::new(),
+            last_refresh_at: Cell::new(None),
+            refresh_pending: Cell::new(false),
+            retry_not_before: Cell::new(None),
+            failover_threshold: DEFAULT_FAILOVER_THRESHOLD,
+            consecutive_failures: Cell::new(0),
+            failed_over_at: Cell::new(None),
+            probed_configuration: Cell::new(None),
+            last_error: Arc::default(),
+            errors_since_refresh: Cell::new(0),
+            offline_details: Arc::default(),
+            last_http_status: Cell::new(None),
+            polling_interval: None,
+            client_side_failure: Cell::new(false),
+            step_controller: None,
+        }
+    }
+
+    pub(crate) fn with_refresh_coalescing_window(mut self, window: Duration) -> Self {
+        self.refresh_coalescing_window = window;
+        self
+    }
+
+    pub(crate) fn with_polling_interval(mut self, interval: Option<Duration>) -> Self {
+        self.polling_interval = interval;
+        self
+    }
+
+    pub(crate) fn with_max_fetches_per_hour(mut self, max_fetches: Option<u32>) -> Self {
+        self.store = self.store.with_fetch_budget(FetchBudget::new(max_fetches));
+        self
+    }
+
+    pub(crate) fn with_step_controller(mut self, controller: Option<StepController>) -> Self {
+        self.step_controller = controller;
+        self
+    }
+
+    pub(crate) fn with_validation_mode(mut self, validation_mode: ValidationMode) -> Self {
+        self.store = self.store.with_validation_mode(validation_mode);
+        self
+    }
+
+    pub(crate) fn with_expected_schema_version(mut self, version: Option<u32>) -> Self {
+        self.store = self.store.with_expected_schema_version(version);
+        self
+    }
+
+    pub(crate) fn with_failover_threshold(mut self, threshold: Option<u32>) -> Self {
+        self.failover_threshold = threshold.unwrap_or(DEFAULT_FAILOVER_THRESHOLD).max(1);
+        self
+    }
+
+    pub(crate) fn with_last_error(
+        mut self,
+        last_error: Arc<Mutex<Option<SdkErrorSnapshot>>>,
+    ) -> Self {
+        self.last_error = last_error;
+        self
+    }
+
+    pub(crate) fn with_offline_details(
+        mut self,
+        offline_details: Arc<Mutex<Option<OfflineDetails>>>,
+    ) -> Self {
+        self.offline_details = offline_details;
+        self
+    }
+
+    pub(crate) fn with_configuration_guards(mut self, guards: Vec<ConfigurationGuard>) -> Self {
+        self.store = self.store.with_configuration_guards(guards);
+        self
+    }
+
+    pub(crate) fn with_error_emitter(mut self, error_emitter: RuntimeEventEmitter) -> Self {
+        self.error_emitter = error_emitter;
+        self
+    }
+
+    /// Pauses at `step` if the [`StepController`] asks to.
+    fn reached(&self, step: WorkerStep) {
+        if let Some(controller) = &self.step_controller {
+            controller.reached(step);
+        }
+    }
+
+    /// Whether a fetch now would fall within the coalescing window of the previous one.
+    fn refresh_throttled(&self) -> bool {
+        self.last_refresh_at
+            .get()
+            .is_some_and(|last| last.elapsed() < self.refresh_coalescing_window)
+    }
+
+    /// Keeps `error`, after which the thread keeps running, as the last error of the client.
+    fn record_error(&self, error: &dyn std::fmt::Display) {
+        self.record_error_with(error, None, true);
+    }
+
+    /// Keeps `error` as the last error of the client.
+    fn record_error_with(
+        &self,
+        error: &dyn std::fmt::Display,
+        category: Option<NetworkErrorCategory>,
+        will_retry: bool,
+    ) {
+        let retry_count = self.errors_since_refresh.get();
+        self.errors_since_refresh.set(retry_count.saturating_add(1));
+        *self.last_error.lock_unpoisoned() = Some(SdkErrorSnapshot {
+            message: error.to_string(),
+            timestamp: chrono::Utc::now(),
+            retry_count,
+            category,
+            will_retry,
+        });
+    }
+
+    /// Keeps the failed request to the server as the last error of the client.
+    fn record_network_error(&self, error: &NetworkError) {
+        let (category, will_retry) = (error.category(), error.is_recoverable());
+        log::info!(
+            "Request to the App Configuration server failed ({category} error, {}): {error}",
+            if will_retry { "retrying" } else { "giving up" }
+        );
+        self.record_error_with(error, Some(category), will_retry);
+        self.last_http_status.set(error.http_status());
+    }
+
+    /// Records when the next attempt to get online is made.
+    fn record_next_retry(&self, delay: Duration) {
+        if let Some(details) = self.offline_details.lock_unpoisoned().as_mut() {
+            details.next_retry_at = chrono::Duration::from_std(delay)
+                .ok()
+                .map(|delay| chrono::Utc::now() + delay);
+        }
+    }
+
+    /// Counts a failure to connect to or fetch from the active service address
+    /// and fails over to the next one when the threshold is reached. Returns
+    /// whether the active address changed.
+    fn record_server_failure(&self) -> bool {
+        let address_count = self.server_client.service_address_count();
+        if address_count < 2 {
+            return false;
+        }
+        let failures = self.consecutive_failures.get().saturating_add(1);
+        if failures < self.failover_threshold {
+            self.consecutive_failures.set(failures);
+            return false;
+        }
+
+        let next = (self.server_client.active_service_address() + 1) % address_count;
+        log::warn!(
+            "[WORKER] {failures} consecutive server failures — failing over to service address #{next}"
+        );
+        self.server_client.set_active_service_address(next);
+        self.consecutive_failures.set(0);
+        self.failed_over_at.set((next != 0).then(Instant::now));
+        true
+    }
+
+    /// While a fallback address is in use, checks periodically whether the
+    /// primary address serves the configuration again and switches back to it.
+    /// Returns whether the active address changed.
+    fn fail_back_if_primary_recovered(&self) -> bool {
+        let active = self.server_client.active_service_address();
+        let probe_due = self
+            .failed_over_at
+            .get()
+            .is_some_and(|at| at.elapsed() >= FAILBACK_PROBE_INTERVAL);
+        if active == 0 || !probe_due || self.store.fetch_budget().take().is_some() {
+            return false;
+        }
+
+        self.failed_over_at.set(Some(Instant::now()));
+        self.server_client.set_active_service_address(0);
+        let fetched_at = chrono::Utc::now();
+        match self
+            .server_client
+            .get_configuration_json(&self.configuration_id)
+        {
+            Ok(config_json) => {
+                log::info!("[WORKER] Primary service address recovered — failing back");
+                self.probed_configuration
+                    .set(Some((config_json, fetched_at)));
+                self.failed_over_at.set(None);
+                self.consecutive_failures.set(0);
+                true
+            }
+            Err(e) => {
+                log::debug!("[WORKER] Primary service address still unavailable: {e}");
+                self.server_client.set_active_service_address(active);
+                false
+            }
+        }
+    }
+
+    /// Time left until the server accepts a new fetch, if it asked to wait and
+    /// that time has not passed yet.
+    fn retry_after_remaining(&self) -> Option<Duration> {
+        self.retry_not_before
+            .get()
+            .map(|instant| instant.saturating_duration_since(Instant::now()))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Runs the pending refresh, if any, unless it has to be coalesced with a later one.
+    fn refresh_if_pending(&self, apply_jitter: bool) -> Result<()> {
+        if !self.refresh_pending.get() {
+            return Ok(());
+        }
+        if let Some(remaining) = self.retry_after_remaining() {
+            log::debug!(
+                "[WORKER] Config refresh postponed — the server asked to retry in {:.2}s.",
+                remaining.as_secs_f64()
+            );
+            return Ok(());
+        }
+        if self.refresh_throttled() {
+            log::debug!(
+                "[WORKER] Config refresh coalesced — last fetch was less than {:.2}s ago.",
+                self.refresh_coalescing_window.as_secs_f64()
+            );
+            return Ok(());
+        }
+
+        if apply_jitter {
+            let jitter_ms = rand::rng().random_range(0..5000u64);
+            if jitter_ms > 0 {
+                log::debug!(
+                    "[WORKER] Config refresh will start in {:.2}s (jitter).",
+                    jitter_ms as f64 / 1000.0
+                );
+                std::thread::sleep(Duration::from_millis(jitter_ms));
+            }
         }
+        self.update_configuration_from_server_and_current_mode_with_reason(
+            CurrentModeOfflineReason::FailedToGetNewConfiguration,
+            true,
+        )?;
+        Ok(())
     }
 
     pub(crate) fn with_persistent_cache_file(mut self, path: impl AsRef<Path>) -> Self {
-        self.persistent_cache_path = Some(path.as_ref().to_path_buf());
+        self.store = self.store.with_persistent_cache_file(path);
+        self
+    }
+
+    pub(crate) fn with_cache_cipher(mut self, cipher: Option<CacheCipher>) -> Self {
+        self.store = self.store.with_cache_cipher(cipher);
+        self
+    }
+
+    pub(crate) fn with_shared_cache(mut self, shared_cache: Option<SharedCache>) -> Self {
+        self.store = self.store.with_shared_cache(shared_cache);
         self
     }
 
+    /// Applies the configurations downloaded on demand like those of this thread.
+    pub(crate) fn configuration_store(&self) -> &ConfigurationStore {
+        &self.store
+    }
+
+    /// Returns the configuration downloaded when failing back to the primary
+    /// address if any, from the shared cache if another client downloaded it
+    /// recently enough, from the server otherwise. The second item is when it
+    /// was requested from the server, `None` if it was read from the shared
+    /// cache.
+    fn fetch_configuration_json(
+        &self,
+    ) -> NetworkResult<(ConfigurationJson, Option<chrono::DateTime<chrono::Utc>>)> {
+        let notified_at = self.notified_at.take();
+        if let Some((config_json, fetched_at)) = self.probed_configuration.take() {
+            return Ok((config_json, Some(fetched_at)));
+        }
+        if let Some(config_json) = self
+            .store
+            .shared_cache()
+            .and_then(|cache| cache.read(&self.configuration_id, notified_at))
+        {
+            return Ok((config_json, None));
+        }
+        if let Some(retry_after) = self.store.fetch_budget().take() {
+            log::warn!(
+                "[WORKER] Config fetch postponed {:.2}s — the budget of fetches per hour is exhausted.",
+                retry_after.as_secs_f64()
+            );
+            self.error_emitter
+                .emit_error(SdkError::FetchBudgetExhausted(retry_after));
+            return Err(NetworkError::FetchBudgetExhausted { retry_after });
+        }
+        let fetched_at = chrono::Utc::now();
+        let config_json = self
+            .server_client
+            .get_configuration_json(&self.configuration_id)?;
+        Ok((config_json, Some(fetched_at)))
+    }
+
     /// Single-shot (no retries) connectivity check used inside the backoff polling loop.
     /// Cheaper than the full 3-retry check (max 5s vs 15s) so the loop genuinely
     /// wakes ~every 500ms rather than ~every 5.5s while offline.
@@ -99,7 +449,9 @@ impl<T: ServerClient> UpdateThreadWorker<T> {
             NetworkError::ReqwestError(_)
             | NetworkError::WebsocketTimeout
             | NetworkError::TokenProviderError(_)
-            | NetworkError::TungsteniteError(_) => {
+            | NetworkError::TokenRequestRejected { .. }
+            | NetworkError::TungsteniteError(_)
+            | NetworkError::ServerError(_) => {
                 // Could be internet or server — mark as generic WebsocketError.
                 // The polling loop will detect true internet loss separately.
                 CurrentModeOfflineReason::WebsocketError
@@ -110,12 +462,17 @@ impl<T: ServerClient> UpdateThreadWorker<T> {
             | NetworkError::DeserializationError(_)
             | NetworkError::UrlParseError(_)
             | NetworkError::InvalidHeaderValue(_)
-            | NetworkError::CannotAcquireLock => CurrentModeOfflineReason::WebsocketError,
+            | NetworkError::RateLimited { .. }
+            | NetworkError::FetchBudgetExhausted { .. }
+            | NetworkError::Unauthorized
+            | NetworkError::ConfigurationNotFound { .. } => {
+                CurrentModeOfflineReason::WebsocketError
+            }
         }
     }
 
     fn get_runtime_status(&self) -> Result<RuntimeStatus> {
-        let mode = self.current_mode.get()?;
+        let mode = self.current_mode.get();
         let status = match mode {
             CurrentMode::Online => RuntimeStatus {
                 is_connected: true,
@@ -137,7 +494,7 @@ impl<T: ServerClient> UpdateThreadWorker<T> {
     }
 
     fn emit_runtime_event(&self, kind: RuntimeEventKind) -> Result<()> {
-        let listeners = self.runtime_event_listeners.lock()?.clone();
+        let listeners = self.runtime_event_listeners.lock_unpoisoned().clone();
         let event = RuntimeEvent {
             kind,
             status: self.get_runtime_status()?,
@@ -159,8 +516,22 @@ impl<T: ServerClient> UpdateThreadWorker<T> {
             _ => RuntimeEventKind::Disconnected,
         };
 
-        self.current_mode
-            .set(CurrentMode::Offline(offline_reason))?;
+        {
+            let was_offline = matches!(self.current_mode.get(), CurrentMode::Offline(_));
+            let mut details = self.offline_details.lock_unpoisoned();
+            let since = details
+                .as_ref()
+                .filter(|_| was_offline)
+                .map(|details| details.since)
+                .unwrap_or_else(chrono::Utc::now);
+            *details = Some(OfflineDetails {
+                reason: offline_reason.clone(),
+                http_status: self.last_http_status.get(),
+                since,
+                next_retry_at: None,
+            });
+        }
+        self.current_mode.set(CurrentMode::Offline(offline_reason));
         self.emit_runtime_event(kind)
     }
 
@@ -226,6 +597,7 @@ impl<T: ServerClient> UpdateThreadWorker<T> {
         }
 
         let delay = Self::calculate_retry_delay(attempt);
+        self.record_next_retry(delay);
         log::info!(
             "Websocket retry scheduled in {:.2} seconds (attempt #{})",
             delay.as_secs_f64(),
@@ -247,12 +619,9 @@ impl<T: ServerClient> UpdateThreadWorker<T> {
             let remaining = deadline.saturating_duration_since(Instant::now());
             let sleep_for = remaining.min(POLL_INTERVAL);
 
-            match thread_termination_receiver.recv_timeout(sleep_for) {
-                Ok(_) | Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
-                    result = Err(());
-                    break;
-                }
-                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+            if wait(thread_termination_receiver, sleep_for).is_err() {
+                result = Err(());
+                break;
             }
 
             // Short-circuit if internet came back while we were waiting.
@@ -280,17 +649,17 @@ impl<T: ServerClient> UpdateThreadWorker<T> {
             return Ok(());
         }
 
-        let delay = Self::calculate_config_refresh_retry_delay(attempt);
+        // Never sooner than the server asked for
+        let delay = Self::calculate_config_refresh_retry_delay(attempt)
+            .max(self.retry_after_remaining().unwrap_or_default());
+        self.record_next_retry(delay);
         log::warn!(
             "Config refresh retry scheduled in {:.2} minutes (attempt #{})",
             delay.as_secs_f64() / 60.0,
             attempt + 1
         );
 
-        let result = match thread_termination_receiver.recv_timeout(delay) {
-            Ok(_) | Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => Err(()),
-            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => Ok(()),
-        };
+        let result = wait(thread_termination_receiver, delay);
         self.retry_pending.store(false, Ordering::SeqCst);
         result
     }
@@ -307,12 +676,28 @@ impl<T: ServerClient> UpdateThreadWorker<T> {
     /// The loop exits when:
     /// * a termination signal is received via `thread_termination_receiver`, OR
     /// * an unrecoverable error occurs (see [`UpdateThreadWorker::recoverable_error`])
-
     fn run_internal(&self, thread_termination_receiver: Receiver<()>) -> Result<()> {
+        if let Some(interval) = self.polling_interval {
+            return self.run_polling(&thread_termination_receiver, interval);
+        }
         let mut websocket_retry_attempt = 0u32;
         let mut config_refresh_retry_attempt = 0u32;
 
         'outer: loop {
+            // The configuration is fetched once connected
+            if let Some(exhausted_for) = self.store.fetch_budget().exhausted_for() {
+                log::warn!(
+                    "[WORKER] WebSocket connection postponed {:.2}s — the budget of fetches per hour is exhausted.",
+                    exhausted_for.as_secs_f64()
+                );
+                self.error_emitter
+                    .emit_error(SdkError::FetchBudgetExhausted(exhausted_for));
+                if wait(&thread_termination_receiver, exhausted_for).is_err() {
+                    return Ok(());
+                }
+                continue 'outer;
+            }
+
             log::debug!(
                 "[WORKER] Connecting to WebSocket (attempt #{})",
                 websocket_retry_attempt
@@ -327,7 +712,11 @@ impl<T: ServerClient> UpdateThreadWorker<T> {
                 Ok(socket) => {
                     log::info!("[WORKER] WebSocket connected");
                     websocket_retry_attempt = 0;
-                    config_refresh_retry_attempt = 0;
+                    // `config_refresh_retry_attempt` is only reset once a fetch
+                    // succeeds: the websocket is reconnected after each failed
+                    // fetch, resetting it here would retry with the shortest
+                    // delay forever while the server fails to serve the
+                    // configuration.
                     self.retry_pending.store(false, Ordering::SeqCst);
                     self.is_connected.store(true, Ordering::SeqCst);
                     self.emit_runtime_event(RuntimeEventKind::Connected)?;
@@ -340,11 +729,18 @@ impl<T: ServerClient> UpdateThreadWorker<T> {
                         e
                     );
                     let offline_reason = Self::classify_connectivity_error(&e);
+                    self.record_network_error(&e);
+                    // Outdated once connected, the updates meanwhile are not notified
+                    self.probed_configuration.set(None);
                     // Mark disconnected so the poll loop detects restoration.
                     self.is_connected.store(false, Ordering::SeqCst);
                     Self::recoverable_error(e)?;
                     self.emit_offline_runtime_event(offline_reason)?;
 
+                    if self.record_server_failure() {
+                        websocket_retry_attempt = 0;
+                        continue 'outer;
+                    }
                     if self
                         .wait_before_retry(&thread_termination_receiver, websocket_retry_attempt)
                         .is_err()
@@ -373,7 +769,17 @@ impl<T: ServerClient> UpdateThreadWorker<T> {
 
             if initial_fetch_succeeded {
                 config_refresh_retry_attempt = 0;
+                self.consecutive_failures.set(0);
+            } else if self.client_side_failure.get() {
+                // Not a failure of the server. A postponed fetch is still
+                // pending, and done after a websocket message once the
+                // budget allows it. A rejected configuration is replaced by
+                // the next update notified.
             } else {
+                if self.record_server_failure() {
+                    config_refresh_retry_attempt = 0;
+                    continue 'outer;
+                }
                 log::warn!(
                     "[WORKER] Config fetch failed — backing off (attempt #{})",
                     config_refresh_retry_attempt
@@ -405,7 +811,15 @@ impl<T: ServerClient> UpdateThreadWorker<T> {
                     _ => return Ok(()),
                 }
 
-                match self.handle_websocket_message(socket)? {
+                if self.fail_back_if_primary_recovered() {
+                    // Reconnect the websocket to the primary address
+                    break 'inner;
+                }
+
+                self.reached(WorkerStep::BeforeWebsocketRead);
+                let socket_alive = self.handle_websocket_message(socket)?;
+                self.reached(WorkerStep::AfterWebsocketRead);
+                match socket_alive {
                     Some(ws) => {
                         socket = ws;
                     }
@@ -441,11 +855,80 @@ impl<T: ServerClient> UpdateThreadWorker<T> {
         }
     }
 
+    /// Fetches the configuration every `interval`, without a websocket.
+    ///
+    /// Failed fetches are retried with the usual backoff, but never later than
+    /// the next scheduled fetch, unless the server asked to wait longer.
+    fn run_polling(
+        &self,
+        thread_termination_receiver: &Receiver<()>,
+        interval: Duration,
+    ) -> Result<()> {
+        let mut retry_attempt = 0u32;
+        loop {
+            let succeeded = self.update_configuration_from_server_and_current_mode_with_reason(
+                CurrentModeOfflineReason::FailedToGetNewConfiguration,
+                true,
+            )?;
+            let delay = if succeeded {
+                retry_attempt = 0;
+                self.consecutive_failures.set(0);
+                // Served by a fallback address, the primary one is probed
+                // before the next scheduled fetch. What it served is applied
+                // right away.
+                if self.fail_back_if_primary_recovered() {
+                    Duration::ZERO
+                } else {
+                    interval
+                }
+            } else if self.client_side_failure.get() {
+                // Not a failure of the server
+                self.retry_after_remaining()
+                    .unwrap_or_default()
+                    .max(interval)
+            } else if self.record_server_failure() {
+                retry_attempt = 0;
+                Duration::ZERO
+            } else {
+                let delay = Self::calculate_config_refresh_retry_delay(retry_attempt)
+                    .min(interval)
+                    .max(self.retry_after_remaining().unwrap_or_default());
+                self.record_next_retry(delay);
+                log::warn!(
+                    "[WORKER] Config fetch failed — polling again in {:.2}s (attempt #{})",
+                    delay.as_secs_f64(),
+                    retry_attempt + 1
+                );
+                retry_attempt = retry_attempt.saturating_add(1);
+                delay
+            };
+            if wait(thread_termination_receiver, delay).is_err() {
+                return Ok(());
+            }
+        }
+    }
+
     /// Executes [`UpdateThreadWorker::run_internal`] and forwards its result. When this method returns,
-    /// [`UpdateThreadWorker::current_mode`] is set to [`CurrentMode::Defunct`].
+    /// or unwinds, [`UpdateThreadWorker::current_mode`] is set to [`CurrentMode::Defunct`].
     pub(crate) fn run(&self, thread_termination_receiver: Receiver<()>) -> Result<()> {
-        let result = self.run_internal(thread_termination_receiver);
-        let _ = self.current_mode.set(CurrentMode::Defunct(result.clone()));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            self.run_internal(thread_termination_receiver)
+        }))
+        .unwrap_or_else(|payload| {
+            // The panic is reported by the thread, clients keep serving the
+            // last configuration as for any other defunct worker.
+            self.current_mode
+                .set(CurrentMode::Defunct(Err(Error::UnrecoverableError(
+                    panic_message(&*payload),
+                ))));
+            let _ = self.emit_runtime_event(RuntimeEventKind::Closed);
+            std::panic::resume_unwind(payload)
+        });
+        if let Err(e) = &result {
+            self.error_emitter
+                .emit_error(SdkError::Defunct(e.to_string()));
+        }
+        self.current_mode.set(CurrentMode::Defunct(result.clone()));
         let _ = self.emit_runtime_event(RuntimeEventKind::Closed);
         result
     }
@@ -460,48 +943,66 @@ impl<T: ServerClient> UpdateThreadWorker<T> {
         default_offline_reason: CurrentModeOfflineReason,
         update_runtime_state_on_failure: bool,
     ) -> Result<bool> {
-        // Fetch configuration JSON from server
-        match self
-            .server_client
-            .get_configuration_json(&self.configuration_id)
-        {
-            Ok(config_json) => {
-                // Write to persistent cache if path is configured
-                if let Some(path) = &self.persistent_cache_path {
-                    if let Err(e) = config_json.write_to_file(path) {
-                        log::warn!(
-                            "Failed to write configuration to persistent cache at '{}': {}",
-                            path.display(),
-                            e
-                        );
-                    } else {
-                        log::debug!(
-                            "Successfully wrote configuration to persistent cache at '{}'",
-                            path.display()
-                        );
-                    }
-                }
+        self.reached(WorkerStep::BeforeFetch);
+        let result = self
+            .fetch_and_apply_configuration(default_offline_reason, update_runtime_state_on_failure);
+        self.reached(WorkerStep::AfterFetch);
+        result
+    }
 
-                // Convert JSON to Configuration object
-                let config = Configuration::new(
-                    &self.configuration_id.environment_id,
-                    &self.configuration_id.collection_id,
-                    config_json,
-                )
-                .map_err(|e| {
-                    Error::ThreadInternalError(format!("Failed to parse configuration: {}", e))
-                })?;
-                {
-                    let mut current_config = self.configuration.lock()?;
-                    *current_config = Some(config);
+    fn fetch_and_apply_configuration(
+        &self,
+        default_offline_reason: CurrentModeOfflineReason,
+        update_runtime_state_on_failure: bool,
+    ) -> Result<bool> {
+        let requested_at = Instant::now();
+        self.last_refresh_at.set(Some(requested_at));
+        self.refresh_pending.set(false);
+        self.client_side_failure.set(false);
+
+        // Fetch configuration JSON from server
+        match self.fetch_configuration_json() {
+            Ok((config_json, fetched_at)) => {
+                let applied = self
+                    .store
+                    .apply(config_json, requested_at, fetched_at)
+                    .map_err(|e| {
+                        // The thread stops
+                        self.record_error_with(&e, None, false);
+                        Error::ThreadInternalError(format!("Failed to parse configuration: {}", e))
+                    })?;
+                if let Applied::Rejected(reason) = applied {
+                    // Keep serving the previous configuration, in the same
+                    // mode: the server answered
+                    log::warn!("Rejected the configuration received from the server: {reason}");
+                    self.record_error(&reason);
+                    self.error_emitter
+                        .emit_error(SdkError::ConfigurationRejected(reason));
+                    self.client_side_failure.set(true);
+                    self.emit_refresh_failure_event()?;
+                    return Ok(false);
                 }
 
-                self.current_mode.set(CurrentMode::Online)?;
+                self.retry_not_before.set(None);
+                self.errors_since_refresh.set(0);
+                self.last_http_status.set(None);
+                *self.offline_details.lock_unpoisoned() = None;
+                self.current_mode.set(CurrentMode::Online);
                 self.emit_runtime_event(RuntimeEventKind::RefreshSuccess)?;
 
                 Ok(true)
             }
+            Err(NetworkError::FetchBudgetExhausted { retry_after }) => {
+                // A limit of this client: the server is not counted as
+                // failing and the mode is kept
+                self.retry_not_before
+                    .set(Some(Instant::now() + retry_after));
+                self.refresh_pending.set(true);
+                self.client_side_failure.set(true);
+                Ok(false)
+            }
             Err(e) => {
+                self.record_network_error(&e);
                 let classified_reason = Self::classify_connectivity_error(&e);
                 let offline_reason = match default_offline_reason {
                     CurrentModeOfflineReason::FailedToGetNewConfiguration => {
@@ -515,6 +1016,15 @@ impl<T: ServerClient> UpdateThreadWorker<T> {
                     },
                 };
 
+                if let NetworkError::RateLimited {
+                    retry_after: Some(retry_after),
+                } = &e
+                {
+                    let retry_after = (*retry_after).min(CONFIG_REFRESH_CAP_DELAY);
+                    self.retry_not_before
+                        .set(Some(Instant::now() + retry_after));
+                }
+
                 Self::recoverable_error(e)?;
                 if update_runtime_state_on_failure {
                     self.emit_offline_runtime_event(offline_reason)?;
@@ -538,26 +1048,26 @@ impl<T: ServerClient> UpdateThreadWorker<T> {
         match socket.read_msg() {
             Ok(msg) => match msg {
                 tungstenite::Message::Text(utf8_bytes) => {
-                    if utf8_bytes.as_str() == SERVER_HEARTBEAT {
-                        log::debug!(
-                            "[WORKER] Heartbeat received — connection alive, no config fetch needed."
-                        );
-                        return Ok(Some(socket));
-                    }
-
-                    log::debug!("[WORKER] Config-change notification received — re-fetching.");
-                    let jitter_ms = rand::rng().random_range(0..5000u64);
-                    if jitter_ms > 0 {
-                        log::debug!(
-                            "[WORKER] Config refresh will start in {:.2}s (jitter).",
-                            jitter_ms as f64 / 1000.0
-                        );
-                        std::thread::sleep(Duration::from_millis(jitter_ms));
+                    match WebsocketMessage::parse(utf8_bytes.as_str()) {
+                        WebsocketMessage::Heartbeat => {
+                            log::debug!("[WORKER] Heartbeat received — connection alive.");
+                            // Only fetches if an earlier notification was coalesced.
+                            self.refresh_if_pending(false)?;
+                        }
+                        WebsocketMessage::ConfigurationChanged(change) => {
+                            match change.resource() {
+                                Some(resource) => log::debug!(
+                                    "[WORKER] Config-change notification received for {resource} — re-fetching."
+                                ),
+                                None => log::debug!(
+                                    "[WORKER] Config-change notification received — re-fetching."
+                                ),
+                            }
+                            self.refresh_pending.set(true);
+                            self.notified_at.set(Some(chrono::Utc::now()));
+                            self.refresh_if_pending(true)?;
+                        }
                     }
-                    self.update_configuration_from_server_and_current_mode_with_reason(
-                        CurrentModeOfflineReason::FailedToGetNewConfiguration,
-                        true,
-                    )?;
                     Ok(Some(socket))
                 }
                 tungstenite::Message::Close(_) => {
@@ -579,6 +1089,7 @@ impl<T: ServerClient> UpdateThreadWorker<T> {
                         log::debug!("Failed to flush auto-pong response: {:?}", e);
                         return Ok(None); // Connection is dead
                     }
+                    self.refresh_if_pending(false)?;
 
                     Ok(Some(socket))
                 }
@@ -599,6 +1110,7 @@ impl<T: ServerClient> UpdateThreadWorker<T> {
                     "Socket read timed out after {}s — no server heartbeat received.",
                     crate::network::http_client::WEBSOCKET_READ_TIMEOUT_SECS
                 );
+                self.record_network_error(&NetworkError::WebsocketTimeout);
                 self.emit_offline_runtime_event(
                     CurrentModeOfflineReason::WebsocketHeartbeatTimeout,
                 )?;
@@ -607,6 +1119,7 @@ impl<T: ServerClient> UpdateThreadWorker<T> {
             Err(error) => {
                 // This triggers on hard drops (ConnectionReset, BrokenPipe) and other websocket errors
                 log::debug!("Websocket error detected, closing connection: {:?}", error);
+                self.record_network_error(&NetworkError::TungsteniteError(error));
                 self.emit_offline_runtime_event(CurrentModeOfflineReason::WebsocketError)?;
                 Ok(None)
             }
@@ -616,34 +1129,25 @@ impl<T: ServerClient> UpdateThreadWorker<T> {
     /// Whether the [`NetworkError`] will be permanent (it depends on static data) or we
     /// want to keep running the thread in case it eventually succeeds
     fn recoverable_error(error: NetworkError) -> Result<()> {
-        match error {
-            NetworkError::ReqwestError(_) => Ok(()),
-            NetworkError::TungsteniteError(_) => Ok(()),
-            NetworkError::ProtocolError => Ok(()),
-            NetworkError::ContactToServerLost => Ok(()),
-            NetworkError::UrlParseError(e) => Err(Error::UnrecoverableError(e)),
-            NetworkError::InvalidHeaderValue(e) => Err(Error::UnrecoverableError(e)),
-            NetworkError::CannotAcquireLock => Err(Error::CannotAcquireLock),
-            NetworkError::ConfigurationDataError(_) => Ok(()),
-            NetworkError::WebsocketTimeout => Ok(()),
-            NetworkError::TokenProviderError(_) => Ok(()),
-            NetworkError::WebsocketHttpStatus {
-                status_code,
-                message,
-            } => {
-                if (400..500).contains(&status_code) && status_code != 429 {
-                    Err(Error::UnrecoverableError(message))
-                } else {
-                    Ok(())
-                }
-            }
-            NetworkError::DeserializationError(_) => Ok(()),
+        if error.is_recoverable() {
+            return Ok(());
         }
+        Err(Error::UnrecoverableError(match error {
+            NetworkError::UrlParseError(e) | NetworkError::InvalidHeaderValue(e) => e,
+            NetworkError::WebsocketHttpStatus { message, .. } => message,
+            e => e.to_string(),
+        }))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::{AtomicU32, AtomicUsize};
+
+    use crate::models::ConfigurationSource;
+    use crate::network::live_configuration::GuardDecision;
+    use crate::network::serialization::SERVER_HEARTBEAT;
+    use crate::network::serialization::fixtures;
     use crate::{ConfigurationDataError, network::NetworkResult};
 
     use super::*;
@@ -659,199 +1163,677 @@ mod tests {
             Ok(())
         }
     }
-    #[test]
-    fn test_update_configuration_happy() {
-        struct ServerClientMock {}
-        impl ServerClient for ServerClientMock {
-            fn get_configuration(
-                &self,
-                _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<Configuration> {
-                Ok(crate::network::serialization::fixtures::configuration_feature1_enabled())
-            }
-            fn get_configuration_json(
-                &self,
-                _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<crate::network::serialization::ConfigurationJson> {
-                Ok(crate::network::serialization::fixtures::configuration_json_feature1_enabled())
-            }
 
-            #[allow(unreachable_code)]
-            fn get_configuration_monitoring_websocket(
-                &self,
-                _collection: &ConfigurationId,
-            ) -> NetworkResult<impl WebsocketReader> {
-                unreachable!() as NetworkResult<WebsocketMockReader>
+    type Response<T> = Arc<dyn Fn() -> NetworkResult<T> + Send + Sync>;
+
+    fn unexpected<T>() -> NetworkResult<T> {
+        unreachable!()
+    }
+
+    /// A [`ServerClient`] answering with the given responses. Requests it has
+    /// no response for are not expected by the test.
+    #[derive(Clone)]
+    struct ServerClientMock {
+        configuration_json: Response<ConfigurationJson>,
+        websocket: Response<Box<dyn WebsocketReader>>,
+        address_count: usize,
+        active_address: Arc<AtomicUsize>,
+    }
+
+    impl ServerClientMock {
+        fn new() -> Self {
+            Self {
+                configuration_json: Arc::new(unexpected),
+                websocket: Arc::new(unexpected),
+                address_count: 1,
+                active_address: Arc::default(),
             }
         }
-        let configuration_id =
-            ConfigurationId::new("".into(), "environment_id".into(), "collection_id".into());
-        let configuration = Arc::new(Mutex::new(None));
-        let current_mode =
-            Waitable::new(CurrentMode::Offline(CurrentModeOfflineReason::Initializing));
-
-        let worker = UpdateThreadWorker::new(
-            ServerClientMock {},
-            configuration_id,
-            configuration.clone(),
-            current_mode.clone(),
+
+        /// Answers every download with the same configuration.
+        fn serving() -> Self {
+            Self::new()
+                .with_configuration_json(|| Ok(fixtures::configuration_json_feature1_enabled()))
+        }
+
+        fn with_configuration_json(
+            mut self,
+            response: impl Fn() -> NetworkResult<ConfigurationJson> + Send + Sync + 'static,
+        ) -> Self {
+            self.configuration_json = Arc::new(response);
+            self
+        }
+
+        fn with_websocket(
+            mut self,
+            response: impl Fn() -> NetworkResult<Box<dyn WebsocketReader>> + Send + Sync + 'static,
+        ) -> Self {
+            self.websocket = Arc::new(response);
+            self
+        }
+
+        fn with_service_addresses(mut self, count: usize, active: Arc<AtomicUsize>) -> Self {
+            self.address_count = count;
+            self.active_address = active;
+            self
+        }
+
+        /// Counts the downloads in `fetches`.
+        fn counting_fetches(mut self, fetches: Arc<AtomicU32>) -> Self {
+            let response = self.configuration_json;
+            self.configuration_json = Arc::new(move || {
+                fetches.fetch_add(1, Ordering::SeqCst);
+                response()
+            });
+            self
+        }
+    }
+
+    impl ServerClient for ServerClientMock {
+        fn get_configuration_json(
+            &self,
+            _configuration_id: &ConfigurationId,
+        ) -> NetworkResult<ConfigurationJson> {
+            (self.configuration_json)()
+        }
+
+        fn get_configuration_monitoring_websocket(
+            &self,
+            _collection: &ConfigurationId,
+        ) -> NetworkResult<Box<dyn WebsocketReader>> {
+            (self.websocket)()
+        }
+
+        fn service_address_count(&self) -> usize {
+            self.address_count
+        }
+
+        fn active_service_address(&self) -> usize {
+            self.active_address.load(Ordering::SeqCst)
+        }
+
+        fn set_active_service_address(&self, index: usize) {
+            self.active_address.store(index, Ordering::SeqCst);
+        }
+    }
+
+    fn configuration_id() -> ConfigurationId {
+        ConfigurationId::new(
+            "guid".into(),
+            "environment_id".into(),
+            "collection_id".into(),
+        )
+        .unwrap()
+    }
+
+    /// A worker without configuration yet, online and without listeners.
+    fn worker(server_client: ServerClientMock) -> UpdateThreadWorker<ServerClientMock> {
+        UpdateThreadWorker::new(
+            server_client,
+            configuration_id(),
+            Arc::new(Mutex::new(None)),
+            Waitable::new(CurrentMode::Online),
             Arc::new(Mutex::new(Vec::new())),
-        );
+        )
+    }
 
-        let r = worker.update_configuration_from_server_and_current_mode_with_reason(
+    /// Collects the errors emitted by `emitter`.
+    fn collect_errors(emitter: &RuntimeEventEmitter) -> Arc<Mutex<Vec<SdkError>>> {
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        {
+            let errors = errors.clone();
+            emitter
+                .on_error(Arc::new(move |e| errors.lock().unwrap().push(e)))
+                .unwrap();
+        }
+        errors
+    }
+
+    fn refresh(worker: &UpdateThreadWorker<ServerClientMock>) -> Result<bool> {
+        worker.update_configuration_from_server_and_current_mode_with_reason(
             CurrentModeOfflineReason::FailedToGetNewConfiguration,
             true,
-        );
+        )
+    }
+
+    #[test]
+    fn test_update_configuration_happy() {
+        let worker = worker(ServerClientMock::serving());
+        let configuration = worker.store.configuration();
+        let current_mode = worker.current_mode.clone();
+        current_mode.set(CurrentMode::Offline(CurrentModeOfflineReason::Initializing));
+
+        let r = refresh(&worker);
 
         assert!(r.is_ok());
         assert!(configuration.lock().unwrap().is_some());
-        assert_eq!(current_mode.get().unwrap(), CurrentMode::Online);
+        assert_eq!(current_mode.get(), CurrentMode::Online);
     }
 
     #[test]
     fn test_update_configuration_invalid_configuration() {
-        struct ServerClientMock {}
-        impl ServerClient for ServerClientMock {
-            fn get_configuration(
-                &self,
-                _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<Configuration> {
-                Err(ConfigurationDataError::EnvironmentNotFound(
-                    "environment not in response".to_string(),
-                )
-                .into())
+        let worker = worker(ServerClientMock::new().with_configuration_json(|| {
+            Err(ConfigurationDataError::EnvironmentNotFound {
+                environment_id: "environment not in response".to_string(),
+                available: Vec::new(),
             }
+            .into())
+        }));
+        let configuration = worker.store.configuration();
+        let current_mode = worker.current_mode.clone();
+        current_mode.set(CurrentMode::Offline(CurrentModeOfflineReason::Initializing));
 
-            fn get_configuration_json(
-                &self,
-                _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<crate::network::serialization::ConfigurationJson> {
-                Err(NetworkError::ProtocolError)
-            }
-
-            #[allow(unreachable_code)]
-            fn get_configuration_monitoring_websocket(
-                &self,
-                _collection: &ConfigurationId,
-            ) -> NetworkResult<impl WebsocketReader> {
-                unreachable!() as NetworkResult<WebsocketMockReader>
-            }
-        }
-        let configuration_id = ConfigurationId::new("".into(), "not used".into(), "".into());
-        let configuration = Arc::new(Mutex::new(None));
-        let current_mode =
-            Waitable::new(CurrentMode::Offline(CurrentModeOfflineReason::Initializing));
-
-        let worker = UpdateThreadWorker::new(
-            ServerClientMock {},
-            configuration_id,
-            configuration.clone(),
-            current_mode.clone(),
-            Arc::new(Mutex::new(Vec::new())),
-        );
-
-        let r = worker.update_configuration_from_server_and_current_mode_with_reason(
-            CurrentModeOfflineReason::FailedToGetNewConfiguration,
-            true,
-        );
+        let r = refresh(&worker);
 
         assert!(r.is_ok());
         assert!(configuration.lock().unwrap().is_none());
         assert_eq!(
-            current_mode.get().unwrap(),
+            current_mode.get(),
             CurrentMode::Offline(CurrentModeOfflineReason::FailedToGetNewConfiguration)
         );
     }
 
     #[test]
-    fn test_update_configuration_protocol_error_recoverable() {
-        struct ServerClientMock {}
-        impl ServerClient for ServerClientMock {
-            fn get_configuration(
-                &self,
-                _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<Configuration> {
-                Err(NetworkError::ProtocolError)
-            }
+    fn test_update_configuration_rejected() {
+        let mut configuration_json = fixtures::configuration_json_feature1_enabled();
+        configuration_json.checksum = Some("corrupted".into());
+        let configuration_json = Arc::new(Mutex::new(configuration_json));
+        let server_client = {
+            let configuration_json = configuration_json.clone();
+            ServerClientMock::new()
+                .with_configuration_json(move || Ok(configuration_json.lock().unwrap().clone()))
+        };
+        let previous = fixtures::configuration_property1_enabled();
+        let error_emitter = RuntimeEventEmitter::new();
+        let errors = collect_errors(&error_emitter);
+        let worker = worker(server_client)
+            .with_expected_schema_version(Some(2))
+            .with_error_emitter(error_emitter);
+        let configuration = worker.store.configuration();
+        *configuration.lock().unwrap() = Some(Arc::new(previous.clone()));
+        let update = || {
+            worker.update_configuration_from_server_and_current_mode_with_reason(
+                CurrentModeOfflineReason::FailedToGetNewConfiguration,
+                false,
+            )
+        };
 
-            fn get_configuration_json(
-                &self,
-                _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<crate::network::serialization::ConfigurationJson> {
-                Err(NetworkError::ProtocolError)
-            }
+        // Neither the checksum nor the schema version match
+        assert!(!update().unwrap());
+        assert_eq!(configuration.lock().unwrap().as_deref(), Some(&previous));
 
-            #[allow(unreachable_code)]
-            fn get_configuration_monitoring_websocket(
-                &self,
-                _collection: &ConfigurationId,
-            ) -> NetworkResult<impl WebsocketReader> {
-                unreachable!() as NetworkResult<WebsocketMockReader>
-            }
+        {
+            let mut configuration_json = configuration_json.lock().unwrap();
+            configuration_json.schema_version = Some(2);
+            configuration_json.checksum = None;
+            configuration_json.checksum = Some(configuration_json.checksum().unwrap());
         }
-        let configuration_id = ConfigurationId::new("".into(), "environment_id".into(), "".into());
-        let configuration = Arc::new(Mutex::new(None));
-        let current_mode = Waitable::new(CurrentMode::Online);
+        assert!(update().unwrap());
+        assert_ne!(configuration.lock().unwrap().as_deref(), Some(&previous));
+
+        let errors = errors.lock().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            SdkError::ConfigurationRejected(message) if message.contains("schema version")
+        ));
+    }
 
-        let worker = UpdateThreadWorker::new(
-            ServerClientMock {},
-            configuration_id,
-            configuration.clone(),
-            current_mode.clone(),
-            Arc::new(Mutex::new(Vec::new())),
+    #[test]
+    fn test_update_configuration_guard() {
+        let previous = fixtures::configuration_property1_enabled();
+        let error_emitter = RuntimeEventEmitter::new();
+        let errors = collect_errors(&error_emitter);
+        // Property p1 must not be removed while enforced
+        let enforced = Arc::new(AtomicBool::new(true));
+        let guard = {
+            let enforced = enforced.clone();
+            ConfigurationGuard::new(move |_configuration, diff| {
+                let removes_p1 = diff.properties.iter().any(|change| {
+                    matches!(change, crate::ResourceChange::Removed { id, .. } if id == "p1")
+                });
+                if removes_p1 && enforced.load(Ordering::SeqCst) {
+                    GuardDecision::Reject("property p1 must exist".into())
+                } else {
+                    GuardDecision::Accept
+                }
+            })
+        };
+        let cache_path = std::env::temp_dir().join(format!(
+            "appconfiguration-guard-test-{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&cache_path);
+        let worker = worker(ServerClientMock::serving())
+            .with_configuration_guards(vec![
+                ConfigurationGuard::new(|_, _| GuardDecision::Accept),
+                guard,
+            ])
+            .with_persistent_cache_file(&cache_path)
+            .with_error_emitter(error_emitter);
+        let configuration = worker.store.configuration();
+        *configuration.lock().unwrap() = Some(Arc::new(previous.clone()));
+        let update = || {
+            worker
+                .update_configuration_from_server_and_current_mode_with_reason(
+                    CurrentModeOfflineReason::FailedToGetNewConfiguration,
+                    false,
+                )
+                .unwrap()
+        };
+
+        // Rejected configurations are neither used nor cached
+        assert!(!update());
+        assert_eq!(configuration.lock().unwrap().as_deref(), Some(&previous));
+        assert!(!cache_path.exists());
+        assert_eq!(
+            errors.lock().unwrap().as_slice(),
+            [SdkError::ConfigurationRejected(
+                "property p1 must exist".into()
+            )]
         );
 
-        let r = worker.update_configuration_from_server_and_current_mode_with_reason(
-            CurrentModeOfflineReason::FailedToGetNewConfiguration,
-            true,
+        enforced.store(false, Ordering::SeqCst);
+        assert!(update());
+        assert_ne!(configuration.lock().unwrap().as_deref(), Some(&previous));
+        assert!(cache_path.exists());
+        assert_eq!(errors.lock().unwrap().len(), 1);
+        std::fs::remove_file(&cache_path).unwrap();
+    }
+
+    #[test]
+    fn test_update_configuration_rejected_stays_online() {
+        let error_emitter = RuntimeEventEmitter::new();
+        let errors = collect_errors(&error_emitter);
+        let worker = worker(ServerClientMock::serving())
+            .with_configuration_guards(vec![ConfigurationGuard::new(|_, _| panic!("guard bug"))])
+            .with_error_emitter(error_emitter);
+
+        // A panicking guard rejects the configuration, the server is not at fault
+        assert!(!refresh(&worker).unwrap());
+        assert!(worker.client_side_failure.get());
+        assert_eq!(worker.current_mode.get(), CurrentMode::Online);
+        assert!(matches!(
+            errors.lock().unwrap().as_slice(),
+            [SdkError::ConfigurationRejected(message)] if message.contains("guard bug")
+        ));
+    }
+
+    #[test]
+    fn test_update_configuration_protocol_error_recoverable() {
+        let worker = worker(
+            ServerClientMock::new().with_configuration_json(|| Err(NetworkError::ProtocolError)),
         );
+        let configuration = worker.store.configuration();
+
+        let r = refresh(&worker);
 
         // check if we transition from online to offline:
         assert!(r.is_ok());
         assert!(configuration.lock().unwrap().is_none());
         assert_eq!(
-            current_mode.get().unwrap(),
+            worker.current_mode.get(),
             CurrentMode::Offline(CurrentModeOfflineReason::FailedToGetNewConfiguration)
         );
     }
 
     #[test]
-    fn test_update_configuration_reqwest_error_classified_as_connectivity_issue() {
-        struct ServerClientMock {}
-        impl ServerClient for ServerClientMock {
-            fn get_configuration(
+    fn test_last_error() {
+        let available = Arc::new(AtomicBool::new(false));
+        let server_client = {
+            let available = available.clone();
+            ServerClientMock::new().with_configuration_json(move || {
+                if available.load(Ordering::SeqCst) {
+                    Ok(fixtures::configuration_json_feature1_enabled())
+                } else {
+                    Err(NetworkError::ServerError(503))
+                }
+            })
+        };
+        let last_error = Arc::new(Mutex::new(None));
+        let worker = worker(server_client).with_last_error(last_error.clone());
+        let refresh = || refresh(&worker).unwrap();
+        let retry_count = || last_error.lock().unwrap().as_ref().unwrap().retry_count;
+
+        assert!(!refresh());
+        let error = last_error.lock().unwrap().clone().unwrap();
+        assert_eq!(error.message, NetworkError::ServerError(503).to_string());
+        assert_eq!(error.retry_count, 0);
+        assert_eq!(error.category, Some(NetworkErrorCategory::Status));
+        assert!(error.will_retry);
+        assert!(chrono::Utc::now() - error.timestamp < chrono::TimeDelta::seconds(5));
+
+        assert!(!refresh());
+        assert_eq!(retry_count(), 1);
+
+        // Kept after recovering, the count restarts with the next error
+        available.store(true, Ordering::SeqCst);
+        assert!(refresh());
+        assert_eq!(retry_count(), 1);
+        available.store(false, Ordering::SeqCst);
+        assert!(!refresh());
+        assert_eq!(retry_count(), 0);
+    }
+
+    #[test]
+    fn test_shared_cache() {
+        #[derive(Default)]
+        struct MemoryCache(Mutex<Option<crate::CachedConfiguration>>);
+        impl crate::DistributedCache for Arc<MemoryCache> {
+            fn get(
                 &self,
                 _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<Configuration> {
-                Err(NetworkError::ContactToServerLost)
+            ) -> std::result::Result<Option<crate::CachedConfiguration>, String> {
+                Ok(self.0.lock().unwrap().clone())
             }
 
-            fn get_configuration_json(
+            fn put(
                 &self,
                 _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<crate::network::serialization::ConfigurationJson> {
-                Err(NetworkError::ContactToServerLost)
+                configuration: &crate::CachedConfiguration,
+            ) -> std::result::Result<(), String> {
+                *self.0.lock().unwrap() = Some(configuration.clone());
+                Ok(())
             }
+        }
 
-            #[allow(unreachable_code)]
-            fn get_configuration_monitoring_websocket(
-                &self,
-                _collection: &ConfigurationId,
-            ) -> NetworkResult<impl WebsocketReader> {
-                unreachable!() as NetworkResult<WebsocketMockReader>
+        let fetches = Arc::new(AtomicU32::new(0));
+        let server_client = ServerClientMock::serving().counting_fetches(fetches.clone());
+        let cache = Arc::new(MemoryCache::default());
+        let shared_cache = SharedCache::new(cache.clone(), Duration::from_secs(60));
+        // One worker for each replica, sharing the cache
+        let replica = || {
+            let worker =
+                worker(server_client.clone()).with_shared_cache(Some(shared_cache.clone()));
+            let configuration = worker.store.configuration();
+            (worker, configuration)
+        };
+
+        let (first, _) = replica();
+        assert!(refresh(&first).unwrap());
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        let cached = cache.0.lock().unwrap().clone().unwrap();
+        assert_eq!(cached.etag.len(), 64);
+
+        // Recent enough for the other replicas
+        let (second, configuration) = replica();
+        assert!(refresh(&second).unwrap());
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            configuration
+                .lock()
+                .unwrap()
+                .as_ref()
+                .unwrap()
+                .get_feature_ids_refs(),
+            [&"f1".to_string()]
+        );
+        let source = || configuration.lock().unwrap().as_ref().unwrap().source();
+        assert_eq!(source(), ConfigurationSource::SharedCache);
+
+        // Not after an update notification received later
+        second.notified_at.set(Some(chrono::Utc::now()));
+        assert!(refresh(&second).unwrap());
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+        assert_eq!(source(), ConfigurationSource::Live);
+        assert!(cache.0.lock().unwrap().as_ref().unwrap().fetched_at > cached.fetched_at);
+    }
+
+    #[test]
+    fn test_polling_without_websocket() {
+        let fetches = Arc::new(AtomicU32::new(0));
+        let controller = StepController::new();
+        controller.pause_at(WorkerStep::AfterFetch);
+        let worker = worker(ServerClientMock::serving().counting_fetches(fetches.clone()))
+            .with_polling_interval(Some(Duration::from_millis(10)))
+            .with_step_controller(Some(controller.clone()));
+        let current_mode = worker.current_mode.clone();
+        current_mode.set(CurrentMode::Offline(CurrentModeOfflineReason::Initializing));
+
+        let (terminator, receiver) = std::sync::mpsc::channel();
+        let thread = std::thread::spawn(move || worker.run(receiver));
+        for expected_fetches in 1..=3 {
+            assert_eq!(
+                controller.wait_paused(Duration::from_secs(10)),
+                Some(WorkerStep::AfterFetch),
+                "Polling did not fetch in time"
+            );
+            assert_eq!(fetches.load(Ordering::SeqCst), expected_fetches);
+            assert_eq!(current_mode.get(), CurrentMode::Online);
+            controller.resume();
+        }
+
+        controller.release();
+        terminator.send(()).unwrap();
+        assert!(thread.join().unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_offline_details() {
+        let available = Arc::new(AtomicBool::new(false));
+        let server_client = {
+            let available = available.clone();
+            ServerClientMock::new().with_configuration_json(move || {
+                if available.load(Ordering::SeqCst) {
+                    Ok(fixtures::configuration_json_feature1_enabled())
+                } else {
+                    Err(NetworkError::Unauthorized)
+                }
+            })
+        };
+        let offline_details = Arc::new(Mutex::new(None));
+        let worker = worker(server_client).with_offline_details(offline_details.clone());
+        let refresh = || refresh(&worker).unwrap();
+
+        assert!(!refresh());
+        let details = offline_details.lock().unwrap().clone().unwrap();
+        assert_eq!(
+            details.reason,
+            CurrentModeOfflineReason::FailedToGetNewConfiguration
+        );
+        assert_eq!(details.http_status, Some(401));
+        assert_eq!(details.next_retry_at, None);
+        assert!(details.help().contains("apikey"));
+
+        // Still offline since the first failure
+        assert!(!refresh());
+        let later_details = offline_details.lock().unwrap().clone().unwrap();
+        assert_eq!(later_details.since, details.since);
+
+        worker.record_next_retry(Duration::from_secs(60));
+        let next_retry_at = offline_details
+            .lock()
+            .unwrap()
+            .clone()
+            .unwrap()
+            .next_retry_at;
+        assert!(next_retry_at.unwrap() > chrono::Utc::now() + chrono::TimeDelta::seconds(50));
+
+        available.store(true, Ordering::SeqCst);
+        assert!(refresh());
+        assert_eq!(*offline_details.lock().unwrap(), None);
+    }
+
+    #[test]
+    fn test_update_configuration_rate_limited() {
+        let requests = Arc::new(AtomicU32::new(0));
+        let worker = worker(
+            ServerClientMock::new()
+                .with_configuration_json(|| {
+                    Err(NetworkError::RateLimited {
+                        retry_after: Some(Duration::from_secs(60)),
+                    })
+                })
+                .counting_fetches(requests.clone()),
+        );
+
+        assert!(!refresh(&worker).unwrap());
+        let remaining = worker.retry_after_remaining().unwrap();
+        assert!(remaining > Duration::from_secs(55) && remaining <= Duration::from_secs(60));
+
+        // Notifications received meanwhile do not fetch until the delay elapsed
+        worker.refresh_pending.set(true);
+        worker.refresh_if_pending(false).unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        assert!(worker.refresh_pending.get());
+
+        // An elapsed delay does not hold back the next attempts
+        worker.retry_not_before.set(Some(Instant::now()));
+        assert_eq!(worker.retry_after_remaining(), None);
+        worker.refresh_if_pending(false).unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_update_configuration_fetch_budget() {
+        let requests = Arc::new(AtomicU32::new(0));
+        let error_emitter = RuntimeEventEmitter::new();
+        let errors = collect_errors(&error_emitter);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let worker = worker(ServerClientMock::serving().counting_fetches(requests.clone()))
+            .with_max_fetches_per_hour(Some(2))
+            .with_error_emitter(error_emitter);
+        {
+            let events = events.clone();
+            worker
+                .runtime_event_listeners
+                .lock()
+                .unwrap()
+                .push(Arc::new(move |event: RuntimeEvent| {
+                    events.lock().unwrap().push(event.kind)
+                }));
+        }
+        let refresh = || refresh(&worker).unwrap();
+
+        assert!(refresh());
+        assert!(refresh());
+        events.lock().unwrap().clear();
+        assert!(!refresh());
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+        let remaining = worker.retry_after_remaining().unwrap();
+        assert!(remaining > Duration::from_secs(59 * 60) && remaining <= Duration::from_secs(3600));
+        assert!(matches!(
+            errors.lock().unwrap().as_slice(),
+            [SdkError::FetchBudgetExhausted(_)]
+        ));
+        // Postponed, not counted as a failure of the server
+        assert!(worker.client_side_failure.get());
+        assert!(worker.refresh_pending.get());
+        assert_eq!(worker.current_mode.get(), CurrentMode::Online);
+        assert!(events.lock().unwrap().is_empty());
+        assert_eq!(worker.last_error.lock().unwrap().clone(), None);
+
+        // The budget is available again once the oldest fetch is an hour old
+        worker
+            .store
+            .fetch_budget()
+            .age_oldest_fetch(Duration::from_secs(3600));
+        assert!(refresh());
+        assert!(!worker.client_side_failure.get());
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+        assert_eq!(worker.retry_after_remaining(), None);
+    }
+
+    #[test]
+    fn test_failover_and_failback() {
+        let new_worker = |address_count: usize| {
+            let active = Arc::new(AtomicUsize::new(0));
+            let primary_available = Arc::new(AtomicBool::new(false));
+            let server_client = {
+                let active = active.clone();
+                let primary_available = primary_available.clone();
+                ServerClientMock::new().with_configuration_json(move || {
+                    assert_eq!(active.load(Ordering::SeqCst), 0);
+                    if primary_available.load(Ordering::SeqCst) {
+                        Ok(fixtures::configuration_json_feature1_enabled())
+                    } else {
+                        Err(NetworkError::ProtocolError)
+                    }
+                })
             }
+            .with_service_addresses(address_count, active.clone());
+            let worker = worker(server_client).with_failover_threshold(Some(2));
+            (worker, active, primary_available)
+        };
+
+        // Without fallback addresses there is nothing to fail over to
+        let (worker, active, _) = new_worker(1);
+        for _ in 0..4 {
+            assert!(!worker.record_server_failure());
         }
-        let configuration_id = ConfigurationId::new("".into(), "environment_id".into(), "".into());
-        let configuration = Arc::new(Mutex::new(None));
-        let current_mode = Waitable::new(CurrentMode::Online);
+        assert_eq!(active.load(Ordering::SeqCst), 0);
+
+        let (worker, active, primary_available) = new_worker(2);
+        assert!(!worker.record_server_failure());
+        assert!(worker.record_server_failure());
+        assert_eq!(active.load(Ordering::SeqCst), 1);
+
+        // The primary address is not probed before the interval elapsed
+        assert!(!worker.fail_back_if_primary_recovered());
+
+        let probe_due = Instant::now() - FAILBACK_PROBE_INTERVAL;
+        worker.failed_over_at.set(Some(probe_due));
+        assert!(!worker.fail_back_if_primary_recovered());
+        assert_eq!(active.load(Ordering::SeqCst), 1);
+
+        primary_available.store(true, Ordering::SeqCst);
+        worker.failed_over_at.set(Some(probe_due));
+        assert!(worker.fail_back_if_primary_recovered());
+        assert_eq!(active.load(Ordering::SeqCst), 0);
+        assert!(worker.failed_over_at.get().is_none());
+        assert!(!worker.fail_back_if_primary_recovered());
+
+        // The configuration downloaded by the probe is applied without
+        // downloading it again
+        primary_available.store(false, Ordering::SeqCst);
+        assert!(refresh(&worker).unwrap());
+        assert!(!refresh(&worker).unwrap());
+    }
 
-        let worker = UpdateThreadWorker::new(
-            ServerClientMock {},
-            configuration_id,
-            configuration.clone(),
-            current_mode.clone(),
-            Arc::new(Mutex::new(Vec::new())),
+    #[test]
+    fn test_recoverable_http_errors() {
+        type Worker = UpdateThreadWorker<crate::network::ServerClientImpl>;
+        assert!(Worker::recoverable_error(NetworkError::ServerError(503)).is_ok());
+        assert!(Worker::recoverable_error(NetworkError::Unauthorized).is_ok());
+        assert!(Worker::recoverable_error(NetworkError::RateLimited { retry_after: None }).is_ok());
+        // Retried until the credentials are replaced
+        assert!(
+            Worker::recoverable_error(NetworkError::TokenRequestRejected {
+                status_code: 401,
+                message: "Provided API key could not be found".into(),
+            })
+            .is_ok()
+        );
+
+        let error = Worker::recoverable_error(NetworkError::ConfigurationNotFound {
+            guid: "guid".into(),
+            environment_id: "dev".into(),
+            collection_id: "blue-charge".into(),
+        })
+        .unwrap_err();
+        let Error::UnrecoverableError(message) = error else {
+            panic!("Unexpected error: {error:?}");
+        };
+        assert!(message.contains("'guid'") && message.contains("'blue-charge'"));
+
+        let error = Worker::recoverable_error(NetworkError::WebsocketHttpStatus {
+            status_code: 400,
+            message: "Bad request".into(),
+        })
+        .unwrap_err();
+        let Error::UnrecoverableError(message) = error else {
+            panic!("Unexpected error: {error:?}");
+        };
+        assert_eq!(message, "Bad request");
+    }
+
+    #[test]
+    fn test_update_configuration_reqwest_error_classified_as_connectivity_issue() {
+        let worker = worker(
+            ServerClientMock::new()
+                .with_configuration_json(|| Err(NetworkError::ContactToServerLost)),
         );
+        let configuration = worker.store.configuration();
 
         let r = worker.update_configuration_from_server_and_current_mode_with_reason(
             CurrentModeOfflineReason::InternetConnectivityError,
@@ -861,53 +1843,19 @@ mod tests {
         assert!(r.is_ok());
         assert!(configuration.lock().unwrap().is_none());
         assert_eq!(
-            current_mode.get().unwrap(),
+            worker.current_mode.get(),
             CurrentMode::Offline(CurrentModeOfflineReason::InternetConnectivityError)
         );
     }
 
     #[test]
     fn test_update_configuration_network_error_non_recoverable() {
-        struct ServerClientMock {}
-        impl ServerClient for ServerClientMock {
-            fn get_configuration(
-                &self,
-                _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<Configuration> {
-                Err(NetworkError::CannotAcquireLock)
-            }
-
-            fn get_configuration_json(
-                &self,
-                _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<crate::network::serialization::ConfigurationJson> {
-                Err(NetworkError::CannotAcquireLock)
-            }
-
-            #[allow(unreachable_code)]
-            fn get_configuration_monitoring_websocket(
-                &self,
-                _collection: &ConfigurationId,
-            ) -> NetworkResult<impl WebsocketReader> {
-                unreachable!() as NetworkResult<WebsocketMockReader>
-            }
-        }
-        let configuration_id = ConfigurationId::new("".into(), "environment_id".into(), "".into());
-        let configuration = Arc::new(Mutex::new(None));
-        let current_mode = Waitable::new(CurrentMode::Online);
-
-        let worker = UpdateThreadWorker::new(
-            ServerClientMock {},
-            configuration_id,
-            configuration.clone(),
-            current_mode.clone(),
-            Arc::new(Mutex::new(Vec::new())),
+        let worker = worker(
+            ServerClientMock::new()
+                .with_configuration_json(|| Err(NetworkError::UrlParseError("not a url".into()))),
         );
 
-        let r = worker.update_configuration_from_server_and_current_mode_with_reason(
-            CurrentModeOfflineReason::FailedToGetNewConfiguration,
-            true,
-        );
+        let r = refresh(&worker);
 
         // check if we transition from online to offline:
         assert!(r.is_err());
@@ -916,43 +1864,10 @@ mod tests {
 
     #[test]
     fn test_handle_websocket_when_get_configuration_succeeds() {
-        struct ServerClientMock {}
-        impl ServerClient for ServerClientMock {
-            fn get_configuration(
-                &self,
-                _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<Configuration> {
-                Ok(crate::network::serialization::fixtures::configuration_feature1_enabled())
-            }
-
-            fn get_configuration_json(
-                &self,
-                _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<crate::network::serialization::ConfigurationJson> {
-                Ok(crate::network::serialization::fixtures::configuration_json_feature1_enabled())
-            }
-
-            #[allow(unreachable_code)]
-            fn get_configuration_monitoring_websocket(
-                &self,
-                _collection: &ConfigurationId,
-            ) -> NetworkResult<impl WebsocketReader> {
-                unreachable!() as NetworkResult<WebsocketMockReader>
-            }
-        }
-        let configuration_id =
-            ConfigurationId::new("".into(), "environment_id".into(), "collection_id".into());
-        let configuration = Arc::new(Mutex::new(None));
-        let current_mode =
-            Waitable::new(CurrentMode::Offline(CurrentModeOfflineReason::Initializing));
-
-        let worker = UpdateThreadWorker::new(
-            ServerClientMock {},
-            configuration_id,
-            configuration.clone(),
-            current_mode.clone(),
-            Arc::new(Mutex::new(Vec::new())),
-        );
+        let worker = worker(ServerClientMock::serving());
+        let configuration = worker.store.configuration();
+        let current_mode = worker.current_mode.clone();
+        current_mode.set(CurrentMode::Offline(CurrentModeOfflineReason::Initializing));
 
         // Heartbeat while offline → does NOT trigger a fetch (keep-alive only).
         // Configuration stays None, mode stays Offline.
@@ -962,20 +1877,20 @@ mod tests {
         assert!(r.unwrap().is_some());
         assert!(configuration.lock().unwrap().is_none()); // no fetch happened
         assert_eq!(
-            current_mode.get().unwrap(),
+            current_mode.get(),
             CurrentMode::Offline(CurrentModeOfflineReason::Initializing)
         );
 
-        // A non-heartbeat text message IS a config-change notification → fetch triggered.
+        // A notification of the collection is a config-change → fetch triggered.
         // After a successful fetch the mode transitions to Online.
         let r = worker.handle_websocket_message(WebsocketMockReader {
             message: Some(Ok(tungstenite::Message::text(
-                "collection_id:c1;environment_id:e1",
+                "collection_id:collection_id;environment_id:environment_id",
             ))),
         });
         assert!(r.unwrap().is_some());
         assert!(configuration.lock().unwrap().is_some());
-        assert_eq!(current_mode.get().unwrap(), CurrentMode::Online);
+        assert_eq!(current_mode.get(), CurrentMode::Online);
 
         // Heartbeat while already online → still no fetch (keep-alive only).
         *configuration.lock().unwrap() = None;
@@ -984,7 +1899,7 @@ mod tests {
         });
         assert!(r.unwrap().is_some());
         assert!(configuration.lock().unwrap().is_none());
-        assert_eq!(current_mode.get().unwrap(), CurrentMode::Online);
+        assert_eq!(current_mode.get(), CurrentMode::Online);
 
         // Ping frames are a noop — no fetch, no state change
         let r = worker.handle_websocket_message(WebsocketMockReader {
@@ -992,17 +1907,27 @@ mod tests {
         });
         assert!(r.unwrap().is_some());
         assert!(configuration.lock().unwrap().is_none());
-        assert_eq!(current_mode.get().unwrap(), CurrentMode::Online);
+        assert_eq!(current_mode.get(), CurrentMode::Online);
 
-        // Another non-heartbeat text → config re-fetch
+        // The content of the notifications is not relied on: naming another
+        // collection → config re-fetch
         let r = worker.handle_websocket_message(WebsocketMockReader {
             message: Some(Ok(tungstenite::Message::text(
-                "collection_id:c1;environment_id:e1",
+                "collection_id:other;environment_id:environment_id",
             ))),
         });
         assert!(r.unwrap().is_some());
         assert!(configuration.lock().unwrap().is_some());
-        assert_eq!(current_mode.get().unwrap(), CurrentMode::Online);
+        assert_eq!(current_mode.get(), CurrentMode::Online);
+
+        // Unparseable text → config re-fetch
+        *configuration.lock().unwrap() = None;
+        let r = worker.handle_websocket_message(WebsocketMockReader {
+            message: Some(Ok(tungstenite::Message::text("configuration updated"))),
+        });
+        assert!(r.unwrap().is_some());
+        assert!(configuration.lock().unwrap().is_some());
+        assert_eq!(current_mode.get(), CurrentMode::Online);
 
         // After websocket is closed, it is consumed and we are offline
         let r = worker.handle_websocket_message(WebsocketMockReader {
@@ -1011,49 +1936,53 @@ mod tests {
         assert!(r.unwrap().is_none()); // WS consumed
         assert!(configuration.lock().unwrap().is_some());
         assert_eq!(
-            current_mode.get().unwrap(),
+            current_mode.get(),
             CurrentMode::Offline(CurrentModeOfflineReason::WebsocketClosed)
         );
     }
 
     #[test]
-    fn test_handle_websocket_update_when_get_configuration_fails() {
-        struct ServerClientMock {}
-        impl ServerClient for ServerClientMock {
-            fn get_configuration(
-                &self,
-                _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<Configuration> {
-                Err(NetworkError::UrlParseError("".to_string()))
-            }
-
-            fn get_configuration_json(
-                &self,
-                _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<crate::network::serialization::ConfigurationJson> {
-                Err(NetworkError::UrlParseError("".to_string()))
-            }
+    fn test_handle_websocket_coalesces_refresh_burst() {
+        let fetches = Arc::new(AtomicU32::new(0));
+        let worker = worker(ServerClientMock::serving().counting_fetches(fetches.clone()))
+            .with_refresh_coalescing_window(Duration::from_secs(60));
+        let notification = || WebsocketMockReader {
+            message: Some(Ok(tungstenite::Message::text(
+                "collection_id:collection_id;environment_id:environment_id",
+            ))),
+        };
 
-            #[allow(unreachable_code)]
-            fn get_configuration_monitoring_websocket(
-                &self,
-                _collection: &ConfigurationId,
-            ) -> NetworkResult<impl WebsocketReader> {
-                unreachable!() as NetworkResult<WebsocketMockReader>
-            }
-        }
-        let configuration_id = ConfigurationId::new("".into(), "environment_id".into(), "".into());
-        let configuration = Arc::new(Mutex::new(None));
-        let current_mode =
-            Waitable::new(CurrentMode::Offline(CurrentModeOfflineReason::Initializing));
+        // The first notification fetches right away.
+        worker.handle_websocket_message(notification()).unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        // A burst within the window does not fetch again...
+        worker.handle_websocket_message(notification()).unwrap();
+        worker.handle_websocket_message(notification()).unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert!(worker.refresh_pending.get());
+
+        // ... until the window has elapsed: the next heartbeat runs a single fetch.
+        worker
+            .last_refresh_at
+            .set(Some(Instant::now() - Duration::from_secs(61)));
+        worker
+            .handle_websocket_message(WebsocketMockReader {
+                message: Some(Ok(tungstenite::Message::text(SERVER_HEARTBEAT))),
+            })
+            .unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+        assert!(!worker.refresh_pending.get());
+    }
 
-        let worker = UpdateThreadWorker::new(
-            ServerClientMock {},
-            configuration_id,
-            configuration.clone(),
-            current_mode.clone(),
-            Arc::new(Mutex::new(Vec::new())),
+    #[test]
+    fn test_handle_websocket_update_when_get_configuration_fails() {
+        let worker = worker(
+            ServerClientMock::new()
+                .with_configuration_json(|| Err(NetworkError::UrlParseError("".to_string()))),
         );
+        let current_mode = worker.current_mode.clone();
+        current_mode.set(CurrentMode::Offline(CurrentModeOfflineReason::Initializing));
 
         // A heartbeat (= "test message") is a keep-alive only — no fetch, no error,
         // regardless of current mode.
@@ -1066,13 +1995,13 @@ mod tests {
         // The mock returns UrlParseError (unrecoverable) → propagated as Err.
         let r = worker.handle_websocket_message(WebsocketMockReader {
             message: Some(Ok(tungstenite::Message::text(
-                "collection_id:c1;environment_id:e1",
+                "collection_id:collection_id;environment_id:environment_id",
             ))),
         });
         assert!(r.is_err());
 
         // Same behaviour when already Online: heartbeat = noop.
-        current_mode.set(CurrentMode::Online).unwrap();
+        current_mode.set(CurrentMode::Online);
         let r = worker.handle_websocket_message(WebsocketMockReader {
             message: Some(Ok(tungstenite::Message::text(SERVER_HEARTBEAT))),
         });
@@ -1081,34 +2010,7 @@ mod tests {
 
     #[test]
     fn test_handle_websocket_read_failure() {
-        struct ServerClientMock {}
-        impl ServerClient for ServerClientMock {
-            fn get_configuration(
-                &self,
-                _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<Configuration> {
-                unreachable!()
-            }
-
-            #[allow(unreachable_code)]
-            fn get_configuration_monitoring_websocket(
-                &self,
-                _collection: &ConfigurationId,
-            ) -> NetworkResult<impl WebsocketReader> {
-                unreachable!() as NetworkResult<WebsocketMockReader>
-            }
-        }
-        let configuration_id = ConfigurationId::new("".into(), "environment_id".into(), "".into());
-        let configuration = Arc::new(Mutex::new(None));
-        let current_mode = Waitable::new(CurrentMode::Online);
-
-        let worker = UpdateThreadWorker::new(
-            ServerClientMock {},
-            configuration_id,
-            configuration.clone(),
-            current_mode.clone(),
-            Arc::new(Mutex::new(Vec::new())),
-        );
+        let worker = worker(ServerClientMock::new());
 
         let r = worker.handle_websocket_message(WebsocketMockReader {
             message: Some(Err(tungstenite::Error::AttackAttempt)),
@@ -1122,65 +2024,44 @@ mod tests {
 
         // websocket read error changes current_mode to Offline
         assert_eq!(
-            current_mode.get().unwrap(),
+            worker.current_mode.get(),
             CurrentMode::Offline(CurrentModeOfflineReason::WebsocketError)
         );
     }
 
     #[test]
     fn test_run_initial_config_retrieval_fails_unrecoverably() {
-        struct ServerClientMock {
-            tx: std::sync::mpsc::Sender<String>,
-        }
-        impl ServerClient for ServerClientMock {
-            fn get_configuration(
-                &self,
-                _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<Configuration> {
-                Err(NetworkError::UrlParseError("".to_string()))
-            }
-
-            fn get_configuration_json(
-                &self,
-                _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<crate::network::serialization::ConfigurationJson> {
-                self.tx.send("get_configuration_json".to_string()).unwrap();
-                Err(NetworkError::UrlParseError("".to_string()))
-            }
-
-            #[allow(unreachable_code)]
-            fn get_configuration_monitoring_websocket(
-                &self,
-                _collection: &ConfigurationId,
-            ) -> NetworkResult<impl WebsocketReader> {
-                self.tx
-                    .send("get_configuration_monitoring_websocket".to_string())
-                    .unwrap();
-                Ok(WebsocketMockReader { message: None })
-            }
-        }
-        let configuration_id = ConfigurationId::new("".into(), "environment_id".into(), "".into());
-        let configuration = Arc::new(Mutex::new(None));
-        let current_mode = Waitable::new(CurrentMode::Online);
-
         let (tx_serverclient_call_logs, rx_serverclient_call_logs) = std::sync::mpsc::channel();
-        let worker = UpdateThreadWorker::new(
-            ServerClientMock {
-                tx: tx_serverclient_call_logs,
-            },
-            configuration_id,
-            configuration.clone(),
-            current_mode.clone(),
-            Arc::new(Mutex::new(Vec::new())),
-        );
+        let server_client = {
+            let tx = tx_serverclient_call_logs.clone();
+            ServerClientMock::new()
+                .with_configuration_json(move || {
+                    tx.send("get_configuration_json".to_string()).unwrap();
+                    Err(NetworkError::UrlParseError("".to_string()))
+                })
+                .with_websocket(move || {
+                    tx_serverclient_call_logs
+                        .send("get_configuration_monitoring_websocket".to_string())
+                        .unwrap();
+                    Ok(Box::new(WebsocketMockReader { message: None }))
+                })
+        };
+        let error_emitter = RuntimeEventEmitter::new();
+        let errors = collect_errors(&error_emitter);
+        let worker = worker(server_client).with_error_emitter(error_emitter);
+        let current_mode = worker.current_mode.clone();
         let (_, rx_thread_terminator) = std::sync::mpsc::channel();
 
         let r = worker.run(rx_thread_terminator);
         assert!(r.is_err());
         assert_eq!(
-            current_mode.get().unwrap(),
+            current_mode.get(),
             CurrentMode::Defunct(Err(Error::UnrecoverableError("".into())))
         );
+        assert!(matches!(
+            errors.lock().unwrap().as_slice(),
+            [SdkError::Defunct(_)]
+        ));
 
         // We first called the websocket creation, and then get the configuration JSON. This way
         // we are not losing configuration updates. Every update notification will be waiting in
@@ -1201,129 +2082,59 @@ mod tests {
 
     #[test]
     fn test_run_get_websocket_fail() {
-        struct ServerClientMock {}
-        impl ServerClient for ServerClientMock {
-            fn get_configuration(
-                &self,
-                _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<Configuration> {
-                Ok(crate::network::serialization::fixtures::configuration_feature1_enabled())
-            }
-
-            fn get_configuration_json(
-                &self,
-                _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<crate::network::serialization::ConfigurationJson> {
-                Ok(crate::network::serialization::fixtures::configuration_json_feature1_enabled())
-            }
-
-            fn get_configuration_monitoring_websocket(
-                &self,
-                _collection: &ConfigurationId,
-            ) -> NetworkResult<impl WebsocketReader> {
-                Err::<WebsocketMockReader, _>(NetworkError::InvalidHeaderValue("".into()))
-            }
-        }
-        let configuration_id = ConfigurationId::new("".into(), "environment_id".into(), "".into());
-        let configuration = Arc::new(Mutex::new(None));
-        let current_mode = Waitable::new(CurrentMode::Online);
-
-        let worker = UpdateThreadWorker::new(
-            ServerClientMock {},
-            configuration_id,
-            configuration.clone(),
-            current_mode.clone(),
-            Arc::new(Mutex::new(Vec::new())),
+        let worker = worker(
+            ServerClientMock::serving()
+                .with_websocket(|| Err(NetworkError::InvalidHeaderValue("".into()))),
         );
+        let current_mode = worker.current_mode.clone();
         let (_, rx) = std::sync::mpsc::channel();
 
         let r = worker.run(rx);
         assert!(r.is_err());
         assert_eq!(
-            current_mode.get().unwrap(),
+            current_mode.get(),
             CurrentMode::Defunct(Err(Error::UnrecoverableError("".into())))
         );
     }
 
     #[test]
     fn test_run_thread_terminated() {
-        struct ServerClientMock {}
-        impl ServerClient for ServerClientMock {
-            fn get_configuration(
-                &self,
-                _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<Configuration> {
-                Ok(crate::network::serialization::fixtures::configuration_feature1_enabled())
-            }
-
-            fn get_configuration_json(
-                &self,
-                _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<crate::network::serialization::ConfigurationJson> {
-                Ok(crate::network::serialization::fixtures::configuration_json_feature1_enabled())
-            }
-
-            fn get_configuration_monitoring_websocket(
-                &self,
-                _collection: &ConfigurationId,
-            ) -> NetworkResult<impl WebsocketReader> {
-                Ok(WebsocketMockReader {
-                    message: Some(Err(tungstenite::Error::AttackAttempt)),
-                })
-            }
-        }
-        let configuration_id = ConfigurationId::new("".into(), "environment_id".into(), "".into());
-        let configuration = Arc::new(Mutex::new(None));
-        let current_mode = Waitable::new(CurrentMode::Online);
-
-        let worker = UpdateThreadWorker::new(
-            ServerClientMock {},
-            configuration_id,
-            configuration.clone(),
-            current_mode.clone(),
-            Arc::new(Mutex::new(Vec::new())),
-        );
+        let worker = worker(ServerClientMock::serving().with_websocket(|| {
+            Ok(Box::new(WebsocketMockReader {
+                message: Some(Err(tungstenite::Error::AttackAttempt)),
+            }))
+        }));
+        let current_mode = worker.current_mode.clone();
         let (tx, rx) = std::sync::mpsc::channel();
         drop(tx);
         let r = worker.run(rx);
         assert!(r.is_ok());
-        assert_eq!(current_mode.get().unwrap(), CurrentMode::Defunct(Ok(())));
+        assert_eq!(current_mode.get(), CurrentMode::Defunct(Ok(())));
     }
 
     #[test]
-    fn test_run_websocket_reconnect() {
-        struct ServerClientMock {
-            rx: std::sync::mpsc::Receiver<NetworkResult<WebsocketMockReader>>,
-        }
-        impl ServerClient for ServerClientMock {
-            fn get_configuration(
-                &self,
-                _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<Configuration> {
-                Ok(crate::network::serialization::fixtures::configuration_feature1_enabled())
-            }
-
-            fn get_configuration_json(
-                &self,
-                _configuration_id: &ConfigurationId,
-            ) -> NetworkResult<crate::network::serialization::ConfigurationJson> {
-                Ok(crate::network::serialization::fixtures::configuration_json_feature1_enabled())
-            }
-
-            fn get_configuration_monitoring_websocket(
-                &self,
-                _collection: &ConfigurationId,
-            ) -> NetworkResult<impl WebsocketReader> {
-                self.rx.recv().unwrap()
-            }
-        }
-        let configuration_id = ConfigurationId::new("".into(), "environment_id".into(), "".into());
-        let configuration = Arc::new(Mutex::new(None));
-        let current_mode = Waitable::new(CurrentMode::Online);
+    fn test_run_panicked() {
+        let worker = worker(
+            ServerClientMock::new()
+                .with_configuration_json(|| panic!("server client bug"))
+                .with_websocket(|| Ok(Box::new(WebsocketMockReader { message: None }))),
+        );
+        let current_mode = worker.current_mode.clone();
+        let (_tx, rx) = std::sync::mpsc::channel();
+        let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| worker.run(rx)));
+        assert!(r.is_err());
+        assert_eq!(
+            current_mode.get(),
+            CurrentMode::Defunct(Err(Error::UnrecoverableError(
+                "Thread panicked: server client bug".into()
+            )))
+        );
+    }
 
+    #[test]
+    fn test_run_websocket_reconnect() {
         let (get_ws_tx, get_ws_rx) = std::sync::mpsc::channel();
-
-        let server_client = ServerClientMock { rx: get_ws_rx };
+        let get_ws_rx = Mutex::new(get_ws_rx);
 
         get_ws_tx
             .send(Ok(WebsocketMockReader {
@@ -1331,23 +2142,27 @@ mod tests {
             }))
             .unwrap();
         get_ws_tx
-            .send(Err(NetworkError::CannotAcquireLock))
+            .send(Err(NetworkError::UrlParseError("not a url".into())))
             .unwrap();
 
-        let worker = UpdateThreadWorker::new(
-            server_client,
-            configuration_id,
-            configuration.clone(),
-            current_mode.clone(),
-            Arc::new(Mutex::new(Vec::new())),
-        );
+        let worker = worker(ServerClientMock::serving().with_websocket(move || {
+            get_ws_rx
+                .lock()
+                .unwrap()
+                .recv()
+                .unwrap()
+                .map(|reader| Box::new(reader) as Box<dyn WebsocketReader>)
+        }));
         let (_terminate_tx, terminate_rx) = std::sync::mpsc::channel();
         let r = worker.run(terminate_rx);
 
         // We assert that the websocket was attempted to be created 2 times:
         // Fist time successfully, but with a websocket returning errors on read causing reconnect
-        // Second time (reconnect attempt) fails with CannotAcquireLock error.
+        // Second time (reconnect attempt) fails with UrlParseError error.
         // The second fails WS creation is unrecoverable, which we can test:
-        assert_eq!(r.unwrap_err(), Error::CannotAcquireLock);
+        assert_eq!(
+            r.unwrap_err(),
+            Error::UnrecoverableError("not a url".into())
+        );
     }
 }
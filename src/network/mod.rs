@@ -13,19 +13,35 @@
 // limitations under the License.
 
 pub(crate) mod cache_file;
+#[cfg(feature = "remote")]
 pub(crate) mod connectivity;
 pub mod errors;
+#[cfg(feature = "remote")]
 pub(crate) mod http_client;
+#[cfg(feature = "remote")]
+mod request_authenticator;
+#[cfg(feature = "remote")]
 mod token_provider;
 
-pub(crate) use http_client::ServerClientImpl;
+#[cfg(feature = "remote")]
 pub use http_client::ServiceAddress;
+#[cfg(feature = "remote")]
 pub(crate) use http_client::ServiceAddressProtocol;
+#[cfg(feature = "remote")]
+pub use http_client::{ServerClient, SocketOptions, WebsocketReader};
+#[cfg(feature = "remote")]
+pub(crate) use http_client::{ServerClientImpl, SharedServerClient, SyncHistory};
+#[cfg(feature = "remote")]
+pub(crate) use request_authenticator::check_credentials;
+#[cfg(feature = "remote")]
+pub use request_authenticator::{OutgoingRequest, RequestAuthenticator};
+#[cfg(feature = "remote")]
 pub use token_provider::TokenProvider;
-pub(crate) use token_provider::TokenProviderImpl;
+#[cfg(feature = "remote")]
+pub(crate) use token_provider::{TokenProviderImpl, TrustedProfileTokenProviderImpl};
 pub(crate) mod live_configuration;
 pub(crate) use cache_file::CacheFile;
-pub use errors::NetworkError;
+pub use errors::{NetworkError, NetworkErrorCategory};
 pub type NetworkResult<T> = std::result::Result<T, NetworkError>;
 
 pub(crate) mod serialization; // FIXME: Make this module private to 'network'
@@ -0,0 +1,179 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderName, HeaderValue};
+use url::Url;
+
+use super::{NetworkError, NetworkResult, TokenProvider};
+
+/// A request to the App Configuration service about to be sent, as seen by a
+/// [`RequestAuthenticator`].
+///
+/// Covers the configuration fetches, the websocket handshake and the usage
+/// metering pushes.
+#[derive(Debug)]
+pub struct OutgoingRequest<'a> {
+    method: &'static str,
+    url: &'a mut Url,
+    headers: &'a mut HeaderMap,
+    body: &'a [u8],
+}
+
+impl<'a> OutgoingRequest<'a> {
+    pub(crate) fn new(
+        method: &'static str,
+        url: &'a mut Url,
+        headers: &'a mut HeaderMap,
+        body: &'a [u8],
+    ) -> Self {
+        Self {
+            method,
+            url,
+            headers,
+            body,
+        }
+    }
+
+    /// The HTTP method, e.g. `"GET"`. The websocket handshake is a `"GET"` as well.
+    pub fn method(&self) -> &str {
+        self.method
+    }
+
+    /// The full URL, including the query parameters added so far.
+    pub fn url(&self) -> &str {
+        self.url.as_str()
+    }
+
+    /// The path of the URL, e.g. `/apprapp/feature/v1/instances/<guid>/config`.
+    pub fn path(&self) -> &str {
+        self.url.path()
+    }
+
+    /// The query string of the URL, without the leading `?`.
+    pub fn query(&self) -> Option<&str> {
+        self.url.query()
+    }
+
+    /// The body of the request, empty for the `"GET"` ones.
+    pub fn body(&self) -> &[u8] {
+        self.body
+    }
+
+    /// Value of the header `name`, if it is set and is valid UTF-8.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|value| value.to_str().ok())
+    }
+
+    /// Sets the header `name`, replacing any previous value.
+    pub fn set_header(&mut self, name: &str, value: &str) -> NetworkResult<()> {
+        let header_name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| NetworkError::InvalidHeaderValue(name.to_string()))?;
+        let header_value = HeaderValue::from_str(value)
+            .map_err(|_| NetworkError::InvalidHeaderValue(name.to_string()))?;
+        self.headers.insert(header_name, header_value);
+        Ok(())
+    }
+
+    /// Appends a query parameter to the URL.
+    pub fn append_query_pair(&mut self, name: &str, value: &str) {
+        self.url.query_pairs_mut().append_pair(name, value);
+    }
+}
+
+/// Adds the credentials expected by the server to the outgoing requests.
+///
+/// Every [`TokenProvider`] is a `RequestAuthenticator` sending its access token
+/// as a bearer token, which is what IBM Cloud IAM expects. Implement this trait
+/// directly for other schemes, e.g. HMAC signed requests or API gateway keys in
+/// self-hosted deployments.
+pub trait RequestAuthenticator: std::fmt::Debug + Send + Sync {
+    /// Adds the credentials to `request`, just before it is sent.
+    fn authenticate(&self, request: &mut OutgoingRequest<'_>) -> NetworkResult<()>;
+
+    /// Discards any cached credential after the server rejected a request
+    /// with HTTP 401. The request is then authenticated and sent once more.
+    fn invalidate_credentials(&self) {}
+}
+
+/// Authenticates a request that is not sent, so that the credentials of a
+/// token provider are exchanged for an access token. Fails if IAM rejects them.
+pub(crate) fn check_credentials(authenticator: &dyn RequestAuthenticator) -> NetworkResult<()> {
+    let mut url = Url::parse("https://localhost/").expect("the URL is valid");
+    let mut headers = HeaderMap::new();
+    authenticator.authenticate(&mut OutgoingRequest::new(
+        "GET",
+        &mut url,
+        &mut headers,
+        &[],
+    ))
+}
+
+impl<T: TokenProvider + ?Sized> RequestAuthenticator for T {
+    fn authenticate(&self, request: &mut OutgoingRequest<'_>) -> NetworkResult<()> {
+        let bearer = format!("Bearer {}", self.get_access_token()?);
+        let value = HeaderValue::from_str(&bearer)
+            .map_err(|_| NetworkError::InvalidHeaderValue("Authorization".to_string()))?;
+        request.headers.insert(AUTHORIZATION, value);
+        Ok(())
+    }
+
+    fn invalidate_credentials(&self) {
+        self.invalidate_access_token()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct MockTokenProvider;
+
+    impl TokenProvider for MockTokenProvider {
+        fn get_access_token(&self) -> NetworkResult<String> {
+            Ok("token".to_string())
+        }
+    }
+
+    #[test]
+    fn test_token_provider_sets_bearer_token() {
+        let mut url = Url::parse("https://ibm.com/config").unwrap();
+        let mut headers = HeaderMap::new();
+        let mut request = OutgoingRequest::new("GET", &mut url, &mut headers, &[]);
+
+        MockTokenProvider.authenticate(&mut request).unwrap();
+        assert_eq!(request.header("Authorization"), Some("Bearer token"));
+    }
+
+    #[test]
+    fn test_outgoing_request_mutation() {
+        let mut url = Url::parse("https://ibm.com/apprapp/config?action=sdkConfig").unwrap();
+        let mut headers = HeaderMap::new();
+        let mut request = OutgoingRequest::new("POST", &mut url, &mut headers, b"{}");
+        assert_eq!(request.method(), "POST");
+        assert_eq!(request.path(), "/apprapp/config");
+        assert_eq!(request.body(), b"{}");
+
+        request.append_query_pair("signature", "a b");
+        request.set_header("X-Api-Key", "key").unwrap();
+        request.set_header("X-Api-Key", "other").unwrap();
+        assert!(matches!(
+            request.set_header("X Api Key", "key"),
+            Err(NetworkError::InvalidHeaderValue(name)) if name == "X Api Key"
+        ));
+
+        assert_eq!(request.query(), Some("action=sdkConfig&signature=a+b"));
+        assert_eq!(request.header("x-api-key"), Some("other"));
+    }
+}
@@ -13,9 +13,13 @@
 // limitations under the License.
 
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
-use super::Segment;
+use super::{ConfigurationFileFormat, Segment, migration};
+#[cfg(feature = "remote")]
+use crate::CacheCipher;
 use crate::Result;
+use crate::errors::{ConfigurationDataError, DeserializationError};
 use crate::network::CacheFile;
 use crate::network::serialization::environment::Environment;
 /// Represents AppConfig data in a structure intended for data exchange
@@ -28,21 +32,184 @@ use crate::network::serialization::environment::Environment;
 pub(crate) struct Collection {
     pub collection_id: String,
 }
-#[derive(Debug, Deserialize, Serialize)]
-pub(crate) struct ConfigurationJson {
-    pub environments: Vec<Environment>,
-    pub collections: Option<Vec<Collection>>,
-    pub segments: Vec<Segment>,
+/// A configuration as sent by the App Configuration service: the features,
+/// properties and segments of all the environments and collections of an
+/// instance.
+///
+/// Returned by [`ServerClient::get_configuration_json`](crate::ServerClient::get_configuration_json).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ConfigurationJson {
+    pub(crate) environments: Vec<Environment>,
+    pub(crate) collections: Option<Vec<Collection>>,
+    pub(crate) segments: Vec<Segment>,
+    /// Version of the format of the payload, checked against
+    /// [`LiveConfigurationOptions::expected_schema_version`](crate::LiveConfigurationOptions::expected_schema_version).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) schema_version: Option<u32>,
+    /// See [`ConfigurationJson::checksum`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) checksum: Option<String>,
+    /// Checksum of the document the configuration was read from, `None` for
+    /// the configurations built by the SDK.
+    #[serde(skip)]
+    pub(crate) document_checksum: Option<String>,
 }
 
 impl ConfigurationJson {
-    /// Parses a ConfigurationJson from a file
+    /// Parses a ConfigurationJson from a file, in the format given by its extension
     pub(crate) fn new(filepath: &std::path::Path) -> Result<Self> {
-        CacheFile::read_json_file(filepath)
+        Self::from_file(filepath, ConfigurationFileFormat::from_path(filepath))
+    }
+
+    /// Parses a ConfigurationJson from a file in the given format
+    pub(crate) fn from_file(
+        filepath: &std::path::Path,
+        format: ConfigurationFileFormat,
+    ) -> Result<Self> {
+        let source = format!("file '{}'", filepath.display());
+        let value = match format {
+            ConfigurationFileFormat::Json => CacheFile::read_json_file(filepath)?,
+            _ => format.parse(&CacheFile::read_file(filepath)?, &source)?,
+        };
+        Self::from_value(value, &source)
+    }
+
+    /// Parses the JSON payload of the service, or of a configuration file.
+    pub fn from_json_str(json: &str) -> Result<Self> {
+        let value = ConfigurationFileFormat::Json.parse(json, "JSON string")?;
+        Self::from_value(value, "JSON string")
+    }
+
+    /// Parses the contents of a configuration file in `format`, `source` names
+    /// it in the errors.
+    #[cfg(feature = "remote")]
+    pub(crate) fn from_str_in_format(
+        content: &str,
+        format: ConfigurationFileFormat,
+        source: &str,
+    ) -> Result<Self> {
+        Self::from_value(format.parse(content, source)?, source)
+    }
+
+    /// Deserializes a configuration read from `source`, migrating it from
+    /// older formats first.
+    fn from_value(mut value: serde_json::Value, source: &str) -> Result<Self> {
+        let document_checksum = checksum_of_document(&mut value);
+        migration::migrate(&mut value);
+        let mut configuration: Self =
+            serde_json::from_value(value).map_err(|e| DeserializationError {
+                string: format!("Error deserializing Configuration from {source}"),
+                source: e.into(),
+            })?;
+        configuration.document_checksum = Some(document_checksum);
+        Ok(configuration)
+    }
+
+    /// Deserializes the payload of the service.
+    #[cfg(feature = "remote")]
+    pub(crate) fn from_payload(mut value: serde_json::Value) -> serde_json::Result<Self> {
+        let document_checksum = checksum_of_document(&mut value);
+        let mut configuration: Self = serde_json::from_value(value)?;
+        configuration.document_checksum = Some(document_checksum);
+        Ok(configuration)
+    }
+
+    /// Writes the configuration to a file, together with its checksum: in the
+    /// format given by its extension, or encrypted JSON with `cipher`. The file
+    /// is replaced at once, a crash while writing keeps the previous one.
+    #[cfg(feature = "remote")]
+    pub(crate) fn write_to_file(
+        &self,
+        filepath: &std::path::Path,
+        cipher: Option<&CacheCipher>,
+    ) -> Result<()> {
+        let format = match cipher {
+            Some(_) => ConfigurationFileFormat::Json,
+            None => ConfigurationFileFormat::from_path(filepath),
+        };
+        let mut value = format.prepare(self.to_value()?);
+        let checksum = checksum_of_document(&mut value);
+        if let serde_json::Value::Object(fields) = &mut value {
+            fields.insert("checksum".into(), checksum.into());
+        }
+        let contents = format.serialize(&value, &format!("file '{}'", filepath.display()))?;
+        match cipher {
+            Some(cipher) => CacheFile::write_file(&cipher.encrypt(contents.as_bytes())?, filepath),
+            None => CacheFile::write_file(contents.as_bytes(), filepath),
+        }
+    }
+
+    /// Returns the SHA-256 of the configuration, hex encoded, see
+    /// [`checksum_of_document`]. Computed over the document it was read from,
+    /// fields unknown to the SDK included, or over its serialization for the
+    /// configurations built by the SDK.
+    pub(crate) fn checksum(&self) -> std::result::Result<String, ConfigurationDataError> {
+        match &self.document_checksum {
+            Some(checksum) => Ok(checksum.clone()),
+            None => Ok(checksum_of_document(&mut self.to_value()?)),
+        }
+    }
+
+    fn to_value(&self) -> std::result::Result<serde_json::Value, ConfigurationDataError> {
+        serde_json::to_value(self).map_err(ConfigurationDataError::Serialization)
+    }
+
+    /// Checks the configuration against the checksum it carries, if any.
+    pub(crate) fn verify_checksum(&self) -> std::result::Result<(), ConfigurationDataError> {
+        match &self.checksum {
+            Some(expected) => {
+                let found = self.checksum()?;
+                if found.eq_ignore_ascii_case(expected) {
+                    Ok(())
+                } else {
+                    Err(ConfigurationDataError::ChecksumMismatch {
+                        expected: expected.clone(),
+                        found,
+                    })
+                }
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Checks the schema version of the configuration, if one is expected.
+    #[cfg(feature = "remote")]
+    pub(crate) fn verify_schema_version(
+        &self,
+        expected: Option<u32>,
+    ) -> std::result::Result<(), ConfigurationDataError> {
+        match expected {
+            Some(expected) if self.schema_version != Some(expected) => {
+                Err(ConfigurationDataError::SchemaVersionMismatch {
+                    expected,
+                    found: self.schema_version,
+                })
+            }
+            _ => Ok(()),
+        }
     }
-    pub(crate) fn write_to_file(&self, filepath: &std::path::Path) -> Result<()> {
-        CacheFile::write_json_file(self, filepath)
+}
+
+/// SHA-256 of the compact JSON serialization of `document`, with sorted keys
+/// and without its `checksum` field, hex encoded.
+fn checksum_of_document(document: &mut serde_json::Value) -> String {
+    let serde_json::Value::Object(fields) = document else {
+        return checksum_of(document);
+    };
+    let checksum = fields.remove("checksum");
+    let found = checksum_of(document);
+    if let (serde_json::Value::Object(fields), Some(checksum)) = (document, checksum) {
+        fields.insert("checksum".into(), checksum);
     }
+    found
+}
+
+/// SHA-256 of the compact JSON serialization of `value`, hex encoded.
+fn checksum_of(value: &serde_json::Value) -> String {
+    Sha256::digest(value.to_string())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
 }
 
 #[cfg(test)]
@@ -50,7 +217,7 @@ pub(crate) mod fixtures {
 
     use crate::models::Configuration;
     use crate::network::serialization::config_value::ConfigValue;
-    use crate::network::serialization::segments::Segments;
+    use crate::network::serialization::segments::{Segments, SegmentsOperator};
     use crate::network::serialization::{Feature, Property, Rule, SegmentRule, ValueType};
 
     use super::*;
@@ -71,10 +238,13 @@ pub(crate) mod fixtures {
         let environment_id = "environment_id".to_string();
         ConfigurationJson {
             environments: vec![Environment {
+                name: None,
                 environment_id: environment_id.clone(),
                 features: vec![Feature {
-                    name: "F1".to_string(),
-                    feature_id: "f1".to_string(),
+                    name: "F1".into(),
+                    feature_id: "f1".into(),
+                    description: None,
+                    tags: None,
                     r#type: ValueType::Numeric,
                     format: None,
                     enabled_value: ConfigValue(serde_json::Value::Number(42.into())),
@@ -93,6 +263,9 @@ pub(crate) mod fixtures {
                 collection_id: "collection_id".to_string(),
             }]),
             segments: Vec::new(),
+            schema_version: None,
+            checksum: None,
+            document_checksum: None,
         }
     }
 
@@ -113,10 +286,13 @@ pub(crate) mod fixtures {
         let environment_id = "environment_id".to_string();
         let config_json = ConfigurationJson {
             environments: vec![Environment {
+                name: None,
                 environment_id: environment_id.clone(),
                 features: vec![Feature {
-                    name: "F1".to_string(),
-                    feature_id: "f1".to_string(),
+                    name: "F1".into(),
+                    feature_id: "f1".into(),
+                    description: None,
+                    tags: None,
                     r#type: ValueType::Numeric,
                     format: None,
                     enabled_value: ConfigValue(serde_json::Value::Number(42.into())),
@@ -135,6 +311,9 @@ pub(crate) mod fixtures {
                 collection_id: "collection_id".to_string(),
             }]),
             segments: Vec::new(),
+            schema_version: None,
+            checksum: None,
+            document_checksum: None,
         };
         Configuration::new(&environment_id, "collection_id", config_json).unwrap()
     }
@@ -144,10 +323,12 @@ pub(crate) mod fixtures {
         let environment_id = "environment_id".to_string();
         ConfigurationJson {
             environments: vec![Environment {
+                name: None,
                 environment_id: environment_id.clone(),
                 properties: vec![Property {
                     name: "P1".to_string(),
                     property_id: "p1".to_string(),
+                    description: None,
                     r#type: ValueType::Numeric,
                     format: None,
                     value: ConfigValue(serde_json::Value::Number(42.into())),
@@ -161,6 +342,9 @@ pub(crate) mod fixtures {
                 collection_id: "collection_id".to_string(),
             }]),
             segments: Vec::new(),
+            schema_version: None,
+            checksum: None,
+            document_checksum: None,
         }
     }
 
@@ -169,10 +353,12 @@ pub(crate) mod fixtures {
         let environment_id = "environment_id".to_string();
         let config_json = ConfigurationJson {
             environments: vec![Environment {
+                name: None,
                 environment_id: environment_id.clone(),
                 properties: vec![Property {
                     name: "P1".to_string(),
                     property_id: "p1".to_string(),
+                    description: None,
                     r#type: ValueType::Numeric,
                     format: None,
                     value: ConfigValue(serde_json::Value::Number(42.into())),
@@ -186,6 +372,9 @@ pub(crate) mod fixtures {
                 collection_id: "collection_id".to_string(),
             }]),
             segments: Vec::new(),
+            schema_version: None,
+            checksum: None,
+            document_checksum: None,
         };
         Configuration::new(&environment_id, "collection_id", config_json).unwrap()
     }
@@ -196,6 +385,7 @@ pub(crate) mod fixtures {
             SegmentRule {
                 rules: vec![Segments {
                     segments: vec!["some_segment_id_1".into()],
+                    operator: SegmentsOperator::In,
                 }],
                 value: ConfigValue(serde_json::Value::Number((-48).into())),
                 order: 1,
@@ -203,10 +393,13 @@ pub(crate) mod fixtures {
                 rule_id: None,
                 rollout_type: None,
                 rollout_configuration: None,
+                start_at: None,
+                end_at: None,
             },
             SegmentRule {
                 rules: vec![Segments {
                     segments: vec!["some_segment_id_2".into()],
+                    operator: SegmentsOperator::In,
                 }],
                 value: ConfigValue(serde_json::Value::Number((-49).into())),
                 order: 0,
@@ -214,6 +407,8 @@ pub(crate) mod fixtures {
                 rule_id: None,
                 rollout_type: None,
                 rollout_configuration: None,
+                start_at: None,
+                end_at: None,
             },
         ];
         assert!(segment_rules[0].order > segment_rules[1].order);
@@ -221,10 +416,13 @@ pub(crate) mod fixtures {
         let environment_id = "environment_id".to_string();
         let config_json = ConfigurationJson {
             environments: vec![Environment {
+                name: None,
                 environment_id: environment_id.clone(),
                 features: vec![Feature {
-                    name: "F1".to_string(),
-                    feature_id: "f1".to_string(),
+                    name: "F1".into(),
+                    feature_id: "f1".into(),
+                    description: None,
+                    tags: None,
                     r#type: ValueType::Numeric,
                     format: None,
                     enabled_value: ConfigValue(serde_json::Value::Number((-42).into())),
@@ -240,6 +438,7 @@ pub(crate) mod fixtures {
                 properties: vec![Property {
                     name: "P1".to_string(),
                     property_id: "f1".to_string(),
+                    description: None,
                     r#type: ValueType::Numeric,
                     format: None,
                     value: ConfigValue(serde_json::Value::Number((-42).into())),
@@ -275,7 +474,131 @@ pub(crate) mod fixtures {
                     }],
                 },
             ],
+            schema_version: None,
+            checksum: None,
+            document_checksum: None,
         };
         Configuration::new(&environment_id, "collection_id", config_json).unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::fixtures::configuration_json_feature1_enabled;
+    use super::*;
+    use crate::models::Configuration;
+
+    #[test]
+    fn test_checksum() {
+        let mut configuration_json = configuration_json_feature1_enabled();
+        let checksum = configuration_json.checksum().unwrap();
+        assert_eq!(checksum.len(), 64);
+
+        // The checksum field itself is not part of it
+        configuration_json.checksum = Some(checksum.to_uppercase());
+        assert_eq!(configuration_json.checksum().unwrap(), checksum);
+        assert!(configuration_json.verify_checksum().is_ok());
+
+        configuration_json.environments[0].features[0].enabled = false;
+        assert!(matches!(
+            configuration_json.verify_checksum(),
+            Err(ConfigurationDataError::ChecksumMismatch { .. })
+        ));
+        assert!(matches!(
+            Configuration::new("environment_id", "collection_id", configuration_json),
+            Err(ConfigurationDataError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_checksum_covers_the_document_read() {
+        let document = serde_json::json!({
+            "environments": [],
+            "segments": [],
+            "unknown_field": "kept",
+        });
+        let checksum = ConfigurationJson::from_json_str(&document.to_string())
+            .unwrap()
+            .checksum()
+            .unwrap();
+
+        let mut signed = document.clone();
+        signed["checksum"] = checksum.clone().into();
+        let read = ConfigurationJson::from_json_str(&signed.to_string()).unwrap();
+        assert_eq!(read.checksum().unwrap(), checksum);
+        assert!(read.verify_checksum().is_ok());
+
+        // The fields unknown to the SDK are covered as well
+        signed["unknown_field"] = "tampered".into();
+        let read = ConfigurationJson::from_json_str(&signed.to_string()).unwrap();
+        assert!(matches!(
+            read.verify_checksum(),
+            Err(ConfigurationDataError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_write_to_file_adds_checksum() {
+        let path = std::env::temp_dir().join(format!("checksum-{}.json", std::process::id()));
+        let configuration_json = configuration_json_feature1_enabled();
+        configuration_json.write_to_file(&path, None).unwrap();
+
+        let read = ConfigurationJson::new(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read.checksum, Some(configuration_json.checksum().unwrap()));
+        assert!(read.verify_checksum().is_ok());
+    }
+
+    #[cfg(all(feature = "yaml", feature = "toml"))]
+    #[rstest::rstest]
+    #[case("yaml")]
+    #[case("toml")]
+    fn test_write_to_file_keeps_format(#[case] extension: &str) {
+        let directory = std::env::temp_dir().join(format!("write-format-{}", std::process::id()));
+        let path = directory.join(format!("bootstrap.{extension}"));
+        let configuration_json = configuration_json_feature1_enabled();
+        configuration_json.write_to_file(&path, None).unwrap();
+        // Written again over the previous file
+        configuration_json.write_to_file(&path, None).unwrap();
+
+        let read = ConfigurationJson::new(&path);
+        let files = std::fs::read_dir(&directory).unwrap().count();
+        std::fs::remove_dir_all(&directory).unwrap();
+        let read = read.unwrap();
+        assert!(read.checksum.is_some());
+        assert!(read.verify_checksum().is_ok());
+        // No temporary file left behind
+        assert_eq!(files, 1);
+    }
+
+    #[test]
+    fn test_optional_fields_left_out() {
+        // Absent fields are not written, so the files written before they were
+        // added keep their checksum
+        let value = serde_json::to_value(configuration_json_feature1_enabled()).unwrap();
+        let feature = &value["environments"][0]["features"][0];
+        assert!(feature.get("description").is_none());
+        assert!(feature.get("tags").is_none());
+        let property = serde_json::to_value(
+            super::fixtures::configuration_json_property1_enabled().environments[0].properties[0]
+                .clone(),
+        )
+        .unwrap();
+        assert!(property.get("description").is_none());
+    }
+
+    #[test]
+    fn test_verify_schema_version() {
+        let mut configuration_json = configuration_json_feature1_enabled();
+        assert!(configuration_json.verify_schema_version(None).is_ok());
+        assert!(matches!(
+            configuration_json.verify_schema_version(Some(1)),
+            Err(ConfigurationDataError::SchemaVersionMismatch {
+                expected: 1,
+                found: None
+            })
+        ));
+        configuration_json.schema_version = Some(1);
+        assert!(configuration_json.verify_schema_version(Some(1)).is_ok());
+    }
+}
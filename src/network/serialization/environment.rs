@@ -16,8 +16,9 @@ use serde::{Deserialize, Serialize};
 
 use super::{Feature, Property};
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub(crate) struct Environment {
+    pub name: Option<String>,
     pub environment_id: String,
     pub features: Vec<Feature>,
     pub properties: Vec<Property>,
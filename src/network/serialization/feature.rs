@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
 
 use super::{Collection, SegmentRule, ValueType};
@@ -54,8 +56,12 @@ pub(crate) struct Experiment {
 }
 #[derive(Debug, Deserialize, Clone, PartialEq, Eq, Serialize)]
 pub(crate) struct Feature {
-    pub name: String,
-    pub feature_id: String,
+    pub name: Arc<str>,
+    pub feature_id: Arc<str>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<String>,
     pub r#type: ValueType,
     pub format: Option<String>,
     pub enabled_value: ConfigValue,
@@ -0,0 +1,182 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+
+use crate::errors::{DeserializationError, DeserializationErrorKind, Error, Result};
+
+/// Format of a configuration file.
+///
+/// The exports of the service are JSON. The same structure can be kept in YAML,
+/// with the `yaml` feature, or TOML, with the `toml` feature.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConfigurationFileFormat {
+    #[default]
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl ConfigurationFileFormat {
+    /// Guesses the format from the extension of `path`: `.yaml` and `.yml` are
+    /// YAML, `.toml` is TOML, anything else is read as JSON.
+    pub fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("yaml" | "yml") => Self::Yaml,
+            Some("toml") => Self::Toml,
+            _ => Self::Json,
+        }
+    }
+
+    /// Deserializes `content`, `source` names it in the errors.
+    pub(crate) fn parse<T: DeserializeOwned>(self, content: &str, source: &str) -> Result<T> {
+        let deserialization_error = |e: DeserializationErrorKind| {
+            Error::DeserializationError(DeserializationError {
+                string: format!("Error deserializing Configuration from {source}"),
+                source: e,
+            })
+        };
+        match self {
+            Self::Json => {
+                serde_json::from_str(content).map_err(|e| deserialization_error(e.into()))
+            }
+            #[cfg(feature = "yaml")]
+            Self::Yaml => {
+                serde_yaml_ng::from_str(content).map_err(|e| deserialization_error(e.into()))
+            }
+            #[cfg(feature = "toml")]
+            Self::Toml => toml::from_str(content).map_err(|e| deserialization_error(e.into())),
+            #[allow(unreachable_patterns)]
+            _ => Err(Error::Other(format!(
+                "Reading {self} configurations requires the '{}' feature",
+                self.to_string().to_ascii_lowercase()
+            ))),
+        }
+    }
+
+    /// Serializes `value`, `destination` names it in the errors. TOML has no
+    /// null, see [`ConfigurationFileFormat::prepare`].
+    #[cfg(feature = "remote")]
+    pub(crate) fn serialize(self, value: &serde_json::Value, destination: &str) -> Result<String> {
+        let serialization_error = |e: &dyn std::fmt::Display| {
+            Error::Other(format!(
+                "Error serializing Configuration to {destination}: {e}"
+            ))
+        };
+        match self {
+            Self::Json => Ok(value.to_string()),
+            #[cfg(feature = "yaml")]
+            Self::Yaml => serde_yaml_ng::to_string(value).map_err(|e| serialization_error(&e)),
+            #[cfg(feature = "toml")]
+            Self::Toml => toml::to_string(value).map_err(|e| serialization_error(&e)),
+            #[allow(unreachable_patterns)]
+            _ => Err(serialization_error(&format!(
+                "writing {self} configurations requires the '{}' feature",
+                self.to_string().to_ascii_lowercase()
+            ))),
+        }
+    }
+
+    /// Adapts `value` to what the format can represent before it is
+    /// serialized: the null fields are left out in TOML.
+    #[cfg(feature = "remote")]
+    pub(crate) fn prepare(self, value: serde_json::Value) -> serde_json::Value {
+        match self {
+            Self::Toml => without_nulls(value),
+            _ => value,
+        }
+    }
+}
+
+#[cfg(feature = "remote")]
+fn without_nulls(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(fields) => fields
+            .into_iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| (key, without_nulls(value)))
+            .collect(),
+        serde_json::Value::Array(items) => items.into_iter().map(without_nulls).collect(),
+        value => value,
+    }
+}
+
+impl std::fmt::Display for ConfigurationFileFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Json => write!(f, "JSON"),
+            Self::Yaml => write!(f, "YAML"),
+            Self::Toml => write!(f, "TOML"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(all(feature = "yaml", feature = "toml"))]
+    use crate::network::serialization::ConfigurationJson;
+    #[cfg(all(feature = "yaml", feature = "toml"))]
+    use crate::network::serialization::fixtures::example_configuration_enterprise_path;
+    use rstest::rstest;
+    #[cfg(all(feature = "yaml", feature = "toml"))]
+    use std::path::PathBuf;
+
+    #[rstest]
+    #[case("config.json", ConfigurationFileFormat::Json)]
+    #[case("config.YAML", ConfigurationFileFormat::Yaml)]
+    #[case("config.yml", ConfigurationFileFormat::Yaml)]
+    #[case("config.toml", ConfigurationFileFormat::Toml)]
+    #[case("config.cache", ConfigurationFileFormat::Json)]
+    #[case("config", ConfigurationFileFormat::Json)]
+    fn test_format_from_path(#[case] path: &str, #[case] expected: ConfigurationFileFormat) {
+        assert_eq!(
+            ConfigurationFileFormat::from_path(Path::new(path)),
+            expected
+        );
+    }
+
+    #[cfg(all(feature = "yaml", feature = "toml"))]
+    #[rstest]
+    fn test_yaml_and_toml_match_json(example_configuration_enterprise_path: PathBuf) {
+        let checksum = |document: &serde_json::Value| {
+            ConfigurationJson::from_json_str(&document.to_string())
+                .unwrap()
+                .checksum()
+                .unwrap()
+        };
+        let document: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(&example_configuration_enterprise_path).unwrap(),
+        )
+        .unwrap();
+
+        let yaml = serde_yaml_ng::to_string(&document).unwrap();
+        let from_yaml: serde_json::Value =
+            ConfigurationFileFormat::Yaml.parse(&yaml, "test").unwrap();
+        assert_eq!(checksum(&from_yaml), checksum(&document));
+
+        let document = ConfigurationFileFormat::Toml.prepare(document);
+        let toml = toml::to_string(&document).unwrap();
+        let from_toml: serde_json::Value =
+            ConfigurationFileFormat::Toml.parse(&toml, "test").unwrap();
+        assert_eq!(checksum(&from_toml), checksum(&document));
+    }
+}
@@ -0,0 +1,160 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Migrations of configuration files exported in older formats.
+//!
+//! Files declare their format in the `schema_version` field. Files without
+//! it go through every migration: they only fill in fields that are missing,
+//! so a file in the current format is left unchanged.
+
+use serde_json::{Map, Value};
+
+/// Version of the format of the configuration files read by this SDK.
+pub(crate) const CURRENT_SCHEMA_VERSION: u32 = 2;
+
+/// Brings `configuration`, read from a file, to the current format.
+///
+/// Configurations declaring a newer version are left as they are, fields
+/// unknown to this SDK are ignored when deserializing them.
+pub(crate) fn migrate(configuration: &mut Value) {
+    let version = configuration.get("schema_version").and_then(Value::as_u64);
+    if version.is_some_and(|version| version >= u64::from(CURRENT_SCHEMA_VERSION)) {
+        return;
+    }
+    if let Some(version) = version {
+        log::info!(
+            "Migrating configuration from schema version {version} to {CURRENT_SCHEMA_VERSION}"
+        );
+    }
+    migrate_from_v1(configuration);
+}
+
+/// Version 1 files were exported before percentage rollouts and ordered
+/// segment rules, and leave out empty lists.
+fn migrate_from_v1(configuration: &mut Value) {
+    let Some(fields) = configuration.as_object_mut() else {
+        return;
+    };
+    fields
+        .entry("segments")
+        .or_insert_with(|| Value::Array(Vec::new()));
+    let environments = fields
+        .get_mut("environments")
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object_mut);
+    for environment in environments {
+        for (list, is_feature) in [("features", true), ("properties", false)] {
+            let resources = environment
+                .entry(list)
+                .or_insert_with(|| Value::Array(Vec::new()))
+                .as_array_mut()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_object_mut);
+            for resource in resources {
+                if is_feature {
+                    resource
+                        .entry("rollout_percentage")
+                        .or_insert_with(|| Value::from(100));
+                }
+                order_segment_rules(resource);
+            }
+        }
+    }
+}
+
+/// Segment rules without `order` are evaluated in the order of the file.
+fn order_segment_rules(resource: &mut Map<String, Value>) {
+    let rules = resource
+        .entry("segment_rules")
+        .or_insert_with(|| Value::Array(Vec::new()))
+        .as_array_mut()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object_mut);
+    for (position, rule) in rules.enumerate() {
+        rule.entry("order")
+            .or_insert_with(|| Value::from(position + 1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::serialization::ConfigurationJson;
+    use crate::network::serialization::fixtures::example_configuration_enterprise_path;
+    use rstest::rstest;
+    use std::path::PathBuf;
+
+    #[rstest]
+    fn test_current_format_unchanged(example_configuration_enterprise_path: PathBuf) {
+        let json = std::fs::read_to_string(example_configuration_enterprise_path).unwrap();
+        let original: Value = serde_json::from_str(&json).unwrap();
+        let mut migrated = original.clone();
+        migrate(&mut migrated);
+        assert_eq!(migrated, original);
+    }
+
+    #[test]
+    fn test_migrate_from_v1() {
+        let mut configuration = serde_json::json!({
+            "schema_version": 1,
+            "environments": [{
+                "environment_id": "dev",
+                "features": [{
+                    "name": "F1", "feature_id": "f1", "type": "BOOLEAN",
+                    "enabled_value": true, "disabled_value": false, "enabled": true,
+                    "segment_rules": [
+                        {"rules": [{"segments": ["s1"]}], "value": false},
+                        {"rules": [{"segments": ["s2"]}], "value": true}
+                    ]
+                }]
+            }]
+        });
+        migrate(&mut configuration);
+
+        let environment = &configuration["environments"][0];
+        assert_eq!(configuration["segments"], serde_json::json!([]));
+        assert_eq!(environment["properties"], serde_json::json!([]));
+        assert_eq!(environment["features"][0]["rollout_percentage"], 100);
+        let orders: Vec<_> = environment["features"][0]["segment_rules"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|rule| rule["order"].clone())
+            .collect();
+        assert_eq!(orders, [1, 2]);
+        // The declared version is kept, it may be checked against the expected one
+        assert_eq!(configuration["schema_version"], 1);
+
+        let configuration: ConfigurationJson = serde_json::from_value(configuration).unwrap();
+        assert_eq!(
+            configuration.environments[0].features[0].segment_rules[1].order,
+            2
+        );
+    }
+
+    #[test]
+    fn test_newer_versions_not_migrated() {
+        let mut configuration = serde_json::json!({
+            "schema_version": CURRENT_SCHEMA_VERSION + 1,
+            "environments": []
+        });
+        let original = configuration.clone();
+        migrate(&mut configuration);
+        assert_eq!(configuration, original);
+    }
+}
@@ -19,20 +19,33 @@ mod config_value;
 mod configuration;
 mod environment;
 mod feature;
+mod file_format;
+mod migration;
 mod property;
 mod rule;
 mod segment;
 mod segment_rule;
 mod segments;
 mod value_type;
+#[cfg(feature = "remote")]
+mod websocket_message;
 
-pub(crate) use configuration::{Collection, ConfigurationJson};
-pub(crate) use feature::Feature;
+pub(crate) use config_value::ConfigValue;
+pub(crate) use configuration::Collection;
+pub use configuration::ConfigurationJson;
+pub(crate) use environment::Environment;
+pub(crate) use feature::{Experiment, Feature};
+pub use file_format::ConfigurationFileFormat;
 pub(crate) use property::Property;
 pub(crate) use rule::Rule;
 pub(crate) use segment::Segment;
 pub(crate) use segment_rule::SegmentRule;
+pub(crate) use segments::SegmentsOperator;
 pub(crate) use value_type::ValueType;
+#[cfg(all(test, feature = "remote"))]
+pub(crate) use websocket_message::SERVER_HEARTBEAT;
+#[cfg(feature = "remote")]
+pub(crate) use websocket_message::WebsocketMessage;
 
 use crate::Value;
 
@@ -43,6 +56,14 @@ impl TryFrom<(ValueType, config_value::ConfigValue)> for Value {
         value: (ValueType, config_value::ConfigValue),
     ) -> std::result::Result<Self, Self::Error> {
         let (kind, value) = value;
+        // Arrays are lists of values of the type, e.g. the tenant IDs of a STRING property
+        if let serde_json::Value::Array(items) = value.0 {
+            return items
+                .into_iter()
+                .map(|item| Value::try_from((kind, config_value::ConfigValue(item))))
+                .collect::<std::result::Result<_, _>>()
+                .map(Value::List);
+        }
         match kind {
             ValueType::Numeric => {
                 if let Some(n) = value.as_i64() {
@@ -65,6 +86,7 @@ impl TryFrom<(ValueType, config_value::ConfigValue)> for Value {
                 .as_string()
                 .map(Value::String)
                 .ok_or(crate::Error::MismatchType),
+            ValueType::Unknown => Err(crate::Error::MismatchType),
         }
     }
 }
@@ -22,6 +22,8 @@ use crate::network::serialization::configuration::Collection;
 pub(crate) struct Property {
     pub name: String,
     pub property_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
     pub r#type: ValueType,
     pub tags: Option<String>,
     pub format: Option<String>,
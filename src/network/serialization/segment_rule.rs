@@ -31,12 +31,16 @@ pub(crate) struct SegmentRule {
     pub rule_id: Option<String>,
     pub rollout_type: Option<String>,
     pub rollout_configuration: Option<RolloutConfiguration>,
+    /// RFC 3339 timestamp before which the rule does not apply (scheduled targeting)
+    pub start_at: Option<String>,
+    /// RFC 3339 timestamp from which the rule no longer applies (scheduled targeting)
+    pub end_at: Option<String>,
 }
 
 #[cfg(test)]
 pub mod fixtures {
     use super::*;
-    use crate::network::serialization::SegmentRule;
+    use crate::network::serialization::{SegmentRule, SegmentsOperator};
     use rstest::*;
 
     pub fn create_one_segment_rule(
@@ -47,6 +51,7 @@ pub mod fixtures {
         vec![SegmentRule {
             rules: vec![Segments {
                 segments: vec![segment_id],
+                operator: SegmentsOperator::In,
             }],
             value: ConfigValue(value),
             order: 0,
@@ -54,6 +59,8 @@ pub mod fixtures {
             rule_id: None,
             rollout_type: None,
             rollout_configuration: None,
+            start_at: None,
+            end_at: None,
         }]
     }
 
@@ -62,6 +69,7 @@ pub mod fixtures {
         vec![SegmentRule {
             rules: vec![Segments {
                 segments: vec!["some_segment_id".into()],
+                operator: SegmentsOperator::In,
             }],
             value: ConfigValue(serde_json::Value::Number((-48).into())),
             order: 0,
@@ -69,6 +77,8 @@ pub mod fixtures {
             rule_id: None,
             rollout_type: None,
             rollout_configuration: None,
+            start_at: None,
+            end_at: None,
         }]
     }
 
@@ -78,9 +88,11 @@ pub mod fixtures {
             rules: vec![
                 Segments {
                     segments: vec!["some_segment_id_1".into(), "some_segment_id_2".into()],
+                    operator: SegmentsOperator::In,
                 },
                 Segments {
                     segments: vec!["some_segment_id_3".into()],
+                    operator: SegmentsOperator::In,
                 },
             ],
             value: ConfigValue(serde_json::Value::Number((-48).into())),
@@ -89,6 +101,8 @@ pub mod fixtures {
             rule_id: None,
             rollout_type: None,
             rollout_configuration: None,
+            start_at: None,
+            end_at: None,
         }]
     }
 
@@ -97,6 +111,7 @@ pub mod fixtures {
         vec![SegmentRule {
             rules: vec![Segments {
                 segments: vec!["non_existing_segment_id".into()],
+                operator: SegmentsOperator::In,
             }],
             value: ConfigValue(serde_json::Value::Number((-48).into())),
             order: 0,
@@ -104,6 +119,8 @@ pub mod fixtures {
             rule_id: None,
             rollout_type: None,
             rollout_configuration: None,
+            start_at: None,
+            end_at: None,
         }]
     }
 }
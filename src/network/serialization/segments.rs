@@ -16,4 +16,47 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
 pub(crate) struct Segments {
     pub segments: Vec<String>,
+    #[serde(default, skip_serializing_if = "SegmentsOperator::is_in")]
+    pub operator: SegmentsOperator,
+}
+
+/// How an entity is matched against a list of [`Segments`].
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum SegmentsOperator {
+    /// The entity belongs to any of the segments.
+    #[default]
+    In,
+    /// The entity belongs to none of the segments (exclusion).
+    Not,
+}
+
+impl SegmentsOperator {
+    fn is_in(&self) -> bool {
+        *self == SegmentsOperator::In
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_operator() {
+        let segments: Segments =
+            serde_json::from_str(r#"{"segments": ["s1", "s2"], "operator": "not"}"#).unwrap();
+        assert_eq!(segments.operator, SegmentsOperator::Not);
+
+        let segments: Segments = serde_json::from_str(r#"{"segments": ["s1"]}"#).unwrap();
+        assert_eq!(segments.operator, SegmentsOperator::In);
+        // Written back as received
+        assert_eq!(
+            serde_json::to_string(&segments).unwrap(),
+            r#"{"segments":["s1"]}"#
+        );
+
+        assert!(
+            serde_json::from_str::<Segments>(r#"{"segments": ["s1"], "operator": "nor"}"#).is_err()
+        );
+    }
 }
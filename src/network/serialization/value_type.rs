@@ -16,6 +16,8 @@ use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
 
+use crate::Value;
+
 #[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub(crate) enum ValueType {
     #[serde(rename(serialize = "NUMERIC", deserialize = "NUMERIC"))]
@@ -26,6 +28,10 @@ pub(crate) enum ValueType {
     String,
     #[serde(rename(serialize = "SECRETREF", deserialize = "SECRETREF"))]
     SecretRef,
+    /// A type introduced by a newer version of the service. Resources with this
+    /// type are skipped instead of rejecting the whole configuration.
+    #[serde(rename(serialize = "UNKNOWN"), other)]
+    Unknown,
 }
 
 impl Display for ValueType {
@@ -35,7 +41,35 @@ impl Display for ValueType {
             Self::Boolean => "BOOLEAN",
             Self::String => "STRING",
             Self::SecretRef => "SECRETREF",
+            Self::Unknown => "UNKNOWN",
         };
         write!(f, "{label}")
     }
 }
+
+impl From<&str> for ValueType {
+    fn from(label: &str) -> Self {
+        match label {
+            "NUMERIC" => Self::Numeric,
+            "BOOLEAN" => Self::Boolean,
+            "STRING" => Self::String,
+            "SECRETREF" => Self::SecretRef,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl ValueType {
+    /// Whether `value` is a value of this type, or a list of them. Any value
+    /// is accepted for unknown types.
+    pub(crate) fn accepts(self, value: &Value) -> bool {
+        match (self, value) {
+            (_, Value::List(items)) => items.iter().all(|item| self.accepts(item)),
+            (Self::Numeric, Value::Float64(_) | Value::UInt64(_) | Value::Int64(_))
+            | (Self::Boolean, Value::Boolean(_))
+            | (Self::String | Self::SecretRef, Value::String(_))
+            | (Self::Unknown, _) => true,
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,144 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::Deserialize;
+
+/// Text the server sends periodically to keep the websocket alive.
+pub(crate) const SERVER_HEARTBEAT: &str = "test message";
+
+/// Text messages received on the websocket monitoring configuration changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum WebsocketMessage {
+    /// Keep-alive, nothing changed.
+    Heartbeat,
+    /// The configuration of an environment and collection changed.
+    ConfigurationChanged(ConfigurationChange),
+}
+
+/// Notification of a configuration change, on the websocket opened for one
+/// environment and collection.
+///
+/// The service does not document the content of these messages, so the
+/// fields read from them are only logged: every message other than the
+/// heartbeat is applied by downloading the whole configuration again.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+pub(crate) struct ConfigurationChange {
+    #[serde(default)]
+    pub environment_id: Option<String>,
+    #[serde(default)]
+    pub collection_id: Option<String>,
+    #[serde(default)]
+    pub feature_id: Option<String>,
+    #[serde(default)]
+    pub property_id: Option<String>,
+}
+
+impl WebsocketMessage {
+    /// Parses a text message: the heartbeat, a JSON object or a list of
+    /// `key:value` pairs separated by `;`. Any message other than the
+    /// heartbeat is a change, even if it cannot be parsed, so no update is
+    /// missed.
+    pub(crate) fn parse(text: &str) -> Self {
+        let text = text.trim();
+        if text == SERVER_HEARTBEAT {
+            return Self::Heartbeat;
+        }
+        let change = if text.starts_with('{') {
+            serde_json::from_str(text).unwrap_or_else(|e| {
+                log::debug!("Unexpected websocket message '{text}': {e}");
+                ConfigurationChange::default()
+            })
+        } else {
+            ConfigurationChange::from_pairs(text)
+        };
+        Self::ConfigurationChanged(change)
+    }
+}
+
+impl ConfigurationChange {
+    fn from_pairs(text: &str) -> Self {
+        let mut change = Self::default();
+        for (key, value) in text
+            .split(';')
+            .filter_map(|pair| pair.split_once(':'))
+            .map(|(key, value)| (key.trim(), value.trim().to_string()))
+        {
+            match key {
+                "environment_id" => change.environment_id = Some(value),
+                "collection_id" => change.collection_id = Some(value),
+                "feature_id" => change.feature_id = Some(value),
+                "property_id" => change.property_id = Some(value),
+                _ => {}
+            }
+        }
+        change
+    }
+
+    /// The feature or property that changed, if the notification names one.
+    pub(crate) fn resource(&self) -> Option<String> {
+        match (&self.feature_id, &self.property_id) {
+            (Some(feature_id), _) => Some(format!("feature '{feature_id}'")),
+            (None, Some(property_id)) => Some(format!("property '{property_id}'")),
+            (None, None) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn change(environment_id: Option<&str>, collection_id: Option<&str>) -> WebsocketMessage {
+        WebsocketMessage::ConfigurationChanged(ConfigurationChange {
+            environment_id: environment_id.map(str::to_string),
+            collection_id: collection_id.map(str::to_string),
+            ..Default::default()
+        })
+    }
+
+    #[rstest]
+    #[case(SERVER_HEARTBEAT, WebsocketMessage::Heartbeat)]
+    #[case("collection_id:c1;environment_id:e1", change(Some("e1"), Some("c1")))]
+    #[case(" environment_id : e1 ", change(Some("e1"), None))]
+    #[case(
+        r#"{"environment_id": "e1", "collection_id": "c1", "event": "update"}"#,
+        change(Some("e1"), Some("c1"))
+    )]
+    #[case(r#"{"collection_id": "c1"}"#, change(None, Some("c1")))]
+    #[case(r#"{"collection_id": 1}"#, change(None, None))]
+    #[case("", change(None, None))]
+    #[case("configuration updated", change(None, None))]
+    fn test_parse(#[case] text: &str, #[case] expected: WebsocketMessage) {
+        assert_eq!(WebsocketMessage::parse(text), expected);
+    }
+
+    #[test]
+    fn test_parse_resource() {
+        let resource = |text| match WebsocketMessage::parse(text) {
+            WebsocketMessage::ConfigurationChanged(change) => change.resource(),
+            WebsocketMessage::Heartbeat => unreachable!(),
+        };
+
+        assert_eq!(
+            resource("environment_id:e1;collection_id:c1;feature_id:f1").as_deref(),
+            Some("feature 'f1'")
+        );
+        assert_eq!(
+            resource(r#"{"environment_id": "e1", "property_id": "p1"}"#).as_deref(),
+            Some("property 'p1'")
+        );
+        assert_eq!(resource("environment_id:e1;collection_id:c1"), None);
+    }
+}
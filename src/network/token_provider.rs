@@ -12,16 +12,32 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::path::{Path, PathBuf};
 use std::{cmp::max, collections::HashMap, sync::RwLock};
 
 use super::{NetworkError, NetworkResult};
 use crate::network::http_client::SDK_USER_AGENT;
+use crate::utils::RwLockExt;
 use reqwest::blocking::{Client, ClientBuilder};
 use reqwest::header::{ACCEPT, HeaderMap, HeaderValue, USER_AGENT};
 use serde::Deserialize;
 
 pub trait TokenProvider: std::fmt::Debug + Send + Sync {
     fn get_access_token(&self) -> NetworkResult<String>;
+
+    /// Discards the cached access token (e.g. because the server rejected it),
+    /// so the next call to [`TokenProvider::get_access_token`] requests a new one.
+    fn invalidate_access_token(&self) {}
+}
+
+impl<T: TokenProvider + ?Sized> TokenProvider for Box<T> {
+    fn get_access_token(&self) -> NetworkResult<String> {
+        (**self).get_access_token()
+    }
+
+    fn invalidate_access_token(&self) {
+        (**self).invalidate_access_token()
+    }
 }
 
 #[derive(Debug, Default)]
@@ -30,15 +46,26 @@ struct AccessToken {
     expiration: u64,
 }
 
+/// Seconds since the epoch, or 0 if the system clock is set before it.
+fn now_secs() -> u64 {
+    std::time::UNIX_EPOCH
+        .elapsed()
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
 impl AccessToken {
     fn expired(&self) -> bool {
-        let now = std::time::UNIX_EPOCH.elapsed().unwrap().as_secs();
-        now >= self.expiration
+        now_secs() >= self.expiration
+    }
+
+    fn invalidate(&mut self) {
+        self.expiration = 0;
     }
 
     fn renew(&mut self, token: String, expires_in: u64) -> u64 {
         self.token = token;
-        self.expiration = std::time::UNIX_EPOCH.elapsed().unwrap().as_secs() + expires_in;
+        self.expiration = now_secs().saturating_add(expires_in);
         self.expiration
     }
 }
@@ -60,7 +87,7 @@ impl TokenProviderImpl {
     }
 
     fn expired(&self) -> bool {
-        self.access_token.read().map_or(true, |t| t.expired())
+        self.access_token.read_unpoisoned().expired()
     }
 
     fn safe_expires_in(expires_in: u64) -> u64 {
@@ -71,20 +98,6 @@ impl TokenProviderImpl {
         }
     }
 
-    fn build_http_client() -> NetworkResult<Client> {
-        ClientBuilder::new()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .map_err(NetworkError::ReqwestError)
-    }
-
-    fn build_default_headers() -> HeaderMap {
-        let mut headers = HeaderMap::new();
-        headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
-        headers.insert(USER_AGENT, HeaderValue::from_static(SDK_USER_AGENT));
-        headers
-    }
-
     // Renews the stored token.
     //
     // It will use 90% of the expires_in value returned by the server
@@ -100,30 +113,151 @@ impl TokenProviderImpl {
         );
         form_data.insert("apikey".to_string(), self.apikey.to_string());
 
-        let client = Self::build_http_client()?;
-        let new_token = client
-            .post(&self.endpoint)
-            .headers(Self::build_default_headers())
-            .form(&form_data)
-            .send()
-            .and_then(|response| response.error_for_status())
-            .map_err(|error| {
-                NetworkError::TokenProviderError(format!(
-                    "Failed to get authentication token for websocket connect. Error {error}"
-                ))
-            })?
-            .json::<AccessTokenResponse>()
-            .map_err(|error| {
-                if error.is_decode() {
-                    NetworkError::DeserializationError(format!(
-                        "Failed to deserialize authentication token response. Error {error}"
+        let new_token = request_token(&self.endpoint, &form_data)?;
+
+        let mut access_token = self.access_token.write_unpoisoned();
+        Ok(access_token.renew(
+            new_token.access_token,
+            TokenProviderImpl::safe_expires_in(new_token.expires_in),
+        ))
+    }
+}
+
+fn build_http_client() -> NetworkResult<Client> {
+    ClientBuilder::new()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .map_err(NetworkError::ReqwestError)
+}
+
+fn build_default_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+    headers.insert(USER_AGENT, HeaderValue::from_static(SDK_USER_AGENT));
+    headers
+}
+
+/// Posts the given grant to the IAM token endpoint and parses the response.
+fn request_token(
+    endpoint: &str,
+    form_data: &HashMap<String, String>,
+) -> NetworkResult<AccessTokenResponse> {
+    let client = build_http_client()?;
+    let response = client
+        .post(endpoint)
+        .headers(build_default_headers())
+        .form(form_data)
+        .send()?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(NetworkError::TokenRequestRejected {
+            status_code: status.as_u16(),
+            message: iam_error_message(&response.text().unwrap_or_default()),
+        });
+    }
+    response.json::<AccessTokenResponse>().map_err(|error| {
+        if error.is_decode() {
+            NetworkError::DeserializationError(format!(
+                "Failed to deserialize authentication token response. Error {error}"
+            ))
+        } else {
+            NetworkError::ReqwestError(error)
+        }
+    })
+}
+
+/// IAM explains the errors in the `errorMessage` field of a JSON body.
+fn iam_error_message(body: &str) -> String {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct IamError {
+        error_message: String,
+    }
+    serde_json::from_str::<IamError>(body)
+        .map(|error| error.error_message)
+        .unwrap_or_else(|_| body.trim().to_string())
+}
+
+/// Locations where IKS and Code Engine mount the compute resource token, in lookup order.
+const DEFAULT_CR_TOKEN_FILES: [&str; 3] = [
+    "/var/run/secrets/tokens/vault-token",
+    "/var/run/secrets/tokens/sa-token",
+    "/var/run/secrets/codeengine.cloud.ibm.com/compute-resource-token/token",
+];
+
+/// Authenticates using an IBM Cloud trusted profile.
+///
+/// The compute resource (CR) token mounted by the platform is exchanged for an IAM
+/// access token. The file is read again on every renewal because the platform
+/// rotates it periodically.
+#[derive(Debug)]
+pub(crate) struct TrustedProfileTokenProviderImpl {
+    pub(crate) endpoint: String,
+    profile_id: String,
+    cr_token_file: Option<PathBuf>,
+    access_token: RwLock<AccessToken>,
+}
+
+impl TrustedProfileTokenProviderImpl {
+    /// When `cr_token_file` is `None` the default IKS / Code Engine locations are tried.
+    pub fn new(profile_id: &str, cr_token_file: Option<PathBuf>, endpoint: &str) -> Self {
+        Self {
+            endpoint: endpoint.to_owned(),
+            profile_id: profile_id.to_string(),
+            cr_token_file,
+            access_token: RwLock::default(),
+        }
+    }
+
+    fn expired(&self) -> bool {
+        self.access_token.read_unpoisoned().expired()
+    }
+
+    fn read_cr_token(&self) -> NetworkResult<String> {
+        match &self.cr_token_file {
+            Some(path) => Self::read_cr_token_file(path),
+            None => DEFAULT_CR_TOKEN_FILES
+                .iter()
+                .map(Path::new)
+                .find(|path| path.exists())
+                .ok_or_else(|| {
+                    NetworkError::TokenProviderError(format!(
+                        "No compute resource token found in any of {DEFAULT_CR_TOKEN_FILES:?}"
                     ))
-                } else {
-                    NetworkError::ReqwestError(error)
-                }
-            })?;
+                })
+                .and_then(Self::read_cr_token_file),
+        }
+    }
 
-        let mut access_token = self.access_token.write()?;
+    fn read_cr_token_file(path: &Path) -> NetworkResult<String> {
+        let token = std::fs::read_to_string(path).map_err(|e| {
+            NetworkError::TokenProviderError(format!(
+                "Failed to read compute resource token from '{}': {e}",
+                path.display()
+            ))
+        })?;
+        let token = token.trim();
+        if token.is_empty() {
+            return Err(NetworkError::TokenProviderError(format!(
+                "Compute resource token file '{}' is empty",
+                path.display()
+            )));
+        }
+        Ok(token.to_string())
+    }
+
+    fn renew_token(&self) -> NetworkResult<u64> {
+        let mut form_data = HashMap::new();
+        form_data.insert(
+            "grant_type".to_string(),
+            "urn:ibm:params:oauth:grant-type:cr-token".to_string(),
+        );
+        form_data.insert("cr_token".to_string(), self.read_cr_token()?);
+        form_data.insert("profile_id".to_string(), self.profile_id.clone());
+
+        let new_token = request_token(&self.endpoint, &form_data)?;
+
+        let mut access_token = self.access_token.write_unpoisoned();
         Ok(access_token.renew(
             new_token.access_token,
             TokenProviderImpl::safe_expires_in(new_token.expires_in),
@@ -131,6 +265,19 @@ impl TokenProviderImpl {
     }
 }
 
+impl TokenProvider for TrustedProfileTokenProviderImpl {
+    fn get_access_token(&self) -> NetworkResult<String> {
+        if self.expired() {
+            self.renew_token()?;
+        }
+
+        Ok(self.access_token.read_unpoisoned().token.to_string())
+    }
+    fn invalidate_access_token(&self) {
+        self.access_token.write_unpoisoned().invalidate();
+    }
+}
+
 #[derive(Deserialize)]
 struct AccessTokenResponse {
     access_token: String,
@@ -148,7 +295,10 @@ impl TokenProvider for TokenProviderImpl {
             self.renew_token()?;
         }
 
-        Ok(self.access_token.read()?.token.to_string())
+        Ok(self.access_token.read_unpoisoned().token.to_string())
+    }
+    fn invalidate_access_token(&self) {
+        self.access_token.write_unpoisoned().invalidate();
     }
 }
 
@@ -241,4 +391,53 @@ mod tests {
         mock.assert();
         assert_eq!(token.unwrap(), "the-new-token");
     }
+
+    #[test]
+    fn test_trusted_profile_token_provider_renew_call() {
+        let endpoint = "/give/me/a/token";
+        let cr_token_file =
+            std::env::temp_dir().join(format!("appconfiguration-cr-token-{}", std::process::id()));
+        std::fs::write(&cr_token_file, "the-cr-token\n").unwrap();
+
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path(endpoint)
+                .form_urlencoded_tuple("grant_type", "urn:ibm:params:oauth:grant-type:cr-token")
+                .form_urlencoded_tuple("cr_token", "the-cr-token")
+                .form_urlencoded_tuple("profile_id", "Profile-1234");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(serde_json::json!(
+                    {
+                    "access_token": "the-new-token",
+                    "expires_in": 60
+                    }
+                ));
+        });
+
+        let token_provider = TrustedProfileTokenProviderImpl::new(
+            "Profile-1234",
+            Some(cr_token_file.clone()),
+            &std::format!("http://{}:{}{}", server.host(), server.port(), endpoint),
+        );
+        let token = token_provider.get_access_token();
+        std::fs::remove_file(&cr_token_file).unwrap();
+
+        mock.assert();
+        assert_eq!(token.unwrap(), "the-new-token");
+    }
+
+    #[test]
+    fn test_trusted_profile_token_provider_missing_cr_token() {
+        let token_provider = TrustedProfileTokenProviderImpl::new(
+            "Profile-1234",
+            Some(PathBuf::from("/does/for/sure/not/exist")),
+            "<endpoint>",
+        );
+        assert!(matches!(
+            token_provider.get_access_token().unwrap_err(),
+            NetworkError::TokenProviderError(msg) if msg.contains("/does/for/sure/not/exist")
+        ));
+    }
 }
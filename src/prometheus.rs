@@ -0,0 +1,240 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Metrics of a client in the [Prometheus text format], to be served from the
+//! `/metrics` endpoint of the application:
+//!
+//! ```ignore
+//! async fn metrics(State(client): State<Arc<AppConfigurationClientIBMCloud>>) -> String {
+//!     ibm_appconfiguration_rust_sdk::prometheus::metrics_text(client.as_ref()).unwrap_or_default()
+//! }
+//! ```
+//!
+//! | Metric | Type | |
+//! |---|---|---|
+//! | `appconfiguration_sdk_mode{mode}` | gauge | `1` for the current mode: `online`, `offline` or `defunct` |
+//! | `appconfiguration_sdk_seconds_since_last_refresh` | gauge | Age of the last configuration received |
+//! | `appconfiguration_sdk_websocket_reconnects_total` | counter | |
+//! | `appconfiguration_sdk_evaluations_total` | counter | |
+//! | `appconfiguration_sdk_metering_queue_depth` | gauge | Evaluations not sent to the server yet |
+//! | `appconfiguration_sdk_metering_flush_failures_total` | counter | |
+//! | `appconfiguration_sdk_metering_dropped_evaluations_total` | counter | Evaluations not metered because the metering thread fell behind |
+//! | `appconfiguration_sdk_fetch_budget_exhaustions_total` | counter | Fetches postponed by `max_fetches_per_hour` |
+//! | `appconfiguration_sdk_fetch_latency_seconds` | histogram | Latency of the downloads of the configuration, failed ones included |
+//!
+//! Clients without a connection to the server, like
+//! [`AppConfigurationOffline`](crate::AppConfigurationOffline), report no metrics.
+//!
+//! [Prometheus text format]: https://prometheus.io/docs/instrumenting/exposition_formats/
+
+use std::fmt::Write;
+
+use crate::errors::Result;
+use crate::{ConfigurationProvider, RuntimeMode, RuntimeStatus, SdkStats};
+
+/// Returns the metrics of `client` in the Prometheus text format.
+pub fn metrics_text(client: &(impl ConfigurationProvider + ?Sized)) -> Result<String> {
+    Ok(render(
+        client.get_runtime_status()?,
+        client.sdk_stats()?,
+        chrono::Utc::now(),
+    ))
+}
+
+fn render(
+    status: Option<RuntimeStatus>,
+    stats: Option<SdkStats>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> String {
+    let mut text = String::new();
+    if let Some(mode) = status.and_then(|status| status.mode) {
+        header(&mut text, "mode", "gauge", "Current mode of the client.");
+        for (label, value) in [
+            ("online", RuntimeMode::Online),
+            ("offline", RuntimeMode::Offline),
+            ("defunct", RuntimeMode::Defunct),
+        ] {
+            let _ = writeln!(
+                text,
+                "appconfiguration_sdk_mode{{mode=\"{label}\"}} {}",
+                u8::from(mode == value)
+            );
+        }
+    }
+    let Some(stats) = stats else {
+        return text;
+    };
+    if let Some(last_refresh) = stats.last_refresh {
+        let age = (now - last_refresh).num_milliseconds().max(0) as f64 / 1000.0;
+        metric(
+            &mut text,
+            "seconds_since_last_refresh",
+            "gauge",
+            "Seconds since a configuration was last received from the server.",
+            age,
+        );
+    }
+    metric(
+        &mut text,
+        "websocket_reconnects_total",
+        "counter",
+        "Websocket connections established after the first one.",
+        stats.websocket_reconnects,
+    );
+    metric(
+        &mut text,
+        "evaluations_total",
+        "counter",
+        "Evaluations of features and properties.",
+        stats.evaluations,
+    );
+    metric(
+        &mut text,
+        "metering_queue_depth",
+        "gauge",
+        "Evaluations recorded for metering and not sent to the server yet.",
+        stats.metering_queue_depth,
+    );
+    metric(
+        &mut text,
+        "metering_flush_failures_total",
+        "counter",
+        "Transmissions of metering data that failed.",
+        stats.metering_flush_failures,
+    );
+    metric(
+        &mut text,
+        "metering_dropped_evaluations_total",
+        "counter",
+        "Evaluations not recorded for metering because the metering thread fell behind.",
+        stats.metering_dropped_evaluations,
+    );
+    metric(
+        &mut text,
+        "fetch_budget_exhaustions_total",
+        "counter",
+        "Configuration fetches postponed because the maximum per hour was reached.",
+        stats.fetch_budget_exhaustions,
+    );
+    header(
+        &mut text,
+        "fetch_latency_seconds",
+        "histogram",
+        "Latency of the downloads of the configuration.",
+    );
+    let latency = &stats.fetch_latency;
+    for (bound, count) in &latency.buckets {
+        let _ = writeln!(
+            text,
+            "appconfiguration_sdk_fetch_latency_seconds_bucket{{le=\"{}\"}} {count}",
+            bound.as_secs_f64()
+        );
+    }
+    let _ = writeln!(
+        text,
+        "appconfiguration_sdk_fetch_latency_seconds_bucket{{le=\"+Inf\"}} {}",
+        latency.count
+    );
+    let _ = writeln!(
+        text,
+        "appconfiguration_sdk_fetch_latency_seconds_sum {}",
+        latency.sum.as_secs_f64()
+    );
+    let _ = writeln!(
+        text,
+        "appconfiguration_sdk_fetch_latency_seconds_count {}",
+        latency.count
+    );
+    text
+}
+
+fn header(text: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(text, "# HELP appconfiguration_sdk_{name} {help}");
+    let _ = writeln!(text, "# TYPE appconfiguration_sdk_{name} {kind}");
+}
+
+fn metric(text: &mut String, name: &str, kind: &str, help: &str, value: impl std::fmt::Display) {
+    header(text, name, kind, help);
+    let _ = writeln!(text, "appconfiguration_sdk_{name} {value}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::serialization::fixtures::example_configuration_enterprise_path;
+    use crate::{AppConfigurationOffline, LatencyHistogram};
+
+    #[test]
+    fn test_render() {
+        let now = chrono::Utc::now();
+        let status = RuntimeStatus {
+            is_connected: true,
+            mode: Some(RuntimeMode::Online),
+            offline_reason: None,
+        };
+        let stats = SdkStats {
+            websocket_reconnects: 2,
+            last_refresh: Some(now - chrono::Duration::milliseconds(1500)),
+            evaluations: 10,
+            metering_queue_depth: 3,
+            metering_flush_failures: 1,
+            metering_dropped_evaluations: 5,
+            fetch_budget_exhaustions: 4,
+            fetch_latency: LatencyHistogram {
+                buckets: vec![
+                    (std::time::Duration::from_millis(100), 1),
+                    (std::time::Duration::from_secs(1), 2),
+                ],
+                count: 3,
+                sum: std::time::Duration::from_millis(2550),
+            },
+        };
+
+        let text = render(Some(status), Some(stats), now);
+        let samples: Vec<&str> = text.lines().filter(|l| !l.starts_with('#')).collect();
+        assert_eq!(
+            samples,
+            [
+                "appconfiguration_sdk_mode{mode=\"online\"} 1",
+                "appconfiguration_sdk_mode{mode=\"offline\"} 0",
+                "appconfiguration_sdk_mode{mode=\"defunct\"} 0",
+                "appconfiguration_sdk_seconds_since_last_refresh 1.5",
+                "appconfiguration_sdk_websocket_reconnects_total 2",
+                "appconfiguration_sdk_evaluations_total 10",
+                "appconfiguration_sdk_metering_queue_depth 3",
+                "appconfiguration_sdk_metering_flush_failures_total 1",
+                "appconfiguration_sdk_metering_dropped_evaluations_total 5",
+                "appconfiguration_sdk_fetch_budget_exhaustions_total 4",
+                "appconfiguration_sdk_fetch_latency_seconds_bucket{le=\"0.1\"} 1",
+                "appconfiguration_sdk_fetch_latency_seconds_bucket{le=\"1\"} 2",
+                "appconfiguration_sdk_fetch_latency_seconds_bucket{le=\"+Inf\"} 3",
+                "appconfiguration_sdk_fetch_latency_seconds_sum 2.55",
+                "appconfiguration_sdk_fetch_latency_seconds_count 3",
+            ]
+        );
+        assert!(text.contains("# TYPE appconfiguration_sdk_fetch_latency_seconds histogram\n"));
+        assert!(text.contains("# TYPE appconfiguration_sdk_evaluations_total counter\n"));
+    }
+
+    #[test]
+    fn test_offline_client_has_no_metrics() {
+        let client = AppConfigurationOffline::new(
+            &example_configuration_enterprise_path(),
+            "dev",
+            "blue-charge",
+        )
+        .unwrap();
+        assert_eq!(metrics_text(&client).unwrap(), "");
+    }
+}
@@ -25,8 +25,6 @@ pub trait Property {
 
     fn get_property_data_format(&self) -> Result<Option<String>>;
 
-    fn get_current_value(&self, entity: &impl Entity) -> Result<PropertyEvaluationResult>;
-
     /// Evaluates a property for the given [`Entity`] and returns a [`Value`].
     ///
     /// Use the methods available in [`Value`] to return the actual primitive value. If
@@ -47,10 +45,47 @@ pub trait Property {
     ///         Value::Int64(v) => println!("i64 with value {v}"),
     ///         Value::String(v) => println!("String with value {v}"),
     ///         Value::Boolean(v) => println!("bool with value {v}"),
+    ///         Value::List(v) => println!("list of {} values", v.len()),
+    ///         other => println!("other value {other}"),
     ///     }
     /// #   Ok(())
     /// # }
     /// ```
+    fn get_current_value(
+        &self,
+        entity: &(impl Entity + ?Sized),
+    ) -> Result<PropertyEvaluationResult>;
+
+    /// Returns the value of the property without evaluating it for an entity,
+    /// for properties holding service-wide settings.
+    ///
+    /// Targeting rules are ignored and the default value of the property is
+    /// returned, unless `strict` is set: then properties with targeting rules
+    /// fail with [`Error::PropertyHasTargetingRules`](crate::Error::PropertyHasTargetingRules).
+    /// Local overrides still apply. No usage is recorded, and placeholders in
+    /// String values are not substituted as there is no entity.
+    ///
+    /// The properties of the SDK implement it. Other implementations of this
+    /// trait fail by default, as they cannot tell their default value apart.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ibm_appconfiguration_rust_sdk::{AppConfigurationClient, Property, Result};
+    /// # fn doctest_get_static_value(client: impl AppConfigurationClient) -> Result<()> {
+    ///     let timeout: u64 = client.get_property("timeout_seconds")?
+    ///         .get_static_value(true)?
+    ///         .try_into()?;
+    /// #   Ok(())
+    /// # }
+    /// ```
+    fn get_static_value(&self, strict: bool) -> Result<Value> {
+        let _ = strict;
+        Err(crate::Error::Other(format!(
+            "Property '{}' does not support static values",
+            self.get_property_id()?
+        )))
+    }
 
     /// Evaluates a property for the given [`Entity`] and returns its value converted (if possible)
     /// to the given type.
@@ -72,7 +107,7 @@ pub trait Property {
     /// ```
     fn get_value_into<T: TryFrom<Value, Error = crate::Error>>(
         &self,
-        entity: &impl Entity,
+        entity: &(impl Entity + ?Sized),
     ) -> Result<T> {
         let value = self.get_current_value(entity).map(|r| r.value)?;
         value.try_into()
@@ -0,0 +1,251 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! [`DistributedCache`] storing the configurations in Redis, to share them
+//! between the clients of a fleet:
+//!
+//! ```ignore
+//! let cache = RedisCache::new(redis::Client::open("redis://cache:6379")?)
+//!     .with_ttl(Duration::from_secs(24 * 60 * 60));
+//! let options = LiveConfigurationOptions {
+//!     shared_cache: Some(SharedCache::new(cache, Duration::from_secs(60))),
+//!     ..Default::default()
+//! };
+//! ```
+//!
+//! Connecting, reading and writing time out after 5 seconds by default, see
+//! [`RedisCache::with_timeout`], so an unreachable server does not block the
+//! updates. With a [`CacheCipher`](crate::CacheCipher) in the options, the
+//! payloads are stored encrypted.
+//!
+//! Each configuration is a hash, at `appconfiguration:{guid}:{environment_id}:{collection_id}`
+//! by default, with the fields `etag`, `fetched_at` (milliseconds since the
+//! epoch) and `payload`.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::{CachedConfiguration, ConfigurationId, DistributedCache};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Stores the configurations in Redis, see the [module](self) documentation.
+#[derive(Debug, Clone)]
+pub struct RedisCache {
+    client: redis::Client,
+    key_prefix: String,
+    ttl: Option<Duration>,
+    timeout: Duration,
+}
+
+impl RedisCache {
+    /// Stores the configurations without expiration, under the
+    /// `appconfiguration:` prefix. A connection is opened for each request.
+    pub fn new(client: redis::Client) -> Self {
+        Self {
+            client,
+            key_prefix: "appconfiguration:".to_string(),
+            ttl: None,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Prefixes the keys with `prefix` instead of `appconfiguration:`.
+    pub fn with_key_prefix(mut self, prefix: &str) -> Self {
+        self.key_prefix = prefix.to_string();
+        self
+    }
+
+    /// Expires the configurations `ttl` after they are stored.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Gives up connecting, and waiting for each read or write, after
+    /// `timeout` instead of 5 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn connect(&self) -> Result<redis::Connection, String> {
+        let connection = self
+            .client
+            .get_connection_with_timeout(self.timeout)
+            .map_err(|e| e.to_string())?;
+        connection
+            .set_read_timeout(Some(self.timeout))
+            .and_then(|()| connection.set_write_timeout(Some(self.timeout)))
+            .map_err(|e| e.to_string())?;
+        Ok(connection)
+    }
+
+    fn key(&self, configuration_id: &ConfigurationId) -> String {
+        format!(
+            "{}{}:{}:{}",
+            self.key_prefix,
+            configuration_id.guid,
+            configuration_id.environment_id,
+            configuration_id.collection_id
+        )
+    }
+}
+
+/// Reads a configuration from the fields of its hash.
+fn parse_fields(mut fields: HashMap<String, Vec<u8>>) -> Result<CachedConfiguration, String> {
+    let mut field = |name: &str| {
+        fields
+            .remove(name)
+            .ok_or_else(|| format!("Missing field '{name}'"))
+    };
+    let etag = String::from_utf8(field("etag")?).map_err(|e| e.to_string())?;
+    let fetched_at = String::from_utf8(field("fetched_at")?)
+        .ok()
+        .and_then(|millis| millis.parse().ok())
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .ok_or("Invalid field 'fetched_at'")?;
+    Ok(CachedConfiguration {
+        etag,
+        fetched_at,
+        payload: field("payload")?,
+    })
+}
+
+impl DistributedCache for RedisCache {
+    fn get(
+        &self,
+        configuration_id: &ConfigurationId,
+    ) -> Result<Option<CachedConfiguration>, String> {
+        let mut connection = self.connect()?;
+        let fields: HashMap<String, Vec<u8>> = redis::cmd("HGETALL")
+            .arg(self.key(configuration_id))
+            .query(&mut connection)
+            .map_err(|e| e.to_string())?;
+        if fields.is_empty() {
+            return Ok(None);
+        }
+        parse_fields(fields).map(Some)
+    }
+
+    fn put(
+        &self,
+        configuration_id: &ConfigurationId,
+        configuration: &CachedConfiguration,
+    ) -> Result<(), String> {
+        let mut connection = self.connect()?;
+        let key = self.key(configuration_id);
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .cmd("HSET")
+            .arg(&key)
+            .arg("etag")
+            .arg(&configuration.etag)
+            .arg("fetched_at")
+            .arg(configuration.fetched_at.timestamp_millis())
+            .arg("payload")
+            .arg(&configuration.payload)
+            .ignore();
+        if let Some(ttl) = self.ttl {
+            pipe.cmd("PEXPIRE")
+                .arg(&key)
+                .arg(ttl.as_millis() as u64)
+                .ignore();
+        }
+        pipe.query::<()>(&mut connection).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(entries: &[(&str, &[u8])]) -> HashMap<String, Vec<u8>> {
+        entries
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn test_key() {
+        let cache = RedisCache::new(redis::Client::open("redis://localhost").unwrap());
+        let configuration_id =
+            ConfigurationId::new("guid".into(), "dev".into(), "collection".into()).unwrap();
+        assert_eq!(
+            cache.key(&configuration_id),
+            "appconfiguration:guid:dev:collection"
+        );
+
+        let cache = cache.with_key_prefix("fleet:");
+        assert_eq!(cache.key(&configuration_id), "fleet:guid:dev:collection");
+    }
+
+    #[test]
+    fn test_parse_fields() {
+        let configuration = parse_fields(fields(&[
+            ("etag", b"\"abc\""),
+            ("fetched_at", b"1700000000000"),
+            ("payload", b"{}"),
+        ]))
+        .unwrap();
+        assert_eq!(
+            configuration,
+            CachedConfiguration {
+                etag: "\"abc\"".to_string(),
+                fetched_at: chrono::DateTime::from_timestamp_millis(1_700_000_000_000).unwrap(),
+                payload: b"{}".to_vec(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_fields_missing() {
+        let error = parse_fields(fields(&[
+            ("fetched_at", b"1700000000000"),
+            ("payload", b"{}"),
+        ]))
+        .unwrap_err();
+        assert_eq!(error, "Missing field 'etag'");
+
+        let error = parse_fields(fields(&[("etag", b"abc"), ("payload", b"{}")])).unwrap_err();
+        assert_eq!(error, "Missing field 'fetched_at'");
+
+        let error = parse_fields(fields(&[
+            ("etag", b"abc"),
+            ("fetched_at", b"1700000000000"),
+        ]))
+        .unwrap_err();
+        assert_eq!(error, "Missing field 'payload'");
+    }
+
+    #[test]
+    fn test_parse_fields_invalid() {
+        let error = parse_fields(fields(&[
+            ("etag", b"abc"),
+            ("fetched_at", b"yesterday"),
+            ("payload", b"{}"),
+        ]))
+        .unwrap_err();
+        assert_eq!(error, "Invalid field 'fetched_at'");
+
+        let error = parse_fields(fields(&[
+            ("etag", b"\xff"),
+            ("fetched_at", b"1700000000000"),
+            ("payload", b"{}"),
+        ]))
+        .unwrap_err();
+        assert!(error.contains("utf-8"), "{error}");
+    }
+}
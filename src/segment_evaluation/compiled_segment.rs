@@ -0,0 +1,140 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::errors::ConfigurationDataError;
+use crate::network::serialization::{Rule, Segment};
+use crate::segment_evaluation::rule_operator::{Operator, RuleValue};
+
+/// A [`Segment`] with its rules prepared for evaluation: operators are parsed
+/// and rule values converted when the configuration is loaded instead of on
+/// every evaluation.
+///
+/// A [`Configuration`](crate::Configuration) compiles each segment once and
+/// shares it (`Arc<CompiledSegment>`) with all the features and properties that
+/// target it.
+#[derive(Debug)]
+pub(crate) struct CompiledSegment {
+    pub(crate) segment: Segment,
+    pub(crate) rules: Vec<CompiledRule>,
+    /// `false` if the rules could not be compiled, see [`CompiledSegment::unsupported`].
+    pub(crate) supported: bool,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct CompiledRule {
+    pub(crate) attribute_name: String,
+    pub(crate) operator: Operator,
+    pub(crate) values: Vec<RuleValue>,
+}
+
+impl TryFrom<Segment> for CompiledSegment {
+    type Error = ConfigurationDataError;
+
+    /// Fails if any of the rules uses an unknown operator.
+    fn try_from(segment: Segment) -> std::result::Result<Self, Self::Error> {
+        let rules = segment
+            .rules
+            .iter()
+            .map(|rule| CompiledRule::compile(&segment, rule))
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(Self {
+            segment,
+            rules,
+            supported: true,
+        })
+    }
+}
+
+impl CompiledSegment {
+    /// A segment whose rules use operators unknown to this version of the SDK.
+    /// It is kept so the features and properties targeting it still load, but
+    /// no entity belongs to it and the segment lists excluding it never apply.
+    pub(crate) fn unsupported(segment: Segment) -> Self {
+        Self {
+            segment,
+            rules: Vec::new(),
+            supported: false,
+        }
+    }
+}
+
+// Compiled data is derived from `segment`, comparing it is enough.
+impl PartialEq for CompiledSegment {
+    fn eq(&self, other: &Self) -> bool {
+        self.segment == other.segment
+    }
+}
+
+impl Eq for CompiledSegment {}
+
+impl CompiledRule {
+    fn compile(
+        segment: &Segment,
+        rule: &Rule,
+    ) -> std::result::Result<Self, ConfigurationDataError> {
+        let operator = rule.operator.parse().map_err(|_| {
+            ConfigurationDataError::UnknownSegmentRuleOperator {
+                segment_id: segment.segment_id.clone(),
+                operator: rule.operator.clone(),
+            }
+        })?;
+        Ok(Self {
+            attribute_name: rule.attribute_name.clone(),
+            operator,
+            values: rule.values.iter().map(|v| v.as_str().into()).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(operator: &str) -> Segment {
+        Segment {
+            name: "".into(),
+            segment_id: "some_segment_id".into(),
+            description: None,
+            tags: None,
+            rules: vec![Rule {
+                attribute_name: "age".into(),
+                operator: operator.into(),
+                values: vec!["42".into()],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_compile_segment() {
+        let compiled = CompiledSegment::try_from(segment("greaterThanEquals")).unwrap();
+        assert_eq!(compiled.rules.len(), 1);
+        assert_eq!(compiled.rules[0].attribute_name, "age");
+        assert_eq!(compiled.rules[0].operator, Operator::GreaterThanEquals);
+        assert_eq!(compiled.rules[0].values[0].raw, "42");
+    }
+
+    #[test]
+    fn test_compile_segment_unknown_operator() {
+        let error = CompiledSegment::try_from(segment("between")).unwrap_err();
+        assert!(matches!(
+            error,
+            ConfigurationDataError::UnknownSegmentRuleOperator { segment_id, operator }
+                if segment_id == "some_segment_id" && operator == "between"
+        ));
+
+        let unsupported = CompiledSegment::unsupported(segment("between"));
+        assert!(!unsupported.supported);
+        assert!(unsupported.rules.is_empty());
+    }
+}
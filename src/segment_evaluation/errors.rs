@@ -14,7 +14,7 @@
 
 use thiserror::Error;
 
-use crate::network::serialization::{Rule, Segment};
+use crate::segment_evaluation::compiled_segment::{CompiledRule, CompiledSegment};
 
 #[derive(Debug, Error)]
 pub(crate) enum SegmentEvaluationError {
@@ -41,14 +41,28 @@ pub(crate) struct SegmentEvaluationErrorKind {
     pub(crate) source: CheckOperatorErrorDetail,
 }
 
-impl From<(CheckOperatorErrorDetail, &Segment, &Rule, String)> for SegmentEvaluationError {
-    fn from(value: (CheckOperatorErrorDetail, &Segment, &Rule, String)) -> Self {
+impl
+    From<(
+        CheckOperatorErrorDetail,
+        &CompiledSegment,
+        &CompiledRule,
+        String,
+    )> for SegmentEvaluationError
+{
+    fn from(
+        value: (
+            CheckOperatorErrorDetail,
+            &CompiledSegment,
+            &CompiledRule,
+            String,
+        ),
+    ) -> Self {
         let (source, segment, segment_rule, value) = value;
         Self::SegmentEvaluationFailed(SegmentEvaluationErrorKind {
-            segment_id: segment.segment_id.clone(),
+            segment_id: segment.segment.segment_id.clone(),
             segment_rule_attribute_name: segment_rule.attribute_name.clone(),
-            segment_rule_operator: segment_rule.operator.clone(),
-            value: value,
+            segment_rule_operator: segment_rule.operator.to_string(),
+            value,
             source,
         })
     }
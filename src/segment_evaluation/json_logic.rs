@@ -0,0 +1,182 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conversion of segments to [JsonLogic](https://jsonlogic.com) conditions, as
+//! understood by flagd and other OpenFeature evaluation engines.
+
+use serde_json::{Value as JsonValue, json};
+
+use super::ENTITY_ID_ATTRIBUTE;
+use super::compiled_segment::{CompiledRule, CompiledSegment};
+use super::rule_operator::{Operator, RuleValue};
+
+/// Context attribute flagd uses for the ID of the evaluated entity.
+const TARGETING_KEY: &str = "targetingKey";
+
+impl CompiledSegment {
+    /// Returns a condition that holds if all the rules of the segment match,
+    /// never for unsupported segments.
+    pub(crate) fn to_json_logic(&self) -> JsonValue {
+        if !self.supported {
+            return JsonValue::Bool(false);
+        }
+        json!({ "and": self.rules.iter().map(CompiledRule::to_json_logic).collect::<Vec<_>>() })
+    }
+}
+
+impl CompiledRule {
+    /// Like in [`MatchesAttributes`](super::MatchesAttributes), the attribute
+    /// must be present and match ANY of the values, or ALL of them for negations.
+    fn to_json_logic(&self) -> JsonValue {
+        let attribute = if self.attribute_name == ENTITY_ID_ATTRIBUTE {
+            TARGETING_KEY
+        } else {
+            self.attribute_name.as_str()
+        };
+        let var = json!({ "var": attribute });
+        let checks: Vec<_> = self
+            .values
+            .iter()
+            .map(|value| check(self.operator, &var, value))
+            .collect();
+        let combinator = if self.operator.is_negation() {
+            "and"
+        } else {
+            "or"
+        };
+        json!({ "and": [{ "!=": [var, null] }, { combinator: checks }] })
+    }
+}
+
+fn check(operator: Operator, var: &JsonValue, value: &RuleValue) -> JsonValue {
+    let raw = &value.raw;
+    match operator {
+        Operator::Is => equals(var, raw),
+        Operator::IsNot => json!({ "!": [equals(var, raw)] }),
+        Operator::Contains => json!({ "in": [raw, var] }),
+        Operator::NotContains => json!({ "!": [{ "in": [raw, var] }] }),
+        Operator::StartsWith => json!({ "starts_with": [var, raw] }),
+        Operator::NotStartsWith => json!({ "!": [{ "starts_with": [var, raw] }] }),
+        Operator::EndsWith => json!({ "ends_with": [var, raw] }),
+        Operator::NotEndsWith => json!({ "!": [{ "ends_with": [var, raw] }] }),
+        Operator::GreaterThan => json!({ ">": [var, number(raw)] }),
+        Operator::LesserThan => json!({ "<": [var, number(raw)] }),
+        Operator::GreaterThanEquals => json!({ ">=": [var, number(raw)] }),
+        Operator::LesserThanEquals => json!({ "<=": [var, number(raw)] }),
+    }
+}
+
+// Rule values are always strings in the configuration. Like in
+// `RuleOperator::is`, string attributes are compared to them as written, and
+// boolean and numeric attributes to their parsed value.
+fn equals(var: &JsonValue, raw: &str) -> JsonValue {
+    let string = json!({ "===": [var, raw] });
+    let typed = raw
+        .parse::<bool>()
+        .map(JsonValue::Bool)
+        .ok()
+        .or_else(|| parsed_number(raw));
+    match typed {
+        Some(typed) => json!({ "or": [string, { "===": [var, typed] }] }),
+        None => string,
+    }
+}
+
+// Ordering operators only apply to numeric attributes.
+fn number(raw: &str) -> JsonValue {
+    parsed_number(raw).unwrap_or_else(|| JsonValue::String(raw.to_string()))
+}
+
+fn parsed_number(raw: &str) -> Option<JsonValue> {
+    raw.parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+        .map(JsonValue::Number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::serialization::{Rule, Segment};
+
+    fn segment(rules: Vec<(&str, &str, Vec<&str>)>) -> CompiledSegment {
+        Segment {
+            name: "segment".to_string(),
+            segment_id: "segment".to_string(),
+            description: None,
+            tags: None,
+            rules: rules
+                .into_iter()
+                .map(|(attribute_name, operator, values)| Rule {
+                    attribute_name: attribute_name.to_string(),
+                    operator: operator.to_string(),
+                    values: values.into_iter().map(String::from).collect(),
+                })
+                .collect(),
+        }
+        .try_into()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_segment_to_json_logic() {
+        let segment = segment(vec![
+            ("entityId", "is", vec!["a1", "a2"]),
+            ("age", "greaterThanEquals", vec!["18"]),
+            ("email", "notEndsWith", vec!["@example.com"]),
+        ]);
+        assert_eq!(
+            segment.to_json_logic(),
+            json!({"and": [
+                {"and": [{"!=": [{"var": "targetingKey"}, null]}, {"or": [
+                    {"===": [{"var": "targetingKey"}, "a1"]},
+                    {"===": [{"var": "targetingKey"}, "a2"]},
+                ]}]},
+                {"and": [{"!=": [{"var": "age"}, null]}, {"or": [
+                    {">=": [{"var": "age"}, 18.0]},
+                ]}]},
+                {"and": [{"!=": [{"var": "email"}, null]}, {"and": [
+                    {"!": [{"ends_with": [{"var": "email"}, "@example.com"]}]},
+                ]}]},
+            ]})
+        );
+    }
+
+    /// Values that parse as booleans or numbers still match string attributes
+    /// as written, e.g. zip codes with leading zeros.
+    #[test]
+    fn test_equality_to_json_logic() {
+        let segment = segment(vec![
+            ("zip", "is", vec!["01234"]),
+            ("beta", "isNot", vec!["true"]),
+        ]);
+        assert_eq!(
+            segment.to_json_logic(),
+            json!({"and": [
+                {"and": [{"!=": [{"var": "zip"}, null]}, {"or": [
+                    {"or": [
+                        {"===": [{"var": "zip"}, "01234"]},
+                        {"===": [{"var": "zip"}, 1234.0]},
+                    ]},
+                ]}]},
+                {"and": [{"!=": [{"var": "beta"}, null]}, {"and": [
+                    {"!": [{"or": [
+                        {"===": [{"var": "beta"}, "true"]},
+                        {"===": [{"var": "beta"}, true]},
+                    ]}]},
+                ]}]},
+            ]})
+        );
+    }
+}
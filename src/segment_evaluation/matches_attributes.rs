@@ -16,7 +16,7 @@ use std::collections::HashMap;
 
 use super::errors::CheckOperatorErrorDetail;
 use crate::Value;
-use crate::network::serialization::{Rule, Segment};
+use crate::segment_evaluation::compiled_segment::{CompiledRule, CompiledSegment};
 use crate::segment_evaluation::errors::SegmentEvaluationError;
 use crate::segment_evaluation::rule_operator::RuleOperator;
 
@@ -29,15 +29,19 @@ pub(crate) trait MatchesAttributes {
     ) -> std::result::Result<bool, Self::Error>;
 }
 
-impl MatchesAttributes for Segment {
+impl MatchesAttributes for CompiledSegment {
     type Error = SegmentEvaluationError;
 
-    /// A [`Segment`] matches attributes iif:
+    /// A [`CompiledSegment`] matches attributes iif:
+    /// * it is supported, AND
     /// * ALL the rules match the attributes
     fn matches_attributes(
         &self,
         attributes: &HashMap<String, Value>,
     ) -> std::result::Result<bool, Self::Error> {
+        if !self.supported {
+            return Ok(false);
+        }
         self.rules
             .iter()
             .map(|rule| {
@@ -49,10 +53,10 @@ impl MatchesAttributes for Segment {
     }
 }
 
-impl MatchesAttributes for Rule {
+impl MatchesAttributes for CompiledRule {
     type Error = (CheckOperatorErrorDetail, String);
 
-    /// A [`Rule`] matches attributes iif:
+    /// A [`CompiledRule`] matches attributes iif:
     /// * the attributes contain the requested attribute, AND
     /// * the attribute satisfies ANY of the rule values.
     ///
@@ -61,9 +65,6 @@ impl MatchesAttributes for Rule {
         &self,
         attributes: &HashMap<String, Value>,
     ) -> std::result::Result<bool, Self::Error> {
-        const NEGATION_OPERATORS: &[&str] =
-            &["isNot", "notContains", "notStartsWith", "notEndsWith"];
-
         attributes
             .get(&self.attribute_name)
             .map_or(Ok(false), |attr_value| {
@@ -72,12 +73,12 @@ impl MatchesAttributes for Rule {
                     .iter()
                     .map(|value| {
                         attr_value
-                            .operate(&self.operator, value)
-                            .map_err(|e| (e, value.to_owned()))
+                            .operate(self.operator, value)
+                            .map_err(|e| (e, value.raw.clone()))
                     })
                     .collect::<std::result::Result<Vec<bool>, _>>()?;
 
-                if NEGATION_OPERATORS.contains(&self.operator.as_str()) {
+                if self.operator.is_negation() {
                     // ALL values must individually satisfy the negation
                     Ok(results.iter().all(|&x| x))
                 } else {
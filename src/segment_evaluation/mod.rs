@@ -12,7 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod compiled_segment;
 pub(crate) mod errors;
+mod json_logic;
 mod matches_attributes;
 mod rule_operator;
 
@@ -21,27 +23,101 @@ use crate::entity::Entity;
 use crate::errors::Error;
 use crate::errors::Result;
 use crate::models::{DELIMITER, ROLLOUT_TYPE_PROGRESSIVE};
-use crate::network::serialization::{Segment, SegmentRule, ValueType};
-use crate::segment_evaluation::matches_attributes::MatchesAttributes;
-use crate::utils::{get_current_rollout_percentage, parse_rollout_configuration_phases};
-use chrono::Utc;
+use crate::network::serialization::{Segment, SegmentRule, SegmentsOperator, ValueType};
+pub(crate) use crate::segment_evaluation::compiled_segment::CompiledSegment;
+pub(crate) use crate::segment_evaluation::matches_attributes::MatchesAttributes;
+use crate::utils::{
+    get_current_rollout_percentage, parse_rollout_configuration_phases, parse_timestamp_millis,
+};
 use errors::SegmentEvaluationError;
 use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Built-in attribute holding the ID of the evaluated entity, so segment rules
+/// can target entities by ID even if they have no attributes.
+pub(crate) const ENTITY_ID_ATTRIBUTE: &str = "entityId";
+
+/// Returns the attributes segment rules are evaluated against: those of the
+/// entity plus [`ENTITY_ID_ATTRIBUTE`], unless the entity sets it itself.
+pub(crate) fn entity_attributes(entity: &(impl Entity + ?Sized)) -> HashMap<String, Value> {
+    let mut attributes = entity.get_attributes();
+    attributes
+        .entry(ENTITY_ID_ATTRIBUTE.to_string())
+        .or_insert_with(|| Value::String(entity.get_id()));
+    attributes
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct TargetingRules {
     segment_rules: Vec<SegmentRule>,
-    segments: HashMap<String, Segment>,
+    segments: HashMap<String, Arc<CompiledSegment>>,
     r#type: ValueType,
     /// Map of segment rule rollout configurations (key: feature_id + DELIMITER + rule_id)
     rollout_config_map: HashMap<String, BTreeMap<i64, u32>>,
     /// Feature ID for this targeting rules (needed for progressive rollout)
     feature_id: String,
+    /// Window of each segment rule, parsed once instead of on every evaluation
+    schedules: Vec<Schedule>,
+}
+
+/// The `[start_at, end_at)` window of a segment rule, in milliseconds since
+/// epoch. Missing bounds leave that side of the window open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Schedule {
+    start_ms: Option<i64>,
+    end_ms: Option<i64>,
+}
+
+impl Schedule {
+    /// Bounds that are not RFC 3339 timestamps, rejected when the configuration
+    /// is validated, close the window: the rule never applies.
+    fn of(segment_rule: &SegmentRule) -> Self {
+        let bound = |timestamp: &Option<String>| {
+            timestamp
+                .as_deref()
+                .map(|timestamp| parse_timestamp_millis(timestamp).ok_or(()))
+                .transpose()
+        };
+        match (bound(&segment_rule.start_at), bound(&segment_rule.end_at)) {
+            (Ok(start_ms), Ok(end_ms)) => Self { start_ms, end_ms },
+            _ => Self {
+                start_ms: Some(i64::MAX),
+                end_ms: Some(i64::MIN),
+            },
+        }
+    }
+
+    fn contains(&self, now_ms: i64) -> bool {
+        self.start_ms.is_none_or(|start| now_ms >= start)
+            && self.end_ms.is_none_or(|end| now_ms < end)
+    }
+}
+
+/// Checks that the `start_at` and `end_at` bounds of `segment_rule` are RFC
+/// 3339 timestamps, returning the location and the reason of the first one
+/// that is not.
+pub(crate) fn check_schedule(
+    segment_rule: &SegmentRule,
+) -> std::result::Result<(), (String, String)> {
+    for (bound, timestamp) in [
+        ("start_at", &segment_rule.start_at),
+        ("end_at", &segment_rule.end_at),
+    ] {
+        if let Some(timestamp) = timestamp
+            && parse_timestamp_millis(timestamp).is_none()
+        {
+            return Err((
+                format!("{bound} of segment rule {}", segment_rule.order),
+                format!("'{timestamp}' is not an RFC 3339 timestamp"),
+            ));
+        }
+    }
+    Ok(())
 }
 
 impl TargetingRules {
     pub(crate) fn new(
-        segments: HashMap<String, Segment>,
+        segments: HashMap<String, Arc<CompiledSegment>>,
         segment_rules: Vec<SegmentRule>,
         r#type: ValueType,
         feature_id: Option<&str>,
@@ -54,12 +130,12 @@ impl TargetingRules {
                     continue;
                 }
 
-                if let Some(rollout_config) = &segment_rule.rollout_configuration {
-                    if let Some(rule_id) = &segment_rule.rule_id {
-                        let key = format!("{}{}{}", feature_id, DELIMITER, rule_id);
-                        if let Ok(btree) = parse_rollout_configuration_phases(rollout_config) {
-                            rollout_config_map.insert(key, btree);
-                        }
+                if let Some(rollout_config) = &segment_rule.rollout_configuration
+                    && let Some(rule_id) = &segment_rule.rule_id
+                {
+                    let key = format!("{}{}{}", feature_id, DELIMITER, rule_id);
+                    if let Ok(btree) = parse_rollout_configuration_phases(rollout_config) {
+                        rollout_config_map.insert(key, btree);
                     }
                 }
             }
@@ -67,6 +143,7 @@ impl TargetingRules {
 
         Self {
             segments,
+            schedules: segment_rules.iter().map(Schedule::of).collect(),
             segment_rules,
             r#type,
             rollout_config_map,
@@ -78,18 +155,44 @@ impl TargetingRules {
         self.segment_rules.is_empty()
     }
 
+    #[cfg(test)]
+    pub(crate) fn segment(&self, segment_id: &str) -> Option<&Arc<CompiledSegment>> {
+        self.segments.get(segment_id)
+    }
+
+    /// Whether evaluating these rules depends on the current time (scheduled
+    /// rules or progressive rollouts).
+    pub(crate) fn is_time_dependent(&self) -> bool {
+        !self.rollout_config_map.is_empty()
+            || self
+                .segment_rules
+                .iter()
+                .any(|rule| rule.start_at.is_some() || rule.end_at.is_some())
+    }
+
     /// Finds the [`TargetingRule`] and the [`Segment`] which a given entity can be associated to.
     /// Note: A feature/property can have multiple TargetingRules, which define a specific feature/property value. One TargetingRule can point to multiple Segments. Rules and Segments are iterated in order and the first match is reported.
     /// TODO: A TargetingRule can have Rules and Segments also have Rules. Those are easily confused. Especially, as TargetingRules are sometimes referred to as SegmentRules, which causes even greater confusion.
+    /// Rules scheduled outside of `now_ms` (milliseconds since epoch) are skipped.
+    /// The segment is `None` if the rule applies because the entity is excluded
+    /// from its segments.
     pub(crate) fn find_applicable_targeting_rule_and_segment_for_entity(
         &self,
-        entity: &impl Entity,
-    ) -> Result<Option<(TargetingRule<'_>, &Segment)>> {
-        for segment_rule in self.segment_rules.iter() {
+        entity: &(impl Entity + ?Sized),
+        now_ms: i64,
+    ) -> Result<Option<(TargetingRule<'_>, Option<&Segment>)>> {
+        let attributes = entity_attributes(entity);
+        for segment_rule in self
+            .segment_rules
+            .iter()
+            .zip(&self.schedules)
+            .filter(|(_, schedule)| schedule.contains(now_ms))
+            .map(|(segment_rule, _)| segment_rule)
+        {
             if let Some(segment) = find_segment_of_targeting_rule_which_applies_to_entity(
                 &self.segments,
                 segment_rule,
-                entity,
+                &attributes,
             )? {
                 return Ok(Some((
                     TargetingRule {
@@ -97,6 +200,7 @@ impl TargetingRules {
                         r#type: self.r#type,
                         rollout_config_map: &self.rollout_config_map,
                         feature_id: self.feature_id.clone(),
+                        now_ms,
                     },
                     segment,
                 )));
@@ -112,6 +216,8 @@ pub(crate) struct TargetingRule<'a> {
     r#type: ValueType,
     rollout_config_map: &'a HashMap<String, BTreeMap<i64, u32>>,
     feature_id: String,
+    /// Evaluation time, in milliseconds since epoch
+    now_ms: i64,
 }
 
 impl TargetingRule<'_> {
@@ -124,11 +230,11 @@ impl TargetingRule<'_> {
     }
 
     pub(crate) fn entity_id_for_hash(&self, entity_id: String) -> String {
-        if self.segment_rule.rollout_type.as_deref() == Some(ROLLOUT_TYPE_PROGRESSIVE) {
-            if let Some(rollout_config) = &self.segment_rule.rollout_configuration {
-                // Append start_at for entity-stability in progressive rollout
-                return format!("{}{}", entity_id, rollout_config.start_at);
-            }
+        if self.segment_rule.rollout_type.as_deref() == Some(ROLLOUT_TYPE_PROGRESSIVE)
+            && let Some(rollout_config) = &self.segment_rule.rollout_configuration
+        {
+            // Append start_at for entity-stability in progressive rollout
+            return format!("{}{}", entity_id, rollout_config.start_at);
         }
         entity_id
     }
@@ -139,13 +245,12 @@ impl TargetingRule<'_> {
     ///   the given `default` argument.
     /// * Otherwise it will return the rollout value from the [`TargetingRule`] converted to u32
     pub(crate) fn rollout_percentage(&self, default: u32) -> Result<u32> {
-        if self.segment_rule.rollout_type.as_deref() == Some(ROLLOUT_TYPE_PROGRESSIVE) {
-            if let Some(rule_id) = &self.segment_rule.rule_id {
-                let key = format!("{}{}{}", self.feature_id, DELIMITER, rule_id);
-                if let Some(btree) = self.rollout_config_map.get(&key) {
-                    let current_time_ms = Utc::now().timestamp_millis();
-                    return Ok(get_current_rollout_percentage(btree, current_time_ms));
-                }
+        if self.segment_rule.rollout_type.as_deref() == Some(ROLLOUT_TYPE_PROGRESSIVE)
+            && let Some(rule_id) = &self.segment_rule.rule_id
+        {
+            let key = format!("{}{}{}", self.feature_id, DELIMITER, rule_id);
+            if let Some(btree) = self.rollout_config_map.get(&key) {
+                return Ok(get_current_rollout_percentage(btree, self.now_ms));
             }
         }
 
@@ -185,20 +290,37 @@ impl TargetingRule<'_> {
 }
 
 // Finds out if a given TargetingRule (referring to multiple Segments) applies to a given entity.
-// Basically this means it returns true, if one of the segments referred to by
-// the targeting_rule matches the entity.
+// Basically this means it returns Some, if one of the segments referred to by
+// the targeting_rule matches the entity, or if the entity matches none of the
+// segments of an exclusion list. In the latter case there is no segment to report.
 fn find_segment_of_targeting_rule_which_applies_to_entity<'a>(
-    segments: &'a HashMap<String, Segment>,
+    segments: &'a HashMap<String, Arc<CompiledSegment>>,
     segment_rule: &SegmentRule,
-    entity: &impl Entity,
-) -> std::result::Result<Option<&'a Segment>, SegmentEvaluationError> {
+    attributes: &HashMap<String, Value>,
+) -> std::result::Result<Option<Option<&'a Segment>>, SegmentEvaluationError> {
     // NOTE: In the JSON model the targeted segments (list of list) are called "rules" of a targeting rule.
     let targeted_segment_list_of_list = &segment_rule.rules;
     for targeted_segment_list in targeted_segment_list_of_list.iter() {
-        if let Some(segment) =
-            find_segment_which_applies_to_entity(segments, &targeted_segment_list.segments, entity)?
+        // Nobody belongs to unsupported segments, but excluding them must not
+        // target everybody either
+        if targeted_segment_list.operator == SegmentsOperator::Not
+            && targeted_segment_list.segments.iter().any(|segment_id| {
+                segments
+                    .get(segment_id)
+                    .is_some_and(|segment| !segment.supported)
+            })
         {
-            return Ok(Some(segment));
+            continue;
+        }
+        let segment = find_segment_which_applies_to_entity(
+            segments,
+            &targeted_segment_list.segments,
+            attributes,
+        )?;
+        match (targeted_segment_list.operator, segment) {
+            (SegmentsOperator::In, Some(segment)) => return Ok(Some(Some(segment))),
+            (SegmentsOperator::Not, None) => return Ok(Some(None)),
+            _ => {}
         }
     }
 
@@ -206,16 +328,16 @@ fn find_segment_of_targeting_rule_which_applies_to_entity<'a>(
 }
 
 fn find_segment_which_applies_to_entity<'a>(
-    segments: &'a HashMap<String, Segment>,
+    segments: &'a HashMap<String, Arc<CompiledSegment>>,
     segment_ids: &[String],
-    entity: &impl Entity,
+    attributes: &HashMap<String, Value>,
 ) -> std::result::Result<Option<&'a Segment>, SegmentEvaluationError> {
     Ok(segment_ids
         .iter()
         .map(|segment_id| match segments.get(segment_id) {
             Some(segment) => segment
-                .matches_attributes(&entity.get_attributes())
-                .map(|v| v.then_some(segment)),
+                .matches_attributes(attributes)
+                .map(|v| v.then_some(&segment.segment)),
             None => Err(SegmentEvaluationError::SegmentIdNotFound(
                 segment_id.clone(),
             )),
@@ -236,25 +358,39 @@ pub mod tests {
     use crate::network::serialization::{Segment, SegmentRule};
     use rstest::*;
 
+    pub(crate) fn compile_segments(
+        segments: HashMap<String, Segment>,
+    ) -> HashMap<String, Arc<CompiledSegment>> {
+        segments
+            .into_iter()
+            .map(|(id, segment)| (id, Arc::new(segment.try_into().unwrap())))
+            .collect()
+    }
+
     #[rstest]
     fn test_targeting_rule_matches_and_correct_segment_reported_back(
         some_segments: HashMap<String, Segment>,
         some_segment_rules: Vec<SegmentRule>,
     ) {
-        let segment_rules =
-            TargetingRules::new(some_segments, some_segment_rules, ValueType::String, None);
+        let segment_rules = TargetingRules::new(
+            compile_segments(some_segments),
+            some_segment_rules,
+            ValueType::String,
+            None,
+        );
         let entity = crate::tests::GenericEntity {
             id: "a2".into(),
             attributes: HashMap::from([("name".into(), Value::from("peter".to_string()))]),
         };
 
         {
-            let rule = segment_rules.find_applicable_targeting_rule_and_segment_for_entity(&entity);
+            let rule =
+                segment_rules.find_applicable_targeting_rule_and_segment_for_entity(&entity, 0);
             // Segment evaluation should succeed:
             let (rule, segment) = rule.unwrap().unwrap();
             // And we should get the correct rule and the matched segment
             assert!(rule.segment_rule.order == 0);
-            assert!(segment.segment_id == "some_segment_id_2");
+            assert!(segment.unwrap().segment_id == "some_segment_id_2");
         }
 
         let entity = crate::tests::GenericEntity {
@@ -262,12 +398,13 @@ pub mod tests {
             attributes: HashMap::from([("name".into(), Value::from("jane".to_string()))]),
         };
         {
-            let rule = segment_rules.find_applicable_targeting_rule_and_segment_for_entity(&entity);
+            let rule =
+                segment_rules.find_applicable_targeting_rule_and_segment_for_entity(&entity, 0);
             // Segment evaluation should succeed:
             let (rule, segment) = rule.unwrap().unwrap();
             // And we should get the correct rule and the matched segment
             assert!(rule.segment_rule.order == 0);
-            assert!(segment.segment_id == "some_segment_id_3");
+            assert!(segment.unwrap().segment_id == "some_segment_id_3");
         }
 
         let entity = crate::tests::GenericEntity {
@@ -275,12 +412,156 @@ pub mod tests {
             attributes: HashMap::from([("name".into(), Value::from("noname".to_string()))]),
         };
         {
-            let rule = segment_rules.find_applicable_targeting_rule_and_segment_for_entity(&entity);
+            let rule =
+                segment_rules.find_applicable_targeting_rule_and_segment_for_entity(&entity, 0);
             // Segment evaluation should succeed, but no rule is found:
             assert!(rule.unwrap().is_none());
         }
     }
 
+    // Exclusion lists apply to the entities belonging to none of their segments.
+    #[rstest]
+    fn test_targeting_rule_exclusion(
+        some_segments: HashMap<String, Segment>,
+        mut some_segment_rules: Vec<SegmentRule>,
+    ) {
+        // Targets everyone but "heinz" and "peter"
+        some_segment_rules[0].rules.truncate(1);
+        some_segment_rules[0].rules[0].operator = SegmentsOperator::Not;
+        let segment_rules = TargetingRules::new(
+            compile_segments(some_segments),
+            some_segment_rules,
+            ValueType::String,
+            None,
+        );
+        let find = |name: &str| {
+            let entity = crate::tests::GenericEntity {
+                id: "a1".into(),
+                attributes: HashMap::from([("name".into(), Value::from(name.to_string()))]),
+            };
+            segment_rules
+                .find_applicable_targeting_rule_and_segment_for_entity(&entity, 0)
+                .unwrap()
+                .map(|(rule, segment)| (rule.order(), segment.is_some()))
+        };
+
+        assert_eq!(find("heinz"), None);
+        assert_eq!(find("peter"), None);
+        // Matches, but without reporting any segment
+        assert_eq!(find("jane"), Some((0, false)));
+
+        // Entities without the attribute do not belong to the segments either
+        let entity = crate::tests::GenericEntity {
+            id: "a1".into(),
+            attributes: HashMap::new(),
+        };
+        let rule = segment_rules
+            .find_applicable_targeting_rule_and_segment_for_entity(&entity, 0)
+            .unwrap();
+        assert!(rule.is_some());
+    }
+
+    // Nobody belongs to unsupported segments, and excluding them applies to nobody.
+    #[rstest]
+    fn test_targeting_rule_unsupported_segment(
+        some_segments: HashMap<String, Segment>,
+        mut some_segment_rules: Vec<SegmentRule>,
+    ) {
+        let segments: HashMap<_, _> = some_segments
+            .into_iter()
+            .map(|(id, segment)| (id, Arc::new(CompiledSegment::unsupported(segment))))
+            .collect();
+        some_segment_rules[0].rules.truncate(1);
+        let find = |operator: SegmentsOperator| {
+            let mut segment_rules = some_segment_rules.clone();
+            segment_rules[0].rules[0].operator = operator;
+            let segment_rules =
+                TargetingRules::new(segments.clone(), segment_rules, ValueType::String, None);
+            let entity = crate::tests::GenericEntity {
+                id: "a1".into(),
+                attributes: HashMap::from([("name".into(), Value::from("heinz".to_string()))]),
+            };
+            segment_rules
+                .find_applicable_targeting_rule_and_segment_for_entity(&entity, 0)
+                .unwrap()
+                .is_some()
+        };
+
+        assert!(!find(SegmentsOperator::In));
+        assert!(!find(SegmentsOperator::Not));
+    }
+
+    // Scheduled targeting rules only apply within their [start_at, end_at) window.
+    #[rstest]
+    fn test_targeting_rule_scheduled_window(
+        some_segments: HashMap<String, Segment>,
+        mut some_segment_rules: Vec<SegmentRule>,
+    ) {
+        some_segment_rules[0].start_at = Some("2030-01-01T00:00:00Z".into());
+        some_segment_rules[0].end_at = Some("2030-01-02T00:00:00Z".into());
+        let start_ms = parse_timestamp_millis("2030-01-01T00:00:00Z").unwrap();
+        let end_ms = parse_timestamp_millis("2030-01-02T00:00:00Z").unwrap();
+
+        let segment_rules = TargetingRules::new(
+            compile_segments(some_segments),
+            some_segment_rules,
+            ValueType::String,
+            None,
+        );
+        let entity = crate::tests::GenericEntity {
+            id: "a2".into(),
+            attributes: HashMap::from([("name".into(), Value::from("peter".to_string()))]),
+        };
+
+        let matches_at = |now_ms| {
+            segment_rules
+                .find_applicable_targeting_rule_and_segment_for_entity(&entity, now_ms)
+                .unwrap()
+                .is_some()
+        };
+        assert!(!matches_at(start_ms - 1));
+        assert!(matches_at(start_ms));
+        assert!(matches_at(end_ms - 1));
+        assert!(!matches_at(end_ms));
+    }
+
+    // Malformed bounds are rejected by the validation, and never match
+    #[rstest]
+    fn test_targeting_rule_malformed_schedule(
+        some_segments: HashMap<String, Segment>,
+        mut some_segment_rules: Vec<SegmentRule>,
+    ) {
+        some_segment_rules[0].start_at = Some("2030-01-01T00:00:00Z".into());
+        assert_eq!(check_schedule(&some_segment_rules[0]), Ok(()));
+        some_segment_rules[0].end_at = Some("tomorrow".into());
+        assert_eq!(
+            check_schedule(&some_segment_rules[0]),
+            Err((
+                format!("end_at of segment rule {}", some_segment_rules[0].order),
+                "'tomorrow' is not an RFC 3339 timestamp".to_string()
+            ))
+        );
+
+        let segment_rules = TargetingRules::new(
+            compile_segments(some_segments),
+            some_segment_rules,
+            ValueType::String,
+            None,
+        );
+        let entity = crate::tests::GenericEntity {
+            id: "a2".into(),
+            attributes: HashMap::from([("name".into(), Value::from("peter".to_string()))]),
+        };
+        for now_ms in [i64::MIN, 0, i64::MAX] {
+            assert!(
+                segment_rules
+                    .find_applicable_targeting_rule_and_segment_for_entity(&entity, now_ms)
+                    .unwrap()
+                    .is_none()
+            );
+        }
+    }
+
     // SCENARIO - If the SDK user fail to pass the “attributes” for evaluation of featureflag which is segmented - we have considered that evaluation as “does not belong to any segment” and we serve the enabled_value.
     // EXAMPLE - Assume two teams are using same featureflag. One team is interested only in enabled_value & disabled_value. This team doesn’t pass attributes for  their evaluation. Other team wants to have overridden_value, as a result they update the featureflag by adding segment rules to it. This team passes attributes in their evaluation to get the overridden_value for matching segment, and enabled_value for non-matching segment.
     //  We should not fail the evaluation.
@@ -289,13 +570,17 @@ pub mod tests {
         some_segments: HashMap<String, Segment>,
         some_segment_rules: Vec<SegmentRule>,
     ) {
-        let segment_rules =
-            TargetingRules::new(some_segments, some_segment_rules, ValueType::String, None);
+        let segment_rules = TargetingRules::new(
+            compile_segments(some_segments),
+            some_segment_rules,
+            ValueType::String,
+            None,
+        );
         let entity = crate::tests::GenericEntity {
             id: "a2".into(),
             attributes: HashMap::from([("name2".into(), Value::from("heinz".to_string()))]),
         };
-        let rule = segment_rules.find_applicable_targeting_rule_and_segment_for_entity(&entity);
+        let rule = segment_rules.find_applicable_targeting_rule_and_segment_for_entity(&entity, 0);
         // Segment evaluation should not fail:
         let rule = rule.unwrap();
         // But no segment should be found:
@@ -315,12 +600,12 @@ pub mod tests {
             attributes: HashMap::from([("name".into(), Value::from(42.0))]),
         };
         let segment_rules = TargetingRules::new(
-            some_segments,
+            compile_segments(some_segments),
             segment_rules_with_invalid_segment_id,
             ValueType::String,
             None,
         );
-        let rule = segment_rules.find_applicable_targeting_rule_and_segment_for_entity(&entity);
+        let rule = segment_rules.find_applicable_targeting_rule_and_segment_for_entity(&entity, 0);
         // Error message should look something like this:
         //  Failed to evaluate entity: Failed to evaluate entity 'a2' against targeting rule '0'.
         //  Caused by: Segment 'non_existing_segment_id' not found.
@@ -343,13 +628,17 @@ pub mod tests {
         some_segments: HashMap<String, Segment>,
         some_segment_rules: Vec<SegmentRule>,
     ) {
-        let segment_rules =
-            TargetingRules::new(some_segments, some_segment_rules, ValueType::String, None);
+        let segment_rules = TargetingRules::new(
+            compile_segments(some_segments),
+            some_segment_rules,
+            ValueType::String,
+            None,
+        );
         let entity = crate::tests::GenericEntity {
             id: "a2".into(),
             attributes: HashMap::from([("name".into(), Value::from(42.0))]),
         };
-        let rule = segment_rules.find_applicable_targeting_rule_and_segment_for_entity(&entity);
+        let rule = segment_rules.find_applicable_targeting_rule_and_segment_for_entity(&entity, 0);
         let e = rule.unwrap_err();
         assert!(matches!(e, Error::EntityEvaluationError(_)));
         let Error::EntityEvaluationError(EntityEvaluationError(
@@ -362,4 +651,42 @@ pub mod tests {
         assert_eq!(error.segment_rule_attribute_name, "name");
         assert_eq!(error.value, "heinz");
     }
+
+    // SCENARIO - a list attribute matches if any of its items does, even if
+    // the operator cannot be applied to the items before it.
+    #[rstest]
+    fn test_list_attribute(
+        some_segments: HashMap<String, Segment>,
+        some_segment_rules: Vec<SegmentRule>,
+    ) {
+        let segment_rules = TargetingRules::new(
+            compile_segments(some_segments),
+            some_segment_rules,
+            ValueType::String,
+            None,
+        );
+        let entity = |names: Vec<Value>| crate::tests::GenericEntity {
+            id: "a2".into(),
+            attributes: HashMap::from([("name".into(), Value::List(names))]),
+        };
+
+        let rule = segment_rules
+            .find_applicable_targeting_rule_and_segment_for_entity(
+                &entity(vec![Value::from(42.0), Value::from("heinz".to_string())]),
+                0,
+            )
+            .unwrap();
+        let (_, segment) = rule.unwrap();
+        assert_eq!(segment.unwrap().segment_id, "some_segment_id_1");
+
+        // Fails if no item can be compared
+        assert!(
+            segment_rules
+                .find_applicable_targeting_rule_and_segment_for_entity(
+                    &entity(vec![Value::from(42.0)]),
+                    0,
+                )
+                .is_err()
+        );
+    }
 }
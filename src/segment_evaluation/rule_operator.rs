@@ -12,75 +12,194 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::fmt::Display;
+use std::num::{ParseFloatError, ParseIntError};
+use std::str::{FromStr, ParseBoolError};
+
 use super::errors::CheckOperatorErrorDetail;
 use crate::Value;
 
+/// Operator of a segment rule, parsed from its name in the configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Operator {
+    Is,
+    Contains,
+    StartsWith,
+    EndsWith,
+    GreaterThan,
+    LesserThan,
+    GreaterThanEquals,
+    LesserThanEquals,
+    IsNot,
+    NotContains,
+    NotStartsWith,
+    NotEndsWith,
+}
+
+impl Operator {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Operator::Is => "is",
+            Operator::Contains => "contains",
+            Operator::StartsWith => "startsWith",
+            Operator::EndsWith => "endsWith",
+            Operator::GreaterThan => "greaterThan",
+            Operator::LesserThan => "lesserThan",
+            Operator::GreaterThanEquals => "greaterThanEquals",
+            Operator::LesserThanEquals => "lesserThanEquals",
+            Operator::IsNot => "isNot",
+            Operator::NotContains => "notContains",
+            Operator::NotStartsWith => "notStartsWith",
+            Operator::NotEndsWith => "notEndsWith",
+        }
+    }
+
+    /// Negation operators must be satisfied by ALL the rule values instead of ANY.
+    pub(crate) fn is_negation(&self) -> bool {
+        matches!(
+            self,
+            Operator::IsNot
+                | Operator::NotContains
+                | Operator::NotStartsWith
+                | Operator::NotEndsWith
+        )
+    }
+}
+
+impl FromStr for Operator {
+    type Err = CheckOperatorErrorDetail;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match s {
+            "is" => Operator::Is,
+            "contains" => Operator::Contains,
+            "startsWith" => Operator::StartsWith,
+            "endsWith" => Operator::EndsWith,
+            "greaterThan" => Operator::GreaterThan,
+            "lesserThan" => Operator::LesserThan,
+            "greaterThanEquals" => Operator::GreaterThanEquals,
+            "lesserThanEquals" => Operator::LesserThanEquals,
+            "isNot" => Operator::IsNot,
+            "notContains" => Operator::NotContains,
+            "notStartsWith" => Operator::NotStartsWith,
+            "notEndsWith" => Operator::NotEndsWith,
+            _ => return Err(CheckOperatorErrorDetail::OperatorNotImplemented),
+        })
+    }
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A segment rule value, converted once to every type an entity attribute can
+/// be compared with.
+///
+/// Conversion errors are kept and only reported if an attribute of that type
+/// is evaluated against the value.
+#[derive(Debug, Clone)]
+pub(crate) struct RuleValue {
+    pub(crate) raw: String,
+    boolean: std::result::Result<bool, ParseBoolError>,
+    float: std::result::Result<f64, ParseFloatError>,
+    int: std::result::Result<i64, ParseIntError>,
+    uint: std::result::Result<u64, ParseIntError>,
+}
+
+impl From<&str> for RuleValue {
+    fn from(value: &str) -> Self {
+        Self {
+            raw: value.to_string(),
+            boolean: value.parse(),
+            float: value.parse(),
+            int: value.parse(),
+            uint: value.parse(),
+        }
+    }
+}
+
 pub(crate) trait RuleOperator {
     fn operate(
         &self,
-        operator: &str,
-        value: &str,
+        operator: Operator,
+        value: &RuleValue,
     ) -> std::result::Result<bool, CheckOperatorErrorDetail> {
         match operator {
-            "is" => self.is(value),
-            "contains" => self.contains(value),
-            "startsWith" => self.starts_with(value),
-            "endsWith" => self.ends_with(value),
-            "greaterThan" => self.greater_than(value),
-            "lesserThan" => self.lesser_than(value),
+            Operator::Is => self.is(value),
+            Operator::Contains => self.contains(value),
+            Operator::StartsWith => self.starts_with(value),
+            Operator::EndsWith => self.ends_with(value),
+            Operator::GreaterThan => self.greater_than(value),
+            Operator::LesserThan => self.lesser_than(value),
             // Counterpart operators
-            "greaterThanEquals" => self.lesser_than(value).map(std::ops::Not::not),
-            "lesserThanEquals" => self.greater_than(value).map(std::ops::Not::not),
-            "isNot" => self.is(value).map(std::ops::Not::not),
-            "notContains" => self.contains(value).map(std::ops::Not::not),
-            "notStartsWith" => self.starts_with(value).map(std::ops::Not::not),
-            "notEndsWith" => self.ends_with(value).map(std::ops::Not::not),
-            _ => Err(CheckOperatorErrorDetail::OperatorNotImplemented),
+            Operator::GreaterThanEquals => self.lesser_than(value).map(std::ops::Not::not),
+            Operator::LesserThanEquals => self.greater_than(value).map(std::ops::Not::not),
+            Operator::IsNot => self.is(value).map(std::ops::Not::not),
+            Operator::NotContains => self.contains(value).map(std::ops::Not::not),
+            Operator::NotStartsWith => self.starts_with(value).map(std::ops::Not::not),
+            Operator::NotEndsWith => self.ends_with(value).map(std::ops::Not::not),
         }
     }
 
-    fn is(&self, value: &str) -> std::result::Result<bool, CheckOperatorErrorDetail>;
-    fn contains(&self, value: &str) -> std::result::Result<bool, CheckOperatorErrorDetail>;
-    fn starts_with(&self, value: &str) -> std::result::Result<bool, CheckOperatorErrorDetail>;
-    fn ends_with(&self, value: &str) -> std::result::Result<bool, CheckOperatorErrorDetail>;
-    fn greater_than(&self, value: &str) -> std::result::Result<bool, CheckOperatorErrorDetail>;
-    fn lesser_than(&self, value: &str) -> std::result::Result<bool, CheckOperatorErrorDetail>;
+    fn is(&self, value: &RuleValue) -> std::result::Result<bool, CheckOperatorErrorDetail>;
+    fn contains(&self, value: &RuleValue) -> std::result::Result<bool, CheckOperatorErrorDetail>;
+    fn starts_with(&self, value: &RuleValue)
+    -> std::result::Result<bool, CheckOperatorErrorDetail>;
+    fn ends_with(&self, value: &RuleValue) -> std::result::Result<bool, CheckOperatorErrorDetail>;
+    fn greater_than(
+        &self,
+        value: &RuleValue,
+    ) -> std::result::Result<bool, CheckOperatorErrorDetail>;
+    fn lesser_than(&self, value: &RuleValue)
+    -> std::result::Result<bool, CheckOperatorErrorDetail>;
 }
 
 impl RuleOperator for Value {
-    fn is(&self, value: &str) -> std::result::Result<bool, CheckOperatorErrorDetail> {
+    fn is(&self, value: &RuleValue) -> std::result::Result<bool, CheckOperatorErrorDetail> {
         match self {
-            Value::String(data) => Ok(*data == value),
-            Value::Boolean(data) => Ok(*data == value.parse::<bool>()?),
-            Value::Float64(data) => Ok(*data == value.parse::<f64>()?),
-            Value::UInt64(data) => Ok(*data == value.parse::<u64>()?),
-            Value::Int64(data) => Ok(*data == value.parse::<i64>()?),
+            Value::String(data) => Ok(*data == value.raw),
+            Value::Boolean(data) => Ok(*data == value.boolean.clone()?),
+            Value::Float64(data) => Ok(*data == value.float.clone()?),
+            Value::UInt64(data) => Ok(*data == value.uint.clone()?),
+            Value::Int64(data) => Ok(*data == value.int.clone()?),
+            Value::List(items) => any_item(items, |item| item.is(value)),
         }
     }
 
-    fn contains(&self, value: &str) -> std::result::Result<bool, CheckOperatorErrorDetail> {
+    fn contains(&self, value: &RuleValue) -> std::result::Result<bool, CheckOperatorErrorDetail> {
         match self {
-            Value::String(data) => Ok(data.contains(value)),
+            Value::String(data) => Ok(data.contains(&value.raw)),
+            Value::List(items) => any_item(items, |item| item.contains(value)),
             _ => Err(CheckOperatorErrorDetail::StringExpected),
         }
     }
 
-    fn starts_with(&self, value: &str) -> std::result::Result<bool, CheckOperatorErrorDetail> {
+    fn starts_with(
+        &self,
+        value: &RuleValue,
+    ) -> std::result::Result<bool, CheckOperatorErrorDetail> {
         match self {
-            Value::String(data) => Ok(data.starts_with(value)),
+            Value::String(data) => Ok(data.starts_with(&value.raw)),
+            Value::List(items) => any_item(items, |item| item.starts_with(value)),
             _ => Err(CheckOperatorErrorDetail::StringExpected),
         }
     }
 
-    fn ends_with(&self, value: &str) -> std::result::Result<bool, CheckOperatorErrorDetail> {
+    fn ends_with(&self, value: &RuleValue) -> std::result::Result<bool, CheckOperatorErrorDetail> {
         match self {
-            Value::String(data) => Ok(data.ends_with(value)),
+            Value::String(data) => Ok(data.ends_with(&value.raw)),
+            Value::List(items) => any_item(items, |item| item.ends_with(value)),
             _ => Err(CheckOperatorErrorDetail::StringExpected),
         }
     }
 
-    fn greater_than(&self, value: &str) -> std::result::Result<bool, CheckOperatorErrorDetail> {
-        let threshold: f64 = value.parse()?;
+    fn greater_than(
+        &self,
+        value: &RuleValue,
+    ) -> std::result::Result<bool, CheckOperatorErrorDetail> {
+        let threshold: f64 = value.float.clone()?;
         match self {
             // TODO: Go implementation also compares strings (by parsing them as floats). Do we need this?
             //       https://github.com/IBM/appconfiguration-go-sdk/blob/master/lib/internal/models/Rule.go#L82
@@ -88,17 +207,49 @@ impl RuleOperator for Value {
             Value::Float64(data) => Ok(*data > threshold),
             Value::UInt64(data) => Ok((*data as f64) > threshold),
             Value::Int64(data) => Ok((*data as f64) > threshold),
+            Value::List(items) => any_item(items, |item| item.greater_than(value)),
             _ => Err(CheckOperatorErrorDetail::EntityAttrNotANumber),
         }
     }
 
-    fn lesser_than(&self, value: &str) -> std::result::Result<bool, CheckOperatorErrorDetail> {
-        let threshold: f64 = value.parse()?;
+    fn lesser_than(
+        &self,
+        value: &RuleValue,
+    ) -> std::result::Result<bool, CheckOperatorErrorDetail> {
+        let threshold: f64 = value.float.clone()?;
         match self {
             Value::Float64(data) => Ok(*data < threshold),
             Value::UInt64(data) => Ok((*data as f64) < threshold),
             Value::Int64(data) => Ok((*data as f64) < threshold),
+            Value::List(items) => any_item(items, |item| item.lesser_than(value)),
             _ => Err(CheckOperatorErrorDetail::EntityAttrNotANumber),
         }
     }
 }
+
+/// Entity attributes given as a list match an operator if any of their items
+/// does, e.g. `roles is admin` for `roles: [admin, dev]`. Negation operators
+/// then require that none of the items matches.
+///
+/// Items the operator cannot be applied to do not match. The operator fails
+/// only if it can be applied to none of the items.
+fn any_item(
+    items: &[Value],
+    matches: impl Fn(&Value) -> std::result::Result<bool, CheckOperatorErrorDetail>,
+) -> std::result::Result<bool, CheckOperatorErrorDetail> {
+    let mut first_error = None;
+    let mut compared = false;
+    for item in items {
+        match matches(item) {
+            Ok(true) => return Ok(true),
+            Ok(false) => compared = true,
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    match first_error {
+        Some(e) if !compared => Err(e),
+        _ => Ok(false),
+    }
+}
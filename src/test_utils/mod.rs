@@ -12,6 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod smoke_test;
+
+pub use smoke_test::{
+    SMOKE_TEST_ENTITY_ID, SmokeTestEvaluation, SmokeTestReport, live_smoke_test, run_smoke_test,
+};
+
+pub use crate::network::live_configuration::{StepController, WorkerStep};
+
 use crate::client::app_configuration_http::AppConfigurationClientHttp;
 use crate::network::{NetworkResult, ServiceAddress, TokenProvider};
 use crate::{AppConfigurationClient, Result};
@@ -32,13 +40,13 @@ pub fn create_app_configuration_client_live(
     configuration_id: ConfigurationId,
     offline_mode: OfflineMode,
 ) -> Result<Box<dyn AppConfigurationClient>> {
-    let token_provider = Box::new(MockTokenProvider {});
     let client = AppConfigurationClientHttp::new(
         service_address,
-        token_provider,
+        std::sync::Arc::new(MockTokenProvider {}),
         configuration_id,
         offline_mode,
         crate::RuntimeEventEmitter::new(),
+        Default::default(),
     )?;
 
     Ok(Box::new(client))
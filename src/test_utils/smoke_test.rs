@@ -0,0 +1,203 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::{Duration, Instant};
+
+use crate::errors::{Error, Result};
+use crate::{
+    AppConfiguration, AppConfigurationContextOptions, ConfigurationProvider, Entity, Feature,
+    Property, SdkErrorSnapshot, Value,
+};
+
+/// ID of the entity the features and properties are evaluated for.
+pub const SMOKE_TEST_ENTITY_ID: &str = "appconfiguration-smoke-test";
+
+struct SmokeTestEntity;
+
+impl Entity for SmokeTestEntity {
+    fn get_id(&self) -> String {
+        SMOKE_TEST_ENTITY_ID.to_string()
+    }
+}
+
+/// Evaluation of a feature or property by a smoke test.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmokeTestEvaluation {
+    pub id: String,
+    /// The value for the smoke test entity, or why it could not be evaluated.
+    pub result: std::result::Result<Value, String>,
+}
+
+/// Outcome of [`live_smoke_test`] and [`run_smoke_test`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmokeTestReport {
+    /// Whether the client received the configuration from the server.
+    pub online: bool,
+    /// Time waited for the client to get online, or to give up.
+    pub time_to_online: Duration,
+    /// Why the client is not online, if it is not.
+    pub last_error: Option<SdkErrorSnapshot>,
+    pub features: Vec<SmokeTestEvaluation>,
+    pub properties: Vec<SmokeTestEvaluation>,
+}
+
+impl SmokeTestReport {
+    /// Whether the client got online and evaluated every feature and property.
+    pub fn is_success(&self) -> bool {
+        self.online
+            && self
+                .features
+                .iter()
+                .chain(&self.properties)
+                .all(|evaluation| evaluation.result.is_ok())
+    }
+}
+
+impl std::fmt::Display for SmokeTestReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = if self.is_success() { "OK" } else { "FAILED" };
+        writeln!(
+            f,
+            "Smoke test {status}: online={} after {:.1}s",
+            self.online,
+            self.time_to_online.as_secs_f64()
+        )?;
+        if let Some(error) = &self.last_error {
+            writeln!(f, "  last error: {}", error.message)?;
+        }
+        for (kind, evaluations) in [("feature", &self.features), ("property", &self.properties)] {
+            for evaluation in evaluations {
+                match &evaluation.result {
+                    Ok(value) => writeln!(f, "  {kind} {}: {value}", evaluation.id)?,
+                    Err(e) => writeln!(f, "  {kind} {}: error: {e}", evaluation.id)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Connects to the App Configuration instance given by the environment, or a
+/// `.env` file, and checks that its configuration can be fetched and evaluated.
+///
+/// Reads `REGION`, `GUID`, `APIKEY`, `COLLECTION_ID` and `ENVIRONMENT_ID`, the
+/// same variables as the examples, and optionally `SERVICE_URL` to override the
+/// service endpoint. Every feature and property is evaluated for an entity with
+/// ID [`SMOKE_TEST_ENTITY_ID`] and no attributes.
+///
+/// Failing to connect is reported in the [`SmokeTestReport`], errors are
+/// returned only for missing variables or invalid settings.
+pub fn live_smoke_test() -> Result<SmokeTestReport> {
+    let _ = dotenvy::dotenv();
+    let var =
+        |name: &str| std::env::var(name).map_err(|_| Error::Other(format!("{name} must be set")));
+
+    let mut client = AppConfiguration::new();
+    if let Ok(url) = std::env::var("SERVICE_URL") {
+        client.override_service_url(&url);
+    }
+    client.init(&var("REGION")?, &var("GUID")?, &var("APIKEY")?)?;
+    client.set_context(
+        &var("COLLECTION_ID")?,
+        &var("ENVIRONMENT_ID")?,
+        AppConfigurationContextOptions::default(),
+    )?;
+
+    let report = run_smoke_test(&client);
+    client.clean_up()?;
+    report
+}
+
+/// Waits for `client` to get online and evaluates all its features and
+/// properties, see [`live_smoke_test`].
+pub fn run_smoke_test(client: &impl ConfigurationProvider) -> Result<SmokeTestReport> {
+    let started = Instant::now();
+    let online = client.wait_until_online();
+    let time_to_online = started.elapsed();
+    let last_error = if online { None } else { client.last_error()? };
+
+    // Nothing to evaluate when there is no configuration (e.g. the client never got online)
+    let features = match client.get_feature_ids() {
+        Ok(ids) => ids
+            .into_iter()
+            .map(|id| {
+                let result = client
+                    .get_feature(&id)
+                    .and_then(|feature| feature.get_current_value(&SmokeTestEntity))
+                    .map(|evaluation| evaluation.value)
+                    .map_err(|e| e.to_string());
+                SmokeTestEvaluation { id, result }
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    let properties = match client.get_property_ids() {
+        Ok(ids) => ids
+            .into_iter()
+            .map(|id| {
+                let result = client
+                    .get_property(&id)
+                    .and_then(|property| property.get_current_value(&SmokeTestEntity))
+                    .map(|evaluation| evaluation.value)
+                    .map_err(|e| e.to_string());
+                SmokeTestEvaluation { id, result }
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+
+    Ok(SmokeTestReport {
+        online,
+        time_to_online,
+        last_error,
+        features,
+        properties,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppConfigurationOffline;
+    use crate::network::serialization::fixtures::example_configuration_enterprise_path;
+
+    #[test]
+    fn test_run_smoke_test() {
+        let client = AppConfigurationOffline::new(
+            &example_configuration_enterprise_path(),
+            "dev",
+            "blue-charge",
+        )
+        .unwrap();
+
+        let report = run_smoke_test(&client).unwrap();
+        // The offline client never gets online
+        assert!(!report.online);
+        assert!(!report.is_success());
+        assert_eq!(report.features.len(), 5);
+        assert_eq!(report.properties.len(), 3);
+        assert!(
+            report
+                .features
+                .iter()
+                .chain(&report.properties)
+                .all(|evaluation| evaluation.result.is_ok())
+        );
+        assert!(
+            report
+                .to_string()
+                .starts_with("Smoke test FAILED: online=false")
+        );
+    }
+}
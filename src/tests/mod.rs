@@ -15,10 +15,13 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+mod test_configuration_dump;
 mod test_get_feature;
 mod test_get_feature_ids;
 mod test_get_property;
 mod test_get_property_ids;
+mod test_get_segment;
+mod test_proxies;
 mod test_using_example_data;
 
 use crate::client::{AppConfigurationClient, AppConfigurationOffline};
@@ -0,0 +1,126 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use rstest::*;
+
+use crate::entity::tests::TrivialEntity;
+use crate::network::serialization::fixtures::example_configuration_enterprise_path;
+use crate::{
+    AppConfigurationOffline, ConfigurationDataError, ConfigurationDump, ConfigurationProvider,
+    Error, Feature, ValidationMode, Value,
+};
+
+#[rstest]
+fn test_list_environments(example_configuration_enterprise_path: std::path::PathBuf) {
+    let environments =
+        AppConfigurationOffline::environments(&example_configuration_enterprise_path).unwrap();
+    assert_eq!(environments, ["dev", "prod", "stage"]);
+
+    let dump = ConfigurationDump::from_file(&example_configuration_enterprise_path).unwrap();
+    assert_eq!(dump.environments(), environments);
+    assert_eq!(
+        dump.collections(),
+        ["blue-charge", "ci-pipeline", "my-dashboard"]
+    );
+}
+
+#[cfg(feature = "yaml")]
+#[rstest]
+fn test_yaml_file(example_configuration_enterprise_path: std::path::PathBuf) {
+    let json = std::fs::read_to_string(example_configuration_enterprise_path).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let dir = std::env::temp_dir().join(format!("appconfiguration-yaml-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let yaml_path = dir.join("flags.yml");
+    let conf_path = dir.join("flags.conf");
+    let yaml = serde_yaml_ng::to_string(&value).unwrap();
+    std::fs::write(&yaml_path, &yaml).unwrap();
+    std::fs::write(&conf_path, &yaml).unwrap();
+
+    // Picked by the extension
+    let from_yaml = AppConfigurationOffline::new(&yaml_path, "dev", "blue-charge").unwrap();
+    let from_json = AppConfigurationOffline::from_json_str(&json, "dev", "blue-charge").unwrap();
+    assert_eq!(
+        from_yaml.get_configuration_snapshot().unwrap(),
+        from_json.get_configuration_snapshot().unwrap()
+    );
+
+    // Other extensions are JSON, unless the format is given
+    assert!(matches!(
+        ConfigurationDump::from_file(&conf_path),
+        Err(Error::DeserializationError(_))
+    ));
+    let dump =
+        ConfigurationDump::from_file_with_format(&conf_path, crate::ConfigurationFileFormat::Yaml)
+            .unwrap();
+    assert_eq!(dump.environments(), ["dev", "prod", "stage"]);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[rstest]
+fn test_open_several_environments(example_configuration_enterprise_path: std::path::PathBuf) {
+    let json = std::fs::read_to_string(example_configuration_enterprise_path).unwrap();
+    let dump = ConfigurationDump::from_json_str(&json).unwrap();
+
+    let dev = dump.open("dev", "blue-charge").unwrap();
+    let prod = dump.open("prod", "blue-charge").unwrap();
+    let value = |client: &AppConfigurationOffline| {
+        client
+            .get_feature("f1")
+            .unwrap()
+            .get_current_value(&TrivialEntity)
+            .unwrap()
+            .value
+    };
+    assert_eq!(value(&dev), Value::Int64(5));
+    assert_eq!(value(&prod), Value::Int64(-1));
+
+    // Same result as reading the file for that environment only
+    let from_json = AppConfigurationOffline::from_json_str(&json, "prod", "blue-charge").unwrap();
+    assert_eq!(
+        prod.get_configuration_snapshot().unwrap(),
+        from_json.get_configuration_snapshot().unwrap()
+    );
+
+    let err = dump.open("qa", "blue-charge").unwrap_err();
+    assert!(matches!(
+        err,
+        Error::ConfigurationDataError(ConfigurationDataError::EnvironmentNotFound { ref environment_id, .. }) if environment_id == "qa"
+    ));
+}
+
+#[rstest]
+fn test_open_with_validation_mode(example_configuration_enterprise_path: std::path::PathBuf) {
+    let mut json: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(example_configuration_enterprise_path).unwrap(),
+    )
+    .unwrap();
+    json["segments"].as_array_mut().unwrap().remove(0);
+    let dump = ConfigurationDump::from_json_str(&json.to_string()).unwrap();
+
+    assert!(matches!(
+        dump.open("dev", "blue-charge").unwrap_err(),
+        Error::ConfigurationDataError(ConfigurationDataError::MissingSegments(_))
+    ));
+
+    let client = dump
+        .with_validation_mode(ValidationMode::Lenient)
+        .open("dev", "blue-charge")
+        .unwrap();
+    let snapshot = client.get_configuration_snapshot().unwrap();
+    assert!(!snapshot.validation_report().is_empty());
+    assert!(client.get_feature("f1").is_err());
+    assert!(client.get_feature("f3").is_ok());
+}
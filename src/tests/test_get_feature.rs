@@ -21,7 +21,9 @@ use rstest::*;
 
 use super::client_enterprise;
 use crate::feature::Feature;
-use crate::network::serialization::fixtures::configuration_unordered_segment_rules;
+use crate::network::serialization::fixtures::{
+    configuration_unordered_segment_rules, example_configuration_enterprise_path,
+};
 
 #[rstest]
 fn test_get_feature_doesnt_exist(client_enterprise: Box<dyn AppConfigurationClient>) {
@@ -33,10 +35,41 @@ fn test_get_feature_doesnt_exist(client_enterprise: Box<dyn AppConfigurationClie
     );
 }
 
+#[rstest]
+fn test_find_feature_by_name(example_configuration_enterprise_path: std::path::PathBuf) {
+    let mut json: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(example_configuration_enterprise_path).unwrap(),
+    )
+    .unwrap();
+    let features = json["environments"][0]["features"].as_array_mut().unwrap();
+    for feature in features {
+        if feature["feature_id"] == "f4" {
+            feature["name"] = "F6".into();
+        }
+    }
+    let client = AppConfigurationOffline {
+        config_snapshot: Configuration::from_json_str(&json.to_string(), "dev", "blue-charge")
+            .unwrap()
+            .into(),
+    };
+
+    let feature = client.find_feature_by_name("F1").unwrap();
+    assert_eq!(feature.get_feature_id().unwrap(), "f1");
+
+    assert_eq!(
+        client.find_feature_by_name("f1").unwrap_err().to_string(),
+        "No feature is named 'f1'"
+    );
+    assert!(matches!(
+        client.find_feature_by_name("F6").unwrap_err(),
+        crate::Error::AmbiguousName { kind: "feature", ids, .. } if ids == ["f4", "f6"]
+    ));
+}
+
 #[rstest]
 fn test_get_feature_ordered(configuration_unordered_segment_rules: Configuration) {
     let client = AppConfigurationOffline {
-        config_snapshot: configuration_unordered_segment_rules,
+        config_snapshot: configuration_unordered_segment_rules.into(),
     };
 
     let entity = crate::tests::GenericEntity {
@@ -50,3 +83,67 @@ fn test_get_feature_ordered(configuration_unordered_segment_rules: Configuration
         .unwrap();
     assert!(matches!(value.value, Value::Int64(ref v) if v == &(-49)));
 }
+
+#[rstest]
+fn test_get_feature_with_entity_trait_object(configuration_unordered_segment_rules: Configuration) {
+    let client = AppConfigurationOffline {
+        config_snapshot: configuration_unordered_segment_rules.into(),
+    };
+
+    // Entities built at runtime, e.g. from the context of a request
+    let entity: Box<dyn crate::Entity> = Box::new(crate::tests::GenericEntity {
+        id: "a2".into(),
+        attributes: HashMap::from([("name".into(), Value::from("heinz".to_string()))]),
+    });
+    let feature = client.get_feature("f1").unwrap();
+    let value = feature.get_current_value(entity.as_ref()).unwrap();
+    assert!(matches!(value.value, Value::Int64(-49)));
+    let value: i64 = feature.get_value_into(entity.as_ref()).unwrap();
+    assert_eq!(value, -49);
+
+    let proxy = client.get_feature_proxy("f1").unwrap();
+    let value: i64 = proxy.get_value_into(entity.as_ref()).unwrap();
+    assert_eq!(value, -49);
+}
+
+#[rstest]
+fn test_get_values_for(configuration_unordered_segment_rules: Configuration) {
+    let client = AppConfigurationOffline {
+        config_snapshot: configuration_unordered_segment_rules.into(),
+    };
+
+    let heinz = crate::tests::GenericEntity {
+        id: "a2".into(),
+        attributes: HashMap::from([("name".into(), Value::from("heinz".to_string()))]),
+    };
+    let other = crate::tests::GenericEntity {
+        id: "a3".into(),
+        attributes: HashMap::new(),
+    };
+    let entities: [&dyn crate::Entity; 3] = [&heinz, &other, &heinz];
+    let feature = client.get_feature("f1").unwrap();
+    let values: Vec<Value> = feature
+        .get_values_for(&entities)
+        .into_iter()
+        .collect::<crate::Result<_>>()
+        .unwrap();
+    let expected: Vec<Value> = entities
+        .iter()
+        .map(|entity| feature.get_current_value(*entity).unwrap().value)
+        .collect();
+    assert_eq!(values, expected);
+    assert_eq!(values[0], Value::Int64(-49));
+
+    let proxy = client.get_feature_proxy("f1").unwrap();
+    let values: Vec<Value> = proxy
+        .get_values_for(&entities)
+        .into_iter()
+        .collect::<crate::Result<_>>()
+        .unwrap();
+    assert_eq!(values, expected);
+
+    let proxy = crate::FeatureProxy::new(&client, "missing".to_string());
+    let values = proxy.get_values_for(&entities);
+    assert_eq!(values.len(), 3);
+    assert!(values.iter().all(|value| value.is_err()));
+}
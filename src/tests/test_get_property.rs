@@ -20,7 +20,9 @@ use crate::{ConfigurationProvider, Property, Value};
 use rstest::*;
 
 use super::client_enterprise;
-use crate::network::serialization::fixtures::configuration_unordered_segment_rules;
+use crate::network::serialization::fixtures::{
+    configuration_unordered_segment_rules, example_configuration_enterprise_path,
+};
 
 #[rstest]
 fn test_get_property_doesnt_exist(client_enterprise: Box<dyn AppConfigurationClient>) {
@@ -32,10 +34,45 @@ fn test_get_property_doesnt_exist(client_enterprise: Box<dyn AppConfigurationCli
     );
 }
 
+#[rstest]
+fn test_find_property_by_name(client_enterprise: Box<dyn AppConfigurationClient>) {
+    let property = client_enterprise.find_property_by_name("p2").unwrap();
+    assert_eq!(property.get_property_id().unwrap(), "p2");
+
+    assert!(matches!(
+        client_enterprise.find_property_by_name("P2").unwrap_err(),
+        crate::Error::NameNotFound { kind: "property", name } if name == "P2"
+    ));
+}
+
+#[rstest]
+fn test_get_property_value(client_enterprise: Box<dyn AppConfigurationClient>) {
+    // p1 has targeting rules, its default value is returned
+    assert_eq!(
+        client_enterprise.get_property_value("p1").unwrap(),
+        Value::Int64(5)
+    );
+    assert_eq!(
+        client_enterprise.get_property_value("p4").unwrap(),
+        Value::String("my text".to_string())
+    );
+    assert!(
+        client_enterprise
+            .get_property_value("non-existing")
+            .is_err()
+    );
+
+    let p1 = client_enterprise.get_property("p1").unwrap();
+    assert!(matches!(
+        p1.get_static_value(true).unwrap_err(),
+        crate::Error::PropertyHasTargetingRules { property_id } if property_id == "p1"
+    ));
+}
+
 #[rstest]
 fn test_get_property_ordered(configuration_unordered_segment_rules: Configuration) {
     let client = AppConfigurationOffline {
-        config_snapshot: configuration_unordered_segment_rules,
+        config_snapshot: configuration_unordered_segment_rules.into(),
     };
 
     let entity = crate::tests::GenericEntity {
@@ -49,3 +86,39 @@ fn test_get_property_ordered(configuration_unordered_segment_rules: Configuratio
         .unwrap();
     assert!(matches!(value.value, Value::Int64(ref v) if v == &(-49)));
 }
+
+#[rstest]
+fn test_get_properties_into(example_configuration_enterprise_path: std::path::PathBuf) {
+    #[derive(serde::Deserialize)]
+    struct Settings {
+        p1: u32,
+        p2: String,
+        p4: String,
+        #[serde(default)]
+        not_a_property: Option<String>,
+    }
+    #[derive(Debug, serde::Deserialize)]
+    #[allow(dead_code)]
+    struct WrongType {
+        p4: bool,
+    }
+
+    let client =
+        AppConfigurationOffline::new(&example_configuration_enterprise_path, "dev", "blue-charge")
+            .unwrap();
+    let entity = crate::tests::GenericEntity {
+        id: "a1".into(),
+        attributes: HashMap::new(),
+    };
+
+    let settings: Settings = client.get_properties_into(&entity).unwrap();
+    assert_eq!(settings.p1, 5);
+    assert_eq!(settings.p2, "inherited-property");
+    assert_eq!(settings.p4, "my text");
+    assert_eq!(settings.not_a_property, None);
+
+    let error = client
+        .get_properties_into::<WrongType>(&entity)
+        .unwrap_err();
+    assert!(matches!(error, crate::Error::DeserializationError(_)));
+}
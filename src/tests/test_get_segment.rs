@@ -0,0 +1,67 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use super::{GenericEntity, client_enterprise};
+use crate::client::AppConfigurationClient;
+use crate::{ConfigurationDataError, Error, Value};
+use rstest::*;
+
+#[rstest]
+fn test_get_segment_ids(client_enterprise: Box<dyn AppConfigurationClient>) {
+    let mut segments = client_enterprise.get_segment_ids().unwrap();
+    segments.sort();
+    assert_eq!(segments, vec!["l2dfo8do", "l2dfos8y", "l2dfr61d"]);
+}
+
+#[rstest]
+fn test_get_segment(client_enterprise: Box<dyn AppConfigurationClient>) {
+    let segment = client_enterprise.get_segment("l2dfos8y").unwrap();
+    assert_eq!(segment.id(), "l2dfos8y");
+    assert_eq!(segment.name(), "test1");
+
+    let rules = segment.rules();
+    assert_eq!(rules.len(), 2);
+    assert_eq!(rules[1].attribute_name(), "number");
+    assert_eq!(rules[1].operator(), "greaterThan");
+    assert_eq!(rules[1].values(), ["20"]);
+}
+
+#[rstest]
+fn test_get_segment_doesnt_exist(client_enterprise: Box<dyn AppConfigurationClient>) {
+    let segment = client_enterprise.get_segment("non-existing");
+    assert!(matches!(
+        segment.unwrap_err(),
+        Error::ConfigurationDataError(ConfigurationDataError::SegmentNotFound(ref id)) if id == "non-existing"
+    ));
+}
+
+#[rstest]
+fn test_entity_matches_segment(client_enterprise: Box<dyn AppConfigurationClient>) {
+    let entity = GenericEntity {
+        id: "a1".into(),
+        attributes: HashMap::from([("code".into(), Value::from("AMD-12".to_string()))]),
+    };
+    assert!(
+        client_enterprise
+            .entity_matches_segment("l2dfo8do", &entity)
+            .unwrap()
+    );
+    assert!(
+        !client_enterprise
+            .entity_matches_segment("l2dfos8y", &entity)
+            .unwrap()
+    );
+}
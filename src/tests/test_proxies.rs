@@ -0,0 +1,289 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use rstest::*;
+
+use crate::client::{AppConfigurationClient, ConfigurationProvider};
+use crate::entity::Entity;
+use crate::entity::tests::TrivialEntity;
+use crate::models::{Configuration, FeatureSnapshot, PropertySnapshot, SecretPropertySnapshot};
+use crate::network::serialization::fixtures::example_configuration_enterprise_path;
+use crate::{Feature, FeatureProxy, Property, PropertyProxy, Result, Value};
+
+/// Provider whose configuration can be replaced, like a live client receiving updates.
+struct SwappableConfiguration(Mutex<Configuration>);
+
+impl ConfigurationProvider for SwappableConfiguration {
+    fn get_feature_ids(&self) -> Result<Vec<String>> {
+        self.0.lock().unwrap().get_feature_ids()
+    }
+
+    fn get_feature(&self, feature_id: &str) -> Result<FeatureSnapshot> {
+        self.0.lock().unwrap().get_feature(feature_id)
+    }
+
+    fn get_property_ids(&self) -> Result<Vec<String>> {
+        self.0.lock().unwrap().get_property_ids()
+    }
+
+    fn get_property(&self, property_id: &str) -> Result<PropertySnapshot> {
+        self.0.lock().unwrap().get_property(property_id)
+    }
+
+    fn get_configuration_snapshot(&self) -> Result<Configuration> {
+        Ok(self.0.lock().unwrap().clone())
+    }
+
+    fn is_online(&self) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn wait_until_online(&self) -> bool {
+        true
+    }
+
+    fn get_secret_property(&self, property_id: &str) -> Result<SecretPropertySnapshot> {
+        self.0.lock().unwrap().get_secret_property(property_id)
+    }
+}
+
+/// Enterprise example data with the enabled value of `f6` and the value of `p4` replaced.
+fn configuration(path: &PathBuf, f6: &str, p4: &str) -> Configuration {
+    let mut json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    let environment = &mut json["environments"][0];
+    for feature in environment["features"].as_array_mut().unwrap() {
+        if feature["feature_id"] == "f6" {
+            feature["enabled_value"] = f6.into();
+        }
+    }
+    for property in environment["properties"].as_array_mut().unwrap() {
+        if property["property_id"] == "p4" {
+            property["value"] = p4.into();
+        }
+    }
+    Configuration::from_json_str(&json.to_string(), "dev", "blue-charge").unwrap()
+}
+
+#[rstest]
+fn test_proxies_evaluate_latest_configuration(example_configuration_enterprise_path: PathBuf) {
+    let path = example_configuration_enterprise_path;
+    let client = Arc::new(SwappableConfiguration(Mutex::new(configuration(
+        &path, "before", "before",
+    ))));
+
+    let feature = client.get_feature_proxy("f6").unwrap();
+    let property = client.get_property_proxy("p4").unwrap();
+    let shared_feature = FeatureProxy::shared(client.clone(), "f6");
+    let shared_property = PropertyProxy::shared(client.clone(), "p4");
+    let snapshot = client.get_feature("f6").unwrap();
+
+    let expected = Value::String("before".into());
+    assert_eq!(
+        feature.get_current_value(&TrivialEntity).unwrap().value,
+        expected
+    );
+    assert_eq!(
+        property.get_current_value(&TrivialEntity).unwrap().value,
+        expected
+    );
+    assert_eq!(
+        shared_feature
+            .get_current_value(&TrivialEntity)
+            .unwrap()
+            .value,
+        expected
+    );
+    assert_eq!(
+        shared_property
+            .get_current_value(&TrivialEntity)
+            .unwrap()
+            .value,
+        expected
+    );
+
+    *client.0.lock().unwrap() = configuration(&path, "after", "after");
+
+    let expected = Value::String("after".into());
+    assert_eq!(
+        feature.get_current_value(&TrivialEntity).unwrap().value,
+        expected
+    );
+    assert_eq!(
+        property.get_current_value(&TrivialEntity).unwrap().value,
+        expected
+    );
+    assert_eq!(
+        shared_feature
+            .get_current_value(&TrivialEntity)
+            .unwrap()
+            .value,
+        expected
+    );
+    assert_eq!(
+        shared_property
+            .get_current_value(&TrivialEntity)
+            .unwrap()
+            .value,
+        expected
+    );
+    // Snapshots keep the values from when they were taken
+    assert_eq!(
+        snapshot.get_current_value(&TrivialEntity).unwrap().value,
+        Value::String("before".into())
+    );
+}
+
+#[rstest]
+fn test_shared_proxy_outlives_client_handle(example_configuration_enterprise_path: PathBuf) {
+    let client: Arc<dyn AppConfigurationClient> = Arc::new(
+        crate::AppConfigurationOffline::new(
+            &example_configuration_enterprise_path,
+            "dev",
+            "blue-charge",
+        )
+        .unwrap(),
+    );
+    let feature = FeatureProxy::shared(client, "f3");
+    assert!(feature.get_bool_value(&TrivialEntity).unwrap());
+}
+
+#[rstest]
+fn test_pinned_configuration_ignores_updates(example_configuration_enterprise_path: PathBuf) {
+    let path = example_configuration_enterprise_path;
+    let client = SwappableConfiguration(Mutex::new(configuration(&path, "before", "before")));
+
+    let before = Value::String("before".into());
+    let after = Value::String("after".into());
+    {
+        let pinned = client.pin_configuration().unwrap();
+        let feature = pinned.get_feature_proxy("f6").unwrap();
+
+        *client.0.lock().unwrap() = configuration(&path, "after", "after");
+
+        assert_eq!(
+            client
+                .get_feature("f6")
+                .unwrap()
+                .get_current_value(&TrivialEntity)
+                .unwrap()
+                .value,
+            after
+        );
+        assert_eq!(
+            feature.get_current_value(&TrivialEntity).unwrap().value,
+            before
+        );
+        assert_eq!(
+            pinned
+                .get_property("p4")
+                .unwrap()
+                .get_current_value(&TrivialEntity)
+                .unwrap()
+                .value,
+            before
+        );
+    }
+
+    // Pinning again picks the latest configuration
+    let pinned = client.pin_configuration().unwrap();
+    assert_eq!(
+        pinned
+            .get_feature("f6")
+            .unwrap()
+            .get_current_value(&TrivialEntity)
+            .unwrap()
+            .value,
+        after
+    );
+}
+
+#[rstest]
+fn test_pinned_configurations_are_shared(example_configuration_enterprise_path: PathBuf) {
+    let client = crate::AppConfigurationOffline::new(
+        &example_configuration_enterprise_path,
+        "dev",
+        "blue-charge",
+    )
+    .unwrap();
+
+    let first = client.pin_configuration().unwrap();
+    let second = client.pin_configuration().unwrap();
+    assert!(std::ptr::eq(first.configuration(), second.configuration()));
+
+    let session = client.evaluation_session(&TrivialEntity).unwrap();
+    assert!(std::ptr::eq(
+        first.configuration(),
+        session.configuration().configuration()
+    ));
+}
+
+/// Entity counting how many times its attributes are collected.
+#[derive(Default)]
+struct CountingEntity(Cell<usize>);
+
+impl Entity for CountingEntity {
+    fn get_id(&self) -> String {
+        "a1".into()
+    }
+
+    fn get_attributes(&self) -> HashMap<String, Value> {
+        self.0.set(self.0.get() + 1);
+        HashMap::new()
+    }
+}
+
+#[rstest]
+fn test_evaluation_session(example_configuration_enterprise_path: PathBuf) {
+    let path = example_configuration_enterprise_path;
+    let client = SwappableConfiguration(Mutex::new(configuration(&path, "before", "before")));
+    let entity = CountingEntity::default();
+
+    let session = client.evaluation_session(&entity).unwrap();
+    assert_eq!(session.entity_id(), "a1");
+    assert!(session.get_bool("f3").unwrap());
+    assert_eq!(session.get_string("p4").unwrap(), "before");
+
+    *client.0.lock().unwrap() = configuration(&path, "after", "after");
+
+    assert_eq!(session.get_string("p4").unwrap(), "before");
+    assert_eq!(
+        session.get_feature_value("f6").unwrap().value,
+        Value::String("before".into())
+    );
+    let value: String = session.get_feature_value_into("f6").unwrap();
+    assert_eq!(value, "before");
+    assert!(session.get_bool("f6").is_err());
+    assert_eq!(entity.0.get(), 1);
+}
+
+// Providers implementing only the required methods get the segments from the
+// configuration snapshot.
+#[rstest]
+fn test_default_segment_methods(example_configuration_enterprise_path: PathBuf) {
+    let client = SwappableConfiguration(Mutex::new(
+        Configuration::from_file(&example_configuration_enterprise_path, "dev", "blue-charge")
+            .unwrap(),
+    ));
+
+    let mut segments = client.get_segment_ids().unwrap();
+    segments.sort();
+    assert_eq!(segments, vec!["l2dfo8do", "l2dfos8y", "l2dfr61d"]);
+    assert_eq!(client.get_segment("l2dfos8y").unwrap().name(), "test1");
+}
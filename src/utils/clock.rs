@@ -0,0 +1,87 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+
+/// Source of the current time used when evaluating features and properties.
+///
+/// Time-based targeting (scheduled targeting rules, progressive rollouts) is
+/// resolved against this clock at evaluation time, so flags flip at the
+/// scheduled moment even between configuration refreshes. Tests can inject a
+/// fixed or manually advanced clock to simulate time.
+pub trait Clock: Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> SystemTime;
+}
+
+impl std::fmt::Debug for dyn Clock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Clock").field("now", &self.now()).finish()
+    }
+}
+
+/// [`Clock`] backed by the system wall-clock. Used unless another clock is injected.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// Milliseconds since the Unix epoch for the current time of `clock`.
+pub(crate) fn now_millis(clock: &dyn Clock) -> i64 {
+    DateTime::<Utc>::from(clock.now()).timestamp_millis()
+}
+
+/// Parses an RFC 3339 timestamp into milliseconds since the Unix epoch.
+pub(crate) fn parse_timestamp_millis(timestamp: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|t| t.timestamp_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    struct FixedClock(SystemTime);
+
+    impl Clock for FixedClock {
+        fn now(&self) -> SystemTime {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_now_millis() {
+        let clock = FixedClock(SystemTime::UNIX_EPOCH + Duration::from_millis(1_234));
+        assert_eq!(now_millis(&clock), 1_234);
+    }
+
+    #[test]
+    fn test_parse_timestamp_millis() {
+        assert_eq!(parse_timestamp_millis("1970-01-01T00:00:01Z"), Some(1_000));
+        assert_eq!(
+            parse_timestamp_millis("1970-01-01T01:00:01+01:00"),
+            Some(1_000)
+        );
+        assert_eq!(parse_timestamp_millis("yesterday"), None);
+    }
+}
@@ -0,0 +1,81 @@
+// Copyright 2026 IBM Corp. All Rights Reserved.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//       http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Locks that never fail.
+//!
+//! A lock is poisoned when a thread panics while holding it. The values kept
+//! behind the locks of this crate are replaced as a whole, never left half
+//! written, so a poisoned lock still holds a valid value and is recovered
+//! instead of failing every later access.
+//!
+//! Most locks are only taken to read or replace their value, and listeners
+//! are called after releasing them. The few that are nested are always taken
+//! in the order documented in `network::live_configuration`.
+
+use std::sync::{Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+pub(crate) trait MutexExt<T: ?Sized> {
+    /// Like [`Mutex::lock`], recovering the guard if the mutex is poisoned.
+    fn lock_unpoisoned(&self) -> MutexGuard<'_, T>;
+}
+
+impl<T: ?Sized> MutexExt<T> for Mutex<T> {
+    fn lock_unpoisoned(&self) -> MutexGuard<'_, T> {
+        self.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+pub(crate) trait RwLockExt<T> {
+    /// Like [`RwLock::read`], recovering the guard if the lock is poisoned.
+    fn read_unpoisoned(&self) -> RwLockReadGuard<'_, T>;
+
+    /// Like [`RwLock::write`], recovering the guard if the lock is poisoned.
+    fn write_unpoisoned(&self) -> RwLockWriteGuard<'_, T>;
+}
+
+impl<T> RwLockExt<T> for RwLock<T> {
+    fn read_unpoisoned(&self) -> RwLockReadGuard<'_, T> {
+        self.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write_unpoisoned(&self) -> RwLockWriteGuard<'_, T> {
+        self.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_poisoned_locks_are_recovered() {
+        let mutex = Arc::new(Mutex::new(1));
+        let rwlock = Arc::new(RwLock::new(1));
+        let (m, r) = (mutex.clone(), rwlock.clone());
+        let _ = std::thread::spawn(move || {
+            let _m = m.lock().unwrap();
+            let _r = r.write().unwrap();
+            panic!("poison the locks");
+        })
+        .join();
+        assert!(mutex.is_poisoned());
+        assert!(rwlock.is_poisoned());
+
+        *mutex.lock_unpoisoned() += 1;
+        *rwlock.write_unpoisoned() += 1;
+        assert_eq!(*mutex.lock_unpoisoned(), 2);
+        assert_eq!(*rwlock.read_unpoisoned(), 2);
+    }
+}
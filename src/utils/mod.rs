@@ -12,12 +12,29 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod clock;
+mod lock;
+mod ready;
 mod rollout_parser;
+#[cfg(feature = "remote")]
+mod scheduler;
 mod thread_handle;
+#[cfg(feature = "remote")]
 mod waitable;
 
+pub use clock::{Clock, SystemClock};
+pub(crate) use clock::{now_millis, parse_timestamp_millis};
+pub(crate) use lock::{MutexExt, RwLockExt};
+pub use ready::Ready;
+#[cfg(feature = "remote")]
+pub(crate) use ready::ReadySignal;
 pub(crate) use rollout_parser::{
     get_current_rollout_percentage, parse_rollout_configuration_phases,
 };
-pub(crate) use thread_handle::{ThreadHandle, ThreadStatus};
+#[cfg(feature = "remote")]
+pub(crate) use scheduler::{ScheduledTask, TaskRun, TaskWaker};
+pub use thread_handle::ThreadOptions;
+#[cfg(feature = "remote")]
+pub(crate) use thread_handle::{ThreadHandle, ThreadStatus, panic_message};
+#[cfg(feature = "remote")]
 pub(crate) use waitable::Waitable;
@@ -0,0 +1,139 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use super::MutexExt;
+
+/// Set once a client has a configuration to serve, and never reset.
+#[derive(Debug, Default)]
+pub(crate) struct ReadySignal {
+    state: Mutex<ReadyState>,
+}
+
+#[derive(Debug, Default)]
+struct ReadyState {
+    ready: bool,
+    wakers: Vec<Waker>,
+}
+
+#[cfg_attr(not(feature = "remote"), allow(dead_code))]
+impl ReadySignal {
+    pub(crate) fn set(&self) {
+        let wakers = {
+            let mut state = self.state.lock_unpoisoned();
+            if state.ready {
+                return;
+            }
+            state.ready = true;
+            std::mem::take(&mut state.wakers)
+        };
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+/// Future resolving once the client has loaded its first configuration, see
+/// [`ConfigurationProvider::ready`](crate::ConfigurationProvider::ready).
+///
+/// It does not depend on any async runtime. Once resolved it stays resolved:
+/// the futures created afterwards are ready right away, even if the client
+/// went offline in the meantime.
+#[derive(Debug, Clone)]
+pub struct Ready {
+    /// `None` for clients that are always ready.
+    signal: Option<Arc<ReadySignal>>,
+}
+
+impl Ready {
+    #[cfg_attr(not(feature = "remote"), allow(dead_code))]
+    pub(crate) fn new(signal: Arc<ReadySignal>) -> Self {
+        Self {
+            signal: Some(signal),
+        }
+    }
+
+    pub(crate) fn resolved() -> Self {
+        Self { signal: None }
+    }
+
+    /// Whether the future is resolved, to check readiness without awaiting.
+    pub fn is_ready(&self) -> bool {
+        self.signal
+            .as_ref()
+            .is_none_or(|signal| signal.state.lock_unpoisoned().ready)
+    }
+}
+
+impl Future for Ready {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let Some(signal) = &self.signal else {
+            return Poll::Ready(());
+        };
+        let mut state = signal.state.lock_unpoisoned();
+        if state.ready {
+            return Poll::Ready(());
+        }
+        if !state.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            state.wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::Wake;
+
+    #[derive(Default)]
+    struct CountingWaker(std::sync::atomic::AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_ready() {
+        let signal = Arc::new(ReadySignal::default());
+        let counter = Arc::new(CountingWaker::default());
+        let waker = Waker::from(counter.clone());
+        let mut context = Context::from_waker(&waker);
+
+        let mut ready = Ready::new(signal.clone());
+        assert!(!ready.is_ready());
+        assert_eq!(Pin::new(&mut ready).poll(&mut context), Poll::Pending);
+        // Polling again does not register the same waker twice
+        assert_eq!(Pin::new(&mut ready).poll(&mut context), Poll::Pending);
+
+        signal.set();
+        signal.set();
+        assert_eq!(counter.0.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(Pin::new(&mut ready).poll(&mut context), Poll::Ready(()));
+
+        // Level-triggered: later futures are ready right away
+        let mut later = Ready::new(signal);
+        assert!(later.is_ready());
+        assert_eq!(Pin::new(&mut later).poll(&mut context), Poll::Ready(()));
+        assert!(Ready::resolved().is_ready());
+    }
+}
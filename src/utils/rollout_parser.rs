@@ -33,9 +33,9 @@ use std::collections::BTreeMap;
 /// let config = RolloutConfiguration {
 ///     start_at: "2024-01-01T00:00:00Z".to_string(),
 ///     phases: vec![
-///         RolloutPhase { percentage: 10, duration: Some(1), duration_type: Some("days".to_string()) },
-///         RolloutPhase { percentage: 50, duration: Some(2), duration_type: Some("days".to_string()) },
-///         RolloutPhase { percentage: 100, duration: None, duration_type: None },
+///         RolloutPhase { percentage: 10, duration: Some(1), duration_type: Some("days".to_string()), ramp: false },
+///         RolloutPhase { percentage: 50, duration: Some(2), duration_type: Some("days".to_string()), ramp: false },
+///         RolloutPhase { percentage: 100, duration: None, duration_type: None, ramp: false },
 ///     ],
 /// };
 /// let btree = parse_rollout_configuration_phases(&config)?;
@@ -60,7 +60,19 @@ pub fn parse_rollout_configuration_phases(
 
     let mut transition_time = start_timestamp;
 
-    for phase in &configuration.phases {
+    // Checked upfront, as ramps look at the percentage of the next phase
+    if let Some(phase) = configuration
+        .phases
+        .iter()
+        .find(|phase| phase.percentage > 100)
+    {
+        return Err(Error::ProtocolError(format!(
+            "Invalid rollout percentage: {}. Must be between 0 and 100",
+            phase.percentage
+        )));
+    }
+
+    for (index, phase) in configuration.phases.iter().enumerate() {
         // Insert the percentage at the current transition time
         btree.insert(transition_time, phase.percentage);
 
@@ -77,17 +89,57 @@ pub fn parse_rollout_configuration_phases(
                     )));
                 }
             };
-            transition_time += duration_ms;
+            let next_transition_time =
+                transition_time.checked_add(duration_ms).ok_or_else(|| {
+                    Error::ProtocolError(
+                        "Rollout phases end past the representable time".to_string(),
+                    )
+                })?;
+            if phase.ramp
+                && let Some(next_phase) = configuration.phases.get(index + 1)
+            {
+                insert_ramp_steps(
+                    &mut btree,
+                    transition_time,
+                    duration_ms,
+                    phase.percentage,
+                    next_phase.percentage,
+                );
+            }
+            transition_time = next_transition_time;
         }
     }
 
     Ok(btree)
 }
 
+/// Inserts a step for each percentage between `from` and `to`, both excluded,
+/// evenly spread over `duration_ms` after `start`: percentages are compared with
+/// whole-number hashes, so the ramp is as smooth as a continuous one.
+fn insert_ramp_steps(
+    btree: &mut BTreeMap<i64, u32>,
+    start: i64,
+    duration_ms: i64,
+    from: u32,
+    to: u32,
+) {
+    let steps = i64::from(from.abs_diff(to));
+    for step in 1..steps {
+        let percentage = if to > from {
+            from + step as u32
+        } else {
+            from - step as u32
+        };
+        // Cannot overflow: the offset is at most `duration_ms`.
+        let offset = i128::from(duration_ms) * i128::from(step) / i128::from(steps);
+        btree.insert(start + offset as i64, percentage);
+    }
+}
+
 /// Get the current rollout percentage from a BTreeMap at a given timestamp.
 ///
 /// This function finds the entry with the largest key that is less than or equal to
-
+/// the given timestamp.
 ///
 /// # Arguments
 /// * `btree` - The BTreeMap containing timestamp-to-percentage mappings
@@ -117,16 +169,19 @@ mod tests {
                     percentage: 10,
                     duration: Some(1),
                     duration_type: Some("days".to_string()),
+                    ramp: false,
                 },
                 RolloutPhase {
                     percentage: 50,
                     duration: Some(2),
                     duration_type: Some("days".to_string()),
+                    ramp: false,
                 },
                 RolloutPhase {
                     percentage: 100,
                     duration: None,
                     duration_type: None,
+                    ramp: false,
                 },
             ],
         };
@@ -149,6 +204,58 @@ mod tests {
         assert_eq!(btree.get(&day3_ts), Some(&100));
     }
 
+    #[test]
+    fn test_ramp_phase() {
+        let config = RolloutConfiguration {
+            start_at: "2024-01-01T00:00:00Z".to_string(),
+            phases: vec![
+                RolloutPhase {
+                    percentage: 10,
+                    duration: Some(10),
+                    duration_type: Some("hours".to_string()),
+                    ramp: true,
+                },
+                RolloutPhase {
+                    percentage: 60,
+                    duration: Some(1),
+                    duration_type: Some("hours".to_string()),
+                    ramp: true,
+                },
+                RolloutPhase {
+                    percentage: 40,
+                    duration: None,
+                    duration_type: None,
+                    ramp: true,
+                },
+            ],
+        };
+        let btree = parse_rollout_configuration_phases(&config).unwrap();
+        let start_ts = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+            .unwrap()
+            .timestamp_millis();
+        let at = |minutes: i64| get_current_rollout_percentage(&btree, start_ts + minutes * 60_000);
+
+        assert_eq!(at(-1), 0);
+        assert_eq!(at(0), 10);
+        // 5 points per hour
+        assert_eq!(at(12), 11);
+        assert_eq!(at(5 * 60), 35);
+        assert_eq!(at(10 * 60 - 1), 59);
+        assert_eq!(at(10 * 60), 60);
+        // Down to the last phase, which has no duration to ramp over
+        assert_eq!(at(10 * 60 + 30), 50);
+        assert_eq!(at(11 * 60), 40);
+        assert_eq!(at(100 * 60), 40);
+
+        // Serialized only when set
+        let json = serde_json::to_value(&config.phases[2]).unwrap();
+        assert_eq!(json["ramp"], true);
+        let phase: RolloutPhase =
+            serde_json::from_str(r#"{"percentage": 5, "duration": null, "duration_type": null}"#)
+                .unwrap();
+        assert!(!phase.ramp);
+    }
+
     #[test]
     fn test_get_current_rollout_percentage() {
         let mut btree = BTreeMap::new();
@@ -192,10 +299,68 @@ mod tests {
                 percentage: 10,
                 duration: Some(1),
                 duration_type: Some("invalid".to_string()),
+                ramp: false,
             }],
         };
 
         let result = parse_rollout_configuration_phases(&config);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_invalid_percentage() {
+        let config = RolloutConfiguration {
+            start_at: "2024-01-01T00:00:00Z".to_string(),
+            phases: vec![
+                RolloutPhase {
+                    percentage: 10,
+                    duration: Some(1),
+                    duration_type: Some("days".to_string()),
+                    ramp: true,
+                },
+                RolloutPhase {
+                    percentage: u32::MAX,
+                    duration: None,
+                    duration_type: None,
+                    ramp: false,
+                },
+            ],
+        };
+
+        let result = parse_rollout_configuration_phases(&config);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_phases_past_representable_time() {
+        let phase = RolloutPhase {
+            percentage: 0,
+            duration: Some(u32::MAX),
+            duration_type: Some("days".to_string()),
+            ramp: true,
+        };
+        let config = RolloutConfiguration {
+            start_at: "9999-12-31T23:59:59Z".to_string(),
+            phases: vec![phase.clone(); 100],
+        };
+
+        let result = parse_rollout_configuration_phases(&config);
+        assert!(result.is_err());
+
+        // A single long ramp must not overflow when spreading its steps
+        let config = RolloutConfiguration {
+            start_at: "2024-01-01T00:00:00Z".to_string(),
+            phases: vec![
+                phase,
+                RolloutPhase {
+                    percentage: 100,
+                    duration: None,
+                    duration_type: None,
+                    ramp: false,
+                },
+            ],
+        };
+        let btree = parse_rollout_configuration_phases(&config).unwrap();
+        assert_eq!(btree.len(), 102);
+    }
 }
@@ -0,0 +1,361 @@
+// (C) Copyright IBM Corp. 2025.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use super::lock::MutexExt;
+use super::thread_handle::{ThreadOptions, panic_message, spawn};
+
+/// The scheduler threads running, one for each set of thread options. A
+/// thread exits, and is removed, once it has no tasks left.
+static SCHEDULERS: Mutex<Vec<(ThreadOptions, Arc<Scheduler>)>> = Mutex::new(Vec::new());
+
+type TaskFn = Box<dyn FnMut(TaskRun) -> ControlFlow<()> + Send>;
+
+/// Why a task runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TaskRun {
+    /// Its period elapsed, or it was woken up with [`TaskWaker::wake`].
+    Due,
+    /// Its [`ScheduledTask`] was dropped, it does not run again.
+    Last,
+}
+
+#[derive(Debug, Default)]
+struct TaskFlags {
+    woken: AtomicBool,
+    finishing: AtomicBool,
+}
+
+struct Task {
+    period: Duration,
+    next_run: Instant,
+    flags: Arc<TaskFlags>,
+    run: TaskFn,
+}
+
+impl Task {
+    fn is_due(&self, now: Instant) -> bool {
+        self.next_run <= now
+            || self.flags.woken.load(Ordering::Relaxed)
+            || self.flags.finishing.load(Ordering::Relaxed)
+    }
+
+    /// Runs the task, returning it if it runs again later. A panic drops it,
+    /// so the other tasks of the thread go on.
+    fn run(mut self) -> Option<Self> {
+        self.flags.woken.store(false, Ordering::Relaxed);
+        let run = if self.flags.finishing.load(Ordering::Relaxed) {
+            TaskRun::Last
+        } else {
+            TaskRun::Due
+        };
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| (self.run)(run))) {
+            Ok(ControlFlow::Continue(())) if run == TaskRun::Due => {
+                self.next_run = Instant::now() + self.period;
+                Some(self)
+            }
+            Ok(_) => None,
+            Err(payload) => {
+                log::error!("Scheduled task stopped: {}", panic_message(&*payload));
+                None
+            }
+        }
+    }
+}
+
+/// Periodic tasks sharing a thread, instead of a thread per task.
+///
+/// Tasks run one at a time: a slow task delays the other tasks of the thread,
+/// so only short tasks, or tasks that may be late, are scheduled. The threads
+/// are only shared by tasks scheduled with the same [`ThreadOptions`].
+#[derive(Default)]
+struct Scheduler {
+    tasks: Mutex<Vec<Task>>,
+    /// Notified when a task is added, woken up or finishing.
+    changed: Condvar,
+}
+
+impl Scheduler {
+    fn notify(&self) {
+        // Taking the lock makes sure the thread is not between looking at the
+        // tasks and waiting, where it would miss the notification
+        drop(self.tasks.lock_unpoisoned());
+        self.changed.notify_one();
+    }
+
+    /// Runs the tasks as they become due, until none is left.
+    fn run(self: Arc<Self>) {
+        let mut tasks = self.tasks.lock_unpoisoned();
+        loop {
+            if tasks.is_empty() {
+                // Locked in the same order as `ScheduledTask::new`
+                drop(tasks);
+                let mut schedulers = SCHEDULERS.lock_unpoisoned();
+                tasks = self.tasks.lock_unpoisoned();
+                if tasks.is_empty() {
+                    schedulers.retain(|(_, scheduler)| !Arc::ptr_eq(scheduler, &self));
+                    return;
+                }
+                continue;
+            }
+
+            let now = Instant::now();
+            let (due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut *tasks)
+                .into_iter()
+                .partition(|task| task.is_due(now));
+            *tasks = pending;
+            if due.is_empty() {
+                let timeout = tasks
+                    .iter()
+                    .map(|task| task.next_run.saturating_duration_since(now))
+                    .min()
+                    .unwrap_or_default();
+                tasks = self
+                    .changed
+                    .wait_timeout(tasks, timeout)
+                    .unwrap_or_else(|e| e.into_inner())
+                    .0;
+                continue;
+            }
+
+            // Run without the lock, so tasks can be added meanwhile
+            drop(tasks);
+            let due: Vec<_> = due.into_iter().filter_map(Task::run).collect();
+            tasks = self.tasks.lock_unpoisoned();
+            tasks.extend(due);
+        }
+    }
+}
+
+/// A task run every `period` by a thread shared with the other tasks
+/// scheduled with the same [`ThreadOptions`].
+///
+/// When dropped, the task runs one last time, with [`TaskRun::Last`], and is
+/// removed. The thread exits once it has no tasks left.
+pub(crate) struct ScheduledTask {
+    scheduler: Arc<Scheduler>,
+    flags: Arc<TaskFlags>,
+}
+
+impl std::fmt::Debug for ScheduledTask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScheduledTask")
+            .field("flags", &self.flags)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ScheduledTask {
+    /// Runs `f` every `period`, the first time right away. The task is removed
+    /// once `f` returns [`ControlFlow::Break`].
+    ///
+    /// Panics if a new thread is needed and cannot be spawned, like
+    /// [`std::thread::spawn`].
+    pub(crate) fn new(
+        options: &ThreadOptions,
+        period: Duration,
+        f: impl FnMut(TaskRun) -> ControlFlow<()> + Send + 'static,
+    ) -> Self {
+        let mut schedulers = SCHEDULERS.lock_unpoisoned();
+        let scheduler = match schedulers.iter().find(|(o, _)| o == options) {
+            Some((_, scheduler)) => scheduler.clone(),
+            None => {
+                let scheduler = Arc::new(Scheduler::default());
+                schedulers.push((options.clone(), scheduler.clone()));
+                let thread_scheduler = scheduler.clone();
+                spawn(options, move || thread_scheduler.run());
+                scheduler
+            }
+        };
+        let flags = Arc::new(TaskFlags::default());
+        scheduler.tasks.lock_unpoisoned().push(Task {
+            period,
+            next_run: Instant::now(),
+            flags: flags.clone(),
+            run: Box::new(f),
+        });
+        drop(schedulers);
+        scheduler.changed.notify_one();
+        Self { scheduler, flags }
+    }
+
+    /// Runs the task as soon as the thread is free, e.g. when work piled up
+    /// before the end of its period.
+    pub(crate) fn waker(&self) -> TaskWaker {
+        TaskWaker {
+            scheduler: self.scheduler.clone(),
+            flags: self.flags.clone(),
+        }
+    }
+}
+
+impl Drop for ScheduledTask {
+    fn drop(&mut self) {
+        self.flags.finishing.store(true, Ordering::Relaxed);
+        self.scheduler.notify();
+    }
+}
+
+/// Runs a [`ScheduledTask`] before the end of its period.
+#[derive(Clone)]
+pub(crate) struct TaskWaker {
+    scheduler: Arc<Scheduler>,
+    flags: Arc<TaskFlags>,
+}
+
+impl std::fmt::Debug for TaskWaker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskWaker")
+            .field("flags", &self.flags)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TaskWaker {
+    pub(crate) fn wake(&self) {
+        if !self.flags.woken.swap(true, Ordering::Relaxed) {
+            self.scheduler.notify();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU32;
+    use std::sync::mpsc;
+
+    use super::*;
+
+    /// Options of a thread used by a single test, the tests run in parallel.
+    fn options(name: &str) -> ThreadOptions {
+        ThreadOptions::default().with_name(format!("test-{name}"))
+    }
+
+    fn is_running(options: &ThreadOptions) -> bool {
+        SCHEDULERS
+            .lock_unpoisoned()
+            .iter()
+            .any(|(o, _)| o == options)
+    }
+
+    #[test]
+    fn test_tasks_run_periodically() {
+        let (runs_sender, runs) = mpsc::channel();
+        let task = ScheduledTask::new(
+            &options("periodic"),
+            Duration::from_millis(20),
+            move |run| {
+                let thread = std::thread::current().name().map(str::to_string);
+                runs_sender.send((run, thread)).unwrap();
+                ControlFlow::Continue(())
+            },
+        );
+
+        let timeout = Duration::from_secs(5);
+        let thread = Some("test-periodic".to_string());
+        for _ in 0..3 {
+            assert_eq!(
+                runs.recv_timeout(timeout).unwrap(),
+                (TaskRun::Due, thread.clone())
+            );
+        }
+        drop(task);
+        while runs.recv_timeout(timeout).unwrap().0 == TaskRun::Due {}
+        // Not run anymore after the last run
+        assert!(runs.recv_timeout(Duration::from_millis(100)).is_err());
+    }
+
+    #[test]
+    fn test_tasks_share_a_thread() {
+        let options = options("shared");
+        let (runs_sender, runs) = mpsc::channel();
+        let new_task = |id: u32| {
+            let runs_sender = runs_sender.clone();
+            ScheduledTask::new(&options, Duration::from_secs(3600), move |_| {
+                runs_sender.send((id, std::thread::current().id())).unwrap();
+                ControlFlow::Continue(())
+            })
+        };
+        let first = new_task(1);
+        let second = new_task(2);
+
+        let timeout = Duration::from_secs(5);
+        let (_, first_thread) = runs.recv_timeout(timeout).unwrap();
+        let (_, second_thread) = runs.recv_timeout(timeout).unwrap();
+        assert_eq!(first_thread, second_thread);
+
+        // The thread exits with its last task
+        drop(first);
+        drop(second);
+        let deadline = Instant::now() + timeout;
+        while is_running(&options) {
+            assert!(Instant::now() < deadline);
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_wake() {
+        let (runs_sender, runs) = mpsc::channel();
+        let task = ScheduledTask::new(&options("wake"), Duration::from_secs(3600), move |_| {
+            runs_sender.send(()).unwrap();
+            ControlFlow::Continue(())
+        });
+        let timeout = Duration::from_secs(5);
+        runs.recv_timeout(timeout).unwrap();
+        assert!(runs.recv_timeout(Duration::from_millis(50)).is_err());
+
+        task.waker().wake();
+        runs.recv_timeout(timeout).unwrap();
+    }
+
+    #[test]
+    fn test_panicking_task_is_removed() {
+        let runs = Arc::new(AtomicU32::new(0));
+        let options = options("panic");
+        let _panicking = ScheduledTask::new(&options, Duration::ZERO, |_| panic!("task bug"));
+        let _task = {
+            let runs = runs.clone();
+            ScheduledTask::new(&options, Duration::from_millis(10), move |_| {
+                runs.fetch_add(1, Ordering::SeqCst);
+                ControlFlow::Continue(())
+            })
+        };
+
+        // The other tasks of the thread go on
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while runs.load(Ordering::SeqCst) < 3 {
+            assert!(Instant::now() < deadline);
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_break_removes_the_task() {
+        let runs = Arc::new(AtomicU32::new(0));
+        let _task = {
+            let runs = runs.clone();
+            ScheduledTask::new(&options("break"), Duration::ZERO, move |_| {
+                runs.fetch_add(1, Ordering::SeqCst);
+                ControlFlow::Break(())
+            })
+        };
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+}
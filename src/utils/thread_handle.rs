@@ -12,10 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "remote")]
 use std::sync::mpsc::Receiver;
+#[cfg(feature = "remote")]
 use std::thread::JoinHandle;
+#[cfg(feature = "remote")]
 use std::time::Duration;
 
+use crate::errors::{Error, Result};
+
+#[cfg(feature = "remote")]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ThreadStatus<ResultType> {
     #[allow(dead_code)]
@@ -24,10 +30,134 @@ pub enum ThreadStatus<ResultType> {
     FailedInternalError(String),
 }
 
+/// Returns the message a thread panicked with.
+#[cfg(feature = "remote")]
+pub(crate) fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(panic_msg) = payload.downcast_ref::<String>() {
+        format!("Thread panicked: {}", panic_msg)
+    } else if let Some(panic_msg) = payload.downcast_ref::<&str>() {
+        format!("Thread panicked: {}", panic_msg)
+    } else {
+        "Thread panicked".to_string()
+    }
+}
+
+/// Name given to the background threads of the SDK when
+/// [`ThreadOptions::name`] is not set.
+#[cfg(feature = "remote")]
+pub(crate) const DEFAULT_THREAD_NAME: &str = "appconfig-sync";
+
+/// Niceness added to threads with [`ThreadOptions::low_priority`].
+#[cfg(all(feature = "remote", target_os = "linux"))]
+const LOW_PRIORITY_NICE_INCREMENT: i32 = 10;
+
+/// How the background threads of the SDK are spawned.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThreadOptions {
+    /// Thread name shown by debuggers, profilers and `ps`. `None` (the default)
+    /// names them `appconfig-sync`, and `appconfig-meter` for the metering
+    /// thread, which the clients with the same options share. On Linux only
+    /// the first 15 bytes are shown.
+    pub name: Option<String>,
+
+    /// Stack size in bytes. `None` (the default) uses the standard library
+    /// default, which can be changed with the `RUST_MIN_STACK` variable.
+    pub stack_size: Option<usize>,
+
+    /// Runs the threads with a lower scheduling priority (a niceness increased
+    /// by 10), so refreshes and metering yield to the application under load.
+    /// Only supported on Linux, ignored on other platforms.
+    pub low_priority: bool,
+}
+
+impl ThreadOptions {
+    /// Sets [`ThreadOptions::name`].
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets [`ThreadOptions::stack_size`].
+    pub fn with_stack_size(mut self, bytes: usize) -> Self {
+        self.stack_size = Some(bytes);
+        self
+    }
+
+    /// Sets [`ThreadOptions::low_priority`].
+    pub fn with_low_priority(mut self, low_priority: bool) -> Self {
+        self.low_priority = low_priority;
+        self
+    }
+
+    /// Checks that threads can be spawned with these options: names cannot
+    /// contain NUL bytes.
+    pub fn validate(&self) -> Result<()> {
+        if self.name.as_ref().is_some_and(|name| name.contains('\0')) {
+            return Err(Error::Other(
+                "thread_options.name cannot contain NUL bytes".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Lowers the scheduling priority of the calling thread. Best effort: failures
+/// are only logged.
+#[cfg(all(feature = "remote", target_os = "linux"))]
+fn lower_current_thread_priority() {
+    // On Linux the niceness is a per-thread attribute, so this leaves the other
+    // threads of the process untouched.
+    // SAFETY: `nice` has no memory-safety preconditions.
+    let result = unsafe {
+        *libc::__errno_location() = 0;
+        libc::nice(LOW_PRIORITY_NICE_INCREMENT)
+    };
+    // -1 is also a valid niceness, errno tells the difference
+    if result == -1 && std::io::Error::last_os_error().raw_os_error() != Some(0) {
+        log::warn!(
+            "Failed to lower the priority of the SDK thread: {}",
+            std::io::Error::last_os_error()
+        );
+    }
+}
+
+#[cfg(all(feature = "remote", not(target_os = "linux")))]
+fn lower_current_thread_priority() {}
+
+/// Spawns a thread running `f` as given by `options`.
+///
+/// Panics if the thread cannot be spawned, like [`std::thread::spawn`].
+#[cfg(feature = "remote")]
+pub(crate) fn spawn<T, F>(options: &ThreadOptions, f: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let mut builder = std::thread::Builder::new().name(
+        options
+            .name
+            .clone()
+            .unwrap_or_else(|| DEFAULT_THREAD_NAME.to_string()),
+    );
+    if let Some(stack_size) = options.stack_size {
+        builder = builder.stack_size(stack_size);
+    }
+    let low_priority = options.low_priority;
+    builder
+        .spawn(move || {
+            if low_priority {
+                lower_current_thread_priority();
+            }
+            f()
+        })
+        .expect("failed to spawn thread")
+}
+
 /// Wrapper around a thread that offers the following capabilities:
 /// * The thread is terminated when the object goes out of scope
 /// * A `get_thread_status` method to check if thread is running or
 ///   finished with a specific result
+#[cfg(feature = "remote")]
 #[derive(Debug)]
 pub(crate) struct ThreadHandle<ResultType: Send + Clone + 'static> {
     pub(crate) _thread_termination_sender: std::sync::mpsc::Sender<()>,
@@ -35,15 +165,29 @@ pub(crate) struct ThreadHandle<ResultType: Send + Clone + 'static> {
     pub(crate) finished_thread_status_cached: Option<ThreadStatus<ResultType>>,
 }
 
+#[cfg(feature = "remote")]
 impl<ResultType: Send + Clone + 'static> ThreadHandle<ResultType> {
+    #[cfg(test)]
     pub(crate) fn new<F>(f: F) -> Self
     where
         F: FnOnce(Receiver<()>) -> ResultType,
         F: Send + 'static,
     {
-        let (thread_termination_sender, thread_termination_receiver) = std::sync::mpsc::channel();
+        Self::with_options(&ThreadOptions::default(), f)
+    }
 
-        let t: JoinHandle<ResultType> = std::thread::spawn(move || f(thread_termination_receiver));
+    /// Like [`ThreadHandle::new`], spawning the thread as given by `options`.
+    ///
+    /// Panics if the thread cannot be spawned, like [`std::thread::spawn`], or
+    /// if `options` are invalid: the clients check them with
+    /// [`ThreadOptions::validate`] beforehand.
+    pub(crate) fn with_options<F>(options: &ThreadOptions, f: F) -> Self
+    where
+        F: FnOnce(Receiver<()>) -> ResultType,
+        F: Send + 'static,
+    {
+        let (thread_termination_sender, thread_termination_receiver) = std::sync::mpsc::channel();
+        let t = spawn(options, move || f(thread_termination_receiver));
 
         Self {
             _thread_termination_sender: thread_termination_sender,
@@ -60,21 +204,7 @@ impl<ResultType: Send + Clone + 'static> ThreadHandle<ResultType> {
                 if t.is_finished() {
                     let thread_finished_status = match t.join() {
                         Ok(r) => ThreadStatus::Finished(r),
-                        Err(e) => {
-                            if let Some(panic_msg) = e.downcast_ref::<String>() {
-                                ThreadStatus::FailedInternalError(format!(
-                                    "Thread panicked: {}",
-                                    panic_msg
-                                ))
-                            } else if let Some(panic_msg) = e.downcast_ref::<&str>() {
-                                ThreadStatus::FailedInternalError(format!(
-                                    "Thread panicked: {}",
-                                    panic_msg
-                                ))
-                            } else {
-                                ThreadStatus::FailedInternalError("Thread panicked".to_string())
-                            }
-                        }
+                        Err(e) => ThreadStatus::FailedInternalError(panic_message(&*e)),
                     };
                     self.finished_thread_status_cached = Some(thread_finished_status.clone());
                     thread_finished_status
@@ -121,15 +251,7 @@ impl<ResultType: Send + Clone + 'static> ThreadHandle<ResultType> {
 
         let thread_finished_status = match thread_handle.join() {
             Ok(r) => ThreadStatus::Finished(r),
-            Err(e) => {
-                if let Some(panic_msg) = e.downcast_ref::<String>() {
-                    ThreadStatus::FailedInternalError(format!("Thread panicked: {}", panic_msg))
-                } else if let Some(panic_msg) = e.downcast_ref::<&str>() {
-                    ThreadStatus::FailedInternalError(format!("Thread panicked: {}", panic_msg))
-                } else {
-                    ThreadStatus::FailedInternalError("Thread panicked".to_string())
-                }
-            }
+            Err(e) => ThreadStatus::FailedInternalError(panic_message(&*e)),
         };
         self.finished_thread_status_cached = Some(thread_finished_status.clone());
         Ok(thread_finished_status)
@@ -180,6 +302,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn thread_options() {
+        let mut handle =
+            ThreadHandle::new(move |_terminator| std::thread::current().name().map(str::to_string));
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert_eq!(
+            handle.get_thread_status(),
+            ThreadStatus::Finished(Some(DEFAULT_THREAD_NAME.to_string()))
+        );
+
+        let options = ThreadOptions::default()
+            .with_name("my-thread")
+            .with_stack_size(256 * 1024);
+        let mut handle = ThreadHandle::with_options(&options, move |_terminator| {
+            std::thread::current().name().map(str::to_string)
+        });
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert_eq!(
+            handle.get_thread_status(),
+            ThreadStatus::Finished(Some("my-thread".to_string()))
+        );
+    }
+
+    #[test]
+    fn thread_name_with_nul() {
+        assert!(ThreadOptions::default().validate().is_ok());
+        assert!(ThreadOptions::default().with_name("sdk").validate().is_ok());
+        let err = ThreadOptions::default()
+            .with_name("s\0dk")
+            .validate()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "thread_options.name cannot contain NUL bytes"
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn low_priority_thread() {
+        let niceness = || unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) };
+        let spawning_thread_niceness = niceness();
+
+        let options = ThreadOptions::default().with_low_priority(true);
+        let mut handle = ThreadHandle::with_options(&options, move |_terminator| niceness());
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert_eq!(
+            handle.get_thread_status(),
+            ThreadStatus::Finished(
+                (spawning_thread_niceness + LOW_PRIORITY_NICE_INCREMENT).min(19)
+            )
+        );
+        // Other threads are not affected
+        assert_eq!(niceness(), spawning_thread_niceness);
+    }
+
     #[test]
     fn panicking_thread() {
         let mut handle = ThreadHandle::new(move |_terminator| {
@@ -12,75 +12,80 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::sync::{Arc, Condvar, Mutex};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
 use std::time::Duration;
 
+use arc_swap::ArcSwap;
+
+use super::MutexExt;
+
 /// Thread-safe wrapper around a value that allows threads to wait for specific conditions on that value.
+///
+/// Reading the value is lock-free, so readers never wait for a writer or for
+/// each other. The lock is only taken to wait for a change and to notify the
+/// waiters, never while holding another lock, and a poisoned lock is recovered.
 #[derive(Debug, Clone)]
 pub struct Waitable<T> {
-    inner: Arc<(Mutex<T>, Condvar)>,
+    inner: Arc<Inner<T>>,
+}
+
+#[derive(Debug)]
+struct Inner<T> {
+    value: ArcSwap<T>,
+    changed: (Mutex<()>, Condvar),
 }
 
 impl<T> Waitable<T> {
     pub fn new(value: T) -> Self {
         Self {
-            inner: Arc::new((Mutex::new(value), Condvar::new())),
+            inner: Arc::new(Inner {
+                value: ArcSwap::from_pointee(value),
+                changed: (Mutex::new(()), Condvar::new()),
+            }),
         }
     }
 
-    pub fn set(
-        &self,
-        value: T,
-    ) -> Result<(), std::sync::PoisonError<std::sync::MutexGuard<'_, T>>> {
-        let (mutex, condvar) = &*self.inner;
-        let mut guard = mutex.lock()?;
-        *guard = value;
+    pub fn set(&self, value: T) {
+        self.inner.value.store(Arc::new(value));
+        // Waiters check the value with the lock held, taking it here
+        // ensures none misses the notification
+        let (mutex, condvar) = &self.inner.changed;
+        drop(mutex.lock_unpoisoned());
         condvar.notify_all();
-        Ok(())
     }
 
-    pub fn get(&self) -> Result<T, std::sync::PoisonError<std::sync::MutexGuard<'_, T>>>
+    pub fn get(&self) -> T
     where
         T: Clone,
     {
-        let (mutex, _) = &*self.inner;
-        let guard = mutex.lock()?;
-        Ok(guard.clone())
+        T::clone(&self.inner.value.load())
     }
 
     /// Waits until the value equals the given value
     #[allow(dead_code)]
-    pub fn wait_for(
-        &self,
-        expected: T,
-    ) -> Result<T, std::sync::PoisonError<std::sync::MutexGuard<'_, T>>>
+    pub fn wait_for(&self, expected: T) -> T
     where
         T: Clone + PartialEq,
     {
-        let (mutex, condvar) = &*self.inner;
-        let guard = mutex.lock()?;
-        let guard = condvar.wait_while(guard, |value| *value != expected)?;
-        Ok(guard.clone())
+        let (mutex, condvar) = &self.inner.changed;
+        let guard = mutex.lock_unpoisoned();
+        let _guard = condvar
+            .wait_while(guard, |()| **self.inner.value.load() != expected)
+            .unwrap_or_else(PoisonError::into_inner);
+        expected
     }
 
-    pub fn wait_for_timeout(
-        &self,
-        expected: T,
-        timeout: Duration,
-    ) -> Result<Option<T>, std::sync::PoisonError<std::sync::MutexGuard<'_, T>>>
+    pub fn wait_for_timeout(&self, expected: T, timeout: Duration) -> Option<T>
     where
         T: Clone + PartialEq,
     {
-        let (mutex, condvar) = &*self.inner;
-        let guard = mutex.lock()?;
-        let (guard, timeout_result) = condvar
-            .wait_timeout_while(guard, timeout, |value| *value != expected)
-            .map_err(|err| std::sync::PoisonError::new(err.into_inner().0))?;
-        if timeout_result.timed_out() && *guard != expected {
-            Ok(None)
-        } else {
-            Ok(Some(guard.clone()))
-        }
+        let (mutex, condvar) = &self.inner.changed;
+        let guard = mutex.lock_unpoisoned();
+        let (_guard, timeout_result) = condvar
+            .wait_timeout_while(guard, timeout, |()| **self.inner.value.load() != expected)
+            .unwrap_or_else(PoisonError::into_inner);
+        // Only timed out if the value still differs
+        (!timeout_result.timed_out()).then_some(expected)
     }
 }
 
@@ -95,11 +100,11 @@ mod tests {
         let waitable = Waitable::new(42);
 
         // Test get
-        assert_eq!(waitable.get().unwrap(), 42);
+        assert_eq!(waitable.get(), 42);
 
         // Test set
-        waitable.set(100).unwrap();
-        assert_eq!(waitable.get().unwrap(), 100);
+        waitable.set(100);
+        assert_eq!(waitable.get(), 100);
     }
 
     #[test]
@@ -110,11 +115,31 @@ mod tests {
         // Spawn a thread that will change the value after a delay
         thread::spawn(move || {
             thread::sleep(Duration::from_millis(50));
-            waitable_clone.set(42).unwrap();
+            waitable_clone.set(42);
         });
 
         // Wait for the value to become 42
-        let result = waitable.wait_for(42).unwrap();
+        let result = waitable.wait_for(42);
         assert_eq!(result, 42);
     }
+
+    #[test]
+    fn test_wait_for_timeout() {
+        let waitable = Waitable::new(0);
+        assert_eq!(
+            waitable.wait_for_timeout(1, Duration::from_millis(10)),
+            None
+        );
+
+        let waitable_clone = waitable.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            waitable_clone.set(1);
+        });
+        assert_eq!(
+            waitable.wait_for_timeout(1, Duration::from_secs(5)),
+            Some(1)
+        );
+        assert_eq!(waitable.wait_for_timeout(1, Duration::ZERO), Some(1));
+    }
 }
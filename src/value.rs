@@ -12,16 +12,28 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
 use crate::Error;
 
 /// A wrapper on top of the primitive types acepted by the library.
+///
+/// It (de)serializes as the plain JSON boolean, number, string or array.
+///
+/// New kinds of values may be added, matches on it need a wildcard arm.
 #[derive(PartialEq, Debug, Clone)]
+#[non_exhaustive]
 pub enum Value {
     Float64(f64),
     UInt64(u64),
     Int64(i64),
     String(String),
     Boolean(bool),
+    /// Values of features and properties given as a JSON array, e.g. a list of
+    /// tenant IDs. Its items have the type of the feature or property.
+    List(Vec<Value>),
 }
 
 impl From<f64> for Value {
@@ -54,13 +66,76 @@ impl From<bool> for Value {
     }
 }
 
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::Int64(value.into())
+    }
+}
+
+impl From<u32> for Value {
+    fn from(value: u32) -> Self {
+        Value::UInt64(value.into())
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Value::Float64(value.into())
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(value: Vec<T>) -> Self {
+        Value::List(value.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Writes the inner value, strings without quotes. Lists are written as their
+/// comma-separated items in brackets, e.g. `[a, b]`.
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Float64(v) => v.fmt(f),
+            Value::UInt64(v) => v.fmt(f),
+            Value::Int64(v) => v.fmt(f),
+            Value::String(v) => v.fmt(f),
+            Value::Boolean(v) => v.fmt(f),
+            Value::List(items) => {
+                f.write_str("[")?;
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    item.fmt(f)?;
+                }
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+impl Value {
+    fn conversion_error(self, expected: &'static str) -> Error {
+        Error::ValueConversionError {
+            expected,
+            found: self,
+        }
+    }
+}
+
 impl TryFrom<Value> for f64 {
     type Error = crate::Error;
 
     fn try_from(value: Value) -> Result<Self, Self::Error> {
         match value {
             Value::Float64(f) => Ok(f),
-            _ => Err(Error::MismatchType),
+            value => Err(value.conversion_error("f64")),
         }
     }
 }
@@ -71,8 +146,10 @@ impl TryFrom<Value> for u64 {
     fn try_from(value: Value) -> Result<Self, Self::Error> {
         match value {
             Value::UInt64(f) => Ok(f),
-            Value::Int64(v) => v.try_into().map_err(|_| Error::MismatchType),
-            _ => Err(Error::MismatchType),
+            Value::Int64(v) => v
+                .try_into()
+                .map_err(|_| Value::Int64(v).conversion_error("u64")),
+            value => Err(value.conversion_error("u64")),
         }
     }
 }
@@ -83,8 +160,10 @@ impl TryFrom<Value> for i64 {
     fn try_from(value: Value) -> Result<Self, Self::Error> {
         match value {
             Value::Int64(f) => Ok(f),
-            Value::UInt64(v) => v.try_into().map_err(|_| Error::MismatchType),
-            _ => Err(Error::MismatchType),
+            Value::UInt64(v) => v
+                .try_into()
+                .map_err(|_| Value::UInt64(v).conversion_error("i64")),
+            value => Err(value.conversion_error("i64")),
         }
     }
 }
@@ -95,7 +174,7 @@ impl TryFrom<Value> for String {
     fn try_from(value: Value) -> Result<Self, Self::Error> {
         match value {
             Value::String(f) => Ok(f),
-            _ => Err(Error::MismatchType),
+            value => Err(value.conversion_error("String")),
         }
     }
 }
@@ -106,11 +185,133 @@ impl TryFrom<Value> for bool {
     fn try_from(value: Value) -> Result<Self, Self::Error> {
         match value {
             Value::Boolean(f) => Ok(f),
+            value => Err(value.conversion_error("bool")),
+        }
+    }
+}
+
+/// Converts every item of a [`Value::List`], e.g. into a `Vec<String>`. The
+/// first item that does not convert is reported.
+impl<T: TryFrom<Value, Error = crate::Error>> TryFrom<Value> for Vec<T> {
+    type Error = crate::Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::List(items) => items.into_iter().map(T::try_from).collect(),
+            value => Err(value.conversion_error("Vec")),
+        }
+    }
+}
+
+impl From<Value> for serde_json::Value {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Float64(v) => v.into(),
+            Value::UInt64(v) => v.into(),
+            Value::Int64(v) => v.into(),
+            Value::String(v) => v.into(),
+            Value::Boolean(v) => v.into(),
+            Value::List(items) => items
+                .into_iter()
+                .map(serde_json::Value::from)
+                .collect::<Vec<_>>()
+                .into(),
+        }
+    }
+}
+
+impl TryFrom<serde_json::Value> for Value {
+    type Error = crate::Error;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        match value {
+            serde_json::Value::Bool(v) => Ok(Value::Boolean(v)),
+            serde_json::Value::String(v) => Ok(Value::String(v)),
+            serde_json::Value::Number(n) => n
+                .as_i64()
+                .map(Value::Int64)
+                .or_else(|| n.as_u64().map(Value::UInt64))
+                .or_else(|| n.as_f64().map(Value::Float64))
+                .ok_or(Error::MismatchType),
+            serde_json::Value::Array(items) => items
+                .into_iter()
+                .map(Value::try_from)
+                .collect::<Result<_, _>>()
+                .map(Value::List),
             _ => Err(Error::MismatchType),
         }
     }
 }
 
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Float64(v) => serializer.serialize_f64(*v),
+            Value::UInt64(v) => serializer.serialize_u64(*v),
+            Value::Int64(v) => serializer.serialize_i64(*v),
+            Value::String(v) => serializer.serialize_str(v),
+            Value::Boolean(v) => serializer.serialize_bool(*v),
+            Value::List(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+        }
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a boolean, number, string or array")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Boolean(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::Int64(v))
+    }
+
+    // Like the conversion from JSON values, integers are signed unless they
+    // don't fit into an i64.
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Value, E> {
+        Ok(i64::try_from(v).map_or(Value::UInt64(v), Value::Int64))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::Float64(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Value::List(items))
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
 
@@ -126,19 +327,19 @@ pub mod tests {
 
         assert!(matches!(
             TryInto::<u64>::try_into(value.clone()).unwrap_err(),
-            Error::MismatchType
+            Error::ValueConversionError { .. }
         ));
         assert!(matches!(
             TryInto::<i64>::try_into(value.clone()).unwrap_err(),
-            Error::MismatchType
+            Error::ValueConversionError { .. }
         ));
         assert!(matches!(
             TryInto::<String>::try_into(value.clone()).unwrap_err(),
-            Error::MismatchType
+            Error::ValueConversionError { .. }
         ));
         assert!(matches!(
             TryInto::<bool>::try_into(value.clone()).unwrap_err(),
-            Error::MismatchType
+            Error::ValueConversionError { .. }
         ));
     }
 
@@ -157,15 +358,15 @@ pub mod tests {
 
             assert!(matches!(
                 TryInto::<f64>::try_into(value.clone()).unwrap_err(),
-                Error::MismatchType
+                Error::ValueConversionError { .. }
             ));
             assert!(matches!(
                 TryInto::<String>::try_into(value.clone()).unwrap_err(),
-                Error::MismatchType
+                Error::ValueConversionError { .. }
             ));
             assert!(matches!(
                 TryInto::<bool>::try_into(value.clone()).unwrap_err(),
-                Error::MismatchType
+                Error::ValueConversionError { .. }
             ));
         }
 
@@ -174,7 +375,7 @@ pub mod tests {
             let value = Value::from(u64::MAX);
             assert!(matches!(
                 TryInto::<i64>::try_into(value.clone()).unwrap_err(),
-                Error::MismatchType
+                Error::ValueConversionError { .. }
             ));
         }
     }
@@ -194,15 +395,15 @@ pub mod tests {
 
             assert!(matches!(
                 TryInto::<f64>::try_into(value.clone()).unwrap_err(),
-                Error::MismatchType
+                Error::ValueConversionError { .. }
             ));
             assert!(matches!(
                 TryInto::<String>::try_into(value.clone()).unwrap_err(),
-                Error::MismatchType
+                Error::ValueConversionError { .. }
             ));
             assert!(matches!(
                 TryInto::<bool>::try_into(value.clone()).unwrap_err(),
-                Error::MismatchType
+                Error::ValueConversionError { .. }
             ));
         }
 
@@ -211,7 +412,7 @@ pub mod tests {
             let value = Value::from(-2i64);
             assert!(matches!(
                 TryInto::<u64>::try_into(value.clone()).unwrap_err(),
-                Error::MismatchType
+                Error::ValueConversionError { .. }
             ));
         }
     }
@@ -226,19 +427,19 @@ pub mod tests {
 
         assert!(matches!(
             TryInto::<f64>::try_into(value.clone()).unwrap_err(),
-            Error::MismatchType
+            Error::ValueConversionError { .. }
         ));
         assert!(matches!(
             TryInto::<u64>::try_into(value.clone()).unwrap_err(),
-            Error::MismatchType
+            Error::ValueConversionError { .. }
         ));
         assert!(matches!(
             TryInto::<i64>::try_into(value.clone()).unwrap_err(),
-            Error::MismatchType
+            Error::ValueConversionError { .. }
         ));
         assert!(matches!(
             TryInto::<bool>::try_into(value.clone()).unwrap_err(),
-            Error::MismatchType
+            Error::ValueConversionError { .. }
         ));
     }
 
@@ -253,19 +454,140 @@ pub mod tests {
 
         assert!(matches!(
             TryInto::<f64>::try_into(value.clone()).unwrap_err(),
-            Error::MismatchType
+            Error::ValueConversionError { .. }
         ));
         assert!(matches!(
             TryInto::<u64>::try_into(value.clone()).unwrap_err(),
-            Error::MismatchType
+            Error::ValueConversionError { .. }
         ));
         assert!(matches!(
             TryInto::<i64>::try_into(value.clone()).unwrap_err(),
-            Error::MismatchType
+            Error::ValueConversionError { .. }
         ));
         assert!(matches!(
             TryInto::<String>::try_into(value.clone()).unwrap_err(),
-            Error::MismatchType
+            Error::ValueConversionError { .. }
+        ));
+    }
+
+    #[test]
+    fn test_from_json() {
+        let value: Value = serde_json::json!(true).try_into().unwrap();
+        assert_eq!(value, Value::Boolean(true));
+        let value: Value = serde_json::json!(-3).try_into().unwrap();
+        assert_eq!(value, Value::Int64(-3));
+        let value: Value = serde_json::json!(u64::MAX).try_into().unwrap();
+        assert_eq!(value, Value::UInt64(u64::MAX));
+        let value: Value = serde_json::json!(1.5).try_into().unwrap();
+        assert_eq!(value, Value::Float64(1.5));
+        let value: Value = serde_json::json!("on").try_into().unwrap();
+        assert_eq!(value, Value::String("on".into()));
+        let value: Value = serde_json::json!(["a", 1]).try_into().unwrap();
+        assert_eq!(
+            value,
+            Value::List(vec![Value::String("a".into()), Value::Int64(1)])
+        );
+
+        for json in [
+            serde_json::json!(null),
+            serde_json::json!([1, null]),
+            serde_json::json!({}),
+        ] {
+            assert!(matches!(
+                Value::try_from(json).unwrap_err(),
+                Error::MismatchType
+            ));
+        }
+    }
+
+    #[test]
+    fn test_serde() {
+        let values = [
+            (Value::Boolean(true), "true"),
+            (Value::Int64(-3), "-3"),
+            (Value::UInt64(u64::MAX), "18446744073709551615"),
+            (Value::Float64(1.5), "1.5"),
+            (Value::String("a".into()), "\"a\""),
+            (Value::from(vec!["a", "b"]), "[\"a\",\"b\"]"),
+            (Value::List(vec![]), "[]"),
+        ];
+        for (value, json) in values {
+            assert_eq!(serde_json::to_string(&value).unwrap(), json);
+            assert_eq!(serde_json::from_str::<Value>(json).unwrap(), value);
+        }
+
+        // Integers are signed when they fit into an i64
+        assert_eq!(
+            serde_json::from_str::<Value>("42").unwrap(),
+            Value::Int64(42)
+        );
+        assert!(serde_json::from_str::<Value>("null").is_err());
+        assert!(serde_json::from_str::<Value>("[null]").is_err());
+    }
+
+    #[test]
+    fn test_list() {
+        let value = Value::from(vec![1i64, 2]);
+        assert_eq!(value.to_string(), "[1, 2]");
+
+        let as_vec: Vec<i64> = value.clone().try_into().unwrap();
+        assert_eq!(as_vec, [1, 2]);
+        let as_vec: Vec<u64> = value.clone().try_into().unwrap();
+        assert_eq!(as_vec, [1, 2]);
+        assert!(matches!(
+            TryInto::<Vec<String>>::try_into(value.clone()).unwrap_err(),
+            Error::ValueConversionError {
+                expected: "String",
+                found: Value::Int64(1)
+            }
+        ));
+        assert!(matches!(
+            TryInto::<String>::try_into(value).unwrap_err(),
+            Error::ValueConversionError { .. }
+        ));
+        assert!(matches!(
+            TryInto::<Vec<String>>::try_into(Value::from("a")).unwrap_err(),
+            Error::ValueConversionError {
+                expected: "Vec",
+                ..
+            }
+        ));
+
+        let as_vec: Vec<String> = Value::from(vec!["a", "b"]).try_into().unwrap();
+        assert_eq!(as_vec, ["a", "b"]);
+    }
+
+    #[test]
+    fn test_from_narrow_types() {
+        assert_eq!(Value::from("a"), Value::String("a".into()));
+        assert_eq!(Value::from(-3i32), Value::Int64(-3));
+        assert_eq!(Value::from(3u32), Value::UInt64(3));
+        assert_eq!(Value::from(1.5f32), Value::Float64(1.5));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Value::from("a b").to_string(), "a b");
+        assert_eq!(Value::from(true).to_string(), "true");
+        assert_eq!(Value::from(-3i64).to_string(), "-3");
+        assert_eq!(Value::from(3u64).to_string(), "3");
+        assert_eq!(Value::from(1.5f64).to_string(), "1.5");
+    }
+
+    #[test]
+    fn test_conversion_error_names_found_value() {
+        let error = TryInto::<bool>::try_into(Value::from("yes")).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Value String(\"yes\") cannot be converted to bool"
+        );
+        let error = TryInto::<i64>::try_into(Value::from(u64::MAX)).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::ValueConversionError {
+                expected: "i64",
+                found: Value::UInt64(u64::MAX)
+            }
         ));
     }
 }
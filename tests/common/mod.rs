@@ -12,10 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+// Not every integration test binary uses every helper.
+#![allow(dead_code)]
+
 use std::io::{BufRead, BufReader, Write};
 use std::net::{TcpListener, TcpStream};
 use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::mpsc;
 
+use ibm_appconfiguration_rust_sdk::{
+    ConfigurationId, ConfigurationJson, NetworkResult, ServerClient, WebsocketReader,
+};
 use tungstenite::WebSocket;
 
 pub fn handle_config_request_trivial_config(server: &TcpListener) {
@@ -71,3 +79,79 @@ pub fn handle_websocket(server: &TcpListener) -> WebSocket<TcpStream> {
         .unwrap();
     websocket
 }
+
+/// Changes the configuration served, given the number of requests received
+/// before.
+type ConfigurationEdit = Box<dyn Fn(&mut serde_json::Value, u32) + Send>;
+
+/// Serves the example configuration from a file, and notifies the changes sent
+/// through a channel.
+pub struct FileServerClient {
+    json: serde_json::Value,
+    requests: Mutex<u32>,
+    edit: ConfigurationEdit,
+    notifications: Mutex<Option<mpsc::Receiver<tungstenite::Message>>>,
+}
+
+impl FileServerClient {
+    pub fn new(notifications: mpsc::Receiver<tungstenite::Message>) -> Self {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("data/data-dump-enterprise-plan-sdk-testing.json");
+        let json = std::fs::read_to_string(path).unwrap();
+        Self {
+            json: serde_json::from_str(&json).unwrap(),
+            requests: Mutex::new(0),
+            edit: Box::new(|_, _| {}),
+            notifications: Mutex::new(Some(notifications)),
+        }
+    }
+
+    /// Changes the configuration served, given the number of requests received
+    /// before.
+    pub fn with_edit(
+        mut self,
+        edit: impl Fn(&mut serde_json::Value, u32) + Send + 'static,
+    ) -> Self {
+        self.edit = Box::new(edit);
+        self
+    }
+}
+
+impl ServerClient for FileServerClient {
+    fn get_configuration_json(
+        &self,
+        _configuration_id: &ConfigurationId,
+    ) -> NetworkResult<ConfigurationJson> {
+        let mut json = self.json.clone();
+        let mut requests = self.requests.lock().unwrap();
+        (self.edit)(&mut json, *requests);
+        *requests += 1;
+        Ok(ConfigurationJson::from_json_str(&json.to_string()).unwrap())
+    }
+
+    fn get_configuration_monitoring_websocket(
+        &self,
+        _collection: &ConfigurationId,
+    ) -> NetworkResult<Box<dyn WebsocketReader>> {
+        let receiver = self.notifications.lock().unwrap().take();
+        Ok(Box::new(ChannelReader { receiver }))
+    }
+}
+
+/// Reads the messages sent through a channel, closed once the channel is.
+pub struct ChannelReader {
+    receiver: Option<mpsc::Receiver<tungstenite::Message>>,
+}
+
+impl WebsocketReader for ChannelReader {
+    fn read_msg(&mut self) -> tungstenite::error::Result<tungstenite::Message> {
+        self.receiver
+            .as_ref()
+            .and_then(|receiver| receiver.recv().ok())
+            .ok_or(tungstenite::Error::ConnectionClosed)
+    }
+
+    fn flush_socket(&mut self) -> tungstenite::error::Result<()> {
+        Ok(())
+    }
+}
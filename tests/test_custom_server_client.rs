@@ -0,0 +1,51 @@
+// Copyright 2026 IBM Corp. All Rights Reserved.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//       http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod common;
+
+use ibm_appconfiguration_rust_sdk::{
+    AppConfigurationClient, AppConfigurationClientIBMCloud, ConfigurationId,
+    LiveConfigurationOptions, OfflineMode, RuntimeEventEmitter,
+};
+
+use std::sync::Arc;
+use std::sync::mpsc;
+
+#[test]
+fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let (sender, receiver) = mpsc::channel();
+    let server_client = common::FileServerClient::new(receiver);
+
+    let config_id = ConfigurationId::new(
+        "guid".to_string(),
+        "dev".to_string(),
+        "blue-charge".to_string(),
+    )?;
+    let client: Arc<dyn AppConfigurationClient> =
+        Arc::new(AppConfigurationClientIBMCloud::new_with_server_client(
+            server_client,
+            config_id,
+            OfflineMode::Fail,
+            LiveConfigurationOptions::default(),
+            RuntimeEventEmitter::new(),
+        )?);
+
+    assert!(client.wait_until_online());
+    let mut features = client.get_feature_ids()?;
+    features.sort();
+    assert_eq!(features, vec!["f1", "f2", "f3", "f4", "f6"]);
+
+    drop(sender);
+    Ok(())
+}
@@ -44,7 +44,8 @@ fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
             "guid".to_string(),
             "dev".to_string(),
             "collection_id".to_string(),
-        );
+        )
+        .unwrap();
 
         ibm_appconfiguration_rust_sdk::test_utils::create_app_configuration_client_live(
             address,
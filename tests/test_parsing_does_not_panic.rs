@@ -0,0 +1,226 @@
+// Copyright 2026 IBM Corp. All Rights Reserved.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//       http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Property tests feeding arbitrary input to the configuration parser and to
+//! the evaluation of features and properties: they may fail, but never panic.
+
+use ibm_appconfiguration_rust_sdk::{
+    Configuration, ConfigurationJson, ConfigurationProvider, Entity, Feature, Property, Value,
+};
+
+use proptest::prelude::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+const ENVIRONMENT_ID: &str = "dev";
+const COLLECTION_ID: &str = "blue-charge";
+
+fn enterprise_configuration_json() -> serde_json::Value {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("data/data-dump-enterprise-plan-sdk-testing.json");
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[derive(Debug)]
+struct ArbitraryEntity {
+    id: String,
+    attributes: HashMap<String, Value>,
+}
+
+impl Entity for ArbitraryEntity {
+    fn get_id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn get_attributes(&self) -> HashMap<String, Value> {
+        self.attributes.clone()
+    }
+}
+
+fn arbitrary_value() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        any::<String>().prop_map(Value::String),
+        any::<bool>().prop_map(Value::Boolean),
+        any::<f64>().prop_map(Value::Float64),
+        any::<i64>().prop_map(Value::Int64),
+        any::<u64>().prop_map(Value::UInt64),
+    ]
+}
+
+fn arbitrary_entity() -> impl Strategy<Value = ArbitraryEntity> {
+    // Mostly the attributes used by the segments of the enterprise configuration.
+    let name = prop_oneof![
+        Just("code".to_string()),
+        Just("email".to_string()),
+        Just("entityId".to_string()),
+        any::<String>(),
+    ];
+    (
+        any::<String>(),
+        prop::collection::hash_map(name, arbitrary_value(), 0..4),
+    )
+        .prop_map(|(id, attributes)| ArbitraryEntity { id, attributes })
+}
+
+fn arbitrary_json() -> impl Strategy<Value = serde_json::Value> {
+    let leaf = prop_oneof![
+        Just(serde_json::Value::Null),
+        any::<bool>().prop_map(serde_json::Value::from),
+        any::<i64>().prop_map(serde_json::Value::from),
+        any::<u64>().prop_map(serde_json::Value::from),
+        any::<f64>().prop_map(serde_json::Value::from),
+        any::<String>().prop_map(serde_json::Value::from),
+        Just(serde_json::Value::from("$default")),
+    ];
+    leaf.prop_recursive(4, 32, 4, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..4).prop_map(serde_json::Value::from),
+            prop::collection::btree_map(any::<String>(), inner, 0..4)
+                .prop_map(|map| serde_json::Value::Object(map.into_iter().collect())),
+        ]
+    })
+}
+
+/// Returns the JSON pointers to every value in `value`.
+fn json_pointers(value: &serde_json::Value, prefix: String, pointers: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                json_pointers(item, format!("{prefix}/{index}"), pointers);
+            }
+        }
+        serde_json::Value::Object(fields) => {
+            for (key, field) in fields {
+                let key = key.replace('~', "~0").replace('/', "~1");
+                json_pointers(field, format!("{prefix}/{key}"), pointers);
+            }
+        }
+        _ => {}
+    }
+    pointers.push(prefix);
+}
+
+/// Evaluates every feature, property and segment of the configuration.
+fn evaluate_all(configuration: &Configuration, entity: &ArbitraryEntity) {
+    for feature_id in configuration.get_feature_ids().unwrap_or_default() {
+        if let Ok(feature) = configuration.get_feature(&feature_id) {
+            let _ = feature.get_current_value(entity);
+        }
+    }
+    for property_id in configuration.get_property_ids().unwrap_or_default() {
+        if let Ok(property) = configuration.get_property(&property_id) {
+            let _ = property.get_current_value(entity);
+        }
+    }
+    for segment_id in configuration.get_segment_ids().unwrap_or_default() {
+        let _ = configuration.entity_matches_segment(&segment_id, entity);
+    }
+}
+
+proptest! {
+    #[test]
+    fn arbitrary_text(json in any::<String>()) {
+        let _ = ConfigurationJson::from_json_str(&json);
+        let _ = Configuration::from_json_str(&json, ENVIRONMENT_ID, COLLECTION_ID);
+    }
+
+    #[test]
+    fn arbitrary_json_documents(json in arbitrary_json()) {
+        let json = json.to_string();
+        let _ = ConfigurationJson::from_json_str(&json);
+        let _ = Configuration::from_json_str(&json, ENVIRONMENT_ID, COLLECTION_ID);
+    }
+
+    #[test]
+    fn configuration_with_an_arbitrary_field(
+        index in any::<prop::sample::Index>(),
+        replacement in arbitrary_json(),
+        entity in arbitrary_entity(),
+    ) {
+        let mut json = enterprise_configuration_json();
+        let mut pointers = Vec::new();
+        json_pointers(&json, String::new(), &mut pointers);
+        let pointer = index.get(&pointers);
+        *json.pointer_mut(pointer).unwrap() = replacement;
+
+        if let Ok(configuration) =
+            Configuration::from_json_str(&json.to_string(), ENVIRONMENT_ID, COLLECTION_ID)
+        {
+            evaluate_all(&configuration, &entity);
+        }
+    }
+
+    #[test]
+    fn arbitrary_attributes(entity in arbitrary_entity()) {
+        let json = enterprise_configuration_json().to_string();
+        let configuration =
+            Configuration::from_json_str(&json, ENVIRONMENT_ID, COLLECTION_ID).unwrap();
+        evaluate_all(&configuration, &entity);
+    }
+
+    #[test]
+    fn arbitrary_progressive_rollout(
+        start_at in prop_oneof![
+            "[0-9]{4}-[01][0-9]-[0-3][0-9]T[0-2][0-9]:[0-5][0-9]:[0-5][0-9]Z",
+            any::<String>(),
+        ],
+        phases in prop::collection::vec(
+            (
+                prop_oneof![0..=100u32, any::<u32>()],
+                any::<Option<u32>>(),
+                prop_oneof![Just("minutes"), Just("hours"), Just("days"), Just("weeks")],
+                any::<bool>(),
+            ),
+            0..8,
+        ),
+        entity in arbitrary_entity(),
+    ) {
+        let phases: Vec<serde_json::Value> = phases
+            .into_iter()
+            .map(|(percentage, duration, duration_type, ramp)| {
+                serde_json::json!({
+                    "percentage": percentage,
+                    "duration": duration,
+                    "duration_type": duration_type,
+                    "ramp": ramp,
+                })
+            })
+            .collect();
+        let rollout = serde_json::json!({
+            "rollout_type": "PROGRESSIVE",
+            "rollout_configuration": {"start_at": start_at, "phases": phases},
+        });
+
+        let mut json = enterprise_configuration_json();
+        for feature in json["environments"][0]["features"].as_array_mut().unwrap() {
+            let feature = feature.as_object_mut().unwrap();
+            feature.extend(rollout.as_object().unwrap().clone());
+            for (index, rule) in feature["segment_rules"]
+                .as_array_mut()
+                .unwrap()
+                .iter_mut()
+                .enumerate()
+            {
+                let rule = rule.as_object_mut().unwrap();
+                rule.extend(rollout.as_object().unwrap().clone());
+                rule.insert("rule_id".to_string(), index.to_string().into());
+            }
+        }
+
+        let configuration =
+            Configuration::from_json_str(&json.to_string(), ENVIRONMENT_ID, COLLECTION_ID)
+                .unwrap();
+        evaluate_all(&configuration, &entity);
+    }
+}
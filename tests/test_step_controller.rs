@@ -0,0 +1,95 @@
+// Copyright 2026 IBM Corp. All Rights Reserved.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//       http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod common;
+
+use ibm_appconfiguration_rust_sdk::test_utils::{StepController, WorkerStep};
+use ibm_appconfiguration_rust_sdk::tungstenite;
+use ibm_appconfiguration_rust_sdk::{
+    AppConfigurationClientIBMCloud, ConfigurationId, ConfigurationProvider,
+    LiveConfigurationOptions, OfflineMode, RuntimeEventEmitter,
+};
+
+use std::sync::mpsc;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[test]
+fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let (sender, receiver) = mpsc::channel();
+    // Without the first feature after the first request
+    let server_client = common::FileServerClient::new(receiver).with_edit(|json, requests| {
+        if requests > 0 {
+            json["environments"][0]["features"]
+                .as_array_mut()
+                .unwrap()
+                .remove(0);
+        }
+    });
+
+    let controller = StepController::new();
+    controller.pause_at(WorkerStep::BeforeFetch);
+    controller.pause_at(WorkerStep::AfterFetch);
+    let client = AppConfigurationClientIBMCloud::new_with_server_client(
+        server_client,
+        ConfigurationId::new(
+            "guid".to_string(),
+            "dev".to_string(),
+            "blue-charge".to_string(),
+        )?,
+        OfflineMode::Fail,
+        LiveConfigurationOptions {
+            step_controller: Some(controller.clone()),
+            ..Default::default()
+        },
+        RuntimeEventEmitter::new(),
+    )?;
+
+    // Nothing fetched yet
+    assert_eq!(
+        controller.wait_paused(TIMEOUT),
+        Some(WorkerStep::BeforeFetch)
+    );
+    assert!(client.get_feature_ids().is_err());
+
+    controller.resume();
+    assert_eq!(
+        controller.wait_paused(TIMEOUT),
+        Some(WorkerStep::AfterFetch)
+    );
+    assert!(client.is_online()?);
+    assert!(client.get_feature_ids()?.contains(&"f1".to_string()));
+
+    // A change is notified: the current configuration is served until the
+    // thread is allowed to fetch the new one
+    controller.resume();
+    sender.send(tungstenite::Message::text("configuration changed"))?;
+    assert_eq!(
+        controller.wait_paused(TIMEOUT),
+        Some(WorkerStep::BeforeFetch)
+    );
+    assert!(client.get_feature_ids()?.contains(&"f1".to_string()));
+
+    controller.resume();
+    assert_eq!(
+        controller.wait_paused(TIMEOUT),
+        Some(WorkerStep::AfterFetch)
+    );
+    assert!(!client.get_feature_ids()?.contains(&"f1".to_string()));
+
+    controller.release();
+    drop(sender);
+    Ok(())
+}
@@ -92,7 +92,8 @@ fn main() {
         "guid".to_string(),
         "dev".to_string(),
         "blue-charge".to_string(),
-    );
+    )
+    .unwrap();
     let client = ibm_appconfiguration_rust_sdk::test_utils::create_app_configuration_client_live(
         address,
         config_id,
@@ -125,7 +125,8 @@ fn test_reconnects_after_ws_close() {
         "guid".to_string(),
         "dev".to_string(),
         "blue-charge".to_string(),
-    );
+    )
+    .unwrap();
     let client = ibm_appconfiguration_rust_sdk::test_utils::create_app_configuration_client_live(
         address,
         config_id,